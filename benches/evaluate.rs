@@ -1 +1,167 @@
+//! Criterion benchmarks for the inference pipeline.
+//!
+//! Run with `cargo bench`; add `--features simd` or `--features parallel` to compare
+//! engine configurations against the default build.
+use std::collections::HashMap;
 
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use rust_fuzzylogic::{
+    aggregate::aggregation,
+    antecedent::{eval_antecedent, Antecedent},
+    defuzz::defuzzification,
+    mamdani::{Consequent, Rule},
+    membership::trapezoidal::Trapezoidal,
+    ops::Ops,
+    prelude::*,
+    rulespace::RuleSpace,
+    variable::Variable,
+};
+
+const SAMPLER_SIZES: [usize; 3] = [101, 501, 2001];
+
+fn variable(min: Float, max: Float, names: &[&str]) -> Variable {
+    let mut v = Variable::new(min, max).unwrap();
+    let step = (max - min) / (names.len() - 1) as Float;
+    for (i, name) in names.iter().enumerate() {
+        let c = min + step * i as Float;
+        v.insert_term(
+            name,
+            Term::new(*name, Triangular::new(c - step, c, c + step).unwrap()),
+        )
+        .unwrap();
+    }
+    v
+}
+
+fn atom(var: &'static str, term: &'static str) -> Antecedent {
+    Antecedent::Atom {
+        var: var.into(),
+        term: term.into(),
+    }
+}
+
+/// Two inputs with five terms each, one output, full 5x5 rule table.
+fn system() -> (HashMap<String, Variable>, Vec<Rule>) {
+    let labels = ["nb", "ns", "z", "ps", "pb"];
+    let mut vars = HashMap::new();
+    vars.insert("error".to_string(), variable(-1.0, 1.0, &labels));
+    vars.insert("delta".to_string(), variable(-1.0, 1.0, &labels));
+    vars.insert("out".to_string(), variable(-1.0, 1.0, &labels));
+
+    let mut rules = Vec::new();
+    for (i, e) in labels.iter().enumerate() {
+        for (j, d) in labels.iter().enumerate() {
+            rules.push(Rule {
+                antecedent: Antecedent::And(Box::new(atom("error", e)), Box::new(atom("delta", d))),
+                consequent: vec![Consequent::Term {
+                    var: "out".into(),
+                    term: labels[(i + j) / 2].into(),
+                    hedge: None,
+                }],
+                weight: 1.0,
+            });
+        }
+    }
+    (vars, rules)
+}
+
+fn inputs() -> HashMap<&'static str, Float> {
+    HashMap::from([("error", 0.3), ("delta", -0.45)])
+}
+
+fn membership(c: &mut Criterion) {
+    let tri = Triangular::new(-1.0, 0.0, 1.0).unwrap();
+    let trap = Trapezoidal::new(-1.0, -0.5, 0.5, 1.0).unwrap();
+    let gauss = Gaussian::with_mean_sd(0.0, 0.5).unwrap();
+    let shapes: [(&str, &dyn MembershipFn); 3] = [
+        ("triangular", &tri),
+        ("trapezoidal", &trap),
+        ("gaussian", &gauss),
+    ];
+
+    let mut group = c.benchmark_group("membership");
+    for (name, mf) in shapes {
+        group.bench_function(BenchmarkId::new("eval", name), |b| {
+            b.iter(|| mf.eval(black_box(0.25)))
+        });
+        for n in SAMPLER_SIZES {
+            let xs = UniformSampler::new(n).unwrap().sample(-1.5, 1.5).unwrap();
+            let mut out = vec![0.0; n];
+            group.bench_with_input(
+                BenchmarkId::new(format!("eval_grid/{name}"), n),
+                &n,
+                |b, _| b.iter(|| mf.eval_grid(black_box(&xs), &mut out)),
+            );
+        }
+    }
+    group.finish();
+}
+
+fn antecedent(c: &mut Criterion) {
+    let (vars, rules) = system();
+    let input = inputs();
+    let deep = Antecedent::Or(
+        Box::new(rules[6].antecedent.clone()),
+        Box::new(Antecedent::Not(Box::new(rules[18].antecedent.clone()))),
+    );
+    c.bench_function("antecedent/and", |b| {
+        b.iter(|| eval_antecedent(black_box(&rules[6].antecedent), &input, &vars))
+    });
+    c.bench_function("antecedent/nested", |b| {
+        b.iter(|| eval_antecedent(black_box(&deep), &input, &vars))
+    });
+}
+
+fn ops(c: &mut Criterion) {
+    let mut group = c.benchmark_group("ops");
+    for (name, family) in [
+        ("minmax", Ops::MinMax),
+        ("product", Ops::Product),
+        ("lukasiewicz", Ops::Lukasiewicz),
+    ] {
+        group.bench_function(name, |b| {
+            b.iter(|| {
+                let (a, x) = (black_box(0.3), black_box(0.8));
+                family.s(family.t(a, x), family.c(a))
+            })
+        });
+    }
+    group.finish();
+}
+
+fn pipeline(c: &mut Criterion) {
+    let (vars, rules) = system();
+    let input = inputs();
+
+    let mut group = c.benchmark_group("pipeline");
+    for n in SAMPLER_SIZES {
+        let sampler = UniformSampler::new(n).unwrap();
+        group.bench_with_input(BenchmarkId::new("aggregation", n), &n, |b, _| {
+            b.iter(|| aggregation(&rules, black_box(&input), &vars, &sampler))
+        });
+
+        let agg = aggregation(&rules, &input, &vars, &sampler).unwrap();
+        group.bench_with_input(BenchmarkId::new("defuzzification", n), &n, |b, _| {
+            b.iter(|| defuzzification(black_box(&agg), &vars))
+        });
+
+        let (space_vars, space_rules) = system();
+        let space = RuleSpace::new(space_vars, space_rules).unwrap();
+        group.bench_with_input(BenchmarkId::new("rulespace/evaluate", n), &n, |b, _| {
+            b.iter(|| space.evaluate(black_box(&input), &sampler))
+        });
+
+        let mut scratch = space.scratch(&sampler).unwrap();
+        group.bench_with_input(BenchmarkId::new("rulespace/evaluate_in", n), &n, |b, _| {
+            b.iter(|| {
+                space
+                    .evaluate_in(black_box(&input), &mut scratch)
+                    .map(|o| o["out"])
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, membership, antecedent, ops, pipeline);
+criterion_main!(benches);
@@ -1,2 +1,61 @@
-//pub use rust_fuzzylogic::builder::FuzzySystem;
-fn main() {}
+//! Evaluate a whole column of temperatures in one call.
+//!
+//! Run with `cargo run --example batch --features parallel`; rows are spread
+//! across the rayon thread pool.
+use rust_fuzzylogic::prelude::*;
+use std::collections::HashMap;
+
+fn main() {
+    let mut temp = Variable::new(0.0, 40.0).unwrap();
+    temp.insert_term(
+        "cold",
+        Term::new("cold", Triangular::new(-20.0, 0.0, 20.0).unwrap()),
+    )
+    .unwrap();
+    temp.insert_term(
+        "hot",
+        Term::new("hot", Triangular::new(20.0, 40.0, 60.0).unwrap()),
+    )
+    .unwrap();
+
+    let mut fan = Variable::new(0.0, 100.0).unwrap();
+    fan.insert_term(
+        "low",
+        Term::new("low", Triangular::new(0.0, 25.0, 50.0).unwrap()),
+    )
+    .unwrap();
+    fan.insert_term(
+        "high",
+        Term::new("high", Triangular::new(50.0, 75.0, 100.0).unwrap()),
+    )
+    .unwrap();
+
+    let rule = |t: &'static str, f: &'static str| Rule {
+        antecedent: Antecedent::Atom {
+            var: "temp".into(),
+            term: t.into(),
+        },
+        consequent: vec![Consequent::Term {
+            var: "fan".into(),
+            term: f.into(),
+            hedge: None,
+        }],
+        weight: 1.0,
+    };
+
+    let mut vars = HashMap::new();
+    vars.insert("temp".to_string(), temp);
+    vars.insert("fan".to_string(), fan);
+    let space = RuleSpace::new(vars, vec![rule("cold", "low"), rule("hot", "high")]).unwrap();
+
+    let rows: Vec<HashMap<&str, Float>> = (0..=40)
+        .map(|t| HashMap::from([("temp", t as Float)]))
+        .collect();
+    let outputs = space
+        .evaluate_batch(&rows, &UniformSampler::default())
+        .unwrap();
+
+    for (row, out) in rows.iter().zip(&outputs) {
+        println!("temp = {:>4.1} -> fan = {:>5.1}", row["temp"], out["fan"]);
+    }
+}
@@ -1 +1,39 @@
-fn main() {}
+//! Derive a variable's terms from data with fuzzy c-means.
+//!
+//! Readings cluster around three operating temperatures; the clusters become
+//! `cool`/`normal`/`hot` terms. Run with
+//! `cargo run --example fuzzy-c-means --features parallel`.
+use rust_fuzzylogic::{
+    cluster::{fuzzy_c_means, FcmOptions, TermShape},
+    prelude::*,
+};
+
+fn main() {
+    let readings: Vec<Vec<Float>> = [12.0, 14.5, 13.2, 21.0, 22.4, 20.3, 19.8, 31.5, 33.0, 29.9]
+        .iter()
+        .map(|&t| vec![t])
+        .collect();
+
+    let fcm = fuzzy_c_means(&readings, FcmOptions::default()).unwrap();
+    println!("converged after {} iterations", fcm.iterations);
+
+    let temp = fcm
+        .variable(
+            &readings,
+            0,
+            TermShape::Triangular,
+            &["cool", "normal", "hot"],
+            (0.0, 40.0),
+        )
+        .unwrap();
+    for (name, term) in temp.terms() {
+        println!("{name}: {:?}", term.params());
+    }
+    for t in [10.0, 18.0, 25.0, 35.0] {
+        let degrees: Vec<String> = temp
+            .terms()
+            .map(|(name, term)| format!("{name}={:.2}", term.eval(t)))
+            .collect();
+        println!("{t:>5}: {}", degrees.join(" "));
+    }
+}
@@ -1 +1,76 @@
-fn main() {}
+//! Tune membership parameters of a Mamdani system against labelled data.
+//!
+//! The data is generated by a "reference" controller whose `hot` term peaks at
+//! 35°C; the controller being tuned starts with the apex at 20°C, and every term is
+//! adjusted until its outputs match the reference data more closely.
+//! Run with `cargo run --example gradient-descent --features parallel`.
+use rust_fuzzylogic::{prelude::*, tune::TuneOptions};
+use std::collections::HashMap;
+
+fn controller(hot_apex: Float) -> RuleSpace {
+    let mut temp = Variable::new(0.0, 40.0).unwrap();
+    temp.insert_term(
+        "cold",
+        Term::new("cold", Triangular::new(-40.0, 0.0, 40.0).unwrap()),
+    )
+    .unwrap();
+    temp.insert_term(
+        "hot",
+        Term::new("hot", Triangular::new(0.0, hot_apex, 80.0).unwrap()),
+    )
+    .unwrap();
+
+    let mut fan = Variable::new(0.0, 100.0).unwrap();
+    fan.insert_term(
+        "low",
+        Term::new("low", Triangular::new(-50.0, 0.0, 50.0).unwrap()),
+    )
+    .unwrap();
+    fan.insert_term(
+        "high",
+        Term::new("high", Triangular::new(50.0, 100.0, 150.0).unwrap()),
+    )
+    .unwrap();
+
+    let rule = |t: &'static str, f: &'static str| Rule {
+        antecedent: Antecedent::Atom {
+            var: "temp".into(),
+            term: t.into(),
+        },
+        consequent: vec![Consequent::Term {
+            var: "fan".into(),
+            term: f.into(),
+            hedge: None,
+        }],
+        weight: 1.0,
+    };
+
+    let vars = HashMap::from([("temp".to_string(), temp), ("fan".to_string(), fan)]);
+    RuleSpace::new(vars, vec![rule("cold", "low"), rule("hot", "high")]).unwrap()
+}
+
+fn main() {
+    let sampler = UniformSampler::default();
+    let reference = controller(35.0);
+    let data: Vec<_> = (0..=40)
+        .map(|t| {
+            let input = HashMap::from([("temp", t as Float)]);
+            let fan = reference.evaluate(&input, &sampler).unwrap()["fan"];
+            (input, HashMap::from([("fan", fan)]))
+        })
+        .collect();
+
+    let mut space = controller(20.0);
+    let history = space.tune(&data, &sampler, TuneOptions::default()).unwrap();
+
+    println!(
+        "loss: {:.6} -> {:.6}",
+        history[0],
+        history[history.len() - 1]
+    );
+    for (name, var) in space.vars() {
+        for (term, t) in var.terms() {
+            println!("{name}.{term}: {:?}", t.params());
+        }
+    }
+}
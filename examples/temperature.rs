@@ -1,14 +1,8 @@
 fn main() {
-    use rust_fuzzylogic::{
-        aggregate::aggregation,
-        antecedent::Antecedent,
-        defuzz::defuzzification,
-        mamdani::{Consequent, Rule},
-        prelude::*,
-        variable::Variable,
-    };
+    use rust_fuzzylogic::prelude::*;
     use std::collections::HashMap;
 
+    #[allow(dead_code)]
     fn end_to_end_test() {
         let mut temp = Variable::new(-10.0, 10.0).unwrap();
         temp.insert_term(
@@ -77,33 +71,39 @@ fn main() {
             }))),
         );
 
-        let csqt_1 = Consequent {
-            var: "fanpspeed".to_string(),
-            term: "High".to_string(),
+        let csqt_1 = Consequent::Term {
+            var: "fanpspeed".into(),
+            term: "High".into(),
+            hedge: None,
         };
 
-        let csqt_2 = Consequent {
-            var: "pumpspeed".to_string(),
-            term: "High".to_string(),
+        let csqt_2 = Consequent::Term {
+            var: "pumpspeed".into(),
+            term: "High".into(),
+            hedge: None,
         };
 
-        let csqt_3 = Consequent {
-            var: "fanpspeed".to_string(),
-            term: "Low".to_string(),
+        let csqt_3 = Consequent::Term {
+            var: "fanpspeed".into(),
+            term: "Low".into(),
+            hedge: None,
         };
 
-        let csqt_4 = Consequent {
-            var: "pumpspeed".to_string(),
-            term: "Low".to_string(),
+        let csqt_4 = Consequent::Term {
+            var: "pumpspeed".into(),
+            term: "Low".into(),
+            hedge: None,
         };
 
         let rule = Rule {
             antecedent: ast,
             consequent: vec![csqt_1, csqt_2],
+            weight: 1.0,
         };
         let rule_2 = Rule {
             antecedent: ast_2,
             consequent: vec![csqt_3, csqt_4],
+            weight: 1.0,
         };
 
         let mut inputs: HashMap<&str, Float> = HashMap::new();
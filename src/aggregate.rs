@@ -1,30 +1,108 @@
 // Aggregation utilities for combining rule outputs across consequents.
 
-use crate::{mamdani::Rule, prelude::*, variable::Variable};
+use crate::{mamdani::Rule, prelude::*, sampler::GridCache, variable::Variable};
 use std::{borrow::Borrow, collections::HashMap, hash::Hash};
 
+/// How the implicated sets of several rules are merged into one output set.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Aggregation {
+    /// `max(a, b)`
+    #[default]
+    Max,
+    /// `min(1, a + b)`: overlapping rules reinforce each other.
+    BoundedSum,
+    /// `a + b - a * b`, see `ops::probabilistic_sum`.
+    ProbabilisticSum,
+}
+
+impl Aggregation {
+    /// Merged degree of `a` and `b`.
+    pub fn apply(self, a: Float, b: Float) -> Float {
+        match self {
+            Aggregation::Max => a.max(b),
+            Aggregation::BoundedSum => (a + b).min(1.0),
+            Aggregation::ProbabilisticSum => crate::ops::probabilistic_sum(a, b),
+        }
+    }
+}
+
 /// Combine two membership sample vectors by taking the pointwise maximum.
-pub fn elements_max(data: &mut Vec<Float>, src: &Vec<Float>) {
+pub fn elements_max(data: &mut [Float], src: &[Float]) {
     for (d, s) in data.iter_mut().zip(src) {
         *d = d.max(*s)
     }
 }
 
 /// Aggregate the contributions of all rules into output membership functions.
-pub fn aggregation<KI, KV>(
-    rules: &[Rule],
+///
+/// `rules` may be any iterator of rule references: a slice, a filtered view
+/// (`rules.iter().filter(..)`), or rules generated on the fly.
+pub fn aggregation<'a, KI, KV>(
+    rules: impl IntoIterator<Item = &'a Rule>,
     input: &HashMap<KI, Float>,
     vars: &HashMap<KV, Variable>,
     sampler: &UniformSampler,
 ) -> Result<HashMap<String, Vec<Float>>>
+where
+    KI: Eq + Hash + Borrow<str>,
+    KV: Eq + Hash + Borrow<str>,
+{
+    aggregation_cached(rules, input, vars, sampler, &mut GridCache::new())
+}
+
+/// Same as `aggregation`, but reuses sampled domain grids from `grids`.
+pub fn aggregation_cached<'a, KI, KV>(
+    rules: impl IntoIterator<Item = &'a Rule>,
+    input: &HashMap<KI, Float>,
+    vars: &HashMap<KV, Variable>,
+    sampler: &UniformSampler,
+    grids: &mut GridCache,
+) -> Result<HashMap<String, Vec<Float>>>
 where
     KI: Eq + Hash + Borrow<str>,
     KV: Eq + Hash + Borrow<str>,
 {
     let mut implicated_map: HashMap<String, Vec<Float>> = HashMap::new();
-    for i in 0..rules.len() {
-        let alpha = rules[i].activation(&input, &vars)?;
-        let implicated = rules[i].implicate(alpha, vars, &sampler)?;
+    for rule in rules {
+        let alpha = rule.activation(input, vars)?;
+        let implicated = rule.implicate_cached(alpha, vars, sampler, grids)?;
+
+        for (k, v) in implicated {
+            implicated_map
+                .entry(k)
+                .and_modify(|cur| elements_max(cur, &v))
+                .or_insert(v);
+        }
+    }
+
+    Ok(implicated_map)
+}
+
+/// Aggregate only the output variables named in `outputs`.
+///
+/// Rules that conclude on none of them are skipped without evaluating their
+/// antecedent, and consequents for other variables are never implicated.
+pub fn aggregation_for<'a, KI, KV>(
+    rules: impl IntoIterator<Item = &'a Rule>,
+    input: &HashMap<KI, Float>,
+    vars: &HashMap<KV, Variable>,
+    sampler: &UniformSampler,
+    outputs: &[&str],
+) -> Result<HashMap<String, Vec<Float>>>
+where
+    KI: Eq + Hash + Borrow<str>,
+    KV: Eq + Hash + Borrow<str>,
+{
+    let keep = |var: &str| outputs.contains(&var);
+    let mut grids = GridCache::new();
+    let mut implicated_map: HashMap<String, Vec<Float>> = HashMap::new();
+    for rule in rules {
+        if !rule.consequent.iter().any(|c| keep(c.var())) {
+            continue;
+        }
+        let alpha = rule.activation(input, vars)?;
+        let implicated = rule.implicate_where(alpha, vars, sampler, &mut grids, keep)?;
 
         for (k, v) in implicated {
             implicated_map
@@ -34,5 +112,5 @@ where
         }
     }
 
-    return Ok(implicated_map);
+    Ok(implicated_map)
 }
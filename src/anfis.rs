@@ -0,0 +1,400 @@
+// Adaptive neuro-fuzzy inference (ANFIS).
+//
+// A first-order Takagi–Sugeno network: Gaussian premise terms per input, product
+// firing strengths, normalized weights and a linear consequent per rule. Training
+// uses Jang's hybrid rule: each epoch solves the consequent coefficients by least
+// squares with the premises fixed, then takes one gradient step on the premise
+// means and widths with the consequents fixed.
+use crate::{
+    error::{FuzzyError, Result},
+    membership::{Gaussian, MembershipFn},
+    Float,
+};
+
+/// Smallest premise width kept during training, so no term collapses to a spike.
+const MIN_SD: Float = 1e-3;
+
+/// Ridge term added to the normal equations to keep them solvable when some
+/// rules never fire on the training data.
+const RIDGE: Float = 1e-9;
+
+/// Training knobs for `Anfis::fit`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AnfisOptions {
+    /// Number of hybrid passes over the data.
+    pub epochs: usize,
+    /// Step size for the premise gradient update.
+    pub learning_rate: Float,
+}
+
+impl Default for AnfisOptions {
+    fn default() -> Self {
+        Self {
+            epochs: 50,
+            learning_rate: 0.01,
+        }
+    }
+}
+
+/// A trainable first-order Sugeno fuzzy model.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Anfis {
+    /// Premise terms, `premises[i]` for input `i`.
+    premises: Vec<Vec<Gaussian>>,
+    /// For each rule, the premise term it uses on every input.
+    rules: Vec<Vec<usize>>,
+    /// For each rule, `[p_1, .., p_n, r]` so that `f = p·x + r`.
+    coeffs: Vec<Vec<Float>>,
+}
+
+impl Anfis {
+    /// Builds a model from explicit premise terms and rules.
+    ///
+    /// Every rule must name one term index per input. Consequents start at zero.
+    pub fn new(premises: Vec<Vec<Gaussian>>, rules: Vec<Vec<usize>>) -> Result<Self> {
+        if premises.is_empty() || rules.is_empty() {
+            return Err(FuzzyError::EmptyInput);
+        }
+        for rule in &rules {
+            if rule.len() != premises.len() {
+                return Err(FuzzyError::BadArity);
+            }
+            if rule
+                .iter()
+                .zip(&premises)
+                .any(|(&k, terms)| k >= terms.len())
+            {
+                return Err(FuzzyError::OutOfBounds);
+            }
+        }
+        let coeffs = vec![vec![0.0; premises.len() + 1]; rules.len()];
+        Ok(Self {
+            premises,
+            rules,
+            coeffs,
+        })
+    }
+
+    /// Grid partition: `terms` evenly spaced Gaussians per input domain and one rule
+    /// per combination of terms (`terms^inputs` rules).
+    pub fn grid(domains: &[(Float, Float)], terms: usize) -> Result<Self> {
+        if terms < 2 {
+            return Err(FuzzyError::OutOfBounds);
+        }
+        let premises = domains
+            .iter()
+            .map(|&(min, max)| {
+                if min >= max {
+                    return Err(FuzzyError::OutOfBounds);
+                }
+                let step = (max - min) / (terms - 1) as Float;
+                // Neighbouring curves cross at μ = 0.5.
+                let sd = step / (2.0 * (2.0 as Float).ln()).sqrt();
+                (0..terms)
+                    .map(|k| Gaussian::with_mean_sd(min + k as Float * step, sd))
+                    .collect()
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let mut rules = vec![Vec::new()];
+        for _ in domains {
+            rules = rules
+                .into_iter()
+                .flat_map(|r| {
+                    (0..terms).map(move |k| {
+                        let mut r = r.clone();
+                        r.push(k);
+                        r
+                    })
+                })
+                .collect();
+        }
+        Self::new(premises, rules)
+    }
+
+    /// One rule per cluster, e.g. from `cluster::subtractive` run on
+    /// `[inputs.., target]` rows: every input gets one Gaussian per cluster,
+    /// centered on the cluster's coordinate with the matching `sigmas` width.
+    /// Coordinates past `inputs` (the target) are ignored.
+    pub fn from_clusters(centers: &[Vec<Float>], sigmas: &[Float], inputs: usize) -> Result<Self> {
+        if inputs == 0 || sigmas.len() < inputs || centers.iter().any(|c| c.len() < inputs) {
+            return Err(FuzzyError::BadArity);
+        }
+        let premises = (0..inputs)
+            .map(|i| {
+                centers
+                    .iter()
+                    .map(|c| Gaussian::with_mean_sd(c[i], sigmas[i]))
+                    .collect::<Result<Vec<_>>>()
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let rules = (0..centers.len()).map(|k| vec![k; inputs]).collect();
+        Self::new(premises, rules)
+    }
+
+    /// Premise terms, one list per input.
+    pub fn premises(&self) -> &[Vec<Gaussian>] {
+        &self.premises
+    }
+
+    /// Consequent coefficients `[p_1, .., p_n, r]` of every rule.
+    pub fn coefficients(&self) -> &[Vec<Float>] {
+        &self.coeffs
+    }
+
+    /// Crisp output for one input row. NaN when no rule fires.
+    pub fn predict(&self, x: &[Float]) -> Result<Float> {
+        if x.len() != self.premises.len() {
+            return Err(FuzzyError::BadArity);
+        }
+        let w = self.firing(x);
+        let total: Float = w.iter().sum();
+        Ok(w.iter()
+            .zip(&self.coeffs)
+            .map(|(wr, c)| wr * linear(c, x))
+            .sum::<Float>()
+            / total)
+    }
+
+    /// Trains on `(inputs, target)` rows with the hybrid rule and returns each
+    /// epoch's root-mean-square error, measured after its least-squares pass.
+    pub fn fit(&mut self, data: &[(Vec<Float>, Float)], opts: AnfisOptions) -> Result<Vec<Float>> {
+        if data.is_empty() {
+            return Err(FuzzyError::EmptyInput);
+        }
+        if data.iter().any(|(x, _)| x.len() != self.premises.len()) {
+            return Err(FuzzyError::BadArity);
+        }
+        let mut history = Vec::with_capacity(opts.epochs);
+        for _ in 0..opts.epochs {
+            self.solve_consequents(data)?;
+            history.push(self.premise_step(data, opts.learning_rate));
+        }
+        Ok(history)
+    }
+
+    fn firing(&self, x: &[Float]) -> Vec<Float> {
+        self.rules
+            .iter()
+            .map(|rule| {
+                rule.iter()
+                    .zip(&self.premises)
+                    .zip(x)
+                    .map(|((&k, terms), &xi)| terms[k].eval(xi))
+                    .product()
+            })
+            .collect()
+    }
+
+    /// Forward pass: least-squares fit of all consequent coefficients.
+    fn solve_consequents(&mut self, data: &[(Vec<Float>, Float)]) -> Result<()> {
+        let width = self.premises.len() + 1;
+        let cols = self.rules.len() * width;
+        let mut ata = vec![0.0; cols * cols];
+        let mut aty = vec![0.0; cols];
+        let mut row = vec![0.0; cols];
+
+        for (x, y) in data {
+            let w = self.firing(x);
+            let total: Float = w.iter().sum();
+            if total <= 0.0 {
+                continue;
+            }
+            for (r, wr) in w.iter().enumerate() {
+                let wbar = wr / total;
+                for (j, xj) in x.iter().chain(std::iter::once(&1.0)).enumerate() {
+                    row[r * width + j] = wbar * xj;
+                }
+            }
+            for i in 0..cols {
+                aty[i] += row[i] * y;
+                for j in 0..cols {
+                    ata[i * cols + j] += row[i] * row[j];
+                }
+            }
+        }
+        for i in 0..cols {
+            ata[i * cols + i] += RIDGE;
+        }
+
+        let theta = solve(ata, aty)?;
+        for (c, chunk) in self.coeffs.iter_mut().zip(theta.chunks(width)) {
+            c.copy_from_slice(chunk);
+        }
+        Ok(())
+    }
+
+    /// Backward pass: one gradient step on premise means and widths.
+    /// Returns the RMSE measured before the step.
+    fn premise_step(&mut self, data: &[(Vec<Float>, Float)], lr: Float) -> Float {
+        let mut grads: Vec<Vec<(Float, Float)>> = self
+            .premises
+            .iter()
+            .map(|terms| vec![(0.0, 0.0); terms.len()])
+            .collect();
+        let mut sse = 0.0;
+        let n = data.len() as Float;
+
+        for (x, target) in data {
+            let w = self.firing(x);
+            let total: Float = w.iter().sum();
+            if total <= 0.0 {
+                continue;
+            }
+            let f: Vec<Float> = self.coeffs.iter().map(|c| linear(c, x)).collect();
+            let y = w.iter().zip(&f).map(|(wr, fr)| wr * fr).sum::<Float>() / total;
+            let err = y - target;
+            sse += err * err;
+
+            for (r, rule) in self.rules.iter().enumerate() {
+                // dE/dw_r for E = mean squared error.
+                let de_dw = 2.0 * err * (f[r] - y) / total / n;
+                for (i, &k) in rule.iter().enumerate() {
+                    // Product of the other premises of this rule, computed directly
+                    // so that a zero membership does not poison the quotient.
+                    let others: Float = rule
+                        .iter()
+                        .enumerate()
+                        .filter(|&(j, _)| j != i)
+                        .map(|(j, &kj)| self.premises[j][kj].eval(x[j]))
+                        .product();
+                    let (dm, ds) = self.premises[i][k].gradient(x[i]);
+                    let g = &mut grads[i][k];
+                    g.0 += de_dw * others * dm;
+                    g.1 += de_dw * others * ds;
+                }
+            }
+        }
+
+        for (terms, g) in self.premises.iter_mut().zip(&grads) {
+            for (term, &(dm, ds)) in terms.iter_mut().zip(g) {
+                let sd = (term.sd() - lr * ds).max(MIN_SD);
+                *term = Gaussian::with_mean_sd(term.mean() - lr * dm, sd).unwrap_or(*term);
+            }
+        }
+        (sse / n).sqrt()
+    }
+}
+
+fn linear(c: &[Float], x: &[Float]) -> Float {
+    let (r, p) = c.split_last().expect("coefficients hold at least the bias");
+    p.iter().zip(x).map(|(pi, xi)| pi * xi).sum::<Float>() + r
+}
+
+/// Solves the dense square system `a · θ = b` by Gaussian elimination with
+/// partial pivoting. `a` is row-major.
+fn solve(mut a: Vec<Float>, mut b: Vec<Float>) -> Result<Vec<Float>> {
+    let n = b.len();
+    for col in 0..n {
+        let pivot = (col..n)
+            .max_by(|&i, &j| a[i * n + col].abs().total_cmp(&a[j * n + col].abs()))
+            .ok_or(FuzzyError::EmptyInput)?;
+        if a[pivot * n + col] == 0.0 {
+            return Err(FuzzyError::BadArity);
+        }
+        if pivot != col {
+            for j in 0..n {
+                a.swap(pivot * n + j, col * n + j);
+            }
+            b.swap(pivot, col);
+        }
+        for row in col + 1..n {
+            let factor = a[row * n + col] / a[col * n + col];
+            if factor != 0.0 {
+                for j in col..n {
+                    a[row * n + j] -= factor * a[col * n + j];
+                }
+                b[row] -= factor * b[col];
+            }
+        }
+    }
+    let mut x = vec![0.0; n];
+    for row in (0..n).rev() {
+        let tail: Float = (row + 1..n).map(|j| a[row * n + j] * x[j]).sum();
+        x[row] = (b[row] - tail) / a[row * n + row];
+    }
+    Ok(x)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn samples(f: impl Fn(Float, Float) -> Float) -> Vec<(Vec<Float>, Float)> {
+        let mut data = Vec::new();
+        for i in 0..=10 {
+            for j in 0..=10 {
+                let (a, b) = (i as Float / 10.0, j as Float / 10.0);
+                data.push((vec![a, b], f(a, b)));
+            }
+        }
+        data
+    }
+
+    #[test]
+    fn linear_target_is_fit_by_least_squares_alone() {
+        let data = samples(|a, b| 3.0 * a - 2.0 * b + 0.5);
+        let mut model = Anfis::grid(&[(0.0, 1.0), (0.0, 1.0)], 2).unwrap();
+        let rmse = model
+            .fit(
+                &data,
+                AnfisOptions {
+                    epochs: 1,
+                    learning_rate: 0.0,
+                },
+            )
+            .unwrap();
+        assert!(rmse[0] < 1e-2, "{rmse:?}");
+        assert!((model.predict(&[0.25, 0.75]).unwrap() - (0.75 - 1.5 + 0.5)).abs() < 1e-2);
+    }
+
+    #[test]
+    fn hybrid_training_reduces_error_on_nonlinear_target() {
+        let data = samples(|a, b| (3.0 * a).sin() * b * b);
+        let mut model = Anfis::grid(&[(0.0, 1.0), (0.0, 1.0)], 3).unwrap();
+        let rmse = model
+            .fit(
+                &data,
+                AnfisOptions {
+                    epochs: 30,
+                    learning_rate: 0.05,
+                },
+            )
+            .unwrap();
+        assert_eq!(rmse.len(), 30);
+        assert!(rmse[29] <= rmse[0], "{rmse:?}");
+        assert!(rmse[29] < 0.05, "{rmse:?}");
+    }
+
+    #[test]
+    fn subtractive_clusters_seed_a_compact_model() {
+        use crate::cluster::{subtractive, SubtractiveOptions};
+
+        let data = samples(|a, b| (3.0 * a).sin() * b * b);
+        let rows: Vec<Vec<Float>> = data.iter().map(|(x, y)| vec![x[0], x[1], *y]).collect();
+        let sc = subtractive(&rows, SubtractiveOptions::default()).unwrap();
+        let mut model = Anfis::from_clusters(&sc.centers, &sc.sigmas, 2).unwrap();
+        assert_eq!(model.rules.len(), sc.centers.len());
+
+        let rmse = model.fit(&data, AnfisOptions::default()).unwrap();
+        assert!(rmse[rmse.len() - 1] < 0.1, "{rmse:?}");
+    }
+
+    #[test]
+    fn shape_errors_are_reported() {
+        let mut model = Anfis::grid(&[(0.0, 1.0)], 2).unwrap();
+        assert_eq!(model.rules.len(), 2);
+        assert_eq!(model.predict(&[0.1, 0.2]), Err(FuzzyError::BadArity));
+        assert_eq!(
+            model.fit(&[], AnfisOptions::default()),
+            Err(FuzzyError::EmptyInput)
+        );
+        assert_eq!(
+            Anfis::new(
+                vec![vec![Gaussian::with_mean_sd(0.0, 1.0).unwrap()]],
+                vec![vec![1]]
+            ),
+            Err(FuzzyError::OutOfBounds)
+        );
+    }
+}
@@ -1,9 +1,20 @@
-use std::{borrow::Borrow, collections::HashMap, hash::Hash};
+use std::{
+    borrow::{Borrow, Cow},
+    collections::HashMap,
+    fmt,
+    hash::Hash,
+};
 
 // Public APIs used by this module:
 // - `prelude::*`: common scalar, error types, and traits (e.g., `Float`, `Result`, `FuzzyError`).
 // - `Variable`: crisp variable with named fuzzy terms and domain validation.
-use crate::{prelude::*, variable::Variable};
+use crate::{hedges::Hedge, ops::Mean, prelude::*, variable::Variable};
+
+/// Variable or term name stored in rules.
+///
+/// Names given as string literals are borrowed, so rule bases written in code
+/// allocate nothing for them and clone cheaply; names built at runtime are owned.
+pub type Name = Cow<'static, str>;
 
 /// Antecedent abstract syntax tree (AST) for fuzzy rules.
 ///
@@ -17,13 +28,110 @@ use crate::{prelude::*, variable::Variable};
 #[derive(Debug, Clone, PartialEq)]
 pub enum Antecedent {
     /// Atomic predicate: membership of `term` for variable `var`.
-    Atom { var: String, term: String },
+    Atom { var: Name, term: Name },
     /// Conjunction: `min(left, right)` with the default operator family.
     And(Box<Self>, Box<Self>),
     /// Disjunction: `max(left, right)` with the default operator family.
     Or(Box<Self>, Box<Self>),
     /// Negation: `1 - value` with the default operator family.
     Not(Box<Self>),
+    /// Hedged sub-expression, e.g. `temp IS very hot`.
+    Hedged(Hedge, Box<Self>),
+    /// Averaging combination of the operands, between AND and OR.
+    Mean(Mean, Vec<Self>),
+    /// A fixed degree, e.g. left behind when `RuleSpace::specialize` folds
+    /// atoms on fixed inputs.
+    Const(Float),
+}
+
+impl Antecedent {
+    /// Conjunction `self AND other`.
+    pub fn and(self, other: Self) -> Self {
+        Self::And(Box::new(self), Box::new(other))
+    }
+
+    /// Disjunction `self OR other`.
+    pub fn or(self, other: Self) -> Self {
+        Self::Or(Box::new(self), Box::new(other))
+    }
+
+    /// Applies `hedge` to this expression's degree.
+    pub fn hedged(self, hedge: Hedge) -> Self {
+        Self::Hedged(hedge, Box::new(self))
+    }
+}
+
+/// Rule-text form, e.g. `temp IS very hot AND NOT (rh IS low OR rh IS high)`.
+///
+/// Parentheses are written only where AND and OR mix or an operator applies to
+/// a compound operand.
+impl fmt::Display for Antecedent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Writes `child`, parenthesized if it is AND/OR under another operator
+        // (`same` is whether the parent is AND, `None` for unary parents).
+        let operand = |f: &mut fmt::Formatter<'_>, child: &Self, same: Option<bool>| {
+            let bare = match child {
+                Self::Atom { .. } | Self::Mean(..) | Self::Not(_) | Self::Const(_) => true,
+                Self::Hedged(_, a) => matches!(**a, Self::Atom { .. }),
+                Self::And(..) => same == Some(true),
+                Self::Or(..) => same == Some(false),
+            };
+            if bare {
+                write!(f, "{child}")
+            } else {
+                write!(f, "({child})")
+            }
+        };
+        match self {
+            Self::Atom { var, term } => write!(f, "{var} IS {term}"),
+            Self::And(a, b) | Self::Or(a, b) => {
+                let and = matches!(self, Self::And(..));
+                operand(f, a, Some(and))?;
+                f.write_str(if and { " AND " } else { " OR " })?;
+                operand(f, b, Some(and))
+            }
+            Self::Not(a) => {
+                f.write_str("NOT ")?;
+                operand(f, a, None)
+            }
+            Self::Hedged(hedge, a) => match &**a {
+                Self::Atom { var, term } => write!(f, "{var} IS {hedge} {term}"),
+                _ => {
+                    write!(f, "{hedge} ")?;
+                    operand(f, a, None)
+                }
+            },
+            Self::Mean(kind, operands) => {
+                write!(f, "{kind}(")?;
+                for (i, a) in operands.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(", ")?;
+                    }
+                    write!(f, "{a}")?;
+                }
+                f.write_str(")")
+            }
+            Self::Const(c) => write!(f, "{c}"),
+        }
+    }
+}
+
+/// Atomic predicate `var IS term`.
+pub fn atom(var: impl Into<Name>, term: impl Into<Name>) -> Antecedent {
+    Antecedent::Atom {
+        var: var.into(),
+        term: term.into(),
+    }
+}
+
+/// Averaging combination of `operands`, e.g. `mean(Mean::Geometric, [a, b, c])`.
+pub fn mean(kind: Mean, operands: impl IntoIterator<Item = Antecedent>) -> Antecedent {
+    Antecedent::Mean(kind, operands.into_iter().collect())
+}
+
+/// Negation `NOT ant`.
+pub fn not(ant: Antecedent) -> Antecedent {
+    Antecedent::Not(Box::new(ant))
 }
 
 /// Evaluate a fuzzy antecedent to a membership degree in [0, 1].
@@ -52,33 +160,126 @@ where
     KI: Eq + Hash + Borrow<str>,
     KV: Eq + Hash + Borrow<str>,
 {
-    // Recursive evaluation according to the default Min–Max family.
+    eval_with(ant, &mut |var, term| {
+        let v = vars.get(var).ok_or(FuzzyError::NotFound {
+            space: crate::error::MissingSpace::Var,
+            key: var.to_string(),
+        })?;
+        let x = *input.get(var).ok_or(FuzzyError::NotFound {
+            space: crate::error::MissingSpace::Input,
+            key: var.to_string(),
+        })?;
+        v.eval(term, x)
+    })
+}
+
+/// The default Min–Max family `eval_with` combines with.
+pub(crate) struct DefaultOps;
+
+impl FuzzyOps for DefaultOps {
+    fn t(&self, a: Float, b: Float) -> Float {
+        a.min(b)
+    }
+
+    fn s(&self, a: Float, b: Float) -> Float {
+        a.max(b)
+    }
+
+    fn c(&self, a: Float) -> Float {
+        1.0 - a
+    }
+}
+
+/// Evaluate `ant` with a caller-supplied resolver for atomic predicates.
+///
+/// `atom(var, term)` is called once per atom in depth-first, left-to-right order,
+/// which lets the compiled engine pair atoms with pre-resolved ids instead of names.
+pub(crate) fn eval_with<F>(ant: &Antecedent, atom: &mut F) -> Result<Float>
+where
+    F: FnMut(&str, &str) -> Result<Float>,
+{
+    eval_ops(ant, &DefaultOps, atom)
+}
+
+/// Like `eval_with`, with AND, OR and NOT taken from `ops`.
+pub(crate) fn eval_ops<O, F>(ant: &Antecedent, ops: &O, atom: &mut F) -> Result<Float>
+where
+    O: FuzzyOps + ?Sized,
+    F: FnMut(&str, &str) -> Result<Float>,
+{
     match ant {
-        Antecedent::Atom { var, term } => {
-            let v = vars.get(var.as_str()).ok_or(FuzzyError::NotFound {
-                space: crate::error::MissingSpace::Var,
-                key: var.clone(),
-            })?;
-            let x = *input.get(var.as_str()).ok_or(FuzzyError::NotFound {
-                space: crate::error::MissingSpace::Input,
-                key: term.clone(),
-            })?;
-            v.eval(term.as_str(), x)
-        }
+        Antecedent::Atom { var, term } => atom(var, term),
         Antecedent::And(a, b) => {
-            let a = eval_antecedent(a, input, vars)?;
-            let b = eval_antecedent(b, input, vars)?;
-            Ok(a.min(b))
+            let a = eval_ops(a, ops, atom)?;
+            let b = eval_ops(b, ops, atom)?;
+            Ok(ops.t(a, b))
         }
         Antecedent::Or(a, b) => {
-            let a = eval_antecedent(a, input, vars)?;
-            let b = eval_antecedent(b, input, vars)?;
-            Ok(a.max(b))
+            let a = eval_ops(a, ops, atom)?;
+            let b = eval_ops(b, ops, atom)?;
+            Ok(ops.s(a, b))
         }
         Antecedent::Not(a) => {
-            let a = eval_antecedent(a, input, vars)?;
-            Ok(1.0 - a)
+            let a = eval_ops(a, ops, atom)?;
+            Ok(ops.c(a))
+        }
+        Antecedent::Hedged(hedge, a) => Ok(hedge.apply(eval_ops(a, ops, atom)?)),
+        Antecedent::Mean(kind, operands) => {
+            if operands.is_empty() {
+                return Err(FuzzyError::EmptyInput);
+            }
+            let xs = operands
+                .iter()
+                .map(|a| eval_ops(a, ops, atom))
+                .collect::<Result<Vec<_>>>()?;
+            Ok(kind.apply(&xs))
+        }
+        Antecedent::Const(c) => Ok(*c),
+    }
+}
+
+/// Fail with `InvalidParameter` for the first hedge of `ant` that does not
+/// pass `Hedge::validate` or the first `Const` degree outside `[0, 1]`.
+pub(crate) fn validate_antecedent(ant: &Antecedent) -> crate::error::Result<()> {
+    match ant {
+        Antecedent::Atom { .. } => Ok(()),
+        Antecedent::Const(c) if !(0.0..=1.0).contains(c) => Err(FuzzyError::InvalidParameter {
+            mf: "Antecedent",
+            field: "const",
+            value: *c,
+        }),
+        Antecedent::Const(_) => Ok(()),
+        Antecedent::And(a, b) | Antecedent::Or(a, b) => {
+            validate_antecedent(a)?;
+            validate_antecedent(b)
+        }
+        Antecedent::Not(a) => validate_antecedent(a),
+        Antecedent::Hedged(hedge, a) => {
+            hedge.validate()?;
+            validate_antecedent(a)
+        }
+        Antecedent::Mean(_, operands) => operands.iter().try_for_each(validate_antecedent),
+    }
+}
+
+/// Visit every atom of `ant` in the same order `eval_with` resolves them.
+pub(crate) fn for_each_atom<'a, F>(ant: &'a Antecedent, f: &mut F)
+where
+    F: FnMut(&'a str, &'a str),
+{
+    match ant {
+        Antecedent::Atom { var, term } => f(var, term),
+        Antecedent::And(a, b) | Antecedent::Or(a, b) => {
+            for_each_atom(a, f);
+            for_each_atom(b, f);
         }
+        Antecedent::Not(a) | Antecedent::Hedged(_, a) => for_each_atom(a, f),
+        Antecedent::Mean(_, operands) => {
+            for a in operands {
+                for_each_atom(a, f);
+            }
+        }
+        Antecedent::Const(_) => {}
     }
 }
 
@@ -86,6 +287,7 @@ where
 mod tests {
     use std::collections::HashMap;
 
+    use crate::antecedent::eval_antecedent;
     use crate::membership::triangular::Triangular;
     use crate::prelude::*;
     use crate::term::Term;
@@ -180,4 +382,53 @@ mod tests {
         let y = crate::antecedent::eval_antecedent(&ast, &inputs, &vars).unwrap();
         assert!((y - expected).abs() < crate::Float::EPSILON);
     }
+
+    #[test]
+    fn mean_compensates_between_and_and_or() {
+        let mut temp = Variable::new(0.0, 10.0).unwrap();
+        for (name, c) in [("warm", 5.0), ("hot", 10.0)] {
+            temp.insert_term(
+                name,
+                Term::new(name, Triangular::new(c - 5.0, c, c + 5.0).unwrap()),
+            )
+            .unwrap();
+        }
+        let vars = HashMap::from([("temp", temp)]);
+        let inputs = HashMap::from([("temp", 6.0)]);
+        let eval = |ant: &Antecedent| eval_antecedent(ant, &inputs, &vars).unwrap();
+
+        let (warm, hot) = (atom("temp", "warm"), atom("temp", "hot"));
+        let and = eval(&warm.clone().and(hot.clone()));
+        let or = eval(&warm.clone().or(hot.clone()));
+        for kind in [
+            Mean::Arithmetic,
+            Mean::Geometric,
+            Mean::Harmonic,
+            Mean::Power(2.0),
+        ] {
+            let y = eval(&mean(kind, [warm.clone(), hot.clone()]));
+            assert!(and < y && y < or, "{kind}: {y}");
+            assert_eq!(y, kind.apply(&[eval(&warm), eval(&hot)]));
+        }
+        assert_eq!(
+            eval_antecedent(&mean(Mean::Arithmetic, []), &inputs, &vars),
+            Err(FuzzyError::EmptyInput)
+        );
+    }
+
+    #[test]
+    fn display_reads_as_rule_text() {
+        let ant = atom("temp", "hot")
+            .hedged(Hedge::Very)
+            .and(not(atom("rh", "low").or(atom("rh", "high"))))
+            .and(atom("wind", "calm"));
+        assert_eq!(
+            ant.to_string(),
+            "temp IS very hot AND NOT (rh IS low OR rh IS high) AND wind IS calm"
+        );
+        let mixed = atom("a", "x").or(atom("b", "y").and(atom("c", "z")));
+        assert_eq!(mixed.to_string(), "a IS x OR (b IS y AND c IS z)");
+        let avg = mean(Mean::Geometric, [atom("a", "x"), atom("b", "y")]);
+        assert_eq!(avg.to_string(), "geometric mean(a IS x, b IS y)");
+    }
 }
@@ -0,0 +1,354 @@
+// Property-test generators (the `proptest` feature).
+//
+// `Arbitrary` implementations for membership shapes, hedges, means, variables,
+// antecedents and rules, so invariants such as "degrees stay in [0, 1]" or
+// "outputs stay in their domain" can be checked across random systems. Every
+// generated value is valid by construction: shape key points are strictly
+// increasing inside the requested domain, and antecedents and rules only name
+// variables and terms of their `Vocabulary`. `system` ties the pieces together
+// into inputs for `RuleSpace::new`.
+use std::collections::HashMap;
+
+use proptest::{
+    arbitrary::{any, any_with, Arbitrary},
+    collection::vec,
+    prop_oneof,
+    sample::{select, subsequence},
+    strategy::{BoxedStrategy, Just, Strategy},
+};
+
+use crate::{
+    antecedent::{atom, Antecedent},
+    hedges::Hedge,
+    mamdani::Rule,
+    membership::{trapezoidal::Trapezoidal, Gaussian, MembershipKind, Rectangular, Triangular},
+    ops::Mean,
+    term::Term,
+    variable::Variable,
+    Float,
+};
+
+/// Resolution of generated key points: shapes place them on a grid of this
+/// many steps across their domain.
+const STEPS: u32 = 1000;
+
+/// Domain the key points of generated membership shapes lie in.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Domain {
+    pub min: Float,
+    pub max: Float,
+}
+
+impl Default for Domain {
+    fn default() -> Self {
+        Self { min: 0.0, max: 1.0 }
+    }
+}
+
+impl Domain {
+    /// `n` strictly increasing points of the domain.
+    fn points(self, n: usize) -> impl Strategy<Value = Vec<Float>> {
+        let grid: Vec<u32> = (0..=STEPS).collect();
+        subsequence(grid, n).prop_map(move |ks| {
+            ks.into_iter()
+                .map(|k| self.min + (self.max - self.min) * k as Float / STEPS as Float)
+                .collect()
+        })
+    }
+}
+
+/// Variables, with their term names, that generated rules may refer to.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Vocabulary {
+    /// Variables antecedents test; every one needs at least one term.
+    pub inputs: Vec<(String, Vec<String>)>,
+    /// Variables consequents conclude on; every one needs at least one term.
+    pub outputs: Vec<(String, Vec<String>)>,
+}
+
+impl Vocabulary {
+    /// Inputs `x0, x1, ...` and outputs `y0, y1, ...` with the given numbers of
+    /// terms, named `t0, t1, ...` as in `variable`.
+    pub fn numbered(inputs: &[usize], outputs: &[usize]) -> Self {
+        let named = |prefix: &str, counts: &[usize]| {
+            counts
+                .iter()
+                .enumerate()
+                .map(|(i, &n)| {
+                    (
+                        format!("{prefix}{i}"),
+                        (0..n).map(|j| format!("t{j}")).collect(),
+                    )
+                })
+                .collect()
+        };
+        Self {
+            inputs: named("x", inputs),
+            outputs: named("y", outputs),
+        }
+    }
+
+    fn pairs(vars: &[(String, Vec<String>)]) -> Vec<(String, String)> {
+        vars.iter()
+            .flat_map(|(v, terms)| terms.iter().map(move |t| (v.clone(), t.clone())))
+            .collect()
+    }
+}
+
+/// Two inputs and one output with three terms each.
+impl Default for Vocabulary {
+    fn default() -> Self {
+        Self::numbered(&[3, 3], &[3])
+    }
+}
+
+impl Arbitrary for Triangular {
+    type Parameters = Domain;
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(domain: Domain) -> Self::Strategy {
+        domain
+            .points(3)
+            .prop_map(|p| Triangular::new(p[0], p[1], p[2]).expect("points increase"))
+            .boxed()
+    }
+}
+
+impl Arbitrary for Trapezoidal {
+    type Parameters = Domain;
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(domain: Domain) -> Self::Strategy {
+        domain
+            .points(4)
+            .prop_map(|p| Trapezoidal::new(p[0], p[1], p[2], p[3]).expect("points increase"))
+            .boxed()
+    }
+}
+
+impl Arbitrary for Rectangular {
+    type Parameters = Domain;
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(domain: Domain) -> Self::Strategy {
+        domain
+            .points(2)
+            .prop_map(|p| Rectangular::new(p[0], p[1]).expect("points increase"))
+            .boxed()
+    }
+}
+
+/// Mean inside the domain, standard deviation between 2% and 50% of its width.
+impl Arbitrary for Gaussian {
+    type Parameters = Domain;
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(domain: Domain) -> Self::Strategy {
+        let width = domain.max - domain.min;
+        (domain.points(1), 1..=25u32)
+            .prop_map(move |(p, k)| {
+                Gaussian::with_mean_sd(p[0], width * k as Float / 50.0).expect("sd is positive")
+            })
+            .boxed()
+    }
+}
+
+/// One of the built-in shapes; never `Custom`.
+impl Arbitrary for MembershipKind {
+    type Parameters = Domain;
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(domain: Domain) -> Self::Strategy {
+        prop_oneof![
+            any_with::<Triangular>(domain).prop_map(Self::Triangular),
+            any_with::<Trapezoidal>(domain).prop_map(Self::Trapezoidal),
+            any_with::<Gaussian>(domain).prop_map(Self::Gaussian),
+            any_with::<Rectangular>(domain).prop_map(Self::Rectangular),
+        ]
+        .boxed()
+    }
+}
+
+impl Arbitrary for Hedge {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: ()) -> Self::Strategy {
+        prop_oneof![
+            Just(Self::Very),
+            Just(Self::Somewhat),
+            Just(Self::Indeed),
+            (1..=40u32).prop_map(|k| Self::Power(k as Float / 10.0)),
+        ]
+        .boxed()
+    }
+}
+
+impl Arbitrary for Mean {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: ()) -> Self::Strategy {
+        prop_oneof![
+            Just(Self::Arithmetic),
+            Just(Self::Geometric),
+            Just(Self::Harmonic),
+            (-40..=40i32).prop_map(|k| Self::Power(k as Float / 10.0)),
+        ]
+        .boxed()
+    }
+}
+
+/// A variable with 1 to 5 terms, see `variable`.
+impl Arbitrary for Variable {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: ()) -> Self::Strategy {
+        (1..=5usize).prop_flat_map(variable).boxed()
+    }
+}
+
+/// A variable with `terms` built-in terms named `t0, t1, ...`, on a domain
+/// with bounds in `[-1000, 2000]` and width at least 1.
+pub fn variable(terms: usize) -> BoxedStrategy<Variable> {
+    (-1000..=1000i32, 1..=1000u32)
+        .prop_flat_map(move |(min, width)| {
+            let domain = Domain {
+                min: min as Float,
+                max: min as Float + width as Float,
+            };
+            (Just(domain), vec(any_with::<MembershipKind>(domain), terms))
+        })
+        .prop_map(|(domain, shapes)| {
+            let mut var = Variable::new(domain.min, domain.max).expect("min < max");
+            for (i, shape) in shapes.into_iter().enumerate() {
+                let name = format!("t{i}");
+                var.insert_term(&name, Term::with_kind(name.clone(), shape))
+                    .expect("names are distinct");
+            }
+            var
+        })
+        .boxed()
+}
+
+/// Expressions up to four operators deep over the vocabulary's inputs, with
+/// occasional constant degrees.
+///
+/// Panics if the vocabulary has no input term.
+impl Arbitrary for Antecedent {
+    type Parameters = Vocabulary;
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(vocabulary: Vocabulary) -> Self::Strategy {
+        let leaf = prop_oneof![
+            9 => select(Vocabulary::pairs(&vocabulary.inputs)).prop_map(|(v, t)| atom(v, t)),
+            1 => (0..=100u32).prop_map(|k| Self::Const(k as Float / 100.0)),
+        ];
+        leaf.prop_recursive(4, 24, 3, |inner| {
+            prop_oneof![
+                (inner.clone(), inner.clone()).prop_map(|(a, b)| a.and(b)),
+                (inner.clone(), inner.clone()).prop_map(|(a, b)| a.or(b)),
+                inner.clone().prop_map(|a| Self::Not(Box::new(a))),
+                (any::<Hedge>(), inner.clone()).prop_map(|(h, a)| a.hedged(h)),
+                (any::<Mean>(), vec(inner, 1..=3)).prop_map(|(m, xs)| Self::Mean(m, xs)),
+            ]
+        })
+        .boxed()
+    }
+}
+
+/// A rule over the vocabulary concluding on one output term, hedged a quarter
+/// of the time.
+///
+/// Panics if the vocabulary has no input or no output term.
+impl Arbitrary for Rule {
+    type Parameters = Vocabulary;
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(vocabulary: Vocabulary) -> Self::Strategy {
+        let consequent = select(Vocabulary::pairs(&vocabulary.outputs));
+        let hedge = prop_oneof![3 => Just(None), 1 => any::<Hedge>().prop_map(Some)];
+        (any_with::<Antecedent>(vocabulary), consequent, hedge)
+            .prop_map(|(antecedent, (var, term), hedge)| {
+                let builder = Rule::builder().when(antecedent);
+                match hedge {
+                    None => builder.then(var, term),
+                    Some(h) => builder.then_hedged(var, h, term),
+                }
+                .build()
+                .expect("rule has an antecedent and a consequent")
+            })
+            .boxed()
+    }
+}
+
+/// Variables and rules of a random system accepted by `RuleSpace::new`: one to
+/// three inputs `x0, ...` and one or two outputs `y0, ...` with 1 to 4 terms
+/// each, and 1 to 8 rules.
+pub fn system() -> BoxedStrategy<(HashMap<String, Variable>, Vec<Rule>)> {
+    (vec(1..=4usize, 1..=3), vec(1..=4usize, 1..=2))
+        .prop_flat_map(|(inputs, outputs)| {
+            let vocabulary = Vocabulary::numbered(&inputs, &outputs);
+            let vars: Vec<_> = vocabulary
+                .inputs
+                .iter()
+                .chain(&vocabulary.outputs)
+                .map(|(name, terms)| {
+                    let name = name.clone();
+                    variable(terms.len()).prop_map(move |v| (name.clone(), v))
+                })
+                .collect();
+            (vars, vec(any_with::<Rule>(vocabulary), 1..=8))
+        })
+        .prop_map(|(vars, rules)| (vars.into_iter().collect(), rules))
+        .boxed()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use proptest::{collection::vec, prelude::*};
+
+    use super::system;
+    use crate::prelude::*;
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(64))]
+
+        #[test]
+        fn degrees_stay_in_unit_interval(var in any::<Variable>(), ks in vec(0..=100u32, 8)) {
+            let (min, max) = var.domain();
+            for k in ks {
+                let x = min + (max - min) * k as Float / 100.0;
+                for (name, _) in var.terms() {
+                    let mu = var.eval(name, x).unwrap();
+                    prop_assert!((0.0..=1.0).contains(&mu), "{name}({x}) = {mu}");
+                }
+            }
+        }
+
+        #[test]
+        fn outputs_stay_in_domain((vars, rules) in system(), ks in vec(0..=100u32, 3)) {
+            let domains: HashMap<String, (Float, Float)> =
+                vars.iter().map(|(n, v)| (n.clone(), v.domain())).collect();
+            let rs = RuleSpace::new(vars, rules).unwrap();
+            let input: HashMap<String, Float> = domains
+                .iter()
+                .filter(|(n, _)| n.starts_with('x'))
+                .map(|(n, &(min, max))| {
+                    let i: usize = n[1..].parse().unwrap();
+                    (n.clone(), min + (max - min) * ks[i] as Float / 100.0)
+                })
+                .collect();
+            let out = rs.evaluate(&input, &UniformSampler::default()).unwrap();
+            for (name, y) in out {
+                let (min, max) = domains[&name];
+                let slack = 1e-3 * (max - min);
+                // NaN when no rule concluding on the output fired.
+                prop_assert!(y.is_nan() || (min - slack..=max + slack).contains(&y), "{name} = {y}");
+            }
+        }
+    }
+}
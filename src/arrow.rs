@@ -0,0 +1,292 @@
+// Arrow / Parquet batch evaluation (features `arrow` and `parquet`).
+//
+// Input variables are read from same-named numeric columns of a record batch and
+// the defuzzified outputs are appended as new columns, so the engine plugs into
+// columnar pipelines without intermediate CSV conversion.
+use std::{collections::HashMap, fmt, sync::Arc};
+
+use arrow_array::{types::ArrowPrimitiveType, Array, ArrayRef, PrimitiveArray, RecordBatch};
+use arrow_schema::{ArrowError, Field, Schema};
+
+use crate::{
+    error::{FuzzyError, MissingSpace},
+    rulespace::RuleSpace,
+    sampler::UniformSampler,
+    Float,
+};
+
+#[cfg(feature = "f32")]
+type FloatType = arrow_array::types::Float32Type;
+#[cfg(not(feature = "f32"))]
+type FloatType = arrow_array::types::Float64Type;
+
+/// Errors from Arrow/Parquet evaluation: either the engine or the I/O layer failed.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum BatchError {
+    Fuzzy(FuzzyError),
+    Arrow(ArrowError),
+    #[cfg(feature = "parquet")]
+    Parquet(parquet::errors::ParquetError),
+    Io(std::io::Error),
+}
+
+impl fmt::Display for BatchError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BatchError::Fuzzy(e) => write!(f, "{e}"),
+            BatchError::Arrow(e) => write!(f, "Arrow error: {e}"),
+            #[cfg(feature = "parquet")]
+            BatchError::Parquet(e) => write!(f, "Parquet error: {e}"),
+            BatchError::Io(e) => write!(f, "I/O error: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for BatchError {}
+
+impl From<FuzzyError> for BatchError {
+    fn from(e: FuzzyError) -> Self {
+        BatchError::Fuzzy(e)
+    }
+}
+
+impl From<ArrowError> for BatchError {
+    fn from(e: ArrowError) -> Self {
+        BatchError::Arrow(e)
+    }
+}
+
+#[cfg(feature = "parquet")]
+impl From<parquet::errors::ParquetError> for BatchError {
+    fn from(e: parquet::errors::ParquetError) -> Self {
+        BatchError::Parquet(e)
+    }
+}
+
+impl From<std::io::Error> for BatchError {
+    fn from(e: std::io::Error) -> Self {
+        BatchError::Io(e)
+    }
+}
+
+impl RuleSpace {
+    /// Evaluate every row of `batch` and return it with one column per output appended.
+    ///
+    /// Each input variable is read from the column of the same name, cast to `Float`.
+    /// Missing columns fail with `NotFound`, nulls with `TypeMismatch`, and rule
+    /// bases with crisp consequents as in `evaluate_columns`. An existing column
+    /// named like an output is replaced.
+    pub fn evaluate_record_batch(
+        &self,
+        batch: &RecordBatch,
+        sampler: &UniformSampler,
+    ) -> Result<RecordBatch, BatchError> {
+        self.require_term_consequents()?;
+        let mut columns: HashMap<&str, PrimitiveArray<FloatType>> = HashMap::new();
+        for name in self.inputs() {
+            let col = batch
+                .column_by_name(name)
+                .ok_or_else(|| FuzzyError::NotFound {
+                    space: MissingSpace::Input,
+                    key: name.to_string(),
+                })?;
+            let col = arrow_cast::cast(col, &FloatType::DATA_TYPE)?;
+            if col.null_count() > 0 {
+                return Err(FuzzyError::TypeMismatch.into());
+            }
+            let col = col
+                .as_any()
+                .downcast_ref::<PrimitiveArray<FloatType>>()
+                .ok_or(FuzzyError::TypeMismatch)?
+                .clone();
+            columns.insert(name, col);
+        }
+        let inputs: HashMap<&str, &[Float]> = columns
+            .iter()
+            .map(|(&k, v)| (k, v.values().as_ref()))
+            .collect();
+
+        let rows = batch.num_rows();
+        let names: Vec<&str> = self.outputs().collect();
+        let mut outputs = vec![vec![0.0; rows]; names.len()];
+        let mut slots: Vec<&mut [Float]> = outputs.iter_mut().map(Vec::as_mut_slice).collect();
+        self.evaluate_columns(&inputs, sampler, &mut slots)?;
+
+        let mut fields: Vec<Arc<Field>> = Vec::new();
+        let mut arrays: Vec<ArrayRef> = Vec::new();
+        for (field, array) in batch.schema().fields().iter().zip(batch.columns()) {
+            if !names.contains(&field.name().as_str()) {
+                fields.push(field.clone());
+                arrays.push(array.clone());
+            }
+        }
+        for (name, values) in names.iter().zip(outputs) {
+            fields.push(Arc::new(Field::new(*name, FloatType::DATA_TYPE, false)));
+            arrays.push(Arc::new(PrimitiveArray::<FloatType>::from(values)));
+        }
+        Ok(RecordBatch::try_new(Arc::new(Schema::new(fields)), arrays)?)
+    }
+
+    /// Evaluate every record batch of a Parquet file and write the result to `output`.
+    ///
+    /// The output schema is the input's with the output columns appended, as in
+    /// `evaluate_record_batch`; an input without rows still produces a file with
+    /// that schema and no row groups. Returns the number of rows written.
+    #[cfg(feature = "parquet")]
+    pub fn evaluate_parquet(
+        &self,
+        input: &std::path::Path,
+        output: &std::path::Path,
+        sampler: &UniformSampler,
+    ) -> Result<usize, BatchError> {
+        use parquet::arrow::{arrow_reader::ParquetRecordBatchReaderBuilder, ArrowWriter};
+
+        self.require_term_consequents()?;
+        let builder = ParquetRecordBatchReaderBuilder::try_new(std::fs::File::open(input)?)?;
+        let empty = RecordBatch::new_empty(builder.schema().clone());
+        let schema = self.evaluate_record_batch(&empty, sampler)?.schema();
+        let reader = builder.build()?;
+        let mut writer = ArrowWriter::try_new(std::fs::File::create(output)?, schema, None)?;
+        let mut rows = 0;
+        for batch in reader {
+            let out = self.evaluate_record_batch(&batch?, sampler)?;
+            writer.write(&out)?;
+            rows += out.num_rows();
+        }
+        writer.close()?;
+        Ok(rows)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashMap, sync::Arc};
+
+    use arrow_array::{Array, Float64Array, Int32Array, RecordBatch};
+
+    use crate::prelude::*;
+    use crate::test_support::space;
+
+    #[test]
+    fn record_batch_gains_output_columns() {
+        let rs = space();
+        let sampler = UniformSampler::default();
+        // Integer input column is cast to Float.
+        let batch = RecordBatch::try_from_iter([
+            ("id", Arc::new(Int32Array::from(vec![1, 2, 3])) as _),
+            ("temp", Arc::new(Int32Array::from(vec![1, 5, 9])) as _),
+        ])
+        .unwrap();
+        let out = rs.evaluate_record_batch(&batch, &sampler).unwrap();
+        assert_eq!(out.num_columns(), 3);
+
+        let fan = out
+            .column_by_name("fan")
+            .unwrap()
+            .as_any()
+            .downcast_ref::<Float64Array>()
+            .unwrap();
+        let expected = rs
+            .evaluate(&HashMap::from([("temp", 9.0)]), &sampler)
+            .unwrap();
+        assert!((fan.value(2) - expected["fan"]).abs() < 1e-9);
+        assert_eq!(fan.len(), 3);
+    }
+
+    #[cfg(feature = "parquet")]
+    #[test]
+    fn parquet_file_round_trip() {
+        use parquet::arrow::{arrow_reader::ParquetRecordBatchReaderBuilder, ArrowWriter};
+
+        let dir = std::env::temp_dir();
+        let input = dir.join(format!("fuzzy-in-{}.parquet", std::process::id()));
+        let output = dir.join(format!("fuzzy-out-{}.parquet", std::process::id()));
+
+        let batch = RecordBatch::try_from_iter([(
+            "temp",
+            Arc::new(Float64Array::from(vec![0.5, 2.0, 7.5, 9.0])) as _,
+        )])
+        .unwrap();
+        let mut w =
+            ArrowWriter::try_new(std::fs::File::create(&input).unwrap(), batch.schema(), None)
+                .unwrap();
+        w.write(&batch).unwrap();
+        w.close().unwrap();
+
+        let rows = space()
+            .evaluate_parquet(&input, &output, &UniformSampler::default())
+            .unwrap();
+        assert_eq!(rows, 4);
+
+        let read: Vec<RecordBatch> =
+            ParquetRecordBatchReaderBuilder::try_new(std::fs::File::open(&output).unwrap())
+                .unwrap()
+                .build()
+                .unwrap()
+                .collect::<std::result::Result<_, _>>()
+                .unwrap();
+        assert!(read[0].column_by_name("fan").is_some());
+
+        let _ = std::fs::remove_file(input);
+        let _ = std::fs::remove_file(output);
+    }
+
+    #[cfg(feature = "parquet")]
+    #[test]
+    fn empty_parquet_file_keeps_the_schema() {
+        use arrow_schema::{DataType, Field, Schema};
+        use parquet::{
+            arrow::{arrow_reader::ParquetRecordBatchReaderBuilder, ArrowWriter},
+            file::reader::{FileReader, SerializedFileReader},
+        };
+
+        let dir = std::env::temp_dir();
+        let input = dir.join(format!("fuzzy-empty-in-{}.parquet", std::process::id()));
+        let output = dir.join(format!("fuzzy-empty-out-{}.parquet", std::process::id()));
+
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("id", DataType::Int32, false),
+            Field::new("temp", DataType::Float64, false),
+        ]));
+        ArrowWriter::try_new(std::fs::File::create(&input).unwrap(), schema, None)
+            .unwrap()
+            .close()
+            .unwrap();
+
+        let rows = space()
+            .evaluate_parquet(&input, &output, &UniformSampler::default())
+            .unwrap();
+        assert_eq!(rows, 0);
+
+        let file = std::fs::File::open(&output).unwrap();
+        let meta = SerializedFileReader::new(file.try_clone().unwrap())
+            .unwrap()
+            .metadata()
+            .clone();
+        assert_eq!(meta.num_row_groups(), 0);
+        let written = ParquetRecordBatchReaderBuilder::try_new(file)
+            .unwrap()
+            .schema()
+            .clone();
+        let names: Vec<&str> = written.fields().iter().map(|f| f.name().as_str()).collect();
+        assert_eq!(names, ["id", "temp", "fan"]);
+
+        let _ = std::fs::remove_file(input);
+        let _ = std::fs::remove_file(output);
+    }
+
+    #[test]
+    fn crisp_consequents_are_rejected() {
+        let rs = crate::test_support::crisp_space();
+        let temps: Arc<dyn Array> = Arc::new(Float64Array::from(vec![2.0, 8.0]));
+        let batch = RecordBatch::try_from_iter([("temp", temps)]).unwrap();
+        let err = rs
+            .evaluate_record_batch(&batch, &UniformSampler::default())
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            super::BatchError::Fuzzy(ref e) if crate::test_support::rejects_crisp(e)
+        ));
+    }
+}
@@ -0,0 +1,272 @@
+// Per-rule attribution of defuzzified outputs.
+//
+// Explainability dashboards want to answer "which rules produced this value".
+// Two complementary measures are reported for every rule concluding on an
+// output:
+//
+// - leave-one-out: the output is re-aggregated without the rule and the change
+//   in the crisp value is recorded, which captures how far the rule pulls it;
+// - area share: every grid point of the aggregated set is credited to the rule
+//   whose clipped set reaches the maximum there, and the credited area is
+//   divided by the total. Crisp outputs use the rule's share of the summed
+//   firing strengths instead. Shares of one output add up to one whenever
+//   anything fired.
+use std::{borrow::Borrow, collections::HashMap, hash::Hash};
+
+use crate::{
+    error,
+    intern::VarId,
+    rulespace::RuleSpace,
+    sampler::{GridCache, UniformSampler},
+    system::System,
+    Float,
+};
+
+/// How much one rule contributed to one output.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RuleContribution {
+    /// Index of the rule in `RuleSpace::rules`.
+    pub rule: usize,
+    pub firing: Float,
+    /// Output value minus the value re-aggregated without this rule; NaN when
+    /// the output is undefined without it (no other rule fired).
+    pub delta: Float,
+    /// Fraction of the aggregated area (or of the summed firing strength, for
+    /// crisp outputs) credited to this rule.
+    pub share: Float,
+}
+
+impl RuleSpace {
+    /// Attributes every output of `input` to the rules concluding on it.
+    ///
+    /// The lists are keyed by output name and ranked by `share`, largest
+    /// first, ties in rule order. Costs one re-aggregation per rule and
+    /// output; fails in the same cases as `evaluate`.
+    pub fn attribution<KI>(
+        &self,
+        input: &HashMap<KI, Float>,
+        sampler: &UniformSampler,
+    ) -> error::Result<HashMap<String, Vec<RuleContribution>>>
+    where
+        KI: Eq + Hash + Borrow<str>,
+    {
+        let mut grids = GridCache::new();
+        let table = self.grid_table(sampler, &mut grids)?;
+        let mut inputs = Vec::with_capacity(self.vars.len());
+        self.resolve_inputs_into(input, &mut inputs);
+        let alphas = self.activations(input)?;
+
+        let mut full = Vec::new();
+        let mut partial = Vec::new();
+        let mut scratch = Vec::new();
+        let mut out = HashMap::with_capacity(self.outputs.len());
+        for &v in &self.outputs {
+            let xs = table[v.index()];
+            let concluding: Vec<usize> = (0..self.rules.len())
+                .filter(|&i| self.concludes(i, v))
+                .collect();
+            self.aggregate_rules(v, &alphas, &inputs, &table, &mut full, &mut scratch, |_| {
+                true
+            })?;
+            let value = self.output_value(v, xs, &full[v.index()]);
+
+            // Clipped set (or crisp accumulator) of each concluding rule alone.
+            let mut singles = Vec::with_capacity(concluding.len());
+            let mut contributions = Vec::with_capacity(concluding.len());
+            for &i in &concluding {
+                self.aggregate_rules(
+                    v,
+                    &alphas,
+                    &inputs,
+                    &table,
+                    &mut partial,
+                    &mut scratch,
+                    |j| j != i,
+                )?;
+                let without = self.output_value(v, xs, &partial[v.index()]);
+                self.aggregate_rules(
+                    v,
+                    &alphas,
+                    &inputs,
+                    &table,
+                    &mut partial,
+                    &mut scratch,
+                    |j| j == i,
+                )?;
+                singles.push(std::mem::take(&mut partial[v.index()]));
+                contributions.push(RuleContribution {
+                    rule: i,
+                    firing: alphas[i],
+                    delta: value - without,
+                    share: 0.0,
+                });
+            }
+
+            let mu = &full[v.index()];
+            if self.crisp[v.index()] {
+                for (c, single) in contributions.iter_mut().zip(&singles) {
+                    c.share = ratio(single[1], mu[1]);
+                }
+            } else {
+                let mut credit = vec![0.0; singles.len()];
+                for (p, &m) in mu.iter().enumerate() {
+                    if let Some(k) = singles.iter().position(|s| m > 0.0 && s[p] >= m) {
+                        credit[k] += m;
+                    }
+                }
+                let total: Float = mu.iter().sum();
+                for (c, credit) in contributions.iter_mut().zip(credit) {
+                    c.share = ratio(credit, total);
+                }
+            }
+            contributions.sort_by(|a, b| b.share.total_cmp(&a.share).then(a.rule.cmp(&b.rule)));
+            out.insert(self.var_name(v).to_string(), contributions);
+        }
+        Ok(out)
+    }
+
+    /// Whether rule `i` concludes on `v`, by term or crisp expression.
+    fn concludes(&self, i: usize, v: VarId) -> bool {
+        let c = &self.compiled[i];
+        c.consequent.iter().any(|&(u, _)| u == v) || c.crisp.iter().any(|k| k.var == v)
+    }
+
+    /// Aggregate output `v` into `agg` from the rules passing `include`, with
+    /// firing strengths `alphas` already computed.
+    #[allow(clippy::too_many_arguments)]
+    fn aggregate_rules(
+        &self,
+        v: VarId,
+        alphas: &[Float],
+        inputs: &[Option<Float>],
+        table: &[&[Float]],
+        agg: &mut Vec<Vec<Float>>,
+        scratch: &mut Vec<Float>,
+        include: impl Fn(usize) -> bool,
+    ) -> error::Result<()> {
+        self.reset_agg(table, agg);
+        for (i, (compiled, &alpha)) in self.compiled.iter().zip(alphas).enumerate() {
+            if include(i) {
+                compiled.implicate_into(alpha, &self.vars, table, agg, scratch, |u| u == v);
+                compiled.accumulate_crisp(alpha, inputs, &self.names, agg, |u| u == v)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl System {
+    /// Per-rule attribution of every output, see `RuleSpace::attribution`.
+    pub fn attribution<KI>(
+        &self,
+        input: &HashMap<KI, Float>,
+    ) -> error::Result<HashMap<String, Vec<RuleContribution>>>
+    where
+        KI: Eq + Hash + Borrow<str>,
+    {
+        self.space().attribution(input, self.sampler())
+    }
+}
+
+fn ratio(part: Float, total: Float) -> Float {
+    if total > 0.0 {
+        part / total
+    } else {
+        0.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::mamdani::CrispExpr;
+    use crate::prelude::*;
+
+    fn variable(max: Float, terms: &[&str]) -> Variable {
+        let step = max / (terms.len() - 1) as Float;
+        let mut var = Variable::new(0.0, max).unwrap();
+        for (i, name) in terms.iter().enumerate() {
+            let c = i as Float * step;
+            var.insert_term(
+                name,
+                Term::new(*name, Triangular::new(c - step, c, c + step).unwrap()),
+            )
+            .unwrap();
+        }
+        var
+    }
+
+    fn rule(temp: &'static str, fan: &'static str) -> Rule {
+        Rule::builder()
+            .when(atom("temp", temp))
+            .then("fan", fan)
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn contributions_are_ranked_and_shares_sum_to_one() {
+        let vars = HashMap::from([
+            ("temp".to_string(), variable(10.0, &["cold", "warm", "hot"])),
+            ("fan".to_string(), variable(100.0, &["low", "mid", "high"])),
+            ("power".to_string(), Variable::new(0.0, 10.0).unwrap()),
+        ]);
+        let rules = vec![
+            rule("cold", "low"),
+            rule("warm", "mid"),
+            rule("hot", "high"),
+            Rule::builder()
+                .when(atom("temp", "warm"))
+                .then_crisp("power", CrispExpr::constant(2.0))
+                .build()
+                .unwrap(),
+            Rule::builder()
+                .when(atom("temp", "hot"))
+                .then_crisp("power", CrispExpr::constant(8.0))
+                .build()
+                .unwrap(),
+        ];
+        let rs = RuleSpace::new(vars, rules).unwrap();
+        let sampler = UniformSampler::new(201).unwrap();
+        let input = HashMap::from([("temp", 7.0)]);
+        let out = rs.attribution(&input, &sampler).unwrap();
+        let value = rs.evaluate(&input, &sampler).unwrap();
+
+        let fan = &out["fan"];
+        let order: Vec<usize> = fan.iter().map(|c| c.rule).collect();
+        assert_eq!(order, [1, 2, 0]);
+        assert!(fan[0].share > fan[1].share);
+        assert_eq!(fan[2].share, 0.0);
+        assert_eq!(fan[2].delta, 0.0);
+        let total: Float = fan.iter().map(|c| c.share).sum();
+        assert!((total - 1.0).abs() < 1e-4);
+        // Dropping "hot -> high" lowers the fan speed, dropping "warm -> mid" raises it.
+        assert!(fan[1].delta > 0.0 && fan[0].delta < 0.0);
+
+        // Crisp shares are the firing-strength weights; the weighted average
+        // without one rule is the other rule's constant.
+        let power = &out["power"];
+        assert_eq!(power[0].rule, 3);
+        assert!((power[0].share - 0.6).abs() < 1e-4);
+        assert!((power[1].share - 0.4).abs() < 1e-4);
+        assert!((power[0].delta - (value["power"] - 8.0)).abs() < 1e-4);
+    }
+
+    #[test]
+    fn sole_rule_has_undefined_delta() {
+        let vars = HashMap::from([
+            ("temp".to_string(), variable(10.0, &["cold", "warm", "hot"])),
+            ("fan".to_string(), variable(100.0, &["low", "mid", "high"])),
+        ]);
+        let rs = RuleSpace::new(vars, vec![rule("cold", "low"), rule("hot", "high")]).unwrap();
+        let system = System::new(rs, UniformSampler::default()).unwrap();
+        let out = system.attribution(&HashMap::from([("temp", 1.0)])).unwrap();
+        let fan = &out["fan"];
+        assert_eq!(fan[0].rule, 0);
+        assert!((fan[0].share - 1.0).abs() < 1e-6);
+        assert!(fan[0].delta.is_nan());
+        assert_eq!(fan[1].firing, 0.0);
+    }
+}
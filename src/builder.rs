@@ -1 +1,125 @@
+// Fluent assembly of a `System`.
+//
+// `RuleSpace::new` wants a finished map of variables and a list of rules, which
+// makes even small controllers verbose to set up. `SystemBuilder` collects the
+// variables, their terms and the rules in one chain and, like `RuleBuilder`,
+// reports the first mistake from `build`.
+use std::collections::HashMap;
 
+use crate::{
+    error::{self, FuzzyError, MissingSpace},
+    mamdani::Rule,
+    membership::MembershipFn,
+    rulespace::RuleSpace,
+    sampler::UniformSampler,
+    system::System,
+    term::Term,
+    variable::Variable,
+    Float,
+};
+
+/// Builder for a `System`, started with `System::builder()`.
+///
+/// Errors are deferred: the first failing call is remembered and later calls
+/// are ignored until `build` returns it.
+#[derive(Default)]
+pub struct SystemBuilder {
+    vars: Vec<(String, Variable)>,
+    rules: Vec<Rule>,
+    sampler: UniformSampler,
+    error: Option<FuzzyError>,
+}
+
+impl SystemBuilder {
+    /// Declare the variable `name` over the domain `[min, max]`.
+    ///
+    /// Fails like `Variable::new`, and with `TypeMismatch` for a repeated name.
+    pub fn variable(mut self, name: impl Into<String>, min: Float, max: Float) -> Self {
+        let name = name.into();
+        if self.vars.iter().any(|(n, _)| *n == name) {
+            return self.fail(FuzzyError::TypeMismatch);
+        }
+        match Variable::new(min, max) {
+            Ok(var) => {
+                let var = var.with_name(name.clone());
+                self.vars.push((name, var))
+            }
+            Err(e) => return self.fail(e),
+        }
+        self
+    }
+
+    /// Add the term `name` shaped by `mf` to the declared variable `var`.
+    ///
+    /// Fails with `NotFound` for an undeclared variable and like
+    /// `Variable::insert_term` otherwise.
+    pub fn term<M>(mut self, var: &str, name: &str, mf: M) -> Self
+    where
+        M: MembershipFn + Send + Sync + 'static,
+    {
+        if self.error.is_some() {
+            return self;
+        }
+        let Some((_, v)) = self.vars.iter_mut().find(|(n, _)| n == var) else {
+            return self.fail(FuzzyError::NotFound {
+                space: MissingSpace::Var,
+                key: var.to_string(),
+            });
+        };
+        match v.insert_term(name, Term::new(name, mf)) {
+            Ok(()) => self,
+            Err(e) => self.fail(e),
+        }
+    }
+
+    /// Sum-normalize the term degrees of the declared variable `var`, see
+    /// `Variable::set_normalized`.
+    pub fn normalized(mut self, var: &str) -> Self {
+        match self.vars.iter_mut().find(|(n, _)| n == var) {
+            Some((_, v)) => {
+                v.set_normalized(true);
+                self
+            }
+            None => self.fail(FuzzyError::NotFound {
+                space: MissingSpace::Var,
+                key: var.to_string(),
+            }),
+        }
+    }
+
+    /// Append a rule; its names are checked by `build`.
+    pub fn rule(mut self, rule: Rule) -> Self {
+        self.rules.push(rule);
+        self
+    }
+
+    /// Append several rules, e.g. from `fam::fam_rules` or `fam::expand_rules`.
+    pub fn rules(mut self, rules: impl IntoIterator<Item = Rule>) -> Self {
+        self.rules.extend(rules);
+        self
+    }
+
+    /// Sample output domains with `sampler` (`UniformSampler::default()` otherwise).
+    pub fn sampler(mut self, sampler: UniformSampler) -> Self {
+        self.sampler = sampler;
+        self
+    }
+
+    /// Finish the system.
+    ///
+    /// Returns the first error recorded while building, then fails like
+    /// `RuleSpace::new` (e.g. `EmptyInput` without rules, `InRule` for a rule
+    /// naming an unknown variable or term).
+    pub fn build(self) -> error::Result<System> {
+        if let Some(e) = self.error {
+            return Err(e);
+        }
+        let vars: HashMap<String, Variable> = self.vars.into_iter().collect();
+        System::new(RuleSpace::new(vars, self.rules)?, self.sampler)
+    }
+
+    fn fail(mut self, error: FuzzyError) -> Self {
+        self.error.get_or_insert(error);
+        self
+    }
+}
@@ -0,0 +1,202 @@
+// Result caching for repeated inputs.
+//
+// Simulations and lookup-heavy callers often evaluate the same few thousand
+// points over and over. `CachedSpace` owns a rule space and remembers the
+// outputs of every input vector it has seen, keyed by the inputs quantized to a
+// configurable precision, so a repeat costs one hash lookup instead of a full
+// aggregation and defuzzification.
+use std::{borrow::Borrow, collections::HashMap, hash::Hash};
+
+use crate::{
+    error::{self, FuzzyError},
+    rulespace::{RuleSpace, Scratch},
+    sampler::UniformSampler,
+    Float,
+};
+
+/// A rule space with a cache of evaluated outputs.
+///
+/// Inputs that quantize to the same grid cell (`round(x / precision)` per
+/// input) share one entry: a hit returns the outputs computed for the first
+/// input seen in that cell. Use a precision at or below the resolution your
+/// inputs actually take, e.g. the spacing of a simulation grid.
+pub struct CachedSpace {
+    space: RuleSpace,
+    scratch: Scratch,
+    precision: Float,
+    max_entries: Option<usize>,
+    inputs: Vec<String>,
+    outputs: Vec<String>,
+    entries: HashMap<Vec<i64>, Vec<Float>>,
+    key: Vec<i64>,
+    result: HashMap<String, Float>,
+    hits: u64,
+    misses: u64,
+}
+
+impl CachedSpace {
+    /// Wraps `space`; fails with `OutOfBounds` unless `precision` is positive.
+    pub fn new(
+        space: RuleSpace,
+        sampler: &UniformSampler,
+        precision: Float,
+    ) -> error::Result<Self> {
+        if precision.is_nan() || precision <= 0.0 {
+            return Err(FuzzyError::OutOfBounds);
+        }
+        Ok(Self {
+            scratch: space.scratch(sampler)?,
+            inputs: space.inputs().map(str::to_string).collect(),
+            outputs: space.outputs().map(str::to_string).collect(),
+            space,
+            precision,
+            max_entries: None,
+            entries: HashMap::new(),
+            key: Vec::new(),
+            result: HashMap::new(),
+            hits: 0,
+            misses: 0,
+        })
+    }
+
+    /// Bounds the cache to `max_entries`; when a new entry would exceed it the
+    /// cache is cleared first.
+    pub fn with_max_entries(mut self, max_entries: usize) -> Self {
+        self.max_entries = Some(max_entries);
+        self
+    }
+
+    /// Evaluates `input`, from the cache when its quantized inputs were seen
+    /// before. Inputs with a missing or non-finite value bypass the cache (and
+    /// fail as `evaluate_in` would).
+    pub fn evaluate<KI>(
+        &mut self,
+        input: &HashMap<KI, Float>,
+    ) -> error::Result<&HashMap<String, Float>>
+    where
+        KI: Eq + Hash + Borrow<str>,
+    {
+        self.key.clear();
+        for name in &self.inputs {
+            match input.get(name.as_str()) {
+                Some(&x) if x.is_finite() => self.key.push((x / self.precision).round() as i64),
+                _ => {
+                    self.misses += 1;
+                    return self.space.evaluate_in(input, &mut self.scratch);
+                }
+            }
+        }
+
+        if let Some(ys) = self.entries.get(&self.key) {
+            self.hits += 1;
+            fill(&mut self.result, &self.outputs, ys);
+            return Ok(&self.result);
+        }
+        self.misses += 1;
+        let out = self.space.evaluate_in(input, &mut self.scratch)?;
+        let ys: Vec<Float> = self.outputs.iter().map(|o| out[o]).collect();
+        fill(&mut self.result, &self.outputs, &ys);
+        if self
+            .max_entries
+            .is_some_and(|max| self.entries.len() >= max)
+        {
+            self.entries.clear();
+        }
+        self.entries.insert(self.key.clone(), ys);
+        Ok(&self.result)
+    }
+
+    /// Lookups answered from the cache.
+    pub fn hits(&self) -> u64 {
+        self.hits
+    }
+
+    /// Lookups that evaluated the rule space.
+    pub fn misses(&self) -> u64 {
+        self.misses
+    }
+
+    /// Number of cached input cells.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Drops every cached entry and zeroes the hit and miss counts.
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.hits = 0;
+        self.misses = 0;
+    }
+
+    pub fn precision(&self) -> Float {
+        self.precision
+    }
+
+    /// The wrapped rule space.
+    pub fn space(&self) -> &RuleSpace {
+        &self.space
+    }
+
+    /// Unwraps the rule space, dropping the cache.
+    pub fn into_inner(self) -> RuleSpace {
+        self.space
+    }
+}
+
+fn fill(result: &mut HashMap<String, Float>, names: &[String], ys: &[Float]) {
+    for (name, &y) in names.iter().zip(ys) {
+        match result.get_mut(name) {
+            Some(slot) => *slot = y,
+            None => {
+                result.insert(name.clone(), y);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::CachedSpace;
+    use crate::error::FuzzyError;
+    use crate::prelude::*;
+    use crate::test_support::space;
+
+    #[test]
+    fn repeated_inputs_hit_the_cache() {
+        let sampler = UniformSampler::default();
+        let mut cached = CachedSpace::new(space(), &sampler, 0.01).unwrap();
+        let direct = space()
+            .evaluate(&HashMap::from([("temp", 4.0)]), &sampler)
+            .unwrap();
+
+        for _ in 0..3 {
+            let out = cached.evaluate(&HashMap::from([("temp", 4.0)])).unwrap();
+            assert_eq!(out, &direct);
+        }
+        // Within the same cell: served from the cache.
+        cached.evaluate(&HashMap::from([("temp", 4.001)])).unwrap();
+        assert_eq!((cached.hits(), cached.misses(), cached.len()), (3, 1, 1));
+
+        cached.evaluate(&HashMap::from([("temp", 6.0)])).unwrap();
+        assert_eq!(cached.len(), 2);
+        let err = cached.evaluate(&HashMap::<&str, Float>::new()).unwrap_err();
+        assert!(matches!(err.root(), FuzzyError::NotFound { .. }));
+
+        let mut small = CachedSpace::new(space(), &sampler, 0.5)
+            .unwrap()
+            .with_max_entries(1);
+        small.evaluate(&HashMap::from([("temp", 1.0)])).unwrap();
+        small.evaluate(&HashMap::from([("temp", 2.0)])).unwrap();
+        assert_eq!(small.len(), 1);
+        assert!(matches!(
+            CachedSpace::new(space(), &sampler, 0.0),
+            Err(FuzzyError::OutOfBounds)
+        ));
+    }
+}
@@ -0,0 +1,288 @@
+// Classification output mode.
+//
+// When the rule base is a classifier rather than a controller, a centroid
+// between "benign" and "malicious" means nothing; what matters is which term
+// the rules support most. `RuleSpace::classify` fires the rules, takes each
+// output term's degree as the height of its aggregated set (the strongest rule
+// concluding on it) and reports the winning label with every term's degree.
+// No output grid is sampled and nothing is defuzzified.
+//
+// Multi-label decisions ("alert: high, and critical partially") keep every
+// term above a threshold instead of only the winner; see `labels_above`.
+use std::{
+    borrow::Borrow,
+    collections::{BTreeMap, HashMap},
+    hash::Hash,
+};
+
+use crate::{
+    error::{self, FuzzyError},
+    rulespace::RuleSpace,
+    system::System,
+    Float,
+};
+
+/// Classification of one output variable.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Classification {
+    /// Term with the greatest degree, the first declared one on ties; `None`
+    /// when no rule concluding on the output fired.
+    pub label: Option<String>,
+    /// Degree of every term of the output variable, keyed by term name.
+    pub degrees: BTreeMap<String, Float>,
+}
+
+impl Classification {
+    /// Degree of `term`, zero for a term the output does not have.
+    pub fn degree(&self, term: &str) -> Float {
+        self.degrees.get(term).copied().unwrap_or(0.0)
+    }
+
+    /// Terms whose degree exceeds `threshold`, strongest first (ties by name).
+    pub fn above(&self, threshold: Float) -> Vec<(&str, Float)> {
+        let mut labels: Vec<(&str, Float)> = self
+            .degrees
+            .iter()
+            .filter(|&(_, &d)| d > threshold)
+            .map(|(name, &d)| (name.as_str(), d))
+            .collect();
+        labels.sort_by(|a, b| b.1.total_cmp(&a.1));
+        labels
+    }
+}
+
+impl RuleSpace {
+    /// Classifies `input`: for each term-valued output, the label of the term
+    /// with the greatest aggregated membership and the degree of every term.
+    ///
+    /// A term's degree is the strongest firing strength among the rules
+    /// concluding on it, hedged or not, i.e. the height of its clipped set.
+    /// Outputs with crisp consequents have no terms and are not listed.
+    pub fn classify<KI>(
+        &self,
+        input: &HashMap<KI, Float>,
+    ) -> error::Result<HashMap<String, Classification>>
+    where
+        KI: Eq + Hash + Borrow<str>,
+    {
+        let alphas = self.activations(input)?;
+        let mut heights: Vec<Vec<Float>> = self
+            .vars
+            .iter()
+            .map(|v| vec![0.0; v.terms().count()])
+            .collect();
+        for (compiled, &alpha) in self.compiled.iter().zip(&alphas) {
+            for &(v, t) in &compiled.consequent {
+                let h = &mut heights[v.index()][t.index()];
+                *h = h.max(alpha);
+            }
+        }
+
+        Ok(self
+            .outputs
+            .iter()
+            .filter(|v| !self.crisp[v.index()])
+            .map(|&v| {
+                let var = &self.vars[v.index()];
+                let mut label = None;
+                let mut best = 0.0;
+                let mut degrees = BTreeMap::new();
+                for ((name, _), &h) in var.terms().zip(&heights[v.index()]) {
+                    if h > best {
+                        best = h;
+                        label = Some(name.to_string());
+                    }
+                    degrees.insert(name.to_string(), h);
+                }
+                let class = Classification { label, degrees };
+                (self.var_name(v).to_string(), class)
+            })
+            .collect())
+    }
+
+    /// Multi-label classification of `input`: for each term-valued output, the
+    /// terms whose aggregated height exceeds `threshold`, strongest first.
+    ///
+    /// Degrees are those of `classify`. Fails with `OutOfBounds` unless
+    /// `threshold` lies in `[0, 1)`.
+    pub fn labels_above<KI>(
+        &self,
+        input: &HashMap<KI, Float>,
+        threshold: Float,
+    ) -> error::Result<HashMap<String, Vec<(String, Float)>>>
+    where
+        KI: Eq + Hash + Borrow<str>,
+    {
+        if !(0.0..1.0).contains(&threshold) {
+            return Err(FuzzyError::OutOfBounds);
+        }
+        Ok(self
+            .classify(input)?
+            .into_iter()
+            .map(|(var, class)| {
+                let labels = class
+                    .above(threshold)
+                    .into_iter()
+                    .map(|(name, d)| (name.to_string(), d))
+                    .collect();
+                (var, labels)
+            })
+            .collect())
+    }
+}
+
+impl System {
+    /// Classifies `input`, see `RuleSpace::classify`.
+    pub fn classify<KI>(
+        &self,
+        input: &HashMap<KI, Float>,
+    ) -> error::Result<HashMap<String, Classification>>
+    where
+        KI: Eq + Hash + Borrow<str>,
+    {
+        self.space().classify(input)
+    }
+
+    /// Terms above `threshold` per output, see `RuleSpace::labels_above`.
+    pub fn labels_above<KI>(
+        &self,
+        input: &HashMap<KI, Float>,
+        threshold: Float,
+    ) -> error::Result<HashMap<String, Vec<(String, Float)>>>
+    where
+        KI: Eq + Hash + Borrow<str>,
+    {
+        self.space().labels_above(input, threshold)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::error::FuzzyError;
+    use crate::mamdani::CrispExpr;
+    use crate::prelude::*;
+
+    fn variable(max: Float, terms: &[&str]) -> Variable {
+        let step = max / (terms.len() - 1) as Float;
+        let mut var = Variable::new(0.0, max).unwrap();
+        for (i, name) in terms.iter().enumerate() {
+            let c = i as Float * step;
+            var.insert_term(
+                name,
+                Term::new(*name, Triangular::new(c - step, c, c + step).unwrap()),
+            )
+            .unwrap();
+        }
+        var
+    }
+
+    fn space() -> RuleSpace {
+        let rule = |load: &'static str, risk: &'static str| {
+            Rule::builder()
+                .when(atom("load", load))
+                .then("risk", risk)
+                .build()
+                .unwrap()
+        };
+        let vars = HashMap::from([
+            (
+                "load".to_string(),
+                variable(10.0, &["idle", "busy", "full"]),
+            ),
+            (
+                "risk".to_string(),
+                variable(1.0, &["low", "high", "critical"]),
+            ),
+        ]);
+        let rules = vec![
+            rule("idle", "low"),
+            rule("busy", "high"),
+            rule("full", "critical"),
+        ];
+        RuleSpace::new(vars, rules).unwrap()
+    }
+
+    #[test]
+    fn classify_picks_the_strongest_term() {
+        let rs = space();
+        let out = rs.classify(&HashMap::from([("load", 7.0)])).unwrap();
+        let risk = &out["risk"];
+        assert_eq!(risk.label.as_deref(), Some("high"));
+        assert!((risk.degree("high") - 0.6).abs() < 1e-6);
+        assert!((risk.degree("critical") - 0.4).abs() < 1e-6);
+        assert_eq!(risk.degree("low"), 0.0);
+        assert_eq!(risk.degrees.len(), 3);
+        assert_eq!(risk.degree("missing"), 0.0);
+        assert!(!out.contains_key("load"));
+
+        // Ties go to the first declared term.
+        let out = rs.classify(&HashMap::from([("load", 2.5)])).unwrap();
+        assert_eq!(out["risk"].label.as_deref(), Some("low"));
+
+        assert!(matches!(
+            rs.classify(&HashMap::<&str, Float>::new()),
+            Err(FuzzyError::NotFound { .. })
+        ));
+    }
+
+    #[test]
+    fn labels_above_threshold_are_ranked() {
+        let rs = space();
+        let input = HashMap::from([("load", 7.0)]);
+        let out = rs.labels_above(&input, 0.3).unwrap();
+        let names: Vec<&str> = out["risk"].iter().map(|(n, _)| n.as_str()).collect();
+        assert_eq!(names, ["high", "critical"]);
+        assert!((out["risk"][1].1 - 0.4).abs() < 1e-6);
+
+        let out = rs.labels_above(&input, 0.5).unwrap();
+        assert_eq!(out["risk"].len(), 1);
+        let out = rs.labels_above(&input, 0.0).unwrap();
+        assert_eq!(out["risk"].len(), 2);
+
+        // Equal degrees are listed by name.
+        let class = &rs.classify(&HashMap::from([("load", 2.5)])).unwrap()["risk"];
+        assert_eq!(class.above(0.0), [("high", 0.5), ("low", 0.5)]);
+
+        for bad in [-0.1, 1.0, Float::NAN] {
+            assert!(matches!(
+                rs.labels_above(&input, bad),
+                Err(FuzzyError::OutOfBounds)
+            ));
+        }
+    }
+
+    #[test]
+    fn classify_skips_crisp_outputs_and_silent_rules() {
+        let vars = HashMap::from([
+            (
+                "load".to_string(),
+                variable(10.0, &["idle", "busy", "full"]),
+            ),
+            (
+                "risk".to_string(),
+                variable(1.0, &["low", "high", "critical"]),
+            ),
+            ("score".to_string(), Variable::new(0.0, 1.0).unwrap()),
+        ]);
+        let rules = vec![
+            Rule::builder()
+                .when(atom("load", "full"))
+                .then("risk", "critical")
+                .build()
+                .unwrap(),
+            Rule::builder()
+                .when(atom("load", "busy"))
+                .then_crisp("score", CrispExpr::constant(1.0))
+                .build()
+                .unwrap(),
+        ];
+        let rs = RuleSpace::new(vars, rules).unwrap();
+        let out = rs.classify(&HashMap::from([("load", 0.0)])).unwrap();
+        assert_eq!(out.len(), 1);
+        assert_eq!(out["risk"].label, None);
+        assert!(out["risk"].degrees.values().all(|&d| d == 0.0));
+    }
+}
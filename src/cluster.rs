@@ -0,0 +1,464 @@
+// Clustering for data-driven fuzzification.
+//
+// Fuzzy c-means partitions a point cloud into overlapping clusters; projecting the
+// clusters onto one dimension yields membership functions for the matching
+// `Variable`, so term design can start from data instead of guesswork.
+// Subtractive clustering picks the number of clusters itself, which makes it the
+// usual way to propose an initial rule base for `Anfis`.
+use crate::{
+    error::{FuzzyError, Result},
+    membership::{Gaussian, Triangular},
+    term::Term,
+    variable::Variable,
+    Float,
+};
+
+/// Settings for `fuzzy_c_means`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FcmOptions {
+    /// Number of clusters (at least 2, at most the number of points).
+    pub clusters: usize,
+    /// Fuzziness exponent `m > 1`; larger values give softer partitions.
+    pub fuzziness: Float,
+    /// Iteration cap.
+    pub max_iter: usize,
+    /// Stop once no membership changes by more than this between iterations.
+    pub tolerance: Float,
+}
+
+impl Default for FcmOptions {
+    fn default() -> Self {
+        Self {
+            clusters: 3,
+            fuzziness: 2.0,
+            max_iter: 300,
+            tolerance: 1e-5,
+        }
+    }
+}
+
+/// Membership function family used when turning clusters into terms.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TermShape {
+    /// Centered on the cluster, width from the membership-weighted spread.
+    Gaussian,
+    /// Apex on the cluster, feet on the neighbouring centers (a Ruspini partition).
+    Triangular,
+}
+
+/// Result of `fuzzy_c_means`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Fcm {
+    /// Cluster centers, one point per cluster.
+    pub centers: Vec<Vec<Float>>,
+    /// `memberships[i][k]`: degree of point `i` in cluster `k`; each row sums to 1.
+    pub memberships: Vec<Vec<Float>>,
+    /// Iterations run before converging or hitting `max_iter`.
+    pub iterations: usize,
+    fuzziness: Float,
+}
+
+/// Clusters `data` (one row per point) with fuzzy c-means.
+///
+/// Centers are seeded deterministically by farthest-point selection, so the same
+/// data always yields the same partition. Ragged rows fail with `BadArity`, empty
+/// data with `EmptyInput`, and invalid options with `OutOfBounds`.
+pub fn fuzzy_c_means(data: &[Vec<Float>], opts: FcmOptions) -> Result<Fcm> {
+    let dims = check_points(data)?;
+    let c = opts.clusters;
+    if c < 2 || c > data.len() || opts.fuzziness <= 1.0 {
+        return Err(FuzzyError::OutOfBounds);
+    }
+
+    let mut centers = seed_centers(data, c);
+    let mut u = vec![vec![0.0; c]; data.len()];
+    let exp = 2.0 / (opts.fuzziness - 1.0);
+    let mut iterations = 0;
+
+    while iterations < opts.max_iter {
+        iterations += 1;
+        let mut delta: Float = 0.0;
+        for (x, row) in data.iter().zip(&mut u) {
+            let d: Vec<Float> = centers.iter().map(|ck| distance(x, ck)).collect();
+            let next: Vec<Float> = match d.iter().position(|&dk| dk == 0.0) {
+                // A point sitting on a center belongs to it alone.
+                Some(hit) => (0..c).map(|k| if k == hit { 1.0 } else { 0.0 }).collect(),
+                None => d
+                    .iter()
+                    .map(|&dk| 1.0 / d.iter().map(|&dj| (dk / dj).powf(exp)).sum::<Float>())
+                    .collect(),
+            };
+            for (old, new) in row.iter_mut().zip(next) {
+                delta = delta.max((*old - new).abs());
+                *old = new;
+            }
+        }
+
+        for (k, center) in centers.iter_mut().enumerate() {
+            let mut weight = 0.0;
+            let mut acc = vec![0.0; dims];
+            for (x, row) in data.iter().zip(&u) {
+                let w = row[k].powf(opts.fuzziness);
+                weight += w;
+                for (a, xi) in acc.iter_mut().zip(x) {
+                    *a += w * xi;
+                }
+            }
+            if weight > 0.0 {
+                *center = acc.into_iter().map(|a| a / weight).collect();
+            }
+        }
+
+        if delta < opts.tolerance {
+            break;
+        }
+    }
+
+    Ok(Fcm {
+        centers,
+        memberships: u,
+        iterations,
+        fuzziness: opts.fuzziness,
+    })
+}
+
+impl Fcm {
+    /// Projects the clusters onto dimension `dim` of `data` and builds a variable
+    /// over `domain` with one term per cluster, in ascending center order.
+    ///
+    /// `names` labels the terms in that order and must hold one name per cluster.
+    /// `data` must be the points the clustering ran on.
+    pub fn variable(
+        &self,
+        data: &[Vec<Float>],
+        dim: usize,
+        shape: TermShape,
+        names: &[&str],
+        domain: (Float, Float),
+    ) -> Result<Variable> {
+        if names.len() != self.centers.len() || data.len() != self.memberships.len() {
+            return Err(FuzzyError::BadArity);
+        }
+        if self.centers.iter().any(|c| dim >= c.len()) {
+            return Err(FuzzyError::OutOfBounds);
+        }
+
+        let mut order: Vec<usize> = (0..self.centers.len()).collect();
+        order.sort_by(|&a, &b| self.centers[a][dim].total_cmp(&self.centers[b][dim]));
+        let apex: Vec<Float> = order.iter().map(|&k| self.centers[k][dim]).collect();
+
+        let mut var = Variable::new(domain.0, domain.1)?;
+        for (slot, (&k, name)) in order.iter().zip(names).enumerate() {
+            let term = match shape {
+                TermShape::Gaussian => Term::new(
+                    *name,
+                    Gaussian::with_mean_sd(apex[slot], self.spread(data, k, dim)?)?,
+                ),
+                TermShape::Triangular => {
+                    let (left, c, right) = triangle(&apex, slot);
+                    Term::new(*name, Triangular::new(left, c, right)?)
+                }
+            };
+            var.insert_term(name, term)?;
+        }
+        Ok(var)
+    }
+
+    /// Membership-weighted standard deviation of cluster `k` along `dim`.
+    fn spread(&self, data: &[Vec<Float>], k: usize, dim: usize) -> Result<Float> {
+        let center = self.centers[k][dim];
+        let (mut num, mut den) = (0.0, 0.0);
+        for (x, row) in data.iter().zip(&self.memberships) {
+            let w = row[k].powf(self.fuzziness);
+            let d = x.get(dim).ok_or(FuzzyError::BadArity)? - center;
+            num += w * d * d;
+            den += w;
+        }
+        Ok((num / den).sqrt())
+    }
+}
+
+/// Feet and apex of the triangle at `apex[slot]` in a partition where each
+/// triangle reaches its neighbours' apexes; edge terms mirror their only neighbour.
+/// `apex` must be ascending with at least two entries.
+pub(crate) fn triangle(apex: &[Float], slot: usize) -> (Float, Float, Float) {
+    let c = apex[slot];
+    let left = if slot > 0 {
+        apex[slot - 1]
+    } else {
+        2.0 * c - apex[1]
+    };
+    let right = match apex.get(slot + 1) {
+        Some(&r) => r,
+        None => 2.0 * c - apex[slot - 1],
+    };
+    (left, c, right)
+}
+
+/// Settings for `subtractive`. Radii are fractions of each dimension's data range.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SubtractiveOptions {
+    /// Neighbourhood radius `r_a` that contributes to a point's potential.
+    pub radius: Float,
+    /// Factor `r_b / r_a` for the potential removed around an accepted center.
+    pub squash: Float,
+    /// Potential ratio above which a candidate is always accepted.
+    pub accept: Float,
+    /// Potential ratio below which the search stops.
+    pub reject: Float,
+}
+
+impl Default for SubtractiveOptions {
+    fn default() -> Self {
+        Self {
+            radius: 0.5,
+            squash: 1.25,
+            accept: 0.5,
+            reject: 0.15,
+        }
+    }
+}
+
+/// Result of `subtractive`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Subtractive {
+    /// Cluster centers in data units, most potent first.
+    pub centers: Vec<Vec<Float>>,
+    /// Per-dimension Gaussian width matching the radius, `r_a · range / √8`.
+    pub sigmas: Vec<Float>,
+}
+
+/// Chiu's subtractive clustering: every point is a candidate center, scored by
+/// the density of its neighbourhood; accepted centers suppress the score of
+/// nearby points until the remaining potential is too low.
+///
+/// Data is scaled to the unit hypercube internally. Constant dimensions are left
+/// unscaled. Errors follow `fuzzy_c_means`.
+pub fn subtractive(data: &[Vec<Float>], opts: SubtractiveOptions) -> Result<Subtractive> {
+    let dims = check_points(data)?;
+    if opts.radius <= 0.0 || opts.squash <= 0.0 || opts.reject > opts.accept {
+        return Err(FuzzyError::OutOfBounds);
+    }
+
+    let (lo, range): (Vec<Float>, Vec<Float>) = (0..dims)
+        .map(|j| {
+            let (min, max) = data
+                .iter()
+                .fold((Float::INFINITY, Float::NEG_INFINITY), |(a, b), x| {
+                    (a.min(x[j]), b.max(x[j]))
+                });
+            (min, if max > min { max - min } else { 1.0 })
+        })
+        .unzip();
+    let unit: Vec<Vec<Float>> = data
+        .iter()
+        .map(|x| (0..dims).map(|j| (x[j] - lo[j]) / range[j]).collect())
+        .collect();
+
+    let alpha = 4.0 / (opts.radius * opts.radius);
+    let rb = opts.radius * opts.squash;
+    let beta = 4.0 / (rb * rb);
+    let sq = |a: &[Float], b: &[Float]| distance(a, b).powi(2);
+
+    let mut potential: Vec<Float> = unit
+        .iter()
+        .map(|x| unit.iter().map(|y| (-alpha * sq(x, y)).exp()).sum())
+        .collect();
+
+    let mut chosen: Vec<usize> = Vec::new();
+    let mut first = 0.0;
+    loop {
+        let (k, &pk) = potential
+            .iter()
+            .enumerate()
+            .max_by(|a, b| a.1.total_cmp(b.1))
+            .expect("data is not empty");
+        if chosen.is_empty() {
+            first = pk;
+        } else if pk <= opts.reject * first {
+            break;
+        } else if pk < opts.accept * first {
+            let dmin = chosen
+                .iter()
+                .map(|&c| distance(&unit[k], &unit[c]))
+                .fold(Float::INFINITY, Float::min);
+            if dmin / opts.radius + pk / first < 1.0 {
+                // Too weak and too close: drop this candidate and try the next one.
+                potential[k] = 0.0;
+                continue;
+            }
+        }
+        chosen.push(k);
+        for (p, y) in potential.iter_mut().zip(&unit) {
+            *p -= pk * (-beta * sq(&unit[k], y)).exp();
+        }
+        if chosen.len() == data.len() {
+            break;
+        }
+    }
+
+    Ok(Subtractive {
+        centers: chosen.iter().map(|&k| data[k].clone()).collect(),
+        sigmas: range
+            .iter()
+            .map(|r| opts.radius * r / (8.0 as Float).sqrt())
+            .collect(),
+    })
+}
+
+/// Validates a point cloud and returns its dimensionality.
+fn check_points(data: &[Vec<Float>]) -> Result<usize> {
+    let dims = data.first().ok_or(FuzzyError::EmptyInput)?.len();
+    if dims == 0 || data.iter().any(|x| x.len() != dims) {
+        return Err(FuzzyError::BadArity);
+    }
+    Ok(dims)
+}
+
+fn distance(a: &[Float], b: &[Float]) -> Float {
+    a.iter()
+        .zip(b)
+        .map(|(x, y)| (x - y) * (x - y))
+        .sum::<Float>()
+        .sqrt()
+}
+
+/// Farthest-point seeding: start at the point nearest the mean, then repeatedly
+/// take the point farthest from every center chosen so far.
+fn seed_centers(data: &[Vec<Float>], c: usize) -> Vec<Vec<Float>> {
+    let n = data.len() as Float;
+    let mean: Vec<Float> = (0..data[0].len())
+        .map(|j| data.iter().map(|x| x[j]).sum::<Float>() / n)
+        .collect();
+    let nearest = |target: &dyn Fn(&Vec<Float>) -> Float| {
+        data.iter()
+            .min_by(|a, b| target(a).total_cmp(&target(b)))
+            .expect("data is not empty")
+            .clone()
+    };
+
+    let mut centers = vec![nearest(&|x| distance(x, &mean))];
+    while centers.len() < c {
+        let next = nearest(&|x| {
+            -centers
+                .iter()
+                .map(|ck| distance(x, ck))
+                .fold(Float::INFINITY, Float::min)
+        });
+        centers.push(next);
+    }
+    centers
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::membership::MembershipFn;
+
+    fn blobs() -> Vec<Vec<Float>> {
+        let mut data = Vec::new();
+        for (cx, cy) in [(0.0, 0.0), (5.0, 5.0), (10.0, 0.0)] {
+            for i in 0..10 {
+                let t = i as Float / 10.0;
+                data.push(vec![cx + 0.3 * (t - 0.5), cy + 0.2 * (0.5 - t)]);
+            }
+        }
+        data
+    }
+
+    #[test]
+    fn finds_well_separated_clusters() {
+        let data = blobs();
+        let fcm = fuzzy_c_means(&data, FcmOptions::default()).unwrap();
+        let mut xs: Vec<Float> = fcm.centers.iter().map(|c| c[0]).collect();
+        xs.sort_by(Float::total_cmp);
+        for (got, want) in xs.iter().zip([0.0, 5.0, 10.0]) {
+            assert!((got - want).abs() < 0.1, "{xs:?}");
+        }
+        for row in &fcm.memberships {
+            assert!((row.iter().sum::<Float>() - 1.0).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn clusters_become_ordered_terms() {
+        let data = blobs();
+        let fcm = fuzzy_c_means(&data, FcmOptions::default()).unwrap();
+
+        let tri = fcm
+            .variable(
+                &data,
+                0,
+                TermShape::Triangular,
+                &["left", "mid", "right"],
+                (0.0, 10.0),
+            )
+            .unwrap();
+        assert!((tri.eval("left", 0.0).unwrap() - 1.0).abs() < 0.05);
+        assert!((tri.eval("mid", 5.0).unwrap() - 1.0).abs() < 0.05);
+        assert!((tri.eval("right", 10.0).unwrap() - 1.0).abs() < 0.05);
+
+        let gauss = fcm
+            .variable(
+                &data,
+                0,
+                TermShape::Gaussian,
+                &["left", "mid", "right"],
+                (0.0, 10.0),
+            )
+            .unwrap();
+        let mid = gauss.get("mid").unwrap();
+        assert!(mid.eval(5.0) > 0.95 && mid.eval(0.0) < 0.05);
+
+        assert_eq!(
+            fcm.variable(&data, 0, TermShape::Gaussian, &["a"], (0.0, 10.0))
+                .err(),
+            Some(FuzzyError::BadArity)
+        );
+    }
+
+    #[test]
+    fn subtractive_finds_the_number_of_clusters() {
+        let sc = subtractive(&blobs(), SubtractiveOptions::default()).unwrap();
+        assert_eq!(sc.centers.len(), 3, "{:?}", sc.centers);
+        let mut xs: Vec<Float> = sc.centers.iter().map(|c| c[0]).collect();
+        xs.sort_by(Float::total_cmp);
+        for (got, want) in xs.iter().zip([0.0, 5.0, 10.0]) {
+            assert!((got - want).abs() < 0.2, "{xs:?}");
+        }
+        assert_eq!(sc.sigmas.len(), 2);
+
+        // A smaller radius resolves finer structure.
+        let fine = subtractive(
+            &blobs(),
+            SubtractiveOptions {
+                radius: 0.01,
+                ..SubtractiveOptions::default()
+            },
+        )
+        .unwrap();
+        assert!(fine.centers.len() > 3);
+    }
+
+    #[test]
+    fn invalid_inputs_are_rejected() {
+        assert_eq!(
+            fuzzy_c_means(&[], FcmOptions::default()),
+            Err(FuzzyError::EmptyInput)
+        );
+        assert_eq!(
+            fuzzy_c_means(&[vec![0.0], vec![1.0, 2.0]], FcmOptions::default()),
+            Err(FuzzyError::BadArity)
+        );
+        assert_eq!(
+            fuzzy_c_means(
+                &blobs(),
+                FcmOptions {
+                    fuzziness: 1.0,
+                    ..FcmOptions::default()
+                }
+            ),
+            Err(FuzzyError::OutOfBounds)
+        );
+    }
+}
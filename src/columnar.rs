@@ -0,0 +1,154 @@
+// Structure-of-arrays batch evaluation.
+//
+// Inputs and outputs are columns (one slice per variable). Antecedent memberships
+// are computed a whole column chunk at a time through `Variable::eval_many`, whose
+// `eval_grid` the `simd` feature vectorizes, before rows are combined and defuzzified.
+use std::{borrow::Borrow, collections::HashMap, hash::Hash};
+
+use crate::{
+    antecedent::eval_with,
+    defuzz::centroid,
+    error::{self, FuzzyError, MissingSpace},
+    rulespace::RuleSpace,
+    sampler::{GridCache, UniformSampler},
+    Float,
+};
+
+/// Rows processed per chunk; keeps the per-atom membership columns cache resident.
+const CHUNK: usize = 256;
+
+impl RuleSpace {
+    /// Evaluate a batch laid out as columns.
+    ///
+    /// `inputs` maps every input variable to a column; all columns must share one
+    /// length `n`. `out[i]` receives output `i` of `outputs()` and must also have
+    /// length `n`. Mismatched shapes fail with `BadArity`, inputs outside a
+    /// variable's domain with `OutOfDomain`, and rule bases with crisp
+    /// consequents with `TypeMismatch` (in `InRule`).
+    pub fn evaluate_columns<KI>(
+        &self,
+        inputs: &HashMap<KI, &[Float]>,
+        sampler: &UniformSampler,
+        out: &mut [&mut [Float]],
+    ) -> error::Result<()>
+    where
+        KI: Eq + Hash + Borrow<str>,
+    {
+        self.require_term_consequents()?;
+        if out.len() != self.outputs.len() {
+            return Err(FuzzyError::BadArity);
+        }
+        let cols: Vec<Option<&[Float]>> =
+            self.names.iter().map(|n| inputs.get(n).copied()).collect();
+        let n = cols
+            .iter()
+            .flatten()
+            .map(|c| c.len())
+            .chain(out.iter().map(|c| c.len()))
+            .next()
+            .unwrap_or(0);
+        if cols.iter().flatten().any(|c| c.len() != n) || out.iter().any(|c| c.len() != n) {
+            return Err(FuzzyError::BadArity);
+        }
+
+        let mut grids = GridCache::new();
+        let table = self.grid_table(sampler, &mut grids)?;
+        let mut agg = Vec::new();
+        let mut scratch = Vec::new();
+        // Membership columns, one per antecedent atom of every rule.
+        let mut mu: Vec<Vec<Vec<Float>>> = self
+            .compiled
+            .iter()
+            .map(|c| vec![vec![0.0; CHUNK]; c.atoms.len()])
+            .collect();
+
+        for start in (0..n).step_by(CHUNK) {
+            let len = CHUNK.min(n - start);
+
+            for (compiled, cols_mu) in self.compiled.iter().zip(mu.iter_mut()) {
+                for (&(v, t), buf) in compiled.atoms.iter().zip(cols_mu.iter_mut()) {
+                    let col = cols[v.index()].ok_or_else(|| FuzzyError::NotFound {
+                        space: MissingSpace::Input,
+                        key: self.var_name(v).to_string(),
+                    })?;
+                    let xs = &col[start..start + len];
+                    self.vars[v.index()].eval_many_id(t, xs, &mut buf[..len])?;
+                }
+            }
+
+            for row in 0..len {
+                self.reset_agg(&table, &mut agg);
+                for ((rule, compiled), cols_mu) in self.rules.iter().zip(&self.compiled).zip(&mu) {
+                    let mut atoms = cols_mu.iter();
+                    let alpha = eval_with(&rule.antecedent, &mut |_, _| {
+                        atoms.next().map(|c| c[row]).ok_or(FuzzyError::BadArity)
+                    })? * rule.weight;
+                    compiled.implicate_into(
+                        alpha,
+                        &self.vars,
+                        &table,
+                        &mut agg,
+                        &mut scratch,
+                        |_| true,
+                    );
+                }
+                for (slot, &v) in self.outputs.iter().enumerate() {
+                    let i = v.index();
+                    out[slot][start + row] = centroid(table[i], &agg[i]);
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::error::FuzzyError;
+
+    use crate::prelude::*;
+    use crate::test_support::space;
+
+    #[test]
+    fn columns_match_row_evaluation() {
+        let rs = space();
+        let sampler = UniformSampler::default();
+        // More rows than one chunk so the chunk boundary is exercised.
+        let temps: Vec<Float> = (0..600).map(|i| (i % 101) as Float / 10.0).collect();
+        let mut fan = vec![0.0; temps.len()];
+        let inputs = HashMap::from([("temp", temps.as_slice())]);
+        rs.evaluate_columns(&inputs, &sampler, &mut [fan.as_mut_slice()])
+            .unwrap();
+
+        for (&t, &y) in temps.iter().zip(&fan).step_by(37) {
+            let row = rs
+                .evaluate(&HashMap::from([("temp", t)]), &sampler)
+                .unwrap();
+            assert!((row["fan"] - y).abs() < 1e-3);
+        }
+
+        let mut short = vec![0.0; 3];
+        assert!(matches!(
+            rs.evaluate_columns(&inputs, &sampler, &mut [short.as_mut_slice()]),
+            Err(FuzzyError::BadArity)
+        ));
+    }
+
+    #[test]
+    fn crisp_consequents_are_rejected() {
+        let rs = crate::test_support::crisp_space();
+        let temps: [Float; 2] = [2.0, 8.0];
+        let inputs = HashMap::from([("temp", &temps[..])]);
+        let (mut fan, mut power) = ([0.0; 2], [0.0; 2]);
+        let err = rs
+            .evaluate_columns(
+                &inputs,
+                &UniformSampler::default(),
+                &mut [&mut fan[..], &mut power[..]],
+            )
+            .unwrap_err();
+        assert!(crate::test_support::rejects_crisp(&err));
+    }
+}
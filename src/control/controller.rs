@@ -0,0 +1,426 @@
+// Stateful fixed-rate controller.
+//
+// `Controller` owns a rule space and everything a real-time loop keeps between
+// ticks: the previous inputs and outputs, and the state of auxiliary inputs
+// derived from raw signals (rates of change and running integrals), the
+// outputs fed back into the rules, and the post-processing stages attached to
+// its outputs. Each `step` advances time by
+// the fixed period `dt`.
+use std::{borrow::Borrow, collections::HashMap, hash::Hash};
+
+use super::{domain, Ema, RateLimit, Stage};
+use crate::{
+    error::{self, FuzzyError, MissingSpace},
+    rulespace::{RuleSpace, Scratch},
+    sampler::UniformSampler,
+    Float,
+};
+
+/// How an auxiliary input is derived from its source signal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AuxKind {
+    /// Backward difference `(x - x_prev) / dt`; zero on the first step.
+    Derivative,
+    /// Rectangle-rule integral `Σ x dt`.
+    Integral,
+}
+
+#[derive(Debug, Clone)]
+struct Aux {
+    source: String,
+    input: String,
+    kind: AuxKind,
+    domain: (Float, Float),
+    prev: Option<Float>,
+    acc: Float,
+}
+
+/// A rule space driven at a fixed rate with persistent state.
+pub struct Controller {
+    space: RuleSpace,
+    scratch: Scratch,
+    dt: Float,
+    aux: Vec<Aux>,
+    /// Outputs read back as inputs, with the value used before the first step
+    /// and the domain fed-back values are clamped into.
+    feedback: Vec<(String, Float, (Float, Float))>,
+    stages: Vec<(String, Box<dyn Stage + Send>)>,
+    inputs: HashMap<String, Float>,
+    prev_inputs: HashMap<String, Float>,
+    outputs: HashMap<String, Float>,
+    steps: usize,
+}
+
+impl Controller {
+    /// Wraps `space`, stepping with period `dt`; fails with `OutOfBounds` unless
+    /// `dt` is positive.
+    pub fn new(space: RuleSpace, sampler: &UniformSampler, dt: Float) -> error::Result<Self> {
+        if dt.is_nan() || dt <= 0.0 {
+            return Err(FuzzyError::OutOfBounds);
+        }
+        Ok(Self {
+            scratch: space.scratch(sampler)?,
+            space,
+            dt,
+            aux: Vec::new(),
+            feedback: Vec::new(),
+            stages: Vec::new(),
+            inputs: HashMap::new(),
+            prev_inputs: HashMap::new(),
+            outputs: HashMap::new(),
+            steps: 0,
+        })
+    }
+
+    /// Feeds input variable `input` with the rate of change of signal `source`.
+    pub fn with_derivative(self, source: &str, input: &str) -> error::Result<Self> {
+        self.with_aux(source, input, AuxKind::Derivative)
+    }
+
+    /// Feeds input variable `input` with the running integral of signal `source`.
+    pub fn with_integral(self, source: &str, input: &str) -> error::Result<Self> {
+        self.with_aux(source, input, AuxKind::Integral)
+    }
+
+    /// Adds an auxiliary input. `input` must be an input variable of the rule
+    /// space (`NotFound` otherwise) that the caller does not also supply; derived
+    /// values are clamped into its domain, which also bounds the integrator.
+    pub fn with_aux(mut self, source: &str, input: &str, kind: AuxKind) -> error::Result<Self> {
+        if !self.space.inputs().any(|n| n == input) {
+            return Err(FuzzyError::NotFound {
+                space: MissingSpace::Input,
+                key: input.to_string(),
+            });
+        }
+        let domain = domain(&self.space, input)?;
+        self.aux.push(Aux {
+            source: source.to_string(),
+            input: input.to_string(),
+            kind,
+            domain,
+            prev: None,
+            acc: 0.0,
+        });
+        Ok(self)
+    }
+
+    /// Feeds the previous step's value of `output` back in as the input of the
+    /// same name, so rules can test what the controller last did ("IF fan WAS
+    /// high AND temp IS cold THEN ..." is `atom("fan", "high").and(atom("temp",
+    /// "cold"))`).
+    ///
+    /// `initial` (clamped into the domain) stands in before the first step, after
+    /// `reset`, and whenever the previous output was NaN. The value fed back is
+    /// the post-processed one, clamped into the domain. Fails with `NotFound` unless some rule concludes
+    /// on `output`.
+    pub fn with_feedback(mut self, output: &str, initial: Float) -> error::Result<Self> {
+        if !self.space.outputs().any(|n| n == output) {
+            return Err(FuzzyError::NotFound {
+                space: MissingSpace::Var,
+                key: output.to_string(),
+            });
+        }
+        let (min, max) = domain(&self.space, output)?;
+        self.feedback
+            .push((output.to_string(), initial.clamp(min, max), (min, max)));
+        Ok(self)
+    }
+
+    /// Attaches a post-processing stage to output `output` (`NotFound` if no rule
+    /// concludes on it). Stages run in the order they were attached.
+    pub fn with_stage(
+        mut self,
+        output: &str,
+        stage: impl Stage + Send + 'static,
+    ) -> error::Result<Self> {
+        if !self.space.outputs().any(|n| n == output) {
+            return Err(FuzzyError::NotFound {
+                space: MissingSpace::Var,
+                key: output.to_string(),
+            });
+        }
+        self.stages.push((output.to_string(), Box::new(stage)));
+        Ok(self)
+    }
+
+    /// Limits the slew rate of `output` to `max_rate` units per unit of time;
+    /// shorthand for `with_stage(output, RateLimit::new(max_rate)?)`.
+    pub fn with_rate_limit(self, output: &str, max_rate: Float) -> error::Result<Self> {
+        self.with_stage(output, RateLimit::new(max_rate)?)
+    }
+
+    /// Smooths `output` with an exponential moving average of time constant
+    /// `tau`; shorthand for `with_stage(output, Ema::new(tau)?)`.
+    pub fn with_smoothing(self, output: &str, tau: Float) -> error::Result<Self> {
+        self.with_stage(output, Ema::new(tau)?)
+    }
+
+    /// Advances one period: derives the auxiliary inputs, evaluates the rule space
+    /// and returns every output after its post-processing stages (NaN where no
+    /// rule fired and no stage replaced it).
+    ///
+    /// `inputs` holds the raw signals; keys that are neither rule inputs nor aux
+    /// sources are ignored, and fed-back outputs override any caller value. A missing aux source fails with `NotFound` and leaves
+    /// the state untouched.
+    pub fn step<KI>(
+        &mut self,
+        inputs: &HashMap<KI, Float>,
+    ) -> error::Result<&HashMap<String, Float>>
+    where
+        KI: Eq + Hash + Borrow<str>,
+    {
+        let mut derived = Vec::with_capacity(self.aux.len());
+        for a in &self.aux {
+            let &x = inputs
+                .get(a.source.as_str())
+                .ok_or_else(|| FuzzyError::NotFound {
+                    space: MissingSpace::Input,
+                    key: a.source.clone(),
+                })?;
+            derived.push(x);
+        }
+
+        self.inputs.clear();
+        for (k, &v) in inputs {
+            self.inputs.insert(k.borrow().to_string(), v);
+        }
+        for (a, x) in self.aux.iter_mut().zip(derived) {
+            let value = match a.kind {
+                AuxKind::Derivative => a.prev.map_or(0.0, |p| (x - p) / self.dt),
+                AuxKind::Integral => {
+                    a.acc = (a.acc + x * self.dt).clamp(a.domain.0, a.domain.1);
+                    a.acc
+                }
+            };
+            a.prev = Some(x);
+            self.inputs
+                .insert(a.input.clone(), value.clamp(a.domain.0, a.domain.1));
+        }
+
+        for (name, initial, (min, max)) in &self.feedback {
+            let prev = self.outputs.get(name).copied().filter(|y| !y.is_nan());
+            self.inputs
+                .insert(name.clone(), prev.map_or(*initial, |y| y.clamp(*min, *max)));
+        }
+
+        let out = self.space.evaluate_in(&self.inputs, &mut self.scratch)?;
+        self.outputs.clone_from(out);
+        for (name, stage) in &mut self.stages {
+            if let Some(y) = self.outputs.get_mut(name.as_str()) {
+                *y = stage.apply(*y, self.dt);
+            }
+        }
+        std::mem::swap(&mut self.prev_inputs, &mut self.inputs);
+        self.steps += 1;
+        Ok(&self.outputs)
+    }
+
+    /// Outputs of the last step (empty before the first).
+    pub fn outputs(&self) -> &HashMap<String, Float> {
+        &self.outputs
+    }
+
+    /// Inputs the last step evaluated, auxiliary inputs included.
+    pub fn previous_inputs(&self) -> &HashMap<String, Float> {
+        &self.prev_inputs
+    }
+
+    /// Number of completed steps.
+    pub fn steps(&self) -> usize {
+        self.steps
+    }
+
+    pub fn dt(&self) -> Float {
+        self.dt
+    }
+
+    /// The wrapped rule space.
+    pub fn space(&self) -> &RuleSpace {
+        &self.space
+    }
+
+    /// Clears all state as if no step had run.
+    pub fn reset(&mut self) {
+        for a in &mut self.aux {
+            a.prev = None;
+            a.acc = 0.0;
+        }
+        for (_, stage) in &mut self.stages {
+            stage.reset();
+        }
+        self.prev_inputs.clear();
+        self.outputs.clear();
+        self.steps = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+    use crate::control::pid::tests::pd_space;
+
+    #[test]
+    fn derives_rate_and_integral_inputs() {
+        let sampler = UniformSampler::default();
+        let mut c = Controller::new(pd_space(), &sampler, 0.5)
+            .unwrap()
+            .with_derivative("e", "de")
+            .unwrap();
+
+        c.step(&HashMap::from([("e", 0.2)])).unwrap();
+        assert_eq!(c.previous_inputs()["de"], 0.0);
+        let u = c.step(&HashMap::from([("e", 0.4)])).unwrap()["u"];
+        assert!((c.previous_inputs()["de"] - 0.4).abs() < 1e-6);
+        let direct = pd_space()
+            .evaluate(&HashMap::from([("e", 0.4), ("de", 0.4)]), &sampler)
+            .unwrap()["u"];
+        assert_eq!(u, direct);
+        assert_eq!(c.steps(), 2);
+
+        // Large rates are clamped into the variable's domain.
+        c.step(&HashMap::from([("e", -1.0)])).unwrap();
+        assert_eq!(c.previous_inputs()["de"], -1.0);
+
+        c.reset();
+        c.step(&HashMap::from([("e", 0.9)])).unwrap();
+        assert_eq!(c.previous_inputs()["de"], 0.0);
+        assert!(matches!(
+            c.step(&HashMap::from([("x", 0.0)])),
+            Err(FuzzyError::NotFound { .. })
+        ));
+    }
+
+    #[test]
+    fn integral_accumulates_and_saturates() {
+        let sampler = UniformSampler::default();
+        let mut c = Controller::new(pd_space(), &sampler, 0.25)
+            .unwrap()
+            .with_integral("err", "e")
+            .unwrap();
+        for _ in 0..2 {
+            c.step(&HashMap::from([("err", 1.0), ("de", 0.0)])).unwrap();
+        }
+        assert!((c.previous_inputs()["e"] - 0.5).abs() < 1e-6);
+        for _ in 0..10 {
+            c.step(&HashMap::from([("err", 1.0), ("de", 0.0)])).unwrap();
+        }
+        assert_eq!(c.previous_inputs()["e"], 1.0);
+        // Saturated integrators unwind immediately.
+        c.step(&HashMap::from([("err", -1.0), ("de", 0.0)]))
+            .unwrap();
+        assert!((c.previous_inputs()["e"] - 0.75).abs() < 1e-6);
+
+        assert!(matches!(
+            Controller::new(pd_space(), &sampler, 0.0),
+            Err(FuzzyError::OutOfBounds)
+        ));
+        assert!(matches!(
+            Controller::new(pd_space(), &sampler, 0.1)
+                .unwrap()
+                .with_integral("err", "u"),
+            Err(FuzzyError::NotFound { .. })
+        ));
+    }
+
+    #[test]
+    fn rate_limit_applies_to_outputs() {
+        let sampler = UniformSampler::default();
+        let mut c = Controller::new(pd_space(), &sampler, 0.1)
+            .unwrap()
+            .with_rate_limit("u", 1.0)
+            .unwrap();
+        let input = |e: Float| HashMap::from([("e", e), ("de", 0.0)]);
+        let first = c.step(&input(0.0)).unwrap()["u"];
+        let second = c.step(&input(1.0)).unwrap()["u"];
+        assert!((second - first - 0.1).abs() < 1e-6, "{first} -> {second}");
+
+        assert!(matches!(
+            Controller::new(pd_space(), &sampler, 0.1)
+                .unwrap()
+                .with_rate_limit("e", 1.0),
+            Err(FuzzyError::NotFound { .. })
+        ));
+    }
+
+    #[test]
+    fn smoothing_damps_output_jitter() {
+        let sampler = UniformSampler::default();
+        let mut raw = Controller::new(pd_space(), &sampler, 0.1).unwrap();
+        let mut smooth = Controller::new(pd_space(), &sampler, 0.1)
+            .unwrap()
+            .with_smoothing("u", 0.5)
+            .unwrap();
+        let swing = |c: &mut Controller| {
+            let mut ys = Vec::new();
+            for k in 0..20 {
+                let e = if k % 2 == 0 { 0.6 } else { 0.2 };
+                ys.push(c.step(&HashMap::from([("e", e), ("de", 0.0)])).unwrap()["u"]);
+            }
+            let tail = &ys[10..];
+            tail.iter().copied().fold(Float::MIN, Float::max)
+                - tail.iter().copied().fold(Float::MAX, Float::min)
+        };
+        assert!(swing(&mut smooth) < 0.5 * swing(&mut raw));
+    }
+
+    #[test]
+    fn feedback_rules_see_previous_output() {
+        use crate::prelude::*;
+
+        // The fan latches on: once high it stays high even after it cools down.
+        let mut temp = Variable::new(0.0, 10.0).unwrap();
+        temp.insert_term(
+            "hot",
+            Term::new("hot", Triangular::new(5.0, 10.0, 15.0).unwrap()),
+        )
+        .unwrap();
+        let mut fan = Variable::new(0.0, 10.0).unwrap();
+        fan.insert_term(
+            "low",
+            Term::new("low", Triangular::new(-10.0, 0.0, 5.0).unwrap()),
+        )
+        .unwrap();
+        fan.insert_term(
+            "high",
+            Term::new("high", Triangular::new(5.0, 10.0, 20.0).unwrap()),
+        )
+        .unwrap();
+        let rules = vec![
+            Rule::builder()
+                .when(atom("temp", "hot").or(atom("fan", "high")))
+                .then("fan", "high")
+                .build()
+                .unwrap(),
+            Rule::builder()
+                .when(not(atom("temp", "hot")).and(not(atom("fan", "high"))))
+                .then("fan", "low")
+                .build()
+                .unwrap(),
+        ];
+        let vars = HashMap::from([("temp".to_string(), temp), ("fan".to_string(), fan)]);
+        let space = RuleSpace::new(vars, rules).unwrap();
+        let sampler = UniformSampler::default();
+        let mut c = Controller::new(space, &sampler, 0.1)
+            .unwrap()
+            .with_feedback("fan", 0.0)
+            .unwrap();
+
+        let cold = HashMap::from([("temp", 0.0)]);
+        assert!(c.step(&cold).unwrap()["fan"] < 5.0);
+        assert_eq!(c.previous_inputs()["fan"], 0.0);
+        assert!(c.step(&HashMap::from([("temp", 10.0)])).unwrap()["fan"] > 5.0);
+        assert!(c.step(&cold).unwrap()["fan"] > 5.0);
+        assert!(c.previous_inputs()["fan"] > 5.0);
+
+        c.reset();
+        assert!(c.step(&cold).unwrap()["fan"] < 5.0);
+        assert!(matches!(
+            Controller::new(pd_space(), &sampler, 0.1)
+                .unwrap()
+                .with_feedback("e", 0.0),
+            Err(FuzzyError::NotFound { .. })
+        ));
+    }
+}
@@ -0,0 +1,36 @@
+// Closed-loop control on top of `RuleSpace`.
+//
+// The engine maps crisp inputs to crisp outputs one call at a time; the types
+// here hold the state a control loop needs between calls (previous errors,
+// integrators, output limits) so applications do not have to rebuild that
+// plumbing around the raw engine.
+use crate::{
+    error::{self, FuzzyError, MissingSpace},
+    rulespace::RuleSpace,
+    Float,
+};
+
+pub mod controller;
+pub mod pid;
+pub mod post;
+pub mod schedule;
+pub mod sim;
+pub mod table;
+
+pub use controller::{AuxKind, Controller};
+pub use pid::{FuzzyPid, PidGains};
+pub use post::{Chain, Deadband, Ema, Hysteresis, RateLimit, Stage, Window};
+pub use schedule::Scheduler;
+pub use sim::{Simulation, Trajectory};
+pub use table::{ede_controller, TableOptions};
+
+/// Domain of variable `name` in `space`, or `NotFound`.
+fn domain(space: &RuleSpace, name: &str) -> error::Result<(Float, Float)> {
+    space
+        .var(name)
+        .map(|v| v.domain())
+        .ok_or_else(|| FuzzyError::NotFound {
+            space: MissingSpace::Var,
+            key: name.to_string(),
+        })
+}
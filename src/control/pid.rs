@@ -0,0 +1,294 @@
+// Fuzzy PID controller.
+//
+// A two-input fuzzy system reads the scaled error `E = ke * e` and error rate
+// `ΔE = kd * de/dt` and produces a normalized action `y`. The controller output
+// is `u = kp * y + ki * ∫y dt`: the proportional path gives a fuzzy PD
+// controller, the integral path a fuzzy PI controller, and both together the
+// usual fuzzy PID. Scaled inputs are clamped into their variables' domains, and
+// the output saturates with the integrator held back (anti-windup).
+use std::collections::HashMap;
+
+use super::domain;
+use crate::{
+    error::{self, FuzzyError, MissingSpace},
+    rulespace::{RuleSpace, Scratch},
+    sampler::UniformSampler,
+    Float,
+};
+
+/// Scaling gains of a `FuzzyPid`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PidGains {
+    /// Error scaling into the error variable.
+    pub error: Float,
+    /// Error-rate scaling into the delta-error variable.
+    pub delta: Float,
+    /// Gain on the fuzzy output.
+    pub proportional: Float,
+    /// Gain on the integrated fuzzy output.
+    pub integral: Float,
+}
+
+impl Default for PidGains {
+    fn default() -> Self {
+        Self {
+            error: 1.0,
+            delta: 1.0,
+            proportional: 1.0,
+            integral: 0.0,
+        }
+    }
+}
+
+/// Fuzzy PID controller around a rule space with error and delta-error inputs.
+pub struct FuzzyPid {
+    space: RuleSpace,
+    scratch: Scratch,
+    inputs: HashMap<String, Float>,
+    error: (String, (Float, Float)),
+    delta: (String, (Float, Float)),
+    output: String,
+    gains: PidGains,
+    limits: (Float, Float),
+    prev_error: Option<Float>,
+    integral: Float,
+    last: Float,
+}
+
+impl FuzzyPid {
+    /// Wraps `space`, which must read input variables `error` and `delta` and
+    /// conclude on `output`; fails with `NotFound` otherwise.
+    pub fn new(
+        space: RuleSpace,
+        sampler: &UniformSampler,
+        error: &str,
+        delta: &str,
+        output: &str,
+        gains: PidGains,
+    ) -> error::Result<Self> {
+        for name in [error, delta] {
+            if !space.inputs().any(|n| n == name) {
+                return Err(FuzzyError::NotFound {
+                    space: MissingSpace::Input,
+                    key: name.to_string(),
+                });
+            }
+        }
+        if !space.outputs().any(|n| n == output) {
+            return Err(FuzzyError::NotFound {
+                space: MissingSpace::Var,
+                key: output.to_string(),
+            });
+        }
+        Ok(Self {
+            scratch: space.scratch(sampler)?,
+            inputs: HashMap::from([(error.to_string(), 0.0), (delta.to_string(), 0.0)]),
+            error: (error.to_string(), domain(&space, error)?),
+            delta: (delta.to_string(), domain(&space, delta)?),
+            output: output.to_string(),
+            space,
+            gains,
+            limits: (Float::NEG_INFINITY, Float::INFINITY),
+            prev_error: None,
+            integral: 0.0,
+            last: 0.0,
+        })
+    }
+
+    /// Saturates the controller output to `[min, max]`; fails with `OutOfBounds`
+    /// if `min > max`.
+    pub fn with_limits(mut self, min: Float, max: Float) -> error::Result<Self> {
+        if min > max {
+            return Err(FuzzyError::OutOfBounds);
+        }
+        self.limits = (min, max);
+        Ok(self)
+    }
+
+    /// Advances the controller by `dt` and returns the new output.
+    ///
+    /// The error rate is zero on the first call after construction or `reset`.
+    /// If no rule fires the fuzzy action counts as zero. Fails with
+    /// `OutOfBounds` unless `dt` is positive.
+    pub fn update(
+        &mut self,
+        setpoint: Float,
+        measurement: Float,
+        dt: Float,
+    ) -> error::Result<Float> {
+        if dt.is_nan() || dt <= 0.0 {
+            return Err(FuzzyError::OutOfBounds);
+        }
+        let e = setpoint - measurement;
+        let de = self.prev_error.map_or(0.0, |p| (e - p) / dt);
+        let g = self.gains;
+        for ((name, (min, max)), x) in [(&self.error, g.error * e), (&self.delta, g.delta * de)] {
+            *self.inputs.get_mut(name).expect("inserted on construction") = x.clamp(*min, *max);
+        }
+        let y = self.space.evaluate_in(&self.inputs, &mut self.scratch)?[&self.output];
+        let y = if y.is_nan() { 0.0 } else { y };
+
+        let p = g.proportional * y;
+        let (min, max) = self.limits;
+        self.integral += g.integral * y * dt;
+        let u = p + self.integral;
+        // Anti-windup: keep the integrator where it puts the output on the limit.
+        if !(min..=max).contains(&u) {
+            self.integral = u.clamp(min, max) - p;
+        }
+        self.prev_error = Some(e);
+        self.last = u.clamp(min, max);
+        Ok(self.last)
+    }
+
+    /// Output of the last `update` (zero before the first).
+    pub fn output(&self) -> Float {
+        self.last
+    }
+
+    /// Clears the integrator and the stored error.
+    pub fn reset(&mut self) {
+        self.prev_error = None;
+        self.integral = 0.0;
+        self.last = 0.0;
+    }
+
+    pub fn gains(&self) -> PidGains {
+        self.gains
+    }
+
+    pub fn set_gains(&mut self, gains: PidGains) {
+        self.gains = gains;
+    }
+
+    /// The wrapped rule space.
+    pub fn space(&self) -> &RuleSpace {
+        &self.space
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+    use crate::antecedent::Antecedent;
+    use crate::mamdani::{Consequent, Rule};
+    use crate::prelude::*;
+    use crate::variable::Variable;
+
+    /// `e`, `de` in [-1, 1] and `u` in [-1, 1], each with neg/zero/pos terms;
+    /// the action follows the sign of `e + de`.
+    pub(crate) fn pd_space() -> RuleSpace {
+        let var = || {
+            let mut v = Variable::new(-1.0, 1.0).unwrap();
+            for (name, c) in [("neg", -1.0), ("zero", 0.0), ("pos", 1.0)] {
+                v.insert_term(
+                    name,
+                    Term::new(name, Triangular::new(c - 1.0, c, c + 1.0).unwrap()),
+                )
+                .unwrap();
+            }
+            v
+        };
+        let atom = |var: &'static str, term: &'static str| {
+            Box::new(Antecedent::Atom {
+                var: var.into(),
+                term: term.into(),
+            })
+        };
+        let table = [
+            ("neg", "neg", "neg"),
+            ("neg", "zero", "neg"),
+            ("neg", "pos", "zero"),
+            ("zero", "neg", "neg"),
+            ("zero", "zero", "zero"),
+            ("zero", "pos", "pos"),
+            ("pos", "neg", "zero"),
+            ("pos", "zero", "pos"),
+            ("pos", "pos", "pos"),
+        ];
+        let rules = table
+            .iter()
+            .map(|&(e, de, u)| Rule {
+                antecedent: Antecedent::And(atom("e", e), atom("de", de)),
+                consequent: vec![Consequent::Term {
+                    var: "u".into(),
+                    term: u.into(),
+                    hedge: None,
+                }],
+                weight: 1.0,
+            })
+            .collect();
+        let vars = HashMap::from([
+            ("e".to_string(), var()),
+            ("de".to_string(), var()),
+            ("u".to_string(), var()),
+        ]);
+        RuleSpace::new(vars, rules).unwrap()
+    }
+
+    #[test]
+    fn pi_loop_tracks_the_setpoint() {
+        let gains = PidGains {
+            error: 0.5,
+            delta: 0.1,
+            proportional: 2.0,
+            integral: 4.0,
+        };
+        let mut pid = FuzzyPid::new(
+            pd_space(),
+            &UniformSampler::default(),
+            "e",
+            "de",
+            "u",
+            gains,
+        )
+        .unwrap()
+        .with_limits(-5.0, 5.0)
+        .unwrap();
+        // First-order plant x' = -x + u.
+        let (mut x, dt) = (0.0, 0.05);
+        for _ in 0..1000 {
+            let u = pid.update(1.5, x, dt).unwrap();
+            assert!((-5.0..=5.0).contains(&u));
+            x += (-x + u) * dt;
+        }
+        assert!((x - 1.5).abs() < 0.05, "{x}");
+
+        pid.reset();
+        assert_eq!(pid.output(), 0.0);
+        assert_eq!(pid.update(0.0, 0.0, 0.0), Err(FuzzyError::OutOfBounds));
+    }
+
+    #[test]
+    fn saturation_holds_back_the_integrator() {
+        let gains = PidGains {
+            proportional: 0.0,
+            integral: 10.0,
+            ..PidGains::default()
+        };
+        let mut pid = FuzzyPid::new(
+            pd_space(),
+            &UniformSampler::default(),
+            "e",
+            "de",
+            "u",
+            gains,
+        )
+        .unwrap()
+        .with_limits(0.0, 1.0)
+        .unwrap();
+        for _ in 0..100 {
+            assert!(pid.update(10.0, 0.0, 0.1).unwrap() <= 1.0);
+        }
+        // The error reverses: without anti-windup the output would stay pinned.
+        let u = pid.update(-10.0, 0.0, 0.1).unwrap();
+        assert!(u < 1.0, "{u}");
+
+        assert!(matches!(
+            FuzzyPid::new(pd_space(), &UniformSampler::default(), "e", "x", "u", gains),
+            Err(FuzzyError::NotFound { .. })
+        ));
+    }
+}
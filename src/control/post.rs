@@ -0,0 +1,339 @@
+// Output post-processing stages.
+//
+// A `Stage` transforms one output value per controller tick and may keep state
+// between ticks. Stages are attached to output variables of a `Controller` and
+// run in the order they were attached, after defuzzification.
+use std::collections::VecDeque;
+
+use crate::{
+    error::{self, FuzzyError},
+    membership::MembershipFn,
+    Float,
+};
+
+/// A stateful transformation applied to one output each step.
+pub trait Stage {
+    /// Transforms `x`, the output at this step; `dt` is the controller period.
+    fn apply(&mut self, x: Float, dt: Float) -> Float;
+
+    /// Forgets any state, as if no step had run.
+    fn reset(&mut self) {}
+
+    /// Runs `next` on this stage's result.
+    fn then<S: Stage>(self, next: S) -> Chain<Self, S>
+    where
+        Self: Sized,
+    {
+        Chain(self, next)
+    }
+}
+
+/// Two stages run one after the other; see `Stage::then`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Chain<A, B>(pub A, pub B);
+
+impl<A: Stage, B: Stage> Stage for Chain<A, B> {
+    fn apply(&mut self, x: Float, dt: Float) -> Float {
+        let y = self.0.apply(x, dt);
+        self.1.apply(y, dt)
+    }
+
+    fn reset(&mut self) {
+        self.0.reset();
+        self.1.reset();
+    }
+}
+
+/// Limits how fast a signal may change (slew-rate limiting).
+///
+/// Rates are in output units per unit of time, so the allowed change per step is
+/// `rate * dt`. The first value passes through unchanged. A NaN input (no rule
+/// fired) holds the previous value.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RateLimit {
+    rise: Float,
+    fall: Float,
+    prev: Option<Float>,
+}
+
+impl RateLimit {
+    /// The same limit in both directions; fails with `OutOfBounds` unless
+    /// `max_rate` is positive.
+    pub fn new(max_rate: Float) -> error::Result<Self> {
+        Self::asymmetric(max_rate, max_rate)
+    }
+
+    /// Separate limits for increasing (`rise`) and decreasing (`fall`) signals,
+    /// both given as positive rates.
+    pub fn asymmetric(rise: Float, fall: Float) -> error::Result<Self> {
+        if !(rise > 0.0 && fall > 0.0) {
+            return Err(FuzzyError::OutOfBounds);
+        }
+        Ok(Self {
+            rise,
+            fall,
+            prev: None,
+        })
+    }
+}
+
+impl Stage for RateLimit {
+    fn apply(&mut self, x: Float, dt: Float) -> Float {
+        let y = match self.prev {
+            None => x,
+            Some(p) if x.is_nan() => p,
+            Some(p) if p.is_nan() => x,
+            Some(p) => x.clamp(p - self.fall * dt, p + self.rise * dt),
+        };
+        self.prev = Some(y);
+        y
+    }
+
+    fn reset(&mut self) {
+        self.prev = None;
+    }
+}
+
+/// Snaps values within `width` of `neutral` to `neutral`, so small corrections
+/// around a setpoint do not chatter the actuator. Stateless.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Deadband {
+    neutral: Float,
+    width: Float,
+}
+
+impl Deadband {
+    /// Fails with `OutOfBounds` if `width` is negative or NaN.
+    pub fn new(neutral: Float, width: Float) -> error::Result<Self> {
+        if width.is_nan() || width < 0.0 {
+            return Err(FuzzyError::OutOfBounds);
+        }
+        Ok(Self { neutral, width })
+    }
+}
+
+impl Stage for Deadband {
+    fn apply(&mut self, x: Float, _dt: Float) -> Float {
+        if (x - self.neutral).abs() <= self.width {
+            self.neutral
+        } else {
+            x
+        }
+    }
+}
+
+/// Two-level switch with hysteresis (a Schmitt trigger): outputs `on` once the
+/// input rises above `high` and `off` once it falls below `low`, holding its
+/// level in between. Starts in the `off` state; NaN inputs hold the level.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Hysteresis {
+    low: Float,
+    high: Float,
+    off: Float,
+    on: Float,
+    active: bool,
+}
+
+impl Hysteresis {
+    /// Fails with `OutOfBounds` unless `low <= high`.
+    pub fn new(low: Float, high: Float, off: Float, on: Float) -> error::Result<Self> {
+        if low.is_nan() || high.is_nan() || low > high {
+            return Err(FuzzyError::OutOfBounds);
+        }
+        Ok(Self {
+            low,
+            high,
+            off,
+            on,
+            active: false,
+        })
+    }
+}
+
+impl Stage for Hysteresis {
+    fn apply(&mut self, x: Float, _dt: Float) -> Float {
+        if x > self.high {
+            self.active = true;
+        } else if x < self.low {
+            self.active = false;
+        }
+        if self.active {
+            self.on
+        } else {
+            self.off
+        }
+    }
+
+    fn reset(&mut self) {
+        self.active = false;
+    }
+}
+
+/// Exponential moving average with time constant `tau`.
+///
+/// Each step blends in the new value with weight `1 - exp(-dt / tau)`, so the
+/// smoothing does not depend on the controller rate. The first value passes
+/// through; NaN inputs are skipped.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Ema {
+    tau: Float,
+    state: Option<Float>,
+}
+
+impl Ema {
+    /// Fails with `OutOfBounds` unless `tau` is positive.
+    pub fn new(tau: Float) -> error::Result<Self> {
+        if tau.is_nan() || tau <= 0.0 {
+            return Err(FuzzyError::OutOfBounds);
+        }
+        Ok(Self { tau, state: None })
+    }
+}
+
+impl Stage for Ema {
+    fn apply(&mut self, x: Float, dt: Float) -> Float {
+        let y = match self.state {
+            Some(s) if x.is_nan() => s,
+            Some(s) if !s.is_nan() => s + (1.0 - (-dt / self.tau).exp()) * (x - s),
+            _ => x,
+        };
+        self.state = Some(y);
+        y
+    }
+
+    fn reset(&mut self) {
+        self.state = None;
+    }
+}
+
+/// Weighted mean over the last `weights.len()` values; `weights[k]` applies to
+/// the value `k` steps old. NaN inputs are skipped, and until the window fills
+/// only the values seen so far count.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Window {
+    weights: Vec<Float>,
+    values: VecDeque<Float>,
+}
+
+impl Window {
+    /// Fails with `EmptyInput` for no weights and `OutOfBounds` if any weight is
+    /// negative or the newest one is not positive.
+    pub fn new(weights: Vec<Float>) -> error::Result<Self> {
+        if weights.is_empty() {
+            return Err(FuzzyError::EmptyInput);
+        }
+        if weights[0].is_nan()
+            || weights[0] <= 0.0
+            || weights.iter().any(|w| w.is_nan() || *w < 0.0)
+        {
+            return Err(FuzzyError::OutOfBounds);
+        }
+        Ok(Self {
+            values: VecDeque::with_capacity(weights.len()),
+            weights,
+        })
+    }
+
+    /// Plain moving average over `len` values.
+    pub fn uniform(len: usize) -> error::Result<Self> {
+        Self::new(vec![1.0; len])
+    }
+
+    /// Fuzzy-weighted window: the weight of a value `k` steps old is its age's
+    /// membership in `recent`, e.g. a triangle `(-1, 0, len)` for linearly fading
+    /// weights.
+    pub fn fuzzy(len: usize, recent: &impl MembershipFn) -> error::Result<Self> {
+        Self::new((0..len).map(|k| recent.eval(k as Float)).collect())
+    }
+}
+
+impl Stage for Window {
+    fn apply(&mut self, x: Float, _dt: Float) -> Float {
+        if !x.is_nan() {
+            if self.values.len() == self.weights.len() {
+                self.values.pop_back();
+            }
+            self.values.push_front(x);
+        }
+        let (num, den) = self
+            .values
+            .iter()
+            .zip(&self.weights)
+            .fold((0.0, 0.0), |(n, d), (v, w)| (n + v * w, d + w));
+        if den > 0.0 {
+            num / den
+        } else {
+            x
+        }
+    }
+
+    fn reset(&mut self) {
+        self.values.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rate_limit_bounds_the_change_per_step() {
+        let mut r = RateLimit::asymmetric(2.0, 10.0).unwrap();
+        assert_eq!(r.apply(5.0, 0.1), 5.0);
+        assert!((r.apply(9.0, 0.1) - 5.2).abs() < 1e-6);
+        assert!((r.apply(Float::NAN, 0.1) - 5.2).abs() < 1e-6);
+        assert!((r.apply(0.0, 0.1) - 4.2).abs() < 1e-6);
+        r.reset();
+        assert_eq!(r.apply(-3.0, 0.1), -3.0);
+        assert_eq!(RateLimit::new(0.0), Err(FuzzyError::OutOfBounds));
+    }
+
+    #[test]
+    fn deadband_and_hysteresis_compose() {
+        let mut d = Deadband::new(0.0, 0.5).unwrap();
+        assert_eq!(d.apply(0.4, 1.0), 0.0);
+        assert_eq!(d.apply(-0.6, 1.0), -0.6);
+        assert!(Deadband::new(0.0, -1.0).is_err());
+
+        let mut h = Hysteresis::new(2.0, 8.0, 0.0, 1.0).unwrap();
+        let levels: Vec<Float> = [5.0, 9.0, 5.0, 1.0, 5.0]
+            .iter()
+            .map(|&x| h.apply(x, 1.0))
+            .collect();
+        assert_eq!(levels, vec![0.0, 1.0, 1.0, 0.0, 0.0]);
+        assert!(Hysteresis::new(3.0, 2.0, 0.0, 1.0).is_err());
+
+        let mut s = Deadband::new(0.0, 0.5)
+            .unwrap()
+            .then(RateLimit::new(1.0).unwrap());
+        assert_eq!(s.apply(0.3, 1.0), 0.0);
+        assert_eq!(s.apply(3.0, 1.0), 1.0);
+        s.reset();
+        assert_eq!(s.apply(3.0, 1.0), 3.0);
+    }
+
+    #[test]
+    fn smoothing_stages_filter_jitter() {
+        let mut ema = Ema::new(1.0).unwrap();
+        assert_eq!(ema.apply(0.0, 0.5), 0.0);
+        let y = ema.apply(1.0, 0.5);
+        assert!((y - (1.0 - (-0.5 as Float).exp())).abs() < 1e-6);
+        assert_eq!(ema.apply(Float::NAN, 0.5), y);
+        assert!(Ema::new(0.0).is_err());
+
+        let mut w = Window::uniform(3).unwrap();
+        let out: Vec<Float> = [3.0, 6.0, 9.0, 12.0]
+            .iter()
+            .map(|&x| w.apply(x, 1.0))
+            .collect();
+        assert_eq!(out, vec![3.0, 4.5, 6.0, 9.0]);
+
+        // Weights 1, 0.5 (and 0 for the oldest slot) from a fading triangle.
+        let fade = crate::membership::Triangular::new(-2.0, 0.0, 2.0).unwrap();
+        let mut f = Window::fuzzy(3, &fade).unwrap();
+        f.apply(0.0, 1.0);
+        f.apply(3.0, 1.0);
+        assert!((f.apply(6.0, 1.0) - (6.0 + 0.5 * 3.0) / 1.5).abs() < 1e-6);
+        assert_eq!(Window::uniform(0), Err(FuzzyError::EmptyInput));
+    }
+}
@@ -0,0 +1,193 @@
+// Gain scheduling by fuzzy interpolation between controllers.
+//
+// A scheduling variable (speed, altitude, load, ...) is partitioned into
+// operating-regime terms, and each regime gets its own rule space. At run time
+// every regime's controller is evaluated and their outputs are blended by the
+// scheduling value's membership in the regimes, so control changes smoothly
+// between regimes without one rule base covering them all.
+use std::{borrow::Borrow, collections::HashMap, hash::Hash};
+
+use crate::{
+    error::{self, FuzzyError},
+    rulespace::{RuleSpace, Scratch},
+    sampler::UniformSampler,
+    variable::Variable,
+    Float,
+};
+
+struct Regime {
+    term: String,
+    space: RuleSpace,
+    scratch: Scratch,
+}
+
+/// Blends regime controllers by the membership of a scheduling value.
+pub struct Scheduler {
+    schedule: Variable,
+    sampler: UniformSampler,
+    regimes: Vec<Regime>,
+    weights: Vec<Float>,
+}
+
+impl Scheduler {
+    /// A scheduler over the regime terms of `schedule`; controllers are evaluated
+    /// with `sampler`.
+    pub fn new(schedule: Variable, sampler: &UniformSampler) -> Self {
+        Self {
+            schedule,
+            sampler: sampler.clone(),
+            regimes: Vec::new(),
+            weights: Vec::new(),
+        }
+    }
+
+    /// Assigns `space` to regime `term`; fails with `TypeMismatch` if the
+    /// scheduling variable has no such term.
+    pub fn with_regime(mut self, term: &str, space: RuleSpace) -> error::Result<Self> {
+        if self.schedule.get(term).is_none() {
+            return Err(FuzzyError::TypeMismatch);
+        }
+        self.regimes.push(Regime {
+            term: term.to_string(),
+            scratch: space.scratch(&self.sampler)?,
+            space,
+        });
+        Ok(self)
+    }
+
+    /// Membership of `value` in each regime, in the order regimes were added.
+    pub fn weights(&self, value: Float) -> error::Result<Vec<Float>> {
+        self.regimes
+            .iter()
+            .map(|r| self.schedule.eval(&r.term, value))
+            .collect()
+    }
+
+    /// Evaluates every active regime at `inputs` and returns each output as the
+    /// membership-weighted mean over the regimes that produced it.
+    ///
+    /// Regimes with zero weight are skipped, and a regime whose output is
+    /// undefined (no rule fired) does not count towards that output. Outputs no
+    /// active regime defines are NaN. `value` outside the scheduling domain fails
+    /// with `OutOfBounds`; `EmptyInput` if no regime was added.
+    pub fn evaluate<KI>(
+        &mut self,
+        value: Float,
+        inputs: &HashMap<KI, Float>,
+    ) -> error::Result<HashMap<String, Float>>
+    where
+        KI: Eq + Hash + Borrow<str>,
+    {
+        if self.regimes.is_empty() {
+            return Err(FuzzyError::EmptyInput);
+        }
+        self.weights.clear();
+        for r in &self.regimes {
+            self.weights.push(self.schedule.eval(&r.term, value)?);
+        }
+
+        let mut sums: HashMap<String, (Float, Float)> = HashMap::new();
+        for (r, &w) in self.regimes.iter_mut().zip(&self.weights) {
+            for name in r.space.outputs() {
+                sums.entry(name.to_string()).or_insert((0.0, 0.0));
+            }
+            if w <= 0.0 {
+                continue;
+            }
+            for (name, &y) in r.space.evaluate_in(inputs, &mut r.scratch)? {
+                if y.is_finite() {
+                    let s = sums.get_mut(name).expect("inserted above");
+                    s.0 += w * y;
+                    s.1 += w;
+                }
+            }
+        }
+        Ok(sums
+            .into_iter()
+            .map(|(name, (num, den))| {
+                let y = if den > 0.0 { num / den } else { Float::NAN };
+                (name, y)
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+    use crate::antecedent::Antecedent;
+    use crate::mamdani::{Consequent, Rule};
+    use crate::prelude::*;
+
+    /// Single rule `x is any => u is at(c)`, so the output is always `c`.
+    fn constant(c: Float) -> RuleSpace {
+        let mut x = Variable::new(0.0, 1.0).unwrap();
+        x.insert_term(
+            "any",
+            Term::new("any", Trapezoidal::new(-1.0, -0.5, 1.5, 2.0).unwrap()),
+        )
+        .unwrap();
+        let mut u = Variable::new(0.0, 10.0).unwrap();
+        u.insert_term(
+            "at",
+            Term::new("at", Triangular::new(c - 1.0, c, c + 1.0).unwrap()),
+        )
+        .unwrap();
+        let rule = Rule {
+            antecedent: Antecedent::Atom {
+                var: "x".into(),
+                term: "any".into(),
+            },
+            consequent: vec![Consequent::Term {
+                var: "u".into(),
+                term: "at".into(),
+                hedge: None,
+            }],
+            weight: 1.0,
+        };
+        let vars = HashMap::from([("x".to_string(), x), ("u".to_string(), u)]);
+        RuleSpace::new(vars, vec![rule]).unwrap()
+    }
+
+    #[test]
+    fn blends_regimes_by_membership() {
+        let mut speed = Variable::new(0.0, 100.0).unwrap();
+        speed
+            .insert_term(
+                "slow",
+                Term::new("slow", Triangular::new(-100.0, 0.0, 100.0).unwrap()),
+            )
+            .unwrap();
+        speed
+            .insert_term(
+                "fast",
+                Term::new("fast", Triangular::new(0.0, 100.0, 200.0).unwrap()),
+            )
+            .unwrap();
+        let sampler = UniformSampler::new(1001).unwrap();
+        let mut s = Scheduler::new(speed, &sampler)
+            .with_regime("slow", constant(2.0))
+            .unwrap()
+            .with_regime("fast", constant(8.0))
+            .unwrap();
+        let input = HashMap::from([("x", 0.5)]);
+
+        assert_eq!(s.weights(25.0).unwrap(), vec![0.75, 0.25]);
+        let at = |s: &mut Scheduler, v: Float| s.evaluate(v, &input).unwrap()["u"];
+        assert!((at(&mut s, 0.0) - 2.0).abs() < 1e-3);
+        assert!((at(&mut s, 25.0) - 3.5).abs() < 1e-3);
+        assert!((at(&mut s, 100.0) - 8.0).abs() < 1e-3);
+
+        assert!(matches!(
+            s.evaluate(150.0, &input),
+            Err(FuzzyError::OutOfDomain { value: 150.0, .. })
+        ));
+        assert!(matches!(
+            Scheduler::new(Variable::new(0.0, 1.0).unwrap(), &sampler)
+                .with_regime("nope", constant(1.0)),
+            Err(FuzzyError::TypeMismatch)
+        ));
+    }
+}
@@ -0,0 +1,179 @@
+// Closed-loop simulation.
+//
+// `Simulation` alternates a controller and a discrete-time plant model
+// `x_next = f(x, u, dt)` for a fixed number of steps and records the resulting
+// trajectory. The controller is a closure, so the same harness drives a
+// `FuzzyPid`, a `Controller`, a `Scheduler` or a bare `RuleSpace`.
+use crate::{
+    error::{self, FuzzyError},
+    Float,
+};
+
+/// Fixed-step simulation settings.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Simulation {
+    pub dt: Float,
+    pub steps: usize,
+}
+
+/// States and control actions recorded by `Simulation::run`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Trajectory {
+    /// `steps + 1` time stamps, starting at zero.
+    pub time: Vec<Float>,
+    /// State at each time stamp; `states[0]` is the initial state.
+    pub states: Vec<Vec<Float>>,
+    /// Action applied from `time[k]` to `time[k + 1]`.
+    pub controls: Vec<Vec<Float>>,
+}
+
+impl Trajectory {
+    /// Component `i` of every recorded state.
+    pub fn state(&self, i: usize) -> Vec<Float> {
+        self.states.iter().map(|x| x[i]).collect()
+    }
+
+    /// The last recorded state.
+    pub fn final_state(&self) -> &[Float] {
+        self.states
+            .last()
+            .expect("a trajectory holds the initial state")
+    }
+
+    /// Integral of absolute error `∫|r - x_i| dt` of state `i` against `setpoint`.
+    pub fn iae(&self, i: usize, setpoint: Float) -> Float {
+        self.time
+            .windows(2)
+            .zip(&self.states[1..])
+            .map(|(t, x)| (setpoint - x[i]).abs() * (t[1] - t[0]))
+            .sum()
+    }
+
+    /// Largest excursion of state `i` beyond `setpoint`, in the direction of the
+    /// setpoint from the initial state (zero if it never overshoots).
+    pub fn overshoot(&self, i: usize, setpoint: Float) -> Float {
+        let sign = if setpoint >= self.states[0][i] {
+            1.0
+        } else {
+            -1.0
+        };
+        self.states
+            .iter()
+            .map(|x| sign * (x[i] - setpoint))
+            .fold(0.0, Float::max)
+    }
+}
+
+impl Simulation {
+    /// Fails with `OutOfBounds` unless `dt` is positive.
+    pub fn new(dt: Float, steps: usize) -> error::Result<Self> {
+        if dt.is_nan() || dt <= 0.0 {
+            return Err(FuzzyError::OutOfBounds);
+        }
+        Ok(Self { dt, steps })
+    }
+
+    /// Runs the loop from `x0`. Each step calls `control(t, x)` for the action
+    /// and `plant(x, u, dt)` for the next state.
+    ///
+    /// Controller errors abort the run. A plant that changes the state's length
+    /// fails with `BadArity`.
+    pub fn run<C, P>(&self, x0: &[Float], mut control: C, mut plant: P) -> error::Result<Trajectory>
+    where
+        C: FnMut(Float, &[Float]) -> error::Result<Vec<Float>>,
+        P: FnMut(&[Float], &[Float], Float) -> Vec<Float>,
+    {
+        let mut traj = Trajectory {
+            time: Vec::with_capacity(self.steps + 1),
+            states: Vec::with_capacity(self.steps + 1),
+            controls: Vec::with_capacity(self.steps),
+        };
+        traj.time.push(0.0);
+        traj.states.push(x0.to_vec());
+        for k in 0..self.steps {
+            let t = k as Float * self.dt;
+            let x = &traj.states[k];
+            let u = control(t, x)?;
+            let next = plant(x, &u, self.dt);
+            if next.len() != x0.len() {
+                return Err(FuzzyError::BadArity);
+            }
+            traj.controls.push(u);
+            traj.states.push(next);
+            traj.time.push((k + 1) as Float * self.dt);
+        }
+        Ok(traj)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+    use crate::control::pid::tests::pd_space;
+    use crate::control::{Controller, FuzzyPid, PidGains};
+    use crate::sampler::UniformSampler;
+
+    /// First-order lag `x' = -x + u`, forward Euler.
+    fn lag(x: &[Float], u: &[Float], dt: Float) -> Vec<Float> {
+        vec![x[0] + (-x[0] + u[0]) * dt]
+    }
+
+    #[test]
+    fn pid_loop_settles_on_the_setpoint() {
+        let sim = Simulation::new(0.05, 1000).unwrap();
+        let gains = PidGains {
+            error: 0.5,
+            delta: 0.1,
+            proportional: 2.0,
+            integral: 4.0,
+        };
+        let mut pid = FuzzyPid::new(
+            pd_space(),
+            &UniformSampler::default(),
+            "e",
+            "de",
+            "u",
+            gains,
+        )
+        .unwrap();
+        let traj = sim
+            .run(&[0.0], |_, x| Ok(vec![pid.update(1.5, x[0], sim.dt)?]), lag)
+            .unwrap();
+        assert_eq!(traj.states.len(), 1001);
+        assert_eq!(traj.controls.len(), 1000);
+        assert!((traj.final_state()[0] - 1.5).abs() < 0.05);
+        assert!(traj.overshoot(0, 1.5) < 0.5);
+        assert!(traj.iae(0, 1.5) < 10.0);
+    }
+
+    #[test]
+    fn controller_closures_and_errors() {
+        let sim = Simulation::new(0.1, 20).unwrap();
+        let mut c = Controller::new(pd_space(), &UniformSampler::default(), sim.dt)
+            .unwrap()
+            .with_derivative("e", "de")
+            .unwrap();
+        let traj = sim
+            .run(
+                &[0.0],
+                |_, x| {
+                    let e = (1.0 - x[0]).clamp(-1.0, 1.0);
+                    Ok(vec![c.step(&HashMap::from([("e", e)]))?["u"]])
+                },
+                lag,
+            )
+            .unwrap();
+        // A proportional-derivative action leaves a steady-state offset.
+        let x = traj.state(0);
+        assert!(x[20] > 0.2 && x[20] < 1.0, "{x:?}");
+        assert_eq!(c.steps(), 20);
+
+        assert_eq!(
+            sim.run(&[0.0], |_, _| Ok(vec![0.0]), |_, _, _| vec![]),
+            Err(FuzzyError::BadArity)
+        );
+        assert_eq!(Simulation::new(0.0, 1), Err(FuzzyError::OutOfBounds));
+    }
+}
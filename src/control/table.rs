@@ -0,0 +1,167 @@
+// Error / delta-error rule-table scaffold.
+//
+// The canonical fuzzy controller has inputs E and ΔE and one output, each
+// partitioned into the same odd number of evenly spaced triangular terms with the
+// standard labels (NB, NM, NS, ZE, PS, PM, PB), and one rule per table cell:
+// `IF E is row AND ΔE is column THEN U is cell`. `ede_controller` builds the
+// whole rule space from that table.
+use std::collections::HashMap;
+
+use crate::{
+    cluster::triangle,
+    error::{self, FuzzyError},
+    fam::fam_rules,
+    membership::Triangular,
+    rulespace::RuleSpace,
+    term::Term,
+    variable::Variable,
+    Float,
+};
+
+/// Labels of a 3-term partition, from most negative to most positive.
+pub const LABELS_3: [&str; 3] = ["N", "ZE", "P"];
+/// Labels of a 5-term partition.
+pub const LABELS_5: [&str; 5] = ["NB", "NS", "ZE", "PS", "PB"];
+/// Labels of a 7-term partition.
+pub const LABELS_7: [&str; 7] = ["NB", "NM", "NS", "ZE", "PS", "PM", "PB"];
+
+/// Variable names and domains of the scaffolded controller.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TableOptions {
+    pub error: (String, (Float, Float)),
+    pub delta: (String, (Float, Float)),
+    pub output: (String, (Float, Float)),
+}
+
+impl Default for TableOptions {
+    /// `e`, `de` and `u`, all normalized to `[-1, 1]`; scale with `PidGains`.
+    fn default() -> Self {
+        Self {
+            error: ("e".into(), (-1.0, 1.0)),
+            delta: ("de".into(), (-1.0, 1.0)),
+            output: ("u".into(), (-1.0, 1.0)),
+        }
+    }
+}
+
+/// Builds an E/ΔE controller from a square rule table of output labels.
+///
+/// `table[i][j]` concludes the output for E in term `i` and ΔE in term `j`,
+/// with terms ordered from most negative to most positive. The table size picks
+/// the labels (`LABELS_3`, `LABELS_5` or `LABELS_7`); other sizes fail with
+/// `BadArity`, as does a non-square table. Cells holding `-` or nothing have no
+/// rule. An unknown label fails with `UnknownTerm` (wrapped in `InRule`), two
+/// variables sharing a name with `TypeMismatch`.
+pub fn ede_controller(table: &[&[&str]], opts: &TableOptions) -> error::Result<RuleSpace> {
+    let labels: &[&str] = match table.len() {
+        3 => &LABELS_3,
+        5 => &LABELS_5,
+        7 => &LABELS_7,
+        _ => return Err(FuzzyError::BadArity),
+    };
+    if table.iter().any(|row| row.len() != labels.len()) {
+        return Err(FuzzyError::BadArity);
+    }
+
+    let mut vars = HashMap::new();
+    for (name, domain) in [&opts.error, &opts.delta, &opts.output] {
+        vars.insert(name.clone(), partition(labels, *domain)?);
+    }
+    if vars.len() != 3 {
+        return Err(FuzzyError::TypeMismatch);
+    }
+
+    let rules = fam_rules(
+        (&opts.error.0, labels),
+        (&opts.delta.0, labels),
+        &opts.output.0,
+        table,
+    )?;
+    RuleSpace::new(vars, rules)
+}
+
+/// Evenly spaced triangles over `domain`, one per label, each reaching its
+/// neighbours' apexes.
+fn partition(labels: &[&str], (min, max): (Float, Float)) -> error::Result<Variable> {
+    let mut var = Variable::new(min, max)?;
+    let step = (max - min) / (labels.len() - 1) as Float;
+    let apex: Vec<Float> = (0..labels.len()).map(|i| min + i as Float * step).collect();
+    for (slot, label) in labels.iter().enumerate() {
+        let (l, c, r) = triangle(&apex, slot);
+        var.insert_term(label, Term::new(*label, Triangular::new(l, c, r)?))?;
+    }
+    Ok(var)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+    use crate::control::{FuzzyPid, PidGains, Simulation};
+    use crate::sampler::UniformSampler;
+
+    // The classic diagonal (Mac Vicar-Whelan) table.
+    const TABLE: [&[&str]; 5] = [
+        &["NB", "NB", "NB", "NS", "ZE"],
+        &["NB", "NB", "NS", "ZE", "PS"],
+        &["NB", "NS", "ZE", "PS", "PB"],
+        &["NS", "ZE", "PS", "PB", "PB"],
+        &["ZE", "PS", "PB", "PB", "PB"],
+    ];
+
+    #[test]
+    fn table_builds_a_working_controller() {
+        let rs = ede_controller(&TABLE, &TableOptions::default()).unwrap();
+        assert_eq!(rs.rules().len(), 25);
+        let sampler = UniformSampler::default();
+        let at = |e: Float, de: Float| {
+            rs.evaluate(&HashMap::from([("e", e), ("de", de)]), &sampler)
+                .unwrap()["u"]
+        };
+        assert!(at(0.0, 0.0).abs() < 1e-6);
+        assert!(at(0.5, 0.0) > 0.3);
+        assert!(at(-0.5, 0.0) < -0.3);
+
+        let gains = PidGains {
+            error: 0.5,
+            delta: 0.1,
+            proportional: 2.0,
+            integral: 4.0,
+        };
+        let mut pid = FuzzyPid::new(rs, &sampler, "e", "de", "u", gains).unwrap();
+        let sim = Simulation::new(0.05, 1000).unwrap();
+        let traj = sim
+            .run(
+                &[0.0],
+                |_, x| Ok(vec![pid.update(1.0, x[0], sim.dt)?]),
+                |x, u, dt| vec![x[0] + (-x[0] + u[0]) * dt],
+            )
+            .unwrap();
+        assert!((traj.final_state()[0] - 1.0).abs() < 0.05);
+    }
+
+    #[test]
+    fn table_shape_and_labels_are_checked() {
+        let opts = TableOptions::default();
+        let sparse: [&[&str]; 3] = [&["N", "-", ""], &["-", "ZE", "-"], &["", "-", "P"]];
+        assert_eq!(ede_controller(&sparse, &opts).unwrap().rules().len(), 3);
+
+        let ragged: [&[&str]; 3] = [&["N", "ZE"], &["N", "ZE", "P"], &["N", "ZE", "P"]];
+        assert!(matches!(
+            ede_controller(&ragged, &opts),
+            Err(FuzzyError::BadArity)
+        ));
+        let unknown: [&[&str]; 3] = [&["N", "ZE", "PB"], &["N", "ZE", "P"], &["N", "ZE", "P"]];
+        assert!(matches!(
+            ede_controller(&unknown, &opts)
+                .err()
+                .map(|e| e.root().clone()),
+            Some(FuzzyError::UnknownTerm { .. })
+        ));
+        assert!(matches!(
+            ede_controller(&TABLE[..4], &opts),
+            Err(FuzzyError::BadArity)
+        ));
+    }
+}
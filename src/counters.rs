@@ -0,0 +1,166 @@
+// Opt-in evaluation counters.
+//
+// When enabled, a rule space counts evaluations, how often each rule fires above
+// a threshold and its summed firing strength, and how often an error policy fell
+// back instead of failing. Counters are relaxed atomics, so they work through
+// `&self` evaluation (including parallel batches) and cost a few uncontended
+// increments per rule; a disabled space pays one branch per evaluation.
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::{rulespace::RuleSpace, Float};
+
+/// Firing strengths are summed in fixed point with this many units per 1.0.
+const ALPHA_SCALE: Float = 1e9;
+
+#[derive(Debug, Default)]
+struct RuleCounter {
+    fired: AtomicU64,
+    alpha: AtomicU64,
+}
+
+#[derive(Debug)]
+pub(crate) struct Counters {
+    threshold: Float,
+    evaluations: AtomicU64,
+    fallbacks: AtomicU64,
+    rules: Vec<RuleCounter>,
+}
+
+impl Counters {
+    fn new(rules: usize, threshold: Float) -> Self {
+        Self {
+            threshold,
+            evaluations: AtomicU64::new(0),
+            fallbacks: AtomicU64::new(0),
+            rules: (0..rules).map(|_| RuleCounter::default()).collect(),
+        }
+    }
+
+    pub(crate) fn evaluation(&self) {
+        self.evaluations.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn fallback(&self) {
+        self.fallbacks.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn rule(&self, index: usize, alpha: Float) {
+        let Some(c) = self.rules.get(index) else {
+            return;
+        };
+        if alpha > self.threshold {
+            c.fired.fetch_add(1, Ordering::Relaxed);
+        }
+        if alpha > 0.0 {
+            let units = (alpha.min(1.0) * ALPHA_SCALE).round() as u64;
+            c.alpha.fetch_add(units, Ordering::Relaxed);
+        }
+    }
+}
+
+/// Per-rule part of a `Snapshot`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RuleStats {
+    /// Evaluations in which the rule fired above the threshold.
+    pub fired: u64,
+    /// Mean firing strength over all counted evaluations.
+    pub mean_alpha: Float,
+}
+
+/// Counter values at one point in time, ready to export to a metrics system.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Snapshot {
+    pub threshold: Float,
+    pub evaluations: u64,
+    /// Problems an error policy replaced with a fallback value.
+    pub fallbacks: u64,
+    /// One entry per rule, in rule order.
+    pub rules: Vec<RuleStats>,
+}
+
+impl RuleSpace {
+    /// Starts counting evaluations, counting a rule as fired when its strength
+    /// exceeds `threshold`. Resets any previous counts.
+    ///
+    /// Every evaluation path that aggregates all rules is counted (`evaluate`,
+    /// `evaluate_in`, the batch and policy variants, `aggregate`, ...). Adding or
+    /// removing rules resets the counts.
+    pub fn enable_counters(&mut self, threshold: Float) {
+        self.counters = Some(Counters::new(self.rules.len(), threshold));
+    }
+
+    /// Stops counting and drops the counts.
+    pub fn disable_counters(&mut self) {
+        self.counters = None;
+    }
+
+    /// Zeroes the counts, keeping the threshold; a no-op when counting is off.
+    pub fn reset_counters(&mut self) {
+        if let Some(threshold) = self.counters.as_ref().map(|c| c.threshold) {
+            self.enable_counters(threshold);
+        }
+    }
+
+    /// Current counts, or `None` if counting is off.
+    pub fn metrics(&self) -> Option<Snapshot> {
+        let c = self.counters.as_ref()?;
+        let evaluations = c.evaluations.load(Ordering::Relaxed);
+        Some(Snapshot {
+            threshold: c.threshold,
+            evaluations,
+            fallbacks: c.fallbacks.load(Ordering::Relaxed),
+            rules: c
+                .rules
+                .iter()
+                .map(|r| RuleStats {
+                    fired: r.fired.load(Ordering::Relaxed),
+                    mean_alpha: if evaluations == 0 {
+                        0.0
+                    } else {
+                        r.alpha.load(Ordering::Relaxed) as Float
+                            / ALPHA_SCALE
+                            / evaluations as Float
+                    },
+                })
+                .collect(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::policy::Policy;
+    use crate::prelude::*;
+    use crate::test_support::space;
+
+    #[test]
+    fn counters_track_evaluations_and_firing() {
+        let sampler = UniformSampler::default();
+        let mut rs = space();
+        assert!(rs.metrics().is_none());
+        rs.enable_counters(0.5);
+
+        for t in [2.0, 8.0, 9.0] {
+            rs.evaluate(&HashMap::from([("temp", t)]), &sampler)
+                .unwrap();
+        }
+        let lenient = Policy::lenient();
+        rs.evaluate_with_policy(&HashMap::<&str, Float>::new(), &sampler, &lenient)
+            .unwrap();
+
+        let m = rs.metrics().unwrap();
+        assert_eq!(m.evaluations, 4);
+        assert_eq!(m.fallbacks, 1);
+        assert_eq!(m.rules[0].fired, 1);
+        assert_eq!(m.rules[1].fired, 2);
+        // hot fires at 0.2, 0.8, 0.9 and 0.5.
+        assert!((m.rules[1].mean_alpha - 2.4 / 4.0).abs() < 1e-6);
+
+        rs.reset_counters();
+        assert_eq!(rs.metrics().unwrap().evaluations, 0);
+        rs.disable_counters();
+        assert!(rs.metrics().is_none());
+    }
+}
@@ -0,0 +1,147 @@
+// Approximate reasoning with fuzzy facts (compositional rule of inference).
+//
+// `evaluate` takes crisp readings. When an input is only known vaguely ("the
+// temperature is about 5"), Zadeh's compositional rule of inference composes
+// the fact with the relation the rules define: `B'(y) = sup_x min(A'(x), R(x, y))`
+// with the Mamdani relation `R = max_r min(A_r(x), B_r(y))`. Max–min composition
+// distributes over that relation, so each rule fires at the degree its atoms
+// match the facts, `sup_x min(A'(x), A_r(x))`, and the rest of the pipeline is
+// the usual clip-and-max.
+use std::{borrow::Borrow, collections::HashMap, hash::Hash};
+
+use crate::{
+    antecedent::eval_with,
+    error::{self, FuzzyError, MissingSpace},
+    membership::MembershipFn,
+    rulespace::RuleSpace,
+    sampler::{GridCache, UniformSampler},
+    Float,
+};
+
+impl RuleSpace {
+    /// Output sets concluded from fuzzy `facts` about the input variables.
+    ///
+    /// Each fact is sampled on its variable's grid, and an atom `x IS A` holds
+    /// to the height of `fact ∩ A`. A crisp reading is the limiting case of a
+    /// spike fact; wider facts let more rules fire. The result is exact for
+    /// rules joining distinct inputs with AND/OR; NOT and hedges are applied to
+    /// the matching degree. Facts on unknown variables are ignored and a
+    /// missing one fails with `NotFound`, as in `evaluate`. Only term
+    /// consequents are supported; a crisp one fails with `TypeMismatch` (in
+    /// `InRule`).
+    pub fn infer_cri<KI, F>(
+        &self,
+        facts: &HashMap<KI, F>,
+        sampler: &UniformSampler,
+    ) -> error::Result<HashMap<String, Vec<Float>>>
+    where
+        KI: Eq + Hash + Borrow<str>,
+        F: MembershipFn,
+    {
+        self.require_term_consequents()?;
+        let mut grids = GridCache::new();
+        let mut sampled: Vec<Option<(Vec<Float>, Vec<Float>)>> = vec![None; self.vars.len()];
+        for (name, fact) in facts {
+            let Some(v) = self.var_id(name.borrow()) else {
+                continue;
+            };
+            let xs = grids
+                .grid(name.borrow(), self.vars[v.index()].domain(), sampler)?
+                .to_vec();
+            let mu = xs.iter().map(|&x| fact.eval(x)).collect();
+            sampled[v.index()] = Some((xs, mu));
+        }
+
+        let table = self.grid_table(sampler, &mut grids)?;
+        let mut agg = Vec::new();
+        self.reset_agg(&table, &mut agg);
+        let mut scratch = Vec::new();
+
+        if let Some(c) = &self.counters {
+            c.evaluation();
+        }
+        for (i, (rule, compiled)) in self.rules.iter().zip(&self.compiled).enumerate() {
+            let mut atoms = compiled.atoms.iter();
+            let alpha = eval_with(&rule.antecedent, &mut |_, _| {
+                let &(v, t) = atoms.next().ok_or(FuzzyError::BadArity)?;
+                let (xs, fact) =
+                    sampled[v.index()]
+                        .as_ref()
+                        .ok_or_else(|| FuzzyError::NotFound {
+                            space: MissingSpace::Input,
+                            key: self.var_name(v).to_string(),
+                        })?;
+                let term = self.vars[v.index()].term(t).ok_or(FuzzyError::BadArity)?;
+                scratch.resize(xs.len(), 0.0);
+                term.eval_grid(xs, &mut scratch);
+                Ok(fact
+                    .iter()
+                    .zip(&scratch)
+                    .fold(0.0, |sup, (&a, &b)| sup.max(a.min(b))))
+            })? * rule.weight;
+            if let Some(c) = &self.counters {
+                c.rule(i, alpha);
+            }
+            compiled.implicate_into(alpha, &self.vars, &table, &mut agg, &mut scratch, |_| true);
+        }
+
+        Ok(self
+            .outputs
+            .iter()
+            .filter(|&&v| !self.crisp[v.index()])
+            .map(|&v| {
+                (
+                    self.var_name(v).to_string(),
+                    std::mem::take(&mut agg[v.index()]),
+                )
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::defuzz::OutputSet;
+    use crate::error::FuzzyError;
+    use crate::prelude::*;
+    use crate::test_support::space;
+
+    #[test]
+    fn fuzzy_facts_fire_rules_by_matching_degree() {
+        let rs = space();
+        let sampler = UniformSampler::default();
+        let xs = sampler.sample(0.0, 100.0).unwrap();
+
+        // "About 5" meets cold = 1 - x/10 where (x - 3)/2 = 1 - x/10, at
+        // x = 25/6, so both rules fire at 7/12 instead of the crisp 0.5.
+        let about_five = HashMap::from([("temp", Triangular::new(3.0, 5.0, 7.0).unwrap())]);
+        let sets = rs.infer_cri(&about_five, &sampler).unwrap();
+        let set = OutputSet::new(&xs, &sets["fan"]).unwrap();
+        assert!((set.height() - 7.0 / 12.0).abs() < 0.01, "{}", set.height());
+        assert!((set.centroid() - 50.0).abs() < 1e-3);
+
+        // Knowing nothing lets every rule fire fully.
+        let unknown = HashMap::from([("temp", Rectangular::new(-1.0, 11.0).unwrap())]);
+        let sets = rs.infer_cri(&unknown, &sampler).unwrap();
+        assert!((sets["fan"][0] - 1.0).abs() < 1e-6);
+        assert!((sets["fan"][xs.len() - 1] - 1.0).abs() < 1e-6);
+
+        let nothing: HashMap<&str, Triangular> = HashMap::new();
+        assert!(matches!(
+            rs.infer_cri(&nothing, &sampler),
+            Err(FuzzyError::NotFound { .. })
+        ));
+    }
+
+    #[test]
+    fn crisp_consequents_are_rejected() {
+        let rs = crate::test_support::crisp_space();
+        let fact = Triangular::new(6.0, 8.0, 10.0).unwrap();
+        let err = rs
+            .infer_cri(&HashMap::from([("temp", fact)]), &UniformSampler::default())
+            .unwrap_err();
+        assert!(crate::test_support::rejects_crisp(&err));
+    }
+}
@@ -0,0 +1,406 @@
+// Datasets for the learning subsystem.
+//
+// A `Dataset` is a table of named input and target columns. It loads from CSV,
+// splits into train/test sets, rescales through a `Scaler`, and converts into the
+// row formats the learners take: `LabeledRow`s for `RuleSpace::tune`/`score`,
+// `(inputs, target)` pairs for `Anfis::fit` and plain points for clustering.
+use std::{
+    collections::HashMap,
+    fmt,
+    io::{self, BufRead},
+};
+
+use crate::{
+    error::{FuzzyError, MissingSpace},
+    rng::Rng,
+    tune::LabeledRow,
+    Float,
+};
+
+/// Errors from loading a dataset.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum DataError {
+    /// Line `line` (1-based) is malformed.
+    Parse {
+        line: usize,
+        message: String,
+    },
+    Io(io::Error),
+    Fuzzy(FuzzyError),
+}
+
+impl fmt::Display for DataError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DataError::Parse { line, message } => write!(f, "line {line}: {message}"),
+            DataError::Io(e) => write!(f, "I/O error: {e}"),
+            DataError::Fuzzy(e) => write!(f, "{e}"),
+        }
+    }
+}
+
+impl std::error::Error for DataError {}
+
+impl From<io::Error> for DataError {
+    fn from(e: io::Error) -> Self {
+        DataError::Io(e)
+    }
+}
+
+impl From<FuzzyError> for DataError {
+    fn from(e: FuzzyError) -> Self {
+        DataError::Fuzzy(e)
+    }
+}
+
+/// Rows of named input and target values.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Dataset {
+    inputs: Vec<String>,
+    targets: Vec<String>,
+    x: Vec<Vec<Float>>,
+    y: Vec<Vec<Float>>,
+}
+
+impl Dataset {
+    /// An empty dataset with the given column names.
+    pub fn new<S: Into<String>>(
+        inputs: impl IntoIterator<Item = S>,
+        targets: impl IntoIterator<Item = S>,
+    ) -> Self {
+        Self {
+            inputs: inputs.into_iter().map(Into::into).collect(),
+            targets: targets.into_iter().map(Into::into).collect(),
+            x: Vec::new(),
+            y: Vec::new(),
+        }
+    }
+
+    /// Appends a row; fails with `BadArity` if a slice does not match its columns.
+    pub fn push(&mut self, x: &[Float], y: &[Float]) -> Result<(), FuzzyError> {
+        if x.len() != self.inputs.len() || y.len() != self.targets.len() {
+            return Err(FuzzyError::BadArity);
+        }
+        self.x.push(x.to_vec());
+        self.y.push(y.to_vec());
+        Ok(())
+    }
+
+    /// Reads comma-separated values with a header row. Columns named in
+    /// `targets` become targets, every other column an input.
+    ///
+    /// Blank lines are skipped and fields are trimmed; quoting is not supported.
+    /// A target missing from the header fails with `NotFound`.
+    pub fn from_csv<R: BufRead>(reader: R, targets: &[&str]) -> Result<Self, DataError> {
+        let mut lines = reader.lines().enumerate();
+        let header = loop {
+            match lines.next() {
+                Some((_, line)) => {
+                    let line = line?;
+                    if !line.trim().is_empty() {
+                        break line;
+                    }
+                }
+                None => return Err(FuzzyError::EmptyInput.into()),
+            }
+        };
+        let columns: Vec<&str> = header.split(',').map(str::trim).collect();
+        if let Some(missing) = targets.iter().find(|t| !columns.contains(t)) {
+            return Err(FuzzyError::NotFound {
+                space: MissingSpace::Var,
+                key: missing.to_string(),
+            }
+            .into());
+        }
+        let is_target: Vec<bool> = columns.iter().map(|c| targets.contains(c)).collect();
+        let mut ds = Dataset::new(
+            columns
+                .iter()
+                .zip(&is_target)
+                .filter(|(_, &t)| !t)
+                .map(|(c, _)| *c),
+            targets.iter().copied(),
+        );
+        let target_pos: Vec<usize> = targets
+            .iter()
+            .map(|t| columns.iter().position(|c| c == t).expect("checked above"))
+            .collect();
+
+        for (i, line) in lines {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+            let parse_error = |message: String| DataError::Parse {
+                line: i + 1,
+                message,
+            };
+            if fields.len() != columns.len() {
+                return Err(parse_error(format!(
+                    "expected {} fields, found {}",
+                    columns.len(),
+                    fields.len()
+                )));
+            }
+            let values = fields
+                .iter()
+                .zip(&columns)
+                .map(|(f, c)| {
+                    f.parse::<Float>()
+                        .map_err(|_| parse_error(format!("column `{c}`: `{f}` is not a number")))
+                })
+                .collect::<Result<Vec<Float>, _>>()?;
+            let x: Vec<Float> = values
+                .iter()
+                .zip(&is_target)
+                .filter(|(_, &t)| !t)
+                .map(|(v, _)| *v)
+                .collect();
+            let y: Vec<Float> = target_pos.iter().map(|&p| values[p]).collect();
+            ds.push(&x, &y)?;
+        }
+        Ok(ds)
+    }
+
+    /// Writes the rows as CSV that `from_csv` reads back: a header row, then
+    /// inputs followed by targets.
+    pub fn write_csv<W: io::Write>(&self, mut w: W) -> io::Result<()> {
+        let header: Vec<&str> = self
+            .inputs
+            .iter()
+            .chain(&self.targets)
+            .map(String::as_str)
+            .collect();
+        writeln!(w, "{}", header.join(","))?;
+        for (x, y) in self.x.iter().zip(&self.y) {
+            let fields: Vec<String> = x.iter().chain(y).map(|v| v.to_string()).collect();
+            writeln!(w, "{}", fields.join(","))?;
+        }
+        Ok(())
+    }
+
+    pub fn inputs(&self) -> &[String] {
+        &self.inputs
+    }
+
+    pub fn targets(&self) -> &[String] {
+        &self.targets
+    }
+
+    /// Number of rows.
+    pub fn len(&self) -> usize {
+        self.x.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.x.is_empty()
+    }
+
+    /// Input and target values of row `i`.
+    pub fn row(&self, i: usize) -> Option<(&[Float], &[Float])> {
+        Some((self.x.get(i)?, self.y.get(i)?))
+    }
+
+    /// Rows as `LabeledRow`s for `RuleSpace::tune` and `RuleSpace::score`.
+    pub fn labeled(&self) -> Vec<LabeledRow<String>> {
+        let named = |names: &[String], values: &[Float]| -> HashMap<String, Float> {
+            names.iter().cloned().zip(values.iter().copied()).collect()
+        };
+        self.x
+            .iter()
+            .zip(&self.y)
+            .map(|(x, y)| (named(&self.inputs, x), named(&self.targets, y)))
+            .collect()
+    }
+
+    /// `(inputs, target)` pairs for `Anfis::fit`; fails with `NotFound` for an
+    /// unknown target.
+    pub fn samples(&self, target: &str) -> Result<Vec<(Vec<Float>, Float)>, FuzzyError> {
+        let t = self
+            .targets
+            .iter()
+            .position(|n| n == target)
+            .ok_or_else(|| FuzzyError::NotFound {
+                space: MissingSpace::Var,
+                key: target.to_string(),
+            })?;
+        Ok(self
+            .x
+            .iter()
+            .zip(&self.y)
+            .map(|(x, y)| (x.clone(), y[t]))
+            .collect())
+    }
+
+    /// Input rows alone, e.g. for `cluster::fuzzy_c_means`.
+    pub fn points(&self) -> &[Vec<Float>] {
+        &self.x
+    }
+
+    /// Shuffles the rows with `seed` and moves `test_fraction` of them (rounded)
+    /// into a second dataset, returning `(train, test)`.
+    ///
+    /// Fails with `OutOfBounds` unless `test_fraction` lies in `[0, 1]`.
+    pub fn split(&self, test_fraction: Float, seed: u64) -> Result<(Self, Self), FuzzyError> {
+        if !(0.0..=1.0).contains(&test_fraction) {
+            return Err(FuzzyError::OutOfBounds);
+        }
+        let mut order: Vec<usize> = (0..self.len()).collect();
+        Rng::new(seed).shuffle(&mut order);
+        let n_test = (test_fraction * self.len() as Float).round() as usize;
+        let pick = |rows: &[usize]| Self {
+            inputs: self.inputs.clone(),
+            targets: self.targets.clone(),
+            x: rows.iter().map(|&i| self.x[i].clone()).collect(),
+            y: rows.iter().map(|&i| self.y[i].clone()).collect(),
+        };
+        Ok((pick(&order[n_test..]), pick(&order[..n_test])))
+    }
+}
+
+/// Affine rescaling of every column, `x -> (x - offset) / scale`, with inputs
+/// first and targets after them.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Scaler {
+    pub offset: Vec<Float>,
+    pub scale: Vec<Float>,
+}
+
+impl Scaler {
+    /// Maps each column of `ds` onto `[0, 1]`. Constant columns are only shifted.
+    pub fn min_max(ds: &Dataset) -> Self {
+        Self::fit(ds, |col| {
+            let min = col.iter().copied().fold(Float::INFINITY, Float::min);
+            let max = col.iter().copied().fold(Float::NEG_INFINITY, Float::max);
+            (min, max - min)
+        })
+    }
+
+    /// Gives each column of `ds` zero mean and unit variance. Constant columns are
+    /// only shifted.
+    pub fn standard(ds: &Dataset) -> Self {
+        Self::fit(ds, |col| {
+            let n = col.len() as Float;
+            let mean = col.iter().sum::<Float>() / n;
+            let var = col.iter().map(|v| (v - mean) * (v - mean)).sum::<Float>() / n;
+            (mean, var.sqrt())
+        })
+    }
+
+    fn fit(ds: &Dataset, stat: impl Fn(&[Float]) -> (Float, Float)) -> Self {
+        let width = ds.inputs.len() + ds.targets.len();
+        let (offset, scale) = (0..width)
+            .map(|c| {
+                let col: Vec<Float> =
+                    ds.x.iter()
+                        .zip(&ds.y)
+                        .map(|(x, y)| column(x, y, c))
+                        .collect();
+                if col.is_empty() {
+                    return (0.0, 1.0);
+                }
+                let (offset, scale) = stat(&col);
+                (offset, if scale > 0.0 { scale } else { 1.0 })
+            })
+            .unzip();
+        Self { offset, scale }
+    }
+
+    /// Rescales `ds` in place; fails with `BadArity` if the column counts differ.
+    pub fn transform(&self, ds: &mut Dataset) -> Result<(), FuzzyError> {
+        self.map(ds, |v, o, s| (v - o) / s)
+    }
+
+    /// Undoes `transform`.
+    pub fn inverse(&self, ds: &mut Dataset) -> Result<(), FuzzyError> {
+        self.map(ds, |v, o, s| v * s + o)
+    }
+
+    fn map(
+        &self,
+        ds: &mut Dataset,
+        f: impl Fn(Float, Float, Float) -> Float,
+    ) -> Result<(), FuzzyError> {
+        if self.offset.len() != ds.inputs.len() + ds.targets.len() {
+            return Err(FuzzyError::BadArity);
+        }
+        for (x, y) in ds.x.iter_mut().zip(&mut ds.y) {
+            for (c, v) in x.iter_mut().chain(y.iter_mut()).enumerate() {
+                *v = f(*v, self.offset[c], self.scale[c]);
+            }
+        }
+        Ok(())
+    }
+}
+
+fn column(x: &[Float], y: &[Float], c: usize) -> Float {
+    if c < x.len() {
+        x[c]
+    } else {
+        y[c - x.len()]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CSV: &str = "temp, humidity, fan\n1, 40, 10\n\n5,50,55\n9,60,90\n";
+
+    #[test]
+    fn csv_loads_and_converts() {
+        let ds = Dataset::from_csv(CSV.as_bytes(), &["fan"]).unwrap();
+        assert_eq!(ds.inputs(), ["temp", "humidity"]);
+        assert_eq!(ds.targets(), ["fan"]);
+        assert_eq!(ds.len(), 3);
+        assert_eq!(ds.row(1), Some((&[5.0, 50.0][..], &[55.0][..])));
+
+        let labeled = ds.labeled();
+        assert_eq!(labeled[2].0["humidity"], 60.0);
+        assert_eq!(labeled[2].1["fan"], 90.0);
+        assert_eq!(ds.samples("fan").unwrap()[0], (vec![1.0, 40.0], 10.0));
+        assert!(matches!(
+            ds.samples("nope"),
+            Err(FuzzyError::NotFound { .. })
+        ));
+
+        let err = Dataset::from_csv("a,b\n1,x\n".as_bytes(), &["b"]).unwrap_err();
+        assert!(matches!(err, DataError::Parse { line: 2, .. }));
+        let err = Dataset::from_csv("a,b\n1,2\n".as_bytes(), &["c"]).unwrap_err();
+        assert!(matches!(err, DataError::Fuzzy(FuzzyError::NotFound { .. })));
+    }
+
+    #[test]
+    fn split_and_scale() {
+        let mut ds = Dataset::new(["x"], ["y"]);
+        for i in 0..10 {
+            ds.push(&[i as Float], &[2.0 * i as Float]).unwrap();
+        }
+        assert_eq!(ds.push(&[1.0, 2.0], &[0.0]), Err(FuzzyError::BadArity));
+
+        let (train, test) = ds.split(0.3, 7).unwrap();
+        assert_eq!((train.len(), test.len()), (7, 3));
+        let mut seen: Vec<Float> = train
+            .points()
+            .iter()
+            .chain(test.points())
+            .map(|p| p[0])
+            .collect();
+        seen.sort_by(Float::total_cmp);
+        assert_eq!(seen, (0..10).map(|i| i as Float).collect::<Vec<_>>());
+
+        let original = ds.clone();
+        let scaler = Scaler::min_max(&ds);
+        scaler.transform(&mut ds).unwrap();
+        assert_eq!(ds.row(9), Some((&[1.0][..], &[1.0][..])));
+        scaler.inverse(&mut ds).unwrap();
+        assert_eq!(ds, original);
+
+        let standard = Scaler::standard(&ds);
+        standard.transform(&mut ds).unwrap();
+        let mean: Float = ds.points().iter().map(|p| p[0]).sum::<Float>() / 10.0;
+        assert!(mean.abs() < 1e-6);
+    }
+}
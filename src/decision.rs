@@ -0,0 +1,238 @@
+// Utility-based decision making.
+//
+// Each candidate action owns a rule space that scores its desirability from
+// world-state inputs. `Decision` evaluates every action against the same state,
+// ranks them, and picks either the best or a softmax-weighted random one, the
+// usual fuzzy utility-AI pattern for game agents.
+use std::{borrow::Borrow, collections::HashMap, hash::Hash};
+
+use crate::{
+    error::{self, FuzzyError, MissingSpace},
+    rulespace::{RuleSpace, Scratch},
+    sampler::UniformSampler,
+    Float,
+};
+
+struct Action {
+    name: String,
+    space: RuleSpace,
+    output: String,
+    floor: Float,
+    scratch: Scratch,
+}
+
+/// A set of scored candidate actions.
+pub struct Decision {
+    sampler: UniformSampler,
+    actions: Vec<Action>,
+}
+
+impl Decision {
+    pub fn new(sampler: &UniformSampler) -> Self {
+        Self {
+            sampler: sampler.clone(),
+            actions: Vec::new(),
+        }
+    }
+
+    /// Adds action `name`, scored by output `output` of `space`; fails with
+    /// `NotFound` if no rule of `space` concludes on `output`.
+    pub fn with_action(
+        mut self,
+        name: &str,
+        space: RuleSpace,
+        output: &str,
+    ) -> error::Result<Self> {
+        if !space.outputs().any(|n| n == output) {
+            return Err(FuzzyError::NotFound {
+                space: MissingSpace::Var,
+                key: output.to_string(),
+            });
+        }
+        let floor = space.var(output).expect("outputs are variables").domain().0;
+        self.actions.push(Action {
+            name: name.to_string(),
+            scratch: space.scratch(&self.sampler)?,
+            space,
+            output: output.to_string(),
+            floor,
+        });
+        Ok(self)
+    }
+
+    /// Scores every action for `world`, best first (ties keep insertion order).
+    ///
+    /// An action none of whose rules fire scores the minimum of its output
+    /// domain. State keys an action does not read are ignored.
+    pub fn rank<KI>(&mut self, world: &HashMap<KI, Float>) -> error::Result<Vec<(String, Float)>>
+    where
+        KI: Eq + Hash + Borrow<str>,
+    {
+        let mut scores = Vec::with_capacity(self.actions.len());
+        for a in &mut self.actions {
+            let y = a.space.evaluate_in(world, &mut a.scratch)?[&a.output];
+            scores.push((a.name.clone(), if y.is_nan() { a.floor } else { y }));
+        }
+        scores.sort_by(|a, b| b.1.total_cmp(&a.1));
+        Ok(scores)
+    }
+
+    /// The highest-scoring action, or `None` if no action was added.
+    pub fn best<KI>(&mut self, world: &HashMap<KI, Float>) -> error::Result<Option<(String, Float)>>
+    where
+        KI: Eq + Hash + Borrow<str>,
+    {
+        Ok(self.rank(world)?.into_iter().next())
+    }
+
+    /// Picks an action at random with softmax probabilities `exp(score / temperature)`.
+    ///
+    /// `r` is a uniform random number in `[0, 1)` from the caller's generator, so
+    /// selection stays reproducible under the game's own seeding. Low temperatures
+    /// approach `best`; high ones approach a uniform pick. Fails with
+    /// `OutOfBounds` unless `temperature` is positive and `EmptyInput` if no
+    /// action was added.
+    pub fn choose<KI>(
+        &mut self,
+        world: &HashMap<KI, Float>,
+        temperature: Float,
+        r: Float,
+    ) -> error::Result<String>
+    where
+        KI: Eq + Hash + Borrow<str>,
+    {
+        if temperature.is_nan() || temperature <= 0.0 {
+            return Err(FuzzyError::OutOfBounds);
+        }
+        let ranked = self.rank(world)?;
+        let probs = softmax(
+            &ranked.iter().map(|(_, s)| *s).collect::<Vec<_>>(),
+            temperature,
+        );
+        let mut acc = 0.0;
+        for ((name, _), p) in ranked.iter().zip(&probs) {
+            acc += p;
+            if r < acc {
+                return Ok(name.clone());
+            }
+        }
+        ranked
+            .into_iter()
+            .last()
+            .map(|(name, _)| name)
+            .ok_or(FuzzyError::EmptyInput)
+    }
+}
+
+/// Softmax of `scores` at `temperature`, shifted by the maximum for stability.
+pub fn softmax(scores: &[Float], temperature: Float) -> Vec<Float> {
+    let max = scores.iter().copied().fold(Float::NEG_INFINITY, Float::max);
+    let exp: Vec<Float> = scores
+        .iter()
+        .map(|s| ((s - max) / temperature).exp())
+        .collect();
+    let total: Float = exp.iter().sum();
+    exp.into_iter().map(|e| e / total).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+    use crate::antecedent::Antecedent;
+    use crate::mamdani::{Consequent, Rule};
+    use crate::prelude::*;
+    use crate::variable::Variable;
+
+    /// Desirability in [0, 100] rising (`eager`) or falling with `state`.
+    fn utility(state: &'static str, eager: bool) -> RuleSpace {
+        let mut input = Variable::new(0.0, 1.0).unwrap();
+        input
+            .insert_term(
+                "low",
+                Term::new("low", Triangular::new(-1.0, 0.0, 1.0).unwrap()),
+            )
+            .unwrap();
+        input
+            .insert_term(
+                "high",
+                Term::new("high", Triangular::new(0.0, 1.0, 2.0).unwrap()),
+            )
+            .unwrap();
+        let mut score = Variable::new(0.0, 100.0).unwrap();
+        score
+            .insert_term(
+                "bad",
+                Term::new("bad", Triangular::new(-100.0, 0.0, 100.0).unwrap()),
+            )
+            .unwrap();
+        score
+            .insert_term(
+                "good",
+                Term::new("good", Triangular::new(0.0, 100.0, 200.0).unwrap()),
+            )
+            .unwrap();
+        let (a, b) = if eager {
+            ("bad", "good")
+        } else {
+            ("good", "bad")
+        };
+        let rule = |t: &'static str, s: &'static str| Rule {
+            antecedent: Antecedent::Atom {
+                var: state.into(),
+                term: t.into(),
+            },
+            consequent: vec![Consequent::Term {
+                var: "score".into(),
+                term: s.into(),
+                hedge: None,
+            }],
+            weight: 1.0,
+        };
+        let vars = HashMap::from([(state.to_string(), input), ("score".to_string(), score)]);
+        RuleSpace::new(vars, vec![rule("low", a), rule("high", b)]).unwrap()
+    }
+
+    fn agent() -> Decision {
+        Decision::new(&UniformSampler::default())
+            .with_action("attack", utility("health", true), "score")
+            .unwrap()
+            .with_action("flee", utility("health", false), "score")
+            .unwrap()
+            .with_action("reload", utility("ammo", false), "score")
+            .unwrap()
+    }
+
+    #[test]
+    fn ranks_actions_by_desirability() {
+        let mut d = agent();
+        let world = HashMap::from([("health", 0.9), ("ammo", 0.6)]);
+        let ranked = d.rank(&world).unwrap();
+        let names: Vec<&str> = ranked.iter().map(|(n, _)| n.as_str()).collect();
+        assert_eq!(names, vec!["attack", "reload", "flee"]);
+        assert_eq!(d.best(&world).unwrap().unwrap().0, "attack");
+
+        let low = HashMap::from([("health", 0.1), ("ammo", 0.95)]);
+        assert_eq!(d.best(&low).unwrap().unwrap().0, "flee");
+    }
+
+    #[test]
+    fn softmax_choice_follows_the_scores() {
+        let mut d = agent();
+        let world = HashMap::from([("health", 0.9), ("ammo", 0.6)]);
+        // Near-zero temperature always picks the best action.
+        assert_eq!(d.choose(&world, 1e-3, 0.99).unwrap(), "attack");
+        // Very high temperature spreads picks evenly in rank order.
+        assert_eq!(d.choose(&world, 1e6, 0.5).unwrap(), "reload");
+        assert_eq!(d.choose(&world, 1e6, 0.9).unwrap(), "flee");
+
+        let p = softmax(&[1.0, 2.0, 3.0], 1.0);
+        assert!((p.iter().sum::<Float>() - 1.0).abs() < 1e-6);
+        assert!(p[2] > p[1] && p[1] > p[0]);
+        assert!(matches!(
+            d.choose(&world, 0.0, 0.5),
+            Err(FuzzyError::OutOfBounds)
+        ));
+    }
+}
@@ -1,5 +1,5 @@
 // Defuzzification utilities for collapsing aggregated membership values.
-use crate::{error::MissingSpace, prelude::*, variable::Variable};
+use crate::{error::MissingSpace, prelude::*, sampler::GridCache, variable::Variable};
 use std::{borrow::Borrow, collections::HashMap, hash::Hash};
 
 /// Defuzzify aggregated membership samples using the centroid of area method.
@@ -7,35 +7,240 @@ pub fn defuzzification<KV>(
     agg_memberships: &HashMap<String, Vec<Float>>,
     vars: &HashMap<KV, Variable>,
 ) -> Result<HashMap<String, Float>>
+where
+    KV: Eq + Hash + Borrow<str>,
+{
+    defuzzification_cached(agg_memberships, vars, &mut GridCache::new())
+}
+
+/// Same as `defuzzification`, but reuses sampled domain grids from `grids`.
+pub fn defuzzification_cached<KV>(
+    agg_memberships: &HashMap<String, Vec<Float>>,
+    vars: &HashMap<KV, Variable>,
+    grids: &mut GridCache,
+) -> Result<HashMap<String, Float>>
 where
     KV: Eq + Hash + Borrow<str>,
 {
     let mut result_map: HashMap<String, Float> = HashMap::new();
+    defuzzification_into(agg_memberships, vars, grids, &mut result_map)?;
+    Ok(result_map)
+}
+
+/// Defuzzify into an existing map, overwriting the values of keys already present.
+///
+/// Keys not produced by this call are left untouched, so a map reused across calls
+/// stops allocating once it holds every output variable.
+pub fn defuzzification_into<KV>(
+    agg_memberships: &HashMap<String, Vec<Float>>,
+    vars: &HashMap<KV, Variable>,
+    grids: &mut GridCache,
+    out: &mut HashMap<String, Float>,
+) -> Result<()>
+where
+    KV: Eq + Hash + Borrow<str>,
+{
     for (i, j) in agg_memberships {
         let num = j.len();
         if num < 2 {
             return Err(FuzzyError::BadArity);
         }
 
-        let (var_min, var_max) = vars
-            .get(&i)
+        let domain = vars
+            .get(i.as_str())
             .ok_or(FuzzyError::NotFound {
                 space: MissingSpace::Var,
                 key: i.to_string(),
             })?
             .domain();
-        let step = (var_max - var_min) / (num as Float - 1.0);
-
-        let (mut sum_agg_memberships_x, mut sum_agg_memberships): (Float, Float) = (0.0, 0.0);
-        let mut l: usize = 0;
-        for k in j {
-            let x = var_min + step * l as Float;
-            sum_agg_memberships_x = sum_agg_memberships_x + (x * k);
-            sum_agg_memberships = sum_agg_memberships + k;
-            l += 1;
+        let xs = grids.grid(i, domain, &UniformSampler::new(num)?)?;
+
+        let y = centroid(xs, j);
+        match out.get_mut(i) {
+            Some(slot) => *slot = y,
+            None => {
+                out.insert(i.to_string(), y);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Method collapsing an aggregated output set into one crisp value.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Defuzz {
+    /// Centre of gravity, see `centroid`.
+    #[default]
+    Centroid,
+    /// The point splitting the area under the set into two equal halves.
+    Bisector,
+    /// Mean of the points where the set reaches its height.
+    MeanOfMaxima,
+    /// Smallest point where the set reaches its height.
+    SmallestOfMaxima,
+    /// Largest point where the set reaches its height.
+    LargestOfMaxima,
+}
+
+impl Defuzz {
+    /// Crisp value of the set `mu` sampled at `xs`; NaN for an empty set, like `centroid`.
+    pub fn apply(self, xs: &[Float], mu: &[Float]) -> Float {
+        let height = mu.iter().copied().fold(0.0, Float::max);
+        if height <= 0.0 {
+            return Float::NAN;
+        }
+        let maxima = || {
+            xs.iter()
+                .zip(mu)
+                .filter(move |&(_, &m)| m == height)
+                .map(|(&x, _)| x)
+        };
+        match self {
+            Defuzz::Centroid => centroid(xs, mu),
+            Defuzz::Bisector => {
+                let half = mu.iter().sum::<Float>() / 2.0;
+                let mut acc = 0.0;
+                for (&x, &m) in xs.iter().zip(mu) {
+                    acc += m;
+                    if acc >= half {
+                        return x;
+                    }
+                }
+                Float::NAN
+            }
+            Defuzz::MeanOfMaxima => {
+                let (sum, n) = maxima().fold((0.0, 0.0), |(s, n), x| (s + x, n + 1.0));
+                sum / n
+            }
+            Defuzz::SmallestOfMaxima => maxima().fold(Float::INFINITY, Float::min),
+            Defuzz::LargestOfMaxima => maxima().fold(Float::NEG_INFINITY, Float::max),
         }
-        result_map.insert(i.to_string(), sum_agg_memberships_x / sum_agg_memberships);
     }
+}
+
+/// Centroid of the membership samples `mu` taken at the points `xs`.
+pub fn centroid(xs: &[Float], mu: &[Float]) -> Float {
+    let (mut sum_agg_memberships_x, mut sum_agg_memberships): (Float, Float) = (0.0, 0.0);
+    for (x, k) in xs.iter().zip(mu) {
+        sum_agg_memberships_x += x * k;
+        sum_agg_memberships += k;
+    }
+    sum_agg_memberships_x / sum_agg_memberships
+}
 
-    return Ok(result_map);
+/// Like `centroid`, but fails with `DivisionByZero` instead of returning NaN when
+/// the set is empty (no membership above zero).
+pub fn try_centroid(xs: &[Float], mu: &[Float]) -> Result<Float> {
+    if !mu.iter().any(|&m| m > 0.0) {
+        return Err(FuzzyError::DivisionByZero { stage: "centroid" });
+    }
+    Ok(centroid(xs, mu))
+}
+
+/// Shape statistics of an aggregated output set sampled on a grid, for callers
+/// that need more than one crisp value (see `RuleSpace::output_set`).
+#[derive(Debug, Clone, Copy)]
+pub struct OutputSet<'a> {
+    xs: &'a [Float],
+    mu: &'a [Float],
+}
+
+impl<'a> OutputSet<'a> {
+    /// The set `mu` sampled at the points `xs`; fails with `BadArity` when the
+    /// lengths differ.
+    pub fn new(xs: &'a [Float], mu: &'a [Float]) -> Result<Self> {
+        if xs.len() != mu.len() {
+            return Err(FuzzyError::BadArity);
+        }
+        Ok(Self { xs, mu })
+    }
+
+    /// Largest membership in the set; 0 when no rule fired.
+    pub fn height(&self) -> Float {
+        self.mu.iter().copied().fold(0.0, Float::max)
+    }
+
+    /// Area under the set by the trapezoidal rule over the sample points.
+    pub fn area(&self) -> Float {
+        self.xs
+            .windows(2)
+            .zip(self.mu.windows(2))
+            .map(|(x, m)| (x[1] - x[0]) * (m[0] + m[1]) / 2.0)
+            .sum()
+    }
+
+    /// Centre of gravity, NaN for an empty set (see `centroid`).
+    pub fn centroid(&self) -> Float {
+        centroid(self.xs, self.mu)
+    }
+
+    /// Membership-weighted standard deviation around the centroid: 0 for a
+    /// single spike, growing as the conclusion spreads over the domain.
+    pub fn spread(&self) -> Float {
+        let c = self.centroid();
+        let (mut num, mut den): (Float, Float) = (0.0, 0.0);
+        for (&x, &m) in self.xs.iter().zip(self.mu) {
+            num += m * (x - c) * (x - c);
+            den += m;
+        }
+        (num / den).sqrt()
+    }
+
+    /// Smallest and largest sample point with membership at least `alpha`, or
+    /// `None` when the α-cut is empty.
+    pub fn alpha_cut(&self, alpha: Float) -> Option<(Float, Float)> {
+        let mut points = self
+            .xs
+            .iter()
+            .zip(self.mu)
+            .filter(|&(_, &m)| m >= alpha && m > 0.0)
+            .map(|(&x, _)| x);
+        let first = points.next()?;
+        Some(points.fold((first, first), |(lo, hi), x| (lo.min(x), hi.max(x))))
+    }
+
+    /// Width of the α-cut, 0 when it is empty.
+    pub fn alpha_width(&self, alpha: Float) -> Float {
+        self.alpha_cut(alpha).map_or(0.0, |(lo, hi)| hi - lo)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::OutputSet;
+    use crate::error::FuzzyError;
+    use crate::Float;
+
+    #[test]
+    fn output_set_statistics() {
+        let xs: Vec<Float> = (0..=10).map(|i| i as Float).collect();
+        // A triangle on [2, 8] peaking at 5, clipped at 0.6.
+        let mu: Vec<Float> = xs
+            .iter()
+            .map(|&x| (1.0 - (x - 5.0).abs() / 3.0).clamp(0.0, 0.6))
+            .collect();
+        let set = OutputSet::new(&xs, &mu).unwrap();
+        assert!((set.height() - 0.6).abs() < 1e-5);
+        assert!((set.centroid() - 5.0).abs() < 1e-5);
+        assert!((set.area() - 37.0 / 15.0).abs() < 1e-5, "{}", set.area());
+        assert_eq!(set.alpha_cut(0.6), Some((4.0, 6.0)));
+        assert_eq!(set.alpha_width(0.3), 4.0);
+        assert_eq!(set.alpha_width(0.7), 0.0);
+
+        let spike = [0.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 0.0];
+        let spike = OutputSet::new(&xs, &spike).unwrap();
+        assert_eq!(spike.spread(), 0.0);
+        assert!(set.spread() > 1.0 && set.spread() < 2.0);
+
+        let empty = OutputSet::new(&xs, &[0.0; 11]).unwrap();
+        assert_eq!(empty.height(), 0.0);
+        assert_eq!(empty.alpha_cut(0.0), None);
+        assert!(empty.centroid().is_nan());
+        assert!(matches!(
+            OutputSet::new(&xs, &[0.0; 3]),
+            Err(FuzzyError::BadArity)
+        ));
+    }
 }
@@ -2,10 +2,12 @@
 use std::error::Error;
 use std::fmt;
 
+use crate::Float;
+
 //Basic Result-Type Definition For the functions in the library
 pub type Result<T> = std::result::Result<T, FuzzyError>;
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq)]
 #[non_exhaustive]
 ///Basic errors that can occur in the rust-fuzzylogic library
 pub enum FuzzyError {
@@ -13,7 +15,58 @@ pub enum FuzzyError {
     EmptyInput,
     TypeMismatch,
     OutOfBounds,
-    NotFound { space: MissingSpace, key: String },
+    NotFound {
+        space: MissingSpace,
+        key: String,
+    },
+    /// A membership function parameter violates the shape's constraints.
+    InvalidParameter {
+        mf: &'static str,
+        field: &'static str,
+        value: Float,
+    },
+    /// A rule names a term its variable does not have.
+    UnknownTerm {
+        var: String,
+        term: String,
+    },
+    /// A term was inserted under a name its variable already uses.
+    DuplicateTerm {
+        var: String,
+        term: String,
+    },
+    /// Input `value` of `var` lies outside the variable's domain (or is NaN).
+    OutOfDomain {
+        var: String,
+        value: Float,
+    },
+    /// A quotient had a zero denominator, e.g. the centroid of an empty set.
+    DivisionByZero {
+        stage: &'static str,
+    },
+    /// Input `var` was NaN or infinite.
+    NonFinite {
+        var: String,
+        value: Float,
+    },
+    /// A `Scratch` or `Incremental` state was used with a rule space other
+    /// than the one it was created for, or after that space changed.
+    ScratchMismatch,
+    /// `source` was raised while processing the rule at index `rule`.
+    InRule {
+        rule: usize,
+        source: Box<FuzzyError>,
+    },
+}
+
+impl FuzzyError {
+    /// The innermost error, looking through `InRule` wrappers.
+    pub fn root(&self) -> &FuzzyError {
+        match self {
+            FuzzyError::InRule { source, .. } => source.root(),
+            e => e,
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
@@ -47,11 +100,42 @@ impl fmt::Display for FuzzyError {
                     }
                 )
             }
+            FuzzyError::InvalidParameter { mf, field, value } => {
+                write!(f, "Invalid parameter. {mf}.{field} = {value}")
+            }
+            FuzzyError::UnknownTerm { var, term } => {
+                write!(f, "Unknown term. {var} has no term {term}")
+            }
+            FuzzyError::DuplicateTerm { var, term } => {
+                write!(f, "Duplicate term. {var} already has a term {term}")
+            }
+            FuzzyError::OutOfDomain { var, value } => {
+                write!(f, "Out of domain. {var} = {value}")
+            }
+            FuzzyError::DivisionByZero { stage } => {
+                write!(f, "Division by zero in {stage}")
+            }
+            FuzzyError::NonFinite { var, value } => {
+                write!(f, "Non-finite input. {var} = {value}")
+            }
+            FuzzyError::ScratchMismatch => {
+                write!(f, "Scratch mismatch. buffers belong to another rule space")
+            }
+            FuzzyError::InRule { rule, source } => {
+                write!(f, "Rule {rule}: {source}")
+            }
         }
     }
 }
 
-impl Error for FuzzyError {}
+impl Error for FuzzyError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            FuzzyError::InRule { source, .. } => Some(source.as_ref()),
+            _ => None,
+        }
+    }
+}
 
 //Basic Unit Tests
 #[cfg(test)]
@@ -64,4 +148,30 @@ mod tests {
         assert_eq!(FuzzyError::TypeMismatch.to_string(), "Invalid type input");
         assert_eq!(FuzzyError::OutOfBounds.to_string(), "Out of bounds");
     }
+
+    #[test]
+    fn contextual_errors_name_their_origin() {
+        let e = FuzzyError::InRule {
+            rule: 3,
+            source: Box::new(FuzzyError::UnknownTerm {
+                var: "temp".into(),
+                term: "warm".into(),
+            }),
+        };
+        assert_eq!(e.to_string(), "Rule 3: Unknown term. temp has no term warm");
+        assert!(matches!(e.root(), FuzzyError::UnknownTerm { .. }));
+        assert_eq!(
+            FuzzyError::InvalidParameter {
+                mf: "Gaussian",
+                field: "sd",
+                value: -1.0
+            }
+            .to_string(),
+            "Invalid parameter. Gaussian.sd = -1"
+        );
+        assert_eq!(
+            crate::defuzz::try_centroid(&[0.0, 1.0], &[0.0, 0.0]),
+            Err(FuzzyError::DivisionByZero { stage: "centroid" })
+        );
+    }
 }
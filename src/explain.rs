@@ -0,0 +1,236 @@
+// Explainable evaluation.
+//
+// `RuleSpace::explain` runs the same Mamdani pipeline as `evaluate` but records
+// every intermediate step: the degree of each input term, each rule's firing
+// strength and clipped consequents, and the aggregated set behind every output.
+// The clipped sets are kept on the output grid, so a UI can draw the classic
+// stacked Mamdani diagram straight from the report. Maps are ordered so
+// serialized reports are stable across runs.
+use std::{
+    borrow::Borrow,
+    collections::{BTreeMap, HashMap},
+    hash::Hash,
+};
+
+use crate::{
+    defuzz::centroid,
+    error,
+    membership::MembershipFn,
+    rulespace::RuleSpace,
+    sampler::{GridCache, UniformSampler},
+    Float,
+};
+
+/// One consequent of a rule after implication (clipping at the firing strength).
+///
+/// Crisp consequents have no set and are not listed.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Contribution {
+    pub var: String,
+    pub term: String,
+    /// Area under the clipped set, in output units.
+    pub area: Float,
+    /// Centroid of the clipped set; NaN when the rule did not fire.
+    pub centroid: Float,
+    /// The clipped set, sampled at the `points` of the output's `OutputTrace`.
+    pub implicated: Vec<Float>,
+}
+
+/// How one rule took part in an evaluation.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RuleTrace {
+    /// Position of the rule in `RuleSpace::rules`.
+    pub index: usize,
+    pub firing: Float,
+    pub contributions: Vec<Contribution>,
+}
+
+/// The aggregated set of an output and its defuzzified value; crisp outputs
+/// have no set and leave `points` and `aggregated` empty.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct OutputTrace {
+    pub value: Float,
+    /// Sample points of the output domain.
+    pub points: Vec<Float>,
+    /// Aggregated membership at each sample point.
+    pub aggregated: Vec<Float>,
+}
+
+/// Every intermediate result of one evaluation.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Explanation {
+    /// Degree of every term of every input variable, keyed by variable then term.
+    pub inputs: BTreeMap<String, BTreeMap<String, Float>>,
+    /// One entry per rule, in rule order.
+    pub rules: Vec<RuleTrace>,
+    pub outputs: BTreeMap<String, OutputTrace>,
+}
+
+impl RuleSpace {
+    /// Evaluates `input` like `evaluate`, returning the full trace.
+    ///
+    /// Fails in the same cases as `evaluate`.
+    pub fn explain<KI>(
+        &self,
+        input: &HashMap<KI, Float>,
+        sampler: &UniformSampler,
+    ) -> error::Result<Explanation>
+    where
+        KI: Eq + Hash + Borrow<str>,
+    {
+        let mut grids = GridCache::new();
+        let table = self.grid_table(sampler, &mut grids)?;
+        let mut inputs = Vec::with_capacity(self.vars.len());
+        self.resolve_inputs_into(input, &mut inputs);
+
+        let mut degrees = BTreeMap::new();
+        for name in self.inputs() {
+            let Some(&x) = input.get(name) else {
+                continue;
+            };
+            let var = self.var(name).expect("input names come from the space");
+            let terms = var
+                .terms()
+                .map(|(term, _)| Ok((term.to_string(), var.eval(term, x)?)))
+                .collect::<error::Result<_>>()?;
+            degrees.insert(name.to_string(), terms);
+        }
+
+        let mut agg = Vec::new();
+        self.reset_agg(&table, &mut agg);
+        let mut clipped = Vec::new();
+        let mut rules = Vec::with_capacity(self.rules.len());
+        for (index, (rule, compiled)) in self.rules.iter().zip(&self.compiled).enumerate() {
+            let firing = compiled.activation(rule, &inputs, &self.vars, &self.names)?;
+            let contributions = compiled
+                .consequent
+                .iter()
+                .zip(rule.consequent.iter().filter(|c| c.term().is_some()))
+                .map(|(&(v, t), c)| {
+                    let xs = table[v.index()];
+                    clipped.clear();
+                    clipped.resize(xs.len(), 0.0);
+                    if let Some(term) = self.vars[v.index()].term(t) {
+                        term.eval_grid(xs, &mut clipped);
+                    }
+                    if let Some(h) = c.hedge() {
+                        h.apply_all(&mut clipped);
+                    }
+                    for (mu, cur) in clipped.iter_mut().zip(agg[v.index()].iter_mut()) {
+                        *mu = mu.min(firing);
+                        *cur = cur.max(*mu);
+                    }
+                    Contribution {
+                        var: c.var().to_string(),
+                        term: c.term().unwrap_or_default().to_string(),
+                        area: clipped.iter().sum::<Float>() * spacing(xs),
+                        centroid: centroid(xs, &clipped),
+                        implicated: clipped.clone(),
+                    }
+                })
+                .collect();
+            compiled.accumulate_crisp(firing, &inputs, &self.names, &mut agg, |_| true)?;
+            rules.push(RuleTrace {
+                index,
+                firing,
+                contributions,
+            });
+        }
+
+        let outputs = self
+            .outputs
+            .iter()
+            .map(|&v| {
+                let (xs, mu) = (table[v.index()], &agg[v.index()]);
+                let value = self.output_value(v, xs, mu);
+                let trace = if self.crisp[v.index()] {
+                    OutputTrace {
+                        value,
+                        points: Vec::new(),
+                        aggregated: Vec::new(),
+                    }
+                } else {
+                    OutputTrace {
+                        value,
+                        points: xs.to_vec(),
+                        aggregated: mu.clone(),
+                    }
+                };
+                (self.var_name(v).to_string(), trace)
+            })
+            .collect();
+        Ok(Explanation {
+            inputs: degrees,
+            rules,
+            outputs,
+        })
+    }
+}
+
+impl Explanation {
+    /// The set rule `rule` implicated for output `var`, with the sample
+    /// points it lies on; `None` if the rule does not conclude on `var`.
+    ///
+    /// A rule with several consequents on `var` yields the first one.
+    pub fn implicated(&self, rule: usize, var: &str) -> Option<(&[Float], &[Float])> {
+        let c = self
+            .rules
+            .get(rule)?
+            .contributions
+            .iter()
+            .find(|c| c.var == var)?;
+        Some((&self.outputs.get(var)?.points, &c.implicated))
+    }
+}
+
+/// Distance between neighbouring sample points of a uniform grid.
+fn spacing(xs: &[Float]) -> Float {
+    match xs {
+        [first, .., last] => (last - first) / (xs.len() - 1) as Float,
+        _ => 0.0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::prelude::*;
+    use crate::test_support::space;
+
+    #[test]
+    fn explanation_traces_every_step() {
+        let sampler = UniformSampler::default();
+        let rs = space();
+        let input = HashMap::from([("temp", 7.5)]);
+        let ex = rs.explain(&input, &sampler).unwrap();
+
+        assert!((ex.inputs["temp"]["cold"] - 0.25).abs() < 1e-6);
+        assert!((ex.inputs["temp"]["hot"] - 0.75).abs() < 1e-6);
+        assert_eq!(ex.rules.len(), 2);
+        assert!((ex.rules[1].firing - 0.75).abs() < 1e-6);
+        let high = &ex.rules[1].contributions[0];
+        assert_eq!((high.var.as_str(), high.term.as_str()), ("fan", "high"));
+        assert!(high.area > ex.rules[0].contributions[0].area);
+
+        let fan = &ex.outputs["fan"];
+        let value = rs.evaluate(&input, &sampler).unwrap()["fan"];
+        assert!((fan.value - value).abs() < 1e-6);
+        assert_eq!(fan.points.len(), fan.aggregated.len());
+
+        // The stacked sets max-merge into the aggregate.
+        let (xs, low) = ex.implicated(0, "fan").unwrap();
+        let (_, high) = ex.implicated(1, "fan").unwrap();
+        assert_eq!(xs, &fan.points[..]);
+        assert!((high.iter().copied().fold(0.0, Float::max) - 0.75).abs() < 1e-6);
+        for ((l, h), a) in low.iter().zip(high).zip(&fan.aggregated) {
+            assert_eq!(l.max(*h), *a);
+        }
+        assert!(ex.implicated(0, "temp").is_none());
+        assert!(ex.implicated(2, "fan").is_none());
+    }
+}
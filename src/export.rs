@@ -0,0 +1,516 @@
+// Plot and graph export for membership functions, aggregates and rule bases.
+//
+// `Series` holds plot-ready (x, μ) points; `to_svg` renders any number of them
+// onto one chart so shapes can be inspected without an external plotting tool.
+// `RuleSpace::to_dot` renders the rule base as a Graphviz graph and
+// `RuleSpace::rule_table` as a Markdown or HTML table for review.
+use std::fmt::Write;
+
+use crate::{
+    antecedent::Antecedent,
+    error::{FuzzyError, Result},
+    mamdani::Consequent,
+    membership::MembershipFn,
+    rulespace::RuleSpace,
+    sampler::{Sampler, UniformSampler},
+    variable::Variable,
+    Float,
+};
+
+/// One labelled curve of (x, μ) points.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Series {
+    pub label: String,
+    pub points: Vec<(Float, Float)>,
+}
+
+impl Series {
+    /// Pairs `xs` with `mu`; both slices must have the same length.
+    pub fn from_samples(label: impl Into<String>, xs: &[Float], mu: &[Float]) -> Result<Self> {
+        if xs.len() != mu.len() {
+            return Err(FuzzyError::BadArity);
+        }
+        Ok(Self {
+            label: label.into(),
+            points: xs.iter().copied().zip(mu.iter().copied()).collect(),
+        })
+    }
+}
+
+/// Samples every term of `var` over its domain, one series per term in insertion order.
+pub fn term_series(var: &Variable, sampler: &UniformSampler) -> Result<Vec<Series>> {
+    let (min, max) = var.domain();
+    let xs = sampler.sample(min, max)?;
+    let mut mu = vec![0.0; xs.len()];
+    var.terms()
+        .map(|(name, term)| {
+            term.eval_grid(&xs, &mut mu);
+            Series::from_samples(name, &xs, &mu)
+        })
+        .collect()
+}
+
+/// Pairs an aggregated output set with the grid of `var` it was sampled on.
+///
+/// `mu` must come from the same `sampler` (e.g. `RuleSpace::aggregated`), otherwise
+/// the lengths disagree and `BadArity` is returned.
+pub fn aggregate_series(
+    label: impl Into<String>,
+    var: &Variable,
+    mu: &[Float],
+    sampler: &UniformSampler,
+) -> Result<Series> {
+    let (min, max) = var.domain();
+    Series::from_samples(label, &sampler.sample(min, max)?, mu)
+}
+
+/// Renders `var`'s terms, plus an optional aggregated set drawn filled, as an SVG document.
+pub fn variable_svg(
+    var: &Variable,
+    sampler: &UniformSampler,
+    aggregate: Option<&[Float]>,
+) -> Result<String> {
+    let mut series = term_series(var, sampler)?;
+    if let Some(mu) = aggregate {
+        series.push(aggregate_series("aggregate", var, mu, sampler)?);
+    }
+    Ok(svg(&series, var.domain(), aggregate.is_some()))
+}
+
+const WIDTH: Float = 640.0;
+const HEIGHT: Float = 320.0;
+const MARGIN: Float = 32.0;
+const PALETTE: [&str; 8] = [
+    "#1f77b4", "#ff7f0e", "#2ca02c", "#d62728", "#9467bd", "#8c564b", "#e377c2", "#17becf",
+];
+
+/// Renders `series` as a standalone SVG line chart with μ on a fixed [0, 1] axis.
+///
+/// The x range spans all points; an empty input yields an empty chart.
+pub fn to_svg(series: &[Series]) -> String {
+    let (min, max) = series
+        .iter()
+        .flat_map(|s| s.points.iter().map(|&(x, _)| x))
+        .fold((Float::INFINITY, Float::NEG_INFINITY), |(lo, hi), x| {
+            (lo.min(x), hi.max(x))
+        });
+    let domain = if min < max { (min, max) } else { (0.0, 1.0) };
+    svg(series, domain, false)
+}
+
+fn svg(series: &[Series], (min, max): (Float, Float), fill_last: bool) -> String {
+    let plot_w = WIDTH - 2.0 * MARGIN;
+    let plot_h = HEIGHT - 2.0 * MARGIN;
+    let sx = |x: Float| MARGIN + (x - min) / (max - min) * plot_w;
+    let sy = |mu: Float| HEIGHT - MARGIN - mu.clamp(0.0, 1.0) * plot_h;
+
+    // Writing into a `String` cannot fail, so the `fmt::Result`s are ignored.
+    let mut out = String::new();
+    let _ = writeln!(
+        out,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{WIDTH}" height="{HEIGHT}" viewBox="0 0 {WIDTH} {HEIGHT}">"#
+    );
+    let _ = writeln!(
+        out,
+        r##"<rect x="{MARGIN}" y="{MARGIN}" width="{plot_w}" height="{plot_h}" fill="none" stroke="#999"/>"##
+    );
+    let _ = writeln!(
+        out,
+        r#"<text x="{MARGIN}" y="{}" font-size="10">{min}</text><text x="{}" y="{}" font-size="10" text-anchor="end">{max}</text>"#,
+        HEIGHT - MARGIN / 3.0,
+        WIDTH - MARGIN,
+        HEIGHT - MARGIN / 3.0,
+    );
+
+    for (i, s) in series.iter().enumerate() {
+        let colour = PALETTE[i % PALETTE.len()];
+        let mut points = String::new();
+        for &(x, mu) in &s.points {
+            let _ = write!(points, "{:.2},{:.2} ", sx(x), sy(mu));
+        }
+        if fill_last && i + 1 == series.len() {
+            if let (Some(&(first, _)), Some(&(last, _))) = (s.points.first(), s.points.last()) {
+                let _ = write!(
+                    points,
+                    "{:.2},{:.2} {:.2},{:.2}",
+                    sx(last),
+                    sy(0.0),
+                    sx(first),
+                    sy(0.0)
+                );
+            }
+            let _ = writeln!(
+                out,
+                r#"<polygon points="{}" fill="{colour}" fill-opacity="0.3" stroke="{colour}"/>"#,
+                points.trim_end()
+            );
+        } else {
+            let _ = writeln!(
+                out,
+                r#"<polyline points="{}" fill="none" stroke="{colour}" stroke-width="2"/>"#,
+                points.trim_end()
+            );
+        }
+        let _ = writeln!(
+            out,
+            r#"<text x="{}" y="{}" font-size="11" fill="{colour}">{}</text>"#,
+            MARGIN + 4.0,
+            MARGIN + 14.0 * (i as Float + 1.0),
+            escape(&s.label)
+        );
+    }
+
+    out.push_str("</svg>\n");
+    out
+}
+
+impl RuleSpace {
+    /// Renders variables, antecedent trees and rule-to-output wiring as a Graphviz
+    /// `digraph`.
+    ///
+    /// Input variables are ellipses, outputs double octagons. Each rule is a cluster
+    /// holding its antecedent AST; atoms are fed by their variable and the rule node
+    /// points at every output it concludes on, labelled with the term.
+    pub fn to_dot(&self) -> String {
+        let outputs: Vec<&str> = self.outputs().collect();
+        let mut out = String::new();
+        out.push_str("digraph rulespace {\n    rankdir=LR;\n    node [fontname=\"Helvetica\"];\n");
+        for (name, _) in self.vars() {
+            let shape = if outputs.contains(&name) {
+                "doubleoctagon"
+            } else {
+                "ellipse"
+            };
+            let _ = writeln!(
+                out,
+                "    \"var:{0}\" [label=\"{0}\", shape={shape}];",
+                dot_escape(name)
+            );
+        }
+
+        for (r, rule) in self.rules().iter().enumerate() {
+            let _ = writeln!(
+                out,
+                "    subgraph cluster_r{r} {{\n        label=\"rule {r}\";\n        r{r} [label=\"THEN\", shape=box];"
+            );
+            let mut wiring = String::new();
+            let mut next = 0;
+            let root = dot_node(&rule.antecedent, r, &mut next, &mut out, &mut wiring);
+            let _ = writeln!(out, "        {root} -> r{r};\n    }}");
+            out.push_str(&wiring);
+            for c in &rule.consequent {
+                let _ = writeln!(
+                    out,
+                    "    r{r} -> \"var:{}\" [label=\"{}\"];",
+                    dot_escape(c.var()),
+                    match c {
+                        Consequent::Term {
+                            term,
+                            hedge: Some(h),
+                            ..
+                        } => dot_escape(&format!("{h} {term}")),
+                        Consequent::Term { term, .. } => dot_escape(term),
+                        Consequent::Crisp { expr, .. } => dot_escape(&format!("= {expr}")),
+                    }
+                );
+            }
+        }
+        out.push_str("}\n");
+        out
+    }
+}
+
+/// Markup produced by `RuleSpace::rule_table`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TableFormat {
+    Markdown,
+    Html,
+}
+
+impl RuleSpace {
+    /// Renders the rules as a table with one `IF` / `THEN` row per rule.
+    ///
+    /// When the rule base is a two-input FAM (every rule `IF a IS x AND b IS y
+    /// THEN out IS z` over the same variables, see `add_fam`), the matrix
+    /// follows as a second table with `a`'s terms as rows and `b`'s as columns;
+    /// empty cells show `-`.
+    pub fn rule_table(&self, format: TableFormat) -> String {
+        let mut out = String::new();
+        let rows: Vec<Vec<String>> = self
+            .rules()
+            .iter()
+            .enumerate()
+            .map(|(i, rule)| {
+                let then: Vec<String> = rule.consequent.iter().map(|c| c.to_string()).collect();
+                vec![
+                    i.to_string(),
+                    rule.antecedent.to_string(),
+                    then.join(" AND "),
+                ]
+            })
+            .collect();
+        write_table(&mut out, format, None, &["#", "IF", "THEN"], &rows);
+
+        if let Some(fam) = self.fam_view() {
+            let corner = format!("{} \\ {}", fam.rows.0, fam.cols.0);
+            let header: Vec<&str> = std::iter::once(corner.as_str())
+                .chain(fam.cols.1.iter().copied())
+                .collect();
+            let rows: Vec<Vec<String>> = fam
+                .rows
+                .1
+                .iter()
+                .zip(&fam.cells)
+                .map(|(term, cells)| {
+                    std::iter::once(term.to_string())
+                        .chain(cells.iter().map(|c| c.unwrap_or("-").to_string()))
+                        .collect()
+                })
+                .collect();
+            out.push('\n');
+            write_table(&mut out, format, Some(fam.output), &header, &rows);
+        }
+        out
+    }
+}
+
+fn write_table(
+    out: &mut String,
+    format: TableFormat,
+    caption: Option<&str>,
+    header: &[&str],
+    rows: &[Vec<String>],
+) {
+    match format {
+        TableFormat::Markdown => {
+            let md = |s: &str| s.replace('|', "\\|");
+            if let Some(caption) = caption {
+                let _ = writeln!(out, "**{}**\n", md(caption));
+            }
+            let _ = writeln!(
+                out,
+                "| {} |",
+                header.iter().map(|h| md(h)).collect::<Vec<_>>().join(" | ")
+            );
+            let _ = writeln!(out, "|{}", "---|".repeat(header.len()));
+            for row in rows {
+                let _ = writeln!(
+                    out,
+                    "| {} |",
+                    row.iter().map(|c| md(c)).collect::<Vec<_>>().join(" | ")
+                );
+            }
+        }
+        TableFormat::Html => {
+            out.push_str("<table>\n");
+            if let Some(caption) = caption {
+                let _ = writeln!(out, "  <caption>{}</caption>", escape(caption));
+            }
+            out.push_str("  <tr>");
+            for h in header {
+                let _ = write!(out, "<th>{}</th>", escape(h));
+            }
+            out.push_str("</tr>\n");
+            for row in rows {
+                out.push_str("  <tr>");
+                for c in row {
+                    let _ = write!(out, "<td>{}</td>", escape(c));
+                }
+                out.push_str("</tr>\n");
+            }
+            out.push_str("</table>\n");
+        }
+    }
+}
+
+/// Emits the nodes of `ant` for rule `r` into `out` and returns the id of its root.
+///
+/// Edges from variables into atoms cross the cluster boundary and go to `wiring`.
+fn dot_node(
+    ant: &Antecedent,
+    r: usize,
+    next: &mut usize,
+    out: &mut String,
+    wiring: &mut String,
+) -> String {
+    let id = format!("r{r}_{next}");
+    *next += 1;
+    match ant {
+        Antecedent::Atom { var, term } => {
+            let _ = writeln!(
+                out,
+                "        {id} [label=\"{} is {}\", shape=note];",
+                dot_escape(var),
+                dot_escape(term)
+            );
+            let _ = writeln!(wiring, "    \"var:{}\" -> {id};", dot_escape(var));
+        }
+        Antecedent::And(a, b) | Antecedent::Or(a, b) => {
+            let op = if matches!(ant, Antecedent::And(..)) {
+                "AND"
+            } else {
+                "OR"
+            };
+            let _ = writeln!(out, "        {id} [label=\"{op}\", shape=circle];");
+            for child in [a, b] {
+                let c = dot_node(child, r, next, out, wiring);
+                let _ = writeln!(out, "        {c} -> {id};");
+            }
+        }
+        Antecedent::Not(a) => {
+            let _ = writeln!(out, "        {id} [label=\"NOT\", shape=circle];");
+            let c = dot_node(a, r, next, out, wiring);
+            let _ = writeln!(out, "        {c} -> {id};");
+        }
+        Antecedent::Hedged(hedge, a) => {
+            let _ = writeln!(out, "        {id} [label=\"{hedge}\", shape=circle];");
+            let c = dot_node(a, r, next, out, wiring);
+            let _ = writeln!(out, "        {c} -> {id};");
+        }
+        Antecedent::Mean(kind, operands) => {
+            let _ = writeln!(out, "        {id} [label=\"{kind}\", shape=circle];");
+            for child in operands {
+                let c = dot_node(child, r, next, out, wiring);
+                let _ = writeln!(out, "        {c} -> {id};");
+            }
+        }
+        Antecedent::Const(c) => {
+            let _ = writeln!(out, "        {id} [label=\"{c}\", shape=note];");
+        }
+    }
+    id
+}
+
+fn dot_escape(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+fn escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{membership::triangular::Triangular, term::Term};
+
+    fn var() -> Variable {
+        let mut v = Variable::new(0.0, 10.0).unwrap();
+        v.insert_term(
+            "low",
+            Term::new("low", Triangular::new(-5.0, 0.0, 5.0).unwrap()),
+        )
+        .unwrap();
+        v.insert_term(
+            "high",
+            Term::new("high", Triangular::new(5.0, 10.0, 15.0).unwrap()),
+        )
+        .unwrap();
+        v
+    }
+
+    #[test]
+    fn term_series_samples_each_term_over_the_domain() {
+        let series = term_series(&var(), &UniformSampler::new(11).unwrap()).unwrap();
+        assert_eq!(series.len(), 2);
+        assert_eq!(series[0].label, "low");
+        assert_eq!(series[0].points.len(), 11);
+        assert_eq!(series[0].points[0], (0.0, 1.0));
+        assert_eq!(series[1].points[10], (10.0, 1.0));
+    }
+
+    #[test]
+    fn svg_contains_one_shape_per_series() {
+        let sampler = UniformSampler::new(11).unwrap();
+        let mu = vec![0.5; 11];
+        let svg = variable_svg(&var(), &sampler, Some(&mu)).unwrap();
+        assert!(svg.starts_with("<svg"));
+        assert_eq!(svg.matches("<polyline").count(), 2);
+        assert_eq!(svg.matches("<polygon").count(), 1);
+
+        assert!(matches!(
+            variable_svg(&var(), &sampler, Some(&[0.0; 3])),
+            Err(FuzzyError::BadArity)
+        ));
+    }
+
+    #[test]
+    fn dot_graph_wires_atoms_and_consequents() {
+        use crate::mamdani::{Consequent, Rule};
+        use std::collections::HashMap;
+
+        let atom = |term: &'static str| Antecedent::Atom {
+            var: "x".into(),
+            term: term.into(),
+        };
+        let rule = Rule {
+            antecedent: Antecedent::Or(
+                Box::new(atom("low")),
+                Box::new(Antecedent::Not(Box::new(atom("high")))),
+            ),
+            consequent: vec![Consequent::Term {
+                var: "y".into(),
+                term: "high".into(),
+                hedge: None,
+            }],
+            weight: 1.0,
+        };
+        let vars = HashMap::from([("x".to_string(), var()), ("y".to_string(), var())]);
+        let dot = RuleSpace::new(vars, vec![rule]).unwrap().to_dot();
+
+        assert!(dot.starts_with("digraph rulespace {"));
+        assert!(dot.contains("\"var:x\" [label=\"x\", shape=ellipse];"));
+        assert!(dot.contains("\"var:y\" [label=\"y\", shape=doubleoctagon];"));
+        assert!(dot.contains("subgraph cluster_r0"));
+        assert_eq!(dot.matches("\"var:x\" -> ").count(), 2);
+        assert!(dot.contains("r0 -> \"var:y\" [label=\"high\"];"));
+        assert!(dot.contains("label=\"OR\"") && dot.contains("label=\"NOT\""));
+        assert_eq!(dot_escape(r#"a"b\c"#), r#"a\"b\\c"#);
+    }
+
+    #[test]
+    fn rule_table_lists_rules_and_fam_matrix() {
+        use crate::antecedent::atom;
+        use std::collections::HashMap;
+
+        let vars = HashMap::from([
+            ("x".to_string(), var()),
+            ("y".to_string(), var()),
+            ("z".to_string(), var()),
+        ]);
+        let levels = ["low", "high"];
+        let rules = crate::fam::fam_rules(
+            ("x", &levels),
+            ("y", &levels),
+            "z",
+            &[&["low", "-"], &["low", "high"]],
+        )
+        .unwrap();
+        let mut rs = RuleSpace::new(vars, rules).unwrap();
+
+        let md = rs.rule_table(TableFormat::Markdown);
+        assert!(md.starts_with("| # | IF | THEN |\n|---|---|---|\n"));
+        assert!(md.contains("| 2 | x IS high AND y IS high | z IS high |"));
+        assert!(md.contains("**z**\n\n| x \\ y | low | high |"));
+        assert!(md.contains("| low | low | - |"));
+
+        let html = rs.rule_table(TableFormat::Html);
+        assert_eq!(html.matches("<table>").count(), 2);
+        assert!(html.contains("<caption>z</caption>"));
+        assert!(html.contains("<tr><td>high</td><td>low</td><td>high</td></tr>"));
+
+        // A rule outside the grid shape drops the matrix.
+        let mut rules = vec![crate::mamdani::Rule::builder()
+            .when(atom("x", "low").or(atom("y", "low")))
+            .then("z", "low")
+            .build()
+            .unwrap()];
+        rs.add_rules(&mut rules).unwrap();
+        let md = rs.rule_table(TableFormat::Markdown);
+        assert!(md.contains("| 3 | x IS low OR y IS low | z IS low |"));
+        assert!(!md.contains("**z**"));
+    }
+}
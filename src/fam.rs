@@ -0,0 +1,336 @@
+// Rule bases from FAM matrices.
+//
+// Two-input controllers are usually specified as a fuzzy associative memory: a
+// grid whose rows are the terms of one input, whose columns are the terms of the
+// other, and whose cells name the output term. Each filled cell is one rule
+// `IF row_var is row AND col_var is col THEN output is cell`.
+//
+// With three or more inputs the matrix becomes a hypercube that is easier to
+// describe as a function: `expand_rules` walks every combination of input terms
+// and asks a callback for the consequents of each.
+use crate::{
+    antecedent::{atom, Antecedent},
+    error::{self, FuzzyError, MissingSpace},
+    mamdani::{Consequent, Rule},
+    rulespace::RuleSpace,
+};
+
+/// One rule per filled cell of `matrix`.
+///
+/// `rows` and `cols` pair each input variable with its terms in matrix order;
+/// `matrix[i][j]` is the output term for `rows.1[i]` and `cols.1[j]`. Cells
+/// holding `-` or only whitespace have no rule. A matrix whose shape does not
+/// match the term lists fails with `BadArity`. Names are checked once the rules
+/// are added to a rule space.
+pub fn fam_rules(
+    rows: (&str, &[&str]),
+    cols: (&str, &[&str]),
+    output: &str,
+    matrix: &[&[&str]],
+) -> error::Result<Vec<Rule>> {
+    if matrix.len() != rows.1.len() || matrix.iter().any(|row| row.len() != cols.1.len()) {
+        return Err(FuzzyError::BadArity);
+    }
+    let mut rules = Vec::new();
+    for (row, r) in matrix.iter().zip(rows.1) {
+        for (&cell, c) in row.iter().zip(cols.1) {
+            let cell = cell.trim();
+            if cell.is_empty() || cell == "-" {
+                continue;
+            }
+            rules.push(Rule {
+                antecedent: atom(rows.0.to_string(), r.to_string())
+                    .and(atom(cols.0.to_string(), c.to_string())),
+                consequent: vec![Consequent::new(output.to_string(), cell.to_string())],
+                weight: 1.0,
+            });
+        }
+    }
+    Ok(rules)
+}
+
+/// One AND rule per combination of input terms, with consequents chosen by
+/// `consequent`.
+///
+/// `inputs` pairs each input variable with the terms to combine. The callback
+/// receives the terms of one combination in `inputs` order and returns the
+/// rule's consequents (an `Option` or a `Vec`); a combination without any gets
+/// no rule. Combinations are visited with the last input varying fastest, so
+/// two inputs give the row-major order of `fam_rules`. An input without terms
+/// yields no rules.
+pub fn expand_rules<F, C>(inputs: &[(&str, &[&str])], mut consequent: F) -> Vec<Rule>
+where
+    F: FnMut(&[&str]) -> C,
+    C: IntoIterator<Item = Consequent>,
+{
+    let mut rules = Vec::new();
+    if inputs.is_empty() || inputs.iter().any(|(_, terms)| terms.is_empty()) {
+        return rules;
+    }
+    let mut index = vec![0; inputs.len()];
+    let mut terms = Vec::with_capacity(inputs.len());
+    loop {
+        terms.clear();
+        terms.extend(inputs.iter().zip(&index).map(|((_, t), &i)| t[i]));
+        let then: Vec<Consequent> = consequent(&terms).into_iter().collect();
+        if !then.is_empty() {
+            let mut atoms = inputs
+                .iter()
+                .zip(&terms)
+                .map(|(&(var, _), &term)| atom(var.to_string(), term.to_string()));
+            let first = atoms.next().expect("inputs is not empty");
+            rules.push(Rule {
+                antecedent: atoms.fold(first, Antecedent::and),
+                consequent: then,
+                weight: 1.0,
+            });
+        }
+
+        // Advance the odometer, last input fastest.
+        let mut k = inputs.len();
+        loop {
+            if k == 0 {
+                return rules;
+            }
+            k -= 1;
+            index[k] += 1;
+            if index[k] < inputs[k].1.len() {
+                break;
+            }
+            index[k] = 0;
+        }
+    }
+}
+
+impl RuleSpace {
+    /// Appends `expand_rules` over every term of each of `inputs`, in the
+    /// order the terms were inserted.
+    ///
+    /// Fails with `NotFound` for an unknown input variable and, like
+    /// `add_rules`, leaves the space unchanged when any rule is invalid.
+    pub fn add_expanded<F, C>(&mut self, inputs: &[&str], consequent: F) -> error::Result<&mut Self>
+    where
+        F: FnMut(&[&str]) -> C,
+        C: IntoIterator<Item = Consequent>,
+    {
+        let terms = inputs
+            .iter()
+            .map(|&name| {
+                self.var(name)
+                    .map(|v| v.terms().map(|(t, _)| t).collect::<Vec<_>>())
+                    .ok_or_else(|| FuzzyError::NotFound {
+                        space: MissingSpace::Var,
+                        key: name.to_string(),
+                    })
+            })
+            .collect::<error::Result<Vec<_>>>()?;
+        let inputs: Vec<(&str, &[&str])> = inputs
+            .iter()
+            .zip(&terms)
+            .map(|(&name, t)| (name, t.as_slice()))
+            .collect();
+        let mut rules = expand_rules(&inputs, consequent);
+        self.add_rules(&mut rules)
+    }
+
+    /// Appends the rules of a FAM matrix indexed by the terms of `row_var` and
+    /// `col_var` in the order they were inserted (see `fam_rules`).
+    ///
+    /// Fails with `NotFound` for an unknown input variable and, like
+    /// `add_rules`, leaves the space unchanged when any rule is invalid.
+    pub fn add_fam(
+        &mut self,
+        row_var: &str,
+        col_var: &str,
+        output: &str,
+        matrix: &[&[&str]],
+    ) -> error::Result<&mut Self> {
+        let terms = |name: &str| {
+            self.var(name)
+                .map(|v| v.terms().map(|(t, _)| t).collect::<Vec<_>>())
+                .ok_or_else(|| FuzzyError::NotFound {
+                    space: MissingSpace::Var,
+                    key: name.to_string(),
+                })
+        };
+        let (rows, cols) = (terms(row_var)?, terms(col_var)?);
+        let mut rules = fam_rules((row_var, &rows), (col_var, &cols), output, matrix)?;
+        self.add_rules(&mut rules)
+    }
+}
+
+/// A rule base read back as a FAM matrix, see `RuleSpace::fam_view`.
+pub(crate) struct FamView<'a> {
+    pub rows: (&'a str, Vec<&'a str>),
+    pub cols: (&'a str, Vec<&'a str>),
+    pub output: &'a str,
+    /// `cells[i][j]` is the output term for row term `i` and column term `j`.
+    pub cells: Vec<Vec<Option<&'a str>>>,
+}
+
+impl RuleSpace {
+    /// The rule base as a FAM matrix, if every rule has the form `IF a IS x AND
+    /// b IS y THEN out IS z` over the same `a`, `b` and `out`, without hedges and
+    /// with at most one rule per cell.
+    pub(crate) fn fam_view(&self) -> Option<FamView<'_>> {
+        let mut shape: Option<(&str, &str, &str)> = None;
+        let mut cells = Vec::with_capacity(self.rules.len());
+        for rule in &self.rules {
+            let Antecedent::And(a, b) = &rule.antecedent else {
+                return None;
+            };
+            let (Antecedent::Atom { var: ra, term: x }, Antecedent::Atom { var: rb, term: y }, [c]) =
+                (&**a, &**b, rule.consequent.as_slice())
+            else {
+                return None;
+            };
+            let Consequent::Term {
+                var: out,
+                term: z,
+                hedge: None,
+            } = c
+            else {
+                return None;
+            };
+            let this = (&**ra, &**rb, &**out);
+            if ra == rb || *shape.get_or_insert(this) != this {
+                return None;
+            }
+            cells.push((&**x, &**y, &**z));
+        }
+        let (row_var, col_var, output) = shape?;
+
+        let terms = |name| -> Vec<&str> {
+            self.var(name)
+                .expect("rule variables exist")
+                .terms()
+                .map(|(t, _)| t)
+                .collect()
+        };
+        let (rows, cols) = (terms(row_var), terms(col_var));
+        let mut matrix = vec![vec![None; cols.len()]; rows.len()];
+        for (x, y, z) in cells {
+            let i = rows.iter().position(|&t| t == x)?;
+            let j = cols.iter().position(|&t| t == y)?;
+            if matrix[i][j].replace(z).is_some() {
+                return None;
+            }
+        }
+        Some(FamView {
+            rows: (row_var, rows),
+            cols: (col_var, cols),
+            output,
+            cells: matrix,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::{expand_rules, fam_rules};
+    use crate::error::FuzzyError;
+    use crate::prelude::*;
+
+    fn var(labels: &[&str]) -> Variable {
+        let mut v = Variable::new(0.0, 2.0).unwrap();
+        for (i, &label) in labels.iter().enumerate() {
+            let c = i as Float;
+            v.insert_term(
+                label,
+                Term::new(label, Triangular::new(c - 1.0, c, c + 1.0).unwrap()),
+            )
+            .unwrap();
+        }
+        v
+    }
+
+    #[test]
+    fn matrix_cells_become_rules() {
+        let levels = ["low", "mid", "high"];
+        let matrix: [&[&str]; 3] = [
+            &["low", "low", "mid"],
+            &["low", "mid", "-"],
+            &["mid", "high", " high "],
+        ];
+        let rules = fam_rules(("a", &levels), ("b", &levels), "out", &matrix).unwrap();
+        assert_eq!(rules.len(), 8);
+        assert_eq!(rules[5].antecedent, atom("a", "high").and(atom("b", "low")));
+        assert_eq!(rules[7].consequent, vec![Consequent::new("out", "high")]);
+
+        let vars = HashMap::from([
+            ("a".to_string(), var(&levels)),
+            ("b".to_string(), var(&levels)),
+            ("out".to_string(), var(&levels)),
+        ]);
+        let mut rs = RuleSpace::new(vars, vec![rules[0].clone()]).unwrap();
+        rs.add_fam("a", "b", "out", &matrix).unwrap();
+        assert_eq!(rs.rules().len(), 9);
+        assert_eq!(&rs.rules()[1..], &rules[..]);
+
+        assert_eq!(
+            fam_rules(("a", &levels), ("b", &levels), "out", &matrix[..2]),
+            Err(FuzzyError::BadArity)
+        );
+        assert!(matches!(
+            rs.add_fam("a", "c", "out", &matrix),
+            Err(FuzzyError::NotFound { .. })
+        ));
+        let typo: [&[&str]; 3] = [&["low"; 3], &["low"; 3], &["low", "low", "hgih"]];
+        assert!(matches!(
+            rs.add_fam("a", "b", "out", &typo),
+            Err(FuzzyError::InRule { rule: 17, .. })
+        ));
+        assert_eq!(rs.rules().len(), 9);
+    }
+
+    #[test]
+    fn expansion_covers_every_combination() {
+        let levels = ["low", "mid", "high"];
+        let rank = |t: &str| levels.iter().position(|&l| l == t).unwrap();
+        // out = the rounded mean level of three inputs, skipping all-`mid`.
+        let choose = |terms: &[&str]| {
+            let sum: usize = terms.iter().map(|t| rank(t)).sum();
+            let all_mid = terms.iter().all(|&t| t == "mid");
+            (!all_mid).then(|| Consequent::new("out", levels[(sum + 1) / 3]))
+        };
+        let inputs: [(&str, &[&str]); 3] = [("a", &levels), ("b", &levels), ("c", &levels)];
+        let rules = expand_rules(&inputs, choose);
+        assert_eq!(rules.len(), 26);
+        assert_eq!(
+            rules[1].antecedent,
+            atom("a", "low").and(atom("b", "low")).and(atom("c", "mid"))
+        );
+        assert_eq!(rules[25].consequent, vec![Consequent::new("out", "high")]);
+
+        // Two inputs reproduce the row-major order of a FAM matrix.
+        let matrix: [&[&str]; 2] = [&["low", "mid"], &["mid", "high"]];
+        let pair: [(&str, &[&str]); 2] = [("a", &levels[..2]), ("b", &levels[..2])];
+        let expanded = expand_rules(&pair, |t| {
+            Some(Consequent::new("out", matrix[rank(t[0])][rank(t[1])]))
+        });
+        let fam = fam_rules(pair[0], pair[1], "out", &matrix).unwrap();
+        assert_eq!(expanded, fam);
+        assert!(expand_rules(&[("a", &[][..])], |_| Vec::new()).is_empty());
+
+        let vars = HashMap::from([
+            ("a".to_string(), var(&levels)),
+            ("b".to_string(), var(&levels)),
+            ("c".to_string(), var(&levels)),
+            ("out".to_string(), var(&levels)),
+        ]);
+        let mut rs = RuleSpace::new(vars, vec![rules[0].clone()]).unwrap();
+        rs.add_expanded(&["a", "b", "c"], choose).unwrap();
+        assert_eq!(&rs.rules()[1..], &rules[..]);
+        assert!(matches!(
+            rs.add_expanded(&["a", "d"], choose),
+            Err(FuzzyError::NotFound { .. })
+        ));
+        assert!(matches!(
+            rs.add_expanded(&["a"], |_| Some(Consequent::new("out", "hgih"))),
+            Err(FuzzyError::InRule { rule: 27, .. })
+        ));
+        assert_eq!(rs.rules().len(), 27);
+    }
+}
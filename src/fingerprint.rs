@@ -0,0 +1,257 @@
+// Structural fingerprint of a rule space.
+//
+// Deployments want to check that they run the intended controller revision, and
+// caches keyed on a rule space need to notice when it changes. The fingerprint
+// hashes every variable, term shape and parameter, and rule in a fixed order
+// (variables by name, terms and rules in insertion order) with FNV-1a, so it does
+// not depend on `HashMap` iteration order, the process, or the Rust version.
+use crate::{
+    antecedent::Antecedent,
+    hedges::Hedge,
+    mamdani::Consequent,
+    membership::{MembershipFn, MembershipKind},
+    ops::Mean,
+    rulespace::RuleSpace,
+};
+
+const OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+const PRIME: u64 = 0x0000_0100_0000_01b3;
+
+struct Fnv(u64);
+
+impl Fnv {
+    fn bytes(&mut self, bytes: &[u8]) {
+        for &b in bytes {
+            self.0 = (self.0 ^ b as u64).wrapping_mul(PRIME);
+        }
+    }
+
+    fn tag(&mut self, tag: u8) {
+        self.bytes(&[tag]);
+    }
+
+    fn usize(&mut self, n: usize) {
+        self.bytes(&(n as u64).to_le_bytes());
+    }
+
+    /// Length-prefixed, so `("ab", "c")` and `("a", "bc")` differ.
+    fn str(&mut self, s: &str) {
+        self.usize(s.len());
+        self.bytes(s.as_bytes());
+    }
+
+    fn float(&mut self, x: crate::Float) {
+        #[allow(clippy::unnecessary_cast)] // Float is f32 under the `f32` feature.
+        self.bytes(&(x as f64).to_bits().to_le_bytes());
+    }
+
+    fn antecedent(&mut self, ant: &Antecedent) {
+        match ant {
+            Antecedent::Atom { var, term } => {
+                self.tag(0);
+                self.str(var);
+                self.str(term);
+            }
+            Antecedent::And(a, b) => {
+                self.tag(1);
+                self.antecedent(a);
+                self.antecedent(b);
+            }
+            Antecedent::Or(a, b) => {
+                self.tag(2);
+                self.antecedent(a);
+                self.antecedent(b);
+            }
+            Antecedent::Not(a) => {
+                self.tag(3);
+                self.antecedent(a);
+            }
+            Antecedent::Hedged(hedge, a) => {
+                self.tag(4);
+                self.hedge(Some(*hedge));
+                self.antecedent(a);
+            }
+            Antecedent::Mean(kind, operands) => {
+                self.tag(5);
+                match kind {
+                    Mean::Arithmetic => self.tag(0),
+                    Mean::Geometric => self.tag(1),
+                    Mean::Harmonic => self.tag(2),
+                    Mean::Power(p) => {
+                        self.tag(3);
+                        self.float(*p);
+                    }
+                }
+                self.usize(operands.len());
+                for a in operands {
+                    self.antecedent(a);
+                }
+            }
+            Antecedent::Const(c) => {
+                self.tag(6);
+                self.float(*c);
+            }
+        }
+    }
+
+    fn hedge(&mut self, hedge: Option<Hedge>) {
+        match hedge.map(Hedge::exponent) {
+            None => self.tag(0),
+            Some(Some(p)) => {
+                self.tag(1);
+                self.float(p);
+            }
+            // Intensification, the one hedge that is not a power.
+            Some(None) => self.tag(2),
+        }
+    }
+}
+
+impl RuleSpace {
+    /// Stable 64-bit hash of the variables, their domains and term shapes and
+    /// parameters, and the rules. Equal rule spaces built in any order hash the
+    /// same; changing any name, parameter or rule changes it (barring
+    /// collisions). Format it with `{:016x}` for display.
+    ///
+    /// Custom membership functions contribute only their `params()`, and f32
+    /// builds hash differently from f64 builds of the same system.
+    pub fn fingerprint(&self) -> u64 {
+        let mut h = Fnv(OFFSET);
+        h.usize(self.vars.len());
+        for (name, var) in self.vars() {
+            h.str(name);
+            let (min, max) = var.domain();
+            h.float(min);
+            h.float(max);
+            // Only normalized variables add a byte, so existing fingerprints are kept.
+            if var.is_normalized() {
+                h.tag(1);
+            }
+            h.usize(var.terms().count());
+            for (term, t) in var.terms() {
+                h.str(term);
+                h.tag(match t.kind() {
+                    MembershipKind::Triangular(_) => 0,
+                    MembershipKind::Trapezoidal(_) => 1,
+                    MembershipKind::Gaussian(_) => 2,
+                    MembershipKind::Rectangular(_) => 3,
+                    MembershipKind::Custom(_) => 255,
+                });
+                let params = t.params();
+                h.usize(params.len());
+                for p in params {
+                    h.float(p);
+                }
+                if let MembershipKind::Gaussian(g) = t.kind() {
+                    if let Some((lo, hi)) = g.support() {
+                        h.float(lo);
+                        h.float(hi);
+                    }
+                }
+            }
+        }
+        h.usize(self.rules.len());
+        for rule in &self.rules {
+            h.antecedent(&rule.antecedent);
+            h.usize(rule.consequent.len());
+            for c in &rule.consequent {
+                h.str(c.var());
+                match c {
+                    Consequent::Term { term, hedge, .. } => {
+                        h.str(term);
+                        h.hedge(*hedge);
+                    }
+                    Consequent::Crisp { expr, .. } => {
+                        h.tag(3);
+                        h.float(expr.constant);
+                        h.usize(expr.coefficients.len());
+                        for (var, k) in &expr.coefficients {
+                            h.str(var);
+                            h.float(*k);
+                        }
+                    }
+                }
+            }
+            // Unit weights hash as before weights existed.
+            if rule.weight != 1.0 {
+                h.tag(4);
+                h.float(rule.weight);
+            }
+        }
+        h.0
+    }
+}
+
+impl RuleSpace {
+    /// Hash of what `Scratch` buffers are sized for: the sampler resolution
+    /// `n`, every variable's name and domain, and the outputs with how each is
+    /// concluded. Term parameters and rules are left out; tuning changes them
+    /// in place while evaluating through one scratch.
+    pub(crate) fn layout_fingerprint(&self, n: usize) -> u64 {
+        let mut h = Fnv(OFFSET);
+        h.usize(n);
+        h.usize(self.vars.len());
+        for (name, var) in self.vars() {
+            h.str(name);
+            let (min, max) = var.domain();
+            h.float(min);
+            h.float(max);
+        }
+        h.usize(self.outputs.len());
+        for v in &self.outputs {
+            h.usize(v.index());
+            h.tag(u8::from(self.crisp[v.index()]));
+        }
+        h.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::prelude::*;
+
+    fn space(hot_apex: Float, reversed: bool, weight: Float) -> RuleSpace {
+        let mut temp = Variable::new(0.0, 10.0).unwrap();
+        temp.insert_term(
+            "hot",
+            Term::new("hot", Triangular::new(0.0, hot_apex, 20.0).unwrap()),
+        )
+        .unwrap();
+        let mut fan = Variable::new(0.0, 100.0).unwrap();
+        fan.insert_term(
+            "high",
+            Term::new("high", Gaussian::with_mean_sd(100.0, 10.0).unwrap()),
+        )
+        .unwrap();
+        let rule = Rule::builder()
+            .when(atom("temp", "hot"))
+            .then("fan", "high")
+            .weight(weight)
+            .build()
+            .unwrap();
+        let mut vars = vec![("temp".to_string(), temp), ("fan".to_string(), fan)];
+        if reversed {
+            vars.reverse();
+        }
+        RuleSpace::new(vars.into_iter().collect::<HashMap<_, _>>(), vec![rule]).unwrap()
+    }
+
+    #[test]
+    fn fingerprint_tracks_structure_not_order() {
+        let base = space(10.0, false, 1.0).fingerprint();
+        assert_eq!(base, space(10.0, true, 1.0).fingerprint());
+        assert_ne!(base, space(9.0, false, 1.0).fingerprint());
+        assert_ne!(base, space(10.0, false, 0.5).fingerprint());
+
+        let mut more = space(10.0, false, 1.0);
+        more.add_rules(&mut vec![Rule::builder()
+            .when(not(atom("temp", "hot")))
+            .then("fan", "high")
+            .build()
+            .unwrap()])
+            .unwrap();
+        assert_ne!(base, more.fingerprint());
+    }
+}
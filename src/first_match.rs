@@ -0,0 +1,236 @@
+// First-match inference.
+//
+// Instead of letting every rule contribute, the rules are read in declared
+// order like the branches of a decision tree with fuzzy guards: for each output
+// the first rule that fires above a threshold decides it. Later rules whose
+// strength is within a margin of that winner's are blended in, so a guard
+// that barely loses does not make the output jump.
+use std::{borrow::Borrow, collections::HashMap, hash::Hash};
+
+use crate::{
+    error::{self, FuzzyError},
+    intern::VarId,
+    rulespace::RuleSpace,
+    sampler::{GridCache, UniformSampler},
+    Float,
+};
+
+/// Settings for `RuleSpace::evaluate_first_match`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FirstMatch {
+    /// A rule matches when its firing strength exceeds this.
+    pub threshold: Float,
+    /// Later matching rules whose strength is within `margin` of the winner's
+    /// are max-merged with it; zero keeps only the winner.
+    pub margin: Float,
+}
+
+impl Default for FirstMatch {
+    fn default() -> Self {
+        Self {
+            threshold: 0.0,
+            margin: 0.0,
+        }
+    }
+}
+
+impl FirstMatch {
+    /// Fails with `OutOfBounds` unless `threshold` lies in `[0, 1)` and `margin`
+    /// is non-negative.
+    pub fn new(threshold: Float, margin: Float) -> error::Result<Self> {
+        if !(0.0..1.0).contains(&threshold) || margin.is_nan() || margin < 0.0 {
+            return Err(FuzzyError::OutOfBounds);
+        }
+        Ok(Self { threshold, margin })
+    }
+}
+
+impl RuleSpace {
+    /// Index of the rule that decides each output under first-match inference,
+    /// keyed by output name; outputs no rule matched are absent.
+    ///
+    /// Only term consequents are supported; a rule base with a crisp one fails
+    /// with `TypeMismatch` (in `InRule`).
+    pub fn first_matches<KI>(
+        &self,
+        input: &HashMap<KI, Float>,
+        mode: &FirstMatch,
+    ) -> error::Result<HashMap<String, usize>>
+    where
+        KI: Eq + Hash + Borrow<str>,
+    {
+        self.require_term_consequents()?;
+        let alphas = self.activations(input)?;
+        Ok(self
+            .outputs()
+            .zip(&self.outputs)
+            .filter_map(|(name, &v)| {
+                self.winner(&alphas, v, mode)
+                    .map(|(i, _)| (name.to_string(), i))
+            })
+            .collect())
+    }
+
+    /// Evaluates `input` with first-match inference: each output is decided by
+    /// the first rule (in declared order) concluding on it whose strength
+    /// exceeds `mode.threshold`, blended with later rules within `mode.margin`
+    /// of it. Outputs no rule matched are NaN. Fails like `first_matches` for
+    /// crisp consequents.
+    pub fn evaluate_first_match<KI>(
+        &self,
+        input: &HashMap<KI, Float>,
+        sampler: &UniformSampler,
+        mode: &FirstMatch,
+    ) -> error::Result<HashMap<String, Float>>
+    where
+        KI: Eq + Hash + Borrow<str>,
+    {
+        self.require_term_consequents()?;
+        let mut grids = GridCache::new();
+        let table = self.grid_table(sampler, &mut grids)?;
+        let alphas = self.activations(input)?;
+        let mut agg = Vec::new();
+        self.reset_agg(&table, &mut agg);
+        let mut scratch = Vec::new();
+        for &v in &self.outputs {
+            let Some((first, best)) = self.winner(&alphas, v, mode) else {
+                continue;
+            };
+            for (i, &alpha) in alphas.iter().enumerate().skip(first) {
+                let compiled = &self.compiled[i];
+                let on_v = compiled.consequent.iter().any(|&(u, _)| u == v);
+                if i == first
+                    || (on_v && alpha > mode.threshold && (alpha - best).abs() <= mode.margin)
+                {
+                    compiled.implicate_into(
+                        alpha,
+                        &self.vars,
+                        &table,
+                        &mut agg,
+                        &mut scratch,
+                        |u| u == v,
+                    );
+                }
+            }
+        }
+        Ok(self.defuzzify_ids(&agg, &table))
+    }
+
+    /// Firing strength of every rule, counted like a full evaluation.
+    pub(crate) fn activations<KI>(&self, input: &HashMap<KI, Float>) -> error::Result<Vec<Float>>
+    where
+        KI: Eq + Hash + Borrow<str>,
+    {
+        let mut inputs = Vec::new();
+        self.resolve_inputs_into(input, &mut inputs);
+        if let Some(c) = &self.counters {
+            c.evaluation();
+        }
+        self.rules
+            .iter()
+            .zip(&self.compiled)
+            .enumerate()
+            .map(|(i, (rule, compiled))| {
+                let alpha = compiled.activation(rule, &inputs, &self.vars, &self.names)?;
+                if let Some(c) = &self.counters {
+                    c.rule(i, alpha);
+                }
+                Ok(alpha)
+            })
+            .collect()
+    }
+
+    /// First rule concluding on `v` that fires above the threshold, with its strength.
+    fn winner(&self, alphas: &[Float], v: VarId, mode: &FirstMatch) -> Option<(usize, Float)> {
+        self.compiled
+            .iter()
+            .zip(alphas)
+            .position(|(c, &alpha)| {
+                alpha > mode.threshold && c.consequent.iter().any(|&(u, _)| u == v)
+            })
+            .map(|i| (i, alphas[i]))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::FirstMatch;
+    use crate::error::FuzzyError;
+    use crate::prelude::*;
+
+    /// Rule 0: warm → fan mid; rule 1: hot → fan high; rule 2: cold → fan low.
+    fn space() -> RuleSpace {
+        let mut temp = Variable::new(0.0, 10.0).unwrap();
+        for (name, c) in [("cold", 0.0), ("warm", 5.0), ("hot", 10.0)] {
+            temp.insert_term(
+                name,
+                Term::new(name, Triangular::new(c - 5.0, c, c + 5.0).unwrap()),
+            )
+            .unwrap();
+        }
+        let mut fan = Variable::new(0.0, 100.0).unwrap();
+        for (name, c) in [("low", 0.0), ("mid", 50.0), ("high", 100.0)] {
+            fan.insert_term(
+                name,
+                Term::new(name, Triangular::new(c - 50.0, c, c + 50.0).unwrap()),
+            )
+            .unwrap();
+        }
+        let rule = |t: &'static str, f: &'static str| {
+            Rule::builder()
+                .when(atom("temp", t))
+                .then("fan", f)
+                .build()
+                .unwrap()
+        };
+        let rules = vec![
+            rule("warm", "mid"),
+            rule("hot", "high"),
+            rule("cold", "low"),
+        ];
+        let vars = HashMap::from([("temp".to_string(), temp), ("fan".to_string(), fan)]);
+        RuleSpace::new(vars, rules).unwrap()
+    }
+
+    #[test]
+    fn first_matching_rule_decides() {
+        let sampler = UniformSampler::default();
+        let rs = space();
+        // warm 0.4, hot 0.6: the flat result leans high, first match keeps mid.
+        let input = HashMap::from([("temp", 8.0)]);
+        let strict = FirstMatch::new(0.2, 0.0).unwrap();
+        assert_eq!(rs.first_matches(&input, &strict).unwrap()["fan"], 0);
+        let y = rs.evaluate_first_match(&input, &sampler, &strict).unwrap()["fan"];
+        assert!((y - 50.0).abs() < 1e-3);
+
+        // Above 0.5 only `hot` matches.
+        let high = FirstMatch::new(0.5, 0.0).unwrap();
+        assert_eq!(rs.first_matches(&input, &high).unwrap()["fan"], 1);
+
+        // A wide margin blends `hot` back in, moving towards the flat result.
+        let blended = FirstMatch::new(0.2, 0.3).unwrap();
+        let z = rs.evaluate_first_match(&input, &sampler, &blended).unwrap()["fan"];
+        let flat = rs.evaluate(&input, &sampler).unwrap()["fan"];
+        assert!((z - flat).abs() < 1e-3);
+
+        let none = FirstMatch::new(0.9, 0.0).unwrap();
+        assert!(rs.first_matches(&input, &none).unwrap().is_empty());
+        assert!(rs.evaluate_first_match(&input, &sampler, &none).unwrap()["fan"].is_nan());
+        assert_eq!(FirstMatch::new(1.0, 0.0), Err(FuzzyError::OutOfBounds));
+    }
+
+    #[test]
+    fn crisp_consequents_are_rejected() {
+        let rs = crate::test_support::crisp_space();
+        let input = HashMap::from([("temp", 8.0)]);
+        let mode = FirstMatch::default();
+        let err = rs.first_matches(&input, &mode).unwrap_err();
+        assert!(crate::test_support::rejects_crisp(&err));
+        let err = rs
+            .evaluate_first_match(&input, &UniformSampler::default(), &mode)
+            .unwrap_err();
+        assert!(crate::test_support::rejects_crisp(&err));
+    }
+}
@@ -0,0 +1,246 @@
+// Golden-output snapshots.
+//
+// A snapshot is the `Dataset` of a rule space's outputs over a deterministic
+// grid of its inputs, stored as CSV. Comparing a later build against it with a
+// per-value tolerance shows whether a refactor of the sampler, operators or
+// defuzzification moved any output.
+use std::{collections::HashMap, fs, io, path::Path};
+
+use crate::{
+    data::Dataset,
+    error::{self, FuzzyError, MissingSpace},
+    rulespace::RuleSpace,
+    sampler::UniformSampler,
+    Float,
+};
+
+/// Environment variable that makes `assert_golden` rewrite existing snapshots.
+pub const UPDATE_ENV: &str = "UPDATE_GOLDEN";
+
+/// An output that moved by more than the tolerance.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Mismatch {
+    pub row: usize,
+    pub var: String,
+    pub expected: Float,
+    pub actual: Float,
+}
+
+impl RuleSpace {
+    /// Evaluates every point of a grid with `steps` evenly spaced values per
+    /// input (domain bounds included) and returns the inputs and outputs as a
+    /// dataset. Undefined outputs are stored as NaN.
+    ///
+    /// Fails with `OutOfBounds` if `steps < 2`.
+    pub fn golden(&self, steps: usize, sampler: &UniformSampler) -> error::Result<Dataset> {
+        if steps < 2 {
+            return Err(FuzzyError::OutOfBounds);
+        }
+        let inputs: Vec<&str> = self.inputs().collect();
+        let outputs: Vec<&str> = self.outputs().collect();
+        let axes: Vec<Vec<Float>> = inputs
+            .iter()
+            .map(|name| {
+                let (min, max) = self.var(name).expect("inputs are variables").domain();
+                let n = steps - 1;
+                (0..=n)
+                    .map(|i| {
+                        if i == n {
+                            max
+                        } else {
+                            min + (max - min) * i as Float / n as Float
+                        }
+                    })
+                    .collect()
+            })
+            .collect();
+
+        let mut ds = Dataset::new(inputs.iter().copied(), outputs.iter().copied());
+        let mut scratch = self.scratch(sampler)?;
+        let mut point = vec![0.0; inputs.len()];
+        for mut index in 0..steps.pow(inputs.len() as u32) {
+            for (x, axis) in point.iter_mut().zip(&axes) {
+                *x = axis[index % steps];
+                index /= steps;
+            }
+            let row: HashMap<&str, Float> = inputs.iter().copied().zip(point.clone()).collect();
+            let out = self.evaluate_in(&row, &mut scratch)?;
+            let y: Vec<Float> = outputs.iter().map(|o| out[*o]).collect();
+            ds.push(&point, &y)?;
+        }
+        Ok(ds)
+    }
+
+    /// Re-evaluates the inputs of `golden` and lists every target that differs
+    /// from the stored value by more than `tolerance`. NaN matches only NaN.
+    ///
+    /// A target the space does not produce fails with `NotFound`.
+    pub fn compare_golden(
+        &self,
+        golden: &Dataset,
+        sampler: &UniformSampler,
+        tolerance: Float,
+    ) -> error::Result<Vec<Mismatch>> {
+        let mut scratch = self.scratch(sampler)?;
+        let mut found = Vec::new();
+        for (row, (input, targets)) in golden.labeled().iter().enumerate() {
+            let out = self.evaluate_in(input, &mut scratch)?;
+            for (var, &expected) in targets {
+                let actual = *out.get(var).ok_or_else(|| FuzzyError::NotFound {
+                    space: MissingSpace::Var,
+                    key: var.clone(),
+                })?;
+                let same = if expected.is_nan() || actual.is_nan() {
+                    expected.is_nan() && actual.is_nan()
+                } else {
+                    (actual - expected).abs() <= tolerance
+                };
+                if !same {
+                    found.push(Mismatch {
+                        row,
+                        var: var.clone(),
+                        expected,
+                        actual,
+                    });
+                }
+            }
+        }
+        found.sort_by(|a, b| a.row.cmp(&b.row).then_with(|| a.var.cmp(&b.var)));
+        Ok(found)
+    }
+}
+
+/// Test helper: compares `space` against the CSV snapshot at `path`, panicking
+/// with the first mismatches if any output moved by more than `tolerance`.
+///
+/// A missing snapshot is created from the current outputs (with `steps` grid
+/// points per input), as is an existing one when the `UPDATE_GOLDEN`
+/// environment variable is set.
+pub fn assert_golden(
+    space: &RuleSpace,
+    path: impl AsRef<Path>,
+    steps: usize,
+    tolerance: Float,
+    sampler: &UniformSampler,
+) {
+    let path = path.as_ref();
+    if !path.exists() || std::env::var_os(UPDATE_ENV).is_some() {
+        let golden = space.golden(steps, sampler).expect("golden grid");
+        let file = fs::File::create(path).expect("create snapshot");
+        golden
+            .write_csv(io::BufWriter::new(file))
+            .expect("write snapshot");
+        return;
+    }
+    let file = fs::File::open(path).expect("open snapshot");
+    let outputs: Vec<&str> = space.outputs().collect();
+    let golden = Dataset::from_csv(io::BufReader::new(file), &outputs).expect("read snapshot");
+    let found = space
+        .compare_golden(&golden, sampler, tolerance)
+        .expect("evaluate snapshot inputs");
+    if !found.is_empty() {
+        let shown: Vec<String> = found
+            .iter()
+            .take(10)
+            .map(|m| {
+                format!(
+                    "row {}: {} expected {} got {}",
+                    m.row, m.var, m.expected, m.actual
+                )
+            })
+            .collect();
+        panic!(
+            "{} value(s) differ from {} (set {UPDATE_ENV}=1 to update):\n{}",
+            found.len(),
+            path.display(),
+            shown.join("\n")
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::assert_golden;
+    use crate::antecedent::Antecedent;
+    use crate::data::Dataset;
+    use crate::mamdani::{Consequent, Rule};
+    use crate::prelude::*;
+    use crate::rulespace::RuleSpace;
+    use crate::variable::Variable;
+
+    fn space(hot_apex: Float) -> RuleSpace {
+        let mut temp = Variable::new(0.0, 10.0).unwrap();
+        temp.insert_term(
+            "cold",
+            Term::new("cold", Triangular::new(-10.0, 0.0, 10.0).unwrap()),
+        )
+        .unwrap();
+        temp.insert_term(
+            "hot",
+            Term::new("hot", Triangular::new(0.0, hot_apex, 20.0).unwrap()),
+        )
+        .unwrap();
+        let mut fan = Variable::new(0.0, 100.0).unwrap();
+        fan.insert_term(
+            "low",
+            Term::new("low", Triangular::new(-100.0, 0.0, 100.0).unwrap()),
+        )
+        .unwrap();
+        fan.insert_term(
+            "high",
+            Term::new("high", Triangular::new(0.0, 100.0, 200.0).unwrap()),
+        )
+        .unwrap();
+        let rule = |t: &'static str, f: &'static str| Rule {
+            antecedent: Antecedent::Atom {
+                var: "temp".into(),
+                term: t.into(),
+            },
+            consequent: vec![Consequent::Term {
+                var: "fan".into(),
+                term: f.into(),
+                hedge: None,
+            }],
+            weight: 1.0,
+        };
+        let vars = HashMap::from([("temp".to_string(), temp), ("fan".to_string(), fan)]);
+        RuleSpace::new(vars, vec![rule("cold", "low"), rule("hot", "high")]).unwrap()
+    }
+
+    #[test]
+    fn snapshots_round_trip_and_catch_drift() {
+        let sampler = UniformSampler::default();
+        let golden = space(10.0).golden(11, &sampler).unwrap();
+        assert_eq!(golden.len(), 11);
+
+        let mut csv = Vec::new();
+        golden.write_csv(&mut csv).unwrap();
+        let read = Dataset::from_csv(csv.as_slice(), &["fan"]).unwrap();
+        assert_eq!(read, golden);
+
+        assert!(space(10.0)
+            .compare_golden(&read, &sampler, 1e-9)
+            .unwrap()
+            .is_empty());
+        let drift = space(8.0).compare_golden(&read, &sampler, 1e-3).unwrap();
+        assert!(!drift.is_empty());
+        assert!(drift.iter().all(|m| m.var == "fan" && m.row > 0));
+    }
+
+    #[test]
+    fn assert_golden_creates_then_checks() {
+        let sampler = UniformSampler::default();
+        let path = std::env::temp_dir().join(format!("golden-{}.csv", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        assert_golden(&space(10.0), &path, 5, 1e-9, &sampler);
+        assert!(path.exists());
+        assert_golden(&space(10.0), &path, 5, 1e-9, &sampler);
+        let moved = std::panic::catch_unwind(|| {
+            assert_golden(&space(8.0), &path, 5, 1e-6, &UniformSampler::default())
+        });
+        std::fs::remove_file(&path).unwrap();
+        assert!(moved.is_err());
+    }
+}
@@ -0,0 +1,268 @@
+// Prioritised rule groups.
+//
+// A flat rule base max-merges every rule into the output sets, so a "safety"
+// rule and a "comfort" rule pulling the same output in opposite directions end
+// up averaged by the centroid. `RuleGroups` overlays named, prioritised groups on
+// the rules of a `RuleSpace` and decides, per group, whether the groups below it
+// still count where it fires.
+use std::{borrow::Borrow, collections::HashMap, hash::Hash};
+
+use crate::{
+    error::{self, FuzzyError},
+    rulespace::RuleSpace,
+    sampler::{GridCache, UniformSampler},
+    Float,
+};
+
+/// How a group treats lower-priority groups on the outputs it fires into.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Overlap {
+    /// Max-merge with lower groups, as in a flat rule base.
+    #[default]
+    Merge,
+    /// Where the group fires at all, lower groups are ignored.
+    Override,
+    /// Lower groups are scaled by `1 - h`, where `h` is the height of this
+    /// group's output set: a fully firing group overrides, a weakly firing one
+    /// mixes with them.
+    Blend,
+}
+
+/// One named group of rules.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Group {
+    pub name: String,
+    pub priority: i32,
+    pub overlap: Overlap,
+    /// Indices into `RuleSpace::rules`.
+    pub rules: Vec<usize>,
+}
+
+/// Named, prioritised groups laid over the rules of a `RuleSpace`.
+///
+/// Rules in no group form an implicit lowest-priority group that merges.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RuleGroups {
+    groups: Vec<Group>,
+}
+
+impl RuleGroups {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a group; groups of equal priority apply in the order added.
+    pub fn group(
+        mut self,
+        name: impl Into<String>,
+        priority: i32,
+        overlap: Overlap,
+        rules: impl IntoIterator<Item = usize>,
+    ) -> Self {
+        self.groups.push(Group {
+            name: name.into(),
+            priority,
+            overlap,
+            rules: rules.into_iter().collect(),
+        });
+        self
+    }
+
+    /// The groups in the order added.
+    pub fn groups(&self) -> &[Group] {
+        &self.groups
+    }
+
+    /// Looks up a group by name.
+    pub fn get(&self, name: &str) -> Option<&Group> {
+        self.groups.iter().find(|g| g.name == name)
+    }
+
+    /// Rule indices per group, highest priority first, with the ungrouped rules
+    /// last. Fails with `OutOfBounds` for an index past `rules` and with
+    /// `TypeMismatch` for a rule listed in two groups.
+    fn ordered(&self, rules: usize) -> error::Result<Vec<(Overlap, Vec<usize>)>> {
+        let mut seen = vec![false; rules];
+        let mut order: Vec<&Group> = self.groups.iter().collect();
+        order.sort_by_key(|g| std::cmp::Reverse(g.priority));
+        let mut out = Vec::with_capacity(order.len() + 1);
+        for g in order {
+            for &r in &g.rules {
+                match seen.get_mut(r) {
+                    None => return Err(FuzzyError::OutOfBounds),
+                    Some(true) => return Err(FuzzyError::TypeMismatch),
+                    Some(s) => *s = true,
+                }
+            }
+            out.push((g.overlap, g.rules.clone()));
+        }
+        let rest = (0..rules).filter(|&r| !seen[r]).collect();
+        out.push((Overlap::Merge, rest));
+        Ok(out)
+    }
+}
+
+impl RuleSpace {
+    /// Evaluates `input` with the rules aggregated group by group, highest
+    /// priority first, as each group's `Overlap` says. With no groups (or only
+    /// merging ones) this matches `evaluate`. Only term consequents are
+    /// supported; a crisp one fails with `TypeMismatch` (in `InRule`).
+    pub fn evaluate_grouped<KI>(
+        &self,
+        input: &HashMap<KI, Float>,
+        sampler: &UniformSampler,
+        groups: &RuleGroups,
+    ) -> error::Result<HashMap<String, Float>>
+    where
+        KI: Eq + Hash + Borrow<str>,
+    {
+        self.require_term_consequents()?;
+        let order = groups.ordered(self.rules.len())?;
+        let mut grids = GridCache::new();
+        let table = self.grid_table(sampler, &mut grids)?;
+        let mut inputs = Vec::new();
+        self.resolve_inputs_into(input, &mut inputs);
+        if let Some(c) = &self.counters {
+            c.evaluation();
+        }
+
+        let mut agg = Vec::new();
+        let mut part = Vec::new();
+        self.reset_agg(&table, &mut agg);
+        let mut weight = vec![1.0; self.vars.len()];
+        let mut scratch = Vec::new();
+        for (overlap, rules) in order {
+            self.reset_agg(&table, &mut part);
+            for r in rules {
+                let compiled = &self.compiled[r];
+                let alpha =
+                    compiled.activation(&self.rules[r], &inputs, &self.vars, &self.names)?;
+                if let Some(c) = &self.counters {
+                    c.rule(r, alpha);
+                }
+                compiled
+                    .implicate_into(alpha, &self.vars, &table, &mut part, &mut scratch, |_| true);
+            }
+            for &v in &self.outputs {
+                let (i, w) = (v.index(), &mut weight[v.index()]);
+                for (cur, &y) in agg[i].iter_mut().zip(&part[i]) {
+                    *cur = cur.max(*w * y);
+                }
+                let height = part[i].iter().copied().fold(0.0, Float::max);
+                match overlap {
+                    Overlap::Merge => {}
+                    Overlap::Override if height > 0.0 => *w = 0.0,
+                    Overlap::Override => {}
+                    Overlap::Blend => *w *= 1.0 - height,
+                }
+            }
+        }
+        Ok(self.defuzzify_ids(&agg, &table))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::{Overlap, RuleGroups};
+    use crate::error::FuzzyError;
+    use crate::prelude::*;
+
+    /// Rule 0 (safety): hot → fan high. Rule 1 (comfort): humid → fan low.
+    fn space() -> RuleSpace {
+        let mut temp = Variable::new(0.0, 10.0).unwrap();
+        temp.insert_term(
+            "hot",
+            Term::new("hot", Triangular::new(0.0, 10.0, 20.0).unwrap()),
+        )
+        .unwrap();
+        let mut humidity = Variable::new(0.0, 10.0).unwrap();
+        humidity
+            .insert_term(
+                "humid",
+                Term::new("humid", Triangular::new(0.0, 10.0, 20.0).unwrap()),
+            )
+            .unwrap();
+        let mut fan = Variable::new(0.0, 100.0).unwrap();
+        fan.insert_term(
+            "low",
+            Term::new("low", Triangular::new(-100.0, 0.0, 100.0).unwrap()),
+        )
+        .unwrap();
+        fan.insert_term(
+            "high",
+            Term::new("high", Triangular::new(0.0, 100.0, 200.0).unwrap()),
+        )
+        .unwrap();
+        let rules = vec![
+            Rule::builder()
+                .when(atom("temp", "hot"))
+                .then("fan", "high")
+                .build()
+                .unwrap(),
+            Rule::builder()
+                .when(atom("humidity", "humid"))
+                .then("fan", "low")
+                .build()
+                .unwrap(),
+        ];
+        let vars = HashMap::from([
+            ("temp".to_string(), temp),
+            ("humidity".to_string(), humidity),
+            ("fan".to_string(), fan),
+        ]);
+        RuleSpace::new(vars, rules).unwrap()
+    }
+
+    #[test]
+    fn priorities_override_and_blend() {
+        let sampler = UniformSampler::default();
+        let rs = space();
+        let input = HashMap::from([("temp", 5.0), ("humidity", 10.0)]);
+        let flat = rs.evaluate(&input, &sampler).unwrap()["fan"];
+        let with = |overlap| {
+            let groups = RuleGroups::new().group("safety", 10, overlap, [0]).group(
+                "comfort",
+                0,
+                Overlap::Merge,
+                [1],
+            );
+            rs.evaluate_grouped(&input, &sampler, &groups).unwrap()["fan"]
+        };
+        assert_eq!(with(Overlap::Merge), flat);
+        let only_safety = space()
+            .evaluate(&HashMap::from([("temp", 5.0), ("humidity", 0.0)]), &sampler)
+            .unwrap()["fan"];
+        assert_eq!(with(Overlap::Override), only_safety);
+        let blended = with(Overlap::Blend);
+        assert!(flat < blended && blended < only_safety);
+
+        let bad =
+            RuleGroups::new()
+                .group("a", 1, Overlap::Merge, [0])
+                .group("b", 0, Overlap::Merge, [0]);
+        assert_eq!(
+            rs.evaluate_grouped(&input, &sampler, &bad),
+            Err(FuzzyError::TypeMismatch)
+        );
+        let bad = RuleGroups::new().group("a", 1, Overlap::Merge, [5]);
+        assert_eq!(
+            rs.evaluate_grouped(&input, &sampler, &bad),
+            Err(FuzzyError::OutOfBounds)
+        );
+    }
+
+    #[test]
+    fn crisp_consequents_are_rejected() {
+        let rs = crate::test_support::crisp_space();
+        let err = rs
+            .evaluate_grouped(
+                &HashMap::from([("temp", 8.0)]),
+                &UniformSampler::default(),
+                &RuleGroups::new(),
+            )
+            .unwrap_err();
+        assert!(crate::test_support::rejects_crisp(&err));
+    }
+}
@@ -0,0 +1,242 @@
+// Linguistic hedges.
+//
+// A hedge reshapes a membership degree, turning "High" into "very High" or
+// "somewhat High". The classic hedges are powers of the degree: concentration
+// (squaring) sharpens a term, dilation (square root) widens it. Intensification
+// ("indeed") pushes degrees away from 0.5 towards whichever end they lean to.
+//
+// The same `Hedge` values modify rule antecedents (`Antecedent::Hedged`),
+// consequents (`Consequent::hedge`) and membership expressions (`MfExpr`).
+use std::fmt;
+
+use crate::{
+    error::{FuzzyError, Result},
+    Float,
+};
+
+/// A modifier applied to a term's membership degree.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Hedge {
+    /// Concentration: `mu²`.
+    Very,
+    /// Dilation: `√mu`.
+    Somewhat,
+    /// Intensification: `2mu²` up to 0.5, `1 - 2(1 - mu)²` above.
+    Indeed,
+    /// `mu^p` for a custom exponent `p > 0`; `Hedge::power` checks it.
+    Power(Float),
+}
+
+impl Hedge {
+    /// Looks up a named hedge: `very`, `somewhat` (or `more_or_less`) and
+    /// `indeed`, ignoring case.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "very" => Some(Self::Very),
+            "somewhat" | "more_or_less" => Some(Self::Somewhat),
+            "indeed" => Some(Self::Indeed),
+            _ => None,
+        }
+    }
+
+    /// `Power(p)`, failing with `InvalidParameter` unless `p` is finite and
+    /// positive.
+    pub fn power(p: Float) -> Result<Self> {
+        Self::Power(p).validate()
+    }
+
+    /// The hedge itself if its exponent is valid (see `power`). Rules and
+    /// derived terms are checked with this, since `Power` can be built
+    /// directly.
+    pub fn validate(self) -> Result<Self> {
+        match self {
+            Self::Power(p) if !p.is_finite() || p <= 0.0 => Err(FuzzyError::InvalidParameter {
+                mf: "Hedge",
+                field: "power",
+                value: p,
+            }),
+            hedge => Ok(hedge),
+        }
+    }
+
+    /// Exponent the hedge raises degrees to; `None` for `Indeed`, which is not
+    /// a power.
+    pub fn exponent(self) -> Option<Float> {
+        match self {
+            Self::Very => Some(2.0),
+            Self::Somewhat => Some(0.5),
+            Self::Indeed => None,
+            Self::Power(p) => Some(p),
+        }
+    }
+
+    /// Hedged degree of `mu`.
+    pub fn apply(self, mu: Float) -> Float {
+        match self {
+            Self::Very => mu * mu,
+            Self::Somewhat => mu.sqrt(),
+            Self::Indeed if mu <= 0.5 => 2.0 * mu * mu,
+            Self::Indeed => 1.0 - 2.0 * (1.0 - mu) * (1.0 - mu),
+            Self::Power(p) => mu.powf(p),
+        }
+    }
+
+    /// Applies the hedge to every degree in `mu`.
+    pub fn apply_all(self, mu: &mut [Float]) {
+        for y in mu {
+            *y = self.apply(*y);
+        }
+    }
+}
+
+impl fmt::Display for Hedge {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Very => f.write_str("very"),
+            Self::Somewhat => f.write_str("somewhat"),
+            Self::Indeed => f.write_str("indeed"),
+            Self::Power(p) => write!(f, "power({p})"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::Hedge;
+    use crate::antecedent::eval_antecedent;
+    use crate::error::FuzzyError;
+    use crate::membership::parse_mf;
+    use crate::prelude::*;
+
+    #[test]
+    fn hedges_reshape_degrees() {
+        assert_eq!(Hedge::Very.apply(0.5), 0.25);
+        assert!((Hedge::Somewhat.apply(0.25) - 0.5).abs() < 1e-6);
+        assert_eq!(Hedge::Power(3.0).apply(0.5), 0.125);
+        assert_eq!(Hedge::Indeed.apply(0.25), 0.125);
+        assert_eq!(Hedge::Indeed.apply(0.75), 0.875);
+        assert_eq!(Hedge::Very.to_string(), "very");
+        assert_eq!(Hedge::from_name("More_Or_Less"), Some(Hedge::Somewhat));
+        assert_eq!(Hedge::from_name("indeed").unwrap().exponent(), None);
+        assert_eq!(Hedge::from_name("slightly"), None);
+    }
+
+    #[test]
+    fn hedged_antecedent_scales_firing_strength() {
+        let (vars, _) = parts(None);
+        let input = HashMap::from([("temp", 5.0)]);
+        let plain = eval_antecedent(&atom("temp", "hot"), &input, &vars).unwrap();
+        for hedge in [
+            Hedge::Very,
+            Hedge::Somewhat,
+            Hedge::Indeed,
+            Hedge::Power(3.0),
+        ] {
+            let hedged = atom("temp", "hot").hedged(hedge);
+            let mu = eval_antecedent(&hedged, &input, &vars).unwrap();
+            assert_eq!(mu, hedge.apply(plain));
+        }
+    }
+
+    fn parts(hedge: Option<Hedge>) -> (HashMap<String, Variable>, Vec<Rule>) {
+        let mut temp = Variable::new(0.0, 10.0).unwrap();
+        temp.insert_term(
+            "hot",
+            Term::new("hot", Triangular::new(0.0, 10.0, 20.0).unwrap()),
+        )
+        .unwrap();
+        let mut fan = Variable::new(0.0, 100.0).unwrap();
+        fan.insert_term(
+            "high",
+            Term::new("high", Triangular::new(0.0, 100.0, 200.0).unwrap()),
+        )
+        .unwrap();
+        let then = Consequent::Term {
+            var: "fan".into(),
+            term: "high".into(),
+            hedge,
+        };
+        let rule = Rule {
+            antecedent: atom("temp", "hot"),
+            consequent: vec![then],
+            weight: 1.0,
+        };
+        let vars = HashMap::from([("temp".to_string(), temp), ("fan".to_string(), fan)]);
+        (vars, vec![rule])
+    }
+
+    #[test]
+    fn hedged_consequent_sharpens_output() {
+        let sampler = UniformSampler::default();
+        let input = HashMap::from([("temp", 10.0)]);
+        let evaluate = |hedge| {
+            let (vars, rules) = parts(hedge);
+            RuleSpace::new(vars, rules)
+                .unwrap()
+                .evaluate(&input, &sampler)
+                .unwrap()["fan"]
+        };
+        // Squaring the rising edge pulls the centroid towards the apex.
+        let very = evaluate(Some(Hedge::Very));
+        assert!(very > evaluate(None));
+
+        let (vars, rules) = parts(Some(Hedge::Very));
+        let agg = aggregation(&rules, &input, &vars, &sampler).unwrap();
+        let y = defuzzification(&agg, &vars).unwrap()["fan"];
+        assert!((y - very).abs() < 1e-3);
+    }
+
+    #[test]
+    fn power_exponents_must_be_finite_and_positive() {
+        assert_eq!(Hedge::power(2.5), Ok(Hedge::Power(2.5)));
+        let invalid = |e: &FuzzyError| {
+            matches!(
+                e.root(),
+                FuzzyError::InvalidParameter {
+                    mf: "Hedge",
+                    field: "power",
+                    ..
+                }
+            )
+        };
+        for p in [0.0, -1.0, Float::NAN, Float::INFINITY] {
+            assert!(invalid(&Hedge::power(p).unwrap_err()), "{p}");
+
+            // Built directly, a bad exponent is caught when the rule compiles.
+            let (vars, _) = parts(None);
+            let rule = Rule::builder()
+                .when(atom("temp", "hot").hedged(Hedge::Power(p)))
+                .then("fan", "high")
+                .build()
+                .unwrap();
+            let err = RuleSpace::new(vars, vec![rule]).err().unwrap();
+            assert!(matches!(err, FuzzyError::InRule { rule: 0, .. }) && invalid(&err));
+
+            let (vars, good) = parts(None);
+            let (_, bad) = parts(Some(Hedge::Power(p)));
+            let mut rs = RuleSpace::new(vars, good).unwrap();
+            assert!(invalid(&rs.add_rules(&mut { bad }).err().unwrap()));
+
+            let (mut vars, _) = parts(None);
+            let temp = vars.get_mut("temp").unwrap();
+            assert!(invalid(
+                &temp.derive_hedged("hot", Hedge::Power(p)).unwrap_err()
+            ));
+        }
+
+        // Both parsers reject the exponents too.
+        for p in ["0", "-1", "1e999"] {
+            assert!(parse_mf(&format!("power({p}, triangle(0, 1, 2))")).is_err());
+            let text = format!(
+                "var temp 0 10\n hot: triangle(0, 10, 20)\n\
+                 var fan 0 100\n high: triangle(0, 100, 200)\n\
+                 IF temp IS power({p}) hot THEN fan IS high"
+            );
+            let err = text.parse::<RuleSpace>().err().unwrap();
+            assert!(err.to_string().contains("power"), "{err}");
+        }
+    }
+}
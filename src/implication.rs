@@ -0,0 +1,258 @@
+// Logical implication operators and implicative inference.
+//
+// Mamdani inference reads a rule as a conjunction: the output is whatever some
+// fired rule supports. In approximate reasoning a rule `IF x IS A THEN y IS B`
+// is a fuzzy implication `A(x) -> B(y)` instead: each rule constrains the
+// output and the conclusion is what every rule still allows (the pointwise
+// minimum). A rule that does not fire allows everything, since `I(0, b) = 1`.
+use std::{borrow::Borrow, collections::HashMap, hash::Hash};
+
+use crate::{
+    defuzz::Defuzz,
+    error,
+    rulespace::RuleSpace,
+    sampler::{GridCache, UniformSampler},
+    Float,
+};
+
+/// A fuzzy implication `I(a, b)`: the truth of "a implies b" for degrees in [0, 1].
+pub trait FuzzyImplication {
+    fn imply(&self, a: Float, b: Float) -> Float;
+}
+
+/// Built-in implication operators.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Implicator {
+    /// Gödel (residuum of `min`): `1` if `a <= b`, else `b`.
+    Godel,
+    /// Łukasiewicz (residuum of the Łukasiewicz T-norm): `min(1, 1 - a + b)`.
+    Lukasiewicz,
+    /// Kleene–Dienes: `max(1 - a, b)`.
+    KleeneDienes,
+    /// Reichenbach: `1 - a + a * b`.
+    Reichenbach,
+}
+
+impl FuzzyImplication for Implicator {
+    fn imply(&self, a: Float, b: Float) -> Float {
+        match self {
+            Implicator::Godel => {
+                if a <= b {
+                    1.0
+                } else {
+                    b
+                }
+            }
+            Implicator::Lukasiewicz => (1.0 - a + b).min(1.0),
+            Implicator::KleeneDienes => (1.0 - a).max(b),
+            Implicator::Reichenbach => (1.0 - a + a * b).clamp(0.0, 1.0),
+        }
+    }
+}
+
+impl RuleSpace {
+    /// Output sets of `input` under implicative inference: each rule's
+    /// consequent becomes `implicator.imply(alpha, mu)` and the rules are
+    /// intersected with `min`.
+    ///
+    /// Samples lie on the grids `sampler` produces for the output domains, as
+    /// for `aggregated`. Missing inputs are handled as in `evaluate`. Only term
+    /// consequents are supported; a crisp one fails with `TypeMismatch` (in
+    /// `InRule`).
+    pub fn infer_implicative<KI, I>(
+        &self,
+        input: &HashMap<KI, Float>,
+        sampler: &UniformSampler,
+        implicator: &I,
+    ) -> error::Result<HashMap<String, Vec<Float>>>
+    where
+        KI: Eq + Hash + Borrow<str>,
+        I: FuzzyImplication + ?Sized,
+    {
+        let mut grids = GridCache::new();
+        let table = self.grid_table(sampler, &mut grids)?;
+        let mut agg = self.implicative_sets(input, &table, implicator)?;
+        Ok(self
+            .outputs
+            .iter()
+            .filter(|&&v| !self.crisp[v.index()])
+            .map(|&v| {
+                (
+                    self.var_name(v).to_string(),
+                    std::mem::take(&mut agg[v.index()]),
+                )
+            })
+            .collect())
+    }
+
+    /// Defuzzifies the sets of `infer_implicative` with `defuzz`.
+    ///
+    /// Implicative conclusions are often flat-topped, so a maxima method
+    /// usually reads them better than the centroid. Contradictory rules can
+    /// leave an empty set, which defuzzifies to NaN.
+    pub fn evaluate_implicative<KI, I>(
+        &self,
+        input: &HashMap<KI, Float>,
+        sampler: &UniformSampler,
+        implicator: &I,
+        defuzz: Defuzz,
+    ) -> error::Result<HashMap<String, Float>>
+    where
+        KI: Eq + Hash + Borrow<str>,
+        I: FuzzyImplication + ?Sized,
+    {
+        let mut grids = GridCache::new();
+        let table = self.grid_table(sampler, &mut grids)?;
+        let agg = self.implicative_sets(input, &table, implicator)?;
+        Ok(self
+            .outputs
+            .iter()
+            .map(|&v| {
+                let i = v.index();
+                (
+                    self.var_name(v).to_string(),
+                    defuzz.apply(table[i], &agg[i]),
+                )
+            })
+            .collect())
+    }
+
+    fn implicative_sets<KI, I>(
+        &self,
+        input: &HashMap<KI, Float>,
+        table: &[&[Float]],
+        implicator: &I,
+    ) -> error::Result<Vec<Vec<Float>>>
+    where
+        KI: Eq + Hash + Borrow<str>,
+        I: FuzzyImplication + ?Sized,
+    {
+        self.require_term_consequents()?;
+        let mut inputs = Vec::new();
+        self.resolve_inputs_into(input, &mut inputs);
+        let mut agg = Vec::new();
+        self.reset_agg(table, &mut agg);
+        for (set, &crisp) in agg.iter_mut().zip(&self.crisp) {
+            if !crisp {
+                set.fill(1.0);
+            }
+        }
+        let mut scratch = Vec::new();
+
+        if let Some(c) = &self.counters {
+            c.evaluation();
+        }
+        for (i, (rule, compiled)) in self.rules.iter().zip(&self.compiled).enumerate() {
+            let alpha = compiled.activation(rule, &inputs, &self.vars, &self.names)?;
+            if let Some(c) = &self.counters {
+                c.rule(i, alpha);
+            }
+            compiled.implicate_into_with(
+                alpha,
+                &self.vars,
+                table,
+                &mut agg,
+                &mut scratch,
+                |_| true,
+                |alpha, mu| implicator.imply(alpha, mu),
+                Float::min,
+            );
+        }
+        Ok(agg)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::{FuzzyImplication, Implicator};
+    use crate::defuzz::Defuzz;
+    use crate::prelude::*;
+    use crate::test_support::space;
+
+    const ALL: [Implicator; 4] = [
+        Implicator::Godel,
+        Implicator::Lukasiewicz,
+        Implicator::KleeneDienes,
+        Implicator::Reichenbach,
+    ];
+
+    #[test]
+    fn implicators_follow_their_definitions() {
+        let eps = 1e-6;
+        let cases = [
+            (Implicator::Godel, 0.3),
+            (Implicator::Lukasiewicz, 0.8),
+            (Implicator::KleeneDienes, 0.5),
+            (Implicator::Reichenbach, 0.65),
+        ];
+        for (imp, expected) in cases {
+            assert!((imp.imply(0.5, 0.3) - expected).abs() < eps, "{imp:?}");
+        }
+        for imp in ALL {
+            for b in [0.0, 0.25, 1.0] {
+                assert_eq!(imp.imply(0.0, b), 1.0, "{imp:?}");
+                assert!((imp.imply(1.0, b) - b).abs() < eps, "{imp:?}");
+            }
+            assert!((0.0..=1.0).contains(&imp.imply(0.4, 0.9)));
+        }
+    }
+
+    #[test]
+    fn implicative_inference_intersects_rules() {
+        let rs = space();
+        let sampler = UniformSampler::default();
+
+        // Only `hot` fires fully, so every operator concludes exactly `high`.
+        let hot = HashMap::from([("temp", 10.0)]);
+        let high = rs.var("fan").unwrap().get("high").unwrap();
+        for imp in ALL {
+            let sets = rs.infer_implicative(&hot, &sampler, &imp).unwrap();
+            let xs = sampler.sample(0.0, 100.0).unwrap();
+            for (&x, &mu) in xs.iter().zip(&sets["fan"]) {
+                assert!((mu - high.eval(x)).abs() < 1e-5, "{imp:?} at {x}");
+            }
+            let y = rs
+                .evaluate_implicative(&hot, &sampler, &imp, Defuzz::MeanOfMaxima)
+                .unwrap()["fan"];
+            assert!((y - 100.0).abs() < 1e-3, "{imp:?}: {y}");
+        }
+
+        // Partial firing: Kleene–Dienes keeps a floor of 1 - alpha everywhere.
+        let warm = HashMap::from([("temp", 7.0)]);
+        let sets = rs
+            .infer_implicative(&warm, &sampler, &Implicator::KleeneDienes)
+            .unwrap();
+        let floor = sets["fan"].iter().copied().fold(1.0, Float::min);
+        assert!((floor - 0.3).abs() < 1e-5, "{floor}");
+        let y = rs
+            .evaluate_implicative(&warm, &sampler, &Implicator::Godel, Defuzz::Centroid)
+            .unwrap()["fan"];
+        let cool = rs
+            .evaluate_implicative(
+                &HashMap::from([("temp", 3.0)]),
+                &sampler,
+                &Implicator::Godel,
+                Defuzz::Centroid,
+            )
+            .unwrap()["fan"];
+        assert!(cool < 50.0 && 50.0 < y, "{cool} {y}");
+    }
+
+    #[test]
+    fn crisp_consequents_are_rejected() {
+        let rs = crate::test_support::crisp_space();
+        let input = HashMap::from([("temp", 8.0)]);
+        let sampler = UniformSampler::default();
+        let err = rs
+            .infer_implicative(&input, &sampler, &Implicator::Godel)
+            .unwrap_err();
+        assert!(crate::test_support::rejects_crisp(&err));
+        let err = rs
+            .evaluate_implicative(&input, &sampler, &Implicator::Godel, Defuzz::Centroid)
+            .unwrap_err();
+        assert!(crate::test_support::rejects_crisp(&err));
+    }
+}
@@ -0,0 +1,366 @@
+// Incremental re-evaluation: cache per-rule activations between calls and only
+// recompute the rules (and outputs) affected by inputs that actually changed.
+use std::{borrow::Borrow, collections::HashMap, hash::Hash};
+
+use crate::{
+    error::{self, FuzzyError, MissingSpace},
+    intern::VarId,
+    rulespace::RuleSpace,
+    sampler::{GridCache, UniformSampler},
+    Float,
+};
+
+/// Evaluation state kept between calls to `RuleSpace::evaluate_incremental`.
+///
+/// Tied to the rule space and sampler it was created from; the cached
+/// activations also go stale when that space's rules or terms change.
+#[derive(Debug, Clone)]
+pub struct Incremental {
+    /// `RuleSpace::layout_fingerprint` of the owning space at creation.
+    layout: u64,
+    /// `RuleSpace::fingerprint` of the owning space at creation.
+    fingerprint: u64,
+    /// Sampler resolution the output grids were built with.
+    n: usize,
+    grids: Vec<Vec<Float>>,
+    inputs: Vec<Option<Float>>,
+    alphas: Vec<Float>,
+    agg: Vec<Vec<Float>>,
+    scratch: Vec<Float>,
+    outputs: HashMap<String, Float>,
+    /// Rules whose antecedent reads each variable, by `VarId`.
+    readers: Vec<Vec<usize>>,
+    /// Rules that conclude on each variable, by `VarId`.
+    writers: Vec<Vec<usize>>,
+    dirty_rules: Vec<bool>,
+    dirty_outputs: Vec<bool>,
+    /// False until a full pass succeeded (and again after any error).
+    valid: bool,
+    recomputed: usize,
+}
+
+impl Incremental {
+    /// Number of rules whose activation was recomputed by the last call.
+    pub fn recomputed_rules(&self) -> usize {
+        self.recomputed
+    }
+
+    /// Forget every cached activation; the next call re-evaluates all rules.
+    pub fn reset(&mut self) {
+        self.valid = false;
+    }
+}
+
+impl RuleSpace {
+    /// Create the state used by `evaluate_incremental`/`update_input`.
+    pub fn incremental(&self, sampler: &UniformSampler) -> error::Result<Incremental> {
+        let mut cache = GridCache::new();
+        let grids: Vec<Vec<Float>> = self
+            .grid_table(sampler, &mut cache)?
+            .into_iter()
+            .map(<[Float]>::to_vec)
+            .collect();
+        let mut agg = Vec::new();
+        self.reset_agg(&grids, &mut agg);
+
+        let n = self.vars.len();
+        let mut readers = vec![Vec::new(); n];
+        let mut writers = vec![Vec::new(); n];
+        for (r, c) in self.compiled.iter().enumerate() {
+            let coefficients = c
+                .crisp
+                .iter()
+                .flat_map(|k| k.coefficients.iter().map(|&(v, _)| v));
+            for v in c.atoms.iter().map(|&(v, _)| v).chain(coefficients) {
+                readers[v.index()].push(r);
+            }
+            let crisp = c.crisp.iter().map(|k| k.var);
+            for v in c.consequent.iter().map(|&(v, _)| v).chain(crisp) {
+                writers[v.index()].push(r);
+            }
+        }
+        for list in readers.iter_mut().chain(writers.iter_mut()) {
+            list.dedup();
+        }
+
+        Ok(Incremental {
+            layout: self.layout_fingerprint(sampler.n),
+            fingerprint: self.fingerprint(),
+            n: sampler.n,
+            grids,
+            inputs: vec![None; n],
+            alphas: vec![0.0; self.rules.len()],
+            agg,
+            scratch: Vec::with_capacity(sampler.n),
+            outputs: self
+                .outputs()
+                .map(|name| (name.to_string(), Float::NAN))
+                .collect(),
+            readers,
+            writers,
+            dirty_rules: vec![false; self.rules.len()],
+            dirty_outputs: vec![false; n],
+            valid: false,
+            recomputed: 0,
+        })
+    }
+
+    /// Evaluate `input`, recomputing only rules that read a variable whose value changed.
+    ///
+    /// A state created by another rule space, or by this one before its
+    /// variables, terms or rules changed, fails with `ScratchMismatch`; create
+    /// a new one with `incremental`. Errors raised while firing a rule are
+    /// wrapped in `InRule`.
+    pub fn evaluate_incremental<'s, KI>(
+        &self,
+        input: &HashMap<KI, Float>,
+        state: &'s mut Incremental,
+    ) -> error::Result<&'s HashMap<String, Float>>
+    where
+        KI: Eq + Hash + Borrow<str>,
+    {
+        self.check_state(state)?;
+        for (i, name) in self.names.iter().enumerate() {
+            let x = input.get(name).copied();
+            if x != state.inputs[i] {
+                state.inputs[i] = x;
+                mark_readers(state, i);
+            }
+        }
+        self.refresh(state)
+    }
+
+    /// Change a single input and re-evaluate only what depends on it.
+    pub fn update_input<'s>(
+        &self,
+        var: &str,
+        x: Float,
+        state: &'s mut Incremental,
+    ) -> error::Result<&'s HashMap<String, Float>> {
+        self.check_state(state)?;
+        let v = self.var_id(var).ok_or_else(|| FuzzyError::NotFound {
+            space: MissingSpace::Var,
+            key: var.to_string(),
+        })?;
+        if state.inputs[v.index()] != Some(x) {
+            state.inputs[v.index()] = Some(x);
+            mark_readers(state, v.index());
+        }
+        self.refresh(state)
+    }
+
+    fn check_state(&self, state: &Incremental) -> error::Result<()> {
+        if state.layout != self.layout_fingerprint(state.n)
+            || state.fingerprint != self.fingerprint()
+        {
+            return Err(FuzzyError::ScratchMismatch);
+        }
+        Ok(())
+    }
+
+    fn refresh<'s>(&self, state: &'s mut Incremental) -> error::Result<&'s HashMap<String, Float>> {
+        let result = self.refresh_inner(state);
+        if result.is_err() {
+            state.valid = false;
+        }
+        result?;
+        Ok(&state.outputs)
+    }
+
+    fn refresh_inner(&self, state: &mut Incremental) -> error::Result<()> {
+        let full = !state.valid;
+        state.recomputed = 0;
+
+        for r in 0..self.rules.len() {
+            if !(full || state.dirty_rules[r]) {
+                continue;
+            }
+            state.dirty_rules[r] = false;
+            state.recomputed += 1;
+            let alpha = self.compiled[r]
+                .activation(&self.rules[r], &state.inputs, &self.vars, &self.names)
+                .map_err(|e| in_rule(r, e))?;
+            if full || alpha != state.alphas[r] {
+                state.alphas[r] = alpha;
+                for &(v, _) in &self.compiled[r].consequent {
+                    state.dirty_outputs[v.index()] = true;
+                }
+            }
+            // A crisp expression may have changed even when the firing did not.
+            for k in &self.compiled[r].crisp {
+                state.dirty_outputs[k.var.index()] = true;
+            }
+        }
+
+        for &v in &self.outputs {
+            let i = v.index();
+            if !(full || state.dirty_outputs[i]) {
+                continue;
+            }
+            state.dirty_outputs[i] = false;
+            state.agg[i].iter_mut().for_each(|y| *y = 0.0);
+            for &r in &state.writers[i] {
+                self.compiled[r].implicate_into(
+                    state.alphas[r],
+                    &self.vars,
+                    &state.grids,
+                    &mut state.agg,
+                    &mut state.scratch,
+                    |w: VarId| w == v,
+                );
+                self.compiled[r]
+                    .accumulate_crisp(
+                        state.alphas[r],
+                        &state.inputs,
+                        &self.names,
+                        &mut state.agg,
+                        |w: VarId| w == v,
+                    )
+                    .map_err(|e| in_rule(r, e))?;
+            }
+            if let Some(slot) = state.outputs.get_mut(self.var_name(v)) {
+                *slot = self.output_value(v, &state.grids[i], &state.agg[i]);
+            }
+        }
+
+        state.valid = true;
+        Ok(())
+    }
+}
+
+fn in_rule(rule: usize, source: FuzzyError) -> FuzzyError {
+    FuzzyError::InRule {
+        rule,
+        source: Box::new(source),
+    }
+}
+
+fn mark_readers(state: &mut Incremental, var: usize) {
+    for &r in &state.readers[var] {
+        state.dirty_rules[r] = true;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::antecedent::Antecedent;
+    use crate::error::FuzzyError;
+    use crate::mamdani::{Consequent, Rule};
+    use crate::prelude::*;
+    use crate::rulespace::RuleSpace;
+    use crate::variable::Variable;
+
+    fn space() -> RuleSpace {
+        let mut vars = HashMap::new();
+        for name in ["temp", "humidity"] {
+            let mut v = Variable::new(0.0, 10.0).unwrap();
+            v.insert_term(
+                "low",
+                Term::new("low", Triangular::new(-10.0, 0.0, 10.0).unwrap()),
+            )
+            .unwrap();
+            v.insert_term(
+                "high",
+                Term::new("high", Triangular::new(0.0, 10.0, 20.0).unwrap()),
+            )
+            .unwrap();
+            vars.insert(name.to_string(), v);
+        }
+        let mut fan = Variable::new(0.0, 100.0).unwrap();
+        fan.insert_term(
+            "slow",
+            Term::new("slow", Triangular::new(0.0, 25.0, 50.0).unwrap()),
+        )
+        .unwrap();
+        fan.insert_term(
+            "fast",
+            Term::new("fast", Triangular::new(50.0, 75.0, 100.0).unwrap()),
+        )
+        .unwrap();
+        vars.insert("fan".to_string(), fan);
+
+        let rule = |var: &'static str, t: &'static str, f: &'static str| Rule {
+            antecedent: Antecedent::Atom {
+                var: var.into(),
+                term: t.into(),
+            },
+            consequent: vec![Consequent::Term {
+                var: "fan".into(),
+                term: f.into(),
+                hedge: None,
+            }],
+            weight: 1.0,
+        };
+        let rules = vec![
+            rule("temp", "low", "slow"),
+            rule("temp", "high", "fast"),
+            rule("humidity", "high", "fast"),
+        ];
+        RuleSpace::new(vars, rules).unwrap()
+    }
+
+    #[test]
+    fn only_rules_reading_changed_inputs_are_recomputed() {
+        let rs = space();
+        let sampler = UniformSampler::default();
+        let mut state = rs.incremental(&sampler).unwrap();
+
+        let mut input = HashMap::from([("temp", 3.0), ("humidity", 4.0)]);
+        rs.evaluate_incremental(&input, &mut state).unwrap();
+        assert_eq!(state.recomputed_rules(), 3);
+
+        *input.get_mut("humidity").unwrap() = 8.0;
+        let fan = rs.evaluate_incremental(&input, &mut state).unwrap()["fan"];
+        assert_eq!(state.recomputed_rules(), 1);
+        assert!((fan - rs.evaluate(&input, &sampler).unwrap()["fan"]).abs() < 1e-9);
+
+        let fan = rs.update_input("temp", 6.5, &mut state).unwrap()["fan"];
+        assert_eq!(state.recomputed_rules(), 2);
+        input.insert("temp", 6.5);
+        assert!((fan - rs.evaluate(&input, &sampler).unwrap()["fan"]).abs() < 1e-9);
+
+        rs.evaluate_incremental(&input, &mut state).unwrap();
+        assert_eq!(state.recomputed_rules(), 0);
+    }
+
+    #[test]
+    fn stale_states_and_rule_errors_are_reported() {
+        let mut rs = space();
+        let sampler = UniformSampler::default();
+        let mut state = rs.incremental(&sampler).unwrap();
+        let input = HashMap::from([("temp", 3.0), ("humidity", 4.0)]);
+        rs.evaluate_incremental(&input, &mut state).unwrap();
+
+        // Same shape, different rule: the cached readers and activations are stale.
+        let edited = Rule::builder()
+            .when(atom("temp", "low"))
+            .then("fan", "fast")
+            .build()
+            .unwrap();
+        rs.replace_rule(0, edited).unwrap();
+        assert_eq!(
+            rs.evaluate_incremental(&input, &mut state).err(),
+            Some(FuzzyError::ScratchMismatch)
+        );
+        assert_eq!(
+            rs.update_input("temp", 5.0, &mut state).err(),
+            Some(FuzzyError::ScratchMismatch)
+        );
+        assert_eq!(
+            space()
+                .evaluate_incremental(&input, &mut rs.incremental(&sampler).unwrap())
+                .err(),
+            Some(FuzzyError::ScratchMismatch)
+        );
+
+        let mut state = rs.incremental(&sampler).unwrap();
+        let fan = rs.evaluate_incremental(&input, &mut state).unwrap()["fan"];
+        assert!((fan - rs.evaluate(&input, &sampler).unwrap()["fan"]).abs() < 1e-9);
+
+        let err = rs.update_input("temp", 12.0, &mut state).unwrap_err();
+        assert!(matches!(err, FuzzyError::InRule { rule: 0, .. }));
+        assert!(matches!(err.root(), FuzzyError::OutOfDomain { .. }));
+    }
+}
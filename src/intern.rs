@@ -0,0 +1,96 @@
+// Interning layer: compact ids for variable and term names.
+//
+// The engine resolves names to ids once (when rules are added) and works on ids
+// afterwards, so string hashing only happens at the API boundary.
+use std::collections::HashMap;
+
+/// Index of a variable inside a `RuleSpace`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct VarId(pub(crate) u32);
+
+/// Index of a term inside its `Variable`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct TermId(pub(crate) u32);
+
+impl VarId {
+    /// Position of the variable in its owning container.
+    pub fn index(self) -> usize {
+        self.0 as usize
+    }
+}
+
+impl TermId {
+    /// Position of the term in its owning variable.
+    pub fn index(self) -> usize {
+        self.0 as usize
+    }
+}
+
+/// Bidirectional name <-> dense id table; ids follow insertion order.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Interner {
+    names: Vec<String>,
+    ids: HashMap<String, u32>,
+}
+
+impl Interner {
+    /// Creates an empty table.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the id of `name`, assigning the next free id if it is new.
+    pub fn intern(&mut self, name: &str) -> u32 {
+        if let Some(&id) = self.ids.get(name) {
+            return id;
+        }
+        let id = self.names.len() as u32;
+        self.names.push(name.to_string());
+        self.ids.insert(name.to_string(), id);
+        id
+    }
+
+    /// Looks up the id of an already interned name.
+    pub fn get(&self, name: &str) -> Option<u32> {
+        self.ids.get(name).copied()
+    }
+
+    /// Returns the name behind `id`.
+    ///
+    /// Panics if `id` was not produced by this table.
+    pub fn name(&self, id: u32) -> &str {
+        &self.names[id as usize]
+    }
+
+    /// Number of interned names.
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    /// Returns `true` when nothing has been interned.
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+
+    /// Iterates names in id order.
+    pub fn iter(&self) -> impl Iterator<Item = &str> {
+        self.names.iter().map(String::as_str)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::intern::Interner;
+
+    #[test]
+    fn interner_assigns_dense_stable_ids() {
+        let mut t = Interner::new();
+        assert_eq!(t.intern("temp"), 0);
+        assert_eq!(t.intern("fan"), 1);
+        assert_eq!(t.intern("temp"), 0);
+        assert_eq!(t.get("fan"), Some(1));
+        assert_eq!(t.get("pump"), None);
+        assert_eq!(t.name(1), "fan");
+        assert_eq!(t.iter().collect::<Vec<_>>(), vec!["temp", "fan"]);
+    }
+}
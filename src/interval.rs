@@ -0,0 +1,213 @@
+// Output ranges for interval-valued inputs.
+//
+// Each input is given as `[lo, hi]` and the range of every output over the box
+// they span is estimated by evaluating a regular grid that includes all the
+// box's vertices. Centroid outputs are not monotone in general, so the interior
+// points matter; the estimate only narrows the true range by what can hide
+// between grid points, and more steps tighten it.
+use std::{borrow::Borrow, collections::HashMap, hash::Hash};
+
+use crate::{
+    error::{self, FuzzyError},
+    rulespace::RuleSpace,
+    sampler::UniformSampler,
+    Float,
+};
+
+/// Grid resolution for `RuleSpace::evaluate_interval`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IntervalOptions {
+    /// Points per non-degenerate input interval, endpoints included.
+    pub steps: usize,
+    /// Largest grid evaluated before failing with `OutOfBounds`.
+    pub max_evaluations: usize,
+}
+
+impl Default for IntervalOptions {
+    fn default() -> Self {
+        Self {
+            steps: 5,
+            max_evaluations: 100_000,
+        }
+    }
+}
+
+impl RuleSpace {
+    /// Range `(min, max)` of every output while each input varies over its
+    /// interval `(lo, hi)`; a crisp input is the interval `(x, x)`.
+    ///
+    /// Points where an output is undefined (no rule fired) are skipped; an output
+    /// undefined everywhere gets `(NaN, NaN)`. Fails with `OutOfBounds` if some
+    /// `lo > hi`, `steps < 2`, or the grid exceeds `max_evaluations`, and with the
+    /// errors of `evaluate` for missing or out-of-domain inputs.
+    pub fn evaluate_interval<KI>(
+        &self,
+        input: &HashMap<KI, (Float, Float)>,
+        sampler: &UniformSampler,
+        opts: IntervalOptions,
+    ) -> error::Result<HashMap<String, (Float, Float)>>
+    where
+        KI: Eq + Hash + Borrow<str>,
+    {
+        if opts.steps < 2 {
+            return Err(FuzzyError::OutOfBounds);
+        }
+        let mut axes: Vec<(&str, Vec<Float>)> = Vec::with_capacity(input.len());
+        let mut total = 1usize;
+        for (name, &(lo, hi)) in input {
+            if lo.is_nan() || hi.is_nan() || lo > hi {
+                return Err(FuzzyError::OutOfBounds);
+            }
+            let points: Vec<Float> = if lo == hi {
+                vec![lo]
+            } else {
+                let n = opts.steps - 1;
+                (0..=n)
+                    .map(|i| {
+                        if i == n {
+                            hi
+                        } else {
+                            lo + (hi - lo) * i as Float / n as Float
+                        }
+                    })
+                    .collect()
+            };
+            total = total
+                .checked_mul(points.len())
+                .filter(|&t| t <= opts.max_evaluations)
+                .ok_or(FuzzyError::OutOfBounds)?;
+            axes.push((name.borrow(), points));
+        }
+
+        let mut ranges: HashMap<String, (Float, Float)> = self
+            .outputs()
+            .map(|o| (o.to_string(), (Float::INFINITY, Float::NEG_INFINITY)))
+            .collect();
+        let mut point: HashMap<&str, Float> = HashMap::with_capacity(axes.len());
+        let mut scratch = self.scratch(sampler)?;
+        for mut index in 0..total {
+            for (name, points) in &axes {
+                point.insert(name, points[index % points.len()]);
+                index /= points.len();
+            }
+            for (name, &y) in self.evaluate_in(&point, &mut scratch)? {
+                if let (false, Some((lo, hi))) = (y.is_nan(), ranges.get_mut(name)) {
+                    *lo = lo.min(y);
+                    *hi = hi.max(y);
+                }
+            }
+        }
+        for range in ranges.values_mut() {
+            if range.0 > range.1 {
+                *range = (Float::NAN, Float::NAN);
+            }
+        }
+        Ok(ranges)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::IntervalOptions;
+    use crate::antecedent::Antecedent;
+    use crate::error::FuzzyError;
+    use crate::mamdani::{Consequent, Rule};
+    use crate::prelude::*;
+    use crate::rulespace::RuleSpace;
+    use crate::variable::Variable;
+
+    fn space() -> RuleSpace {
+        let mut temp = Variable::new(0.0, 40.0).unwrap();
+        temp.insert_term(
+            "cold",
+            Term::new("cold", Triangular::new(-40.0, 0.0, 40.0).unwrap()),
+        )
+        .unwrap();
+        temp.insert_term(
+            "hot",
+            Term::new("hot", Triangular::new(0.0, 40.0, 80.0).unwrap()),
+        )
+        .unwrap();
+        let mut fan = Variable::new(0.0, 100.0).unwrap();
+        fan.insert_term(
+            "low",
+            Term::new("low", Triangular::new(-100.0, 0.0, 100.0).unwrap()),
+        )
+        .unwrap();
+        fan.insert_term(
+            "high",
+            Term::new("high", Triangular::new(0.0, 100.0, 200.0).unwrap()),
+        )
+        .unwrap();
+        let rule = |t: &'static str, f: &'static str| Rule {
+            antecedent: Antecedent::Atom {
+                var: "temp".into(),
+                term: t.into(),
+            },
+            consequent: vec![Consequent::Term {
+                var: "fan".into(),
+                term: f.into(),
+                hedge: None,
+            }],
+            weight: 1.0,
+        };
+        let vars = HashMap::from([("temp".to_string(), temp), ("fan".to_string(), fan)]);
+        RuleSpace::new(vars, vec![rule("cold", "low"), rule("hot", "high")]).unwrap()
+    }
+
+    #[test]
+    fn interval_bounds_cover_the_endpoints() {
+        let sampler = UniformSampler::default();
+        let rs = space();
+        let range = rs
+            .evaluate_interval(
+                &HashMap::from([("temp", (10.0, 30.0))]),
+                &sampler,
+                IntervalOptions::default(),
+            )
+            .unwrap()["fan"];
+        let at = |t: Float| {
+            rs.evaluate(&HashMap::from([("temp", t)]), &sampler)
+                .unwrap()["fan"]
+        };
+        assert!((range.0 - at(10.0)).abs() < 1e-6 && (range.1 - at(30.0)).abs() < 1e-6);
+
+        let crisp = rs
+            .evaluate_interval(
+                &HashMap::from([("temp", (20.0, 20.0))]),
+                &sampler,
+                IntervalOptions::default(),
+            )
+            .unwrap()["fan"];
+        assert_eq!(crisp.0, crisp.1);
+    }
+
+    #[test]
+    fn bad_intervals_are_rejected() {
+        let sampler = UniformSampler::default();
+        let rs = space();
+        let opts = IntervalOptions::default();
+        assert_eq!(
+            rs.evaluate_interval(&HashMap::from([("temp", (30.0, 10.0))]), &sampler, opts),
+            Err(FuzzyError::OutOfBounds)
+        );
+        assert_eq!(
+            rs.evaluate_interval(&HashMap::from([("temp", (10.0, 50.0))]), &sampler, opts)
+                .map_err(|e| e.root().clone()),
+            Err(FuzzyError::OutOfDomain {
+                var: "temp".into(),
+                value: 50.0
+            })
+        );
+        let tiny = IntervalOptions {
+            max_evaluations: 3,
+            ..opts
+        };
+        assert_eq!(
+            rs.evaluate_interval(&HashMap::from([("temp", (10.0, 30.0))]), &sampler, tiny),
+            Err(FuzzyError::OutOfBounds)
+        );
+    }
+}
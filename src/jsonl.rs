@@ -0,0 +1,206 @@
+// Streaming JSON Lines evaluation (feature `jsonl`).
+//
+// Each input line is a JSON object mapping input variable names to numbers; each
+// output line is an object mapping output variable names to their crisp values.
+// Records are processed one at a time, so arbitrarily long streams run in
+// constant memory.
+use std::{
+    collections::HashMap,
+    fmt,
+    io::{self, BufRead, Write},
+};
+
+use serde_json::{Map, Value};
+
+use crate::{error::FuzzyError, rulespace::RuleSpace, sampler::UniformSampler, Float};
+
+/// Errors from JSON Lines evaluation, tagged with the 1-based line they came from.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum StreamError {
+    /// The record parsed but could not be evaluated (missing input, non-numeric value, ...).
+    Fuzzy {
+        line: usize,
+        error: FuzzyError,
+    },
+    /// The line is not a JSON object.
+    Json {
+        line: usize,
+        error: serde_json::Error,
+    },
+    Io(io::Error),
+}
+
+impl fmt::Display for StreamError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            StreamError::Fuzzy { line, error } => write!(f, "line {line}: {error}"),
+            StreamError::Json { line, error } => write!(f, "line {line}: invalid JSON: {error}"),
+            StreamError::Io(e) => write!(f, "I/O error: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for StreamError {}
+
+impl From<io::Error> for StreamError {
+    fn from(e: io::Error) -> Self {
+        StreamError::Io(e)
+    }
+}
+
+impl RuleSpace {
+    /// Evaluate a JSON Lines stream, writing one output object per input record.
+    ///
+    /// Blank lines are skipped; keys that are not variables of the rule space are
+    /// ignored. Non-numeric values fail with `TypeMismatch`, and outputs no rule
+    /// fired for (NaN) are written as `null`. Returns the number of records written.
+    pub fn evaluate_jsonl<R, W>(
+        &self,
+        reader: R,
+        mut writer: W,
+        sampler: &UniformSampler,
+    ) -> Result<usize, StreamError>
+    where
+        R: BufRead,
+        W: Write,
+    {
+        let mut scratch = self
+            .scratch(sampler)
+            .map_err(|error| StreamError::Fuzzy { line: 0, error })?;
+        let mut input: HashMap<String, Float> = HashMap::new();
+        let mut written = 0;
+
+        for (i, line) in reader.lines().enumerate() {
+            let line_no = i + 1;
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let record: Map<String, Value> =
+                serde_json::from_str(&line).map_err(|error| StreamError::Json {
+                    line: line_no,
+                    error,
+                })?;
+
+            input.clear();
+            for (key, value) in record {
+                let x = value.as_f64().ok_or(StreamError::Fuzzy {
+                    line: line_no,
+                    error: FuzzyError::TypeMismatch,
+                })?;
+                input.insert(key, x as Float);
+            }
+
+            let out =
+                self.evaluate_in(&input, &mut scratch)
+                    .map_err(|error| StreamError::Fuzzy {
+                        line: line_no,
+                        error,
+                    })?;
+            let record: Map<String, Value> = self
+                .outputs()
+                .map(|name| (name.to_string(), Value::from(out[name])))
+                .collect();
+            serde_json::to_writer(&mut writer, &record).map_err(|e| StreamError::Io(e.into()))?;
+            writer.write_all(b"\n")?;
+            written += 1;
+        }
+
+        writer.flush()?;
+        Ok(written)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::StreamError;
+    use crate::antecedent::Antecedent;
+    use crate::error::FuzzyError;
+    use crate::mamdani::{Consequent, Rule};
+    use crate::prelude::*;
+    use crate::rulespace::RuleSpace;
+    use crate::variable::Variable;
+    use crate::Float;
+
+    fn space() -> RuleSpace {
+        let mut temp = Variable::new(0.0, 10.0).unwrap();
+        temp.insert_term(
+            "hot",
+            Term::new("hot", Triangular::new(0.0, 10.0, 20.0).unwrap()),
+        )
+        .unwrap();
+        let mut fan = Variable::new(0.0, 10.0).unwrap();
+        fan.insert_term(
+            "high",
+            Term::new("high", Triangular::new(0.0, 10.0, 20.0).unwrap()),
+        )
+        .unwrap();
+        let rule = Rule {
+            antecedent: Antecedent::Atom {
+                var: "temp".into(),
+                term: "hot".into(),
+            },
+            consequent: vec![Consequent::Term {
+                var: "fan".into(),
+                term: "high".into(),
+                hedge: None,
+            }],
+            weight: 1.0,
+        };
+        let vars = HashMap::from([("temp".to_string(), temp), ("fan".to_string(), fan)]);
+        RuleSpace::new(vars, vec![rule]).unwrap()
+    }
+
+    #[test]
+    fn one_output_line_per_record() {
+        let rs = space();
+        let sampler = UniformSampler::default();
+        let input = "{\"temp\": 7.5, \"id\": 3}\n\n{\"temp\": 0}\n";
+        let mut out = Vec::new();
+        let n = rs
+            .evaluate_jsonl(input.as_bytes(), &mut out, &sampler)
+            .unwrap();
+        assert_eq!(n, 2);
+
+        let lines: Vec<serde_json::Value> = String::from_utf8(out)
+            .unwrap()
+            .lines()
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect();
+        let want = rs
+            .evaluate(&HashMap::from([("temp", 7.5)]), &sampler)
+            .unwrap()["fan"];
+        let got: Float = serde_json::from_value(lines[0]["fan"].clone()).unwrap();
+        assert!((got - want).abs() < 1e-6);
+        // Nothing fires at temp = 0, so the centroid is undefined.
+        assert!(lines[1]["fan"].is_null());
+    }
+
+    #[test]
+    fn errors_carry_the_line_number() {
+        let rs = space();
+        let sampler = UniformSampler::default();
+        let err = rs
+            .evaluate_jsonl(
+                "{\"temp\": 1}\n{\"temp\": \"x\"}".as_bytes(),
+                Vec::new(),
+                &sampler,
+            )
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            StreamError::Fuzzy {
+                line: 2,
+                error: FuzzyError::TypeMismatch
+            }
+        ));
+
+        let err = rs
+            .evaluate_jsonl("not json".as_bytes(), Vec::new(), &sampler)
+            .unwrap_err();
+        assert!(matches!(err, StreamError::Json { line: 1, .. }));
+    }
+}
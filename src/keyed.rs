@@ -0,0 +1,196 @@
+// Evaluation keyed by user-defined variable identifiers.
+//
+// The string-keyed API is convenient but leaves typos to runtime and hashes
+// every name on every call. `KeyedSpace` binds a caller's own key type (usually
+// a small `Copy` enum) to the rule space's variables once, then lays inputs out
+// by variable id directly and reports outputs under the same keys.
+use std::{collections::HashMap, hash::Hash};
+
+use crate::{
+    error::{self, FuzzyError, MissingSpace},
+    intern::VarId,
+    rulespace::{RuleSpace, Scratch},
+    sampler::UniformSampler,
+    Float,
+};
+
+/// A rule space whose variables are addressed by keys of type `K`.
+///
+/// ```rust
+/// # use std::collections::HashMap;
+/// # use rust_fuzzylogic::prelude::*;
+/// # use rust_fuzzylogic::keyed::KeyedSpace;
+/// #[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+/// enum Var {
+///     Temp,
+///     Fan,
+/// }
+///
+/// let mut temp = Variable::new(0.0, 10.0).unwrap();
+/// temp.insert_term("hot", Term::new("hot", Triangular::new(0.0, 10.0, 20.0).unwrap()))
+///     .unwrap();
+/// let mut fan = Variable::new(0.0, 100.0).unwrap();
+/// fan.insert_term("high", Term::new("high", Triangular::new(0.0, 100.0, 200.0).unwrap()))
+///     .unwrap();
+/// let rule = Rule::builder().when(atom("temp", "hot")).then("fan", "high").build().unwrap();
+/// let vars = HashMap::from([("temp".to_string(), temp), ("fan".to_string(), fan)]);
+/// let space = RuleSpace::new(vars, vec![rule]).unwrap();
+///
+/// let mut keyed = KeyedSpace::new(
+///     space,
+///     &UniformSampler::default(),
+///     [(Var::Temp, "temp"), (Var::Fan, "fan")],
+/// )
+/// .unwrap();
+/// let out = keyed.evaluate(&HashMap::from([(Var::Temp, 7.0)])).unwrap();
+/// assert!(out[&Var::Fan] > 50.0);
+/// ```
+pub struct KeyedSpace<K> {
+    space: RuleSpace,
+    scratch: Scratch,
+    inputs: Vec<(K, VarId)>,
+    outputs: Vec<Option<K>>,
+    result: HashMap<K, Float>,
+}
+
+impl<K: Copy + Eq + Hash> KeyedSpace<K> {
+    /// Binds each key to the variable it names.
+    ///
+    /// Every input variable must be bound; outputs left unbound are evaluated
+    /// but not reported. Fails with `NotFound` for a name the space lacks or an
+    /// unbound input, and with `TypeMismatch` when a key or variable is bound
+    /// twice.
+    pub fn new<'a>(
+        space: RuleSpace,
+        sampler: &UniformSampler,
+        bindings: impl IntoIterator<Item = (K, &'a str)>,
+    ) -> error::Result<Self> {
+        let mut keys: HashMap<K, VarId> = HashMap::new();
+        let mut bound: Vec<Option<K>> = vec![None; space.vars().count()];
+        for (key, name) in bindings {
+            let id = space.var_id(name).ok_or_else(|| FuzzyError::NotFound {
+                space: MissingSpace::Var,
+                key: name.to_string(),
+            })?;
+            if keys.insert(key, id).is_some() || bound[id.index()].replace(key).is_some() {
+                return Err(FuzzyError::TypeMismatch);
+            }
+        }
+
+        let inputs = space
+            .inputs()
+            .map(|name| {
+                let id = space.var_id(name).expect("input names resolve");
+                bound[id.index()]
+                    .map(|key| (key, id))
+                    .ok_or_else(|| FuzzyError::NotFound {
+                        space: MissingSpace::Input,
+                        key: name.to_string(),
+                    })
+            })
+            .collect::<error::Result<_>>()?;
+        let outputs = space
+            .outputs()
+            .map(|name| bound[space.var_id(name).expect("output names resolve").index()])
+            .collect();
+        Ok(Self {
+            scratch: space.scratch(sampler)?,
+            space,
+            inputs,
+            outputs,
+            result: HashMap::new(),
+        })
+    }
+
+    /// Evaluates `input`, reporting every bound output under its key.
+    ///
+    /// A missing input is handled as in `RuleSpace::evaluate`. The returned map
+    /// is reused by the next call.
+    pub fn evaluate(&mut self, input: &HashMap<K, Float>) -> error::Result<&HashMap<K, Float>> {
+        let Self {
+            space,
+            scratch,
+            inputs,
+            outputs,
+            result,
+        } = self;
+        space.evaluate_ids_in(
+            scratch,
+            |slots| {
+                for &(key, id) in inputs.iter() {
+                    slots[id.index()] = input.get(&key).copied();
+                }
+            },
+            |slot, y| {
+                if let Some(key) = outputs[slot] {
+                    result.insert(key, y);
+                }
+            },
+        )?;
+        Ok(result)
+    }
+
+    /// The wrapped rule space.
+    pub fn space(&self) -> &RuleSpace {
+        &self.space
+    }
+
+    /// Unwraps the rule space, dropping the bindings.
+    pub fn into_inner(self) -> RuleSpace {
+        self.space
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::KeyedSpace;
+    use crate::error::FuzzyError;
+    use crate::prelude::*;
+    use crate::test_support::space;
+
+    #[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+    enum Var {
+        Temp,
+        Fan,
+        Other,
+    }
+
+    #[test]
+    fn keyed_evaluation_matches_named() {
+        let sampler = UniformSampler::default();
+        let mut keyed =
+            KeyedSpace::new(space(), &sampler, [(Var::Temp, "temp"), (Var::Fan, "fan")]).unwrap();
+        for x in [1.0, 4.5, 8.0] {
+            let named = space()
+                .evaluate(&HashMap::from([("temp", x)]), &sampler)
+                .unwrap();
+            let out = keyed.evaluate(&HashMap::from([(Var::Temp, x)])).unwrap();
+            assert_eq!(out.len(), 1);
+            assert!((out[&Var::Fan] - named["fan"]).abs() < 1e-9);
+        }
+        let err = keyed.evaluate(&HashMap::new()).unwrap_err();
+        assert!(matches!(err.root(), FuzzyError::NotFound { .. }));
+
+        // Outputs may stay unbound; inputs may not.
+        let inputs_only = KeyedSpace::new(space(), &sampler, [(Var::Temp, "temp")]).unwrap();
+        assert_eq!(inputs_only.outputs, vec![None]);
+        assert!(matches!(
+            KeyedSpace::new(space(), &sampler, [(Var::Fan, "fan")]),
+            Err(FuzzyError::NotFound { .. })
+        ));
+        assert!(matches!(
+            KeyedSpace::new(space(), &sampler, [(Var::Temp, "temp"), (Var::Other, "rh")]),
+            Err(FuzzyError::NotFound { .. })
+        ));
+        assert!(matches!(
+            KeyedSpace::new(
+                space(),
+                &sampler,
+                [(Var::Temp, "temp"), (Var::Other, "temp")]
+            ),
+            Err(FuzzyError::TypeMismatch)
+        ));
+    }
+}
@@ -1,21 +1,91 @@
 pub mod membership;
 
-//Temporary Module Decleration to avoid error
 pub mod aggregate;
+pub mod anfis;
 pub mod antecedent;
+pub mod attribution;
 pub mod builder;
+pub mod cache;
+pub mod classify;
+pub mod cluster;
+pub mod columnar;
+pub mod control;
+pub mod counters;
+pub mod cri;
+pub mod data;
+pub mod decision;
 pub mod defuzz;
 pub mod error;
+pub mod explain;
+pub mod export;
+pub mod fam;
+pub mod fingerprint;
+pub mod first_match;
+pub mod golden;
+pub mod group;
+pub mod hedges;
+pub mod implication;
+pub mod incremental;
+pub mod intern;
+pub mod interval;
+pub mod keyed;
+pub mod lint;
+pub mod lut;
 pub mod mamdani;
+pub mod metrics;
+pub mod online;
 pub mod ops;
+pub mod overrides;
+pub mod partition;
+pub mod piecewise;
+pub mod pipeline;
+pub mod policy;
+pub mod prune;
+pub(crate) mod rng;
 pub mod rulespace;
 pub mod sampler;
+pub mod sanitize;
+pub mod sensitivity;
+pub mod specialize;
+pub mod stats;
+pub mod strict;
+pub mod surface;
 pub mod system;
 pub mod term;
+pub mod text;
+pub mod tune;
+pub mod validation;
 pub mod variable;
 
 pub mod prelude;
 
+#[cfg(feature = "arrow")]
+pub mod arrow;
+
+#[cfg(feature = "jsonl")]
+pub mod jsonl;
+
+#[cfg(feature = "skfuzzy")]
+pub mod skfuzzy;
+
+#[cfg(feature = "optimize")]
+pub mod optimize;
+
+#[cfg(feature = "python")]
+pub mod python;
+
+#[cfg(feature = "uom")]
+pub mod units;
+
+#[cfg(feature = "async")]
+pub mod yielding;
+
+#[cfg(feature = "proptest")]
+pub mod arbitrary;
+
+#[cfg(test)]
+pub(crate) mod test_support;
+
 //pub use rust_fuzzylogic::triangular::Triangular;
 
 //type definitions
@@ -0,0 +1,247 @@
+// Static checks on a rule base.
+//
+// `RuleSpace::lint` looks for the usual mistakes of hand-written and generated
+// rule bases without evaluating any data: terms no rule mentions, rules that can
+// never fire, and rules entered twice. Reachability is decided by bounding each
+// antecedent over the variables' domains with interval arithmetic on the
+// sampled term memberships. The bound ignores correlations between atoms, so it
+// can miss a contradiction such as `x is a AND NOT x is a`, but it never flags a
+// rule that fires somewhere on the sampling grid.
+use std::{collections::HashSet, fmt};
+
+use crate::{
+    antecedent::Antecedent,
+    error,
+    membership::MembershipFn,
+    rulespace::RuleSpace,
+    sampler::{Sampler, UniformSampler},
+    Float,
+};
+
+/// One finding of `RuleSpace::lint`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Diagnostic {
+    /// A term of an output variable that no consequent concludes.
+    UnusedOutputTerm { var: String, term: String },
+    /// A term of an input variable that no antecedent reads.
+    UnusedInputTerm { var: String, term: String },
+    /// A rule whose antecedent is zero everywhere in the input domains.
+    UnreachableRule { rule: usize },
+    /// A rule identical to an earlier one.
+    DuplicateRule { rule: usize, first: usize },
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Diagnostic::UnusedOutputTerm { var, term } => {
+                write!(f, "output term {var}.{term} is never concluded")
+            }
+            Diagnostic::UnusedInputTerm { var, term } => {
+                write!(f, "input term {var}.{term} is never tested")
+            }
+            Diagnostic::UnreachableRule { rule } => write!(f, "rule {rule} can never fire"),
+            Diagnostic::DuplicateRule { rule, first } => {
+                write!(f, "rule {rule} duplicates rule {first}")
+            }
+        }
+    }
+}
+
+impl RuleSpace {
+    /// Runs every check and returns the findings: unused terms in variable and
+    /// term order, then rule findings in rule order.
+    ///
+    /// Term memberships are sampled with `sampler` to bound the antecedents.
+    pub fn lint(&self, sampler: &UniformSampler) -> error::Result<Vec<Diagnostic>> {
+        let mut read = HashSet::new();
+        let mut concluded = HashSet::new();
+        for rule in &self.rules {
+            collect_atoms(&rule.antecedent, &mut read);
+            for c in &rule.consequent {
+                if let Some(term) = c.term() {
+                    concluded.insert((c.var(), term));
+                }
+            }
+        }
+
+        let mut found = Vec::new();
+        let inputs: HashSet<&str> = self.inputs().collect();
+        let outputs: HashSet<&str> = self.outputs().collect();
+        let mut names: Vec<&str> = self.names.iter().collect();
+        names.sort_unstable();
+        for var in names {
+            let (is_input, is_output) = (inputs.contains(var), outputs.contains(var));
+            let v = self.var(var).expect("names come from the space");
+            for (term, _) in v.terms() {
+                if read.contains(&(var, term)) || concluded.contains(&(var, term)) {
+                    continue;
+                }
+                let (var, term) = (var.to_string(), term.to_string());
+                if is_output {
+                    found.push(Diagnostic::UnusedOutputTerm { var, term });
+                } else if is_input {
+                    found.push(Diagnostic::UnusedInputTerm { var, term });
+                }
+            }
+        }
+
+        for (i, rule) in self.rules.iter().enumerate() {
+            let (_, sup) = self.bounds(&rule.antecedent, sampler)?;
+            if sup <= 0.0 {
+                found.push(Diagnostic::UnreachableRule { rule: i });
+            }
+            let first = self.rules[..i].iter().position(|other| {
+                other.antecedent == rule.antecedent
+                    && other.consequent.len() == rule.consequent.len()
+                    && other
+                        .consequent
+                        .iter()
+                        .zip(&rule.consequent)
+                        .all(|(a, b)| a == b)
+            });
+            if let Some(first) = first {
+                found.push(Diagnostic::DuplicateRule { rule: i, first });
+            }
+        }
+        Ok(found)
+    }
+
+    /// Lower and upper bound of `ant` over the input domains, with the Min–Max
+    /// operators of `eval_with` applied to the bounds of the operands.
+    fn bounds(&self, ant: &Antecedent, sampler: &UniformSampler) -> error::Result<(Float, Float)> {
+        Ok(match ant {
+            Antecedent::Atom { var, term } => {
+                let v = self.var(var).expect("rule atoms name variables");
+                let t = v.get(term).expect("rule atoms name terms");
+                let (min, max) = v.domain();
+                sampler
+                    .sample(min, max)?
+                    .into_iter()
+                    .map(|x| t.eval(x))
+                    .fold((Float::INFINITY, Float::NEG_INFINITY), |(lo, hi), y| {
+                        (lo.min(y), hi.max(y))
+                    })
+            }
+            Antecedent::And(a, b) => {
+                let (a, b) = (self.bounds(a, sampler)?, self.bounds(b, sampler)?);
+                (a.0.min(b.0), a.1.min(b.1))
+            }
+            Antecedent::Or(a, b) => {
+                let (a, b) = (self.bounds(a, sampler)?, self.bounds(b, sampler)?);
+                (a.0.max(b.0), a.1.max(b.1))
+            }
+            Antecedent::Not(a) => {
+                let (lo, hi) = self.bounds(a, sampler)?;
+                (1.0 - hi, 1.0 - lo)
+            }
+            // Every hedge is increasing on [0, 1].
+            Antecedent::Hedged(hedge, a) => {
+                let (lo, hi) = self.bounds(a, sampler)?;
+                (hedge.apply(lo), hedge.apply(hi))
+            }
+            // Means are increasing in every operand too.
+            Antecedent::Mean(kind, operands) => {
+                let bounds = operands
+                    .iter()
+                    .map(|a| self.bounds(a, sampler))
+                    .collect::<error::Result<Vec<_>>>()?;
+                let (lo, hi): (Vec<Float>, Vec<Float>) = bounds.into_iter().unzip();
+                (kind.apply(&lo), kind.apply(&hi))
+            }
+            Antecedent::Const(c) => (*c, *c),
+        })
+    }
+}
+
+fn collect_atoms<'a>(ant: &'a Antecedent, out: &mut HashSet<(&'a str, &'a str)>) {
+    crate::antecedent::for_each_atom(ant, &mut |var, term| {
+        out.insert((var, term));
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::Diagnostic;
+    use crate::antecedent::Antecedent;
+    use crate::mamdani::{Consequent, Rule};
+    use crate::prelude::*;
+    use crate::rulespace::RuleSpace;
+    use crate::variable::Variable;
+
+    fn atom(var: &'static str, term: &'static str) -> Antecedent {
+        Antecedent::Atom {
+            var: var.into(),
+            term: term.into(),
+        }
+    }
+
+    fn rule(antecedent: Antecedent, term: &'static str) -> Rule {
+        Rule {
+            antecedent,
+            consequent: vec![Consequent::Term {
+                var: "fan".into(),
+                term: term.into(),
+                hedge: None,
+            }],
+            weight: 1.0,
+        }
+    }
+
+    #[test]
+    fn lint_reports_each_kind_of_finding() {
+        let mut temp = Variable::new(0.0, 10.0).unwrap();
+        let terms = [
+            ("cold", Triangular::new(-10.0, 0.0, 10.0).unwrap()),
+            ("hot", Triangular::new(0.0, 10.0, 20.0).unwrap()),
+            ("mild", Triangular::new(2.0, 5.0, 8.0).unwrap()),
+            // Lies entirely above the domain.
+            ("scorching", Triangular::new(20.0, 30.0, 40.0).unwrap()),
+        ];
+        for (name, mf) in terms {
+            temp.insert_term(name, Term::new(name, mf)).unwrap();
+        }
+        let mut fan = Variable::new(0.0, 100.0).unwrap();
+        for (name, c) in [("low", 0.0), ("high", 100.0), ("medium", 50.0)] {
+            fan.insert_term(
+                name,
+                Term::new(name, Triangular::new(c - 50.0, c, c + 50.0).unwrap()),
+            )
+            .unwrap();
+        }
+        let vars = HashMap::from([("temp".to_string(), temp), ("fan".to_string(), fan)]);
+        let rules = vec![
+            rule(atom("temp", "cold"), "low"),
+            rule(atom("temp", "hot"), "high"),
+            rule(atom("temp", "cold"), "low"),
+            rule(
+                Antecedent::And(
+                    Box::new(atom("temp", "hot")),
+                    Box::new(atom("temp", "scorching")),
+                ),
+                "high",
+            ),
+        ];
+        let rs = RuleSpace::new(vars, rules).unwrap();
+
+        let found = rs.lint(&UniformSampler::default()).unwrap();
+        assert_eq!(
+            found,
+            vec![
+                Diagnostic::UnusedOutputTerm {
+                    var: "fan".into(),
+                    term: "medium".into()
+                },
+                Diagnostic::UnusedInputTerm {
+                    var: "temp".into(),
+                    term: "mild".into()
+                },
+                Diagnostic::DuplicateRule { rule: 2, first: 0 },
+                Diagnostic::UnreachableRule { rule: 3 },
+            ]
+        );
+        assert_eq!(found[2].to_string(), "rule 2 duplicates rule 0");
+    }
+}
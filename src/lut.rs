@@ -0,0 +1,322 @@
+// Lookup-table code generation.
+//
+// A controller sampled into a `Surface` (by `RuleSpace::surface` on a uniform
+// grid or `RuleSpace::piecewise_linear` on its breakpoints) can be deployed
+// without the inference engine: `Surface::to_lut` prints the axes and samples
+// of one output as static `f32` arrays together with a small interpolation
+// function, in Rust or C. The generated code has no dependencies, allocates
+// nothing and runs in time linear in the axis lengths.
+use std::fmt::Write;
+
+use crate::{
+    error::{FuzzyError, MissingSpace, Result},
+    surface::Surface,
+    Float,
+};
+
+/// Target language of `Surface::to_lut`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LutLanguage {
+    /// `no_std`-compatible Rust: `pub const` arrays and a `pub fn`.
+    Rust,
+    /// C99: `static const` arrays and a function with external linkage.
+    C,
+}
+
+impl Surface {
+    /// Source code evaluating output `output` by interpolating this surface.
+    ///
+    /// The function is named after the output (non-identifier characters
+    /// become `_`) and takes one `f32` per swept input, in axis order. Inputs
+    /// outside the axes are clamped; NaN samples stay NaN. Fails with
+    /// `NotFound` for an unknown output and `BadArity` for an axis with fewer
+    /// than two points.
+    ///
+    /// ```rust
+    /// # use std::collections::HashMap;
+    /// use rust_fuzzylogic::{lut::LutLanguage, prelude::*};
+    ///
+    /// let rs: RuleSpace = "
+    ///     var temp 0 40
+    ///       cold: triangle(-40, 0, 40)
+    ///       hot: triangle(0, 40, 80)
+    ///     var fan 0 100
+    ///       low: triangle(-100, 0, 100)
+    ///       high: triangle(0, 100, 200)
+    ///     IF temp IS cold THEN fan IS low
+    ///     IF temp IS hot THEN fan IS high
+    /// ".parse().unwrap();
+    /// let none: HashMap<&str, Float> = HashMap::new();
+    /// let surface = rs.surface("temp", None, 17, &none, &UniformSampler::default()).unwrap();
+    /// let code = surface.to_lut("fan", LutLanguage::C).unwrap();
+    /// assert!(code.contains("float fan(float temp)"));
+    /// ```
+    pub fn to_lut(&self, output: &str, language: LutLanguage) -> Result<String> {
+        let values = self.output(output).ok_or_else(|| FuzzyError::NotFound {
+            space: MissingSpace::Var,
+            key: output.to_string(),
+        })?;
+        let mut axes = vec![&self.x];
+        axes.extend(&self.y);
+        if axes.iter().any(|a| a.values.len() < 2) {
+            return Err(FuzzyError::BadArity);
+        }
+
+        let name = ident(output);
+        let upper = name.to_ascii_uppercase();
+        let args: Vec<String> = axes.iter().map(|a| ident(&a.name)).collect();
+        let suffixes = ["X", "Y"];
+        let mut out = String::new();
+        let inputs = args.join(", ");
+        let _ = writeln!(
+            out,
+            "// Lookup table for `{output}` over ({inputs}), generated by rust-fuzzylogic."
+        );
+        match language {
+            LutLanguage::Rust => {
+                for (axis, s) in axes.iter().zip(suffixes) {
+                    array_rust(&mut out, &format!("{upper}_{s}"), &axis.values);
+                }
+                array_rust(&mut out, &format!("{upper}_TABLE"), values);
+                let _ = writeln!(
+                    out,
+                    "\nfn {name}_locate(axis: &[f32], v: f32) -> (usize, f32) {{\n    \
+                     let mut i = 0;\n    \
+                     while i + 2 < axis.len() && v >= axis[i + 1] {{\n        i += 1;\n    }}\n    \
+                     let t = (v - axis[i]) / (axis[i + 1] - axis[i]);\n    \
+                     (i, t.max(0.0).min(1.0))\n}}\n"
+                );
+                let params: Vec<String> = args.iter().map(|a| format!("{a}: f32")).collect();
+                let _ = writeln!(
+                    out,
+                    "/// Interpolated `{output}`; inputs outside the table are clamped.\n\
+                     pub fn {name}({}) -> f32 {{",
+                    params.join(", ")
+                );
+                let _ = writeln!(
+                    out,
+                    "    let (i, tx) = {name}_locate(&{upper}_X, {});",
+                    args[0]
+                );
+                let _ = writeln!(out, "    let nx = {upper}_X.len();");
+                match args.get(1) {
+                    None => {
+                        let _ = writeln!(out, "    let row = 0;");
+                    }
+                    Some(y) => {
+                        let _ = writeln!(out, "    let (j, ty) = {name}_locate(&{upper}_Y, {y});");
+                        let _ = writeln!(out, "    let row = j * nx;");
+                    }
+                }
+                let _ = writeln!(
+                    out,
+                    "    let at = |k: usize| {upper}_TABLE[k] + tx * ({upper}_TABLE[k + 1] - {upper}_TABLE[k]);"
+                );
+                if args.len() == 1 {
+                    let _ = writeln!(out, "    at(row + i)");
+                } else {
+                    let _ = writeln!(out, "    let lo = at(row + i);");
+                    let _ = writeln!(out, "    lo + ty * (at(row + nx + i) - lo)");
+                }
+                let _ = writeln!(out, "}}");
+            }
+            LutLanguage::C => {
+                let _ = writeln!(out, "#include <math.h>\n#include <stddef.h>\n");
+                for (axis, s) in axes.iter().zip(suffixes) {
+                    array_c(&mut out, &format!("{upper}_{s}"), &axis.values);
+                }
+                array_c(&mut out, &format!("{upper}_TABLE"), values);
+                let _ = writeln!(
+                    out,
+                    "\nstatic size_t {name}_locate(const float *axis, size_t n, float v, float *t) {{\n    \
+                     size_t i = 0;\n    \
+                     while (i + 2 < n && v >= axis[i + 1]) {{\n        i++;\n    }}\n    \
+                     *t = fminf(fmaxf((v - axis[i]) / (axis[i + 1] - axis[i]), 0.0f), 1.0f);\n    \
+                     return i;\n}}\n"
+                );
+                let params: Vec<String> = args.iter().map(|a| format!("float {a}")).collect();
+                let _ = writeln!(
+                    out,
+                    "/* Interpolated `{output}`; inputs outside the table are clamped. */\n\
+                     float {name}({}) {{",
+                    params.join(", ")
+                );
+                let nx = self.x.values.len();
+                let _ = writeln!(out, "    float tx, ty;");
+                let _ = writeln!(
+                    out,
+                    "    size_t i = {name}_locate({upper}_X, {nx}, {}, &tx);",
+                    args[0]
+                );
+                match args.get(1) {
+                    None => {
+                        let _ = writeln!(out, "    size_t row = 0;\n    (void)ty;");
+                    }
+                    Some(y) => {
+                        let ny = axes[1].values.len();
+                        let _ = writeln!(
+                            out,
+                            "    size_t row = {name}_locate({upper}_Y, {ny}, {y}, &ty) * {nx};"
+                        );
+                    }
+                }
+                let _ = writeln!(
+                    out,
+                    "    const float *k = {upper}_TABLE + row + i;\n    \
+                     float lo = k[0] + tx * (k[1] - k[0]);"
+                );
+                if args.len() == 1 {
+                    let _ = writeln!(out, "    return lo;");
+                } else {
+                    let _ = writeln!(
+                        out,
+                        "    float hi = k[{nx}] + tx * (k[{nx} + 1] - k[{nx}]);\n    \
+                         return lo + ty * (hi - lo);"
+                    );
+                }
+                let _ = writeln!(out, "}}");
+            }
+        }
+        Ok(out)
+    }
+}
+
+/// `name` as an identifier: other characters become `_`, and a leading digit
+/// gets a `_` prefix.
+fn ident(name: &str) -> String {
+    let mut s: String = name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    if s.is_empty() || s.starts_with(|c: char| c.is_ascii_digit()) {
+        s.insert(0, '_');
+    }
+    s
+}
+
+/// `v` as the `f32` the generated tables hold.
+fn single(v: Float) -> f32 {
+    #[allow(clippy::unnecessary_cast)] // Float is f32 under the `f32` feature.
+    let v = v as f32;
+    v
+}
+
+fn array_rust(out: &mut String, name: &str, values: &[Float]) {
+    let items: Vec<String> = values
+        .iter()
+        .map(|&v| match single(v) {
+            v if v.is_nan() => "f32::NAN".to_string(),
+            v => format!("{v:?}"),
+        })
+        .collect();
+    let _ = writeln!(
+        out,
+        "pub const {name}: [f32; {}] = [{}];",
+        values.len(),
+        items.join(", ")
+    );
+}
+
+fn array_c(out: &mut String, name: &str, values: &[Float]) {
+    let items: Vec<String> = values
+        .iter()
+        .map(|&v| match single(v) {
+            v if v.is_nan() => "NAN".to_string(),
+            v => format!("{v:?}f"),
+        })
+        .collect();
+    let _ = writeln!(
+        out,
+        "static const float {name}[{}] = {{{}}};",
+        values.len(),
+        items.join(", ")
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::LutLanguage;
+    use crate::error::FuzzyError;
+    use crate::prelude::*;
+    use crate::surface::{Axis, Surface};
+
+    fn surface() -> Surface {
+        Surface {
+            x: Axis {
+                name: "temp".into(),
+                values: vec![0.0, 10.0, 40.0],
+            },
+            y: Some(Axis {
+                name: "load %".into(),
+                values: vec![0.0, 1.0],
+            }),
+            outputs: vec![(
+                "fan-speed".into(),
+                vec![0.0, 20.0, 80.0, 10.0, 30.0, Float::NAN],
+            )],
+        }
+    }
+
+    #[test]
+    fn rust_table_lists_axes_and_samples() {
+        let code = surface().to_lut("fan-speed", LutLanguage::Rust).unwrap();
+        assert!(code.contains("pub const FAN_SPEED_X: [f32; 3] = [0.0, 10.0, 40.0];"));
+        assert!(code.contains("pub const FAN_SPEED_Y: [f32; 2] = [0.0, 1.0];"));
+        assert!(code.contains(
+            "pub const FAN_SPEED_TABLE: [f32; 6] = [0.0, 20.0, 80.0, 10.0, 30.0, f32::NAN];"
+        ));
+        assert!(code.contains("pub fn fan_speed(temp: f32, load__: f32) -> f32 {"));
+        assert!(code.contains("fn fan_speed_locate("));
+    }
+
+    #[test]
+    fn c_table_uses_float_literals() {
+        let code = surface().to_lut("fan-speed", LutLanguage::C).unwrap();
+        assert!(code.contains("static const float FAN_SPEED_X[3] = {0.0f, 10.0f, 40.0f};"));
+        assert!(code.contains("NAN};"));
+        assert!(code.contains("float fan_speed(float temp, float load__) {"));
+        assert!(code.contains("k[3] + tx * (k[3 + 1] - k[3])"));
+
+        assert!(matches!(
+            surface().to_lut("power", LutLanguage::C),
+            Err(FuzzyError::NotFound { .. })
+        ));
+        let mut short = surface();
+        short.x.values.truncate(1);
+        assert!(matches!(
+            short.to_lut("fan-speed", LutLanguage::Rust),
+            Err(FuzzyError::BadArity)
+        ));
+    }
+
+    /// The generated interpolation mirrors `Surface::interpolate`.
+    #[test]
+    fn sampled_system_round_trips_through_interpolation() {
+        let rs: RuleSpace = "
+            var temp 0 40
+              cold: triangle(-40, 0, 40)
+              hot: triangle(0, 40, 80)
+            var fan 0 100
+              low: triangle(-100, 0, 100)
+              high: triangle(0, 100, 200)
+            IF temp IS cold THEN fan IS low
+            IF temp IS hot THEN fan IS high
+        "
+        .parse()
+        .unwrap();
+        let sampler = UniformSampler::default();
+        let none: HashMap<&str, Float> = HashMap::new();
+        let s = rs.surface("temp", None, 41, &none, &sampler).unwrap();
+        let code = s.to_lut("fan", LutLanguage::Rust).unwrap();
+        assert!(code.contains("pub const FAN_TABLE: [f32; 41]"));
+        for t in [0.5, 13.3, 39.9] {
+            let want = rs
+                .evaluate(&HashMap::from([("temp", t)]), &sampler)
+                .unwrap()["fan"];
+            let got = s.interpolate("fan", t, 0.0).unwrap();
+            assert!((got - want).abs() < 0.5, "{t}: {got} {want}");
+        }
+    }
+}
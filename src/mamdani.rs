@@ -4,31 +4,307 @@ use std::{borrow::Borrow, collections::HashMap, hash::Hash};
 
 //#[cfg(feature = "inference-mamdani")]
 use crate::{
-    antecedent::{eval_antecedent, Antecedent},
+    aggregate::Aggregation,
+    antecedent::{
+        eval_antecedent, eval_ops, for_each_atom, validate_antecedent, Antecedent, DefaultOps, Name,
+    },
     error::{FuzzyError, MissingSpace},
+    hedges::Hedge,
+    intern::{Interner, TermId, VarId},
+    membership::MembershipFn,
     prelude::*,
-    sampler::UniformSampler,
+    sampler::{GridCache, UniformSampler},
     variable::Variable,
 };
 
-/// Output clause of a fuzzy rule referencing a linguistic variable and term.
-pub struct Consequent {
-    pub var: String,
-    pub term: String,
-    //pub weight: Float,
-    //pub imp: Implication,
+/// How a rule's firing strength shapes the sets of its consequent terms.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Implication {
+    /// Mamdani: clip the term at the firing strength, `min(alpha, mu)`.
+    #[default]
+    Min,
+    /// Larsen: scale the term by the firing strength, `alpha * mu`.
+    Product,
+}
+
+impl Implication {
+    /// Implicated degree of a term point with membership `mu` under firing strength `alpha`.
+    pub fn apply(self, alpha: Float, mu: Float) -> Float {
+        match self {
+            Implication::Min => mu.min(alpha),
+            Implication::Product => mu * alpha,
+        }
+    }
+}
+
+/// Output clause of a fuzzy rule.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Consequent {
+    /// `var IS term`: the firing strength shapes the term's set (Mamdani).
+    Term {
+        var: Name,
+        term: Name,
+        /// Optional hedge applied to the term's membership before implication
+        /// ("THEN fan IS very High").
+        hedge: Option<Hedge>,
+    },
+    /// `var = expr`: a crisp value weighted by the firing strength (Sugeno).
+    ///
+    /// The output is the firing-weighted average `sum(alpha * expr) / sum(alpha)`
+    /// over the rules concluding on `var`, NaN when none fires. A variable is
+    /// either crisp or fuzzy across the whole rule base. `evaluate` and its
+    /// variants, `explain`, `evaluate_strict`, `evaluate_with_overrides` and
+    /// incremental evaluation support crisp outputs. Engines built on output
+    /// sets (first-match, grouped, columnar, Arrow/Parquet, implicative and CRI
+    /// inference) reject a rule base with crisp consequents with
+    /// `TypeMismatch` wrapped in `InRule`, naming the first such rule.
+    Crisp { var: Name, expr: CrispExpr },
+}
+
+impl Consequent {
+    /// Unhedged clause `var IS term`.
+    pub fn new(var: impl Into<Name>, term: impl Into<Name>) -> Self {
+        Self::Term {
+            var: var.into(),
+            term: term.into(),
+            hedge: None,
+        }
+    }
+
+    /// Crisp clause `var = expr`.
+    pub fn crisp(var: impl Into<Name>, expr: CrispExpr) -> Self {
+        Self::Crisp {
+            var: var.into(),
+            expr,
+        }
+    }
+
+    /// Sets the hedge applied to the term; crisp clauses are returned unchanged.
+    pub fn with_hedge(mut self, hedge: Hedge) -> Self {
+        if let Self::Term { hedge: h, .. } = &mut self {
+            *h = Some(hedge);
+        }
+        self
+    }
+
+    /// The output variable the clause concludes on.
+    pub fn var(&self) -> &str {
+        match self {
+            Self::Term { var, .. } | Self::Crisp { var, .. } => var,
+        }
+    }
+
+    /// The concluded term; `None` for a crisp clause.
+    pub fn term(&self) -> Option<&str> {
+        match self {
+            Self::Term { term, .. } => Some(term),
+            Self::Crisp { .. } => None,
+        }
+    }
+
+    /// The hedge on the concluded term, if any.
+    pub fn hedge(&self) -> Option<Hedge> {
+        match self {
+            Self::Term { hedge, .. } => *hedge,
+            Self::Crisp { .. } => None,
+        }
+    }
+}
+
+/// Rule-text form, e.g. `fan IS very high` or `fan = 10 + 2 * temp`.
+impl std::fmt::Display for Consequent {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Term {
+                var,
+                term,
+                hedge: Some(hedge),
+            } => write!(f, "{var} IS {hedge} {term}"),
+            Self::Term { var, term, .. } => write!(f, "{var} IS {term}"),
+            Self::Crisp { var, expr } => write!(f, "{var} = {expr}"),
+        }
+    }
+}
+
+/// Linear function of the inputs, `constant + sum(coefficient * input)`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CrispExpr {
+    pub constant: Float,
+    pub coefficients: Vec<(Name, Float)>,
+}
+
+impl CrispExpr {
+    /// The constant `value` (a zero-order Sugeno consequent).
+    pub fn constant(value: Float) -> Self {
+        Self {
+            constant: value,
+            coefficients: Vec::new(),
+        }
+    }
+
+    /// `constant + sum(coefficient * var)` (a first-order Sugeno consequent).
+    pub fn linear<N: Into<Name>>(
+        constant: Float,
+        coefficients: impl IntoIterator<Item = (N, Float)>,
+    ) -> Self {
+        Self {
+            constant,
+            coefficients: coefficients
+                .into_iter()
+                .map(|(var, k)| (var.into(), k))
+                .collect(),
+        }
+    }
+}
+
+/// Written as `c + k * var - k * var`, omitting a zero constant.
+impl std::fmt::Display for CrispExpr {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut first = true;
+        if self.constant != 0.0 || self.coefficients.is_empty() {
+            write!(f, "{}", self.constant)?;
+            first = false;
+        }
+        for (var, k) in &self.coefficients {
+            match (first, *k < 0.0) {
+                (true, _) => write!(f, "{k} * {var}")?,
+                (false, false) => write!(f, " + {k} * {var}")?,
+                (false, true) => write!(f, " - {} * {var}", -k)?,
+            }
+            first = false;
+        }
+        Ok(())
+    }
 }
 
 /// Full fuzzy rule pairing an antecedent with one or more consequents.
+#[derive(Debug, Clone, PartialEq)]
 pub struct Rule {
     pub antecedent: Antecedent,
     pub consequent: Vec<Consequent>,
+    /// Factor in [0, 1] scaling the firing strength; 1 leaves it unchanged.
+    pub weight: Float,
+}
+
+/// Fluent construction of a `Rule`, started with `Rule::builder()`.
+///
+/// ```rust
+/// use rust_fuzzylogic::prelude::*;
+///
+/// let rule = Rule::builder()
+///     .when(atom("temp", "hot").and(not(atom("temp", "cold"))))
+///     .then("fanspeed", "High")
+///     .then("pumpspeed", "High")
+///     .weight(0.8)
+///     .build()
+///     .unwrap();
+/// assert_eq!(rule.consequent.len(), 2);
+/// assert_eq!(rule.weight, 0.8);
+/// ```
+#[derive(Debug, Default)]
+pub struct RuleBuilder {
+    antecedent: Option<Antecedent>,
+    consequent: Vec<Consequent>,
+    weight: Option<Float>,
+}
+
+impl RuleBuilder {
+    /// Set the antecedent; calling `when` again ANDs the new condition on.
+    pub fn when(mut self, antecedent: Antecedent) -> Self {
+        self.antecedent = Some(match self.antecedent.take() {
+            Some(prev) => prev.and(antecedent),
+            None => antecedent,
+        });
+        self
+    }
+
+    /// Add the consequent clause `var IS term`.
+    pub fn then(mut self, var: impl Into<Name>, term: impl Into<Name>) -> Self {
+        self.consequent.push(Consequent::new(var, term));
+        self
+    }
+
+    /// Add the hedged consequent clause `var IS hedge term`.
+    pub fn then_hedged(
+        mut self,
+        var: impl Into<Name>,
+        hedge: Hedge,
+        term: impl Into<Name>,
+    ) -> Self {
+        self.consequent
+            .push(Consequent::new(var, term).with_hedge(hedge));
+        self
+    }
+
+    /// Add the crisp consequent clause `var = expr`.
+    pub fn then_crisp(mut self, var: impl Into<Name>, expr: CrispExpr) -> Self {
+        self.consequent.push(Consequent::crisp(var, expr));
+        self
+    }
+
+    /// Scale the rule's firing strength by `weight`, in [0, 1].
+    pub fn weight(mut self, weight: Float) -> Self {
+        self.weight = Some(weight);
+        self
+    }
+
+    /// Finish the rule. Fails with `EmptyInput` if `when` or `then` was never
+    /// called and with `InvalidParameter` for a weight outside [0, 1].
+    pub fn build(self) -> Result<Rule> {
+        let weight = check_weight(self.weight.unwrap_or(1.0))?;
+        match self.antecedent {
+            Some(antecedent) if !self.consequent.is_empty() => Ok(Rule {
+                antecedent,
+                consequent: self.consequent,
+                weight,
+            }),
+            _ => Err(FuzzyError::EmptyInput),
+        }
+    }
+}
+
+/// `weight` if it is a valid rule weight, a number in [0, 1].
+pub(crate) fn check_weight(weight: Float) -> Result<Float> {
+    if (0.0..=1.0).contains(&weight) {
+        Ok(weight)
+    } else {
+        Err(FuzzyError::InvalidParameter {
+            mf: "Rule",
+            field: "weight",
+            value: weight,
+        })
+    }
 }
 
 //Mamdani Inference Engine
 //#[cfg(feature = "inference-mamdani")]
+/// Rule-text form: `IF <antecedent> THEN <consequent> AND ...`, followed by
+/// `WITH <weight>` unless the weight is 1.
+impl std::fmt::Display for Rule {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "IF {} THEN ", self.antecedent)?;
+        for (i, c) in self.consequent.iter().enumerate() {
+            if i > 0 {
+                f.write_str(" AND ")?;
+            }
+            write!(f, "{c}")?;
+        }
+        if self.weight != 1.0 {
+            write!(f, " WITH {}", self.weight)?;
+        }
+        Ok(())
+    }
+}
+
 impl Rule {
-    /// Evaluate the antecedent against crisp input values to obtain activation.
+    /// Start building a rule fluently; see `RuleBuilder`.
+    pub fn builder() -> RuleBuilder {
+        RuleBuilder::default()
+    }
+
+    /// Evaluate the antecedent against crisp input values to obtain activation,
+    /// scaled by the rule weight.
     pub fn activation<KI, KV>(
         &self,
         input: &HashMap<KI, Float>,
@@ -38,49 +314,279 @@ impl Rule {
         KI: Eq + Hash + Borrow<str>,
         KV: Eq + Hash + Borrow<str>,
     {
-        eval_antecedent(&self.antecedent, input, vars)
+        Ok(eval_antecedent(&self.antecedent, input, vars)? * self.weight)
     }
 
     /// Apply the selected implication operator to produce discretized membership outputs.
     pub fn implicate<KV>(
         &self,
         alpha: Float,
-        vers: &HashMap<KV, Variable>,
+        vars: &HashMap<KV, Variable>,
         sampler: &UniformSampler,
     ) -> Result<HashMap<String, Vec<Float>>>
+    where
+        KV: Eq + Hash + Borrow<str>,
+    {
+        self.implicate_cached(alpha, vars, sampler, &mut GridCache::new())
+    }
+
+    /// Same as `implicate`, but reuses sampled domain grids from `grids`.
+    pub fn implicate_cached<KV>(
+        &self,
+        alpha: Float,
+        vars: &HashMap<KV, Variable>,
+        sampler: &UniformSampler,
+        grids: &mut GridCache,
+    ) -> Result<HashMap<String, Vec<Float>>>
+    where
+        KV: Eq + Hash + Borrow<str>,
+    {
+        self.implicate_where(alpha, vars, sampler, grids, |_| true)
+    }
+
+    /// Same as `implicate_cached`, restricted to consequents whose variable passes `keep`.
+    ///
+    /// Crisp consequents have no set and are skipped.
+    pub(crate) fn implicate_where<KV>(
+        &self,
+        alpha: Float,
+        vars: &HashMap<KV, Variable>,
+        sampler: &UniformSampler,
+        grids: &mut GridCache,
+        keep: impl Fn(&str) -> bool,
+    ) -> Result<HashMap<String, Vec<Float>>>
     where
         KV: Eq + Hash + Borrow<str>,
     {
         let mut result_map: HashMap<String, Vec<Float>> = HashMap::new();
 
-        for i in 0..self.consequent.len() {
-            let mut result_vec = vec![0.0; sampler.n];
-
-            let (dom_min, dom_max) = vers
-                .get(&self.consequent[i].var.as_str())
-                .ok_or(FuzzyError::NotFound {
-                    space: MissingSpace::Var,
-                    key: self.consequent[i].var.clone(),
-                })?
-                .domain();
-
-            let step = (dom_max - dom_min) / (sampler.n - 1) as Float;
-
-            for k in 0..sampler.n {
-                let x = dom_min + (k as Float * step);
-                result_vec[k] = vers
-                    .get(&self.consequent[i].var.as_str())
-                    .ok_or(FuzzyError::NotFound {
-                        space: MissingSpace::Var,
-                        key: self.consequent[i].term.clone(),
-                    })?
-                    .eval(&self.consequent[i].term, x)?
-                    .min(alpha);
+        for csq in self.consequent.iter().filter(|c| keep(c.var())) {
+            let Consequent::Term {
+                var: name,
+                term,
+                hedge,
+            } = csq
+            else {
+                continue;
+            };
+            let var = vars.get(&**name).ok_or(FuzzyError::NotFound {
+                space: MissingSpace::Var,
+                key: name.to_string(),
+            })?;
+            let term = var.get(term).ok_or_else(|| FuzzyError::UnknownTerm {
+                var: name.to_string(),
+                term: term.to_string(),
+            })?;
+            let xs = grids.grid(name, var.domain(), sampler)?;
+
+            // Grid points lie inside the domain, so the whole column goes through `eval_grid`.
+            let mut result_vec = vec![0.0; xs.len()];
+            term.eval_grid(xs, &mut result_vec);
+            if let Some(h) = hedge {
+                h.apply_all(&mut result_vec);
+            }
+            for y in result_vec.iter_mut() {
+                *y = y.min(alpha);
+            }
+
+            result_map.insert(name.to_string(), result_vec);
+        }
+        Ok(result_map)
+    }
+}
+
+/// A `Rule` whose variable and term names have been resolved to ids.
+///
+/// Built once when the rule enters a `RuleSpace`; evaluation then indexes
+/// variables and terms directly instead of hashing their names.
+#[derive(Debug, Clone)]
+pub(crate) struct CompiledRule {
+    /// Antecedent atoms in the order `eval_with` visits them.
+    pub(crate) atoms: Vec<(VarId, TermId)>,
+    pub(crate) consequent: Vec<(VarId, TermId)>,
+    /// Hedge of each entry of `consequent`.
+    pub(crate) hedges: Vec<Option<Hedge>>,
+    /// Crisp consequents in rule order; `consequent` and `hedges` hold the
+    /// term ones.
+    pub(crate) crisp: Vec<CompiledCrisp>,
+}
+
+/// A `Consequent::Crisp` with its variables resolved.
+#[derive(Debug, Clone)]
+pub(crate) struct CompiledCrisp {
+    pub(crate) var: VarId,
+    pub(crate) constant: Float,
+    pub(crate) coefficients: Vec<(VarId, Float)>,
+}
+
+impl CompiledCrisp {
+    /// Value of the expression with inputs laid out by `VarId`.
+    pub(crate) fn eval(&self, inputs: &[Option<Float>], names: &Interner) -> Result<Float> {
+        let mut y = self.constant;
+        for &(v, k) in &self.coefficients {
+            let x = inputs[v.index()].ok_or_else(|| FuzzyError::NotFound {
+                space: MissingSpace::Input,
+                key: names.name(v.0).to_string(),
+            })?;
+            y += k * x;
+        }
+        Ok(y)
+    }
+}
+
+impl CompiledRule {
+    /// Resolve every name in `rule`: terms through `resolve`, the variables of
+    /// crisp consequents through `var`.
+    pub(crate) fn compile<F, G>(rule: &Rule, resolve: F, var: G) -> Result<Self>
+    where
+        F: Fn(&str, &str) -> Result<(VarId, TermId)>,
+        G: Fn(&str) -> Result<VarId>,
+    {
+        validate_antecedent(&rule.antecedent)?;
+        check_weight(rule.weight)?;
+        let mut names = Vec::new();
+        for_each_atom(&rule.antecedent, &mut |var, term| names.push((var, term)));
+        let atoms = names
+            .into_iter()
+            .map(|(var, term)| resolve(var, term))
+            .collect::<Result<Vec<_>>>()?;
+        let (mut consequent, mut hedges, mut crisp) = (Vec::new(), Vec::new(), Vec::new());
+        for c in &rule.consequent {
+            match c {
+                Consequent::Term {
+                    var: v,
+                    term,
+                    hedge,
+                } => {
+                    consequent.push(resolve(v, term)?);
+                    hedges.push(hedge.map(Hedge::validate).transpose()?);
+                }
+                Consequent::Crisp { var: v, expr } => crisp.push(CompiledCrisp {
+                    var: var(v)?,
+                    constant: expr.constant,
+                    coefficients: expr
+                        .coefficients
+                        .iter()
+                        .map(|(name, k)| Ok((var(name)?, *k)))
+                        .collect::<Result<_>>()?,
+                }),
             }
+        }
+        Ok(Self {
+            atoms,
+            consequent,
+            hedges,
+            crisp,
+        })
+    }
+
+    /// Adds `alpha`-weighted crisp consequents to their outputs' accumulators
+    /// (see `RuleSpace::reset_agg`).
+    pub(crate) fn accumulate_crisp(
+        &self,
+        alpha: Float,
+        inputs: &[Option<Float>],
+        names: &Interner,
+        agg: &mut [Vec<Float>],
+        keep: impl Fn(VarId) -> bool,
+    ) -> Result<()> {
+        for k in self.crisp.iter().filter(|k| keep(k.var)) {
+            let y = k.eval(inputs, names)?;
+            let acc = &mut agg[k.var.index()];
+            acc[0] += alpha * y;
+            acc[1] += alpha;
+        }
+        Ok(())
+    }
 
-            result_map.insert(self.consequent[i].var.to_string(), result_vec);
+    /// Activation of `rule`, weight included, with inputs laid out by `VarId`.
+    pub(crate) fn activation(
+        &self,
+        rule: &Rule,
+        inputs: &[Option<Float>],
+        vars: &[Variable],
+        names: &Interner,
+    ) -> Result<Float> {
+        self.activation_with(rule, inputs, vars, names, &DefaultOps)
+    }
+
+    /// Like `activation`, combining atoms with `ops`.
+    pub(crate) fn activation_with<O: FuzzyOps + ?Sized>(
+        &self,
+        rule: &Rule,
+        inputs: &[Option<Float>],
+        vars: &[Variable],
+        names: &Interner,
+        ops: &O,
+    ) -> Result<Float> {
+        let mut atoms = self.atoms.iter();
+        let alpha = eval_ops(&rule.antecedent, ops, &mut |_, _| {
+            let &(v, t) = atoms.next().ok_or(FuzzyError::BadArity)?;
+            let x = inputs[v.index()].ok_or_else(|| FuzzyError::NotFound {
+                space: MissingSpace::Input,
+                key: names.name(v.0).to_string(),
+            })?;
+            vars[v.index()].eval_id(t, x)
+        })?;
+        Ok(alpha * rule.weight)
+    }
+
+    /// Clip each consequent term at `alpha` and max-merge it into `agg` (indexed by `VarId`).
+    ///
+    /// `agg[v]` must already hold one (initially zero) slot per grid point of `grids[v]`;
+    /// `scratch` is reused for the term samples, so nothing is allocated once it has grown.
+    /// Consequents whose variable fails `keep` are skipped.
+    pub(crate) fn implicate_into<T: AsRef<[Float]>>(
+        &self,
+        alpha: Float,
+        vars: &[Variable],
+        grids: &[T],
+        agg: &mut [Vec<Float>],
+        scratch: &mut Vec<Float>,
+        keep: impl Fn(VarId) -> bool,
+    ) {
+        self.implicate_into_with(
+            alpha,
+            vars,
+            grids,
+            agg,
+            scratch,
+            keep,
+            |alpha, mu| Implication::Min.apply(alpha, mu),
+            |a, b| Aggregation::Max.apply(a, b),
+        )
+    }
+
+    /// Like `implicate_into`, shaping the terms with `implication(alpha, mu)`
+    /// and merging them into `agg` with `aggregation(current, implicated)`.
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn implicate_into_with<T: AsRef<[Float]>>(
+        &self,
+        alpha: Float,
+        vars: &[Variable],
+        grids: &[T],
+        agg: &mut [Vec<Float>],
+        scratch: &mut Vec<Float>,
+        keep: impl Fn(VarId) -> bool,
+        implication: impl Fn(Float, Float) -> Float,
+        aggregation: impl Fn(Float, Float) -> Float,
+    ) {
+        for (&(v, t), hedge) in self.consequent.iter().zip(&self.hedges) {
+            if !keep(v) {
+                continue;
+            }
+            let xs = grids[v.index()].as_ref();
+            scratch.clear();
+            scratch.resize(xs.len(), 0.0);
+            if let Some(term) = vars[v.index()].term(t) {
+                term.eval_grid(xs, scratch);
+            }
+            if let Some(h) = hedge {
+                h.apply_all(scratch);
+            }
+            for (cur, &y) in agg[v.index()].iter_mut().zip(scratch.iter()) {
+                *cur = aggregation(*cur, implication(alpha, y));
+            }
         }
-        return Ok(result_map);
-        //TODO: Return type should be hashmap<string, Vec<Float>> where string signifies the variable(eg "fanspeed")
     }
 }
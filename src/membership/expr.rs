@@ -0,0 +1,326 @@
+// Membership functions written as text.
+//
+// Lets variables be defined entirely from configuration strings: the built-in
+// shapes nest inside pointwise combinators and hedges, and an expression prints
+// back in the syntax it was parsed from.
+use std::{fmt, str::FromStr};
+
+use super::{trapezoidal::Trapezoidal, Float, Gaussian, MembershipFn, MembershipKind};
+use super::{Rectangular, Triangular};
+use crate::hedges::Hedge;
+
+/// A membership function built from nested shapes, combinators and hedges.
+///
+/// Parsed from text such as `max(triangle(0, 5, 10), gaussian(7, 1))` or
+/// `very(gaussian(5, 2))`:
+///
+/// - `triangle(a, b, c)`, `trapezoid(a, b, c, d)`, `gaussian(mean, sd)` and
+///   `rectangle(left, right)` (or `triangular`, `trapezoidal`, `rectangular`);
+/// - `max(e, ...)`, `min(e, ...)` and `not(e)`;
+/// - `very(e)`, `somewhat(e)`, `indeed(e)` and `power(p, e)`.
+#[derive(Debug, PartialEq)]
+pub enum MfExpr {
+    Shape(MembershipKind),
+    /// Pointwise maximum (union) of the operands.
+    Max(Vec<MfExpr>),
+    /// Pointwise minimum (intersection) of the operands.
+    Min(Vec<MfExpr>),
+    /// Complement `1 - mu`.
+    Not(Box<MfExpr>),
+    Hedged(Hedge, Box<MfExpr>),
+}
+
+impl MfExpr {
+    /// Unwraps a bare shape into its built-in variant and boxes anything else
+    /// as `MembershipKind::Custom`.
+    pub fn into_kind(self) -> MembershipKind {
+        match self {
+            Self::Shape(kind) => kind,
+            expr => MembershipKind::Custom(Box::new(expr)),
+        }
+    }
+}
+
+/// Parses a membership expression into a term shape, e.g. for
+/// `Term::with_kind(name, parse_mf("very(gaussian(5, 2))")?)`.
+pub fn parse_mf(s: &str) -> Result<MembershipKind, MfParseError> {
+    s.parse::<MfExpr>().map(MfExpr::into_kind)
+}
+
+impl MembershipFn for MfExpr {
+    fn eval(&self, x: Float) -> Float {
+        match self {
+            Self::Shape(kind) => kind.eval(x),
+            Self::Max(es) => es.iter().fold(0.0, |acc, e| acc.max(e.eval(x))),
+            Self::Min(es) => es.iter().fold(1.0, |acc, e| acc.min(e.eval(x))),
+            Self::Not(e) => 1.0 - e.eval(x),
+            Self::Hedged(h, e) => h.apply(e.eval(x)),
+        }
+    }
+}
+
+/// Writes the expression back in the syntax `parse_mf` reads; custom shapes
+/// print as `custom(..)` and do not parse.
+impl fmt::Display for MfExpr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let list = |f: &mut fmt::Formatter<'_>, name: &str, es: &[MfExpr]| {
+            write!(f, "{name}(")?;
+            for (i, e) in es.iter().enumerate() {
+                if i > 0 {
+                    f.write_str(", ")?;
+                }
+                write!(f, "{e}")?;
+            }
+            f.write_str(")")
+        };
+        match self {
+            Self::Shape(kind) => {
+                let name = match kind {
+                    MembershipKind::Triangular(_) => "triangle",
+                    MembershipKind::Trapezoidal(_) => "trapezoid",
+                    MembershipKind::Gaussian(_) => "gaussian",
+                    MembershipKind::Rectangular(_) => "rectangle",
+                    MembershipKind::Custom(_) => return f.write_str("custom(..)"),
+                };
+                let params = kind.params();
+                write!(f, "{name}(")?;
+                for (i, p) in params.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(", ")?;
+                    }
+                    write!(f, "{p}")?;
+                }
+                f.write_str(")")
+            }
+            Self::Max(es) => list(f, "max", es),
+            Self::Min(es) => list(f, "min", es),
+            Self::Not(e) => write!(f, "not({e})"),
+            Self::Hedged(Hedge::Power(p), e) => write!(f, "power({p}, {e})"),
+            Self::Hedged(h, e) => write!(f, "{h}({e})"),
+        }
+    }
+}
+
+impl FromStr for MfExpr {
+    type Err = MfParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut p = Parser { src: s, pos: 0 };
+        let expr = p.expr()?;
+        p.skip_ws();
+        if p.pos < s.len() {
+            return Err(p.error("unexpected trailing input"));
+        }
+        Ok(expr)
+    }
+}
+
+/// Why a membership expression did not parse, with the byte offset it refers to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MfParseError {
+    pub pos: usize,
+    pub message: String,
+}
+
+impl fmt::Display for MfParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at {}", self.message, self.pos)
+    }
+}
+
+impl std::error::Error for MfParseError {}
+
+struct Parser<'a> {
+    src: &'a str,
+    pos: usize,
+}
+
+impl Parser<'_> {
+    fn error(&self, message: impl Into<String>) -> MfParseError {
+        MfParseError {
+            pos: self.pos,
+            message: message.into(),
+        }
+    }
+
+    fn rest(&self) -> &str {
+        &self.src[self.pos..]
+    }
+
+    fn skip_ws(&mut self) {
+        let rest = self.rest();
+        self.pos += rest.len() - rest.trim_start().len();
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        self.skip_ws();
+        if self.rest().starts_with(c) {
+            self.pos += c.len_utf8();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, c: char) -> Result<(), MfParseError> {
+        if self.eat(c) {
+            Ok(())
+        } else {
+            Err(self.error(format!("expected {c:?}")))
+        }
+    }
+
+    fn take_while(&mut self, f: impl Fn(char) -> bool) -> &str {
+        self.skip_ws();
+        let start = self.pos;
+        let len = self.rest().find(|c| !f(c)).unwrap_or(self.rest().len());
+        self.pos += len;
+        &self.src[start..self.pos]
+    }
+
+    fn number(&mut self) -> Result<Float, MfParseError> {
+        self.skip_ws();
+        let start = self.pos;
+        let text =
+            self.take_while(|c| c.is_ascii_digit() || matches!(c, '+' | '-' | '.' | 'e' | 'E'));
+        text.parse().map_err(|_| MfParseError {
+            pos: start,
+            message: "expected a number".to_string(),
+        })
+    }
+
+    /// Comma-separated operands up to the closing parenthesis.
+    fn list<T>(
+        &mut self,
+        mut item: impl FnMut(&mut Self) -> Result<T, MfParseError>,
+    ) -> Result<Vec<T>, MfParseError> {
+        let mut items = vec![item(self)?];
+        while self.eat(',') {
+            items.push(item(self)?);
+        }
+        self.expect(')')?;
+        Ok(items)
+    }
+
+    fn expr(&mut self) -> Result<MfExpr, MfParseError> {
+        self.skip_ws();
+        let start = self.pos;
+        let name = self
+            .take_while(|c| c.is_ascii_alphabetic() || c == '_')
+            .to_ascii_lowercase();
+        if name.is_empty() {
+            return Err(self.error("expected a membership function"));
+        }
+        self.expect('(')?;
+        let at_start = |message: String| MfParseError {
+            pos: start,
+            message,
+        };
+        let shape = |kind: crate::error::Result<MembershipKind>| {
+            kind.map(MfExpr::Shape)
+                .map_err(|e| at_start(format!("{name}: {e}")))
+        };
+
+        match name.as_str() {
+            "max" | "min" => {
+                let es = self.list(Self::expr)?;
+                Ok(if name == "max" {
+                    MfExpr::Max(es)
+                } else {
+                    MfExpr::Min(es)
+                })
+            }
+            "not" => {
+                let e = Box::new(self.expr()?);
+                self.expect(')')?;
+                Ok(MfExpr::Not(e))
+            }
+            _ if Hedge::from_name(&name).is_some() => {
+                let hedge = Hedge::from_name(&name).expect("checked above");
+                let e = Box::new(self.expr()?);
+                self.expect(')')?;
+                Ok(MfExpr::Hedged(hedge, e))
+            }
+            "power" => {
+                let p = self.number()?;
+                self.expect(',')?;
+                let e = Box::new(self.expr()?);
+                self.expect(')')?;
+                let hedge = Hedge::power(p).map_err(|e| at_start(format!("power: {e}")))?;
+                Ok(MfExpr::Hedged(hedge, e))
+            }
+            _ => {
+                let ps = self.list(Self::number)?;
+                match (name.as_str(), ps.as_slice()) {
+                    ("triangle" | "triangular", &[a, b, c]) => {
+                        shape(Triangular::new(a, b, c).map(Into::into))
+                    }
+                    ("trapezoid" | "trapezoidal", &[a, b, c, d]) => {
+                        shape(Trapezoidal::new(a, b, c, d).map(Into::into))
+                    }
+                    ("gaussian", &[mean, sd]) => {
+                        shape(Gaussian::with_mean_sd(mean, sd).map(Into::into))
+                    }
+                    ("rectangle" | "rectangular", &[l, r]) => {
+                        shape(Rectangular::new(l, r).map(Into::into))
+                    }
+                    (
+                        "triangle" | "triangular" | "trapezoid" | "trapezoidal" | "gaussian"
+                        | "rectangle" | "rectangular",
+                        _,
+                    ) => Err(at_start(format!("{name}: wrong number of parameters"))),
+                    _ => Err(at_start(format!("unknown membership function {name:?}"))),
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn nested_expressions_parse_and_evaluate() {
+        let tri = Triangular::new(0.0, 5.0, 10.0).unwrap();
+        let gauss = Gaussian::with_mean_sd(7.0, 1.0).unwrap();
+        let union: MfExpr = "max(triangle(0, 5, 10), gaussian(7, 1))".parse().unwrap();
+        for x in [0.0, 3.0, 6.5, 7.0, 9.0] {
+            assert_eq!(union.eval(x), tri.eval(x).max(gauss.eval(x)));
+        }
+
+        let very = parse_mf(" very( Gaussian(5, 2) ) ").unwrap();
+        let base = Gaussian::with_mean_sd(5.0, 2.0).unwrap();
+        assert!(very.is_custom());
+        assert!((very.eval(6.0) - base.eval(6.0).powi(2)).abs() < 1e-6);
+        assert_eq!(
+            parse_mf("triangular(0, 5, 10)").unwrap(),
+            MembershipKind::Triangular(tri)
+        );
+
+        let text = "min(not(rectangle(2, 4)), power(1.5, trapezoid(0, 1, 8, 9)))";
+        let expr: MfExpr = text.parse().unwrap();
+        assert_eq!(expr.to_string(), text);
+        assert_eq!(expr.to_string().parse::<MfExpr>().unwrap(), expr);
+        assert_eq!(expr.eval(3.0), 0.0);
+        let indeed: MfExpr = "indeed(gaussian(5, 2))".parse().unwrap();
+        assert_eq!(indeed.to_string(), "indeed(gaussian(5, 2))");
+    }
+
+    #[test]
+    fn errors_point_at_the_problem() {
+        let err = "max(triangle(0, 5), gaussian(7, 1))"
+            .parse::<MfExpr>()
+            .unwrap_err();
+        assert_eq!(err.pos, 4);
+        assert!(err.message.contains("wrong number"));
+        assert_eq!("bell(1, 2)".parse::<MfExpr>().unwrap_err().pos, 0);
+        assert_eq!(
+            "very(gaussian(5, 2)) x".parse::<MfExpr>().unwrap_err().pos,
+            21
+        );
+        assert!("triangle(5, 0, 10)".parse::<MfExpr>().is_err());
+        assert!("power(0, gaussian(5, 2))".parse::<MfExpr>().is_err());
+        assert!("max(gaussian(5, 2),)".parse::<MfExpr>().is_err());
+    }
+}
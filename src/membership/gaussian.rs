@@ -1,9 +1,13 @@
-use super::{Float, FuzzyError, MembershipFn};
+use super::{validate_order, Float, FuzzyError, MembershipFn};
 
 ///validation function to check if sd is greater than 0.0 for Gaussian function.
 fn validate_positive(val: Float) -> Result<(), FuzzyError> {
-    if val <= 0.0 {
-        return Err(FuzzyError::OutOfBounds);
+    if val.is_nan() || val <= 0.0 {
+        return Err(FuzzyError::InvalidParameter {
+            mf: "Gaussian",
+            field: "sd",
+            value: val,
+        });
     }
     Ok(())
 }
@@ -16,25 +20,118 @@ pub struct Gaussian {
     sd: Float,
     mean: Float,
     neg_two_sigma_sq: Float,
+    ///Interval outside which the membership is 0; `None` keeps the infinite tails.
+    support: Option<(Float, Float)>,
 }
 
 impl MembershipFn for Gaussian {
     ///Evaluates the membership value for the input x against the membership struct.
     fn eval(&self, x: Float) -> Float {
-        return ((x - self.mean).powi(2) / self.neg_two_sigma_sq).exp();
+        if let Some((lo, hi)) = self.support {
+            if !(lo..=hi).contains(&x) {
+                return 0.0;
+            }
+        }
+        ((x - self.mean).powi(2) / self.neg_two_sigma_sq).exp()
+    }
+
+    #[cfg(feature = "simd")]
+    ///Lane-wise evaluation of the same closed form over whole chunks of `xs`.
+    fn eval_grid(&self, xs: &[Float], out: &mut [Float]) {
+        use super::simd::{map, Lanes};
+        use wide::{CmpGe, CmpLe};
+        let (mean, denom) = (Lanes::splat(self.mean), Lanes::splat(self.neg_two_sigma_sq));
+        let (lo, hi) = self
+            .support
+            .unwrap_or((Float::NEG_INFINITY, Float::INFINITY));
+        let (lo, hi) = (Lanes::splat(lo), Lanes::splat(hi));
+        map(
+            xs,
+            out,
+            |x| {
+                let d = x - mean;
+                let inside = x.cmp_ge(lo) & x.cmp_le(hi);
+                inside.blend((d * d / denom).exp(), Lanes::splat(0.0))
+            },
+            |x| self.eval(x),
+        );
+    }
+
+    ///Parameters `[mean, sd]`.
+    fn params(&self) -> Vec<Float> {
+        vec![self.mean, self.sd]
+    }
+
+    fn set_params(&mut self, params: &[Float]) -> Result<(), FuzzyError> {
+        match *params {
+            [mean, sd] => {
+                *self = Gaussian {
+                    support: self.support,
+                    ..Gaussian::with_mean_sd(mean, sd)?
+                };
+                Ok(())
+            }
+            _ => Err(FuzzyError::BadArity),
+        }
+    }
+
+    fn constrain_params(&self, params: &mut [Float], min_gap: Float) {
+        if let Some(sd) = params.get_mut(1) {
+            *sd = sd.max(min_gap);
+        }
     }
 }
 
 impl Gaussian {
     ///Initializes the struct. Note that it requires sd > 0.0
+    #[deprecated(note = "takes sd before mean; use `Gaussian::with_mean_sd(mean, sd)`")]
     pub fn new(sd: Float, mean: Float) -> crate::error::Result<Self> {
+        Self::with_mean_sd(mean, sd)
+    }
+
+    ///Initializes the curve centred on `mean` with standard deviation `sd`. Note that it requires sd > 0.0
+    pub fn with_mean_sd(mean: Float, sd: Float) -> crate::error::Result<Self> {
         validate_positive(sd)?;
         //precalculate and save constant "neg_two_sigma_sq" for performance at eval().
-        return Ok(Gaussian {
-            sd: sd,
-            mean: mean,
+        Ok(Gaussian {
+            sd,
+            mean,
             neg_two_sigma_sq: (-2.0 * sd.powi(2)),
-        });
+            support: None,
+        })
+    }
+
+    ///Returns a copy truncated to `[lo, hi]`: the membership is 0 outside it, so the tails
+    ///no longer reach across the whole domain. Note that it requires lo < hi.
+    pub fn with_support(self, lo: Float, hi: Float) -> crate::error::Result<Self> {
+        validate_order("Gaussian", &["support_lo", "support_hi"], &[lo, hi])?;
+        Ok(Gaussian {
+            support: Some((lo, hi)),
+            ..self
+        })
+    }
+
+    ///Returns the interval the curve is truncated to, if any.
+    pub fn support(&self) -> Option<(Float, Float)> {
+        self.support
+    }
+
+    ///Returns the center of the curve.
+    pub fn mean(&self) -> Float {
+        self.mean
+    }
+
+    ///Returns the standard deviation (sigma).
+    pub fn sd(&self) -> Float {
+        self.sd
+    }
+
+    ///Partial derivatives of the membership at x with respect to (mean, sd); 0 outside the support.
+    pub fn gradient(&self, x: Float) -> (Float, Float) {
+        let mu = self.eval(x);
+        let d = x - self.mean;
+        let sd_sq = self.sd * self.sd;
+        (mu * d / sd_sq, mu * d * d / (sd_sq * self.sd))
     }
 }
 
@@ -45,8 +142,15 @@ mod tests {
     #[test]
     fn test_gaussian() {
         let eps = crate::Float::EPSILON;
-        assert_eq!(Gaussian::new(0.0, 0.0), Err(FuzzyError::OutOfBounds));
-        let membership = Gaussian::new(1.0, 0.0);
+        assert_eq!(
+            Gaussian::with_mean_sd(0.0, 0.0),
+            Err(FuzzyError::InvalidParameter {
+                mf: "Gaussian",
+                field: "sd",
+                value: 0.0
+            })
+        );
+        let membership = Gaussian::with_mean_sd(0.0, 1.0);
 
         assert!(membership.clone().unwrap().eval(100.0).abs() < eps);
         assert!((membership.clone().unwrap().eval(0.0) - 1.0).abs() < eps);
@@ -55,4 +159,59 @@ mod tests {
             membership.clone().unwrap().eval(1.0)
         );
     }
+
+    #[test]
+    fn gradient_matches_finite_differences() {
+        let g = Gaussian::with_mean_sd(0.5, 1.5).unwrap();
+        let h = 1e-3;
+        for x in [-2.0, 0.0, 0.5, 1.7] {
+            let (d_mean, d_sd) = g.gradient(x);
+            let num_mean = (Gaussian::with_mean_sd(0.5 + h, 1.5).unwrap().eval(x)
+                - Gaussian::with_mean_sd(0.5 - h, 1.5).unwrap().eval(x))
+                / (2.0 * h);
+            let num_sd = (Gaussian::with_mean_sd(0.5, 1.5 + h).unwrap().eval(x)
+                - Gaussian::with_mean_sd(0.5, 1.5 - h).unwrap().eval(x))
+                / (2.0 * h);
+            assert!((d_mean - num_mean).abs() < 1e-3);
+            assert!((d_sd - num_sd).abs() < 1e-3);
+        }
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn deprecated_new_takes_sd_first() {
+        assert_eq!(Gaussian::new(1.5, 0.5), Gaussian::with_mean_sd(0.5, 1.5));
+        assert!(Gaussian::new(0.0, 1.0).is_err());
+    }
+
+    #[test]
+    fn support_truncates_the_tails() {
+        let g = Gaussian::with_mean_sd(0.0, 2.0).unwrap();
+        assert!(g.eval(9.0) > 0.0);
+        let cut = g.with_support(-4.0, 4.0).unwrap();
+        assert_eq!(cut.support(), Some((-4.0, 4.0)));
+        assert_eq!(cut.eval(4.5), 0.0);
+        assert_eq!(cut.eval(-9.0), 0.0);
+        assert_eq!(cut.eval(4.0), g.eval(4.0));
+        assert_eq!(cut.gradient(5.0), (0.0, 0.0));
+
+        let xs: Vec<Float> = (0..11).map(|i| i as Float - 5.0).collect();
+        let mut out = vec![0.0; xs.len()];
+        cut.eval_grid(&xs, &mut out);
+        for (&x, &mu) in xs.iter().zip(&out) {
+            assert!((mu - cut.eval(x)).abs() < 1e-6, "{x}");
+        }
+
+        let mut moved = cut;
+        moved.set_params(&[1.0, 1.0]).unwrap();
+        assert_eq!(moved.support(), Some((-4.0, 4.0)));
+        assert_eq!(
+            g.with_support(1.0, 1.0),
+            Err(FuzzyError::InvalidParameter {
+                mf: "Gaussian",
+                field: "support_hi",
+                value: 1.0
+            })
+        );
+    }
 }
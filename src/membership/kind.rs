@@ -0,0 +1,295 @@
+use std::any::Any;
+use std::fmt;
+
+use super::{Float, Gaussian, MembershipFn, Rectangular, Result, Triangular};
+use crate::membership::trapezoidal::Trapezoidal;
+use crate::term::BoxedMembershipFn;
+
+///Closed set of membership shapes, with a boxed escape hatch for anything else.
+///
+///The built-in variants are plain `Copy` values matched without dynamic dispatch;
+///`Custom` keeps any other `MembershipFn` behind a box. Two `Custom` values never
+///compare equal, and serializing one (with the `serde` feature) fails.
+pub enum MembershipKind {
+    Triangular(Triangular),
+    Trapezoidal(Trapezoidal),
+    Gaussian(Gaussian),
+    Rectangular(Rectangular),
+    Custom(BoxedMembershipFn),
+}
+
+impl MembershipKind {
+    ///Wraps `m`, using the matching built-in variant when `m` is one of the built-in shapes.
+    pub fn new<M>(m: M) -> Self
+    where
+        M: MembershipFn + Send + Sync + 'static,
+    {
+        let mut slot = Some(m);
+        let any = &mut slot as &mut dyn Any;
+        if let Some(t) = any.downcast_mut::<Option<Triangular>>() {
+            return Self::Triangular(t.take().expect("filled above"));
+        }
+        if let Some(t) = any.downcast_mut::<Option<Trapezoidal>>() {
+            return Self::Trapezoidal(t.take().expect("filled above"));
+        }
+        if let Some(g) = any.downcast_mut::<Option<Gaussian>>() {
+            return Self::Gaussian(g.take().expect("filled above"));
+        }
+        if let Some(r) = any.downcast_mut::<Option<Rectangular>>() {
+            return Self::Rectangular(r.take().expect("filled above"));
+        }
+        Self::Custom(Box::new(slot.expect("filled above")))
+    }
+
+    ///Returns true for the `Custom` variant.
+    pub fn is_custom(&self) -> bool {
+        matches!(self, Self::Custom(_))
+    }
+
+    /// Copy of a built-in shape; `None` for `Custom`, whose box cannot be cloned.
+    pub(crate) fn copied(&self) -> Option<Self> {
+        match *self {
+            Self::Triangular(m) => Some(Self::Triangular(m)),
+            Self::Trapezoidal(m) => Some(Self::Trapezoidal(m)),
+            Self::Gaussian(m) => Some(Self::Gaussian(m)),
+            Self::Rectangular(m) => Some(Self::Rectangular(m)),
+            Self::Custom(_) => None,
+        }
+    }
+}
+
+impl From<Triangular> for MembershipKind {
+    fn from(m: Triangular) -> Self {
+        Self::Triangular(m)
+    }
+}
+
+impl From<Trapezoidal> for MembershipKind {
+    fn from(m: Trapezoidal) -> Self {
+        Self::Trapezoidal(m)
+    }
+}
+
+impl From<Gaussian> for MembershipKind {
+    fn from(m: Gaussian) -> Self {
+        Self::Gaussian(m)
+    }
+}
+
+impl From<Rectangular> for MembershipKind {
+    fn from(m: Rectangular) -> Self {
+        Self::Rectangular(m)
+    }
+}
+
+impl From<BoxedMembershipFn> for MembershipKind {
+    fn from(m: BoxedMembershipFn) -> Self {
+        Self::Custom(m)
+    }
+}
+
+impl fmt::Debug for MembershipKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Triangular(m) => f.debug_tuple("Triangular").field(m).finish(),
+            Self::Trapezoidal(m) => f.debug_tuple("Trapezoidal").field(m).finish(),
+            Self::Gaussian(m) => f.debug_tuple("Gaussian").field(m).finish(),
+            Self::Rectangular(m) => f.debug_tuple("Rectangular").field(m).finish(),
+            Self::Custom(m) => f.debug_tuple("Custom").field(&m.params()).finish(),
+        }
+    }
+}
+
+impl PartialEq for MembershipKind {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::Triangular(a), Self::Triangular(b)) => a == b,
+            (Self::Trapezoidal(a), Self::Trapezoidal(b)) => a == b,
+            (Self::Gaussian(a), Self::Gaussian(b)) => a == b,
+            (Self::Rectangular(a), Self::Rectangular(b)) => a == b,
+            _ => false,
+        }
+    }
+}
+
+macro_rules! dispatch {
+    ($self:expr, $m:ident => $e:expr) => {
+        match $self {
+            MembershipKind::Triangular($m) => $e,
+            MembershipKind::Trapezoidal($m) => $e,
+            MembershipKind::Gaussian($m) => $e,
+            MembershipKind::Rectangular($m) => $e,
+            MembershipKind::Custom($m) => $e,
+        }
+    };
+}
+
+impl MembershipFn for MembershipKind {
+    fn eval(&self, x: Float) -> Float {
+        dispatch!(self, m => m.eval(x))
+    }
+
+    fn eval_grid(&self, xs: &[Float], out: &mut [Float]) {
+        dispatch!(self, m => m.eval_grid(xs, out))
+    }
+
+    fn params(&self) -> Vec<Float> {
+        dispatch!(self, m => m.params())
+    }
+
+    fn set_params(&mut self, params: &[Float]) -> Result<()> {
+        dispatch!(self, m => m.set_params(params))
+    }
+
+    fn constrain_params(&self, params: &mut [Float], min_gap: Float) {
+        dispatch!(self, m => m.constrain_params(params, min_gap))
+    }
+}
+
+///Serialized form of the built-in variants; deserializing re-runs the constructors.
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(tag = "shape", rename_all = "lowercase")]
+enum Repr {
+    Triangular {
+        left: Float,
+        center: Float,
+        right: Float,
+    },
+    Trapezoidal {
+        left_leg: Float,
+        left_base: Float,
+        right_base: Float,
+        right_leg: Float,
+    },
+    Gaussian {
+        mean: Float,
+        sd: Float,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        support: Option<(Float, Float)>,
+    },
+    Rectangular {
+        left: Float,
+        right: Float,
+    },
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for MembershipKind {
+    fn serialize<S: serde::Serializer>(&self, s: S) -> std::result::Result<S::Ok, S::Error> {
+        let repr = match (self, self.params().as_slice()) {
+            (Self::Triangular(_), &[left, center, right]) => Repr::Triangular {
+                left,
+                center,
+                right,
+            },
+            (Self::Trapezoidal(_), &[left_leg, left_base, right_base, right_leg]) => {
+                Repr::Trapezoidal {
+                    left_leg,
+                    left_base,
+                    right_base,
+                    right_leg,
+                }
+            }
+            (Self::Gaussian(g), &[mean, sd]) => Repr::Gaussian {
+                mean,
+                sd,
+                support: g.support(),
+            },
+            (Self::Rectangular(_), &[left, right]) => Repr::Rectangular { left, right },
+            _ => {
+                return Err(serde::ser::Error::custom(
+                    "custom membership functions cannot be serialized",
+                ))
+            }
+        };
+        repr.serialize(s)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for MembershipKind {
+    fn deserialize<D: serde::Deserializer<'de>>(d: D) -> std::result::Result<Self, D::Error> {
+        let kind: Result<Self> = match Repr::deserialize(d)? {
+            Repr::Triangular {
+                left,
+                center,
+                right,
+            } => Triangular::new(left, center, right).map(Self::from),
+            Repr::Trapezoidal {
+                left_leg,
+                left_base,
+                right_base,
+                right_leg,
+            } => Trapezoidal::new(left_leg, left_base, right_base, right_leg).map(Self::from),
+            Repr::Gaussian { mean, sd, support } => Gaussian::with_mean_sd(mean, sd)
+                .and_then(|g| match support {
+                    Some((lo, hi)) => g.with_support(lo, hi),
+                    None => Ok(g),
+                })
+                .map(Self::from),
+            Repr::Rectangular { left, right } => Rectangular::new(left, right).map(Self::from),
+        };
+        kind.map_err(|e: crate::error::FuzzyError| serde::de::Error::custom(format!("{e:?}")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::MembershipKind;
+    use crate::membership::trapezoidal::Trapezoidal;
+    use crate::membership::{Gaussian, MembershipFn, Triangular};
+    use crate::Float;
+
+    struct Step;
+
+    impl MembershipFn for Step {
+        fn eval(&self, x: Float) -> Float {
+            if x >= 0.0 {
+                1.0
+            } else {
+                0.0
+            }
+        }
+    }
+
+    #[test]
+    fn new_picks_builtin_variants() {
+        let tri = Triangular::new(-1.0, 0.0, 1.0).unwrap();
+        let kind = MembershipKind::new(tri);
+        assert_eq!(kind, MembershipKind::Triangular(tri));
+        assert_eq!(kind.eval(0.5), tri.eval(0.5));
+        assert!(matches!(
+            MembershipKind::new(Trapezoidal::new(0.0, 1.0, 2.0, 3.0).unwrap()),
+            MembershipKind::Trapezoidal(_)
+        ));
+        assert!(matches!(
+            MembershipKind::new(Gaussian::with_mean_sd(0.0, 1.0).unwrap()),
+            MembershipKind::Gaussian(_)
+        ));
+
+        let step = MembershipKind::new(Step);
+        assert!(step.is_custom());
+        assert_eq!(step.eval(0.5), 1.0);
+        assert_ne!(step, MembershipKind::new(Step));
+
+        let mut kind = kind;
+        kind.set_params(&[0.0, 1.0, 2.0]).unwrap();
+        assert_eq!(kind.params(), vec![0.0, 1.0, 2.0]);
+    }
+
+    #[cfg(all(feature = "serde", feature = "jsonl"))]
+    #[test]
+    fn gaussian_support_round_trips() {
+        let cut = Gaussian::with_mean_sd(0.0, 2.0)
+            .unwrap()
+            .with_support(-4.0, 4.0)
+            .unwrap();
+        let json = serde_json::to_string(&MembershipKind::from(cut)).unwrap();
+        let back: MembershipKind = serde_json::from_str(&json).unwrap();
+        assert_eq!(back, MembershipKind::from(cut));
+        let plain = r#"{"shape":"gaussian","mean":0.0,"sd":2.0}"#;
+        let back: MembershipKind = serde_json::from_str(plain).unwrap();
+        assert!(back.eval(9.0) > 0.0);
+    }
+}
@@ -1,42 +1,181 @@
 use crate::error::*;
 use crate::*;
 
+pub mod expr;
 pub mod gaussian;
+pub mod kind;
+pub mod rectangular;
 pub mod trapezoidal;
 pub mod triangular;
 
+pub use expr::{parse_mf, MfExpr};
 pub use gaussian::Gaussian;
+pub use kind::MembershipKind;
+pub use rectangular::Rectangular;
 pub use triangular::Triangular;
 
 pub trait MembershipFn {
     fn eval(&self, x: crate::Float) -> crate::Float;
+
+    ///Evaluates every point of `xs` into the matching slot of `out` (`out.len() >= xs.len()`).
+    ///Built-in shapes override this with lane-wise code when the `simd` feature is enabled.
+    fn eval_grid(&self, xs: &[crate::Float], out: &mut [crate::Float]) {
+        debug_assert!(out.len() >= xs.len());
+        for (o, &x) in out.iter_mut().zip(xs) {
+            *o = self.eval(x);
+        }
+    }
+
+    ///Shape parameters, for tuners. Opaque shapes return an empty list and are left alone.
+    fn params(&self) -> Vec<crate::Float> {
+        Vec::new()
+    }
+
+    ///Replaces the parameters, validating them like the constructor does.
+    ///Shapes without tunable parameters reject this with `TypeMismatch`.
+    fn set_params(&mut self, _params: &[crate::Float]) -> Result<()> {
+        Err(FuzzyError::TypeMismatch)
+    }
+
+    ///Pulls candidate `params` back into the valid region before `set_params`,
+    ///keeping neighbouring values at least `min_gap` apart where the shape needs it.
+    fn constrain_params(&self, _params: &mut [crate::Float], _min_gap: crate::Float) {}
+}
+
+///Lane-wise helpers backing the `eval_grid` overrides of the built-in shapes.
+#[cfg(feature = "simd")]
+pub(crate) mod simd {
+    use crate::Float;
+
+    #[cfg(feature = "f32")]
+    pub(crate) type Lanes = wide::f32x8;
+    #[cfg(not(feature = "f32"))]
+    pub(crate) type Lanes = wide::f64x4;
+
+    const N: usize = std::mem::size_of::<Lanes>() / std::mem::size_of::<Float>();
+
+    ///Applies `f` to full lanes of `xs` and the scalar `g` to the remaining tail.
+    pub(crate) fn map(
+        xs: &[Float],
+        out: &mut [Float],
+        f: impl Fn(Lanes) -> Lanes,
+        g: impl Fn(Float) -> Float,
+    ) {
+        debug_assert!(out.len() >= xs.len());
+        let out = &mut out[..xs.len()];
+        let mut xc = xs.chunks_exact(N);
+        let mut oc = out.chunks_exact_mut(N);
+        for (x, o) in (&mut xc).zip(&mut oc) {
+            let lanes: [Float; N] = x.try_into().unwrap();
+            o.copy_from_slice(&f(Lanes::new(lanes)).to_array());
+        }
+        for (x, o) in xc.remainder().iter().zip(oc.into_remainder()) {
+            *o = g(*x);
+        }
+    }
+}
+
+///Distance from a peak (or shoulder) within which `Triangular` and `Trapezoidal` return exactly 1.0.
+///It scales with the precision of `Float`, so f32 and f64 builds snap alike; override it per
+///shape with `with_tolerance`.
+pub const DEFAULT_TOLERANCE: Float = 64.0 * Float::EPSILON;
+
+///Checks that a snapping tolerance is finite and not negative.
+fn validate_tolerance(mf: &'static str, tolerance: Float) -> Result<()> {
+    if tolerance.is_finite() && tolerance >= 0.0 {
+        Ok(())
+    } else {
+        Err(FuzzyError::InvalidParameter {
+            mf,
+            field: "tolerance",
+            value: tolerance,
+        })
+    }
 }
 
 ///validation function to check that the order in the tiangular or trapezoidal apexes are correct.
-fn validate_order(vals: &[Float]) -> Result<()> {
+///The first value not above its predecessor is reported by its name in `fields`.
+fn validate_order(mf: &'static str, fields: &[&'static str], vals: &[Float]) -> Result<()> {
     for i in 0..vals.len() - 1 {
-        if vals[i + 1] <= vals[i] {
-            return Err(FuzzyError::BadArity);
+        if vals[i + 1] <= vals[i] || vals[i + 1].is_nan() || vals[i].is_nan() {
+            return Err(FuzzyError::InvalidParameter {
+                mf,
+                field: fields[i + 1],
+                value: vals[i + 1],
+            });
         }
     }
     Ok(())
 }
 
+///Pushes each value at least `gap` above its predecessor so `validate_order` holds.
+fn enforce_order(vals: &mut [Float], gap: Float) {
+    for i in 1..vals.len() {
+        vals[i] = vals[i].max(vals[i - 1] + gap);
+    }
+}
+
 ///Calculate the slope. delta is the change amount.(Either 1.0 or -1.0 by definition.)
 fn slope(value: Float, left: Float, right: Float, delta: Float) -> Float {
-    (delta * (value - left) / (right - left) + ((-1.0 * delta + 1.0) / 2.0)).clamp(0.0, 1.0)
+    (delta * (value - left) / (right - left) + ((-delta + 1.0) / 2.0)).clamp(0.0, 1.0)
 }
 
 //simple unit testing for validation
 #[cfg(test)]
 mod tests {
-    use crate::membership::validate_order;
+    use crate::membership::trapezoidal::Trapezoidal;
+    use crate::membership::{validate_order, Gaussian, MembershipFn, Triangular};
 
     #[test]
     fn test_validation() {
         assert_eq!(
-            validate_order(&[0.0, 1.1, 0.5]),
-            Err(crate::error::FuzzyError::BadArity)
+            validate_order("Triangular", &["left", "center", "right"], &[0.0, 1.1, 0.5]),
+            Err(crate::error::FuzzyError::InvalidParameter {
+                mf: "Triangular",
+                field: "right",
+                value: 0.5
+            })
         );
     }
+
+    #[test]
+    fn eval_grid_matches_scalar_eval() {
+        let xs: Vec<crate::Float> = (0..=37).map(|i| -2.0 + i as crate::Float * 0.125).collect();
+        let shapes: Vec<Box<dyn MembershipFn>> = vec![
+            Box::new(Triangular::new(-1.0, 0.0, 1.5).unwrap()),
+            Box::new(Trapezoidal::new(-1.5, -0.5, 0.5, 2.0).unwrap()),
+            Box::new(Gaussian::with_mean_sd(0.3, 0.7).unwrap()),
+        ];
+        for mf in shapes {
+            let mut out = vec![0.0; xs.len()];
+            mf.eval_grid(&xs, &mut out);
+            for (&x, &y) in xs.iter().zip(&out) {
+                assert!((mf.eval(x) - y).abs() < 1e-6, "x = {x}");
+            }
+        }
+    }
+
+    #[test]
+    fn params_round_trip_and_constrain() {
+        let mut tri = Triangular::new(-1.0, 0.0, 1.0).unwrap();
+        assert_eq!(tri.params(), vec![-1.0, 0.0, 1.0]);
+        tri.set_params(&[0.0, 2.0, 3.0]).unwrap();
+        assert_eq!(tri, Triangular::new(0.0, 2.0, 3.0).unwrap());
+        assert!(matches!(
+            tri.set_params(&[0.0, 3.0, 2.0]),
+            Err(crate::error::FuzzyError::InvalidParameter { field: "right", .. })
+        ));
+
+        let mut p = vec![0.0, -1.0, 0.5, 0.4];
+        Trapezoidal::new(0.0, 1.0, 2.0, 3.0)
+            .unwrap()
+            .constrain_params(&mut p, 0.1);
+        assert!(p.windows(2).all(|w| w[1] >= w[0] + 0.1 - 1e-6));
+
+        let mut g = Gaussian::with_mean_sd(0.0, 1.0).unwrap();
+        let mut p = vec![0.5, -2.0];
+        g.constrain_params(&mut p, 0.01);
+        g.set_params(&p).unwrap();
+        assert_eq!(g.params(), vec![0.5, 0.01]);
+    }
 }
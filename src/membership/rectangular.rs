@@ -0,0 +1,69 @@
+use super::{enforce_order, validate_order, Float, MembershipFn};
+
+///Struct for rectangular (crisp interval) membership function:
+///1 on `[left, right)` and 0 elsewhere.
+///Initialize by calling the new() function.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Rectangular {
+    left: Float,
+    right: Float,
+}
+
+impl MembershipFn for Rectangular {
+    ///Evaluates the membership value for the input x against the membership struct.
+    fn eval(&self, x: Float) -> Float {
+        if x >= self.left && x < self.right {
+            1.0
+        } else {
+            0.0
+        }
+    }
+
+    ///Parameters `[left, right]`.
+    fn params(&self) -> Vec<Float> {
+        vec![self.left, self.right]
+    }
+
+    fn set_params(&mut self, params: &[Float]) -> crate::error::Result<()> {
+        match *params {
+            [l, r] => {
+                *self = Rectangular::new(l, r)?;
+                Ok(())
+            }
+            _ => Err(crate::error::FuzzyError::BadArity),
+        }
+    }
+
+    fn constrain_params(&self, params: &mut [Float], min_gap: Float) {
+        enforce_order(params, min_gap);
+    }
+}
+
+impl Rectangular {
+    ///Initializes the struct. Note that it requires left < right.
+    pub fn new(l: Float, r: Float) -> crate::error::Result<Self> {
+        validate_order("Rectangular", &["left", "right"], &[l, r])?;
+        Ok(Rectangular { left: l, right: r })
+    }
+
+    ///Midpoint of the interval.
+    pub fn center(&self) -> Float {
+        0.5 * (self.left + self.right)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rectangular() {
+        let mf = Rectangular::new(0.5, 1.5).unwrap();
+        assert_eq!(mf.eval(0.5), 1.0);
+        assert_eq!(mf.eval(1.0), 1.0);
+        assert_eq!(mf.eval(1.5), 0.0);
+        assert_eq!(mf.eval(0.0), 0.0);
+        assert_eq!(mf.center(), 1.0);
+        assert!(Rectangular::new(1.0, 1.0).is_err());
+    }
+}
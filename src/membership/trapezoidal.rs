@@ -1,4 +1,7 @@
-use super::{slope, validate_order, Float, MembershipFn};
+use super::{
+    enforce_order, slope, validate_order, validate_tolerance, Float, MembershipFn,
+    DEFAULT_TOLERANCE,
+};
 
 ///Struct for trapezoidal membership function.
 ///Initialize by calling the new() function.
@@ -8,12 +11,13 @@ pub struct Trapezoidal {
     left_base: Float,
     right_base: Float,
     right_leg: Float,
+    tolerance: Float,
 }
 
 impl MembershipFn for Trapezoidal {
     ///Evaluates the membership value for the input x against the membership struct.
     fn eval(&self, x: Float) -> Float {
-        let eps = crate::Float::EPSILON;
+        let eps = self.tolerance;
 
         //out of bounds check
         if x <= self.left_leg {
@@ -34,18 +38,84 @@ impl MembershipFn for Trapezoidal {
             slope(x, self.right_base, self.right_leg, -1.0)
         }
     }
+
+    #[cfg(feature = "simd")]
+    ///Lane-wise evaluation: `clamp(min(rising, falling), 0, 1)` over whole chunks of `xs`.
+    fn eval_grid(&self, xs: &[Float], out: &mut [Float]) {
+        use super::simd::{map, Lanes};
+        use wide::CmpLt;
+        let (ll, lb) = (Lanes::splat(self.left_leg), Lanes::splat(self.left_base));
+        let (rb, rl) = (Lanes::splat(self.right_base), Lanes::splat(self.right_leg));
+        let tolerance = Lanes::splat(self.tolerance);
+        map(
+            xs,
+            out,
+            |x| {
+                let rising = (x - ll) / (lb - ll);
+                let falling = (rl - x) / (rl - rb);
+                let mu = rising
+                    .min(falling)
+                    .min(Lanes::splat(1.0))
+                    .max(Lanes::splat(0.0));
+                let shoulder = (x - lb).abs().cmp_lt(tolerance) | (x - rb).abs().cmp_lt(tolerance);
+                shoulder.blend(Lanes::splat(1.0), mu)
+            },
+            |x| self.eval(x),
+        );
+    }
+
+    ///Parameters `[left_leg, left_base, right_base, right_leg]`.
+    fn params(&self) -> Vec<Float> {
+        vec![
+            self.left_leg,
+            self.left_base,
+            self.right_base,
+            self.right_leg,
+        ]
+    }
+
+    fn set_params(&mut self, params: &[Float]) -> crate::error::Result<()> {
+        match *params {
+            [ll, lb, rb, rl] => {
+                *self = Trapezoidal::new(ll, lb, rb, rl)?.with_tolerance(self.tolerance)?;
+                Ok(())
+            }
+            _ => Err(crate::error::FuzzyError::BadArity),
+        }
+    }
+
+    fn constrain_params(&self, params: &mut [Float], min_gap: Float) {
+        enforce_order(params, min_gap);
+    }
 }
 
 impl Trapezoidal {
     ///Initializes the struct. Note that it requires left_leg < left_base < right_base < right_leg.
     pub fn new(ll: Float, lb: Float, rb: Float, rl: Float) -> crate::error::Result<Self> {
-        validate_order(&[ll, lb, rb, rl])?;
-        return Ok(Trapezoidal {
+        validate_order(
+            "Trapezoidal",
+            &["left_leg", "left_base", "right_base", "right_leg"],
+            &[ll, lb, rb, rl],
+        )?;
+        Ok(Trapezoidal {
             left_leg: ll,
             left_base: lb,
             right_base: rb,
             right_leg: rl,
-        });
+            tolerance: DEFAULT_TOLERANCE,
+        })
+    }
+
+    ///Returns a copy that evaluates to exactly 1.0 within `tolerance` of either base
+    ///(`DEFAULT_TOLERANCE` unless set). Requires a finite tolerance >= 0.0.
+    pub fn with_tolerance(self, tolerance: Float) -> crate::error::Result<Self> {
+        validate_tolerance("Trapezoidal", tolerance)?;
+        Ok(Trapezoidal { tolerance, ..self })
+    }
+
+    ///Returns the distance from the bases that is treated as the plateau.
+    pub fn tolerance(&self) -> Float {
+        self.tolerance
     }
 }
 
@@ -60,7 +130,11 @@ mod tests {
 
         assert_eq!(
             Trapezoidal::new(0.0, 0.0, -1.0, 0.0),
-            Err(crate::error::FuzzyError::BadArity)
+            Err(crate::error::FuzzyError::InvalidParameter {
+                mf: "Trapezoidal",
+                field: "left_base",
+                value: 0.0
+            })
         );
 
         assert!((membership_func.clone().unwrap().eval(0.0) - 1.0).abs() < eps);
@@ -68,4 +142,19 @@ mod tests {
         assert!((membership_func.clone().unwrap().eval(-0.5) - 0.5).abs() < eps);
         assert!((membership_func.unwrap().eval(2.0)).abs() < eps);
     }
+
+    #[test]
+    fn tolerance_snaps_near_the_bases() {
+        let trap = Trapezoidal::new(-1.0, 0.0, 1.0, 2.0).unwrap();
+        assert!(trap.eval(-5e-4) < 1.0);
+        let wide = trap.with_tolerance(1e-3).unwrap();
+        assert_eq!(wide.eval(-5e-4), 1.0);
+        assert_eq!(wide.eval(1.0 + 5e-4), 1.0);
+        assert!(wide.eval(-2e-3) < 1.0);
+
+        let mut moved = wide;
+        moved.set_params(&[0.0, 1.0, 2.0, 3.0]).unwrap();
+        assert_eq!(moved.tolerance(), 1e-3);
+        assert!(trap.with_tolerance(-1.0).is_err());
+    }
 }
@@ -1,4 +1,7 @@
-use super::{slope, validate_order, Float, MembershipFn};
+use super::{
+    enforce_order, slope, validate_order, validate_tolerance, Float, MembershipFn,
+    DEFAULT_TOLERANCE,
+};
 
 ///Struct for triangular membership function.
 ///Initialize by calling the new() function.
@@ -7,6 +10,7 @@ pub struct Triangular {
     left: Float,
     center: Float,
     right: Float,
+    tolerance: Float,
 }
 
 impl MembershipFn for Triangular {
@@ -21,7 +25,7 @@ impl MembershipFn for Triangular {
         }
 
         //calculation within membership function
-        if (x - self.center).abs() < 1e-9 {
+        if (x - self.center).abs() < self.tolerance {
             1.0
         } else if x < self.center {
             slope(x, self.left, self.center, 1.0)
@@ -29,17 +33,73 @@ impl MembershipFn for Triangular {
             slope(x, self.center, self.right, -1.0)
         }
     }
+
+    #[cfg(feature = "simd")]
+    ///Lane-wise evaluation: `max(0, min(rising, falling))` over whole chunks of `xs`.
+    fn eval_grid(&self, xs: &[Float], out: &mut [Float]) {
+        use super::simd::{map, Lanes};
+        use wide::CmpLt;
+        let (l, c, r) = (
+            Lanes::splat(self.left),
+            Lanes::splat(self.center),
+            Lanes::splat(self.right),
+        );
+        let tolerance = Lanes::splat(self.tolerance);
+        map(
+            xs,
+            out,
+            |x| {
+                let rising = (x - l) / (c - l);
+                let falling = (r - x) / (r - c);
+                let mu = rising.min(falling).max(Lanes::splat(0.0));
+                (x - c).abs().cmp_lt(tolerance).blend(Lanes::splat(1.0), mu)
+            },
+            |x| self.eval(x),
+        );
+    }
+
+    ///Parameters `[left, center, right]`.
+    fn params(&self) -> Vec<Float> {
+        vec![self.left, self.center, self.right]
+    }
+
+    fn set_params(&mut self, params: &[Float]) -> crate::error::Result<()> {
+        match *params {
+            [l, c, r] => {
+                *self = Triangular::new(l, c, r)?.with_tolerance(self.tolerance)?;
+                Ok(())
+            }
+            _ => Err(crate::error::FuzzyError::BadArity),
+        }
+    }
+
+    fn constrain_params(&self, params: &mut [Float], min_gap: Float) {
+        enforce_order(params, min_gap);
+    }
 }
 
 impl Triangular {
     ///Initializes the struct. Note that it requires left < center < right.
     pub fn new(l: Float, c: Float, r: Float) -> crate::error::Result<Self> {
-        validate_order(&[l, c, r])?;
-        return Ok(Triangular {
+        validate_order("Triangular", &["left", "center", "right"], &[l, c, r])?;
+        Ok(Triangular {
             left: l,
             center: c,
             right: r,
-        });
+            tolerance: DEFAULT_TOLERANCE,
+        })
+    }
+
+    ///Returns a copy that evaluates to exactly 1.0 within `tolerance` of the center
+    ///(`DEFAULT_TOLERANCE` unless set). Requires a finite tolerance >= 0.0.
+    pub fn with_tolerance(self, tolerance: Float) -> crate::error::Result<Self> {
+        validate_tolerance("Triangular", tolerance)?;
+        Ok(Triangular { tolerance, ..self })
+    }
+
+    ///Returns the distance from the center that is treated as the peak.
+    pub fn tolerance(&self) -> Float {
+        self.tolerance
     }
 }
 
@@ -54,11 +114,44 @@ mod tests {
 
         assert_eq!(
             Triangular::new(0.0, 0.0, -1.0),
-            Err(crate::error::FuzzyError::BadArity)
+            Err(crate::error::FuzzyError::InvalidParameter {
+                mf: "Triangular",
+                field: "center",
+                value: 0.0
+            })
         );
 
         assert!((membership_func.clone().unwrap().eval(0.0) - 1.0).abs() < eps);
         assert!((membership_func.clone().unwrap().eval(0.5) - 0.5).abs() < eps);
         assert!((membership_func.unwrap().eval(1.0)).abs() < eps);
     }
+
+    #[test]
+    fn tolerance_snaps_near_the_center() {
+        let tri = Triangular::new(-1.0, 0.0, 1.0).unwrap();
+        assert!(tri.eval(5e-4) < 1.0);
+        let wide = tri.with_tolerance(1e-3).unwrap();
+        assert_eq!(wide.eval(5e-4), 1.0);
+        assert_eq!(wide.eval(-5e-4), 1.0);
+        assert!(wide.eval(2e-3) < 1.0);
+        let mut out = [0.0; 9];
+        let xs = [-5e-4, 5e-4, 0.5, 0.0, 5e-4, -5e-4, 5e-4, 2e-3, 5e-4];
+        wide.eval_grid(&xs, &mut out);
+        for (&x, &mu) in xs.iter().zip(&out) {
+            assert_eq!(mu, wide.eval(x), "{x}");
+        }
+
+        let mut moved = wide;
+        moved.set_params(&[0.0, 1.0, 2.0]).unwrap();
+        assert_eq!(moved.tolerance(), 1e-3);
+        assert_eq!(
+            tri.with_tolerance(-1.0),
+            Err(crate::error::FuzzyError::InvalidParameter {
+                mf: "Triangular",
+                field: "tolerance",
+                value: -1.0
+            })
+        );
+        assert!(tri.with_tolerance(Float::NAN).is_err());
+    }
 }
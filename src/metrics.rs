@@ -0,0 +1,233 @@
+// Goodness-of-fit metrics against labelled data.
+//
+// `RuleSpace::score` evaluates every row of a dataset in the `LabeledRow` format
+// used by `RuleSpace::tune` and reports RMSE, MAE and R² per output variable,
+// together with the per-sample residuals. Predictions that are undefined (no
+// rule fired) are counted separately and left out of the statistics.
+use std::{borrow::Borrow, collections::HashMap, hash::Hash};
+
+use crate::{
+    error::{self, FuzzyError, MissingSpace},
+    rulespace::RuleSpace,
+    sampler::UniformSampler,
+    tune::LabeledRow,
+    Float,
+};
+
+/// Summary statistics for one output variable.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Metrics {
+    pub rmse: Float,
+    pub mae: Float,
+    /// Coefficient of determination; NaN when the targets have no variance.
+    pub r2: Float,
+    /// Samples the statistics were computed from.
+    pub count: usize,
+    /// Samples skipped because the prediction was undefined.
+    pub undefined: usize,
+}
+
+impl Metrics {
+    /// Statistics of `predicted` against `targets`, pairing them by position.
+    ///
+    /// Pairs with a non-finite prediction count as `undefined`. Fails with
+    /// `BadArity` on a length mismatch and `EmptyInput` if no pair is usable.
+    pub fn compute(predicted: &[Float], targets: &[Float]) -> error::Result<Self> {
+        if predicted.len() != targets.len() {
+            return Err(FuzzyError::BadArity);
+        }
+        let pairs: Vec<(Float, Float)> = predicted
+            .iter()
+            .zip(targets)
+            .filter(|(p, _)| p.is_finite())
+            .map(|(&p, &t)| (p, t))
+            .collect();
+        if pairs.is_empty() {
+            return Err(FuzzyError::EmptyInput);
+        }
+        let n = pairs.len() as Float;
+        let mean = pairs.iter().map(|(_, t)| t).sum::<Float>() / n;
+        let (mut sse, mut sae, mut sst) = (0.0, 0.0, 0.0);
+        for &(p, t) in &pairs {
+            sse += (t - p) * (t - p);
+            sae += (t - p).abs();
+            sst += (t - mean) * (t - mean);
+        }
+        Ok(Self {
+            rmse: (sse / n).sqrt(),
+            mae: sae / n,
+            r2: if sst > 0.0 {
+                1.0 - sse / sst
+            } else {
+                Float::NAN
+            },
+            count: pairs.len(),
+            undefined: predicted.len() - pairs.len(),
+        })
+    }
+}
+
+/// One labelled value compared with the system's prediction.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Residual {
+    /// Index of the row in the dataset.
+    pub row: usize,
+    pub var: String,
+    pub target: Float,
+    /// NaN when no rule fired for this output.
+    pub predicted: Float,
+    /// `target - predicted`.
+    pub residual: Float,
+}
+
+/// Result of `RuleSpace::score`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Report {
+    /// Output name and its metrics, in `RuleSpace::outputs` order, for every
+    /// output that has at least one target.
+    pub outputs: Vec<(String, Metrics)>,
+    /// Residuals in row order, then in the row's target order.
+    pub residuals: Vec<Residual>,
+}
+
+impl Report {
+    /// Metrics of output `name`, if the dataset labelled it.
+    pub fn output(&self, name: &str) -> Option<&Metrics> {
+        self.outputs.iter().find(|(n, _)| n == name).map(|(_, m)| m)
+    }
+}
+
+impl RuleSpace {
+    /// Scores the system's defuzzified outputs against `data`, given as
+    /// `(inputs, targets)` rows.
+    ///
+    /// Targets may name any subset of the outputs; unknown names fail with
+    /// `NotFound`. An output whose predictions are all undefined fails with `EmptyInput`.
+    pub fn score<KI>(
+        &self,
+        data: &[LabeledRow<KI>],
+        sampler: &UniformSampler,
+    ) -> error::Result<Report>
+    where
+        KI: Eq + Hash + Borrow<str>,
+    {
+        if data.is_empty() {
+            return Err(FuzzyError::EmptyInput);
+        }
+        let mut scratch = self.scratch(sampler)?;
+        let mut residuals = Vec::new();
+        let mut pairs: HashMap<&str, (Vec<Float>, Vec<Float>)> = HashMap::new();
+        for (row, (input, targets)) in data.iter().enumerate() {
+            let out = self.evaluate_in(input, &mut scratch)?;
+            for (name, &target) in targets {
+                let name = name.borrow();
+                let &predicted = out.get(name).ok_or_else(|| FuzzyError::NotFound {
+                    space: MissingSpace::Var,
+                    key: name.to_string(),
+                })?;
+                let entry = pairs.entry(name).or_default();
+                entry.0.push(predicted);
+                entry.1.push(target);
+                residuals.push(Residual {
+                    row,
+                    var: name.to_string(),
+                    target,
+                    predicted,
+                    residual: target - predicted,
+                });
+            }
+        }
+
+        let outputs = self
+            .outputs()
+            .filter_map(|name| pairs.get(name).map(|p| (name, p)))
+            .map(|(name, (p, t))| Ok((name.to_string(), Metrics::compute(p, t)?)))
+            .collect::<error::Result<_>>()?;
+        Ok(Report { outputs, residuals })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::Metrics;
+    use crate::antecedent::Antecedent;
+    use crate::error::FuzzyError;
+    use crate::mamdani::{Consequent, Rule};
+    use crate::prelude::*;
+    use crate::rulespace::RuleSpace;
+    use crate::variable::Variable;
+
+    #[test]
+    fn metrics_match_hand_computation() {
+        let m = Metrics::compute(&[1.0, 2.0, 4.0, Float::NAN], &[1.0, 3.0, 2.0, 9.0]).unwrap();
+        assert_eq!(m.count, 3);
+        assert_eq!(m.undefined, 1);
+        assert!((m.mae - 1.0).abs() < 1e-6);
+        assert!((m.rmse - (5.0 as Float / 3.0).sqrt()).abs() < 1e-6);
+        // Targets 1, 3, 2 have mean 2 and total sum of squares 2.
+        assert!((m.r2 - (1.0 - 5.0 / 2.0)).abs() < 1e-6);
+
+        assert!(Metrics::compute(&[1.0], &[1.0]).unwrap().r2.is_nan());
+        assert_eq!(Metrics::compute(&[1.0], &[]), Err(FuzzyError::BadArity));
+        assert_eq!(
+            Metrics::compute(&[Float::NAN], &[1.0]),
+            Err(FuzzyError::EmptyInput)
+        );
+    }
+
+    #[test]
+    fn score_reports_per_output_metrics_and_residuals() {
+        let mut temp = Variable::new(0.0, 10.0).unwrap();
+        temp.insert_term(
+            "hot",
+            Term::new("hot", Triangular::new(0.0, 10.0, 20.0).unwrap()),
+        )
+        .unwrap();
+        let mut fan = Variable::new(0.0, 10.0).unwrap();
+        fan.insert_term(
+            "high",
+            Term::new("high", Triangular::new(0.0, 10.0, 20.0).unwrap()),
+        )
+        .unwrap();
+        let rule = Rule {
+            antecedent: Antecedent::Atom {
+                var: "temp".into(),
+                term: "hot".into(),
+            },
+            consequent: vec![Consequent::Term {
+                var: "fan".into(),
+                term: "high".into(),
+                hedge: None,
+            }],
+            weight: 1.0,
+        };
+        let vars = HashMap::from([("temp".to_string(), temp), ("fan".to_string(), fan)]);
+        let rs = RuleSpace::new(vars, vec![rule]).unwrap();
+        let sampler = UniformSampler::default();
+
+        let row = |t: Float, y: Float| (HashMap::from([("temp", t)]), HashMap::from([("fan", y)]));
+        let exact = rs
+            .evaluate(&HashMap::from([("temp", 5.0)]), &sampler)
+            .unwrap()["fan"];
+        let data = vec![row(5.0, exact), row(5.0, exact + 2.0), row(0.0, 1.0)];
+        let report = rs.score(&data, &sampler).unwrap();
+
+        let fan = report.output("fan").unwrap();
+        assert_eq!((fan.count, fan.undefined), (2, 1));
+        assert!((fan.mae - 1.0).abs() < 1e-4);
+        assert_eq!(report.residuals.len(), 3);
+        assert!((report.residuals[1].residual - 2.0).abs() < 1e-4);
+        assert!(report.residuals[2].predicted.is_nan());
+
+        let bad = vec![(
+            HashMap::from([("temp", 5.0)]),
+            HashMap::from([("nope", 1.0)]),
+        )];
+        assert!(matches!(
+            rs.score(&bad, &sampler),
+            Err(FuzzyError::NotFound { .. })
+        ));
+    }
+}
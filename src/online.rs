@@ -0,0 +1,294 @@
+// Online adaptation of membership parameters and rule weights.
+//
+// `Online` nudges term parameters, and optionally rule weights, after every
+// `(input, target)` pair of a stream, one finite-difference gradient step on that sample's loss (the loss of
+// `RuleSpace::tune`). Two clamps keep a drifting plant from destabilizing the
+// controller: each update moves a parameter by at most `max_step`, and the total
+// drift from the parameters seen at construction is bounded by `max_drift`.
+use std::{borrow::Borrow, hash::Hash};
+
+use crate::{
+    error,
+    membership::MembershipFn,
+    rulespace::{RuleSpace, Scratch},
+    sampler::UniformSampler,
+    tune::{LabeledRow, Slot},
+    Float,
+};
+
+/// Settings for `Online`. Distances are fractions of the owning variable's
+/// domain width; for rule weights, whose range is [0, 1], they are absolute.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct OnlineOptions {
+    /// Step size in domain-normalized coordinates.
+    pub learning_rate: Float,
+    /// Largest change of any parameter in one update.
+    pub max_step: Float,
+    /// Largest distance any parameter may drift from its initial value.
+    pub max_drift: Float,
+    /// Minimum gap kept between ordered parameters (and the minimum Gaussian width).
+    pub min_gap: Float,
+    /// Adapt rule weights as well as term parameters.
+    pub weights: bool,
+}
+
+impl Default for OnlineOptions {
+    fn default() -> Self {
+        Self {
+            learning_rate: 0.05,
+            max_step: 0.01,
+            max_drift: 0.25,
+            min_gap: 1e-2,
+            weights: false,
+        }
+    }
+}
+
+/// Incremental tuner state for one rule space.
+pub struct Online {
+    opts: OnlineOptions,
+    slots: Vec<Slot>,
+    origin: Vec<Vec<Float>>,
+    origin_weights: Vec<Float>,
+    scratch: Scratch,
+    updates: usize,
+}
+
+impl Online {
+    /// Records the current parameters (and weights) of `space` as the origin
+    /// drift is measured from.
+    pub fn new(
+        space: &RuleSpace,
+        sampler: &UniformSampler,
+        opts: OnlineOptions,
+    ) -> error::Result<Self> {
+        let slots = space.tune_slots();
+        let origin = slots.iter().map(|s| space.slot_params(s)).collect();
+        let origin_weights = match opts.weights {
+            true => space.rules.iter().map(|r| r.weight).collect(),
+            false => Vec::new(),
+        };
+        Ok(Self {
+            opts,
+            slots,
+            origin,
+            origin_weights,
+            scratch: space.scratch(sampler)?,
+            updates: 0,
+        })
+    }
+
+    /// Adapts `space` towards one labelled sample and returns the sample's loss
+    /// before the update.
+    ///
+    /// `space` must be the rule space `self` was created from. Targets follow
+    /// `RuleSpace::tune`: any subset of the outputs, unknown names fail with `NotFound`.
+    pub fn update<KI>(
+        &mut self,
+        space: &mut RuleSpace,
+        row: &LabeledRow<KI>,
+    ) -> error::Result<Float>
+    where
+        KI: Eq + Hash + Borrow<str>,
+    {
+        let data = std::slice::from_ref(row);
+        let loss = space.tune_loss(data, &mut self.scratch)?;
+        let current: Vec<Vec<Float>> = self.slots.iter().map(|s| space.slot_params(s)).collect();
+        let grads = space.tune_gradient(&self.slots, &current, data, loss, &mut self.scratch)?;
+        let weight_grads = match self.opts.weights {
+            true => space.weight_gradient(data, &mut self.scratch)?,
+            false => Vec::new(),
+        };
+
+        let o = self.opts;
+        for (((slot, p), g), origin) in self
+            .slots
+            .iter()
+            .zip(&current)
+            .zip(&grads)
+            .zip(&self.origin)
+        {
+            let w = slot.width;
+            let mut next: Vec<Float> = p
+                .iter()
+                .zip(g)
+                .zip(origin)
+                .map(|((pi, gi), oi)| {
+                    let step =
+                        (o.learning_rate * w * w * gi).clamp(-o.max_step * w, o.max_step * w);
+                    (pi - step).clamp(oi - o.max_drift * w, oi + o.max_drift * w)
+                })
+                .collect();
+            space
+                .slot_term(slot)
+                .constrain_params(&mut next, o.min_gap * w);
+            if space.set_slot(slot, &next).is_err() {
+                space.set_slot(slot, p)?;
+            }
+        }
+        for ((rule, g), origin) in space
+            .rules
+            .iter_mut()
+            .zip(&weight_grads)
+            .zip(&self.origin_weights)
+        {
+            let step = (o.learning_rate * g).clamp(-o.max_step, o.max_step);
+            rule.weight = (rule.weight - step)
+                .clamp(origin - o.max_drift, origin + o.max_drift)
+                .clamp(0.0, 1.0);
+        }
+        self.updates += 1;
+        Ok(loss)
+    }
+
+    /// Number of updates applied so far.
+    pub fn updates(&self) -> usize {
+        self.updates
+    }
+
+    /// Restores the parameters `space` had when `self` was created.
+    pub fn reset(&mut self, space: &mut RuleSpace) -> error::Result<()> {
+        for (slot, p) in self.slots.iter().zip(&self.origin) {
+            space.set_slot(slot, p)?;
+        }
+        for (rule, &w) in space.rules.iter_mut().zip(&self.origin_weights) {
+            rule.weight = w;
+        }
+        self.updates = 0;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::{Online, OnlineOptions};
+    use crate::antecedent::Antecedent;
+    use crate::mamdani::{Consequent, Rule};
+    use crate::prelude::*;
+    use crate::rulespace::RuleSpace;
+    use crate::variable::Variable;
+
+    /// temp -> fan with a Gaussian `high` fan term centred on `fan_mean`.
+    fn space(fan_mean: Float) -> RuleSpace {
+        let mut temp = Variable::new(0.0, 10.0).unwrap();
+        temp.insert_term(
+            "cold",
+            Term::new("cold", Triangular::new(-10.0, 0.0, 10.0).unwrap()),
+        )
+        .unwrap();
+        temp.insert_term(
+            "hot",
+            Term::new("hot", Triangular::new(0.0, 10.0, 20.0).unwrap()),
+        )
+        .unwrap();
+        let mut fan = Variable::new(0.0, 100.0).unwrap();
+        fan.insert_term(
+            "low",
+            Term::new("low", Triangular::new(-50.0, 0.0, 50.0).unwrap()),
+        )
+        .unwrap();
+        fan.insert_term(
+            "high",
+            Term::new("high", Gaussian::with_mean_sd(fan_mean, 15.0).unwrap()),
+        )
+        .unwrap();
+        let rule = |t: &'static str, f: &'static str| Rule {
+            antecedent: Antecedent::Atom {
+                var: "temp".into(),
+                term: t.into(),
+            },
+            consequent: vec![Consequent::Term {
+                var: "fan".into(),
+                term: f.into(),
+                hedge: None,
+            }],
+            weight: 1.0,
+        };
+        let vars = HashMap::from([("temp".to_string(), temp), ("fan".to_string(), fan)]);
+        RuleSpace::new(vars, vec![rule("cold", "low"), rule("hot", "high")]).unwrap()
+    }
+
+    fn mean(rs: &RuleSpace) -> Float {
+        rs.var("fan").unwrap().get("high").unwrap().params()[0]
+    }
+
+    #[test]
+    fn follows_a_drifted_plant_within_bounds() {
+        let sampler = UniformSampler::default();
+        let plant = space(85.0);
+        let mut rs = space(70.0);
+        let mut online = Online::new(&rs, &sampler, OnlineOptions::default()).unwrap();
+
+        let mut losses = Vec::new();
+        for i in 0..200 {
+            let input = HashMap::from([("temp", (i % 9 + 1) as Float)]);
+            let y = plant.evaluate(&input, &sampler).unwrap()["fan"];
+            let before = mean(&rs);
+            losses.push(
+                online
+                    .update(&mut rs, &(input, HashMap::from([("fan", y)])))
+                    .unwrap(),
+            );
+            // One update moves a parameter at most `max_step * width`.
+            assert!((mean(&rs) - before).abs() <= 1.0 + 1e-3);
+        }
+        assert_eq!(online.updates(), 200);
+        let early: Float = losses[..9].iter().sum();
+        let late: Float = losses[191..].iter().sum();
+        assert!(late < 0.1 * early, "{early} -> {late}");
+        assert!(mean(&rs) > 75.0, "{}", mean(&rs));
+
+        online.reset(&mut rs).unwrap();
+        assert_eq!(mean(&rs), 70.0);
+    }
+
+    #[test]
+    fn drift_is_bounded() {
+        let sampler = UniformSampler::default();
+        let plant = space(95.0);
+        let mut rs = space(50.0);
+        let opts = OnlineOptions {
+            max_drift: 0.1,
+            ..OnlineOptions::default()
+        };
+        let mut online = Online::new(&rs, &sampler, opts).unwrap();
+        for i in 0..300 {
+            let input = HashMap::from([("temp", (i % 9 + 1) as Float)]);
+            let y = plant.evaluate(&input, &sampler).unwrap()["fan"];
+            online
+                .update(&mut rs, &(input, HashMap::from([("fan", y)])))
+                .unwrap();
+        }
+        assert!(mean(&rs) <= 60.0 + 1e-3, "{}", mean(&rs));
+    }
+
+    #[test]
+    fn weights_adapt_within_their_drift_bound() {
+        let sampler = UniformSampler::default();
+        let mut plant = space(85.0);
+        plant.rules[1].weight = 0.5;
+        let mut rs = space(85.0);
+        let opts = OnlineOptions {
+            learning_rate: 1.0,
+            max_drift: 0.05,
+            weights: true,
+            ..OnlineOptions::default()
+        };
+        let mut online = Online::new(&rs, &sampler, opts).unwrap();
+        for i in 0..200 {
+            let input = HashMap::from([("temp", (i % 9 + 1) as Float)]);
+            let y = plant.evaluate(&input, &sampler).unwrap()["fan"];
+            online
+                .update(&mut rs, &(input, HashMap::from([("fan", y)])))
+                .unwrap();
+        }
+        // The plant's 0.5 lies beyond `max_drift` of the starting 1.
+        let hot = rs.rules()[1].weight;
+        assert!((hot - 0.95).abs() < 1e-6, "{hot}");
+
+        online.reset(&mut rs).unwrap();
+        assert_eq!(rs.rules()[1].weight, 1.0);
+    }
+}
@@ -14,6 +14,17 @@ pub trait FuzzyOps {
     fn c(&self, a: Float) -> Float;
 }
 
+/// Probabilistic (algebraic) sum `a + b - a * b`, the S-norm dual to the
+/// product.
+///
+/// Rounding can push the raw formula a hair outside `[max(a, b), 1]` (e.g.
+/// `s(1, b)` slightly above or below 1), so the result is clamped to that
+/// range: degrees in [0, 1] always give a degree in [0, 1], and 0 and 1 stay
+/// exact identity and absorbing elements.
+pub fn probabilistic_sum(a: Float, b: Float) -> Float {
+    (a + b - a * b).max(a.max(b)).min(1.0)
+}
+
 #[cfg(feature = "ops-minmax")]
 pub struct MinMax;
 #[cfg(feature = "ops-minmax")]
@@ -40,7 +51,7 @@ impl FuzzyOps for MinMax {
     }
 
     fn s(&self, a: Float, b: Float) -> Float {
-        a + b - a * b
+        probabilistic_sum(a, b)
     }
 
     fn c(&self, a: Float) -> Float {
@@ -66,7 +77,7 @@ impl FuzzyOps for MinMax {
 }
 
 #[cfg(feature = "ops-dyn")]
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 /// Built-in operator families providing AND/OR/NOT over degrees.
 pub enum Ops {
     /// Min–Max family
@@ -76,7 +87,7 @@ pub enum Ops {
     MinMax,
     /// Product family
     /// - T: `a * b`
-    /// - S: `a + b - a * b` (not algebraic sum; may exceed 1.0)
+    /// - S: `a + b - a * b` (probabilistic sum, see `probabilistic_sum`)
     /// - C: `1 - a`
     Product,
     /// Łukasiewicz family
@@ -84,6 +95,16 @@ pub enum Ops {
     /// - S: `min(1, a + b)`
     /// - C: `1 - a`
     Lukasiewicz,
+    /// Drastic family, the smallest T-norm and largest S-norm
+    /// - T: `b` if `a == 1`, `a` if `b == 1`, else `0`
+    /// - S: `b` if `a == 0`, `a` if `b == 0`, else `1`
+    /// - C: `1 - a`
+    Drastic,
+    /// Nilpotent (Fodor) family
+    /// - T: `min(a, b)` if `a + b > 1`, else `0`
+    /// - S: `max(a, b)` if `a + b < 1`, else `1`
+    /// - C: `1 - a`
+    Nilpotent,
 }
 #[cfg(feature = "ops-dyn")]
 /// Implements `FuzzyOps` for each `Ops` variant using the formulas above.
@@ -94,6 +115,22 @@ impl FuzzyOps for Ops {
             Ops::MinMax => a.min(b),
             Ops::Product => a * b,
             Ops::Lukasiewicz => (a + b - 1.0).max(0.0),
+            Ops::Drastic => {
+                if a >= 1.0 {
+                    b
+                } else if b >= 1.0 {
+                    a
+                } else {
+                    0.0
+                }
+            }
+            Ops::Nilpotent => {
+                if a + b > 1.0 {
+                    a.min(b)
+                } else {
+                    0.0
+                }
+            }
         }
     }
 
@@ -101,8 +138,24 @@ impl FuzzyOps for Ops {
     fn s(&self, a: Float, b: Float) -> Float {
         match self {
             Ops::MinMax => a.max(b),
-            Ops::Product => a + b - a * b,
+            Ops::Product => probabilistic_sum(a, b),
             Ops::Lukasiewicz => (a + b).min(1.0),
+            Ops::Drastic => {
+                if a <= 0.0 {
+                    b
+                } else if b <= 0.0 {
+                    a
+                } else {
+                    1.0
+                }
+            }
+            Ops::Nilpotent => {
+                if a + b < 1.0 {
+                    a.max(b)
+                } else {
+                    1.0
+                }
+            }
         }
     }
 
@@ -112,6 +165,79 @@ impl FuzzyOps for Ops {
     }
 }
 
+/// Averaging operators: compensatory combinations that lie between AND
+/// (`min`) and OR (`max`), so a weak criterion lowers the result without
+/// vetoing it as a T-norm would.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Mean {
+    /// `(a_1 + ... + a_n) / n`
+    Arithmetic,
+    /// `(a_1 * ... * a_n)^(1/n)`
+    Geometric,
+    /// `n / (1/a_1 + ... + 1/a_n)`, zero if any degree is zero.
+    Harmonic,
+    /// Quasi-arithmetic mean generated by `x^p`: `((a_1^p + ... + a_n^p) / n)^(1/p)`.
+    /// `p = 1` is arithmetic, `p -> 0` geometric, `p = -1` harmonic; large `p`
+    /// approaches `max` and very negative `p` approaches `min`.
+    Power(Float),
+}
+
+impl Mean {
+    /// Mean of the degrees in `xs`; NaN when `xs` is empty.
+    pub fn apply(self, xs: &[Float]) -> Float {
+        let n = xs.len() as Float;
+        match self {
+            Mean::Arithmetic => xs.iter().sum::<Float>() / n,
+            Mean::Geometric | Mean::Power(0.0) => {
+                if xs.is_empty() {
+                    return Float::NAN;
+                }
+                xs.iter().product::<Float>().powf(1.0 / n)
+            }
+            Mean::Harmonic => Mean::Power(-1.0).apply(xs),
+            Mean::Power(p) => {
+                // A zero degree makes every negative-order mean zero.
+                if p < 0.0 && xs.contains(&0.0) {
+                    return 0.0;
+                }
+                (xs.iter().map(|x| x.powf(p)).sum::<Float>() / n).powf(1.0 / p)
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for Mean {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Mean::Arithmetic => f.write_str("mean"),
+            Mean::Geometric => f.write_str("geometric mean"),
+            Mean::Harmonic => f.write_str("harmonic mean"),
+            Mean::Power(p) => write!(f, "power mean({p})"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests_mean {
+    use super::Mean;
+
+    #[test]
+    fn means_lie_between_min_and_max() {
+        let xs = [0.2, 0.8];
+        let eps = 1e-6;
+        assert!((Mean::Arithmetic.apply(&xs) - 0.5).abs() < eps);
+        assert!((Mean::Geometric.apply(&xs) - 0.4).abs() < eps);
+        assert!((Mean::Harmonic.apply(&xs) - 0.32).abs() < eps);
+        assert!((Mean::Power(1.0).apply(&xs) - 0.5).abs() < eps);
+        assert!((Mean::Power(0.0).apply(&xs) - 0.4).abs() < eps);
+        assert!(Mean::Power(50.0).apply(&xs) > 0.78);
+        assert!(Mean::Power(-50.0).apply(&xs) < 0.21);
+        assert_eq!(Mean::Harmonic.apply(&[0.0, 0.9]), 0.0);
+        assert!(Mean::Arithmetic.apply(&[]).is_nan());
+    }
+}
+
 #[cfg(feature = "ops-dyn")]
 #[cfg(test)]
 mod tests_dyn_ops {
@@ -142,7 +268,7 @@ mod tests_dyn_ops {
         let eps = crate::Float::EPSILON;
         // t = a*b
         assert!((v.t(0.2, 0.8) - 0.16).abs() < eps);
-        // s = a + b (per current code)
+        // s = a + b - a * b
         assert!((v.s(0.1, 0.2) - 0.28).abs() < eps);
         // c = 1 - a
         assert!((v.c(0.2) - 0.8).abs() < eps);
@@ -161,4 +287,109 @@ mod tests_dyn_ops {
         // c = 1 - a
         assert!((v.c(0.2) - 0.8).abs() < eps);
     }
+
+    #[test]
+    fn drastic_and_nilpotent_ops() {
+        let d = Ops::Drastic;
+        assert_eq!(d.t(0.9, 0.8), 0.0);
+        assert_eq!(d.t(1.0, 0.3), 0.3);
+        assert_eq!(d.t(0.3, 1.0), 0.3);
+        assert_eq!(d.s(0.1, 0.2), 1.0);
+        assert_eq!(d.s(0.0, 0.3), 0.3);
+        assert_eq!(d.s(0.3, 0.0), 0.3);
+
+        let n = Ops::Nilpotent;
+        assert_eq!(n.t(0.6, 0.7), 0.6);
+        assert_eq!(n.t(0.5, 0.5), 0.0);
+        assert_eq!(n.s(0.2, 0.3), 0.3);
+        assert_eq!(n.s(0.5, 0.5), 1.0);
+
+        // Both families keep 1 and 0 as identities and stay between the
+        // drastic bounds and min/max.
+        let grid = [0.0, 0.25, 0.5, 0.75, 1.0];
+        for ops in [d, n] {
+            for &a in &grid {
+                assert_eq!(ops.t(a, 1.0), a);
+                assert_eq!(ops.s(a, 0.0), a);
+                for &b in &grid {
+                    assert!(d.t(a, b) <= ops.t(a, b) && ops.t(a, b) <= a.min(b));
+                    assert!(a.max(b) <= ops.s(a, b) && ops.s(a, b) <= d.s(a, b));
+                    assert_eq!(ops.t(a, b), ops.t(b, a));
+                    assert_eq!(ops.s(a, b), ops.s(b, a));
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests_bounds {
+    use super::*;
+    use crate::rng::Rng;
+
+    /// Random degree pairs in [0, 1], including the endpoints and the values
+    /// just inside them where rounding is most likely to escape the range.
+    fn pairs() -> Vec<(Float, Float)> {
+        let edges = [0.0, Float::EPSILON, 0.5, 1.0 - Float::EPSILON, 1.0];
+        let mut rng = Rng::new(714);
+        let mut out: Vec<_> = edges
+            .iter()
+            .flat_map(|&a| edges.iter().map(move |&b| (a, b)))
+            .collect();
+        out.extend((0..10_000).map(|_| (rng.uniform(), rng.uniform())));
+        out
+    }
+
+    fn unit(x: Float) -> bool {
+        (0.0..=1.0).contains(&x)
+    }
+
+    #[test]
+    fn probabilistic_sum_stays_in_unit_interval() {
+        for (a, b) in pairs() {
+            let s = probabilistic_sum(a, b);
+            assert!(unit(s) && s >= a.max(b), "{a} {b} -> {s}");
+            assert_eq!(s, probabilistic_sum(b, a));
+        }
+        assert_eq!(probabilistic_sum(1.0, 0.1), 1.0);
+        assert_eq!(probabilistic_sum(0.0, 0.3), 0.3);
+    }
+
+    #[cfg(feature = "ops-dyn")]
+    #[test]
+    fn every_family_stays_in_unit_interval() {
+        let families = [
+            Ops::MinMax,
+            Ops::Product,
+            Ops::Lukasiewicz,
+            Ops::Drastic,
+            Ops::Nilpotent,
+        ];
+        for ops in families {
+            for (a, b) in pairs() {
+                let (t, s) = (ops.t(a, b), ops.s(a, b));
+                assert!(unit(t) && unit(s) && unit(ops.c(a)), "{ops:?} {a} {b}");
+                assert!(t <= a.min(b) && s >= a.max(b), "{ops:?} {a} {b}");
+            }
+        }
+    }
+
+    #[test]
+    fn means_stay_in_unit_interval() {
+        let kinds = [
+            Mean::Arithmetic,
+            Mean::Geometric,
+            Mean::Harmonic,
+            Mean::Power(3.0),
+            Mean::Power(-2.0),
+        ];
+        for kind in kinds {
+            for (a, b) in pairs() {
+                let m = kind.apply(&[a, b]);
+                let eps = 4.0 * Float::EPSILON;
+                assert!(unit(m), "{kind} {a} {b} -> {m}");
+                assert!(m >= a.min(b) - eps && m <= a.max(b) + eps, "{kind} {a} {b}");
+            }
+        }
+    }
 }
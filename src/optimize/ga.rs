@@ -0,0 +1,227 @@
+// Genetic algorithm over a `SearchSpace`.
+//
+// Tournament selection, arithmetic crossover for continuous genes and uniform
+// crossover for discrete ones, Gaussian mutation scaled to each range, and a few
+// elite individuals carried over unchanged.
+use super::{cost, Dim, SearchSpace, Solution};
+use crate::rng::Rng;
+use crate::{
+    error::{FuzzyError, Result},
+    Float,
+};
+
+/// Settings for `genetic`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GaOptions {
+    /// Individuals per generation (at least 2).
+    pub population: usize,
+    pub generations: usize,
+    /// Probability that two parents are recombined rather than copied.
+    pub crossover_rate: Float,
+    /// Per-gene mutation probability.
+    pub mutation_rate: Float,
+    /// Standard deviation of continuous mutations, as a fraction of the gene's range.
+    pub mutation_scale: Float,
+    /// Best individuals copied unchanged into the next generation.
+    pub elitism: usize,
+    /// Individuals compared per tournament selection.
+    pub tournament: usize,
+    pub seed: u64,
+}
+
+impl Default for GaOptions {
+    fn default() -> Self {
+        Self {
+            population: 40,
+            generations: 50,
+            crossover_rate: 0.9,
+            mutation_rate: 0.1,
+            mutation_scale: 0.1,
+            elitism: 2,
+            tournament: 3,
+            seed: 0x5EED,
+        }
+    }
+}
+
+/// Minimizes `objective` over `space` with a genetic algorithm.
+///
+/// `initial`, if given, seeds one individual (e.g. `Tunables::current`), so the
+/// result is never worse than the starting design. NaN costs count as +∞.
+pub fn genetic<F>(
+    space: &SearchSpace,
+    initial: Option<&[Float]>,
+    mut objective: F,
+    opts: GaOptions,
+) -> Result<Solution>
+where
+    F: FnMut(&[Float]) -> Float,
+{
+    space.check()?;
+    if opts.population < 2 || opts.tournament == 0 {
+        return Err(FuzzyError::OutOfBounds);
+    }
+    let mut rng = Rng::new(opts.seed);
+    let mut evaluations = 0;
+
+    let mut pop: Vec<(Vec<Float>, Float)> = Vec::with_capacity(opts.population);
+    let first = space.start(initial, &mut rng)?;
+    let c = cost(&mut objective, &first, &mut evaluations);
+    pop.push((first, c));
+    while pop.len() < opts.population {
+        let v = space.sample(&mut rng);
+        let c = cost(&mut objective, &v, &mut evaluations);
+        pop.push((v, c));
+    }
+    pop.sort_by(|a, b| a.1.total_cmp(&b.1));
+    let mut history = vec![pop[0].1];
+
+    for _ in 0..opts.generations {
+        let mut next: Vec<(Vec<Float>, Float)> = pop.iter().take(opts.elitism).cloned().collect();
+        while next.len() < opts.population {
+            let a = tournament(&pop, opts.tournament, &mut rng);
+            let b = tournament(&pop, opts.tournament, &mut rng);
+            let mut child = if rng.uniform() < opts.crossover_rate {
+                crossover(space, a, b, &mut rng)
+            } else {
+                a.to_vec()
+            };
+            mutate(space, &mut child, &opts, &mut rng);
+            space.clamp(&mut child);
+            let c = cost(&mut objective, &child, &mut evaluations);
+            next.push((child, c));
+        }
+        pop = next;
+        pop.sort_by(|a, b| a.1.total_cmp(&b.1));
+        history.push(pop[0].1);
+    }
+
+    let (params, cost) = pop.swap_remove(0);
+    Ok(Solution {
+        params,
+        cost,
+        evaluations,
+        history,
+    })
+}
+
+/// Best of `k` individuals drawn at random; `pop` is sorted, so the lowest index wins.
+fn tournament<'a>(pop: &'a [(Vec<Float>, Float)], k: usize, rng: &mut Rng) -> &'a [Float] {
+    let best = (0..k).map(|_| rng.below(pop.len())).min().unwrap_or(0);
+    &pop[best].0
+}
+
+fn crossover(space: &SearchSpace, a: &[Float], b: &[Float], rng: &mut Rng) -> Vec<Float> {
+    space
+        .dims
+        .iter()
+        .zip(a.iter().zip(b))
+        .map(|(d, (&x, &y))| match d {
+            Dim::Continuous { .. } => x + rng.uniform() * (y - x),
+            Dim::Discrete { .. } => {
+                if rng.uniform() < 0.5 {
+                    x
+                } else {
+                    y
+                }
+            }
+        })
+        .collect()
+}
+
+fn mutate(space: &SearchSpace, v: &mut [Float], opts: &GaOptions, rng: &mut Rng) {
+    for (x, d) in v.iter_mut().zip(&space.dims) {
+        if rng.uniform() >= opts.mutation_rate {
+            continue;
+        }
+        match *d {
+            Dim::Continuous { min, max } => *x += rng.normal() * opts.mutation_scale * (max - min),
+            Dim::Discrete { .. } => *x = d.sample(rng),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::optimize::tests::{mismatch, space};
+    use crate::optimize::{TunableOptions, Tunables};
+
+    #[test]
+    fn minimizes_a_continuous_bowl() {
+        let space = SearchSpace::new(vec![
+            Dim::Continuous {
+                min: -5.0,
+                max: 5.0
+            };
+            3
+        ]);
+        let sol = genetic(
+            &space,
+            None,
+            |v| v.iter().map(|x| (x - 1.0) * (x - 1.0)).sum(),
+            GaOptions::default(),
+        )
+        .unwrap();
+        assert!(sol.cost < 1e-2, "{sol:?}");
+        assert!(sol.history.windows(2).all(|w| w[1] <= w[0]));
+        assert_eq!(sol.evaluations, 40 + 50 * 38);
+    }
+
+    #[test]
+    fn repairs_swapped_consequents_and_apex() {
+        let reference = space(10.0, false);
+        let mut rs = space(6.0, true);
+        let tunables = Tunables::new(
+            &rs,
+            TunableOptions {
+                consequents: true,
+                ..TunableOptions::default()
+            },
+        );
+        let start = tunables.current(&rs);
+        let before = mismatch(&rs, &reference);
+
+        let sol = genetic(
+            &tunables.search_space(),
+            Some(&start),
+            |v| match tunables.apply(&mut rs, v) {
+                Ok(()) => mismatch(&rs, &reference),
+                Err(_) => Float::INFINITY,
+            },
+            GaOptions::default(),
+        )
+        .unwrap();
+        tunables.apply(&mut rs, &sol.params).unwrap();
+
+        assert_eq!(rs.rules()[0].consequent[0].term(), Some("low"));
+        assert_eq!(rs.rules()[1].consequent[0].term(), Some("high"));
+        assert!(sol.cost < 0.05 * before, "{} vs {before}", sol.cost);
+    }
+
+    #[test]
+    fn invalid_settings_are_rejected() {
+        let space = SearchSpace::new(vec![Dim::Discrete { choices: 2 }]);
+        let zero = |_: &[Float]| 0.0;
+        assert_eq!(
+            genetic(
+                &space,
+                None,
+                zero,
+                GaOptions {
+                    population: 1,
+                    ..GaOptions::default()
+                }
+            ),
+            Err(FuzzyError::OutOfBounds)
+        );
+        assert_eq!(
+            genetic(&space, Some(&[0.0, 1.0]), zero, GaOptions::default()),
+            Err(FuzzyError::BadArity)
+        );
+        assert_eq!(
+            genetic(&SearchSpace::new(vec![]), None, zero, GaOptions::default()),
+            Err(FuzzyError::EmptyInput)
+        );
+    }
+}
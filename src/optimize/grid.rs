@@ -0,0 +1,179 @@
+// Exhaustive grid search over a `SearchSpace`.
+//
+// Continuous coordinates are sampled at evenly spaced points, discrete ones at
+// every choice. Candidates are addressed by a mixed-radix index so they can be
+// generated independently, which lets the `parallel` feature spread them over
+// the rayon pool.
+use super::{Dim, SearchSpace, Solution};
+use crate::{
+    error::{FuzzyError, Result},
+    Float,
+};
+
+/// Settings for `grid_search`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GridOptions {
+    /// Points per continuous coordinate, endpoints included (at least 2).
+    pub steps: usize,
+    /// Refuse grids with more candidates than this.
+    pub max_candidates: usize,
+}
+
+impl Default for GridOptions {
+    fn default() -> Self {
+        Self {
+            steps: 5,
+            max_candidates: 1_000_000,
+        }
+    }
+}
+
+/// Evaluates `objective` at every point of a grid over `space` and returns the best.
+///
+/// The objective must be `Fn + Sync` since with the `parallel` feature candidates
+/// are evaluated concurrently; to tune a `RuleSpace`, build or lock one inside the
+/// closure and `Tunables::apply` the candidate to it. Ties go to the first
+/// candidate in grid order, so results do not depend on scheduling. NaN costs
+/// count as +∞, and `history` holds only the final best cost.
+///
+/// Fails with `OutOfBounds` if `steps < 2` or the grid exceeds `max_candidates`.
+pub fn grid_search<F>(space: &SearchSpace, objective: F, opts: GridOptions) -> Result<Solution>
+where
+    F: Fn(&[Float]) -> Float + Sync,
+{
+    space.check()?;
+    if opts.steps < 2 {
+        return Err(FuzzyError::OutOfBounds);
+    }
+    let radix: Vec<usize> = space
+        .dims
+        .iter()
+        .map(|d| match *d {
+            Dim::Continuous { .. } => opts.steps,
+            Dim::Discrete { choices } => choices,
+        })
+        .collect();
+    let total = radix
+        .iter()
+        .try_fold(1usize, |acc, &r| acc.checked_mul(r))
+        .filter(|&n| n <= opts.max_candidates)
+        .ok_or(FuzzyError::OutOfBounds)?;
+
+    let point = |mut i: usize| -> Vec<Float> {
+        space
+            .dims
+            .iter()
+            .zip(&radix)
+            .map(|(d, &r)| {
+                let k = i % r;
+                i /= r;
+                match *d {
+                    Dim::Continuous { min, max } => {
+                        min + (max - min) * k as Float / (r - 1) as Float
+                    }
+                    Dim::Discrete { .. } => k as Float,
+                }
+            })
+            .collect()
+    };
+    let score = |i: usize| {
+        let c = objective(&point(i));
+        (if c.is_nan() { Float::INFINITY } else { c }, i)
+    };
+    let better = |a: (Float, usize), b: (Float, usize)| {
+        if b.0.total_cmp(&a.0).then(b.1.cmp(&a.1)).is_lt() {
+            b
+        } else {
+            a
+        }
+    };
+
+    #[cfg(feature = "parallel")]
+    let (cost, best) = {
+        use rayon::prelude::*;
+        (0..total)
+            .into_par_iter()
+            .map(score)
+            .reduce(|| (Float::INFINITY, usize::MAX), better)
+    };
+    #[cfg(not(feature = "parallel"))]
+    let (cost, best) = (0..total)
+        .map(score)
+        .fold((Float::INFINITY, usize::MAX), better);
+
+    Ok(Solution {
+        // Every candidate scored +∞ only if `best` was never replaced.
+        params: point(if best == usize::MAX { 0 } else { best }),
+        cost,
+        evaluations: total,
+        history: vec![cost],
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use super::*;
+    use crate::optimize::tests::{mismatch, space};
+    use crate::optimize::{TunableOptions, Tunables};
+
+    #[test]
+    fn visits_every_grid_point() {
+        let space = SearchSpace::new(vec![
+            Dim::Continuous { min: 0.0, max: 4.0 },
+            Dim::Discrete { choices: 3 },
+        ]);
+        let seen = Mutex::new(Vec::new());
+        let sol = grid_search(
+            &space,
+            |v| {
+                seen.lock().unwrap().push(v.to_vec());
+                (v[0] - 3.0).abs() + (v[1] - 1.0).abs()
+            },
+            GridOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(sol.params, vec![3.0, 1.0]);
+        assert_eq!(sol.cost, 0.0);
+        assert_eq!(sol.evaluations, 15);
+        assert_eq!(seen.into_inner().unwrap().len(), 15);
+
+        let tiny = GridOptions {
+            max_candidates: 14,
+            ..GridOptions::default()
+        };
+        assert_eq!(
+            grid_search(&space, |_| 0.0, tiny),
+            Err(FuzzyError::OutOfBounds)
+        );
+    }
+
+    #[test]
+    fn finds_the_reference_consequents() {
+        let reference = space(10.0, false);
+        let rs = Mutex::new(space(10.0, true));
+        let tunables = Tunables::new(
+            &rs.lock().unwrap(),
+            TunableOptions {
+                terms: false,
+                consequents: true,
+                ..TunableOptions::default()
+            },
+        );
+        let sol = grid_search(
+            &tunables.search_space(),
+            |v| {
+                let mut rs = rs.lock().unwrap();
+                match tunables.apply(&mut rs, v) {
+                    Ok(()) => mismatch(&rs, &reference),
+                    Err(_) => Float::INFINITY,
+                }
+            },
+            GridOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(sol.params, vec![0.0, 1.0]);
+        assert_eq!(sol.cost, 0.0);
+    }
+}
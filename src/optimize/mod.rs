@@ -0,0 +1,448 @@
+// Derivative-free optimizers for rule spaces (feature `optimize`).
+//
+// Every optimizer minimizes a user objective over a `SearchSpace`: a list of
+// continuous ranges and discrete choices, with candidates passed as flat `Float`
+// vectors. `Tunables` connects that abstraction to a `RuleSpace` by exposing term
+// parameters, rule consequent assignments and rule weights as such a vector, so
+// one objective closure (`tunables.apply(&mut space, v)` followed by a cost on
+// `space`) works with every optimizer.
+use crate::{
+    error::{FuzzyError, Result},
+    intern::{TermId, VarId},
+    mamdani::Consequent,
+    membership::MembershipFn,
+    rng::Rng,
+    rulespace::RuleSpace,
+    Float,
+};
+
+pub mod ga;
+pub mod grid;
+pub mod pso;
+
+pub use ga::{genetic, GaOptions};
+pub use grid::{grid_search, GridOptions};
+pub use pso::{particle_swarm, PsoOptions};
+
+/// One coordinate of a search space.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Dim {
+    /// Any value in `[min, max]`.
+    Continuous { min: Float, max: Float },
+    /// One of `choices` options, encoded as the index `0.0, 1.0, ..`.
+    Discrete { choices: usize },
+}
+
+impl Dim {
+    /// The continuous interval covering the dimension.
+    fn bounds(&self) -> (Float, Float) {
+        match *self {
+            Dim::Continuous { min, max } => (min, max),
+            Dim::Discrete { choices } => (0.0, (choices - 1) as Float),
+        }
+    }
+
+    fn clamp(&self, v: Float) -> Float {
+        match *self {
+            Dim::Continuous { min, max } => v.clamp(min, max),
+            Dim::Discrete { choices } => v.round().clamp(0.0, (choices - 1) as Float),
+        }
+    }
+
+    fn sample(&self, rng: &mut Rng) -> Float {
+        match *self {
+            Dim::Continuous { min, max } => min + rng.uniform() * (max - min),
+            Dim::Discrete { choices } => rng.below(choices) as Float,
+        }
+    }
+}
+
+/// The coordinates an optimizer searches over.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SearchSpace {
+    pub dims: Vec<Dim>,
+}
+
+impl SearchSpace {
+    pub fn new(dims: Vec<Dim>) -> Self {
+        Self { dims }
+    }
+
+    /// Number of coordinates.
+    pub fn len(&self) -> usize {
+        self.dims.len()
+    }
+
+    /// True when there is nothing to search.
+    pub fn is_empty(&self) -> bool {
+        self.dims.is_empty()
+    }
+
+    /// Pulls every coordinate of `v` into its range (discrete ones are rounded).
+    pub fn clamp(&self, v: &mut [Float]) {
+        for (x, d) in v.iter_mut().zip(&self.dims) {
+            *x = d.clamp(*x);
+        }
+    }
+
+    /// Rejects empty spaces, inverted ranges and choice-less dimensions.
+    fn check(&self) -> Result<()> {
+        if self.dims.is_empty() {
+            return Err(FuzzyError::EmptyInput);
+        }
+        let valid = self.dims.iter().all(|d| match *d {
+            Dim::Continuous { min, max } => min <= max,
+            Dim::Discrete { choices } => choices > 0,
+        });
+        if valid {
+            Ok(())
+        } else {
+            Err(FuzzyError::OutOfBounds)
+        }
+    }
+
+    /// A starting point: `initial` clamped into range, or a uniform sample.
+    fn start(&self, initial: Option<&[Float]>, rng: &mut Rng) -> Result<Vec<Float>> {
+        match initial {
+            Some(v) if v.len() != self.len() => Err(FuzzyError::BadArity),
+            Some(v) => {
+                let mut v = v.to_vec();
+                self.clamp(&mut v);
+                Ok(v)
+            }
+            None => Ok(self.sample(rng)),
+        }
+    }
+
+    fn sample(&self, rng: &mut Rng) -> Vec<Float> {
+        self.dims.iter().map(|d| d.sample(rng)).collect()
+    }
+}
+
+/// Best candidate an optimizer found.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Solution {
+    pub params: Vec<Float>,
+    pub cost: Float,
+    /// Number of objective calls made.
+    pub evaluations: usize,
+    /// Best cost after each generation/iteration, starting with the initial one
+    /// (grid search records only the final best).
+    pub history: Vec<Float>,
+}
+
+/// Calls the objective, mapping NaN to +∞ so failed candidates always lose.
+fn cost<F: FnMut(&[Float]) -> Float>(
+    objective: &mut F,
+    v: &[Float],
+    evaluations: &mut usize,
+) -> Float {
+    *evaluations += 1;
+    let c = objective(v);
+    if c.is_nan() {
+        Float::INFINITY
+    } else {
+        c
+    }
+}
+
+/// What `Tunables` exposes and how far term parameters may move.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TunableOptions {
+    /// Expose the parameters of every term that reports them.
+    pub terms: bool,
+    /// Expose which term each rule consequent concludes.
+    pub consequents: bool,
+    /// Expose every rule's weight, ranging over [0, 1].
+    pub weights: bool,
+    /// Term parameters may move this fraction of their variable's domain width
+    /// away from their current value.
+    pub spread: Float,
+    /// Minimum gap kept between ordered parameters, as a fraction of the domain width.
+    pub min_gap: Float,
+}
+
+impl Default for TunableOptions {
+    fn default() -> Self {
+        Self {
+            terms: true,
+            consequents: false,
+            weights: false,
+            spread: 0.25,
+            min_gap: 1e-2,
+        }
+    }
+}
+
+struct TermSlot {
+    var: usize,
+    term: TermId,
+    width: Float,
+    base: Vec<Float>,
+}
+
+struct ConsequentSlot {
+    rule: usize,
+    index: usize,
+    var: VarId,
+    choices: usize,
+}
+
+/// The tunable parameters of a `RuleSpace`, flattened into one vector.
+///
+/// The layout is fixed at construction: term parameters first (variables and
+/// terms in id order), then one discrete coordinate per rule consequent, then
+/// one weight per rule.
+pub struct Tunables {
+    terms: Vec<TermSlot>,
+    consequents: Vec<ConsequentSlot>,
+    weights: usize,
+    spread: Float,
+    min_gap: Float,
+}
+
+impl Tunables {
+    pub fn new(space: &RuleSpace, opts: TunableOptions) -> Self {
+        let mut terms = Vec::new();
+        if opts.terms {
+            for (v, var) in space.vars.iter().enumerate() {
+                let (min, max) = var.domain();
+                for (name, term) in var.terms() {
+                    let base = term.params();
+                    if !base.is_empty() {
+                        terms.push(TermSlot {
+                            var: v,
+                            term: var.term_id(name).expect("name comes from the variable"),
+                            width: max - min,
+                            base,
+                        });
+                    }
+                }
+            }
+        }
+        let mut consequents = Vec::new();
+        if opts.consequents {
+            for (r, rule) in space.compiled.iter().enumerate() {
+                for (index, &(var, _)) in rule.consequent.iter().enumerate() {
+                    consequents.push(ConsequentSlot {
+                        rule: r,
+                        index,
+                        var,
+                        choices: space.vars[var.index()].terms().count(),
+                    });
+                }
+            }
+        }
+        Self {
+            terms,
+            consequents,
+            weights: if opts.weights { space.rules.len() } else { 0 },
+            spread: opts.spread,
+            min_gap: opts.min_gap,
+        }
+    }
+
+    /// Length of the parameter vector.
+    pub fn len(&self) -> usize {
+        self.terms.iter().map(|s| s.base.len()).sum::<usize>()
+            + self.consequents.len()
+            + self.weights
+    }
+
+    /// True when nothing is tunable.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Ranges for every coordinate, centred on the values seen at construction.
+    pub fn search_space(&self) -> SearchSpace {
+        let terms = self.terms.iter().flat_map(|s| {
+            let r = self.spread * s.width;
+            s.base.iter().map(move |&p| Dim::Continuous {
+                min: p - r,
+                max: p + r,
+            })
+        });
+        let consequents = self
+            .consequents
+            .iter()
+            .map(|c| Dim::Discrete { choices: c.choices });
+        let weights = (0..self.weights).map(|_| Dim::Continuous { min: 0.0, max: 1.0 });
+        SearchSpace::new(terms.chain(consequents).chain(weights).collect())
+    }
+
+    /// The parameter vector describing `space` as it is now.
+    pub fn current(&self, space: &RuleSpace) -> Vec<Float> {
+        let mut v = Vec::with_capacity(self.len());
+        for s in &self.terms {
+            v.extend(term(space, s).params());
+        }
+        for c in &self.consequents {
+            v.push(space.compiled[c.rule].consequent[c.index].1.index() as Float);
+        }
+        v.extend(space.rules[..self.weights].iter().map(|r| r.weight));
+        v
+    }
+
+    /// Writes `params` into `space`.
+    ///
+    /// Term parameters are first pulled back into a valid shape with
+    /// `constrain_params` and weights clamped into [0, 1]. Fails with `BadArity` on a length mismatch; on error
+    /// some terms may already have been updated.
+    pub fn apply(&self, space: &mut RuleSpace, params: &[Float]) -> Result<()> {
+        if params.len() != self.len() {
+            return Err(FuzzyError::BadArity);
+        }
+        let mut rest = params;
+        for s in &self.terms {
+            let (head, tail) = rest.split_at(s.base.len());
+            rest = tail;
+            let mut p = head.to_vec();
+            term(space, s).constrain_params(&mut p, self.min_gap * s.width);
+            space.vars[s.var]
+                .term_mut(s.term)
+                .expect("slot ids come from the variable")
+                .set_params(&p)?;
+        }
+        let (rest, weights) = rest.split_at(self.consequents.len());
+        for (c, &x) in self.consequents.iter().zip(rest) {
+            let id = TermId(x.round().clamp(0.0, (c.choices - 1) as Float) as u32);
+            let name = space.vars[c.var.index()].term_name(id).to_string();
+            space.compiled[c.rule].consequent[c.index].1 = id;
+            let terms = space.rules[c.rule].consequent.iter_mut();
+            if let Some(Consequent::Term { term, .. }) =
+                terms.filter(|c| c.term().is_some()).nth(c.index)
+            {
+                *term = name.into();
+            }
+        }
+        for (rule, &w) in space.rules.iter_mut().zip(weights) {
+            rule.weight = w.clamp(0.0, 1.0);
+        }
+        Ok(())
+    }
+}
+
+fn term<'a>(space: &'a RuleSpace, s: &TermSlot) -> &'a crate::term::Term {
+    space.vars[s.var]
+        .term(s.term)
+        .expect("slot ids come from the variable")
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+    use crate::antecedent::Antecedent;
+    use crate::mamdani::{Consequent, Rule};
+    use crate::prelude::*;
+    use crate::variable::Variable;
+
+    /// temp -> fan; `swap` exchanges the two consequents.
+    pub(crate) fn space(hot_apex: Float, swap: bool) -> RuleSpace {
+        let mut temp = Variable::new(0.0, 10.0).unwrap();
+        temp.insert_term(
+            "cold",
+            Term::new("cold", Triangular::new(-10.0, 0.0, 10.0).unwrap()),
+        )
+        .unwrap();
+        temp.insert_term(
+            "hot",
+            Term::new("hot", Triangular::new(0.0, hot_apex, 20.0).unwrap()),
+        )
+        .unwrap();
+        let mut fan = Variable::new(0.0, 100.0).unwrap();
+        fan.insert_term(
+            "low",
+            Term::new("low", Triangular::new(-50.0, 0.0, 50.0).unwrap()),
+        )
+        .unwrap();
+        fan.insert_term(
+            "high",
+            Term::new("high", Triangular::new(50.0, 100.0, 150.0).unwrap()),
+        )
+        .unwrap();
+        let rule = |t: &'static str, f: &'static str| Rule {
+            antecedent: Antecedent::Atom {
+                var: "temp".into(),
+                term: t.into(),
+            },
+            consequent: vec![Consequent::Term {
+                var: "fan".into(),
+                term: f.into(),
+                hedge: None,
+            }],
+            weight: 1.0,
+        };
+        let (a, b) = if swap {
+            ("high", "low")
+        } else {
+            ("low", "high")
+        };
+        let vars = HashMap::from([("temp".to_string(), temp), ("fan".to_string(), fan)]);
+        RuleSpace::new(vars, vec![rule("cold", a), rule("hot", b)]).unwrap()
+    }
+
+    /// Squared error against `reference` on a sweep of temperatures.
+    pub(crate) fn mismatch(space: &RuleSpace, reference: &RuleSpace) -> Float {
+        let sampler = UniformSampler::default();
+        (1..10)
+            .map(|t| {
+                let input = HashMap::from([("temp", t as Float)]);
+                let a = space.evaluate(&input, &sampler).unwrap()["fan"];
+                let b = reference.evaluate(&input, &sampler).unwrap()["fan"];
+                (a - b) * (a - b)
+            })
+            .sum()
+    }
+
+    #[test]
+    fn tunables_round_trip_through_the_vector() {
+        let mut rs = space(10.0, true);
+        let t = Tunables::new(
+            &rs,
+            TunableOptions {
+                consequents: true,
+                ..TunableOptions::default()
+            },
+        );
+        // 4 triangles * 3 params + 2 consequents.
+        assert_eq!(t.len(), 14);
+        assert_eq!(t.search_space().len(), 14);
+
+        let mut v = t.current(&rs);
+        assert_eq!(&v[12..], &[1.0, 0.0]);
+        v[12] = 0.0;
+        v[13] = 1.0;
+        t.apply(&mut rs, &v).unwrap();
+        assert_eq!(rs.rules()[0].consequent[0].term(), Some("low"));
+        assert_eq!(rs.rules()[1].consequent[0].term(), Some("high"));
+        assert_eq!(mismatch(&rs, &space(10.0, false)), 0.0);
+
+        assert_eq!(t.apply(&mut rs, &v[1..]), Err(FuzzyError::BadArity));
+    }
+
+    #[test]
+    fn tunables_expose_rule_weights() {
+        let mut rs = space(10.0, false);
+        let t = Tunables::new(
+            &rs,
+            TunableOptions {
+                terms: false,
+                weights: true,
+                ..TunableOptions::default()
+            },
+        );
+        assert_eq!(t.current(&rs), [1.0, 1.0]);
+        assert_eq!(
+            t.search_space().dims[1],
+            Dim::Continuous { min: 0.0, max: 1.0 }
+        );
+
+        t.apply(&mut rs, &[0.5, 2.0]).unwrap();
+        assert_eq!(rs.rules()[0].weight, 0.5);
+        assert_eq!(rs.rules()[1].weight, 1.0);
+        assert!(mismatch(&rs, &space(10.0, false)) > 0.0);
+    }
+}
@@ -0,0 +1,209 @@
+// Particle swarm optimization over a `SearchSpace`.
+//
+// Global-best PSO with inertia. Discrete coordinates move continuously over
+// `[0, choices - 1]` and are rounded only when a particle is evaluated, so the
+// same `Tunables` objective works unchanged.
+use super::{cost, SearchSpace, Solution};
+use crate::rng::Rng;
+use crate::{
+    error::{FuzzyError, Result},
+    Float,
+};
+
+/// Settings for `particle_swarm`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PsoOptions {
+    /// Swarm size (at least 1).
+    pub particles: usize,
+    pub iterations: usize,
+    /// Fraction of its velocity a particle keeps each step.
+    pub inertia: Float,
+    /// Pull towards the particle's own best position.
+    pub cognitive: Float,
+    /// Pull towards the swarm's best position.
+    pub social: Float,
+    pub seed: u64,
+}
+
+impl Default for PsoOptions {
+    fn default() -> Self {
+        Self {
+            particles: 30,
+            iterations: 100,
+            inertia: 0.72,
+            cognitive: 1.49,
+            social: 1.49,
+            seed: 0x5EED,
+        }
+    }
+}
+
+struct Particle {
+    position: Vec<Float>,
+    velocity: Vec<Float>,
+    best: Vec<Float>,
+    best_cost: Float,
+}
+
+/// Minimizes `objective` over `space` with particle swarm optimization.
+///
+/// Takes the same arguments as `genetic`: `initial`, if given, places one
+/// particle on the starting design, and NaN costs count as +∞. Velocities are
+/// limited to each coordinate's range.
+pub fn particle_swarm<F>(
+    space: &SearchSpace,
+    initial: Option<&[Float]>,
+    mut objective: F,
+    opts: PsoOptions,
+) -> Result<Solution>
+where
+    F: FnMut(&[Float]) -> Float,
+{
+    space.check()?;
+    if opts.particles == 0 {
+        return Err(FuzzyError::OutOfBounds);
+    }
+    let mut rng = Rng::new(opts.seed);
+    let mut evaluations = 0;
+    let bounds: Vec<(Float, Float)> = space.dims.iter().map(|d| d.bounds()).collect();
+
+    let mut evaluate = |position: &[Float], evaluations: &mut usize| {
+        let mut v = position.to_vec();
+        space.clamp(&mut v);
+        cost(&mut objective, &v, evaluations)
+    };
+
+    let mut swarm = Vec::with_capacity(opts.particles);
+    let mut position = space.start(initial, &mut rng)?;
+    for _ in 0..opts.particles {
+        let velocity = bounds
+            .iter()
+            .map(|&(lo, hi)| (rng.uniform() - 0.5) * (hi - lo))
+            .collect();
+        let c = evaluate(&position, &mut evaluations);
+        swarm.push(Particle {
+            best: position.clone(),
+            position,
+            velocity,
+            best_cost: c,
+        });
+        position = bounds
+            .iter()
+            .map(|&(lo, hi)| lo + rng.uniform() * (hi - lo))
+            .collect();
+    }
+
+    let leader = |swarm: &[Particle]| {
+        let p = swarm
+            .iter()
+            .min_by(|a, b| a.best_cost.total_cmp(&b.best_cost))
+            .expect("swarm is not empty");
+        (p.best.clone(), p.best_cost)
+    };
+    let (mut global, mut global_cost) = leader(&swarm);
+    let mut history = vec![global_cost];
+
+    for _ in 0..opts.iterations {
+        for p in &mut swarm {
+            for (k, &(lo, hi)) in bounds.iter().enumerate() {
+                let limit = hi - lo;
+                let v = opts.inertia * p.velocity[k]
+                    + opts.cognitive * rng.uniform() * (p.best[k] - p.position[k])
+                    + opts.social * rng.uniform() * (global[k] - p.position[k]);
+                p.velocity[k] = v.clamp(-limit, limit);
+                p.position[k] = (p.position[k] + p.velocity[k]).clamp(lo, hi);
+            }
+            let c = evaluate(&p.position, &mut evaluations);
+            if c < p.best_cost {
+                p.best.clone_from(&p.position);
+                p.best_cost = c;
+            }
+        }
+        let (best, best_cost) = leader(&swarm);
+        if best_cost < global_cost {
+            global = best;
+            global_cost = best_cost;
+        }
+        history.push(global_cost);
+    }
+
+    space.clamp(&mut global);
+    Ok(Solution {
+        params: global,
+        cost: global_cost,
+        evaluations,
+        history,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::optimize::tests::{mismatch, space};
+    use crate::optimize::{genetic, Dim, GaOptions, TunableOptions, Tunables};
+
+    #[test]
+    fn minimizes_a_mixed_space() {
+        let space = SearchSpace::new(vec![
+            Dim::Continuous {
+                min: -5.0,
+                max: 5.0,
+            },
+            Dim::Continuous {
+                min: -5.0,
+                max: 5.0,
+            },
+            Dim::Discrete { choices: 4 },
+        ]);
+        let sol = particle_swarm(
+            &space,
+            None,
+            |v| (v[0] - 1.0).powi(2) + (v[1] + 2.0).powi(2) + (v[2] - 2.0).abs(),
+            PsoOptions::default(),
+        )
+        .unwrap();
+        assert!(sol.cost < 1e-3, "{sol:?}");
+        assert_eq!(sol.params[2], 2.0);
+        assert!(sol.history.windows(2).all(|w| w[1] <= w[0]));
+        assert_eq!(sol.evaluations, 30 * 101);
+    }
+
+    #[test]
+    fn shares_the_objective_with_the_genetic_algorithm() {
+        let reference = space(10.0, false);
+        let mut rs = space(6.0, false);
+        let tunables = Tunables::new(&rs, TunableOptions::default());
+        let start = tunables.current(&rs);
+        let before = mismatch(&rs, &reference);
+
+        let mut run = |pso: bool| {
+            let mut objective = |v: &[Float]| match tunables.apply(&mut rs, v) {
+                Ok(()) => mismatch(&rs, &reference),
+                Err(_) => Float::INFINITY,
+            };
+            let space = tunables.search_space();
+            if pso {
+                particle_swarm(&space, Some(&start), &mut objective, PsoOptions::default())
+            } else {
+                genetic(&space, Some(&start), &mut objective, GaOptions::default())
+            }
+            .unwrap()
+        };
+        for sol in [run(true), run(false)] {
+            assert!(sol.cost < 0.05 * before, "{} vs {before}", sol.cost);
+        }
+
+        assert_eq!(
+            particle_swarm(
+                &tunables.search_space(),
+                None,
+                |_| 0.0,
+                PsoOptions {
+                    particles: 0,
+                    ..PsoOptions::default()
+                }
+            ),
+            Err(FuzzyError::OutOfBounds)
+        );
+    }
+}
@@ -0,0 +1,201 @@
+// Per-evaluation inference settings.
+//
+// A rule space always evaluates with Min–Max operators, Mamdani clipping, max
+// aggregation and the centroid. What-if analysis ("how would Larsen implication
+// or mean of maxima change this decision?") should not need a second copy of the
+// rule space, so `evaluate_with_overrides` takes the alternatives as a bundle of optional
+// overrides and leaves the space untouched.
+use std::{borrow::Borrow, collections::HashMap, hash::Hash};
+
+use crate::{
+    aggregate::Aggregation,
+    defuzz::Defuzz,
+    error,
+    mamdani::Implication,
+    rulespace::RuleSpace,
+    sampler::{GridCache, UniformSampler},
+    Float,
+};
+
+#[cfg(feature = "ops-dyn")]
+use crate::ops::Ops;
+
+/// Inference settings replacing the defaults for one evaluation; `None` keeps
+/// the default.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Overrides {
+    /// AND/OR/NOT family for antecedents (default Min–Max).
+    #[cfg(feature = "ops-dyn")]
+    pub ops: Option<Ops>,
+    /// Default `Implication::Min`.
+    pub implication: Option<Implication>,
+    /// Default `Aggregation::Max`.
+    pub aggregation: Option<Aggregation>,
+    /// Default `Defuzz::Centroid`.
+    pub defuzz: Option<Defuzz>,
+}
+
+impl RuleSpace {
+    /// Evaluates `input` like `evaluate`, with the settings in `overrides`.
+    pub fn evaluate_with_overrides<KI>(
+        &self,
+        input: &HashMap<KI, Float>,
+        sampler: &UniformSampler,
+        overrides: &Overrides,
+    ) -> error::Result<HashMap<String, Float>>
+    where
+        KI: Eq + Hash + Borrow<str>,
+    {
+        let mut grids = GridCache::new();
+        let table = self.grid_table(sampler, &mut grids)?;
+        let mut inputs = Vec::new();
+        self.resolve_inputs_into(input, &mut inputs);
+        let mut agg = Vec::new();
+        self.reset_agg(&table, &mut agg);
+        let mut scratch = Vec::new();
+
+        if let Some(c) = &self.counters {
+            c.evaluation();
+        }
+        let implication = overrides.implication.unwrap_or_default();
+        let aggregation = overrides.aggregation.unwrap_or_default();
+        for (i, (rule, compiled)) in self.rules.iter().zip(&self.compiled).enumerate() {
+            #[cfg(feature = "ops-dyn")]
+            let alpha = match &overrides.ops {
+                Some(ops) => compiled.activation_with(rule, &inputs, &self.vars, &self.names, ops),
+                None => compiled.activation(rule, &inputs, &self.vars, &self.names),
+            }?;
+            #[cfg(not(feature = "ops-dyn"))]
+            let alpha = compiled.activation(rule, &inputs, &self.vars, &self.names)?;
+            if let Some(c) = &self.counters {
+                c.rule(i, alpha);
+            }
+            compiled.implicate_into_with(
+                alpha,
+                &self.vars,
+                &table,
+                &mut agg,
+                &mut scratch,
+                |_| true,
+                |alpha, mu| implication.apply(alpha, mu),
+                |a, b| aggregation.apply(a, b),
+            );
+            compiled.accumulate_crisp(alpha, &inputs, &self.names, &mut agg, |_| true)?;
+        }
+
+        let defuzz = overrides.defuzz.unwrap_or_default();
+        Ok(self
+            .outputs
+            .iter()
+            .map(|&v| {
+                let i = v.index();
+                (
+                    self.var_name(v).to_string(),
+                    if self.crisp[i] {
+                        self.output_value(v, table[i], &agg[i])
+                    } else {
+                        defuzz.apply(table[i], &agg[i])
+                    },
+                )
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::Overrides;
+    use crate::aggregate::Aggregation;
+    use crate::defuzz::Defuzz;
+    use crate::mamdani::Implication;
+    use crate::prelude::*;
+
+    fn space() -> RuleSpace {
+        let mut temp = Variable::new(0.0, 10.0).unwrap();
+        for (name, c) in [("cold", 0.0), ("hot", 10.0)] {
+            temp.insert_term(
+                name,
+                Term::new(name, Triangular::new(c - 10.0, c, c + 10.0).unwrap()),
+            )
+            .unwrap();
+        }
+        let mut fan = Variable::new(0.0, 100.0).unwrap();
+        for (name, c) in [("low", 0.0), ("high", 100.0)] {
+            fan.insert_term(
+                name,
+                Term::new(name, Triangular::new(c - 50.0, c, c + 50.0).unwrap()),
+            )
+            .unwrap();
+        }
+        let rule = |a: Antecedent, f: &'static str| {
+            Rule::builder().when(a).then("fan", f).build().unwrap()
+        };
+        let rules = vec![
+            rule(atom("temp", "cold"), "low"),
+            rule(atom("temp", "hot").and(not(atom("temp", "cold"))), "high"),
+        ];
+        let vars = HashMap::from([("temp".to_string(), temp), ("fan".to_string(), fan)]);
+        RuleSpace::new(vars, rules).unwrap()
+    }
+
+    #[test]
+    fn overrides_change_only_what_they_name() {
+        let rs = space();
+        let sampler = UniformSampler::default();
+        let input = HashMap::from([("temp", 7.0)]);
+        let fan = |o: Overrides| rs.evaluate_with_overrides(&input, &sampler, &o).unwrap()["fan"];
+
+        let default = rs.evaluate(&input, &sampler).unwrap()["fan"];
+        assert!((fan(Overrides::default()) - default).abs() < 1e-9);
+
+        // hot = 0.7 wins: the maxima sit on the clipped plateau of `high`.
+        let mom = fan(Overrides {
+            defuzz: Some(Defuzz::MeanOfMaxima),
+            ..Default::default()
+        });
+        assert!((mom - 92.5).abs() < 1.0, "{mom}");
+        let som = fan(Overrides {
+            defuzz: Some(Defuzz::SmallestOfMaxima),
+            ..Default::default()
+        });
+        assert!(som < mom);
+
+        for o in [
+            Overrides {
+                implication: Some(Implication::Product),
+                ..Default::default()
+            },
+            Overrides {
+                aggregation: Some(Aggregation::BoundedSum),
+                defuzz: Some(Defuzz::Bisector),
+                ..Default::default()
+            },
+        ] {
+            let y = fan(o);
+            assert!(y.is_finite() && (y - default).abs() > 1e-3, "{o:?}: {y}");
+        }
+    }
+
+    #[cfg(feature = "ops-dyn")]
+    #[test]
+    fn ops_override_changes_firing_strengths() {
+        use crate::ops::Ops;
+
+        let rs = space();
+        let sampler = UniformSampler::default();
+        let input = HashMap::from([("temp", 7.0)]);
+        let fan = |ops| {
+            let o = Overrides {
+                ops: Some(ops),
+                ..Default::default()
+            };
+            rs.evaluate_with_overrides(&input, &sampler, &o).unwrap()["fan"]
+        };
+        // hot AND NOT cold = min(0.7, 0.7) by default, 0.7 * 0.7 with products.
+        let default = rs.evaluate(&input, &sampler).unwrap()["fan"];
+        assert!((fan(Ops::MinMax) - default).abs() < 1e-9);
+        assert!(fan(Ops::Product) < default);
+    }
+}
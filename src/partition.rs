@@ -0,0 +1,125 @@
+// Data-driven default partitions.
+//
+// `quantile_variable` places one term apex on each of a set of evenly spaced
+// quantiles of a data column (p0, p25, p50, p75, p100 for five terms), so dense
+// regions of the data get narrow terms and sparse regions wide ones. Neighbouring
+// terms overlap: triangles reach their neighbours' apexes, and Gaussians cross
+// at membership 0.5 halfway between apexes.
+use crate::{
+    cluster::{triangle, TermShape},
+    error::{FuzzyError, Result},
+    membership::{Gaussian, Triangular},
+    term::Term,
+    variable::Variable,
+    Float,
+};
+
+/// Quantile `q` in `[0, 1]` of ascending `sorted`, interpolating linearly
+/// between order statistics.
+pub fn quantile(sorted: &[Float], q: Float) -> Float {
+    let pos = q.clamp(0.0, 1.0) * (sorted.len() - 1) as Float;
+    let (lo, frac) = (pos.floor() as usize, pos.fract());
+    match sorted.get(lo + 1) {
+        Some(&hi) if frac > 0.0 => sorted[lo] + frac * (hi - sorted[lo]),
+        _ => sorted[lo],
+    }
+}
+
+/// Builds a variable over `domain` with one term per name, apexes on evenly
+/// spaced quantiles of `data` from p0 to p100.
+///
+/// Fails with `EmptyInput` for empty data, `BadArity` for fewer than two names,
+/// `TypeMismatch` for non-finite data, and `OutOfBounds` when the data has too
+/// few distinct values to separate the quantiles.
+pub fn quantile_variable(
+    data: &[Float],
+    shape: TermShape,
+    names: &[&str],
+    domain: (Float, Float),
+) -> Result<Variable> {
+    if data.is_empty() {
+        return Err(FuzzyError::EmptyInput);
+    }
+    if names.len() < 2 {
+        return Err(FuzzyError::BadArity);
+    }
+    if data.iter().any(|x| !x.is_finite()) {
+        return Err(FuzzyError::TypeMismatch);
+    }
+    let mut sorted = data.to_vec();
+    sorted.sort_by(Float::total_cmp);
+    let last = (names.len() - 1) as Float;
+    let apex: Vec<Float> = (0..names.len())
+        .map(|i| quantile(&sorted, i as Float / last))
+        .collect();
+    if apex.windows(2).any(|w| w[0] >= w[1]) {
+        return Err(FuzzyError::OutOfBounds);
+    }
+
+    // A Gaussian with this sd falls to 0.5 at distance `gap / 2`.
+    let half_width = 2.0 * (2.0 * std::f64::consts::LN_2 as Float).sqrt();
+    let mut var = Variable::new(domain.0, domain.1)?;
+    for (slot, name) in names.iter().enumerate() {
+        let (left, c, right) = triangle(&apex, slot);
+        let term = match shape {
+            TermShape::Triangular => Term::new(*name, Triangular::new(left, c, right)?),
+            TermShape::Gaussian => Term::new(
+                *name,
+                Gaussian::with_mean_sd(c, (right - left) / 2.0 / half_width)?,
+            ),
+        };
+        var.insert_term(name, term)?;
+    }
+    Ok(var)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::membership::MembershipFn;
+
+    #[test]
+    fn apexes_follow_the_quantiles() {
+        // Skewed data: dense near zero, sparse above.
+        let data: Vec<Float> = (0..=100).map(|i| (i as Float / 10.0).powi(2)).collect();
+        let mut sorted = data.clone();
+        sorted.sort_by(Float::total_cmp);
+        assert_eq!(quantile(&sorted, 0.5), 25.0);
+        assert_eq!(quantile(&[1.0, 3.0], 0.25), 1.5);
+
+        let names = ["xs", "s", "m", "l", "xl"];
+        let var = quantile_variable(&data, TermShape::Triangular, &names, (0.0, 100.0)).unwrap();
+        let apex: Vec<Float> = names
+            .iter()
+            .map(|n| var.get(n).unwrap().params()[1])
+            .collect();
+        assert_eq!(apex, vec![0.0, 6.25, 25.0, 56.25, 100.0]);
+        // Neighbouring triangles form a partition of unity between apexes.
+        let x = 40.0;
+        let sum = var.eval("m", x).unwrap() + var.eval("l", x).unwrap();
+        assert!((sum - 1.0).abs() < 1e-6);
+
+        let gauss = quantile_variable(&data, TermShape::Gaussian, &names, (0.0, 100.0)).unwrap();
+        let mid = (25.0 + 56.25) / 2.0;
+        let m = gauss.eval("m", mid).unwrap();
+        assert!(m > 0.3 && m < 0.7, "{m}");
+    }
+
+    #[test]
+    fn degenerate_data_is_rejected() {
+        let names = ["lo", "hi"];
+        let d = (0.0, 1.0);
+        assert!(matches!(
+            quantile_variable(&[], TermShape::Triangular, &names, d),
+            Err(FuzzyError::EmptyInput)
+        ));
+        assert!(matches!(
+            quantile_variable(&[0.5; 4], TermShape::Triangular, &names, d),
+            Err(FuzzyError::OutOfBounds)
+        ));
+        assert!(matches!(
+            quantile_variable(&[0.5], TermShape::Triangular, &names[..1], d),
+            Err(FuzzyError::BadArity)
+        ));
+    }
+}
@@ -0,0 +1,358 @@
+// Piecewise-linear extraction of the input→output mapping.
+//
+// With triangular and trapezoidal input terms and the Min–Max operators, every
+// firing strength is piecewise linear in the inputs: it can only bend at a term
+// corner or where two degrees it compares cross. `RuleSpace::piecewise_linear`
+// collects those breakpoints along one or two swept inputs, evaluates the
+// system there and, since defuzzification may still curve between them,
+// refines every interval whose midpoint strays from the linear interpolant.
+// Zero-order Sugeno rule bases over a partition of unity come out exact on the
+// initial breakpoints; Mamdani ones are approximated to the requested
+// tolerance. The result is a `Surface` on non-uniform axes, ready for
+// verification or for generating lookup tables.
+use std::{borrow::Borrow, collections::HashMap, hash::Hash};
+
+use crate::{
+    error::{self, FuzzyError, MissingSpace},
+    membership::{MembershipFn, MembershipKind},
+    rulespace::RuleSpace,
+    sampler::UniformSampler,
+    surface::{Axis, Surface},
+    variable::Variable,
+    Float,
+};
+
+/// Axis intervals narrower than this fraction of the domain are not split.
+const MIN_WIDTH: Float = 1.0 / (1u32 << 20) as Float;
+
+/// Refinement passes before giving up on reaching the tolerance.
+const MAX_PASSES: usize = 24;
+
+/// A piecewise-linear model of a rule space over one or two inputs.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PiecewiseLinear {
+    /// Breakpoints of the swept inputs and the exact outputs at every one of
+    /// them; `Surface::interpolate` evaluates the model between them.
+    pub surface: Surface,
+    /// Largest deviation of the system from the model found at the probed
+    /// interval midpoints (and cell centres for two inputs).
+    pub max_error: Float,
+}
+
+impl RuleSpace {
+    /// Extracts the piecewise-linear mapping from input `x` (and optionally
+    /// `y`) to every output, holding the other inputs at `fixed`.
+    ///
+    /// Breakpoints start at the corners of the swept variables' terms and the
+    /// crossings between their degrees (and the degrees of fixed inputs);
+    /// intervals are then halved until each deviates by at most `tolerance`.
+    /// Outputs that are undefined (no rule fired) are NaN and not refined.
+    ///
+    /// Fails with `TypeMismatch` if a swept variable has a term that is not
+    /// triangular or trapezoidal, `OutOfBounds` for a non-positive tolerance
+    /// and like `surface` otherwise.
+    pub fn piecewise_linear<KI>(
+        &self,
+        x: &str,
+        y: Option<&str>,
+        fixed: &HashMap<KI, Float>,
+        sampler: &UniformSampler,
+        tolerance: Float,
+    ) -> error::Result<PiecewiseLinear>
+    where
+        KI: Eq + Hash + Borrow<str>,
+    {
+        if y == Some(x) {
+            return Err(FuzzyError::BadArity);
+        }
+        if !(tolerance.is_finite() && tolerance > 0.0) {
+            return Err(FuzzyError::OutOfBounds);
+        }
+        let mut levels = Vec::new();
+        for (name, &value) in fixed {
+            if let Some(var) = self.var(name.borrow()) {
+                levels.extend(var.terms().filter_map(|(t, _)| var.eval(t, value).ok()));
+            }
+        }
+        let axis = |name: &str| -> error::Result<(Axis, Float)> {
+            let var = self.var(name).ok_or_else(|| FuzzyError::NotFound {
+                space: MissingSpace::Var,
+                key: name.to_string(),
+            })?;
+            let (min, max) = var.domain();
+            let axis = Axis {
+                name: name.to_string(),
+                values: breakpoints(var, &levels)?,
+            };
+            Ok((axis, (max - min) * MIN_WIDTH))
+        };
+        let (mut ax, min_x) = axis(x)?;
+        let mut ay = y.map(axis).transpose()?;
+
+        let mut input: HashMap<&str, Float> = fixed.iter().map(|(k, &v)| (k.borrow(), v)).collect();
+        let mut scratch = self.scratch(sampler)?;
+        let names: Vec<String> = self.outputs().map(str::to_string).collect();
+        let mut probe = |px: Float, py: Option<Float>| -> error::Result<Vec<Float>> {
+            input.insert(x, px);
+            if let (Some(y), Some(py)) = (y, py) {
+                input.insert(y, py);
+            }
+            let out = self.evaluate_in(&input, &mut scratch)?;
+            Ok(names.iter().map(|n| out[n.as_str()]).collect())
+        };
+
+        for pass in 0..=MAX_PASSES {
+            let ys: Vec<Option<Float>> = match &ay {
+                Some((a, _)) => a.values.iter().map(|&v| Some(v)).collect(),
+                None => vec![None],
+            };
+            let nx = ax.values.len();
+            let mut grid = Vec::with_capacity(nx * ys.len());
+            for &py in &ys {
+                for &px in &ax.values {
+                    grid.push(probe(px, py)?);
+                }
+            }
+
+            let mut ex = vec![0.0; nx - 1];
+            let mut ey = vec![0.0; ys.len().saturating_sub(1)];
+            for (j, &py) in ys.iter().enumerate() {
+                for i in 0..nx - 1 {
+                    let mid = probe(midpoint(&ax.values, i), py)?;
+                    let e = deviation(&mid, &[&grid[j * nx + i], &grid[j * nx + i + 1]]);
+                    ex[i] = Float::max(ex[i], e);
+                }
+            }
+            if let Some((a, _)) = &ay {
+                for j in 0..ys.len() - 1 {
+                    let my = Some(midpoint(&a.values, j));
+                    for (i, &px) in ax.values.iter().enumerate() {
+                        let mid = probe(px, my)?;
+                        let e = deviation(&mid, &[&grid[j * nx + i], &grid[(j + 1) * nx + i]]);
+                        ey[j] = Float::max(ey[j], e);
+                    }
+                    for i in 0..nx - 1 {
+                        let mid = probe(midpoint(&ax.values, i), my)?;
+                        let corners = [
+                            &grid[j * nx + i],
+                            &grid[j * nx + i + 1],
+                            &grid[(j + 1) * nx + i],
+                            &grid[(j + 1) * nx + i + 1],
+                        ];
+                        let e = deviation(&mid, &corners);
+                        ex[i] = Float::max(ex[i], e);
+                        ey[j] = Float::max(ey[j], e);
+                    }
+                }
+            }
+
+            let max_error = ex.iter().chain(&ey).fold(0.0, |m: Float, &e| m.max(e));
+            let split = pass < MAX_PASSES && {
+                let split_x = refine(&mut ax.values, &ex, tolerance, min_x);
+                let split_y = match &mut ay {
+                    Some((a, min_y)) => refine(&mut a.values, &ey, tolerance, *min_y),
+                    None => false,
+                };
+                split_x || split_y
+            };
+            if !split {
+                let outputs = names
+                    .iter()
+                    .enumerate()
+                    .map(|(o, n)| (n.clone(), grid.iter().map(|v| v[o]).collect()))
+                    .collect();
+                return Ok(PiecewiseLinear {
+                    surface: Surface {
+                        x: ax,
+                        y: ay.map(|(a, _)| a),
+                        outputs,
+                    },
+                    max_error,
+                });
+            }
+        }
+        unreachable!("the last pass returns")
+    }
+}
+
+/// Corners of `var`'s terms and the crossings of their degrees with each other,
+/// with their complements and with `levels`, sorted, within the domain.
+fn breakpoints(var: &Variable, levels: &[Float]) -> error::Result<Vec<Float>> {
+    let (min, max) = var.domain();
+    let mut points = vec![min, max];
+    for (_, term) in var.terms() {
+        match term.kind() {
+            MembershipKind::Triangular(_) | MembershipKind::Trapezoidal(_) => {}
+            _ => return Err(FuzzyError::TypeMismatch),
+        }
+        points.extend(term.params().into_iter().filter(|p| min < *p && *p < max));
+    }
+    sort_dedup(&mut points);
+
+    let mut crossings = Vec::new();
+    for w in points.windows(2) {
+        let (a, b) = (w[0], w[1]);
+        // Every degree is linear on [a, b]; compare them by their end values.
+        let mut lines: Vec<(Float, Float)> =
+            var.terms().map(|(_, t)| (t.eval(a), t.eval(b))).collect();
+        lines.extend(lines.clone().iter().map(|&(p, q)| (1.0 - p, 1.0 - q)));
+        lines.extend(levels.iter().flat_map(|&c| [(c, c), (1.0 - c, 1.0 - c)]));
+        for (k, &(p0, p1)) in lines.iter().enumerate() {
+            for &(q0, q1) in &lines[k + 1..] {
+                let (d0, d1) = (p0 - q0, p1 - q1);
+                if d0 * d1 < 0.0 {
+                    crossings.push(a + d0 / (d0 - d1) * (b - a));
+                }
+            }
+        }
+    }
+    points.extend(crossings);
+    sort_dedup(&mut points);
+    Ok(points)
+}
+
+fn sort_dedup(points: &mut Vec<Float>) {
+    points.sort_by(Float::total_cmp);
+    points.dedup_by(|a, b| (*a - *b).abs() <= Float::EPSILON * b.abs().max(1.0));
+}
+
+fn midpoint(axis: &[Float], i: usize) -> Float {
+    0.5 * (axis[i] + axis[i + 1])
+}
+
+/// Largest difference over outputs between `mid` and the mean of `corners`;
+/// NaN outputs are skipped.
+fn deviation(mid: &[Float], corners: &[&Vec<Float>]) -> Float {
+    let mut worst: Float = 0.0;
+    for (o, &m) in mid.iter().enumerate() {
+        let mean = corners.iter().map(|c| c[o]).sum::<Float>() / corners.len() as Float;
+        let e = (m - mean).abs();
+        if e.is_finite() {
+            worst = worst.max(e);
+        }
+    }
+    worst
+}
+
+/// Inserts the midpoint of every interval whose error exceeds `tolerance` and
+/// that is wider than `min_width`; returns whether any was split.
+fn refine(axis: &mut Vec<Float>, errors: &[Float], tolerance: Float, min_width: Float) -> bool {
+    let mids: Vec<Float> = errors
+        .iter()
+        .enumerate()
+        .filter(|&(i, &e)| e > tolerance && axis[i + 1] - axis[i] > min_width)
+        .map(|(i, _)| midpoint(axis, i))
+        .collect();
+    if mids.is_empty() {
+        return false;
+    }
+    axis.extend(mids);
+    axis.sort_by(Float::total_cmp);
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::error::FuzzyError;
+    use crate::prelude::*;
+
+    const SUGENO: &str = "
+        var temp 0 40
+          cold: triangle(-20, 0, 20)
+          warm: triangle(0, 20, 40)
+          hot: triangle(20, 40, 60)
+        var load 0 1
+          low: trapezoid(-1, 0, 0.3, 0.7)
+          high: trapezoid(0.3, 0.7, 1, 2)
+        var power 0 100
+
+        IF temp IS cold THEN power = 10
+        IF temp IS warm THEN power = 40
+        IF temp IS hot AND load IS low THEN power = 70
+        IF temp IS hot AND load IS high THEN power = 100
+    ";
+
+    #[test]
+    fn sugeno_partition_is_exact_on_its_corners() {
+        let rs: RuleSpace = SUGENO.parse().unwrap();
+        let sampler = UniformSampler::default();
+        let fixed = HashMap::from([("load", 0.2)]);
+        let pl = rs
+            .piecewise_linear("temp", None, &fixed, &sampler, 1e-3)
+            .unwrap();
+        // Corners at 0, 20 and 40; neighbouring degrees cross at 10 and 30.
+        assert_eq!(pl.surface.x.values, [0.0, 10.0, 20.0, 30.0, 40.0]);
+        assert!(pl.max_error < 1e-3);
+        for t in [3.0, 17.5, 26.0, 39.0] {
+            let want = rs
+                .evaluate(&HashMap::from([("temp", t), ("load", 0.2)]), &sampler)
+                .unwrap()["power"];
+            let got = pl.surface.interpolate("power", t, 0.0).unwrap();
+            assert!((got - want).abs() < 1e-3, "{t}: {got} {want}");
+        }
+    }
+
+    #[test]
+    fn two_inputs_are_refined_to_the_tolerance() {
+        let rs: RuleSpace = SUGENO.parse().unwrap();
+        let sampler = UniformSampler::default();
+        let none: HashMap<&str, Float> = HashMap::new();
+        let pl = rs
+            .piecewise_linear("temp", Some("load"), &none, &sampler, 0.25)
+            .unwrap();
+        let load = &pl.surface.y.as_ref().unwrap().values;
+        assert!(load.contains(&0.3) && load.contains(&0.7));
+        assert!(pl.max_error <= 0.25);
+        for t in [5.0, 21.0, 33.0] {
+            for l in [0.1, 0.45, 0.9] {
+                let want = rs
+                    .evaluate(&HashMap::from([("temp", t), ("load", l)]), &sampler)
+                    .unwrap()["power"];
+                let got = pl.surface.interpolate("power", t, l).unwrap();
+                assert!((got - want).abs() < 1.0, "{t} {l}: {got} {want}");
+            }
+        }
+
+        assert!(matches!(
+            rs.piecewise_linear("temp", Some("temp"), &none, &sampler, 0.1),
+            Err(FuzzyError::BadArity)
+        ));
+        assert!(matches!(
+            rs.piecewise_linear("temp", None, &none, &sampler, 0.0),
+            Err(FuzzyError::OutOfBounds)
+        ));
+        let gauss: RuleSpace = "var a 0 1\n g: gaussian(0.5, 0.1)\nvar b 0 1\nIF a IS g THEN b = 1"
+            .parse()
+            .unwrap();
+        assert!(matches!(
+            gauss.piecewise_linear("a", None, &none, &sampler, 0.1),
+            Err(FuzzyError::TypeMismatch)
+        ));
+    }
+
+    #[test]
+    fn mamdani_output_is_approximated() {
+        let rs: RuleSpace = "
+            var temp 0 40
+              cold: triangle(-40, 0, 40)
+              hot: triangle(0, 40, 80)
+            var fan 0 100
+              low: triangle(-100, 0, 100)
+              high: triangle(0, 100, 200)
+            IF temp IS cold THEN fan IS low
+            IF temp IS hot THEN fan IS high
+        "
+        .parse()
+        .unwrap();
+        let sampler = UniformSampler::default();
+        let none: HashMap<&str, Float> = HashMap::new();
+        let pl = rs
+            .piecewise_linear("temp", None, &none, &sampler, 0.1)
+            .unwrap();
+        // The centroid curves between the corners, so refinement adds points.
+        assert!(pl.surface.x.values.len() > 3);
+        assert!(pl.max_error <= 0.1);
+    }
+}
@@ -0,0 +1,317 @@
+// Composition of rule spaces and plain functions into a feed-forward pipeline.
+//
+// Blocks are added in evaluation order and exchange values through named
+// signals: a block reads signals by name and writes its outputs under their own
+// names. A signal no earlier block produces is an external input of the
+// pipeline. Wiring is checked as blocks are added, so `run` only fails on bad
+// input values or a function returning the wrong number of outputs.
+use std::{
+    borrow::Borrow,
+    collections::{HashMap, HashSet},
+    hash::Hash,
+};
+
+use crate::{
+    error::{self, FuzzyError, MissingSpace},
+    rulespace::{RuleSpace, Scratch},
+    sampler::UniformSampler,
+    Float,
+};
+
+type BlockFn = Box<dyn Fn(&[Float]) -> Vec<Float> + Send + Sync>;
+
+enum Kind {
+    Space {
+        space: Box<RuleSpace>,
+        scratch: Scratch,
+    },
+    Func(BlockFn),
+}
+
+struct Block {
+    name: String,
+    inputs: Vec<String>,
+    outputs: Vec<String>,
+    kind: Kind,
+}
+
+/// A directed chain of rule spaces and functions with a single `run` entry point.
+pub struct Pipeline {
+    sampler: UniformSampler,
+    blocks: Vec<Block>,
+    external: Vec<String>,
+    /// Domain of every signal produced by a rule space block.
+    domains: HashMap<String, (Float, Float)>,
+    produced: HashSet<String>,
+    signals: HashMap<String, Float>,
+}
+
+impl Pipeline {
+    /// An empty pipeline whose rule spaces are evaluated with `sampler`.
+    pub fn new(sampler: &UniformSampler) -> Self {
+        Self {
+            sampler: sampler.clone(),
+            blocks: Vec::new(),
+            external: Vec::new(),
+            domains: HashMap::new(),
+            produced: HashSet::new(),
+            signals: HashMap::new(),
+        }
+    }
+
+    /// Appends a rule space reading its input variables and producing its outputs.
+    ///
+    /// An input fed by an earlier rule space must have a domain covering that
+    /// producer's output domain, otherwise this fails with `OutOfBounds`.
+    pub fn with_space(mut self, name: &str, space: RuleSpace) -> error::Result<Self> {
+        let inputs: Vec<String> = space.inputs().map(str::to_string).collect();
+        let outputs: Vec<String> = space.outputs().map(str::to_string).collect();
+        for input in &inputs {
+            let (min, max) = space.var(input).expect("inputs are variables").domain();
+            if let Some(&(lo, hi)) = self.domains.get(input) {
+                if lo < min || hi > max {
+                    return Err(FuzzyError::OutOfBounds);
+                }
+            }
+        }
+        self.check(&inputs, &outputs)?;
+        for output in &outputs {
+            let domain = space.var(output).expect("outputs are variables").domain();
+            self.domains.insert(output.clone(), domain);
+        }
+        let scratch = space.scratch(&self.sampler)?;
+        self.push(
+            name,
+            inputs,
+            outputs,
+            Kind::Space {
+                space: Box::new(space),
+                scratch,
+            },
+        );
+        Ok(self)
+    }
+
+    /// Appends a function from the `inputs` signals, in order, to the `outputs`
+    /// signals. `run` fails with `BadArity` if `f` returns the wrong number of values.
+    pub fn with_fn<F>(
+        mut self,
+        name: &str,
+        inputs: &[&str],
+        outputs: &[&str],
+        f: F,
+    ) -> error::Result<Self>
+    where
+        F: Fn(&[Float]) -> Vec<Float> + Send + Sync + 'static,
+    {
+        let inputs: Vec<String> = inputs.iter().map(|s| s.to_string()).collect();
+        let outputs: Vec<String> = outputs.iter().map(|s| s.to_string()).collect();
+        self.check(&inputs, &outputs)?;
+        self.push(name, inputs, outputs, Kind::Func(Box::new(f)));
+        Ok(self)
+    }
+
+    /// Rejects a block that writes a signal that is already produced or already
+    /// read as an external input (`TypeMismatch`), or that has no outputs
+    /// (`EmptyInput`).
+    fn check(&self, inputs: &[String], outputs: &[String]) -> error::Result<()> {
+        if outputs.is_empty() {
+            return Err(FuzzyError::EmptyInput);
+        }
+        let mut seen = HashSet::new();
+        for output in outputs {
+            if !seen.insert(output)
+                || self.produced.contains(output)
+                || self.external.contains(output)
+                || inputs.contains(output)
+            {
+                return Err(FuzzyError::TypeMismatch);
+            }
+        }
+        Ok(())
+    }
+
+    fn push(&mut self, name: &str, inputs: Vec<String>, outputs: Vec<String>, kind: Kind) {
+        for input in &inputs {
+            if !self.produced.contains(input) && !self.external.contains(input) {
+                self.external.push(input.clone());
+            }
+        }
+        self.produced.extend(outputs.iter().cloned());
+        self.blocks.push(Block {
+            name: name.to_string(),
+            inputs,
+            outputs,
+            kind,
+        });
+    }
+
+    /// Signals that must be supplied to `run`, in order of first use.
+    pub fn inputs(&self) -> impl Iterator<Item = &str> {
+        self.external.iter().map(String::as_str)
+    }
+
+    /// Signals produced by the blocks, in block order.
+    pub fn outputs(&self) -> impl Iterator<Item = &str> {
+        self.blocks
+            .iter()
+            .flat_map(|b| b.outputs.iter().map(String::as_str))
+    }
+
+    /// Block names in evaluation order.
+    pub fn blocks(&self) -> impl Iterator<Item = &str> {
+        self.blocks.iter().map(|b| b.name.as_str())
+    }
+
+    /// Runs every block in order and returns all signals, external and produced.
+    ///
+    /// A missing external input fails with `NotFound`. An undefined (NaN) value
+    /// reaching a rule space makes that block's outputs undefined instead of
+    /// being evaluated.
+    pub fn run<KI>(&mut self, inputs: &HashMap<KI, Float>) -> error::Result<&HashMap<String, Float>>
+    where
+        KI: Eq + Hash + Borrow<str>,
+    {
+        self.signals.clear();
+        for name in &self.external {
+            let x = *inputs
+                .get(name.as_str())
+                .ok_or_else(|| FuzzyError::NotFound {
+                    space: MissingSpace::Input,
+                    key: name.clone(),
+                })?;
+            self.signals.insert(name.clone(), x);
+        }
+        for block in &mut self.blocks {
+            let signals = &mut self.signals;
+            match &mut block.kind {
+                Kind::Space { space, scratch } => {
+                    let args: HashMap<&str, Float> = block
+                        .inputs
+                        .iter()
+                        .map(|n| (n.as_str(), signals[n]))
+                        .collect();
+                    if args.values().any(|x| x.is_nan()) {
+                        for output in &block.outputs {
+                            signals.insert(output.clone(), Float::NAN);
+                        }
+                        continue;
+                    }
+                    let out = space.evaluate_in(&args, scratch)?;
+                    for output in &block.outputs {
+                        signals.insert(output.clone(), out[output]);
+                    }
+                }
+                Kind::Func(f) => {
+                    let args: Vec<Float> = block.inputs.iter().map(|n| signals[n]).collect();
+                    let out = f(&args);
+                    if out.len() != block.outputs.len() {
+                        return Err(FuzzyError::BadArity);
+                    }
+                    for (output, y) in block.outputs.iter().zip(out) {
+                        signals.insert(output.clone(), y);
+                    }
+                }
+            }
+        }
+        Ok(&self.signals)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::Pipeline;
+    use crate::antecedent::Antecedent;
+    use crate::error::FuzzyError;
+    use crate::mamdani::{Consequent, Rule};
+    use crate::prelude::*;
+    use crate::rulespace::RuleSpace;
+    use crate::variable::Variable;
+
+    /// `input` in [0, 10] -> `output` over `out_domain`, low -> low, high -> high.
+    fn mono(input: &'static str, output: &'static str, out_domain: (Float, Float)) -> RuleSpace {
+        let var = |(min, max): (Float, Float)| {
+            let mut v = Variable::new(min, max).unwrap();
+            let w = max - min;
+            v.insert_term(
+                "low",
+                Term::new("low", Triangular::new(min - w, min, max).unwrap()),
+            )
+            .unwrap();
+            v.insert_term(
+                "high",
+                Term::new("high", Triangular::new(min, max, max + w).unwrap()),
+            )
+            .unwrap();
+            v
+        };
+        let rule = |t: &'static str| Rule {
+            antecedent: Antecedent::Atom {
+                var: input.into(),
+                term: t.into(),
+            },
+            consequent: vec![Consequent::Term {
+                var: output.into(),
+                term: t.into(),
+                hedge: None,
+            }],
+            weight: 1.0,
+        };
+        let vars = HashMap::from([
+            (input.to_string(), var((0.0, 10.0))),
+            (output.to_string(), var(out_domain)),
+        ]);
+        RuleSpace::new(vars, vec![rule("low"), rule("high")]).unwrap()
+    }
+
+    #[test]
+    fn blocks_feed_each_other() {
+        let sampler = UniformSampler::default();
+        let mut p = Pipeline::new(&sampler)
+            .with_fn("sum", &["a", "b"], &["x"], |v| vec![v[0] + v[1]])
+            .unwrap()
+            .with_space("stage1", mono("x", "y", (0.0, 10.0)))
+            .unwrap()
+            .with_space("stage2", mono("y", "z", (0.0, 100.0)))
+            .unwrap();
+        assert_eq!(p.inputs().collect::<Vec<_>>(), vec!["a", "b"]);
+        assert_eq!(p.outputs().collect::<Vec<_>>(), vec!["x", "y", "z"]);
+
+        let low = p.run(&HashMap::from([("a", 0.5), ("b", 0.5)])).unwrap()["z"];
+        let high = p.run(&HashMap::from([("a", 4.5), ("b", 4.5)])).unwrap()["z"];
+        assert!(low < high && high <= 100.0);
+
+        assert!(matches!(
+            p.run(&HashMap::from([("a", 1.0)])),
+            Err(FuzzyError::NotFound { .. })
+        ));
+    }
+
+    #[test]
+    fn wiring_is_validated() {
+        let sampler = UniformSampler::default();
+        // y spans [0, 100] but the next stage only accepts [0, 10].
+        let wide = Pipeline::new(&sampler)
+            .with_space("a", mono("x", "y", (0.0, 100.0)))
+            .unwrap()
+            .with_space("b", mono("y", "z", (0.0, 1.0)));
+        assert!(matches!(wide, Err(FuzzyError::OutOfBounds)));
+
+        // y is produced twice.
+        let twice = Pipeline::new(&sampler)
+            .with_fn("f", &["x"], &["y"], |v| v.to_vec())
+            .unwrap()
+            .with_fn("g", &["x"], &["y"], |v| v.to_vec());
+        assert!(matches!(twice, Err(FuzzyError::TypeMismatch)));
+
+        let mut arity = Pipeline::new(&sampler)
+            .with_fn("f", &["x"], &["y", "z"], |v| v.to_vec())
+            .unwrap();
+        assert_eq!(
+            arity.run(&HashMap::from([("x", 1.0)])).err(),
+            Some(FuzzyError::BadArity)
+        );
+    }
+}
@@ -0,0 +1,276 @@
+// Strict versus lenient handling of recoverable evaluation problems.
+//
+// By default evaluation fails on a missing or out-of-domain input and reports an
+// output no rule fired into as NaN. A `Policy` chooses, per kind of problem,
+// between failing and a documented fallback, so batch jobs can keep going past
+// one bad row while control loops stay strict.
+use std::{borrow::Borrow, collections::HashMap, hash::Hash};
+
+use crate::{
+    error::{self, FuzzyError, MissingSpace},
+    rulespace::RuleSpace,
+    sampler::UniformSampler,
+    Float,
+};
+
+/// What to do with an input that is absent or NaN.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OnMissing {
+    /// Fail with `NotFound`.
+    Error,
+    /// Use the midpoint of the variable's domain.
+    Midpoint,
+    /// Use this value (clamped into the domain).
+    Value(Float),
+}
+
+/// What to do with an input outside its variable's domain.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OnOutOfDomain {
+    /// Fail with `OutOfBounds`.
+    Error,
+    /// Clamp to the nearest domain bound.
+    Clamp,
+}
+
+/// What to report for an output no rule fired into.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OnEmptyOutput {
+    /// Report NaN.
+    Nan,
+    /// Fail with `DivisionByZero`.
+    Error,
+    /// Report the midpoint of the output's domain.
+    Midpoint,
+    /// Report this value.
+    Value(Float),
+}
+
+/// Error policy for `RuleSpace::evaluate_with_policy`. The default matches
+/// `evaluate`, except that NaN inputs are treated as missing.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Policy {
+    pub missing_input: OnMissing,
+    pub out_of_domain: OnOutOfDomain,
+    pub empty_output: OnEmptyOutput,
+}
+
+impl Default for Policy {
+    fn default() -> Self {
+        Self {
+            missing_input: OnMissing::Error,
+            out_of_domain: OnOutOfDomain::Error,
+            empty_output: OnEmptyOutput::Nan,
+        }
+    }
+}
+
+impl Policy {
+    /// Fails on every problem, including outputs no rule fired into.
+    pub fn strict() -> Self {
+        Self {
+            empty_output: OnEmptyOutput::Error,
+            ..Self::default()
+        }
+    }
+
+    /// Never fails on bad values: missing inputs and empty outputs fall back to
+    /// the domain midpoint and out-of-domain inputs are clamped.
+    pub fn lenient() -> Self {
+        Self {
+            missing_input: OnMissing::Midpoint,
+            out_of_domain: OnOutOfDomain::Clamp,
+            empty_output: OnEmptyOutput::Midpoint,
+        }
+    }
+}
+
+impl RuleSpace {
+    /// Evaluates `input`, handling missing and out-of-domain inputs and empty
+    /// outputs as `policy` says.
+    pub fn evaluate_with_policy<KI>(
+        &self,
+        input: &HashMap<KI, Float>,
+        sampler: &UniformSampler,
+        policy: &Policy,
+    ) -> error::Result<HashMap<String, Float>>
+    where
+        KI: Eq + Hash + Borrow<str>,
+    {
+        self.evaluate_batch_with_policy(std::slice::from_ref(input), sampler, policy)
+            .map(|mut rows| rows.pop().expect("one row in, one row out"))
+    }
+
+    /// Evaluates every row under `policy`, in row order; the first failing row
+    /// aborts the batch.
+    pub fn evaluate_batch_with_policy<KI>(
+        &self,
+        inputs: &[HashMap<KI, Float>],
+        sampler: &UniformSampler,
+        policy: &Policy,
+    ) -> error::Result<Vec<HashMap<String, Float>>>
+    where
+        KI: Eq + Hash + Borrow<str>,
+    {
+        let names: Vec<&str> = self.inputs().collect();
+        let mut scratch = self.scratch(sampler)?;
+        let mut row: HashMap<&str, Float> = HashMap::with_capacity(names.len());
+        inputs
+            .iter()
+            .map(|input| {
+                row.clear();
+                for &name in &names {
+                    let (min, max) = self.var(name).expect("inputs are variables").domain();
+                    let x = match (input.get(name).copied(), policy.missing_input) {
+                        (Some(x), _) if !x.is_nan() => x,
+                        (_, OnMissing::Error) => {
+                            return Err(FuzzyError::NotFound {
+                                space: MissingSpace::Input,
+                                key: name.to_string(),
+                            })
+                        }
+                        (_, OnMissing::Midpoint) => {
+                            self.fallback();
+                            0.5 * (min + max)
+                        }
+                        (_, OnMissing::Value(v)) => {
+                            self.fallback();
+                            v.clamp(min, max)
+                        }
+                    };
+                    let x = match policy.out_of_domain {
+                        OnOutOfDomain::Clamp if x < min || x > max => {
+                            self.fallback();
+                            x.clamp(min, max)
+                        }
+                        _ => x,
+                    };
+                    row.insert(name, x);
+                }
+                let mut out = self.evaluate_in(&row, &mut scratch)?.clone();
+                for (name, y) in out.iter_mut().filter(|(_, y)| y.is_nan()) {
+                    let (min, max) = self.var(name).expect("outputs are variables").domain();
+                    *y = match policy.empty_output {
+                        OnEmptyOutput::Nan => continue,
+                        OnEmptyOutput::Error => {
+                            return Err(FuzzyError::DivisionByZero { stage: "centroid" })
+                        }
+                        OnEmptyOutput::Midpoint => 0.5 * (min + max),
+                        OnEmptyOutput::Value(v) => v,
+                    };
+                    self.fallback();
+                }
+                Ok(out)
+            })
+            .collect()
+    }
+
+    pub(crate) fn fallback(&self) {
+        if let Some(c) = &self.counters {
+            c.fallback();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::{OnEmptyOutput, Policy};
+    use crate::antecedent::Antecedent;
+    use crate::error::FuzzyError;
+    use crate::mamdani::{Consequent, Rule};
+    use crate::prelude::*;
+    use crate::rulespace::RuleSpace;
+    use crate::variable::Variable;
+
+    /// temp in [0, 10] with only a `hot` term on (5, 10], so low readings fire nothing.
+    fn space() -> RuleSpace {
+        let mut temp = Variable::new(0.0, 10.0).unwrap();
+        temp.insert_term(
+            "hot",
+            Term::new("hot", Triangular::new(5.0, 10.0, 15.0).unwrap()),
+        )
+        .unwrap();
+        let mut fan = Variable::new(0.0, 100.0).unwrap();
+        fan.insert_term(
+            "high",
+            Term::new("high", Triangular::new(50.0, 100.0, 150.0).unwrap()),
+        )
+        .unwrap();
+        let rule = Rule {
+            antecedent: Antecedent::Atom {
+                var: "temp".into(),
+                term: "hot".into(),
+            },
+            consequent: vec![Consequent::Term {
+                var: "fan".into(),
+                term: "high".into(),
+                hedge: None,
+            }],
+            weight: 1.0,
+        };
+        let vars = HashMap::from([("temp".to_string(), temp), ("fan".to_string(), fan)]);
+        RuleSpace::new(vars, vec![rule]).unwrap()
+    }
+
+    #[test]
+    fn default_policy_matches_evaluate() {
+        let sampler = UniformSampler::default();
+        let rs = space();
+        let policy = Policy::default();
+        let input = HashMap::from([("temp", 8.0)]);
+        assert_eq!(
+            rs.evaluate_with_policy(&input, &sampler, &policy).unwrap(),
+            rs.evaluate(&input, &sampler).unwrap()
+        );
+        let cold = HashMap::from([("temp", 1.0)]);
+        assert!(rs.evaluate_with_policy(&cold, &sampler, &policy).unwrap()["fan"].is_nan());
+        assert_eq!(
+            rs.evaluate_with_policy(&HashMap::from([("temp", 12.0)]), &sampler, &policy),
+            Err(FuzzyError::InRule {
+                rule: 0,
+                source: Box::new(FuzzyError::OutOfDomain {
+                    var: "temp".into(),
+                    value: 12.0
+                })
+            })
+        );
+        assert_eq!(
+            rs.evaluate_with_policy(&cold, &sampler, &Policy::strict()),
+            Err(FuzzyError::DivisionByZero { stage: "centroid" })
+        );
+    }
+
+    #[test]
+    fn lenient_policy_degrades_instead_of_failing() {
+        let sampler = UniformSampler::default();
+        let rs = space();
+        let rows = vec![
+            HashMap::from([("temp", 12.0)]),
+            HashMap::from([("temp", Float::NAN)]),
+            HashMap::new(),
+            HashMap::from([("temp", 1.0)]),
+        ];
+        let out = rs
+            .evaluate_batch_with_policy(&rows, &sampler, &Policy::lenient())
+            .unwrap();
+        let top = rs
+            .evaluate(&HashMap::from([("temp", 10.0)]), &sampler)
+            .unwrap()["fan"];
+        assert_eq!(out[0]["fan"], top);
+        // Missing readings use the midpoint 5, where `hot` does not fire yet.
+        assert_eq!(out[1]["fan"], 50.0);
+        assert_eq!(out[2]["fan"], 50.0);
+        assert_eq!(out[3]["fan"], 50.0);
+
+        let fixed = Policy {
+            empty_output: OnEmptyOutput::Value(0.0),
+            ..Policy::lenient()
+        };
+        assert_eq!(
+            rs.evaluate_with_policy(&rows[3], &sampler, &fixed).unwrap()["fan"],
+            0.0
+        );
+    }
+}
@@ -11,19 +11,29 @@
 //! `pub mod prelude;` to make it available to users.
 
 // Core scalar and error types
-pub use crate::error::{FuzzyError, Result};
+pub use crate::error::{FuzzyError, MissingSpace, Result};
 pub use crate::Float;
 
 // Membership trait and built-in shapes
 pub use crate::membership::trapezoidal::Trapezoidal;
 pub use crate::membership::MembershipFn;
-pub use crate::membership::{Gaussian, Triangular};
+pub use crate::membership::{parse_mf, Gaussian, MembershipKind, MfExpr, Rectangular, Triangular};
 
 // Fuzzy Set Operands
-pub use crate::ops::FuzzyOps;
+pub use crate::ops::{FuzzyOps, Mean};
 
 // Term wrapper around a boxed membership function
 pub use crate::term::Term;
 
 //UniformSampling Functionality
 pub use crate::sampler::{Sampler, UniformSampler};
+
+// Variables, rules and the inference engine
+pub use crate::aggregate::aggregation;
+pub use crate::antecedent::{atom, mean, not, Antecedent, Name};
+pub use crate::defuzz::defuzzification;
+pub use crate::hedges::Hedge;
+pub use crate::mamdani::{Consequent, Rule, RuleBuilder};
+pub use crate::rulespace::{RuleSpace, Scratch};
+pub use crate::system::System;
+pub use crate::variable::Variable;
@@ -0,0 +1,197 @@
+// Rule pruning by firing statistics.
+//
+// Runs a dataset through the rule base, recording how strongly each rule fires,
+// so rules that never contribute (common in learned or combinatorially generated
+// rule bases) can be listed for review or removed outright.
+use std::{borrow::Borrow, collections::HashMap, hash::Hash};
+
+use crate::{
+    error::{self, FuzzyError},
+    mamdani::Rule,
+    rulespace::RuleSpace,
+    Float,
+};
+
+/// Firing strength of one rule over a dataset.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FiringStats {
+    pub max: Float,
+    pub mean: Float,
+}
+
+impl RuleSpace {
+    /// Firing strength of every rule for `input`, in rule order.
+    pub fn firing_strengths<KI>(&self, input: &HashMap<KI, Float>) -> error::Result<Vec<Float>>
+    where
+        KI: Eq + Hash + Borrow<str>,
+    {
+        let mut inputs = Vec::with_capacity(self.vars.len());
+        self.resolve_inputs_into(input, &mut inputs);
+        self.rules
+            .iter()
+            .zip(&self.compiled)
+            .map(|(rule, c)| c.activation(rule, &inputs, &self.vars, &self.names))
+            .collect()
+    }
+
+    /// Maximum and mean firing strength of every rule over `data`, in rule order.
+    pub fn firing_stats<KI>(&self, data: &[HashMap<KI, Float>]) -> error::Result<Vec<FiringStats>>
+    where
+        KI: Eq + Hash + Borrow<str>,
+    {
+        if data.is_empty() {
+            return Err(FuzzyError::EmptyInput);
+        }
+        let mut stats = vec![
+            FiringStats {
+                max: 0.0,
+                mean: 0.0
+            };
+            self.rules.len()
+        ];
+        for row in data {
+            for (s, alpha) in stats.iter_mut().zip(self.firing_strengths(row)?) {
+                s.max = s.max.max(alpha);
+                s.mean += alpha;
+            }
+        }
+        for s in &mut stats {
+            s.mean /= data.len() as Float;
+        }
+        Ok(stats)
+    }
+
+    /// Indices of the rules whose maximum firing strength over `data` never
+    /// exceeds `threshold`: candidates for removal.
+    pub fn dormant_rules<KI>(
+        &self,
+        data: &[HashMap<KI, Float>],
+        threshold: Float,
+    ) -> error::Result<Vec<usize>>
+    where
+        KI: Eq + Hash + Borrow<str>,
+    {
+        Ok(self
+            .firing_stats(data)?
+            .iter()
+            .enumerate()
+            .filter(|(_, s)| s.max <= threshold)
+            .map(|(i, _)| i)
+            .collect())
+    }
+
+    /// Removes the rules `dormant_rules` reports and returns them in order.
+    ///
+    /// Fails with `EmptyInput`, leaving the rule space unchanged, if every rule
+    /// would be removed.
+    pub fn prune<KI>(
+        &mut self,
+        data: &[HashMap<KI, Float>],
+        threshold: Float,
+    ) -> error::Result<Vec<Rule>>
+    where
+        KI: Eq + Hash + Borrow<str>,
+    {
+        let dormant = self.dormant_rules(data, threshold)?;
+        if dormant.len() == self.rules.len() {
+            return Err(FuzzyError::EmptyInput);
+        }
+        Ok(self.retain_rules(|i| dormant.binary_search(&i).is_err()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::antecedent::Antecedent;
+    use crate::error::FuzzyError;
+    use crate::mamdani::{Consequent, Rule};
+    use crate::prelude::*;
+    use crate::rulespace::RuleSpace;
+    use crate::variable::Variable;
+
+    fn space() -> RuleSpace {
+        let mut temp = Variable::new(0.0, 30.0).unwrap();
+        for (name, c) in [("cold", 0.0), ("warm", 15.0), ("hot", 30.0)] {
+            temp.insert_term(
+                name,
+                Term::new(name, Triangular::new(c - 10.0, c, c + 10.0).unwrap()),
+            )
+            .unwrap();
+        }
+        let mut fan = Variable::new(0.0, 10.0).unwrap();
+        fan.insert_term(
+            "low",
+            Term::new("low", Triangular::new(-5.0, 0.0, 5.0).unwrap()),
+        )
+        .unwrap();
+        let mut heater = Variable::new(0.0, 10.0).unwrap();
+        heater
+            .insert_term(
+                "on",
+                Term::new("on", Triangular::new(5.0, 10.0, 15.0).unwrap()),
+            )
+            .unwrap();
+        let rule = |t: &'static str, var: &'static str, term: &'static str| Rule {
+            antecedent: Antecedent::Atom {
+                var: "temp".into(),
+                term: t.into(),
+            },
+            consequent: vec![Consequent::Term {
+                var: var.into(),
+                term: term.into(),
+                hedge: None,
+            }],
+            weight: 1.0,
+        };
+        let vars = HashMap::from([
+            ("temp".to_string(), temp),
+            ("fan".to_string(), fan),
+            ("heater".to_string(), heater),
+        ]);
+        RuleSpace::new(
+            vars,
+            vec![
+                rule("cold", "heater", "on"),
+                rule("warm", "fan", "low"),
+                rule("hot", "fan", "low"),
+            ],
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn stats_track_max_and_mean_firing() {
+        let rs = space();
+        let data: Vec<_> = [10.0, 15.0, 20.0]
+            .iter()
+            .map(|&t| HashMap::from([("temp", t)]))
+            .collect();
+        let stats = rs.firing_stats(&data).unwrap();
+        assert_eq!(stats[1].max, 1.0);
+        assert!((stats[1].mean - (0.5 + 1.0 + 0.5) / 3.0).abs() < 1e-6);
+        assert_eq!(stats[0].max, 0.0);
+        assert!((stats[2].max - 0.0).abs() < 1e-6);
+        assert_eq!(rs.dormant_rules(&data, 0.0).unwrap(), vec![0, 2]);
+    }
+
+    #[test]
+    fn prune_removes_dormant_rules_and_their_outputs() {
+        let mut rs = space();
+        let data = vec![HashMap::from([("temp", 14.0)])];
+        let removed = rs.prune(&data, 0.05).unwrap();
+        assert_eq!(removed.len(), 2);
+        assert_eq!(rs.rules().len(), 1);
+        assert_eq!(rs.outputs().collect::<Vec<_>>(), vec!["fan"]);
+        let out = rs.evaluate(&data[0], &UniformSampler::default()).unwrap();
+        assert_eq!(out.len(), 1);
+
+        let nothing = vec![HashMap::from([("temp", 30.0)])];
+        assert!(matches!(
+            rs.prune(&nothing, 0.0),
+            Err(FuzzyError::EmptyInput)
+        ));
+        assert_eq!(rs.rules().len(), 1);
+    }
+}
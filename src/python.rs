@@ -0,0 +1,358 @@
+// Python bindings (feature `python`).
+//
+// Exposes `Variable`, `Antecedent`, `Rule`, and `System` so rule bases can be
+// prototyped from Python against the same engine. Build with
+// `maturin develop`, which enables `extension-module` and builds a `cdylib`
+// (see `pyproject.toml`); `cargo test --features python` links libpython and
+// runs the conversion tests below.
+use std::collections::HashMap;
+
+use pyo3::{exceptions::PyValueError, prelude::*};
+
+use crate::{
+    antecedent::Antecedent,
+    error::FuzzyError,
+    mamdani::{check_weight, Consequent, Rule},
+    membership::{trapezoidal::Trapezoidal, Gaussian, Triangular},
+    rulespace::RuleSpace,
+    sampler::UniformSampler,
+    term::Term,
+    variable::Variable,
+    Float,
+};
+
+impl From<FuzzyError> for PyErr {
+    fn from(e: FuzzyError) -> Self {
+        PyValueError::new_err(e.to_string())
+    }
+}
+
+/// Membership shape recorded by `PyVariable`; terms are only built into a
+/// `Variable` when a `System` is created.
+#[derive(Clone, Debug)]
+enum Shape {
+    Triangular(Float, Float, Float),
+    Trapezoidal(Float, Float, Float, Float),
+    Gaussian { mean: Float, sd: Float },
+}
+
+/// A linguistic variable: an inclusive domain plus named terms.
+#[pyclass(name = "Variable", module = "rust_fuzzylogic")]
+#[derive(Clone)]
+pub struct PyVariable {
+    min: Float,
+    max: Float,
+    terms: Vec<(String, Shape)>,
+}
+
+#[pymethods]
+impl PyVariable {
+    #[new]
+    fn new(min: Float, max: Float) -> PyResult<Self> {
+        Variable::new(min, max)?;
+        Ok(Self {
+            min,
+            max,
+            terms: Vec::new(),
+        })
+    }
+
+    /// Adds a triangular term with feet `left`/`right` and apex `center`.
+    fn triangular(&mut self, name: &str, left: Float, center: Float, right: Float) -> PyResult<()> {
+        Triangular::new(left, center, right)?;
+        self.push(name, Shape::Triangular(left, center, right))
+    }
+
+    /// Adds a trapezoidal term.
+    fn trapezoidal(
+        &mut self,
+        name: &str,
+        left_leg: Float,
+        left_base: Float,
+        right_base: Float,
+        right_leg: Float,
+    ) -> PyResult<()> {
+        Trapezoidal::new(left_leg, left_base, right_base, right_leg)?;
+        self.push(
+            name,
+            Shape::Trapezoidal(left_leg, left_base, right_base, right_leg),
+        )
+    }
+
+    /// Adds a Gaussian term centred on `mean` with standard deviation `sd`.
+    fn gaussian(&mut self, name: &str, mean: Float, sd: Float) -> PyResult<()> {
+        Gaussian::with_mean_sd(mean, sd)?;
+        self.push(name, Shape::Gaussian { mean, sd })
+    }
+
+    fn __repr__(&self) -> String {
+        let names: Vec<&str> = self.terms.iter().map(|(n, _)| n.as_str()).collect();
+        format!("Variable({}, {}, terms={:?})", self.min, self.max, names)
+    }
+}
+
+impl PyVariable {
+    fn push(&mut self, name: &str, shape: Shape) -> PyResult<()> {
+        if name.is_empty() {
+            return Err(FuzzyError::EmptyInput.into());
+        }
+        if self.terms.iter().any(|(n, _)| n == name) {
+            return Err(FuzzyError::TypeMismatch.into());
+        }
+        self.terms.push((name.to_string(), shape));
+        Ok(())
+    }
+
+    fn build(&self) -> crate::error::Result<Variable> {
+        let mut v = Variable::new(self.min, self.max)?;
+        for (name, shape) in &self.terms {
+            let term = match *shape {
+                Shape::Triangular(l, c, r) => Term::new(name.as_str(), Triangular::new(l, c, r)?),
+                Shape::Trapezoidal(ll, lb, rb, rl) => {
+                    Term::new(name.as_str(), Trapezoidal::new(ll, lb, rb, rl)?)
+                }
+                Shape::Gaussian { mean, sd } => {
+                    Term::new(name.as_str(), Gaussian::with_mean_sd(mean, sd)?)
+                }
+            };
+            v.insert_term(name, term)?;
+        }
+        Ok(v)
+    }
+}
+
+/// Antecedent expression; combine with `&`, `|`, and `~`.
+#[pyclass(name = "Antecedent", module = "rust_fuzzylogic")]
+#[derive(Clone)]
+pub struct PyAntecedent(Antecedent);
+
+#[pymethods]
+impl PyAntecedent {
+    /// Atomic predicate `var IS term`.
+    #[staticmethod]
+    fn atom(var: &str, term: &str) -> Self {
+        Self(Antecedent::Atom {
+            var: var.to_string().into(),
+            term: term.to_string().into(),
+        })
+    }
+
+    fn __and__(&self, other: &Self) -> Self {
+        Self(Antecedent::And(
+            Box::new(self.0.clone()),
+            Box::new(other.0.clone()),
+        ))
+    }
+
+    fn __or__(&self, other: &Self) -> Self {
+        Self(Antecedent::Or(
+            Box::new(self.0.clone()),
+            Box::new(other.0.clone()),
+        ))
+    }
+
+    fn __invert__(&self) -> Self {
+        Self(Antecedent::Not(Box::new(self.0.clone())))
+    }
+
+    fn __repr__(&self) -> String {
+        format!("{:?}", self.0)
+    }
+}
+
+/// A rule: `when` antecedent, `then` a list of `(var, term)` consequents and
+/// an optional `weight` in [0, 1].
+#[pyclass(name = "Rule", module = "rust_fuzzylogic")]
+#[derive(Clone)]
+pub struct PyRule {
+    when: Antecedent,
+    then: Vec<(String, String)>,
+    weight: Float,
+}
+
+#[pymethods]
+impl PyRule {
+    #[new]
+    #[pyo3(signature = (when, then, weight = 1.0))]
+    fn new(when: PyAntecedent, then: Vec<(String, String)>, weight: Float) -> PyResult<Self> {
+        if then.is_empty() {
+            return Err(FuzzyError::EmptyInput.into());
+        }
+        Ok(Self {
+            when: when.0,
+            then,
+            weight: check_weight(weight)?,
+        })
+    }
+}
+
+impl PyRule {
+    fn build(&self) -> Rule {
+        Rule {
+            antecedent: self.when.clone(),
+            consequent: self
+                .then
+                .iter()
+                .map(|(var, term)| Consequent::Term {
+                    var: var.clone().into(),
+                    term: term.clone().into(),
+                    hedge: None,
+                })
+                .collect(),
+            weight: self.weight,
+        }
+    }
+}
+
+/// A compiled rule space with a fixed sampler resolution.
+#[pyclass(name = "System", module = "rust_fuzzylogic")]
+pub struct PySystem {
+    space: RuleSpace,
+    sampler: UniformSampler,
+}
+
+#[pymethods]
+impl PySystem {
+    #[new]
+    #[pyo3(signature = (variables, rules, samples = UniformSampler::DEFAULT_N))]
+    fn new(
+        variables: HashMap<String, PyVariable>,
+        rules: Vec<PyRule>,
+        samples: usize,
+    ) -> PyResult<Self> {
+        let vars = variables
+            .iter()
+            .map(|(name, v)| Ok((name.clone(), v.build()?)))
+            .collect::<crate::error::Result<HashMap<_, _>>>()?;
+        let rules = rules.iter().map(PyRule::build).collect();
+        Ok(Self {
+            space: RuleSpace::new(vars, rules)?,
+            sampler: UniformSampler::new(samples)?,
+        })
+    }
+
+    /// Evaluates one row of named inputs into named crisp outputs.
+    fn evaluate(&self, inputs: HashMap<String, Float>) -> PyResult<HashMap<String, Float>> {
+        Ok(self.space.evaluate(&inputs, &self.sampler)?)
+    }
+
+    /// Evaluates many rows; the GIL is released while the engine runs.
+    fn evaluate_batch(
+        &self,
+        py: Python<'_>,
+        rows: Vec<HashMap<String, Float>>,
+    ) -> PyResult<Vec<HashMap<String, Float>>> {
+        Ok(py.allow_threads(|| self.space.evaluate_batch(&rows, &self.sampler))?)
+    }
+
+    /// Names of the output variables.
+    fn outputs(&self) -> Vec<String> {
+        self.space.outputs().map(str::to_string).collect()
+    }
+}
+
+/// Python module entry point.
+#[pymodule]
+fn rust_fuzzylogic(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<PyVariable>()?;
+    m.add_class::<PyAntecedent>()?;
+    m.add_class::<PyRule>()?;
+    m.add_class::<PySystem>()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::{PyAntecedent, PyRule, PySystem, PyVariable};
+    use crate::mamdani::Consequent;
+
+    fn variables() -> HashMap<String, PyVariable> {
+        let mut temp = PyVariable::new(0.0, 10.0).unwrap();
+        temp.triangular("cold", -5.0, 0.0, 5.0).unwrap();
+        temp.trapezoidal("hot", 5.0, 8.0, 10.0, 12.0).unwrap();
+        let mut fan = PyVariable::new(0.0, 100.0).unwrap();
+        fan.triangular("low", 0.0, 25.0, 50.0).unwrap();
+        fan.gaussian("high", 75.0, 10.0).unwrap();
+        HashMap::from([("temp".to_string(), temp), ("fan".to_string(), fan)])
+    }
+
+    /// Terms are validated when added and rebuilt into the same `Variable`.
+    #[test]
+    fn variables_validate_and_build_their_terms() {
+        let mut vars = variables();
+        let temp = vars.get_mut("temp").unwrap();
+        assert_eq!(
+            temp.__repr__(),
+            "Variable(0, 10, terms=[\"cold\", \"hot\"])"
+        );
+        assert!(temp.triangular("cold", 0.0, 1.0, 2.0).is_err());
+        assert!(temp.triangular("", 0.0, 1.0, 2.0).is_err());
+        assert!(temp.triangular("warm", 2.0, 1.0, 0.0).is_err());
+        assert!(temp.gaussian("warm", 5.0, -1.0).is_err());
+        assert!(PyVariable::new(1.0, 0.0).is_err());
+
+        let built = temp.build().unwrap();
+        assert_eq!(built.domain(), (0.0, 10.0));
+        let names: Vec<&str> = built.terms().map(|(n, _)| n).collect();
+        assert_eq!(names, ["cold", "hot"]);
+        assert_eq!(built.eval("hot", 9.0).unwrap(), 1.0);
+    }
+
+    /// Antecedent operators and rules convert into their engine counterparts,
+    /// and a `System` evaluates like the `RuleSpace` it wraps.
+    #[test]
+    fn rules_and_systems_match_the_engine() {
+        let hot = PyAntecedent::atom("temp", "hot");
+        let cold = PyAntecedent::atom("temp", "cold");
+        let either = hot.__or__(&cold.__invert__()).__and__(&hot);
+        assert_eq!(
+            either.0.to_string(),
+            "(temp IS hot OR NOT temp IS cold) AND temp IS hot"
+        );
+        assert!(PyRule::new(hot.clone(), Vec::new(), 1.0).is_err());
+        let then = || vec![("fan".to_string(), "high".to_string())];
+        assert!(PyRule::new(hot.clone(), then(), 1.5).is_err());
+        assert_eq!(
+            PyRule::new(hot.clone(), then(), 0.8)
+                .unwrap()
+                .build()
+                .weight,
+            0.8
+        );
+
+        let rule = PyRule::new(hot, then(), 1.0).unwrap();
+        let built = rule.build();
+        assert!(matches!(
+            &built.consequent[..],
+            [Consequent::Term { var, term, hedge: None }] if var == "fan" && term == "high"
+        ));
+        let rules = vec![
+            rule,
+            PyRule::new(cold, vec![("fan".into(), "low".into())], 1.0).unwrap(),
+        ];
+
+        let system = PySystem::new(variables(), rules.clone(), 101).unwrap();
+        assert_eq!(system.outputs(), ["fan"]);
+        let vars = variables()
+            .iter()
+            .map(|(n, v)| (n.clone(), v.build().unwrap()))
+            .collect();
+        let space =
+            crate::rulespace::RuleSpace::new(vars, rules.iter().map(PyRule::build).collect())
+                .unwrap();
+        let sampler = crate::sampler::UniformSampler::new(101).unwrap();
+        for t in [1.0, 3.0, 9.0] {
+            let input = HashMap::from([("temp".to_string(), t)]);
+            assert_eq!(
+                system.evaluate(input.clone()).unwrap(),
+                space.evaluate(&input, &sampler).unwrap()
+            );
+        }
+        assert!(system
+            .evaluate(HashMap::from([("temp".to_string(), 11.0)]))
+            .is_err());
+        assert!(PySystem::new(variables(), rules, 0).is_err());
+    }
+}
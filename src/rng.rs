@@ -0,0 +1,47 @@
+// Small deterministic random number generator (SplitMix64).
+//
+// Randomized algorithms in the crate (optimizers, fold shuffling) take a seed so
+// their results are reproducible without pulling in an external RNG crate.
+use crate::Float;
+
+#[derive(Debug, Clone)]
+pub(crate) struct Rng(u64);
+
+impl Rng {
+    pub(crate) fn new(seed: u64) -> Self {
+        Self(seed)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform in `[0, 1)`.
+    pub(crate) fn uniform(&mut self) -> Float {
+        ((self.next_u64() >> 11) as f64 / (1u64 << 53) as f64) as Float
+    }
+
+    /// Uniform index in `0..n` (`n > 0`).
+    pub(crate) fn below(&mut self, n: usize) -> usize {
+        ((self.uniform() * n as Float) as usize).min(n - 1)
+    }
+
+    /// Standard normal sample (Box–Muller).
+    #[cfg_attr(not(feature = "optimize"), allow(dead_code))]
+    pub(crate) fn normal(&mut self) -> Float {
+        let u1 = self.uniform().max(Float::MIN_POSITIVE);
+        let u2 = self.uniform();
+        (-2.0 * u1.ln()).sqrt() * (2.0 * std::f64::consts::PI as Float * u2).cos()
+    }
+
+    /// Shuffles `v` in place (Fisher–Yates).
+    pub(crate) fn shuffle<T>(&mut self, v: &mut [T]) {
+        for i in (1..v.len()).rev() {
+            v.swap(i, self.below(i + 1));
+        }
+    }
+}
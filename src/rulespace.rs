@@ -1,44 +1,450 @@
 use std::{borrow::Borrow, collections::HashMap, hash::Hash};
 
 use crate::{
-    aggregate::aggregation,
-    defuzz::defuzzification,
-    error::{self, FuzzyError},
-    mamdani::Rule,
-    sampler::UniformSampler,
+    counters::Counters,
+    defuzz::{centroid, OutputSet},
+    error::{self, FuzzyError, MissingSpace},
+    intern::{Interner, TermId, VarId},
+    mamdani::{CompiledRule, Rule},
+    sampler::{GridCache, UniformSampler},
     variable::Variable,
     Float,
 };
 
 // Container for fuzzy variables, rules, and intermediate membership data.
+//
+// Variable names are interned on construction and every rule is compiled to
+// `(VarId, TermId)` pairs, so evaluation never hashes rule strings.
 pub struct RuleSpace {
-    vars: HashMap<String, Variable>,
+    pub(crate) names: Interner,
+    pub(crate) vars: Vec<Variable>,
     agg_memberships: HashMap<String, Vec<Float>>,
-    rules: Vec<Rule>,
+    pub(crate) rules: Vec<Rule>,
+    pub(crate) compiled: Vec<CompiledRule>,
+    pub(crate) outputs: Vec<VarId>,
+    /// Whether each variable is concluded on by crisp consequents, by `VarId`.
+    pub(crate) crisp: Vec<bool>,
+    grids: GridCache,
+    pub(crate) counters: Option<Counters>,
 }
 
 impl RuleSpace {
     /// Create a rule space with the supplied variables and rules.
+    ///
+    /// Fails with `InRule` naming the offending rule, wrapping `NotFound` for an
+    /// unknown variable, `UnknownTerm` for an unknown term or `InvalidParameter`
+    /// for a `Hedge::Power` exponent that is not finite and positive.
     pub fn new(vars: HashMap<String, Variable>, rules: Vec<Rule>) -> error::Result<Self> {
         if vars.is_empty() || rules.is_empty() {
             return Err(FuzzyError::EmptyInput);
-        } else {
-            return Ok(Self {
-                vars: vars,
-                agg_memberships: HashMap::new(),
-                rules: rules,
-            });
         }
+
+        // Sort names so ids do not depend on HashMap iteration order.
+        let mut vars: Vec<(String, Variable)> = vars.into_iter().collect();
+        vars.sort_by(|a, b| a.0.cmp(&b.0));
+        let mut names = Interner::new();
+        let vars = vars
+            .into_iter()
+            .map(|(name, var)| {
+                names.intern(&name);
+                var.with_name(name)
+            })
+            .collect();
+
+        let mut space = Self {
+            names,
+            vars,
+            agg_memberships: HashMap::new(),
+            rules: Vec::new(),
+            compiled: Vec::new(),
+            outputs: Vec::new(),
+            crisp: Vec::new(),
+            grids: GridCache::new(),
+            counters: None,
+        };
+        space.add_rules(&mut { rules })?;
+        Ok(space)
     }
 
     /// Append additional rules to the existing rule set.
+    ///
+    /// Rules are validated first; on error nothing is appended. A variable
+    /// concluded on both by terms and by crisp expressions fails with
+    /// `TypeMismatch`.
     pub fn add_rules(&mut self, rules: &mut Vec<Rule>) -> error::Result<&mut Self> {
         if rules.is_empty() {
-            Err(FuzzyError::EmptyInput)
-        } else {
-            let _ = &mut self.rules.append(rules);
-            return Ok(self);
+            return Err(FuzzyError::EmptyInput);
+        }
+        let compiled = rules
+            .iter()
+            .enumerate()
+            .map(|(i, r)| {
+                CompiledRule::compile(
+                    r,
+                    |var, term| self.resolve(var, term),
+                    |var| self.resolve_var(var),
+                )
+                .map_err(|e| FuzzyError::InRule {
+                    rule: self.rules.len() + i,
+                    source: Box::new(e),
+                })
+            })
+            .collect::<error::Result<Vec<_>>>()?;
+        self.check_conclusions(self.compiled.iter().chain(&compiled))?;
+        self.compiled.extend(compiled);
+        self.rules.append(rules);
+        self.refresh_outputs();
+        self.reset_counters();
+        Ok(self)
+    }
+
+    /// Remove and return the rule at index `id`; later rules move down by one.
+    ///
+    /// Fails with `OutOfBounds` for an index past the end and `EmptyInput` when
+    /// it is the last rule, leaving the space unchanged.
+    pub fn remove_rule(&mut self, id: usize) -> error::Result<Rule> {
+        if id >= self.rules.len() {
+            return Err(FuzzyError::OutOfBounds);
+        }
+        let mut removed = self.retain(|i, _| i != id)?;
+        Ok(removed.remove(0))
+    }
+
+    /// Replace the rule at index `id` with `rule`, returning the old one.
+    ///
+    /// `rule` is validated like `add_rules`; on error (wrapped in `InRule` with
+    /// index `id`) the space is unchanged. Fails with `OutOfBounds` for an
+    /// index past the end.
+    pub fn replace_rule(&mut self, id: usize, rule: Rule) -> error::Result<Rule> {
+        if id >= self.rules.len() {
+            return Err(FuzzyError::OutOfBounds);
+        }
+        let compiled = CompiledRule::compile(
+            &rule,
+            |var, term| self.resolve(var, term),
+            |var| self.resolve_var(var),
+        )
+        .map_err(|e| FuzzyError::InRule {
+            rule: id,
+            source: Box::new(e),
+        })?;
+        let others = self.compiled.iter().enumerate();
+        self.check_conclusions(others.map(|(i, c)| if i == id { &compiled } else { c }))?;
+        self.compiled[id] = compiled;
+        let old = std::mem::replace(&mut self.rules[id], rule);
+        self.refresh_outputs();
+        self.agg_memberships
+            .retain(|name, _| self.outputs.iter().any(|&v| self.names.name(v.0) == name));
+        self.reset_counters();
+        Ok(old)
+    }
+
+    /// Keep only the rules for which `keep(index, rule)` is true, returning the
+    /// removed rules in order. Outputs no remaining rule concludes on are dropped.
+    ///
+    /// Fails with `EmptyInput`, leaving the space unchanged, if no rule would remain.
+    pub fn retain(
+        &mut self,
+        mut keep: impl FnMut(usize, &Rule) -> bool,
+    ) -> error::Result<Vec<Rule>> {
+        let kept: Vec<bool> = self
+            .rules
+            .iter()
+            .enumerate()
+            .map(|(i, r)| keep(i, r))
+            .collect();
+        if !kept.contains(&true) {
+            return Err(FuzzyError::EmptyInput);
         }
+        Ok(self.retain_rules(|i| kept[i]))
+    }
+
+    /// Fail with `TypeMismatch` (in `InRule`) when a variable is concluded on
+    /// both by terms and by crisp expressions.
+    fn check_conclusions<'a>(
+        &self,
+        compiled: impl Iterator<Item = &'a CompiledRule>,
+    ) -> error::Result<()> {
+        let mut crisp: Vec<Option<bool>> = vec![None; self.vars.len()];
+        for (i, c) in compiled.enumerate() {
+            let concluded = c.consequent.iter().map(|&(v, _)| (v, false));
+            for (v, is_crisp) in concluded.chain(c.crisp.iter().map(|k| (k.var, true))) {
+                if *crisp[v.index()].get_or_insert(is_crisp) != is_crisp {
+                    return Err(FuzzyError::InRule {
+                        rule: i,
+                        source: Box::new(FuzzyError::TypeMismatch),
+                    });
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Fail with `TypeMismatch` (in `InRule`, naming the first offender) when a
+    /// rule has a crisp consequent. Engines that only build output sets call
+    /// this up front.
+    pub(crate) fn require_term_consequents(&self) -> error::Result<()> {
+        match self.compiled.iter().position(|c| !c.crisp.is_empty()) {
+            Some(rule) => Err(FuzzyError::InRule {
+                rule,
+                source: Box::new(FuzzyError::TypeMismatch),
+            }),
+            None => Ok(()),
+        }
+    }
+
+    /// Remove every rule for which `keep(index)` is false, returning the removed
+    /// rules in order. Outputs no remaining rule concludes on are dropped.
+    pub(crate) fn retain_rules(&mut self, mut keep: impl FnMut(usize) -> bool) -> Vec<Rule> {
+        let mut removed = Vec::new();
+        let mut kept = Vec::with_capacity(self.rules.len());
+        let rules = std::mem::take(&mut self.rules);
+        let compiled = std::mem::take(&mut self.compiled);
+        for (i, (rule, c)) in rules.into_iter().zip(compiled).enumerate() {
+            if keep(i) {
+                kept.push(rule);
+                self.compiled.push(c);
+            } else {
+                removed.push(rule);
+            }
+        }
+        self.rules = kept;
+        self.refresh_outputs();
+        self.agg_memberships
+            .retain(|name, _| self.outputs.iter().any(|&v| self.names.name(v.0) == name));
+        self.reset_counters();
+        removed
+    }
+
+    /// The rules in insertion order.
+    pub fn rules(&self) -> &[Rule] {
+        &self.rules
+    }
+
+    /// Looks up a variable by name.
+    pub fn var(&self, name: &str) -> Option<&Variable> {
+        self.var_id(name).map(|id| &self.vars[id.index()])
+    }
+
+    /// Resolves a variable name to its id in this rule space.
+    pub fn var_id(&self, name: &str) -> Option<VarId> {
+        self.names.get(name).map(VarId)
+    }
+
+    /// Name of the variable behind `id`.
+    pub fn var_name(&self, id: VarId) -> &str {
+        self.names.name(id.0)
+    }
+
+    /// Iterates `(name, variable)` pairs in id order.
+    pub fn vars(&self) -> impl Iterator<Item = (&str, &Variable)> {
+        self.names.iter().zip(self.vars.iter())
+    }
+
+    fn resolve_var(&self, var: &str) -> error::Result<VarId> {
+        self.var_id(var).ok_or_else(|| FuzzyError::NotFound {
+            space: MissingSpace::Var,
+            key: var.to_string(),
+        })
+    }
+
+    fn resolve(&self, var: &str, term: &str) -> error::Result<(VarId, TermId)> {
+        let v = self.resolve_var(var)?;
+        let t = self.vars[v.index()]
+            .term_id(term)
+            .ok_or_else(|| FuzzyError::UnknownTerm {
+                var: var.to_string(),
+                term: term.to_string(),
+            })?;
+        Ok((v, t))
+    }
+
+    fn refresh_outputs(&mut self) {
+        let mut outputs: Vec<VarId> = self
+            .compiled
+            .iter()
+            .flat_map(|c| c.consequent.iter().map(|&(v, _)| v))
+            .collect();
+        let mut crisp = vec![false; self.vars.len()];
+        for k in self.compiled.iter().flat_map(|c| &c.crisp) {
+            crisp[k.var.index()] = true;
+            outputs.push(k.var);
+        }
+        outputs.sort();
+        outputs.dedup();
+        self.outputs = outputs;
+        self.crisp = crisp;
+    }
+
+    /// Lay crisp inputs out by `VarId`; this is the only place input keys are hashed.
+    pub(crate) fn resolve_inputs_into<KI>(
+        &self,
+        input: &HashMap<KI, Float>,
+        out: &mut Vec<Option<Float>>,
+    ) where
+        KI: Eq + Hash + Borrow<str>,
+    {
+        out.clear();
+        out.extend(self.names.iter().map(|n| input.get(n).copied()));
+    }
+
+    /// Sample the grid of every output variable and lay them out by `VarId`.
+    pub(crate) fn grid_table<'g>(
+        &self,
+        sampler: &UniformSampler,
+        grids: &'g mut GridCache,
+    ) -> error::Result<Vec<&'g [Float]>> {
+        for &v in &self.outputs {
+            grids.grid(self.var_name(v), self.vars[v.index()].domain(), sampler)?;
+        }
+        let grids: &'g GridCache = grids;
+        Ok(self
+            .names
+            .iter()
+            .map(|n| grids.get(n).unwrap_or(&[]))
+            .collect())
+    }
+
+    /// Zero `agg` so each output holds one slot per grid point and every other variable none.
+    pub(crate) fn reset_agg<T: AsRef<[Float]>>(&self, table: &[T], agg: &mut Vec<Vec<Float>>) {
+        agg.resize_with(self.vars.len(), Vec::new);
+        for v in agg.iter_mut() {
+            v.clear();
+        }
+        for &v in &self.outputs {
+            // A crisp output accumulates `[sum(alpha * y), sum(alpha)]` instead of a set.
+            let len = if self.crisp[v.index()] {
+                2
+            } else {
+                table[v.index()].as_ref().len()
+            };
+            agg[v.index()].resize(len, 0.0);
+        }
+    }
+
+    /// Fire every rule and max-merge the clipped consequents into `agg` (see `reset_agg`).
+    ///
+    /// Only outputs passing `keep` are implicated; rules concluding on none of them
+    /// are not activated at all.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
+    pub(crate) fn aggregate_into<T: AsRef<[Float]>>(
+        &self,
+        inputs: &[Option<Float>],
+        table: &[T],
+        agg: &mut [Vec<Float>],
+        scratch: &mut Vec<Float>,
+        keep: impl Fn(VarId) -> bool + Copy,
+    ) -> error::Result<()> {
+        if let Some(c) = &self.counters {
+            c.evaluation();
+        }
+        for (index, (rule, compiled)) in self.rules.iter().zip(&self.compiled).enumerate() {
+            let concluded = compiled.consequent.iter().map(|&(v, _)| v);
+            if !concluded
+                .chain(compiled.crisp.iter().map(|k| k.var))
+                .any(keep)
+            {
+                continue;
+            }
+            let in_rule = |e| FuzzyError::InRule {
+                rule: index,
+                source: Box::new(e),
+            };
+            let alpha = compiled
+                .activation(rule, inputs, &self.vars, &self.names)
+                .map_err(in_rule)?;
+            #[cfg(feature = "tracing")]
+            tracing::debug!(rule = index, alpha, "rule fired");
+            if let Some(c) = &self.counters {
+                c.rule(index, alpha);
+            }
+            compiled.implicate_into(alpha, &self.vars, table, agg, scratch, keep);
+            compiled
+                .accumulate_crisp(alpha, inputs, &self.names, agg, keep)
+                .map_err(in_rule)?;
+        }
+        Ok(())
+    }
+
+    /// Aggregated output sets laid out by `VarId` (empty where no rule concludes).
+    fn aggregate_ids<KI>(
+        &self,
+        input: &HashMap<KI, Float>,
+        table: &[&[Float]],
+    ) -> error::Result<Vec<Vec<Float>>>
+    where
+        KI: Eq + Hash + Borrow<str>,
+    {
+        let mut inputs = Vec::new();
+        self.resolve_inputs_into(input, &mut inputs);
+        let mut agg = Vec::new();
+        self.reset_agg(table, &mut agg);
+        self.aggregate_into(&inputs, table, &mut agg, &mut Vec::new(), |_| true)?;
+        Ok(agg)
+    }
+
+    /// Centroid of every output, in output slot order (see `outputs`).
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
+    fn defuzz_each<T: AsRef<[Float]>>(
+        &self,
+        agg: &[Vec<Float>],
+        table: &[T],
+        mut f: impl FnMut(usize, &str, Float),
+    ) {
+        for (slot, &v) in self.outputs.iter().enumerate() {
+            let i = v.index();
+            let y = self.output_value(v, table[i].as_ref(), &agg[i]);
+            #[cfg(feature = "tracing")]
+            tracing::debug!(output = self.var_name(v), value = y, "defuzzification");
+            f(slot, self.var_name(v), y);
+        }
+    }
+
+    /// Crisp value of output `v` from its aggregated set `mu` on `xs`, or from
+    /// the accumulators of a crisp output (see `reset_agg`).
+    pub(crate) fn output_value(&self, v: VarId, xs: &[Float], mu: &[Float]) -> Float {
+        match (self.crisp[v.index()], mu) {
+            (true, &[num, den]) => num / den,
+            _ => centroid(xs, mu),
+        }
+    }
+
+    pub(crate) fn defuzzify_ids(
+        &self,
+        agg: &[Vec<Float>],
+        table: &[&[Float]],
+    ) -> HashMap<String, Float> {
+        let mut out = HashMap::with_capacity(self.outputs.len());
+        self.defuzz_each(agg, table, |_, name, y| {
+            out.insert(name.to_string(), y);
+        });
+        out
+    }
+
+    /// Names of the input variables (those some antecedent or crisp consequent
+    /// reads), in id order.
+    pub fn inputs(&self) -> impl Iterator<Item = &str> {
+        let mut read = vec![false; self.vars.len()];
+        for c in &self.compiled {
+            let coefficients = c.crisp.iter().flat_map(|k| &k.coefficients);
+            for v in c
+                .atoms
+                .iter()
+                .map(|&(v, _)| v)
+                .chain(coefficients.map(|&(v, _)| v))
+            {
+                read[v.index()] = true;
+            }
+        }
+        self.names
+            .iter()
+            .zip(read)
+            .filter_map(|(name, r)| r.then_some(name))
+    }
+
+    /// Names of the output variables (those some rule concludes on), in slot order.
+    pub fn outputs(&self) -> impl Iterator<Item = &str> {
+        self.outputs.iter().map(|&v| self.var_name(v))
     }
 
     /// Run the aggregation step for all rules with the provided crisp inputs.
@@ -50,13 +456,41 @@ impl RuleSpace {
     where
         KI: Eq + Hash + Borrow<str>,
     {
-        //let rules = std::mem::take(&mut self.rules);
-        let agg_memberships = aggregation(&self.rules, input, &self.vars, sampler)?;
-        self.agg_memberships = agg_memberships;
+        let mut grids = std::mem::take(&mut self.grids);
+        let agg = self
+            .grid_table(sampler, &mut grids)
+            .and_then(|table| self.aggregate_ids(input, &table));
+        self.grids = grids;
+        let mut agg = agg?;
+        self.agg_memberships = self
+            .outputs
+            .iter()
+            .map(|&v| {
+                let mu = std::mem::take(&mut agg[v.index()]);
+                (self.var_name(v).to_string(), mu)
+            })
+            .collect();
 
         Ok(())
     }
 
+    /// Aggregated membership of output `var` from the last `aggregate`/`defuzzify` call.
+    ///
+    /// The samples lie on the grid the sampler produced for `var`'s domain.
+    /// Crisp outputs have no set and give `None`.
+    pub fn aggregated(&self, var: &str) -> Option<&[Float]> {
+        if self.crisp[self.var_id(var)?.index()] {
+            return None;
+        }
+        self.agg_memberships.get(var).map(Vec::as_slice)
+    }
+
+    /// Shape statistics of the aggregated output `var` from the last
+    /// `aggregate`/`defuzzify` call, see `aggregated`.
+    pub fn output_set(&self, var: &str) -> Option<OutputSet<'_>> {
+        OutputSet::new(self.grids.get(var)?, self.aggregated(var)?).ok()
+    }
+
     /// Aggregate and then defuzzify each output variable using the supplied sampler.
     pub fn defuzzify<KI>(
         &mut self,
@@ -66,10 +500,784 @@ impl RuleSpace {
     where
         KI: Eq + Hash + Borrow<str>,
     {
-        let _ = self.aggregate(input, sampler)?;
-        //let agg_memberships = std::mem::take(&mut self.agg_memberships);
-        Ok(defuzzification(&self.agg_memberships, &self.vars)?)
+        self.aggregate(input, sampler)?;
+        let mut result = HashMap::new();
+        for (name, mu) in &self.agg_memberships {
+            let xs = self.grids.get(name).ok_or(FuzzyError::BadArity)?;
+            let v = self.var_id(name).ok_or(FuzzyError::BadArity)?;
+            result.insert(name.clone(), self.output_value(v, xs, mu));
+        }
+        Ok(result)
+    }
+
+    /// Aggregate and defuzzify `input` without touching the rule space state.
+    ///
+    /// Errors raised while firing a rule come wrapped in `InRule`, e.g.
+    /// `OutOfDomain` for an input outside its variable's domain or `NotFound`
+    /// for a missing one; `FuzzyError::root` unwraps them.
+    pub fn evaluate<KI>(
+        &self,
+        input: &HashMap<KI, Float>,
+        sampler: &UniformSampler,
+    ) -> error::Result<HashMap<String, Float>>
+    where
+        KI: Eq + Hash + Borrow<str>,
+    {
+        self.evaluate_with(input, sampler, &mut GridCache::new())
+    }
+
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
+    fn evaluate_with<KI>(
+        &self,
+        input: &HashMap<KI, Float>,
+        sampler: &UniformSampler,
+        grids: &mut GridCache,
+    ) -> error::Result<HashMap<String, Float>>
+    where
+        KI: Eq + Hash + Borrow<str>,
+    {
+        let table = self.grid_table(sampler, grids)?;
+        let agg = self.aggregate_ids(input, &table)?;
+        Ok(self.defuzzify_ids(&agg, &table))
+    }
+
+    /// Like `evaluate`, but writes into an existing map instead of returning a new one.
+    ///
+    /// Existing keys are overwritten in place, so a reused map only allocates on first use.
+    pub fn evaluate_into<KI>(
+        &self,
+        input: &HashMap<KI, Float>,
+        sampler: &UniformSampler,
+        outputs: &mut HashMap<String, Float>,
+    ) -> error::Result<()>
+    where
+        KI: Eq + Hash + Borrow<str>,
+    {
+        let mut grids = GridCache::new();
+        let table = self.grid_table(sampler, &mut grids)?;
+        let agg = self.aggregate_ids(input, &table)?;
+        self.defuzz_each(&agg, &table, |_, name, y| match outputs.get_mut(name) {
+            Some(slot) => *slot = y,
+            None => {
+                outputs.insert(name.to_string(), y);
+            }
+        });
+        Ok(())
+    }
+
+    /// Like `evaluate_into`, but writes output `i` of `outputs()` into `out[i]`.
+    ///
+    /// `out` must have exactly one slot per output variable, otherwise `BadArity`.
+    pub fn evaluate_into_slice<KI>(
+        &self,
+        input: &HashMap<KI, Float>,
+        sampler: &UniformSampler,
+        out: &mut [Float],
+    ) -> error::Result<()>
+    where
+        KI: Eq + Hash + Borrow<str>,
+    {
+        if out.len() != self.outputs.len() {
+            return Err(FuzzyError::BadArity);
+        }
+        let mut grids = GridCache::new();
+        let table = self.grid_table(sampler, &mut grids)?;
+        let agg = self.aggregate_ids(input, &table)?;
+        self.defuzz_each(&agg, &table, |slot, _, y| out[slot] = y);
+        Ok(())
+    }
+
+    /// Evaluate only the output variables named in `outputs`.
+    ///
+    /// Rules that conclude on none of them are not activated and consequents for
+    /// other variables are skipped. Unknown names fail with `NotFound`; a known
+    /// variable no rule concludes on is simply absent from the result.
+    pub fn evaluate_only<KI>(
+        &self,
+        input: &HashMap<KI, Float>,
+        sampler: &UniformSampler,
+        outputs: &[&str],
+    ) -> error::Result<HashMap<String, Float>>
+    where
+        KI: Eq + Hash + Borrow<str>,
+    {
+        let mut wanted = vec![false; self.vars.len()];
+        for name in outputs {
+            let v = self.var_id(name).ok_or_else(|| FuzzyError::NotFound {
+                space: MissingSpace::Var,
+                key: name.to_string(),
+            })?;
+            wanted[v.index()] = true;
+        }
+        let keep = |v: VarId| wanted[v.index()];
+
+        let mut grids = GridCache::new();
+        let table = self.grid_table(sampler, &mut grids)?;
+        let mut inputs = Vec::new();
+        self.resolve_inputs_into(input, &mut inputs);
+        let mut agg = Vec::new();
+        self.reset_agg(&table, &mut agg);
+        self.aggregate_into(&inputs, &table, &mut agg, &mut Vec::new(), keep)?;
+
+        Ok(self
+            .outputs
+            .iter()
+            .filter(|&&v| keep(v))
+            .map(|&v| {
+                let i = v.index();
+                (
+                    self.var_name(v).to_string(),
+                    self.output_value(v, table[i], &agg[i]),
+                )
+            })
+            .collect())
+    }
+
+    /// Preallocate the buffers `evaluate_in` needs for this rule space and sampler.
+    pub fn scratch(&self, sampler: &UniformSampler) -> error::Result<Scratch> {
+        let mut grids = GridCache::new();
+        let table: Vec<Vec<Float>> = self
+            .grid_table(sampler, &mut grids)?
+            .into_iter()
+            .map(<[Float]>::to_vec)
+            .collect();
+        let mut agg = Vec::new();
+        self.reset_agg(&table, &mut agg);
+        Ok(Scratch {
+            layout: self.layout_fingerprint(sampler.n),
+            n: sampler.n,
+            inputs: Vec::with_capacity(self.vars.len()),
+            clipped: Vec::with_capacity(sampler.n),
+            outputs: self
+                .outputs
+                .iter()
+                .map(|&v| (self.var_name(v).to_string(), Float::NAN))
+                .collect(),
+            grids: table,
+            agg,
+        })
+    }
+
+    /// Evaluate `input` using only the buffers held by `scratch`.
+    ///
+    /// After the first call nothing is allocated on the success path, which makes
+    /// this the entry point for hard real-time loops. The returned map has one
+    /// entry per output variable; its keys never change between calls. A
+    /// scratch created by another rule space, or by this one before its
+    /// variables or outputs changed, fails with `ScratchMismatch`.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", skip_all))]
+    pub fn evaluate_in<'s, KI>(
+        &self,
+        input: &HashMap<KI, Float>,
+        scratch: &'s mut Scratch,
+    ) -> error::Result<&'s HashMap<String, Float>>
+    where
+        KI: Eq + Hash + Borrow<str>,
+    {
+        self.check_scratch(scratch)?;
+        let Scratch {
+            grids,
+            inputs,
+            agg,
+            clipped,
+            outputs,
+            ..
+        } = scratch;
+
+        self.resolve_inputs_into(input, inputs);
+        self.reset_agg(grids, agg);
+        self.aggregate_into(inputs, grids, agg, clipped, |_| true)?;
+        self.defuzz_each(agg, grids, |_, name, y| {
+            if let Some(slot) = outputs.get_mut(name) {
+                *slot = y;
+            }
+        });
+        Ok(outputs)
+    }
+
+    /// Fails with `ScratchMismatch` unless `scratch` was sized for this space.
+    fn check_scratch(&self, scratch: &Scratch) -> error::Result<()> {
+        if scratch.layout != self.layout_fingerprint(scratch.n) {
+            return Err(FuzzyError::ScratchMismatch);
+        }
+        Ok(())
+    }
+
+    /// Like `evaluate_in`, but `fill` writes the inputs straight into their
+    /// `VarId` slots (all `None` beforehand) and output `i` of `outputs()` is
+    /// reported to `f`, so no names are hashed.
+    pub(crate) fn evaluate_ids_in(
+        &self,
+        scratch: &mut Scratch,
+        fill: impl FnOnce(&mut [Option<Float>]),
+        mut f: impl FnMut(usize, Float),
+    ) -> error::Result<()> {
+        self.check_scratch(scratch)?;
+        let Scratch {
+            grids,
+            inputs,
+            agg,
+            clipped,
+            ..
+        } = scratch;
+
+        inputs.clear();
+        inputs.resize(self.vars.len(), None);
+        fill(inputs);
+        self.reset_agg(grids, agg);
+        self.aggregate_into(inputs, grids, agg, clipped, |_| true)?;
+        self.defuzz_each(agg, grids, |slot, _, y| f(slot, y));
+        Ok(())
+    }
+
+    /// Like `evaluate_in`, but reads the inputs from a slice in id order and
+    /// writes output `i` of `outputs()` into `out[i]`, so no names are hashed.
+    ///
+    /// `inputs[i]` is the reading of the `i`-th variable of `vars()` (look a
+    /// slot up once with `var_id(name)?.index()`); slots of variables no rule
+    /// reads are ignored. Nothing is allocated once `scratch` is warm. A slice
+    /// of the wrong length fails with `BadArity`.
+    pub fn evaluate_slice_in(
+        &self,
+        inputs: &[Float],
+        scratch: &mut Scratch,
+        out: &mut [Float],
+    ) -> error::Result<()> {
+        if inputs.len() != self.vars.len() || out.len() != self.outputs.len() {
+            return Err(FuzzyError::BadArity);
+        }
+        self.evaluate_ids_in(
+            scratch,
+            |slots| {
+                for (slot, &x) in slots.iter_mut().zip(inputs) {
+                    *slot = Some(x);
+                }
+            },
+            |slot, y| out[slot] = y,
+        )
+    }
+
+    /// Evaluate every input row, returning the outputs in row order.
+    ///
+    /// With the `parallel` feature rows are distributed across the rayon pool,
+    /// otherwise they are evaluated sequentially. The first failing row aborts the batch.
+    pub fn evaluate_batch<KI>(
+        &self,
+        inputs: &[HashMap<KI, Float>],
+        sampler: &UniformSampler,
+    ) -> error::Result<Vec<HashMap<String, Float>>>
+    where
+        KI: Eq + Hash + Borrow<str> + Sync,
+    {
+        #[cfg(feature = "parallel")]
+        {
+            use rayon::prelude::*;
+            inputs
+                .par_iter()
+                .map_init(GridCache::new, |grids, row| {
+                    self.evaluate_with(row, sampler, grids)
+                })
+                .collect()
+        }
+        #[cfg(not(feature = "parallel"))]
+        {
+            let mut grids = GridCache::new();
+            inputs
+                .iter()
+                .map(|row| self.evaluate_with(row, sampler, &mut grids))
+                .collect()
+        }
+    }
+
+    /// Evaluate a single input, implicating the rules in parallel.
+    ///
+    /// Worth it only for very large rule bases; small systems are faster with `evaluate`.
+    #[cfg(feature = "parallel")]
+    pub fn evaluate_par<KI>(
+        &self,
+        input: &HashMap<KI, Float>,
+        sampler: &UniformSampler,
+    ) -> error::Result<HashMap<String, Float>>
+    where
+        KI: Eq + Hash + Borrow<str> + Sync,
+    {
+        use rayon::prelude::*;
+
+        let mut grids = GridCache::new();
+        let table = self.grid_table(sampler, &mut grids)?;
+        let mut inputs = Vec::new();
+        self.resolve_inputs_into(input, &mut inputs);
+        let fresh = || {
+            let mut agg = Vec::new();
+            self.reset_agg(&table, &mut agg);
+            (agg, Vec::new())
+        };
+        if let Some(c) = &self.counters {
+            c.evaluation();
+        }
+        let (agg, _) = self
+            .rules
+            .par_iter()
+            .zip(self.compiled.par_iter())
+            .enumerate()
+            .try_fold(
+                fresh,
+                |(mut agg, mut scratch), (index, (rule, compiled))| {
+                    let in_rule = |e| FuzzyError::InRule {
+                        rule: index,
+                        source: Box::new(e),
+                    };
+                    let alpha = compiled
+                        .activation(rule, &inputs, &self.vars, &self.names)
+                        .map_err(in_rule)?;
+                    if let Some(c) = &self.counters {
+                        c.rule(index, alpha);
+                    }
+                    compiled.implicate_into(
+                        alpha,
+                        &self.vars,
+                        &table,
+                        &mut agg,
+                        &mut scratch,
+                        |_| true,
+                    );
+                    compiled
+                        .accumulate_crisp(alpha, &inputs, &self.names, &mut agg, |_| true)
+                        .map_err(in_rule)?;
+                    error::Result::Ok((agg, scratch))
+                },
+            )
+            .try_reduce(fresh, |(mut a, scratch), (b, _)| {
+                for ((a, b), &crisp) in a.iter_mut().zip(&b).zip(&self.crisp) {
+                    if crisp {
+                        a[0] += b[0];
+                        a[1] += b[1];
+                    } else {
+                        crate::aggregate::elements_max(a, b);
+                    }
+                }
+                Ok((a, scratch))
+            })?;
+        Ok(self.defuzzify_ids(&agg, &table))
+    }
+
+    /// Drop the cached domain grid of `var` (e.g. after its domain changed).
+    pub fn invalidate_grid(&mut self, var: &str) -> bool {
+        self.grids.invalidate(var)
+    }
+
+    /// Drop every cached domain grid.
+    pub fn clear_grids(&mut self) {
+        self.grids.clear();
     }
     //is there a nessecity?
     //pub fn consequent_keys() {}
 }
+
+/// Reusable buffers for allocation-free evaluation; see `RuleSpace::evaluate_in`.
+///
+/// Tied to the rule space and sampler it was created from.
+#[derive(Debug, Clone)]
+pub struct Scratch {
+    /// `RuleSpace::layout_fingerprint` of the owning space at creation.
+    layout: u64,
+    /// Sampler resolution the output grids were built with.
+    n: usize,
+    grids: Vec<Vec<Float>>,
+    inputs: Vec<Option<Float>>,
+    agg: Vec<Vec<Float>>,
+    clipped: Vec<Float>,
+    outputs: HashMap<String, Float>,
+}
+
+impl Scratch {
+    /// Resolution of the sampler the buffers were created for.
+    pub fn samples(&self) -> usize {
+        self.n
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::antecedent::Antecedent;
+    use crate::error::FuzzyError;
+    use crate::mamdani::{Consequent, Rule};
+    use crate::prelude::*;
+    use crate::rulespace::RuleSpace;
+    use crate::variable::Variable;
+
+    fn fixture_parts() -> (HashMap<String, Variable>, Vec<Rule>) {
+        let mut temp = Variable::new(0.0, 10.0).unwrap();
+        temp.insert_term(
+            "cold",
+            Term::new("cold", Triangular::new(-5.0, 0.0, 5.0).unwrap()),
+        )
+        .unwrap();
+        temp.insert_term(
+            "hot",
+            Term::new("hot", Triangular::new(5.0, 10.0, 15.0).unwrap()),
+        )
+        .unwrap();
+        let mut fan = Variable::new(0.0, 100.0).unwrap();
+        fan.insert_term(
+            "low",
+            Term::new("low", Triangular::new(0.0, 25.0, 50.0).unwrap()),
+        )
+        .unwrap();
+        fan.insert_term(
+            "high",
+            Term::new("high", Triangular::new(50.0, 75.0, 100.0).unwrap()),
+        )
+        .unwrap();
+
+        let rule = |t: &'static str, f: &'static str| Rule {
+            antecedent: Antecedent::Atom {
+                var: "temp".into(),
+                term: t.into(),
+            },
+            consequent: vec![Consequent::Term {
+                var: "fan".into(),
+                term: f.into(),
+                hedge: None,
+            }],
+            weight: 1.0,
+        };
+
+        let mut vars = HashMap::new();
+        vars.insert("temp".to_string(), temp);
+        vars.insert("fan".to_string(), fan);
+        (vars, vec![rule("cold", "low"), rule("hot", "high")])
+    }
+
+    fn fixture() -> RuleSpace {
+        let (vars, rules) = fixture_parts();
+        RuleSpace::new(vars, rules).unwrap()
+    }
+
+    #[test]
+    fn batch_matches_single_evaluation() {
+        let rs = fixture();
+        let sampler = UniformSampler::default();
+        let rows: Vec<HashMap<&str, Float>> = [1.0, 4.0, 6.0, 9.0]
+            .iter()
+            .map(|&t| HashMap::from([("temp", t)]))
+            .collect();
+
+        let batch = rs.evaluate_batch(&rows, &sampler).unwrap();
+        assert_eq!(batch.len(), rows.len());
+        for (row, out) in rows.iter().zip(&batch) {
+            assert_eq!(out, &rs.evaluate(row, &sampler).unwrap());
+        }
+        assert!(batch[0]["fan"] < batch[3]["fan"]);
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn parallel_rules_match_sequential_evaluation() {
+        use crate::mamdani::CrispExpr;
+
+        let (mut vars, mut rules) = fixture_parts();
+        vars.insert("speed".to_string(), Variable::new(0.0, 100.0).unwrap());
+        rules.push(
+            Rule::builder()
+                .when(atom("temp", "hot"))
+                .then_crisp("speed", CrispExpr::linear(10.0, [("temp", 2.0)]))
+                .weight(0.5)
+                .build()
+                .unwrap(),
+        );
+        let rs = RuleSpace::new(vars, rules).unwrap();
+        let sampler = UniformSampler::default();
+        for t in [0.5, 3.0, 5.0, 7.0, 9.5] {
+            let input = HashMap::from([("temp", t)]);
+            let want = rs.evaluate(&input, &sampler).unwrap();
+            let got = rs.evaluate_par(&input, &sampler).unwrap();
+            assert_eq!(got.len(), want.len());
+            for (name, y) in &want {
+                let same = (got[name] - y).abs() < 1e-9 || (got[name].is_nan() && y.is_nan());
+                assert!(same, "{t} {name}: {} {y}", got[name]);
+            }
+        }
+    }
+
+    #[test]
+    fn compiled_engine_matches_name_based_path() {
+        let rs = fixture();
+        let (vars, rules) = fixture_parts();
+        let sampler = UniformSampler::default();
+        for t in [0.5, 3.0, 7.0, 9.5] {
+            let input = HashMap::from([("temp", t)]);
+            let agg = crate::aggregate::aggregation(&rules, &input, &vars, &sampler).unwrap();
+            let expected = crate::defuzz::defuzzification(&agg, &vars).unwrap();
+            let got = rs.evaluate(&input, &sampler).unwrap();
+            assert!((got["fan"] - expected["fan"]).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn unknown_names_rejected_on_construction() {
+        let mut vars = HashMap::new();
+        vars.insert("temp".to_string(), Variable::new(0.0, 1.0).unwrap());
+        let rule = Rule {
+            antecedent: Antecedent::Atom {
+                var: "temp".into(),
+                term: "hot".into(),
+            },
+            consequent: vec![],
+            weight: 1.0,
+        };
+        let err = RuleSpace::new(vars, vec![rule]).err().unwrap();
+        assert!(matches!(err, FuzzyError::InRule { rule: 0, .. }));
+        assert_eq!(
+            err.root(),
+            &FuzzyError::UnknownTerm {
+                var: "temp".into(),
+                term: "hot".into()
+            }
+        );
+    }
+
+    #[test]
+    fn evaluation_errors_name_rule_and_input() {
+        let rs = fixture();
+        let sampler = UniformSampler::default();
+        let err = rs
+            .evaluate(&HashMap::from([("temp", 12.0)]), &sampler)
+            .unwrap_err();
+        assert_eq!(
+            err,
+            FuzzyError::InRule {
+                rule: 0,
+                source: Box::new(FuzzyError::OutOfDomain {
+                    var: "temp".into(),
+                    value: 12.0
+                })
+            }
+        );
+        assert!(err.to_string().contains("Out of domain. temp = 12"));
+        let missing = rs
+            .evaluate(&HashMap::<&str, Float>::new(), &sampler)
+            .unwrap_err();
+        assert!(matches!(missing, FuzzyError::InRule { rule: 0, .. }));
+        assert!(matches!(missing.root(), FuzzyError::NotFound { .. }));
+        #[cfg(feature = "parallel")]
+        assert!(matches!(
+            rs.evaluate_par(&HashMap::from([("temp", -1.0)]), &sampler),
+            Err(FuzzyError::InRule { ref source, .. })
+                if matches!(**source, FuzzyError::OutOfDomain { .. })
+        ));
+    }
+
+    #[test]
+    fn evaluate_into_reuses_caller_storage() {
+        let rs = fixture();
+        let sampler = UniformSampler::default();
+        let input = HashMap::from([("temp", 8.0)]);
+        let expected = rs.evaluate(&input, &sampler).unwrap();
+
+        let mut map = HashMap::from([("fan".to_string(), -1.0), ("stale".to_string(), 3.0)]);
+        rs.evaluate_into(&input, &sampler, &mut map).unwrap();
+        assert_eq!(map["fan"], expected["fan"]);
+        assert_eq!(map["stale"], 3.0);
+
+        assert_eq!(rs.outputs().collect::<Vec<_>>(), vec!["fan"]);
+        let mut slots = [0.0];
+        rs.evaluate_into_slice(&input, &sampler, &mut slots)
+            .unwrap();
+        assert_eq!(slots[0], expected["fan"]);
+        assert!(matches!(
+            rs.evaluate_into_slice(&input, &sampler, &mut [0.0, 0.0]),
+            Err(FuzzyError::BadArity)
+        ));
+    }
+
+    #[test]
+    fn scratch_is_tied_to_its_space() {
+        let rs = fixture();
+        let input = HashMap::from([("temp", 8.0)]);
+        let mut scratch = rs.scratch(&UniformSampler::new(11).unwrap()).unwrap();
+        assert_eq!(scratch.samples(), 11);
+        let coarse = rs.evaluate(&input, &UniformSampler::new(11).unwrap());
+        assert_eq!(
+            rs.evaluate_in(&input, &mut scratch).unwrap(),
+            &coarse.unwrap()
+        );
+        // A rebuilt copy of the space accepts it, term parameters included.
+        assert!(fixture().evaluate_in(&input, &mut scratch).is_ok());
+
+        let (mut vars, mut rules) = fixture_parts();
+        vars.insert("speed".to_string(), Variable::new(0.0, 100.0).unwrap());
+        rules.push(
+            Rule::builder()
+                .when(atom("temp", "hot"))
+                .then_crisp("speed", crate::mamdani::CrispExpr::constant(50.0))
+                .build()
+                .unwrap(),
+        );
+        let other = RuleSpace::new(vars, rules).unwrap();
+        assert_eq!(
+            other.evaluate_in(&input, &mut scratch),
+            Err(FuzzyError::ScratchMismatch)
+        );
+        let mut out = [0.0; 2];
+        assert_eq!(
+            other.evaluate_slice_in(&[8.0, 0.0, 0.0], &mut scratch, &mut out),
+            Err(FuzzyError::ScratchMismatch)
+        );
+        assert_eq!(
+            FuzzyError::ScratchMismatch.to_string(),
+            "Scratch mismatch. buffers belong to another rule space"
+        );
+    }
+
+    #[test]
+    fn evaluate_only_restricts_outputs() {
+        let rs = fixture();
+        let sampler = UniformSampler::default();
+        let input = HashMap::from([("temp", 2.0)]);
+        let only = rs.evaluate_only(&input, &sampler, &["fan"]).unwrap();
+        assert_eq!(only, rs.evaluate(&input, &sampler).unwrap());
+
+        // `temp` is known but no rule concludes on it.
+        assert!(rs
+            .evaluate_only(&input, &sampler, &["temp"])
+            .unwrap()
+            .is_empty());
+        assert!(matches!(
+            rs.evaluate_only(&input, &sampler, &["pump"]),
+            Err(FuzzyError::NotFound { .. })
+        ));
+    }
+
+    #[test]
+    fn output_set_describes_last_aggregation() {
+        let mut rs = fixture();
+        let sampler = UniformSampler::default();
+        assert!(rs.output_set("fan").is_none());
+        let out = rs
+            .defuzzify(&HashMap::from([("temp", 7.0)]), &sampler)
+            .unwrap();
+        let set = rs.output_set("fan").unwrap();
+        assert!((set.centroid() - out["fan"]).abs() < 1e-5);
+        // hot = 0.4 clips `high`, whose 0.4-cut spans 60..90.
+        assert!((set.height() - 0.4).abs() < 1e-5);
+        let (lo, hi) = set.alpha_cut(0.4).unwrap();
+        assert!(
+            (lo - 60.0).abs() < 1.5 && (hi - 90.0).abs() < 1.5,
+            "{lo} {hi}"
+        );
+        assert!(set.area() > 0.0 && set.spread() > 0.0);
+        assert!(rs.output_set("temp").is_none());
+    }
+
+    #[test]
+    fn crisp_consequents_take_weighted_average() {
+        use crate::mamdani::CrispExpr;
+
+        let (mut vars, mut rules) = fixture_parts();
+        vars.insert("speed".to_string(), Variable::new(0.0, 100.0).unwrap());
+        let crisp = |t: &'static str, expr: CrispExpr| {
+            Rule::builder()
+                .when(atom("temp", t))
+                .then_crisp("speed", expr)
+                .build()
+                .unwrap()
+        };
+        let linear = CrispExpr::linear(10.0, [("temp", 2.0)]);
+        rules.push(crisp("cold", CrispExpr::constant(10.0)));
+        rules.push(crisp("hot", linear.clone()));
+        rules.push(crisp("hot", CrispExpr::constant(50.0)));
+        assert_eq!(rules[3].consequent[0].to_string(), "speed = 10 + 2 * temp");
+        let mut rs = RuleSpace::new(vars, rules).unwrap();
+        let sampler = UniformSampler::default();
+
+        // hot = 0.4 fires both hot rules: (0.4 * 24 + 0.4 * 50) / 0.8.
+        let input = HashMap::from([("temp", 7.0)]);
+        let out = rs.evaluate(&input, &sampler).unwrap();
+        assert!((out["speed"] - 37.0).abs() < 1e-4, "{}", out["speed"]);
+        let fan = fixture().evaluate(&input, &sampler).unwrap()["fan"];
+        assert!((out["fan"] - fan).abs() < 1e-6);
+        let cold = rs.evaluate(&HashMap::from([("temp", 2.0)]), &sampler);
+        assert!((cold.unwrap()["speed"] - 10.0).abs() < 1e-5);
+
+        let out = rs.defuzzify(&input, &sampler).unwrap();
+        assert!((out["speed"] - 37.0).abs() < 1e-4);
+        assert!(rs.aggregated("speed").is_none());
+        assert!(rs.output_set("speed").is_none());
+
+        // A variable is concluded on either by terms or by expressions.
+        let (mut vars, mut rules) = fixture_parts();
+        vars.insert("speed".to_string(), Variable::new(0.0, 100.0).unwrap());
+        rules.push(crisp("cold", CrispExpr::constant(10.0)));
+        vars.get_mut("speed")
+            .unwrap()
+            .insert_term(
+                "slow",
+                Term::new("slow", Triangular::new(-50.0, 0.0, 50.0).unwrap()),
+            )
+            .unwrap();
+        let term = Rule::builder()
+            .when(atom("temp", "cold"))
+            .then("speed", "slow")
+            .build()
+            .unwrap();
+        rules.push(term);
+        assert!(matches!(
+            RuleSpace::new(vars, rules),
+            Err(FuzzyError::InRule { source, .. }) if *source == FuzzyError::TypeMismatch
+        ));
+    }
+
+    #[test]
+    fn rules_can_be_removed_replaced_and_retained() {
+        let mut rs = fixture();
+        let sampler = UniformSampler::default();
+        let hot = HashMap::from([("temp", 9.0)]);
+        let rule = |t: &'static str, f: &'static str| {
+            Rule::builder()
+                .when(atom("temp", t))
+                .then("fan", f)
+                .build()
+                .unwrap()
+        };
+
+        // Swapping the conclusions flips which way the fan turns.
+        let before = rs.evaluate(&hot, &sampler).unwrap()["fan"];
+        let old = rs.replace_rule(1, rule("hot", "low")).unwrap();
+        assert_eq!(old, rule("hot", "high"));
+        assert_eq!(rs.rules()[1], rule("hot", "low"));
+        let after = rs.evaluate(&hot, &sampler).unwrap()["fan"];
+        assert!(before > 50.0 && after < 50.0, "{before} {after}");
+
+        // Invalid replacements leave the space as it was.
+        let typo = rule("hot", "hgih");
+        assert!(matches!(
+            rs.replace_rule(1, typo),
+            Err(FuzzyError::InRule { rule: 1, .. })
+        ));
+        assert!(matches!(
+            rs.replace_rule(2, rule("hot", "high")),
+            Err(FuzzyError::OutOfBounds)
+        ));
+        assert_eq!(rs.rules()[1], rule("hot", "low"));
+
+        rs.add_rules(&mut vec![rule("hot", "high")]).unwrap();
+        let removed = rs
+            .retain(|_, r| r.consequent[0].term() == Some("low"))
+            .unwrap();
+        assert_eq!(removed, vec![rule("hot", "high")]);
+        assert_eq!(rs.rules().len(), 2);
+        assert!(matches!(
+            rs.retain(|_, _| false),
+            Err(FuzzyError::EmptyInput)
+        ));
+
+        assert_eq!(rs.remove_rule(0).unwrap(), rule("cold", "low"));
+        assert_eq!(rs.rules(), &[rule("hot", "low")]);
+        assert!(matches!(rs.remove_rule(0), Err(FuzzyError::EmptyInput)));
+        assert!(matches!(rs.remove_rule(1), Err(FuzzyError::OutOfBounds)));
+        assert!(rs.evaluate(&hot, &sampler).unwrap()["fan"] < 50.0);
+    }
+}
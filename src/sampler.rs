@@ -1,4 +1,5 @@
 use crate::{error::FuzzyError, prelude::*, Float};
+use std::collections::HashMap;
 
 pub trait Sampler {
     //Trait shape: Returning Result<Vec<Float>> is fine.
@@ -6,6 +7,7 @@ pub trait Sampler {
     fn sample(&self, min: Float, max: Float) -> Result<Vec<Float>>;
 }
 
+#[derive(Debug, Clone)]
 pub struct UniformSampler {
     pub n: usize,
 }
@@ -23,7 +25,7 @@ impl UniformSampler {
         if n < 2 {
             return Err(FuzzyError::OutOfBounds);
         }
-        Ok(Self { n: n })
+        Ok(Self { n })
     }
 }
 
@@ -50,10 +52,85 @@ impl Sampler for UniformSampler {
     }
 }
 
+/// Sampled x-coordinates of one variable together with the key they were built for.
+#[derive(Debug, Clone)]
+struct Grid {
+    domain: (Float, Float),
+    n: usize,
+    xs: Vec<Float>,
+}
+
+/// Cache of sampled domain grids keyed by variable name.
+///
+/// Each entry remembers the domain and sample count it was built with, so a lookup
+/// with a different domain or sampler size regenerates the grid instead of returning
+/// stale coordinates. Call `invalidate`/`clear` to drop entries explicitly.
+#[derive(Debug, Clone, Default)]
+pub struct GridCache {
+    grids: HashMap<String, Grid>,
+}
+
+impl GridCache {
+    /// Creates an empty cache.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the grid for `var`, sampling `domain` with `sampler` on a miss.
+    pub fn grid(
+        &mut self,
+        var: &str,
+        domain: (Float, Float),
+        sampler: &UniformSampler,
+    ) -> Result<&[Float]> {
+        let fresh = match self.grids.get(var) {
+            Some(g) => g.domain != domain || g.n != sampler.n,
+            None => true,
+        };
+        if fresh {
+            let xs = sampler.sample(domain.0, domain.1)?;
+            self.grids.insert(
+                var.to_string(),
+                Grid {
+                    domain,
+                    n: sampler.n,
+                    xs,
+                },
+            );
+        }
+        Ok(&self.grids[var].xs)
+    }
+
+    /// Returns the cached grid for `var` without sampling on a miss.
+    pub fn get(&self, var: &str) -> Option<&[Float]> {
+        self.grids.get(var).map(|g| g.xs.as_slice())
+    }
+
+    /// Drops the cached grid for `var`; returns whether an entry existed.
+    pub fn invalidate(&mut self, var: &str) -> bool {
+        self.grids.remove(var).is_some()
+    }
+
+    /// Drops every cached grid.
+    pub fn clear(&mut self) {
+        self.grids.clear();
+    }
+
+    /// Number of cached grids.
+    pub fn len(&self) -> usize {
+        self.grids.len()
+    }
+
+    /// Returns `true` when no grid is cached.
+    pub fn is_empty(&self) -> bool {
+        self.grids.is_empty()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::error::FuzzyError;
-    use crate::sampler::{Sampler, UniformSampler};
+    use crate::sampler::{GridCache, Sampler, UniformSampler};
     use crate::Float;
 
     #[test]
@@ -118,6 +195,24 @@ mod tests {
         // min > max must error
         assert!(matches!(s.sample(1.0, 0.0), Err(FuzzyError::BadArity)));
         // Degenerate range should be rejected for a sampler that requires >=2 distinct points
-        assert!(matches!(s.sample(1.0, 1.0), Err(_)));
+        assert!(s.sample(1.0, 1.0).is_err());
+    }
+
+    #[test]
+    fn grid_cache_reuses_and_regenerates() {
+        let mut cache = GridCache::new();
+        let s = UniformSampler::new(5).unwrap();
+
+        let first = cache.grid("temp", (0.0, 4.0), &s).unwrap().to_vec();
+        assert_eq!(first, vec![0.0, 1.0, 2.0, 3.0, 4.0]);
+        assert_eq!(cache.len(), 1);
+
+        // A changed domain must not return the stale grid.
+        let moved = cache.grid("temp", (0.0, 8.0), &s).unwrap().to_vec();
+        assert_eq!(moved, vec![0.0, 2.0, 4.0, 6.0, 8.0]);
+
+        assert!(cache.invalidate("temp"));
+        assert!(!cache.invalidate("temp"));
+        assert!(cache.is_empty());
     }
 }
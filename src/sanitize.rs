@@ -0,0 +1,242 @@
+// Sanitation of non-finite inputs.
+//
+// Sensor glitches and fuzzers produce NaN and infinite readings. `evaluate`
+// rejects them with `OutOfDomain` (NaN lies in no domain), which does not let
+// a control loop keep going. A `Sanitizer` runs in front of
+// evaluation and maps every non-finite input according to one policy: fail
+// with a `NonFinite` error naming the input, clamp it into the domain, or
+// substitute the last finite value seen for the same input. Finite inputs pass
+// through unchanged, out-of-domain ones included; `Policy` covers those.
+use std::{borrow::Borrow, collections::HashMap, hash::Hash};
+
+use crate::{
+    error::{self, FuzzyError},
+    rulespace::RuleSpace,
+    sampler::UniformSampler,
+    system::System,
+    Float,
+};
+
+/// What to do with a NaN or infinite input.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OnNonFinite {
+    /// Fail with `NonFinite`.
+    Error,
+    /// Map `-inf` and `+inf` to the domain bounds, and NaN, which has no side,
+    /// to the domain midpoint.
+    Clamp,
+    /// Use the last finite value this sanitizer saw for the input; fail with
+    /// `NonFinite` if there is none yet.
+    LastGood,
+}
+
+/// Stateful input sanitation stage, see `RuleSpace::evaluate_sanitized`.
+///
+/// Remembers the last finite value of every input it has passed, so one
+/// sanitizer should serve one input stream.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Sanitizer {
+    policy: OnNonFinite,
+    last: HashMap<String, Float>,
+}
+
+impl Sanitizer {
+    pub fn new(policy: OnNonFinite) -> Self {
+        Self {
+            policy,
+            last: HashMap::new(),
+        }
+    }
+
+    pub fn policy(&self) -> OnNonFinite {
+        self.policy
+    }
+
+    /// Last finite value passed for `input`, if any.
+    pub fn last_good(&self, input: &str) -> Option<Float> {
+        self.last.get(input).copied()
+    }
+
+    /// Forgets every remembered value.
+    pub fn reset(&mut self) {
+        self.last.clear();
+    }
+
+    /// Copy of `input` with the non-finite values of `space`'s variables
+    /// replaced as the policy says. Entries naming no variable are copied
+    /// unchanged; each substitution counts as a fallback in the space's usage
+    /// counters.
+    pub fn sanitize<KI>(
+        &mut self,
+        space: &RuleSpace,
+        input: &HashMap<KI, Float>,
+    ) -> error::Result<HashMap<String, Float>>
+    where
+        KI: Eq + Hash + Borrow<str>,
+    {
+        let mut out = HashMap::with_capacity(input.len());
+        for (name, &x) in input {
+            let name = name.borrow();
+            let x = match space.var(name) {
+                Some(_) if x.is_finite() => {
+                    match self.last.get_mut(name) {
+                        Some(last) => *last = x,
+                        None => {
+                            self.last.insert(name.to_string(), x);
+                        }
+                    }
+                    x
+                }
+                Some(var) => {
+                    let (min, max) = var.domain();
+                    let fixed = match self.policy {
+                        OnNonFinite::Error => None,
+                        OnNonFinite::Clamp if x.is_nan() => Some(0.5 * (min + max)),
+                        OnNonFinite::Clamp => Some(x.clamp(min, max)),
+                        OnNonFinite::LastGood => self.last_good(name),
+                    };
+                    let fixed = fixed.ok_or_else(|| FuzzyError::NonFinite {
+                        var: name.to_string(),
+                        value: x,
+                    })?;
+                    space.fallback();
+                    fixed
+                }
+                None => x,
+            };
+            out.insert(name.to_string(), x);
+        }
+        Ok(out)
+    }
+}
+
+impl RuleSpace {
+    /// Evaluates `input` after passing it through `sanitizer`.
+    ///
+    /// Fails with `NonFinite` where the policy does, otherwise as `evaluate`.
+    pub fn evaluate_sanitized<KI>(
+        &self,
+        input: &HashMap<KI, Float>,
+        sampler: &UniformSampler,
+        sanitizer: &mut Sanitizer,
+    ) -> error::Result<HashMap<String, Float>>
+    where
+        KI: Eq + Hash + Borrow<str>,
+    {
+        let input = sanitizer.sanitize(self, input)?;
+        self.evaluate(&input, sampler)
+    }
+}
+
+impl System {
+    /// Evaluates `input` after passing it through `sanitizer`, reusing the
+    /// owned buffers like `evaluate`.
+    pub fn evaluate_sanitized<KI>(
+        &mut self,
+        input: &HashMap<KI, Float>,
+        sanitizer: &mut Sanitizer,
+    ) -> error::Result<&HashMap<String, Float>>
+    where
+        KI: Eq + Hash + Borrow<str>,
+    {
+        let input = sanitizer.sanitize(self.space(), input)?;
+        self.evaluate(&input)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::{OnNonFinite, Sanitizer};
+    use crate::error::FuzzyError;
+    use crate::prelude::*;
+
+    const TEXT: &str = "
+        var temp 0 40
+          cold: triangle(-40, 0, 40)
+          hot: triangle(0, 40, 80)
+        var fan 0 100
+          low: triangle(-100, 0, 100)
+          high: triangle(0, 100, 200)
+        IF temp IS cold THEN fan IS low
+        IF temp IS hot THEN fan IS high
+    ";
+
+    fn fan(rs: &RuleSpace, temp: Float) -> Float {
+        let input = HashMap::from([("temp", temp)]);
+        rs.evaluate(&input, &UniformSampler::default()).unwrap()["fan"]
+    }
+
+    #[test]
+    fn error_policy_names_the_input() {
+        let rs: RuleSpace = TEXT.parse().unwrap();
+        let mut s = Sanitizer::new(OnNonFinite::Error);
+        let sampler = UniformSampler::default();
+        let err = rs
+            .evaluate(&HashMap::from([("temp", Float::NAN)]), &sampler)
+            .unwrap_err();
+        assert!(matches!(err.root(), FuzzyError::OutOfDomain { .. }));
+        for x in [Float::NAN, Float::INFINITY] {
+            let err = rs
+                .evaluate_sanitized(&HashMap::from([("temp", x)]), &sampler, &mut s)
+                .unwrap_err();
+            assert!(matches!(err, FuzzyError::NonFinite { ref var, .. } if var == "temp"));
+        }
+        assert_eq!(
+            FuzzyError::NonFinite {
+                var: "temp".into(),
+                value: Float::NAN
+            }
+            .to_string(),
+            "Non-finite input. temp = NaN"
+        );
+        // Finite values, and names the space does not have, pass through.
+        let input = HashMap::from([("temp", 12.0), ("other", Float::NAN)]);
+        let clean = s.sanitize(&rs, &input).unwrap();
+        assert_eq!(clean["temp"], 12.0);
+        assert!(clean["other"].is_nan());
+    }
+
+    #[test]
+    fn clamp_policy_maps_to_bounds_and_midpoint() {
+        let mut rs: RuleSpace = TEXT.parse().unwrap();
+        rs.enable_counters(0.0);
+        let mut s = Sanitizer::new(OnNonFinite::Clamp);
+        let sampler = UniformSampler::default();
+        for (x, at) in [
+            (Float::INFINITY, 40.0),
+            (Float::NEG_INFINITY, 0.0),
+            (Float::NAN, 20.0),
+        ] {
+            let out = rs
+                .evaluate_sanitized(&HashMap::from([("temp", x)]), &sampler, &mut s)
+                .unwrap();
+            assert_eq!(out["fan"], fan(&rs, at));
+        }
+        assert_eq!(rs.metrics().unwrap().fallbacks, 3);
+    }
+
+    #[test]
+    fn last_good_policy_repeats_the_previous_reading() {
+        let rs: RuleSpace = TEXT.parse().unwrap();
+        let mut system = System::new(rs, UniformSampler::default()).unwrap();
+        let mut s = Sanitizer::new(OnNonFinite::LastGood);
+        let nan = HashMap::from([("temp", Float::NAN)]);
+        assert!(matches!(
+            system.evaluate_sanitized(&nan, &mut s),
+            Err(FuzzyError::NonFinite { .. })
+        ));
+
+        let want = system
+            .evaluate_sanitized(&HashMap::from([("temp", 30.0)]), &mut s)
+            .unwrap()["fan"];
+        assert_eq!(s.last_good("temp"), Some(30.0));
+        let got = system.evaluate_sanitized(&nan, &mut s).unwrap()["fan"];
+        assert_eq!(got, want);
+
+        s.reset();
+        assert_eq!(s.last_good("temp"), None);
+        assert_eq!(s.policy(), OnNonFinite::LastGood);
+    }
+}
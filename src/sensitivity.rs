@@ -0,0 +1,175 @@
+// Numerical sensitivity of outputs with respect to inputs.
+//
+// Finite-difference partial derivatives at an operating point, the local gains
+// of the controller. Central differences are used where both probes stay inside
+// the input's domain; at a domain edge the difference is taken one-sided.
+use std::{borrow::Borrow, collections::HashMap, hash::Hash};
+
+use crate::{
+    error::{self, FuzzyError},
+    rulespace::RuleSpace,
+    sampler::UniformSampler,
+    Float,
+};
+
+/// Partial derivatives keyed by output name, then by input name.
+pub type Jacobian = HashMap<String, HashMap<String, Float>>;
+
+impl RuleSpace {
+    /// Partial derivative of every output with respect to every input at the
+    /// operating point `input`, using step `h` (in input units).
+    ///
+    /// Inputs are differentiated if some rule reads them and `input` holds a value;
+    /// other keys are passed through unchanged. A derivative is NaN when
+    /// an output is undefined (no rule fired) at either probe. Fails with
+    /// `OutOfBounds` unless `h` is positive and finite.
+    pub fn sensitivity<KI>(
+        &self,
+        input: &HashMap<KI, Float>,
+        h: Float,
+        sampler: &UniformSampler,
+    ) -> error::Result<Jacobian>
+    where
+        KI: Eq + Hash + Borrow<str>,
+    {
+        if !(h.is_finite() && h > 0.0) {
+            return Err(FuzzyError::OutOfBounds);
+        }
+        let mut point: HashMap<String, Float> = input
+            .iter()
+            .map(|(k, &v)| (k.borrow().to_string(), v))
+            .collect();
+        let mut scratch = self.scratch(sampler)?;
+        let base = self.evaluate_in(&point, &mut scratch)?.clone();
+
+        let mut jacobian: Jacobian = self
+            .outputs()
+            .map(|o| (o.to_string(), HashMap::new()))
+            .collect();
+        let names: Vec<String> = self
+            .inputs()
+            .filter(|n| point.contains_key(*n))
+            .map(str::to_string)
+            .collect();
+        for name in names {
+            let x = point[&name];
+            let (min, max) = self
+                .var(&name)
+                .expect("input names come from the space")
+                .domain();
+            let (lo, hi) = ((x - h).max(min), (x + h).min(max));
+
+            let mut probe = |at: Float| -> error::Result<HashMap<String, Float>> {
+                if at == x {
+                    return Ok(base.clone());
+                }
+                point.insert(name.clone(), at);
+                self.evaluate_in(&point, &mut scratch).cloned()
+            };
+            let down = probe(lo)?;
+            let up = probe(hi)?;
+            point.insert(name.clone(), x);
+
+            for (out, row) in &mut jacobian {
+                row.insert(name.clone(), (up[out] - down[out]) / (hi - lo));
+            }
+        }
+        Ok(jacobian)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::antecedent::Antecedent;
+    use crate::error::FuzzyError;
+    use crate::mamdani::{Consequent, Rule};
+    use crate::prelude::*;
+    use crate::rulespace::RuleSpace;
+    use crate::variable::Variable;
+
+    fn space() -> RuleSpace {
+        let mut vars = HashMap::new();
+        for name in ["temp", "humidity"] {
+            let mut v = Variable::new(0.0, 10.0).unwrap();
+            v.insert_term(
+                "high",
+                Term::new("high", Triangular::new(0.0, 10.0, 20.0).unwrap()),
+            )
+            .unwrap();
+            vars.insert(name.to_string(), v);
+        }
+        let mut fan = Variable::new(0.0, 10.0).unwrap();
+        for (name, c) in [("low", 0.0), ("high", 10.0)] {
+            fan.insert_term(
+                name,
+                Term::new(name, Triangular::new(c - 10.0, c, c + 10.0).unwrap()),
+            )
+            .unwrap();
+        }
+        vars.insert("fan".to_string(), fan);
+        let atom = |var: &'static str| Antecedent::Atom {
+            var: var.into(),
+            term: "high".into(),
+        };
+        let rules = vec![
+            Rule {
+                antecedent: atom("temp"),
+                consequent: vec![Consequent::Term {
+                    var: "fan".into(),
+                    term: "high".into(),
+                    hedge: None,
+                }],
+                weight: 1.0,
+            },
+            Rule {
+                antecedent: Antecedent::Not(Box::new(atom("temp"))),
+                consequent: vec![Consequent::Term {
+                    var: "fan".into(),
+                    term: "low".into(),
+                    hedge: None,
+                }],
+                weight: 1.0,
+            },
+        ];
+        RuleSpace::new(vars, rules).unwrap()
+    }
+
+    #[test]
+    fn gains_match_the_response_slope() {
+        let rs = space();
+        let sampler = UniformSampler::default();
+        let at = |t: Float| {
+            rs.evaluate(&HashMap::from([("temp", t), ("humidity", 5.0)]), &sampler)
+                .unwrap()["fan"]
+        };
+        let j = rs
+            .sensitivity(
+                &HashMap::from([("temp", 5.0), ("humidity", 5.0)]),
+                0.1,
+                &sampler,
+            )
+            .unwrap();
+        let dt = j["fan"]["temp"];
+        assert!(dt > 0.0);
+        assert!((dt - (at(5.1) - at(4.9)) / 0.2).abs() < 1e-6);
+        // No rule reads humidity.
+        assert!(!j["fan"].contains_key("humidity"));
+
+        // At the domain edge the difference is one-sided.
+        let edge = rs
+            .sensitivity(
+                &HashMap::from([("temp", 10.0), ("humidity", 5.0)]),
+                0.1,
+                &sampler,
+            )
+            .unwrap();
+        assert!((edge["fan"]["temp"] - (at(10.0) - at(9.9)) / 0.1).abs() < 1e-6);
+
+        assert_eq!(
+            rs.sensitivity(&HashMap::from([("temp", 5.0)]), 0.0, &sampler),
+            Err(FuzzyError::OutOfBounds)
+        );
+    }
+}
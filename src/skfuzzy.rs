@@ -0,0 +1,669 @@
+// scikit-fuzzy compatible control-system definitions (feature `skfuzzy`).
+//
+// `ControlSystem` mirrors the objects a `skfuzzy.control` prototype is built from:
+// antecedents and consequents over `np.arange` universes with `trimf`/`trapmf`/
+// `gaussmf` terms, and rules written in skfuzzy's operator syntax
+// (`temp['cold'] & ~humidity['high'] | ...`). Definitions load from JSON, build
+// into a `RuleSpace`, and can be written back out as JSON or as the equivalent
+// skfuzzy Python script for side-by-side comparison.
+use std::fmt::{self, Write};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    antecedent::Antecedent,
+    error::{FuzzyError, MissingSpace},
+    mamdani::{Consequent, Rule},
+    membership::{trapezoidal::Trapezoidal, Gaussian, MembershipFn, Triangular},
+    rulespace::RuleSpace,
+    sampler::UniformSampler,
+    term::Term,
+    variable::Variable,
+    Float,
+};
+
+/// Errors from loading or building a scikit-fuzzy definition.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum SkfuzzyError {
+    Json(serde_json::Error),
+    /// Rule `rule` (0-based) could not be parsed.
+    Parse {
+        rule: usize,
+        message: String,
+    },
+    /// A membership function or defuzzification method this crate does not provide.
+    Unsupported(String),
+    Fuzzy(FuzzyError),
+}
+
+impl fmt::Display for SkfuzzyError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SkfuzzyError::Json(e) => write!(f, "invalid JSON: {e}"),
+            SkfuzzyError::Parse { rule, message } => write!(f, "rule {rule}: {message}"),
+            SkfuzzyError::Unsupported(what) => write!(f, "unsupported: {what}"),
+            SkfuzzyError::Fuzzy(e) => write!(f, "{e}"),
+        }
+    }
+}
+
+impl std::error::Error for SkfuzzyError {}
+
+impl From<serde_json::Error> for SkfuzzyError {
+    fn from(e: serde_json::Error) -> Self {
+        SkfuzzyError::Json(e)
+    }
+}
+
+impl From<FuzzyError> for SkfuzzyError {
+    fn from(e: FuzzyError) -> Self {
+        SkfuzzyError::Fuzzy(e)
+    }
+}
+
+/// `np.arange(start, stop, step)`: `stop` is exclusive.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Universe {
+    pub start: Float,
+    pub stop: Float,
+    pub step: Float,
+}
+
+impl Universe {
+    /// Number of points, computed like numpy: `ceil((stop - start) / step)`.
+    pub fn len(&self) -> usize {
+        ((self.stop - self.start) / self.step).ceil().max(0.0) as usize
+    }
+
+    /// True when the range holds no points.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// First and last point of the range; needs at least two points.
+    pub fn domain(&self) -> Result<(Float, Float), FuzzyError> {
+        let n = self.len();
+        if self.step <= 0.0 || n < 2 {
+            return Err(FuzzyError::OutOfBounds);
+        }
+        Ok((self.start, self.start + (n - 1) as Float * self.step))
+    }
+}
+
+/// One term, e.g. `temp['cold'] = fuzz.trimf(temp.universe, [0, 0, 5])`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TermDef {
+    pub name: String,
+    /// `trimf`, `trapmf` or `gaussmf`.
+    pub mf: String,
+    /// Parameters in skfuzzy order (`gaussmf` takes `[mean, sigma]`).
+    pub params: Vec<Float>,
+}
+
+/// A `ctrl.Antecedent` or `ctrl.Consequent`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct VarDef {
+    pub label: String,
+    pub universe: Universe,
+    pub terms: Vec<TermDef>,
+    /// Only `centroid` (skfuzzy's default) is supported.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub defuzzify_method: Option<String>,
+}
+
+/// A `ctrl.Rule`: both sides use skfuzzy's Python operator syntax.
+///
+/// `consequent` lists one or more `var['term']` separated by commas.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RuleDef {
+    pub antecedent: String,
+    pub consequent: String,
+    /// Rule weight, written `var['term'] % weight` on every consequent in
+    /// skfuzzy; absent means 1.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub weight: Option<Float>,
+}
+
+/// A complete `ctrl.ControlSystem` definition.
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub struct ControlSystem {
+    pub antecedents: Vec<VarDef>,
+    pub consequents: Vec<VarDef>,
+    pub rules: Vec<RuleDef>,
+}
+
+impl ControlSystem {
+    /// Parses a JSON definition.
+    pub fn from_json(json: &str) -> Result<Self, SkfuzzyError> {
+        Ok(serde_json::from_str(json)?)
+    }
+
+    /// Serializes the definition as pretty-printed JSON.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("definition is always serializable")
+    }
+
+    /// Builds the rule space and the sampler matching the consequent universes.
+    ///
+    /// The sampler uses the largest consequent point count, so consequents whose
+    /// universes have the same length defuzzify on exactly skfuzzy's grid.
+    pub fn build(&self) -> Result<(RuleSpace, UniformSampler), SkfuzzyError> {
+        let mut vars = std::collections::HashMap::new();
+        for def in self.antecedents.iter().chain(&self.consequents) {
+            if let Some(method) = def.defuzzify_method.as_deref() {
+                if method != "centroid" {
+                    return Err(SkfuzzyError::Unsupported(format!(
+                        "defuzzify_method {method:?}"
+                    )));
+                }
+            }
+            let (min, max) = def.universe.domain()?;
+            let mut var = Variable::new(min, max)?;
+            for t in &def.terms {
+                var.insert_term(&t.name, term(t)?)?;
+            }
+            if vars.insert(def.label.clone(), var).is_some() {
+                return Err(FuzzyError::TypeMismatch.into());
+            }
+        }
+
+        let rules = self
+            .rules
+            .iter()
+            .enumerate()
+            .map(|(i, r)| {
+                let err = |message: String| SkfuzzyError::Parse { rule: i, message };
+                let antecedent = Parser::new(&r.antecedent).antecedent().map_err(err)?;
+                let consequent = r
+                    .consequent
+                    .split(',')
+                    .map(|c| {
+                        let (var, term) = Parser::new(c).term_ref()?;
+                        Ok(Consequent::new(var, term))
+                    })
+                    .collect::<Result<Vec<_>, String>>()
+                    .map_err(err)?;
+                Ok(Rule {
+                    antecedent,
+                    consequent,
+                    weight: r.weight.unwrap_or(1.0),
+                })
+            })
+            .collect::<Result<Vec<_>, SkfuzzyError>>()?;
+
+        let n = self
+            .consequents
+            .iter()
+            .map(|c| c.universe.len())
+            .max()
+            .unwrap_or(UniformSampler::DEFAULT_N);
+        Ok((RuleSpace::new(vars, rules)?, UniformSampler::new(n)?))
+    }
+
+    /// Writes the current term parameters, rule consequents and rule weights of
+    /// `space` back into the definition, e.g. after `RuleSpace::tune` or an
+    /// optimizer ran on the space `build` produced.
+    ///
+    /// Labels, universes, membership kinds, defuzzification settings and rule
+    /// antecedents are kept as they are; a consequent is only rewritten when its
+    /// terms changed, and a weight of 1 is only written where one was set. Terms that expose no parameters (skfuzzy shoulders) keep
+    /// theirs. Fails with `NotFound`/`TypeMismatch` for a variable or term the
+    /// space lacks, `TypeMismatch` for a crisp consequent skfuzzy cannot express,
+    /// and `BadArity` if the rule counts differ.
+    pub fn update_from(&mut self, space: &RuleSpace) -> Result<(), SkfuzzyError> {
+        if self.rules.len() != space.rules().len() {
+            return Err(FuzzyError::BadArity.into());
+        }
+        for def in self.antecedents.iter_mut().chain(&mut self.consequents) {
+            let var = space.var(&def.label).ok_or_else(|| FuzzyError::NotFound {
+                space: MissingSpace::Var,
+                key: def.label.clone(),
+            })?;
+            for t in &mut def.terms {
+                let params = var.get(&t.name).ok_or(FuzzyError::TypeMismatch)?.params();
+                // `Gaussian` reports `[mean, sd]`, matching `gaussmf`.
+                if !params.is_empty() && params.len() == t.params.len() {
+                    t.params = params;
+                }
+            }
+        }
+        for (i, (def, rule)) in self.rules.iter_mut().zip(space.rules()).enumerate() {
+            let current = def
+                .consequent
+                .split(',')
+                .map(|c| Parser::new(c).term_ref())
+                .collect::<Result<Vec<_>, String>>()
+                .map_err(|message| SkfuzzyError::Parse { rule: i, message })?;
+            let changed = current.len() != rule.consequent.len()
+                || current
+                    .iter()
+                    .zip(&rule.consequent)
+                    .any(|((v, t), c)| *v != c.var() || Some(t.as_str()) != c.term());
+            if changed {
+                def.consequent = rule
+                    .consequent
+                    .iter()
+                    .map(|c| match c.term() {
+                        Some(term) => Ok(format!("{}['{}']", c.var(), term)),
+                        None => Err(FuzzyError::TypeMismatch),
+                    })
+                    .collect::<Result<Vec<_>, _>>()?
+                    .join(", ");
+            }
+            if rule.weight != 1.0 || def.weight.is_some() {
+                def.weight = Some(rule.weight);
+            }
+        }
+        Ok(())
+    }
+
+    /// Renders the equivalent scikit-fuzzy Python script.
+    ///
+    /// The script ends with a `ControlSystemSimulation` named `simulation`.
+    pub fn to_python(&self) -> String {
+        let mut py = String::from(
+            "import numpy as np\nimport skfuzzy as fuzz\nfrom skfuzzy import control as ctrl\n\n",
+        );
+        let kinds = [
+            ("Antecedent", &self.antecedents),
+            ("Consequent", &self.consequents),
+        ];
+        for (kind, defs) in kinds {
+            for d in defs {
+                let u = d.universe;
+                let _ = writeln!(
+                    py,
+                    "{0} = ctrl.{kind}(np.arange({1}, {2}, {3}), '{0}')",
+                    d.label, u.start, u.stop, u.step
+                );
+                if let Some(m) = &d.defuzzify_method {
+                    let _ = writeln!(py, "{}.defuzzify_method = '{m}'", d.label);
+                }
+                for t in &d.terms {
+                    let params: Vec<String> = t.params.iter().map(Float::to_string).collect();
+                    let args = if t.mf == "gaussmf" {
+                        params.join(", ")
+                    } else {
+                        format!("[{}]", params.join(", "))
+                    };
+                    let _ = writeln!(
+                        py,
+                        "{0}['{1}'] = fuzz.{2}({0}.universe, {args})",
+                        d.label, t.name, t.mf
+                    );
+                }
+            }
+        }
+        py.push_str("\nrules = [\n");
+        for r in &self.rules {
+            let consequent = match r.weight {
+                Some(w) => r
+                    .consequent
+                    .split(',')
+                    .map(|c| format!("{} % {w}", c.trim()))
+                    .collect::<Vec<_>>()
+                    .join(", "),
+                None => r.consequent.trim().to_string(),
+            };
+            let _ = writeln!(
+                py,
+                "    ctrl.Rule({}, [{consequent}]),",
+                r.antecedent.trim()
+            );
+        }
+        py.push_str("]\nsimulation = ctrl.ControlSystemSimulation(ctrl.ControlSystem(rules))\n");
+        py
+    }
+}
+
+/// skfuzzy's piecewise-linear `trimf`/`trapmf`, which, unlike `Triangular` and
+/// `Trapezoidal`, allows coincident corners (shoulders such as `[0, 0, 5]`).
+struct Shoulder {
+    a: Float,
+    b: Float,
+    c: Float,
+    d: Float,
+}
+
+impl MembershipFn for Shoulder {
+    fn eval(&self, x: Float) -> Float {
+        if x < self.a || x > self.d {
+            0.0
+        } else if x < self.b {
+            (x - self.a) / (self.b - self.a)
+        } else if x <= self.c {
+            1.0
+        } else {
+            (self.d - x) / (self.d - self.c)
+        }
+    }
+}
+
+fn term(t: &TermDef) -> Result<Term, SkfuzzyError> {
+    let (name, p) = (t.name.as_str(), &t.params);
+    let ordered = p.windows(2).all(|w| w[0] <= w[1]);
+    match (t.mf.as_str(), p.len()) {
+        ("trimf", 3) if ordered => Ok(match Triangular::new(p[0], p[1], p[2]) {
+            Ok(tri) => Term::new(name, tri),
+            Err(_) => Term::new(
+                name,
+                Shoulder {
+                    a: p[0],
+                    b: p[1],
+                    c: p[1],
+                    d: p[2],
+                },
+            ),
+        }),
+        ("trapmf", 4) if ordered => Ok(match Trapezoidal::new(p[0], p[1], p[2], p[3]) {
+            Ok(trap) => Term::new(name, trap),
+            Err(_) => Term::new(
+                name,
+                Shoulder {
+                    a: p[0],
+                    b: p[1],
+                    c: p[2],
+                    d: p[3],
+                },
+            ),
+        }),
+        ("gaussmf", 2) => Ok(Term::new(name, Gaussian::with_mean_sd(p[0], p[1])?)),
+        ("trimf" | "trapmf" | "gaussmf", _) => Err(FuzzyError::BadArity.into()),
+        (other, _) => Err(SkfuzzyError::Unsupported(format!("membership {other:?}"))),
+    }
+}
+
+/// Recursive-descent parser for skfuzzy's rule syntax, with Python precedence:
+/// `~` binds tighter than `&`, which binds tighter than `|`.
+struct Parser<'a> {
+    src: &'a str,
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn new(src: &'a str) -> Self {
+        Self { src, pos: 0 }
+    }
+
+    fn antecedent(mut self) -> Result<Antecedent, String> {
+        let ant = self.or()?;
+        self.end()?;
+        Ok(ant)
+    }
+
+    fn term_ref(mut self) -> Result<(String, String), String> {
+        let r = self.atom()?;
+        self.end()?;
+        Ok(r)
+    }
+
+    fn end(&mut self) -> Result<(), String> {
+        self.skip_ws();
+        match self.peek() {
+            None => Ok(()),
+            Some(c) => Err(format!("unexpected {c:?} at {}", self.pos)),
+        }
+    }
+
+    fn or(&mut self) -> Result<Antecedent, String> {
+        let mut lhs = self.and()?;
+        while self.eat('|') {
+            lhs = Antecedent::Or(Box::new(lhs), Box::new(self.and()?));
+        }
+        Ok(lhs)
+    }
+
+    fn and(&mut self) -> Result<Antecedent, String> {
+        let mut lhs = self.unary()?;
+        while self.eat('&') {
+            lhs = Antecedent::And(Box::new(lhs), Box::new(self.unary()?));
+        }
+        Ok(lhs)
+    }
+
+    fn unary(&mut self) -> Result<Antecedent, String> {
+        if self.eat('~') {
+            return Ok(Antecedent::Not(Box::new(self.unary()?)));
+        }
+        if self.eat('(') {
+            let inner = self.or()?;
+            self.expect(')')?;
+            return Ok(inner);
+        }
+        let (var, term) = self.atom()?;
+        Ok(Antecedent::Atom {
+            var: var.into(),
+            term: term.into(),
+        })
+    }
+
+    /// `var['term']`, `var["term"]` or `var[term]`.
+    fn atom(&mut self) -> Result<(String, String), String> {
+        let var = self.ident()?;
+        self.expect('[')?;
+        self.skip_ws();
+        let term = match self.peek() {
+            Some(q @ ('\'' | '"')) => {
+                self.pos += 1;
+                let len = self.src[self.pos..]
+                    .find(q)
+                    .ok_or_else(|| format!("unterminated string at {}", self.pos))?;
+                let term = self.src[self.pos..self.pos + len].to_string();
+                self.pos += len + 1;
+                term
+            }
+            _ => self.ident()?,
+        };
+        self.expect(']')?;
+        Ok((var, term))
+    }
+
+    fn ident(&mut self) -> Result<String, String> {
+        self.skip_ws();
+        let start = self.pos;
+        while matches!(self.peek(), Some(c) if c.is_alphanumeric() || c == '_') {
+            self.pos += self.peek().map_or(0, char::len_utf8);
+        }
+        if start == self.pos {
+            return Err(format!("expected a name at {start}"));
+        }
+        Ok(self.src[start..self.pos].to_string())
+    }
+
+    fn expect(&mut self, c: char) -> Result<(), String> {
+        if self.eat(c) {
+            Ok(())
+        } else {
+            Err(format!("expected {c:?} at {}", self.pos))
+        }
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        self.skip_ws();
+        if self.peek() == Some(c) {
+            self.pos += c.len_utf8();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn skip_ws(&mut self) {
+        let rest = &self.src[self.pos..];
+        self.pos += rest.len() - rest.trim_start().len();
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.src[self.pos..].chars().next()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    // The tipping problem from the scikit-fuzzy documentation.
+    const TIPPING: &str = r#"{
+        "antecedents": [
+            {"label": "quality", "universe": {"start": 0, "stop": 11, "step": 1}, "terms": [
+                {"name": "poor", "mf": "trimf", "params": [0, 0, 5]},
+                {"name": "average", "mf": "trimf", "params": [0, 5, 10]},
+                {"name": "good", "mf": "trimf", "params": [5, 10, 10]}]},
+            {"label": "service", "universe": {"start": 0, "stop": 11, "step": 1}, "terms": [
+                {"name": "poor", "mf": "trimf", "params": [0, 0, 5]},
+                {"name": "average", "mf": "trimf", "params": [0, 5, 10]},
+                {"name": "good", "mf": "trimf", "params": [5, 10, 10]}]}
+        ],
+        "consequents": [
+            {"label": "tip", "universe": {"start": 0, "stop": 26, "step": 1}, "terms": [
+                {"name": "low", "mf": "trimf", "params": [0, 0, 13]},
+                {"name": "medium", "mf": "trimf", "params": [0, 13, 25]},
+                {"name": "high", "mf": "trimf", "params": [13, 25, 25]}]}
+        ],
+        "rules": [
+            {"antecedent": "quality['poor'] | service['poor']", "consequent": "tip['low']"},
+            {"antecedent": "service['average']", "consequent": "tip['medium']"},
+            {"antecedent": "service['good'] | quality['good']", "consequent": "tip['high']"}
+        ]
+    }"#;
+
+    #[test]
+    fn tipping_example_builds_and_evaluates() {
+        let cs = ControlSystem::from_json(TIPPING).unwrap();
+        let (space, sampler) = cs.build().unwrap();
+        assert_eq!(sampler.n, 26);
+        assert_eq!(space.var("tip").unwrap().domain(), (0.0, 25.0));
+
+        // Shoulder terms keep skfuzzy's semantics at the universe edges.
+        let quality = space.var("quality").unwrap();
+        assert_eq!(quality.eval("poor", 0.0).unwrap(), 1.0);
+        assert_eq!(quality.eval("good", 10.0).unwrap(), 1.0);
+        assert_eq!(quality.eval("poor", 2.5).unwrap(), 0.5);
+
+        let out = space
+            .evaluate(
+                &HashMap::from([("quality", 6.5), ("service", 9.8)]),
+                &sampler,
+            )
+            .unwrap();
+        // skfuzzy reports ~19.85 with its trapezoid-integrated centroid; the
+        // discrete centroid on the same grid lands close to it.
+        assert!((out["tip"] - 19.85).abs() < 0.5, "{}", out["tip"]);
+    }
+
+    #[test]
+    fn rule_syntax_follows_python_precedence() {
+        let atom = |v: &'static str, t: &'static str| Antecedent::Atom {
+            var: v.into(),
+            term: t.into(),
+        };
+        let ant = Parser::new("a['x'] | ~b[\"y\"] & (c[z] | a['w'])")
+            .antecedent()
+            .unwrap();
+        assert_eq!(
+            ant,
+            Antecedent::Or(
+                Box::new(atom("a", "x")),
+                Box::new(Antecedent::And(
+                    Box::new(Antecedent::Not(Box::new(atom("b", "y")))),
+                    Box::new(Antecedent::Or(
+                        Box::new(atom("c", "z")),
+                        Box::new(atom("a", "w"))
+                    )),
+                )),
+            )
+        );
+
+        assert!(Parser::new("a['x'] &").antecedent().is_err());
+        assert!(Parser::new("a['x'").antecedent().is_err());
+    }
+
+    #[test]
+    fn json_round_trip_and_python_script() {
+        let cs = ControlSystem::from_json(TIPPING).unwrap();
+        assert_eq!(ControlSystem::from_json(&cs.to_json()).unwrap(), cs);
+
+        let py = cs.to_python();
+        assert!(py.contains("quality = ctrl.Antecedent(np.arange(0, 11, 1), 'quality')"));
+        assert!(py.contains("tip['high'] = fuzz.trimf(tip.universe, [13, 25, 25])"));
+        assert!(py.contains("ctrl.Rule(service['average'], [tip['medium']]),"));
+    }
+
+    #[test]
+    fn rule_weights_build_and_write_back() {
+        let mut cs = ControlSystem::from_json(TIPPING).unwrap();
+        cs.rules[2].weight = Some(0.5);
+        assert_eq!(ControlSystem::from_json(&cs.to_json()).unwrap(), cs);
+        assert!(!cs.to_json().contains("\"weight\": 1"));
+        assert!(cs
+            .to_python()
+            .contains("ctrl.Rule(service['good'] | quality['good'], [tip['high'] % 0.5]),"));
+
+        let (mut space, _) = cs.build().unwrap();
+        assert_eq!(space.rules()[2].weight, 0.5);
+        assert_eq!(space.rules()[0].weight, 1.0);
+
+        // Tuned weights are written back; untouched unit weights stay absent.
+        space.rules[1].weight = 0.25;
+        space.rules[2].weight = 1.0;
+        cs.update_from(&space).unwrap();
+        let weights: Vec<_> = cs.rules.iter().map(|r| r.weight).collect();
+        assert_eq!(weights, [None, Some(0.25), Some(1.0)]);
+    }
+
+    #[test]
+    fn unsupported_definitions_are_rejected() {
+        let mut cs = ControlSystem::from_json(TIPPING).unwrap();
+        cs.consequents[0].defuzzify_method = Some("mom".into());
+        assert!(matches!(cs.build(), Err(SkfuzzyError::Unsupported(_))));
+
+        let mut cs = ControlSystem::from_json(TIPPING).unwrap();
+        cs.antecedents[0].terms[0].mf = "sigmf".into();
+        assert!(matches!(cs.build(), Err(SkfuzzyError::Unsupported(_))));
+
+        let mut cs = ControlSystem::from_json(TIPPING).unwrap();
+        cs.rules[0].antecedent = "quality['poor'] service['poor']".into();
+        assert!(matches!(
+            cs.build(),
+            Err(SkfuzzyError::Parse { rule: 0, .. })
+        ));
+    }
+
+    #[test]
+    fn tuned_parameters_write_back() {
+        let mut cs = ControlSystem::from_json(TIPPING).unwrap();
+        let (mut space, sampler) = cs.build().unwrap();
+        let v = space.var_id("service").unwrap().index();
+        let id = space.vars[v].term_id("average").unwrap();
+        space.vars[v]
+            .term_mut(id)
+            .unwrap()
+            .set_params(&[1.0, 6.0, 9.0])
+            .unwrap();
+        // A stale consequent in the definition is rewritten from the space.
+        cs.rules[1].consequent = "tip['high']".into();
+
+        cs.update_from(&space).unwrap();
+        assert_eq!(cs.antecedents[1].terms[1].params, vec![1.0, 6.0, 9.0]);
+        // Shoulders have no parameters to report and stay as written.
+        assert_eq!(cs.antecedents[1].terms[0].params, vec![0.0, 0.0, 5.0]);
+        assert_eq!(cs.rules[1].consequent, "tip['medium']");
+        assert_eq!(cs.rules[0].consequent, "tip['low']");
+
+        let (rebuilt, _) = cs.build().unwrap();
+        let input = HashMap::from([("quality", 6.5), ("service", 4.0)]);
+        assert_eq!(
+            rebuilt.evaluate(&input, &sampler).unwrap(),
+            space.evaluate(&input, &sampler).unwrap()
+        );
+
+        cs.rules.pop();
+        assert!(matches!(
+            cs.update_from(&space),
+            Err(SkfuzzyError::Fuzzy(FuzzyError::BadArity))
+        ));
+    }
+}
@@ -0,0 +1,259 @@
+// Partial evaluation for inputs that rarely change.
+//
+// Configuration-like inputs (a site's altitude, a product's grade) stay fixed
+// for thousands of evaluations while sensor inputs change every tick.
+// `RuleSpace::specialize` evaluates every atom on a fixed input once, folds the
+// resulting degrees through the antecedents, drops rules that can no longer
+// fire and removes the fixed variables, leaving a smaller space over the
+// remaining inputs that evaluates exactly like the original.
+use std::{borrow::Borrow, collections::HashMap, hash::Hash};
+
+use crate::{
+    antecedent::Antecedent,
+    error::{self, FuzzyError, MissingSpace},
+    mamdani::{Consequent, CrispExpr, Rule},
+    rulespace::RuleSpace,
+    Float,
+};
+
+impl RuleSpace {
+    /// Fixes the inputs in `fixed` and simplifies the space for the rest.
+    ///
+    /// Atoms on fixed inputs become `Antecedent::Const` degrees and are folded
+    /// with the default Min–Max operators of `evaluate`; crisp consequents absorb
+    /// the fixed inputs into their constant. Rules whose antecedent folds to
+    /// zero are removed, and so are the fixed variables. Evaluating the result
+    /// without the fixed inputs matches evaluating the original with them.
+    ///
+    /// Fails with `NotFound` for an unknown variable or an output (which is not
+    /// an input), `OutOfDomain` for a value outside its domain and `EmptyInput`
+    /// if no rule could fire any more; the space is unchanged on error.
+    pub fn specialize<KI>(&mut self, fixed: &HashMap<KI, Float>) -> error::Result<()>
+    where
+        KI: Eq + Hash + Borrow<str>,
+    {
+        let mut values = vec![None; self.vars.len()];
+        for (name, &x) in fixed {
+            let name = name.borrow();
+            let id = self.var_id(name).ok_or_else(|| FuzzyError::NotFound {
+                space: MissingSpace::Var,
+                key: name.to_string(),
+            })?;
+            if self.outputs.contains(&id) {
+                return Err(FuzzyError::NotFound {
+                    space: MissingSpace::Input,
+                    key: name.to_string(),
+                });
+            }
+            let (min, max) = self.vars[id.index()].domain();
+            if !(min..=max).contains(&x) {
+                return Err(FuzzyError::OutOfDomain {
+                    var: name.to_string(),
+                    value: x,
+                });
+            }
+            values[id.index()] = Some(x);
+        }
+
+        let mut rules = Vec::with_capacity(self.rules.len());
+        for rule in &self.rules {
+            let antecedent = self.fold(&rule.antecedent, &values)?;
+            if matches!(antecedent, Antecedent::Const(c) if c <= 0.0) {
+                continue;
+            }
+            let consequent = rule
+                .consequent
+                .iter()
+                .map(|c| match c {
+                    Consequent::Crisp { var, expr } => Consequent::Crisp {
+                        var: var.clone(),
+                        expr: self.fold_crisp(expr, &values),
+                    },
+                    c => c.clone(),
+                })
+                .collect();
+            rules.push(Rule {
+                antecedent,
+                consequent,
+                weight: rule.weight,
+            });
+        }
+        if rules.is_empty() {
+            return Err(FuzzyError::EmptyInput);
+        }
+
+        let names: Vec<String> = self.names.iter().map(str::to_string).collect();
+        let vars = names
+            .into_iter()
+            .zip(std::mem::take(&mut self.vars))
+            .zip(&values)
+            .filter(|(_, x)| x.is_none())
+            .map(|(pair, _)| pair)
+            .collect();
+        let threshold = self.metrics().map(|m| m.threshold);
+        // The folded rules name only kept variables and their terms, and
+        // conclude on the same outputs as before.
+        *self = RuleSpace::new(vars, rules).expect("folded rules stay valid");
+        if let Some(threshold) = threshold {
+            self.enable_counters(threshold);
+        }
+        Ok(())
+    }
+
+    /// `ant` with atoms on fixed inputs replaced by their degrees, folded.
+    fn fold(&self, ant: &Antecedent, values: &[Option<Float>]) -> error::Result<Antecedent> {
+        use Antecedent::Const;
+        Ok(match ant {
+            Antecedent::Atom { var, term } => {
+                let id = self
+                    .var_id(var)
+                    .expect("compiled rules name known variables");
+                match values[id.index()] {
+                    Some(x) => Const(self.vars[id.index()].eval(term, x)?),
+                    None => ant.clone(),
+                }
+            }
+            Antecedent::And(a, b) => match (self.fold(a, values)?, self.fold(b, values)?) {
+                (Const(x), Const(y)) => Const(x.min(y)),
+                (Const(c), e) | (e, Const(c)) if c >= 1.0 => e,
+                (Const(c), _) | (_, Const(c)) if c <= 0.0 => Const(0.0),
+                (a, b) => a.and(b),
+            },
+            Antecedent::Or(a, b) => match (self.fold(a, values)?, self.fold(b, values)?) {
+                (Const(x), Const(y)) => Const(x.max(y)),
+                (Const(c), e) | (e, Const(c)) if c <= 0.0 => e,
+                (Const(c), _) | (_, Const(c)) if c >= 1.0 => Const(1.0),
+                (a, b) => a.or(b),
+            },
+            Antecedent::Not(a) => match self.fold(a, values)? {
+                Const(c) => Const(1.0 - c),
+                a => Antecedent::Not(Box::new(a)),
+            },
+            Antecedent::Hedged(hedge, a) => match self.fold(a, values)? {
+                Const(c) => Const(hedge.apply(c)),
+                a => a.hedged(*hedge),
+            },
+            Antecedent::Mean(kind, operands) => {
+                let operands = operands
+                    .iter()
+                    .map(|a| self.fold(a, values))
+                    .collect::<error::Result<Vec<_>>>()?;
+                let consts: Option<Vec<Float>> = operands
+                    .iter()
+                    .map(|a| match a {
+                        Const(c) => Some(*c),
+                        _ => None,
+                    })
+                    .collect();
+                match consts {
+                    Some(xs) if !xs.is_empty() => Const(kind.apply(&xs)),
+                    _ => Antecedent::Mean(*kind, operands),
+                }
+            }
+            Const(c) => Const(*c),
+        })
+    }
+
+    /// `expr` with the terms of fixed inputs moved into the constant.
+    fn fold_crisp(&self, expr: &CrispExpr, values: &[Option<Float>]) -> CrispExpr {
+        let mut folded = CrispExpr::constant(expr.constant);
+        for (var, k) in &expr.coefficients {
+            let id = self
+                .var_id(var)
+                .expect("compiled rules name known variables");
+            match values[id.index()] {
+                Some(x) => folded.constant += k * x,
+                None => folded.coefficients.push((var.clone(), *k)),
+            }
+        }
+        folded
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::error::{FuzzyError, MissingSpace};
+    use crate::prelude::*;
+
+    const TEXT: &str = "
+        var grade 0 10
+          low: triangle(-10, 0, 10)
+          high: triangle(0, 10, 20)
+        var temp 0 40
+          cold: triangle(-40, 0, 40)
+          hot: triangle(0, 40, 80)
+        var fan 0 100
+          low: triangle(-100, 0, 100)
+          high: triangle(0, 100, 200)
+
+        IF grade IS high AND temp IS hot THEN fan IS high
+        IF grade IS low OR temp IS cold THEN fan IS low
+        IF very grade IS low AND NOT temp IS hot THEN fan IS low
+        IF mean(grade IS high, temp IS hot) THEN fan IS high
+    ";
+
+    #[test]
+    fn specialized_space_matches_the_original() {
+        let sampler = UniformSampler::default();
+        let original: RuleSpace = TEXT.parse().unwrap();
+        for grade in [0.0, 3.0, 10.0] {
+            let mut rs: RuleSpace = TEXT.parse().unwrap();
+            rs.specialize(&HashMap::from([("grade", grade)])).unwrap();
+            assert!(rs.var("grade").is_none());
+            assert_eq!(rs.inputs().collect::<Vec<_>>(), ["temp"]);
+            for temp in [0.0, 10.0, 25.0, 40.0] {
+                let all = HashMap::from([("grade", grade), ("temp", temp)]);
+                let want = original.evaluate(&all, &sampler).unwrap()["fan"];
+                let got = rs
+                    .evaluate(&HashMap::from([("temp", temp)]), &sampler)
+                    .unwrap()["fan"];
+                assert!((got - want).abs() < 1e-4, "{grade} {temp}: {got} {want}");
+            }
+        }
+
+        // With the grade at its maximum, "grade IS low" is 0: the OR reduces
+        // to its other operand, and the AND rule can never fire.
+        let mut rs: RuleSpace = TEXT.parse().unwrap();
+        rs.specialize(&HashMap::from([("grade", 10.0)])).unwrap();
+        let texts: Vec<String> = rs.rules().iter().map(|r| r.to_string()).collect();
+        assert_eq!(
+            texts,
+            [
+                "IF temp IS hot THEN fan IS high",
+                "IF temp IS cold THEN fan IS low",
+                "IF mean(1, temp IS hot) THEN fan IS high",
+            ]
+        );
+    }
+
+    #[test]
+    fn specialize_folds_crisp_consequents_and_rejects_bad_inputs() {
+        let mut rs: RuleSpace = TEXT.parse().unwrap();
+        assert!(matches!(
+            rs.specialize(&HashMap::from([("speed", 1.0)])),
+            Err(FuzzyError::NotFound { .. })
+        ));
+        assert!(matches!(
+            rs.specialize(&HashMap::from([("fan", 1.0)])),
+            Err(FuzzyError::NotFound { space: MissingSpace::Input, key }) if key == "fan"
+        ));
+        assert!(matches!(
+            rs.specialize(&HashMap::from([("grade", 11.0)])),
+            Err(FuzzyError::OutOfDomain { var, value }) if var == "grade" && value == 11.0
+        ));
+        assert_eq!(rs.rules().len(), 4);
+        assert!(rs.var("grade").is_some());
+
+        let text = format!(
+            "{TEXT}\nvar power 0 100\nIF temp IS hot THEN power = 2 * grade + 0.5 * temp + 1"
+        );
+        let mut system = System::new(text.parse().unwrap(), UniformSampler::default()).unwrap();
+        system.specialize(&HashMap::from([("grade", 4.0)])).unwrap();
+        let rule = system.space().rules().last().unwrap().to_string();
+        assert_eq!(rule, "IF temp IS hot THEN power = 9 + 0.5 * temp");
+        let out = system.evaluate(&HashMap::from([("temp", 40.0)])).unwrap();
+        assert!((out["power"] - 29.0).abs() < 1e-4);
+    }
+}
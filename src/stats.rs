@@ -0,0 +1,282 @@
+// Rule-base statistics.
+//
+// Before a controller goes onto a small target it helps to know how big it
+// really is. `RuleSpace::stats` summarizes the rule base without evaluating
+// anything: rule count, antecedent depth, which inputs each output depends on
+// (fan-in) and how many outputs each input drives (fan-out), how often every
+// term is referenced, and an operation count for one evaluation at the given
+// sampler resolution.
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt,
+};
+
+use crate::{
+    antecedent::{for_each_atom, Antecedent},
+    mamdani::Consequent,
+    rulespace::RuleSpace,
+    sampler::UniformSampler,
+    system::System,
+    Float,
+};
+
+/// Operations one `evaluate` call performs, counted from the rule base.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Cost {
+    /// Membership evaluations, one per antecedent atom.
+    pub memberships: usize,
+    /// AND, OR, NOT, hedge and mean applications in antecedents, plus
+    /// consequent hedges.
+    pub operators: usize,
+    /// Output grid points touched: the clipped set of every term consequent
+    /// and the centroid of every term-valued output, each over the grid.
+    pub grid_points: usize,
+    /// Multiply-adds of crisp consequents.
+    pub crisp_terms: usize,
+}
+
+impl Cost {
+    /// All counted operations together, a rough figure for comparing systems.
+    pub fn total(&self) -> usize {
+        self.memberships + self.operators + self.grid_points + self.crisp_terms
+    }
+}
+
+/// Summary of a rule base, see `RuleSpace::stats`.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Stats {
+    pub rules: usize,
+    /// Mean antecedent depth; an atom has depth 1 and every operator adds one.
+    pub mean_depth: Float,
+    pub max_depth: usize,
+    /// Number of distinct inputs the rules concluding on each output read.
+    pub fan_in: BTreeMap<String, usize>,
+    /// Number of distinct outputs each input is read for.
+    pub fan_out: BTreeMap<String, usize>,
+    /// References to every term of every variable, in declaration order,
+    /// unused terms included with a count of zero.
+    pub term_usage: BTreeMap<String, Vec<(String, usize)>>,
+    pub cost: Cost,
+}
+
+impl RuleSpace {
+    /// Summarizes the rule base; `sampler` sets the grid resolution the cost
+    /// estimate assumes.
+    pub fn stats(&self, sampler: &UniformSampler) -> Stats {
+        let mut usage: BTreeMap<&str, BTreeMap<&str, usize>> = BTreeMap::new();
+        let mut reads: BTreeMap<&str, BTreeSet<&str>> = BTreeMap::new();
+        let mut cost = Cost::default();
+        let mut depths = Vec::with_capacity(self.rules.len());
+        for rule in &self.rules {
+            let mut read = BTreeSet::new();
+            for_each_atom(&rule.antecedent, &mut |var, term| {
+                *usage.entry(var).or_default().entry(term).or_default() += 1;
+                read.insert(var);
+                cost.memberships += 1;
+            });
+            depths.push(depth(&rule.antecedent));
+            cost.operators += operators(&rule.antecedent);
+            for c in &rule.consequent {
+                let read = match c {
+                    Consequent::Term { var, term, hedge } => {
+                        *usage.entry(var).or_default().entry(term).or_default() += 1;
+                        cost.operators += usize::from(hedge.is_some());
+                        cost.grid_points += sampler.n;
+                        read.clone()
+                    }
+                    Consequent::Crisp { expr, .. } => {
+                        cost.crisp_terms += expr.coefficients.len();
+                        let mut read = read.clone();
+                        read.extend(expr.coefficients.iter().map(|(v, _)| v.as_ref()));
+                        read
+                    }
+                };
+                reads.entry(c.var()).or_default().extend(read);
+            }
+        }
+        cost.grid_points += sampler.n
+            * self
+                .outputs
+                .iter()
+                .filter(|v| !self.crisp[v.index()])
+                .count();
+
+        let mut fan_out: BTreeMap<String, usize> =
+            self.inputs().map(|name| (name.to_string(), 0)).collect();
+        for inputs in reads.values() {
+            for &input in inputs {
+                *fan_out.entry(input.to_string()).or_default() += 1;
+            }
+        }
+        let term_usage = self
+            .names
+            .iter()
+            .zip(&self.vars)
+            .map(|(name, var)| {
+                let counts = usage.get(name);
+                let terms = var
+                    .terms()
+                    .map(|(term, _)| {
+                        let n = counts.and_then(|c| c.get(term)).copied().unwrap_or(0);
+                        (term.to_string(), n)
+                    })
+                    .collect();
+                (name.to_string(), terms)
+            })
+            .collect();
+
+        Stats {
+            rules: self.rules.len(),
+            mean_depth: depths.iter().sum::<usize>() as Float / depths.len().max(1) as Float,
+            max_depth: depths.iter().copied().max().unwrap_or(0),
+            fan_in: self
+                .outputs()
+                .map(|name| (name.to_string(), reads.get(name).map_or(0, BTreeSet::len)))
+                .collect(),
+            fan_out,
+            term_usage,
+            cost,
+        }
+    }
+}
+
+impl System {
+    /// Summarizes the rule base at this system's resolution, see
+    /// `RuleSpace::stats`.
+    pub fn stats(&self) -> Stats {
+        self.space().stats(self.sampler())
+    }
+}
+
+/// Multi-line report, one line per statistic.
+impl fmt::Display for Stats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let list = |f: &mut fmt::Formatter<'_>, map: &BTreeMap<String, usize>| {
+            let items: Vec<String> = map.iter().map(|(k, n)| format!("{k} {n}")).collect();
+            writeln!(f, "{}", items.join(", "))
+        };
+        writeln!(f, "rules: {}", self.rules)?;
+        writeln!(
+            f,
+            "antecedent depth: mean {:.2}, max {}",
+            self.mean_depth, self.max_depth
+        )?;
+        write!(f, "fan-in: ")?;
+        list(f, &self.fan_in)?;
+        write!(f, "fan-out: ")?;
+        list(f, &self.fan_out)?;
+        let terms: Vec<String> = self
+            .term_usage
+            .iter()
+            .flat_map(|(var, terms)| terms.iter().map(move |(t, n)| format!("{var}.{t} {n}")))
+            .collect();
+        writeln!(f, "terms: {}", terms.join(", "))?;
+        let c = &self.cost;
+        write!(
+            f,
+            "cost: {} memberships, {} operators, {} grid points, {} crisp terms ({} total)",
+            c.memberships,
+            c.operators,
+            c.grid_points,
+            c.crisp_terms,
+            c.total()
+        )
+    }
+}
+
+fn depth(ant: &Antecedent) -> usize {
+    match ant {
+        Antecedent::Atom { .. } | Antecedent::Const(_) => 1,
+        Antecedent::And(a, b) | Antecedent::Or(a, b) => 1 + depth(a).max(depth(b)),
+        Antecedent::Not(a) | Antecedent::Hedged(_, a) => 1 + depth(a),
+        Antecedent::Mean(_, operands) => 1 + operands.iter().map(depth).max().unwrap_or(0),
+    }
+}
+
+fn operators(ant: &Antecedent) -> usize {
+    match ant {
+        Antecedent::Atom { .. } | Antecedent::Const(_) => 0,
+        Antecedent::And(a, b) | Antecedent::Or(a, b) => 1 + operators(a) + operators(b),
+        Antecedent::Not(a) | Antecedent::Hedged(_, a) => 1 + operators(a),
+        Antecedent::Mean(_, operands) => 1 + operands.iter().map(operators).sum::<usize>(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+
+    const TEXT: &str = "
+        var temp 0 40
+          cold: triangle(-40, 0, 40)
+          warm: triangle(0, 20, 40)
+          hot: triangle(0, 40, 80)
+        var load 0 1
+          low: triangle(-1, 0, 1)
+          high: triangle(0, 1, 2)
+        var fan 0 100
+          low: triangle(-100, 0, 100)
+          high: triangle(0, 100, 200)
+        var power 0 100
+
+        IF temp IS hot AND load IS high THEN fan IS very high
+        IF temp IS cold THEN fan IS low
+        IF NOT (temp IS cold OR temp IS hot) THEN power = 2 * load + 10
+    ";
+
+    #[test]
+    fn stats_summarize_the_rule_base() {
+        let rs: RuleSpace = TEXT.parse().unwrap();
+        let stats = rs.stats(&UniformSampler::new(11).unwrap());
+        assert_eq!(stats.rules, 3);
+        assert_eq!(stats.max_depth, 3);
+        assert!((stats.mean_depth - 2.0).abs() < 1e-6);
+        assert_eq!(stats.fan_in["fan"], 2);
+        assert_eq!(stats.fan_in["power"], 2);
+        assert_eq!(stats.fan_out["temp"], 2);
+        assert_eq!(stats.fan_out["load"], 2);
+        assert_eq!(
+            stats.term_usage["temp"],
+            [
+                ("cold".to_string(), 2),
+                ("warm".to_string(), 0),
+                ("hot".to_string(), 2)
+            ]
+        );
+        assert_eq!(stats.term_usage["fan"][1], ("high".to_string(), 1));
+        assert!(stats.term_usage["power"].is_empty());
+
+        // 5 atoms; AND, NOT, OR and the consequent hedge; two clipped sets and
+        // one centroid of 11 points; one crisp coefficient.
+        let cost = stats.cost;
+        assert_eq!(
+            (
+                cost.memberships,
+                cost.operators,
+                cost.grid_points,
+                cost.crisp_terms
+            ),
+            (5, 4, 33, 1)
+        );
+        assert_eq!(cost.total(), 43);
+    }
+
+    #[test]
+    fn system_stats_use_its_resolution_and_print_a_report() {
+        let rs: RuleSpace = TEXT.parse().unwrap();
+        let system = System::new(rs, UniformSampler::new(11).unwrap()).unwrap();
+        let report = system.stats().to_string();
+        let lines: Vec<&str> = report.lines().collect();
+        assert_eq!(lines[0], "rules: 3");
+        assert_eq!(lines[1], "antecedent depth: mean 2.00, max 3");
+        assert_eq!(lines[2], "fan-in: fan 2, power 2");
+        assert_eq!(lines[3], "fan-out: load 2, temp 2");
+        assert!(lines[4].starts_with("terms: fan.low 1, fan.high 1, load.low 0"));
+        assert_eq!(
+            lines[5],
+            "cost: 5 memberships, 4 operators, 33 grid points, 1 crisp terms (43 total)"
+        );
+    }
+}
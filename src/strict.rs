@@ -0,0 +1,283 @@
+// Numerically checked evaluation.
+//
+// `RuleSpace::evaluate_strict` runs the usual Mamdani pipeline but checks every
+// intermediate value on the way: crisp inputs must be finite, and every term
+// degree, firing strength and sampled consequent must lie in [0, 1]. The first
+// offending value is reported together with the rule and term that produced it,
+// which makes misbehaving custom membership functions or operators easy to
+// locate. It is slower than `evaluate` and meant for debugging and tests.
+use std::{borrow::Borrow, collections::HashMap, error::Error, fmt, hash::Hash};
+
+use crate::{
+    antecedent::eval_with,
+    error::{FuzzyError, MissingSpace},
+    membership::MembershipFn,
+    rulespace::RuleSpace,
+    sampler::{GridCache, UniformSampler},
+    Float,
+};
+
+/// Where in the pipeline a bad value showed up.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Phase {
+    /// A crisp input was NaN or infinite.
+    Input,
+    /// A term degree in an antecedent.
+    Antecedent,
+    /// A rule's firing strength.
+    Firing,
+    /// A consequent term sampled over its output domain.
+    Consequent,
+    /// A defuzzified output of a set some rule fired into.
+    Output,
+}
+
+/// A value outside [0, 1] (or non-finite) and where it came from.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NumericIssue {
+    pub phase: Phase,
+    /// Index of the rule being evaluated, if any.
+    pub rule: Option<usize>,
+    pub var: String,
+    pub term: Option<String>,
+    pub value: Float,
+}
+
+/// Failure of `RuleSpace::evaluate_strict`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum StrictError {
+    /// An ordinary evaluation error.
+    Fuzzy(FuzzyError),
+    /// A numeric check failed.
+    Numeric(NumericIssue),
+}
+
+impl fmt::Display for StrictError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StrictError::Fuzzy(e) => write!(f, "{e}"),
+            StrictError::Numeric(issue) => {
+                write!(
+                    f,
+                    "{:?} value {} for {}",
+                    issue.phase, issue.value, issue.var
+                )?;
+                if let Some(term) = &issue.term {
+                    write!(f, ".{term}")?;
+                }
+                if let Some(rule) = issue.rule {
+                    write!(f, " in rule {rule}")?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl Error for StrictError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            StrictError::Fuzzy(e) => Some(e),
+            StrictError::Numeric(_) => None,
+        }
+    }
+}
+
+impl From<FuzzyError> for StrictError {
+    fn from(e: FuzzyError) -> Self {
+        StrictError::Fuzzy(e)
+    }
+}
+
+fn in_unit(y: Float) -> bool {
+    (0.0..=1.0).contains(&y)
+}
+
+impl RuleSpace {
+    /// Evaluates `input` like `evaluate`, failing with `StrictError::Numeric` on
+    /// the first value that leaves [0, 1] or is not finite.
+    ///
+    /// Outputs no rule fired into are NaN as usual and not reported.
+    pub fn evaluate_strict<KI>(
+        &self,
+        input: &HashMap<KI, Float>,
+        sampler: &UniformSampler,
+    ) -> Result<HashMap<String, Float>, StrictError>
+    where
+        KI: Eq + Hash + Borrow<str>,
+    {
+        let issue = |phase, rule, var: &str, term: Option<&str>, value| {
+            StrictError::Numeric(NumericIssue {
+                phase,
+                rule,
+                var: var.to_string(),
+                term: term.map(str::to_string),
+                value,
+            })
+        };
+        for name in self.inputs() {
+            match input.get(name) {
+                Some(&x) if !x.is_finite() => return Err(issue(Phase::Input, None, name, None, x)),
+                _ => {}
+            }
+        }
+
+        let mut grids = GridCache::new();
+        let table = self.grid_table(sampler, &mut grids)?;
+        let mut inputs = Vec::with_capacity(self.vars.len());
+        self.resolve_inputs_into(input, &mut inputs);
+        let mut agg = Vec::new();
+        self.reset_agg(&table, &mut agg);
+        let mut clipped = Vec::new();
+
+        for (i, (rule, compiled)) in self.rules.iter().zip(&self.compiled).enumerate() {
+            let mut bad = None;
+            let mut atoms = compiled.atoms.iter();
+            let alpha = eval_with(&rule.antecedent, &mut |var, term| {
+                let &(v, t) = atoms.next().ok_or(FuzzyError::BadArity)?;
+                let x = inputs[v.index()].ok_or_else(|| FuzzyError::NotFound {
+                    space: MissingSpace::Input,
+                    key: var.to_string(),
+                })?;
+                let y = self.vars[v.index()].eval_id(t, x)?;
+                if bad.is_none() && !in_unit(y) {
+                    bad = Some(issue(Phase::Antecedent, Some(i), var, Some(term), y));
+                }
+                Ok(y)
+            })? * rule.weight;
+            if let Some(bad) = bad {
+                return Err(bad);
+            }
+            if !in_unit(alpha) {
+                let var = rule.consequent.first().map_or("", |c| c.var());
+                return Err(issue(Phase::Firing, Some(i), var, None, alpha));
+            }
+
+            let terms = rule.consequent.iter().filter(|c| c.term().is_some());
+            for (&(v, t), c) in compiled.consequent.iter().zip(terms) {
+                let xs = table[v.index()];
+                clipped.clear();
+                clipped.resize(xs.len(), 0.0);
+                if let Some(term) = self.vars[v.index()].term(t) {
+                    term.eval_grid(xs, &mut clipped);
+                }
+                if let Some(h) = c.hedge() {
+                    h.apply_all(&mut clipped);
+                }
+                if let Some(&y) = clipped.iter().find(|&&y| !in_unit(y)) {
+                    return Err(issue(Phase::Consequent, Some(i), c.var(), c.term(), y));
+                }
+                for (cur, y) in agg[v.index()].iter_mut().zip(&clipped) {
+                    *cur = cur.max(y.min(alpha));
+                }
+            }
+            for k in &compiled.crisp {
+                let y = k.eval(&inputs, &self.names)?;
+                if !y.is_finite() {
+                    let var = self.var_name(k.var);
+                    return Err(issue(Phase::Consequent, Some(i), var, None, y));
+                }
+                let acc = &mut agg[k.var.index()];
+                acc[0] += alpha * y;
+                acc[1] += alpha;
+            }
+        }
+
+        let mut out = HashMap::with_capacity(self.outputs.len());
+        for &v in &self.outputs {
+            let mu = &agg[v.index()];
+            let y = self.output_value(v, table[v.index()], mu);
+            let name = self.var_name(v);
+            if !y.is_finite() && mu.iter().any(|&m| m > 0.0) {
+                return Err(issue(Phase::Output, None, name, None, y));
+            }
+            out.insert(name.to_string(), y);
+        }
+        Ok(out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::{Phase, StrictError};
+    use crate::antecedent::Antecedent;
+    use crate::error::FuzzyError;
+    use crate::mamdani::{Consequent, Rule};
+    use crate::prelude::*;
+    use crate::rulespace::RuleSpace;
+    use crate::variable::Variable;
+
+    /// A membership function that overshoots 1 near its peak.
+    struct Overshoot;
+
+    impl MembershipFn for Overshoot {
+        fn eval(&self, x: Float) -> Float {
+            1.5 - (x - 5.0).abs() / 5.0
+        }
+    }
+
+    fn space(hot: Term) -> RuleSpace {
+        let mut temp = Variable::new(0.0, 10.0).unwrap();
+        temp.insert_term(
+            "cold",
+            Term::new("cold", Triangular::new(-10.0, 0.0, 10.0).unwrap()),
+        )
+        .unwrap();
+        temp.insert_term("hot", hot).unwrap();
+        let mut fan = Variable::new(0.0, 100.0).unwrap();
+        fan.insert_term(
+            "low",
+            Term::new("low", Triangular::new(-100.0, 0.0, 100.0).unwrap()),
+        )
+        .unwrap();
+        let rule = |t: &'static str| Rule {
+            antecedent: Antecedent::Atom {
+                var: "temp".into(),
+                term: t.into(),
+            },
+            consequent: vec![Consequent::Term {
+                var: "fan".into(),
+                term: "low".into(),
+                hedge: None,
+            }],
+            weight: 1.0,
+        };
+        let vars = HashMap::from([("temp".to_string(), temp), ("fan".to_string(), fan)]);
+        RuleSpace::new(vars, vec![rule("cold"), rule("hot")]).unwrap()
+    }
+
+    #[test]
+    fn strict_evaluation_matches_and_reports_culprits() {
+        let sampler = UniformSampler::default();
+        let good = space(Term::new("hot", Triangular::new(0.0, 10.0, 20.0).unwrap()));
+        let input = HashMap::from([("temp", 4.0)]);
+        let strict = good.evaluate_strict(&input, &sampler).unwrap()["fan"];
+        assert!((strict - good.evaluate(&input, &sampler).unwrap()["fan"]).abs() < 1e-6);
+
+        let bad = space(Term::new("hot", Overshoot));
+        match bad.evaluate_strict(&input, &sampler) {
+            Err(StrictError::Numeric(issue)) => {
+                assert_eq!(issue.phase, Phase::Antecedent);
+                assert_eq!(issue.rule, Some(1));
+                assert_eq!(issue.term.as_deref(), Some("hot"));
+                assert!(issue.value > 1.0);
+            }
+            other => panic!("expected a numeric issue, got {other:?}"),
+        }
+
+        let nan = HashMap::from([("temp", Float::NAN)]);
+        assert!(matches!(
+            good.evaluate_strict(&nan, &sampler),
+            Err(StrictError::Numeric(ref i)) if i.phase == Phase::Input
+        ));
+        assert_eq!(
+            good.evaluate_strict(&HashMap::from([("temp", 11.0)]), &sampler),
+            Err(StrictError::Fuzzy(FuzzyError::OutOfDomain {
+                var: "temp".into(),
+                value: 11.0
+            }))
+        );
+    }
+}
@@ -0,0 +1,280 @@
+// Control-surface generation.
+//
+// Sweeps one or two input variables across their domains while the remaining
+// inputs stay fixed, collecting every output on the resulting grid. This is the
+// usual way to review a controller's input/output behaviour at a glance.
+use std::{borrow::Borrow, collections::HashMap, hash::Hash, io};
+
+use crate::{
+    error::{self, FuzzyError, MissingSpace},
+    rulespace::RuleSpace,
+    sampler::{Sampler, UniformSampler},
+    Float,
+};
+
+/// One swept input: its name and the values it took, in ascending order.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Axis {
+    pub name: String,
+    pub values: Vec<Float>,
+}
+
+/// Output grid(s) produced by `RuleSpace::surface`.
+///
+/// Each output holds `x.values.len() * y.values.len()` samples (or just
+/// `x.values.len()` for a one-dimensional sweep), row-major with `x` varying fastest.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Surface {
+    pub x: Axis,
+    pub y: Option<Axis>,
+    /// Output name and its samples, in `RuleSpace::outputs` order.
+    pub outputs: Vec<(String, Vec<Float>)>,
+}
+
+impl Surface {
+    /// Samples of output `name`, if the rule space produced it.
+    pub fn output(&self, name: &str) -> Option<&[Float]> {
+        self.outputs
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, v)| v.as_slice())
+    }
+
+    /// Value of output `name` at grid cell (`i`, `j`); `j` is ignored for 1-D sweeps.
+    pub fn get(&self, name: &str, i: usize, j: usize) -> Option<Float> {
+        let nx = self.x.values.len();
+        if i >= nx {
+            return None;
+        }
+        let j = if self.y.is_some() { j } else { 0 };
+        self.output(name)?.get(j * nx + i).copied()
+    }
+
+    /// Output `name` at (`x`, `y`) interpolated from the grid: linearly along
+    /// each axis (bilinearly for 2-D sweeps), with points outside the axes
+    /// clamped to them. `y` is ignored for 1-D sweeps; axes with fewer than two
+    /// values give `None`.
+    pub fn interpolate(&self, name: &str, x: Float, y: Float) -> Option<Float> {
+        let short = |a: &Axis| a.values.len() < 2;
+        if short(&self.x) || self.y.as_ref().is_some_and(short) {
+            return None;
+        }
+        let values = self.output(name)?;
+        let (i, tx) = locate(&self.x.values, x);
+        let nx = self.x.values.len();
+        let at = |i: usize, j: usize| values[j * nx + i];
+        let row = |j: usize| at(i, j) + tx * (at(i + 1, j) - at(i, j));
+        match &self.y {
+            None => Some(row(0)),
+            Some(axis) => {
+                let (j, ty) = locate(&axis.values, y);
+                Some(row(j) + ty * (row(j + 1) - row(j)))
+            }
+        }
+    }
+
+    /// Writes the surface as CSV: one row per grid point, axes first, then outputs.
+    pub fn write_csv<W: io::Write>(&self, mut w: W) -> io::Result<()> {
+        let mut header = vec![self.x.name.as_str()];
+        header.extend(self.y.as_ref().map(|y| y.name.as_str()));
+        header.extend(self.outputs.iter().map(|(n, _)| n.as_str()));
+        writeln!(w, "{}", header.join(","))?;
+
+        let ys: &[Float] = self.y.as_ref().map_or(&[0.0], |y| &y.values);
+        let nx = self.x.values.len();
+        for (j, y) in ys.iter().enumerate() {
+            for (i, x) in self.x.values.iter().enumerate() {
+                write!(w, "{x}")?;
+                if self.y.is_some() {
+                    write!(w, ",{y}")?;
+                }
+                for (_, v) in &self.outputs {
+                    write!(w, ",{}", v[j * nx + i])?;
+                }
+                writeln!(w)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Index of the axis interval holding `v` (clamped) and `v`'s position in it.
+fn locate(axis: &[Float], v: Float) -> (usize, Float) {
+    let i = axis.partition_point(|&a| a <= v).clamp(1, axis.len() - 1) - 1;
+    let (lo, hi) = (axis[i], axis[i + 1]);
+    (i, ((v - lo) / (hi - lo)).clamp(0.0, 1.0))
+}
+
+impl RuleSpace {
+    /// Sweep input `x` (and optionally `y`) over `steps` evenly spaced points of
+    /// its domain, holding the other inputs at the values in `fixed`.
+    ///
+    /// Swept variables override any entry for them in `fixed`. Unknown axis names
+    /// fail with `NotFound`, `x == y` with `BadArity`, and `steps < 2` with `OutOfBounds`.
+    pub fn surface<KI>(
+        &self,
+        x: &str,
+        y: Option<&str>,
+        steps: usize,
+        fixed: &HashMap<KI, Float>,
+        sampler: &UniformSampler,
+    ) -> error::Result<Surface>
+    where
+        KI: Eq + Hash + Borrow<str>,
+    {
+        if y == Some(x) {
+            return Err(FuzzyError::BadArity);
+        }
+        let axis_sampler = UniformSampler::new(steps)?;
+        let axis = |name: &str| -> error::Result<Axis> {
+            let var = self.var(name).ok_or_else(|| FuzzyError::NotFound {
+                space: MissingSpace::Var,
+                key: name.to_string(),
+            })?;
+            let (min, max) = var.domain();
+            Ok(Axis {
+                name: name.to_string(),
+                values: axis_sampler.sample(min, max)?,
+            })
+        };
+        let x = axis(x)?;
+        let y = y.map(axis).transpose()?;
+
+        let mut input: HashMap<&str, Float> = fixed.iter().map(|(k, &v)| (k.borrow(), v)).collect();
+        let mut scratch = self.scratch(sampler)?;
+        let ys: &[Float] = y.as_ref().map_or(&[0.0], |y| &y.values);
+        let cells = x.values.len() * ys.len();
+        let mut outputs: Vec<(String, Vec<Float>)> = self
+            .outputs()
+            .map(|n| (n.to_string(), Vec::with_capacity(cells)))
+            .collect();
+
+        for &yv in ys {
+            if let Some(y) = &y {
+                input.insert(&y.name, yv);
+            }
+            for &xv in &x.values {
+                input.insert(&x.name, xv);
+                let out = self.evaluate_in(&input, &mut scratch)?;
+                for (name, values) in &mut outputs {
+                    values.push(out[name.as_str()]);
+                }
+            }
+        }
+
+        Ok(Surface { x, y, outputs })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::antecedent::Antecedent;
+    use crate::error::FuzzyError;
+    use crate::mamdani::{Consequent, Rule};
+    use crate::prelude::*;
+    use crate::rulespace::RuleSpace;
+    use crate::variable::Variable;
+    use crate::Float;
+
+    fn var(lo_name: &str, hi_name: &str) -> Variable {
+        let mut v = Variable::new(0.0, 10.0).unwrap();
+        v.insert_term(
+            lo_name,
+            Term::new(lo_name, Triangular::new(-10.0, 0.0, 10.0).unwrap()),
+        )
+        .unwrap();
+        v.insert_term(
+            hi_name,
+            Term::new(hi_name, Triangular::new(0.0, 10.0, 20.0).unwrap()),
+        )
+        .unwrap();
+        v
+    }
+
+    fn atom(var: &'static str, term: &'static str) -> Antecedent {
+        Antecedent::Atom {
+            var: var.into(),
+            term: term.into(),
+        }
+    }
+
+    fn space() -> RuleSpace {
+        let vars = HashMap::from([
+            ("a".to_string(), var("low", "high")),
+            ("b".to_string(), var("low", "high")),
+            ("out".to_string(), var("small", "large")),
+        ]);
+        let rules = vec![
+            Rule {
+                antecedent: Antecedent::And(
+                    Box::new(atom("a", "high")),
+                    Box::new(atom("b", "high")),
+                ),
+                consequent: vec![Consequent::Term {
+                    var: "out".into(),
+                    term: "large".into(),
+                    hedge: None,
+                }],
+                weight: 1.0,
+            },
+            Rule {
+                antecedent: Antecedent::Or(Box::new(atom("a", "low")), Box::new(atom("b", "low"))),
+                consequent: vec![Consequent::Term {
+                    var: "out".into(),
+                    term: "small".into(),
+                    hedge: None,
+                }],
+                weight: 1.0,
+            },
+        ];
+        RuleSpace::new(vars, rules).unwrap()
+    }
+
+    #[test]
+    fn two_axis_sweep_matches_pointwise_evaluation() {
+        let rs = space();
+        let sampler = UniformSampler::default();
+        let fixed: HashMap<&str, Float> = HashMap::new();
+        let s = rs.surface("a", Some("b"), 5, &fixed, &sampler).unwrap();
+
+        assert_eq!(s.x.values.len(), 5);
+        assert_eq!(s.output("out").unwrap().len(), 25);
+        for (j, &b) in s.y.as_ref().unwrap().values.iter().enumerate() {
+            for (i, &a) in s.x.values.iter().enumerate() {
+                let want = rs
+                    .evaluate(&HashMap::from([("a", a), ("b", b)]), &sampler)
+                    .unwrap()["out"];
+                let got = s.get("out", i, j).unwrap();
+                assert!((got - want).abs() < 1e-9 || (got.is_nan() && want.is_nan()));
+            }
+        }
+
+        let mut csv = Vec::new();
+        s.write_csv(&mut csv).unwrap();
+        let csv = String::from_utf8(csv).unwrap();
+        assert!(csv.starts_with("a,b,out\n"));
+        assert_eq!(csv.lines().count(), 26);
+    }
+
+    #[test]
+    fn one_axis_sweep_holds_other_inputs_fixed() {
+        let rs = space();
+        let fixed = HashMap::from([("b", 10.0)]);
+        let s = rs
+            .surface("a", None, 3, &fixed, &UniformSampler::default())
+            .unwrap();
+        assert!(s.y.is_none());
+        assert_eq!(s.output("out").unwrap().len(), 3);
+
+        assert!(matches!(
+            rs.surface("a", Some("a"), 3, &fixed, &UniformSampler::default()),
+            Err(FuzzyError::BadArity)
+        ));
+        assert!(matches!(
+            rs.surface("nope", None, 3, &fixed, &UniformSampler::default()),
+            Err(FuzzyError::NotFound { .. })
+        ));
+    }
+}
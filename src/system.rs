@@ -1 +1,140 @@
-//use crate::prelude::*;
+// System: a rule space bundled with its sampler and evaluation buffers.
+//
+// `RuleSpace` leaves the sampler and scratch buffers to the caller so one space
+// can serve many resolutions and threads. Most applications evaluate one
+// controller at one resolution, and `System` owns both so evaluating takes
+// nothing but the inputs.
+//
+// A built system is `Send + Sync`: evaluation through `&self` keeps all of its
+// buffers on the caller's stack, and the only shared state, the optional usage
+// counters, is atomic. Sharing one `Arc<System>` between request handlers needs
+// no lock.
+use std::{borrow::Borrow, collections::HashMap, hash::Hash};
+
+use crate::{
+    builder::SystemBuilder,
+    error,
+    rulespace::{RuleSpace, Scratch},
+    sampler::UniformSampler,
+    Float,
+};
+
+/// A rule space ready to evaluate, built with `System::builder` or `System::new`.
+///
+/// `evaluate` reuses owned buffers and needs `&mut self`; `evaluate_shared`
+/// takes `&self`, so one system behind an `Arc` can serve many threads.
+///
+/// ```rust
+/// # use std::collections::HashMap;
+/// use rust_fuzzylogic::prelude::*;
+///
+/// let mut system = System::builder()
+///     .variable("temp", 0.0, 10.0)
+///     .term("temp", "cold", Triangular::new(-10.0, 0.0, 10.0).unwrap())
+///     .term("temp", "hot", Triangular::new(0.0, 10.0, 20.0).unwrap())
+///     .variable("fan", 0.0, 100.0)
+///     .term("fan", "low", Triangular::new(-100.0, 0.0, 100.0).unwrap())
+///     .term("fan", "high", Triangular::new(0.0, 100.0, 200.0).unwrap())
+///     .rule(Rule::builder().when(atom("temp", "cold")).then("fan", "low").build().unwrap())
+///     .rule(Rule::builder().when(atom("temp", "hot")).then("fan", "high").build().unwrap())
+///     .build()
+///     .unwrap();
+/// let out = system.evaluate(&HashMap::from([("temp", 8.0)])).unwrap();
+/// assert!(out["fan"] > 50.0);
+/// ```
+pub struct System {
+    space: RuleSpace,
+    sampler: UniformSampler,
+    scratch: Scratch,
+}
+
+impl System {
+    /// Wraps `space`, evaluating its outputs on the grids `sampler` produces.
+    pub fn new(space: RuleSpace, sampler: UniformSampler) -> error::Result<Self> {
+        Ok(Self {
+            scratch: space.scratch(&sampler)?,
+            space,
+            sampler,
+        })
+    }
+
+    /// Starts assembling a system from variables, terms and rules.
+    pub fn builder() -> SystemBuilder {
+        SystemBuilder::default()
+    }
+
+    /// Evaluates `input`, returning one crisp value per output variable.
+    ///
+    /// Runs `RuleSpace::evaluate_in` on the owned buffers, so repeated calls do
+    /// not allocate; the returned map is reused by the next call. A missing
+    /// input is handled as in `RuleSpace::evaluate`.
+    pub fn evaluate<KI>(
+        &mut self,
+        input: &HashMap<KI, Float>,
+    ) -> error::Result<&HashMap<String, Float>>
+    where
+        KI: Eq + Hash + Borrow<str>,
+    {
+        self.space.evaluate_in(input, &mut self.scratch)
+    }
+
+    /// Evaluates `input` through a shared reference, e.g. from many threads
+    /// holding the same `Arc<System>`.
+    ///
+    /// Each call allocates its own buffers instead of locking the owned ones;
+    /// results match `evaluate`.
+    pub fn evaluate_shared<KI>(
+        &self,
+        input: &HashMap<KI, Float>,
+    ) -> error::Result<HashMap<String, Float>>
+    where
+        KI: Eq + Hash + Borrow<str>,
+    {
+        self.space.evaluate(input, &self.sampler)
+    }
+
+    /// Evaluates every input row, see `RuleSpace::evaluate_batch`.
+    pub fn evaluate_batch<KI>(
+        &self,
+        inputs: &[HashMap<KI, Float>],
+    ) -> error::Result<Vec<HashMap<String, Float>>>
+    where
+        KI: Eq + Hash + Borrow<str> + Sync,
+    {
+        self.space.evaluate_batch(inputs, &self.sampler)
+    }
+
+    /// Fixes the inputs in `fixed`, see `RuleSpace::specialize`; the sampler
+    /// is kept and the buffers are resized for the smaller space.
+    pub fn specialize<KI>(&mut self, fixed: &HashMap<KI, Float>) -> error::Result<()>
+    where
+        KI: Eq + Hash + Borrow<str>,
+    {
+        self.space.specialize(fixed)?;
+        self.scratch = self.space.scratch(&self.sampler)?;
+        Ok(())
+    }
+
+    /// The wrapped rule space.
+    pub fn space(&self) -> &RuleSpace {
+        &self.space
+    }
+
+    /// The sampler output grids are built with.
+    pub fn sampler(&self) -> &UniformSampler {
+        &self.sampler
+    }
+
+    /// Unwraps the rule space, dropping the sampler and buffers.
+    pub fn into_inner(self) -> RuleSpace {
+        self.space
+    }
+}
+
+// A system and everything it is built from can be shared across threads.
+const _: fn() = || {
+    fn shared<T: Send + Sync>() {}
+    shared::<System>();
+    shared::<RuleSpace>();
+    shared::<Scratch>();
+};
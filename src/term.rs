@@ -1,26 +1,38 @@
-use crate::membership::MembershipFn;
+use crate::membership::{MembershipFn, MembershipKind};
 
 //Type: Term holding a boxed membership function.
 pub type BoxedMembershipFn = Box<dyn MembershipFn + Send + Sync + 'static>;
 
+#[derive(Debug)]
 pub struct Term {
     name: String,
-    mf: BoxedMembershipFn,
+    mf: MembershipKind,
 }
 
 impl Term {
     ///Creates a new term label wrapper.
+    ///Built-in shapes are stored unboxed (see `MembershipKind::new`).
     pub fn new<S, M>(name: S, m: M) -> Self
     where
         S: Into<String>,
         M: MembershipFn + Send + Sync + 'static,
     {
+        Self::with_kind(name, MembershipKind::new(m))
+    }
+
+    ///Creates a term from an already wrapped shape.
+    pub fn with_kind<S: Into<String>>(name: S, kind: MembershipKind) -> Self {
         Self {
             name: name.into(),
-            mf: Box::new(m),
+            mf: kind,
         }
     }
 
+    ///Returns the wrapped membership shape.
+    pub fn kind(&self) -> &MembershipKind {
+        &self.mf
+    }
+
     ///Returns the labeled name.
     pub fn name(&self) -> &str {
         &self.name
@@ -32,6 +44,23 @@ impl crate::membership::MembershipFn for Term {
     fn eval(&self, x: crate::Float) -> crate::Float {
         self.mf.eval(x)
     }
+
+    ///Forwards grid evaluation so the wrapped shape's vectorized path is used.
+    fn eval_grid(&self, xs: &[crate::Float], out: &mut [crate::Float]) {
+        self.mf.eval_grid(xs, out)
+    }
+
+    fn params(&self) -> Vec<crate::Float> {
+        self.mf.params()
+    }
+
+    fn set_params(&mut self, params: &[crate::Float]) -> crate::error::Result<()> {
+        self.mf.set_params(params)
+    }
+
+    fn constrain_params(&self, params: &mut [crate::Float], min_gap: crate::Float) {
+        self.mf.constrain_params(params, min_gap)
+    }
 }
 
 #[cfg(test)]
@@ -49,6 +78,10 @@ mod tests {
         assert_eq!(term.name(), "cold");
         let x: crate::Float = 0.25;
         let y = term.eval(x);
-        assert!(y >= 0.0 && y <= 1.0);
+        assert!((0.0..=1.0).contains(&y));
+        assert!(matches!(
+            term.kind(),
+            crate::membership::MembershipKind::Triangular(_)
+        ));
     }
 }
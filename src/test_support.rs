@@ -0,0 +1,44 @@
+// Fixtures shared by the unit tests.
+use crate::{error::FuzzyError, rulespace::RuleSpace};
+
+/// `temp` over [0, 10] with `cold`/`hot` and `fan` over [0, 100] with
+/// `low`/`high`, each term a triangle reaching across the whole domain; the
+/// rules map cold to low and hot to high.
+pub(crate) const TEMP_FAN: &str = "
+    var temp 0 10
+      cold: triangle(-10, 0, 10)
+      hot: triangle(0, 10, 20)
+    var fan 0 100
+      low: triangle(-100, 0, 100)
+      high: triangle(0, 100, 200)
+    IF temp IS cold THEN fan IS low
+    IF temp IS hot THEN fan IS high
+";
+
+/// The rule space of `TEMP_FAN`.
+pub(crate) fn space() -> RuleSpace {
+    TEMP_FAN.parse().unwrap()
+}
+
+/// Rule base whose second rule concludes on `power` with a crisp expression,
+/// for the engines that only support term consequents.
+pub(crate) fn crisp_space() -> RuleSpace {
+    "
+    var temp 0 10
+      cold: triangle(-5, 0, 5)
+      hot: triangle(5, 10, 15)
+    var fan 0 100
+      low: triangle(0, 25, 50)
+      high: triangle(50, 75, 100)
+    var power 0 100
+    IF temp IS cold THEN fan IS low
+    IF temp IS hot THEN power = 5 * temp + 10
+    "
+    .parse()
+    .unwrap()
+}
+
+/// Whether `e` is the rejection of `crisp_space`'s crisp rule.
+pub(crate) fn rejects_crisp(e: &FuzzyError) -> bool {
+    matches!(e, FuzzyError::InRule { rule: 1, source } if **source == FuzzyError::TypeMismatch)
+}
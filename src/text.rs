@@ -0,0 +1,646 @@
+// Rule spaces written as text.
+//
+// A compact, line-oriented format meant to be edited by hand:
+//
+//     # Fan controller
+//     var temp 0 40
+//       cold: triangle(-20, 0, 20)
+//       hot:  triangle(20, 40, 60)
+//     var fan 0 100
+//       low:  triangle(-50, 0, 50)
+//       high: triangle(50, 100, 150)
+//
+//     IF temp IS cold THEN fan IS low
+//     IF temp IS very hot THEN fan IS high
+//
+// `var NAME MIN MAX` declares a variable (append `normalized` to sum-normalize
+// its degrees) and each `TERM: SHAPE` line below it adds a term whose shape is a
+// membership expression (see `parse_mf`). Every `IF` line is one rule in the
+// syntax `Rule` prints with, so rules round-trip through `Display`; a bare
+// number in a condition is a fixed degree and a trailing `WITH 0.8` weights
+// the rule. `#` starts a comment and keywords are case-insensitive.
+use std::{
+    fmt,
+    io::{self, BufRead},
+    str::FromStr,
+};
+
+use crate::{
+    antecedent::{Antecedent, Name},
+    error::FuzzyError,
+    hedges::Hedge,
+    mamdani::{Consequent, CrispExpr, Rule},
+    membership::parse_mf,
+    ops::Mean,
+    rulespace::RuleSpace,
+    term::Term,
+    variable::Variable,
+    Float,
+};
+
+/// Errors from reading a rule space as text, tagged with the 1-based line they came from.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum TextError {
+    /// The line does not follow the format.
+    Syntax {
+        line: usize,
+        message: String,
+    },
+    /// The line parsed but describes an invalid variable, term or rule (an
+    /// unknown term, a misordered shape, ...). `line` is 0 for errors about
+    /// the system as a whole, such as having no rules.
+    Fuzzy {
+        line: usize,
+        error: FuzzyError,
+    },
+    Io(io::Error),
+}
+
+impl fmt::Display for TextError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TextError::Syntax { line, message } => write!(f, "line {line}: {message}"),
+            TextError::Fuzzy { line, error } => write!(f, "line {line}: {error}"),
+            TextError::Io(e) => write!(f, "I/O error: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for TextError {}
+
+impl From<io::Error> for TextError {
+    fn from(e: io::Error) -> Self {
+        TextError::Io(e)
+    }
+}
+
+impl FromStr for RuleSpace {
+    type Err = TextError;
+
+    /// Parses a rule space in the text format described in `text`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut doc = Document::default();
+        for (i, line) in s.lines().enumerate() {
+            doc.line(i + 1, line)?;
+        }
+        doc.finish()
+    }
+}
+
+impl RuleSpace {
+    /// Reads a rule space in the text format line by line, see `text`.
+    pub fn from_reader<R: BufRead>(reader: R) -> Result<Self, TextError> {
+        let mut doc = Document::default();
+        for (i, line) in reader.lines().enumerate() {
+            doc.line(i + 1, &line?)?;
+        }
+        doc.finish()
+    }
+}
+
+/// Parses a single rule line such as `IF temp IS hot THEN fan IS high`.
+pub fn parse_rule(s: &str) -> Result<Rule, TextError> {
+    rule(1, s)
+}
+
+#[derive(Default)]
+struct Document {
+    vars: Vec<(String, Variable)>,
+    rules: Vec<Rule>,
+    /// Line each rule was read from, to place `InRule` errors.
+    rule_lines: Vec<usize>,
+}
+
+impl Document {
+    fn line(&mut self, line: usize, text: &str) -> Result<(), TextError> {
+        let text = text.split('#').next().unwrap_or_default().trim();
+        if text.is_empty() {
+            return Ok(());
+        }
+        let keyword = text.split_whitespace().next().unwrap_or_default();
+        if keyword.eq_ignore_ascii_case("if") {
+            self.rules.push(rule(line, text)?);
+            self.rule_lines.push(line);
+        } else if keyword.eq_ignore_ascii_case("var") {
+            self.var(line, &text[keyword.len()..])?;
+        } else if let Some((name, shape)) = text.split_once(':') {
+            self.term(line, name.trim(), shape)?;
+        } else {
+            return Err(syntax(
+                line,
+                format!("expected `var`, a `term: shape` line or a rule, found {keyword:?}"),
+            ));
+        }
+        Ok(())
+    }
+
+    fn var(&mut self, line: usize, rest: &str) -> Result<(), TextError> {
+        let fields: Vec<&str> = rest.split_whitespace().collect();
+        let (name, min, max, normalized) = match fields.as_slice() {
+            [name, min, max] => (*name, *min, *max, false),
+            [name, min, max, flag] if flag.eq_ignore_ascii_case("normalized") => {
+                (*name, *min, *max, true)
+            }
+            _ => return Err(syntax(line, "expected `var NAME MIN MAX [normalized]`")),
+        };
+        if !is_ident(name) {
+            return Err(syntax(line, format!("invalid variable name {name:?}")));
+        }
+        if self.vars.iter().any(|(n, _)| n == name) {
+            return Err(fuzzy(line, FuzzyError::TypeMismatch));
+        }
+        let bound = |s: &str| {
+            s.parse::<Float>()
+                .map_err(|_| syntax(line, format!("invalid bound {s:?}")))
+        };
+        let mut var = Variable::new(bound(min)?, bound(max)?)
+            .map_err(|e| fuzzy(line, e))?
+            .with_name(name);
+        var.set_normalized(normalized);
+        self.vars.push((name.to_string(), var));
+        Ok(())
+    }
+
+    fn term(&mut self, line: usize, name: &str, shape: &str) -> Result<(), TextError> {
+        if !is_ident(name) {
+            return Err(syntax(line, format!("invalid term name {name:?}")));
+        }
+        let Some((_, var)) = self.vars.last_mut() else {
+            return Err(syntax(line, "term before any `var`"));
+        };
+        let kind = parse_mf(shape).map_err(|e| syntax(line, e.message))?;
+        var.insert_term(name, Term::with_kind(name, kind))
+            .map_err(|e| fuzzy(line, e))
+    }
+
+    fn finish(self) -> Result<RuleSpace, TextError> {
+        let rule_lines = self.rule_lines;
+        RuleSpace::new(self.vars.into_iter().collect(), self.rules).map_err(|e| match e {
+            FuzzyError::InRule { rule, .. } => fuzzy(rule_lines[rule], e),
+            e => fuzzy(0, e),
+        })
+    }
+}
+
+fn syntax(line: usize, message: impl Into<String>) -> TextError {
+    TextError::Syntax {
+        line,
+        message: message.into(),
+    }
+}
+
+fn fuzzy(line: usize, error: FuzzyError) -> TextError {
+    TextError::Fuzzy { line, error }
+}
+
+fn is_ident(s: &str) -> bool {
+    let mut chars = s.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Ident(String),
+    Number(Float),
+    Punct(char),
+}
+
+fn tokenize(line: usize, s: &str) -> Result<Vec<Token>, TextError> {
+    let mut tokens = Vec::new();
+    let mut chars = s.char_indices().peekable();
+    while let Some(&(start, c)) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+        } else if c.is_ascii_alphabetic() || c == '_' {
+            let mut end = start;
+            while let Some(&(i, c)) = chars.peek() {
+                if !(c.is_ascii_alphanumeric() || c == '_') {
+                    break;
+                }
+                end = i + c.len_utf8();
+                chars.next();
+            }
+            tokens.push(Token::Ident(s[start..end].to_string()));
+        } else if c.is_ascii_digit() || c == '.' {
+            let mut end = start;
+            let mut prev = c;
+            while let Some(&(i, c)) = chars.peek() {
+                let exponent_sign = (c == '-' || c == '+') && matches!(prev, 'e' | 'E');
+                if !(c.is_ascii_digit() || matches!(c, '.' | 'e' | 'E') || exponent_sign) {
+                    break;
+                }
+                end = i + 1;
+                prev = c;
+                chars.next();
+            }
+            let n = s[start..end]
+                .parse()
+                .map_err(|_| syntax(line, format!("invalid number {:?}", &s[start..end])))?;
+            tokens.push(Token::Number(n));
+        } else if "(),*+-=".contains(c) {
+            tokens.push(Token::Punct(c));
+            chars.next();
+        } else {
+            return Err(syntax(line, format!("unexpected character {c:?}")));
+        }
+    }
+    Ok(tokens)
+}
+
+fn rule(line: usize, s: &str) -> Result<Rule, TextError> {
+    let tokens = tokenize(line, s)?;
+    let mut p = RuleParser {
+        line,
+        tokens: &tokens,
+        pos: 0,
+    };
+    p.keyword("IF")?;
+    let antecedent = p.or()?;
+    p.keyword("THEN")?;
+    let mut consequent = vec![p.consequent()?];
+    while p.eat_keyword("AND") {
+        consequent.push(p.consequent()?);
+    }
+    let mut weight = 1.0;
+    if p.eat_keyword("WITH") {
+        weight = p.number()?;
+        if !(0.0..=1.0).contains(&weight) {
+            return Err(p.error(format!("weight {weight} is outside [0, 1]")));
+        }
+    }
+    if let Some(t) = p.peek() {
+        return Err(p.error(format!("unexpected {t:?} after the rule")));
+    }
+    Ok(Rule {
+        antecedent,
+        consequent,
+        weight,
+    })
+}
+
+struct RuleParser<'a> {
+    line: usize,
+    tokens: &'a [Token],
+    pos: usize,
+}
+
+impl RuleParser<'_> {
+    fn error(&self, message: impl Into<String>) -> TextError {
+        syntax(self.line, message)
+    }
+
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn peek_at(&self, offset: usize) -> Option<&Token> {
+        self.tokens.get(self.pos + offset)
+    }
+
+    fn is_keyword(token: Option<&Token>, keyword: &str) -> bool {
+        matches!(token, Some(Token::Ident(s)) if s.eq_ignore_ascii_case(keyword))
+    }
+
+    fn eat_keyword(&mut self, keyword: &str) -> bool {
+        let found = Self::is_keyword(self.peek(), keyword);
+        self.pos += found as usize;
+        found
+    }
+
+    fn keyword(&mut self, keyword: &str) -> Result<(), TextError> {
+        if self.eat_keyword(keyword) {
+            Ok(())
+        } else {
+            Err(self.error(format!("expected {keyword}, found {:?}", self.peek())))
+        }
+    }
+
+    fn eat_punct(&mut self, c: char) -> bool {
+        let found = self.peek() == Some(&Token::Punct(c));
+        self.pos += found as usize;
+        found
+    }
+
+    fn punct(&mut self, c: char) -> Result<(), TextError> {
+        if self.eat_punct(c) {
+            Ok(())
+        } else {
+            Err(self.error(format!("expected {c:?}, found {:?}", self.peek())))
+        }
+    }
+
+    fn ident(&mut self) -> Result<Name, TextError> {
+        match self.peek() {
+            Some(Token::Ident(s)) => {
+                let name = Name::from(s.clone());
+                self.pos += 1;
+                Ok(name)
+            }
+            t => Err(self.error(format!("expected a name, found {t:?}"))),
+        }
+    }
+
+    fn number(&mut self) -> Result<Float, TextError> {
+        let negative = self.eat_punct('-');
+        match self.peek() {
+            Some(&Token::Number(n)) => {
+                self.pos += 1;
+                Ok(if negative { -n } else { n })
+            }
+            t => Err(self.error(format!("expected a number, found {t:?}"))),
+        }
+    }
+
+    fn or(&mut self) -> Result<Antecedent, TextError> {
+        let mut a = self.and()?;
+        while self.eat_keyword("OR") {
+            a = a.or(self.and()?);
+        }
+        Ok(a)
+    }
+
+    fn and(&mut self) -> Result<Antecedent, TextError> {
+        let mut a = self.unary()?;
+        while self.eat_keyword("AND") {
+            a = a.and(self.unary()?);
+        }
+        Ok(a)
+    }
+
+    /// A hedge written before `IS`-less operands: `very`, `power(2)`, ...
+    fn hedge(&mut self) -> Result<Option<Hedge>, TextError> {
+        let Some(Token::Ident(name)) = self.peek() else {
+            return Ok(None);
+        };
+        if name.eq_ignore_ascii_case("power") && self.peek_at(1) == Some(&Token::Punct('(')) {
+            self.pos += 2;
+            let p = self.number()?;
+            self.punct(')')?;
+            let hedge = Hedge::power(p).map_err(|e| self.error(format!("power: {e}")))?;
+            return Ok(Some(hedge));
+        }
+        let hedge = Hedge::from_name(name);
+        self.pos += hedge.is_some() as usize;
+        Ok(hedge)
+    }
+
+    fn mean_kind(&mut self) -> Result<Option<Mean>, TextError> {
+        let word = |p: &Self, i, w| Self::is_keyword(p.peek_at(i), w);
+        let open = |p: &Self, i| p.peek_at(i) == Some(&Token::Punct('('));
+        if word(self, 0, "mean") && open(self, 1) {
+            self.pos += 1;
+            return Ok(Some(Mean::Arithmetic));
+        }
+        for (name, kind) in [("geometric", Mean::Geometric), ("harmonic", Mean::Harmonic)] {
+            if word(self, 0, name) && word(self, 1, "mean") && open(self, 2) {
+                self.pos += 2;
+                return Ok(Some(kind));
+            }
+        }
+        if word(self, 0, "power") && word(self, 1, "mean") && open(self, 2) {
+            self.pos += 3;
+            let p = self.number()?;
+            self.punct(')')?;
+            return Ok(Some(Mean::Power(p)));
+        }
+        Ok(None)
+    }
+
+    fn unary(&mut self) -> Result<Antecedent, TextError> {
+        if self.eat_keyword("NOT") {
+            return Ok(Antecedent::Not(Box::new(self.unary()?)));
+        }
+        if self.eat_punct('(') {
+            let a = self.or()?;
+            self.punct(')')?;
+            return Ok(a);
+        }
+        // `var IS ...` is an atom even when `var` is spelled like a hedge.
+        if Self::is_keyword(self.peek_at(1), "IS") {
+            let var = self.ident()?;
+            self.pos += 1;
+            let hedge = self.hedge()?;
+            let atom = Antecedent::Atom {
+                var,
+                term: self.ident()?,
+            };
+            return Ok(match hedge {
+                Some(h) => Antecedent::Hedged(h, Box::new(atom)),
+                None => atom,
+            });
+        }
+        if let Some(kind) = self.mean_kind()? {
+            self.punct('(')?;
+            let mut operands = vec![self.or()?];
+            while self.eat_punct(',') {
+                operands.push(self.or()?);
+            }
+            self.punct(')')?;
+            return Ok(Antecedent::Mean(kind, operands));
+        }
+        if let Some(h) = self.hedge()? {
+            return Ok(Antecedent::Hedged(h, Box::new(self.unary()?)));
+        }
+        if let Some(&Token::Number(c)) = self.peek() {
+            if !(0.0..=1.0).contains(&c) {
+                return Err(self.error(format!("degree {c} is outside [0, 1]")));
+            }
+            self.pos += 1;
+            return Ok(Antecedent::Const(c));
+        }
+        Err(self.error(format!("expected a condition, found {:?}", self.peek())))
+    }
+
+    fn consequent(&mut self) -> Result<Consequent, TextError> {
+        let var = self.ident()?;
+        if self.eat_punct('=') {
+            return Ok(Consequent::Crisp {
+                var,
+                expr: self.crisp()?,
+            });
+        }
+        self.keyword("IS")?;
+        let hedge = self.hedge()?;
+        Ok(Consequent::Term {
+            var,
+            term: self.ident()?,
+            hedge,
+        })
+    }
+
+    /// `c + k * var - k * var ...`; a bare `var` has coefficient 1.
+    fn crisp(&mut self) -> Result<CrispExpr, TextError> {
+        let mut expr = CrispExpr::constant(0.0);
+        let mut sign = if self.eat_punct('-') { -1.0 } else { 1.0 };
+        loop {
+            match self.peek() {
+                Some(&Token::Number(n)) => {
+                    self.pos += 1;
+                    if self.eat_punct('*') {
+                        expr.coefficients.push((self.ident()?, sign * n));
+                    } else {
+                        expr.constant += sign * n;
+                    }
+                }
+                // A name followed by AND starts the next consequent, not a term.
+                Some(Token::Ident(_)) if !Self::is_keyword(self.peek(), "AND") => {
+                    expr.coefficients.push((self.ident()?, sign));
+                }
+                t => return Err(self.error(format!("expected a number or name, found {t:?}"))),
+            }
+            sign = if self.eat_punct('+') {
+                1.0
+            } else if self.eat_punct('-') {
+                -1.0
+            } else {
+                return Ok(expr);
+            };
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::{parse_rule, TextError};
+    use crate::error::FuzzyError;
+    use crate::mamdani::CrispExpr;
+    use crate::prelude::*;
+
+    const FAN: &str = "
+        # Fan controller
+        var temp 0 40
+          cold: triangle(-20, 0, 20)
+          hot:  triangle(20, 40, 60)   # saturates at 40
+        var fan 0 100
+          low:  triangle(-50, 0, 50)
+          high: triangle(50, 100, 150)
+        var pump 0 10
+
+        IF temp IS cold THEN fan IS low
+        if temp is very hot then fan is high and pump = 1 + 0.2 * temp
+    ";
+
+    #[test]
+    fn text_builds_an_equivalent_rule_space() {
+        let rs: RuleSpace = FAN.parse().unwrap();
+        assert_eq!(rs.rules().len(), 2);
+        assert_eq!(
+            rs.rules()[1].to_string(),
+            "IF temp IS very hot THEN fan IS high AND pump = 1 + 0.2 * temp"
+        );
+
+        let rule = |t: &'static str, f: &'static str| {
+            Rule::builder()
+                .when(atom("temp", t))
+                .then("fan", f)
+                .build()
+                .unwrap()
+        };
+        let mut temp = Variable::new(0.0, 40.0).unwrap();
+        temp.insert_term(
+            "cold",
+            Term::new("cold", Triangular::new(-20.0, 0.0, 20.0).unwrap()),
+        )
+        .unwrap();
+        temp.insert_term(
+            "hot",
+            Term::new("hot", Triangular::new(20.0, 40.0, 60.0).unwrap()),
+        )
+        .unwrap();
+        let mut fan = Variable::new(0.0, 100.0).unwrap();
+        fan.insert_term(
+            "low",
+            Term::new("low", Triangular::new(-50.0, 0.0, 50.0).unwrap()),
+        )
+        .unwrap();
+        fan.insert_term(
+            "high",
+            Term::new("high", Triangular::new(50.0, 100.0, 150.0).unwrap()),
+        )
+        .unwrap();
+        let mut hot = rule("hot", "high");
+        hot.antecedent = atom("temp", "hot").hedged(Hedge::Very);
+        hot.consequent.push(Consequent::crisp(
+            "pump",
+            CrispExpr::linear(1.0, [("temp", 0.2)]),
+        ));
+        let vars = HashMap::from([
+            ("temp".to_string(), temp),
+            ("fan".to_string(), fan),
+            ("pump".to_string(), Variable::new(0.0, 10.0).unwrap()),
+        ]);
+        let built = RuleSpace::new(vars, vec![rule("cold", "low"), hot]).unwrap();
+        assert_eq!(rs.rules(), built.rules());
+        assert_eq!(rs.fingerprint(), built.fingerprint());
+
+        let from_reader = RuleSpace::from_reader(FAN.as_bytes()).unwrap();
+        assert_eq!(from_reader.fingerprint(), rs.fingerprint());
+    }
+
+    #[test]
+    fn rules_round_trip_through_display() {
+        let texts = [
+            "IF a IS x AND (b IS y OR NOT c IS z) THEN out IS somewhat high",
+            "IF very (a IS x OR b IS y) THEN out IS power(3) low",
+            "IF geometric mean(a IS x, b IS y) AND power mean(2)(a IS x, c IS z) THEN out IS y",
+            "IF a IS power(0.5) x THEN out = -2 * a + 3 * b AND other IS x",
+            "IF very IS x THEN out = a",
+            "IF 0.25 AND NOT a IS x THEN out IS y",
+            "IF a IS x THEN out IS y AND pump = 2 * a WITH 0.5",
+        ];
+        for text in texts {
+            let rule = parse_rule(text).unwrap();
+            assert_eq!(parse_rule(&rule.to_string()).unwrap(), rule, "{text}");
+        }
+        let rule = parse_rule(texts[3]).unwrap();
+        assert_eq!(
+            rule.consequent[0],
+            Consequent::crisp("out", CrispExpr::linear(0.0, [("a", -2.0), ("b", 3.0)]))
+        );
+        assert_eq!(parse_rule(texts[4]).unwrap().antecedent, atom("very", "x"));
+        assert_eq!(parse_rule(texts[6]).unwrap().weight, 0.5);
+        assert_eq!(parse_rule(texts[0]).unwrap().weight, 1.0);
+    }
+
+    #[test]
+    fn errors_name_the_offending_line() {
+        let line_of = |text: &str| match text.parse::<RuleSpace>() {
+            Err(TextError::Syntax { line, .. } | TextError::Fuzzy { line, .. }) => line,
+            Err(e) => panic!("{e:?}"),
+            Ok(_) => panic!("{text:?} parsed"),
+        };
+        assert_eq!(line_of("var t 0 1\n  x: triangle(0, 1)"), 2);
+        assert_eq!(line_of("x: triangle(0, 1, 2)"), 1);
+        assert_eq!(line_of("var t 0\n"), 1);
+        assert_eq!(line_of("var t 0 1\nvar t 0 1"), 2);
+        assert_eq!(
+            line_of("var t 0 1\n  x: triangle(0, 1, 2)\n\nIF t IS x THEN"),
+            4
+        );
+        assert_eq!(
+            line_of("var t 0 1\n  x: triangle(0, 1, 2)\nIF t IS x THEN t IS y"),
+            3
+        );
+        assert!(matches!(
+            "var t 0 1\n  x: triangle(2, 1, 0)".parse::<RuleSpace>(),
+            Err(TextError::Syntax { line: 2, .. })
+        ));
+        assert!(matches!(
+            "var t 0 1".parse::<RuleSpace>(),
+            Err(TextError::Fuzzy {
+                line: 0,
+                error: FuzzyError::EmptyInput
+            })
+        ));
+        assert!(parse_rule("IF a IS x THEN b IS y extra").is_err());
+        assert!(parse_rule("IF 1.5 THEN b IS y").is_err());
+        assert!(parse_rule("IF a IS x THEN b IS y WITH 1.5").is_err());
+        assert!(parse_rule("IF a IS x THEN b IS y WITH").is_err());
+    }
+}
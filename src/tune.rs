@@ -0,0 +1,368 @@
+// Gradient-descent tuning of membership parameters against labelled data.
+//
+// The loss is the mean squared error of the defuzzified outputs, each scaled by its
+// output domain so variables of different ranges weigh alike. Gradients are taken
+// by central finite differences of that loss with respect to every parameter that
+// a term exposes through `MembershipFn::params`, in coordinates scaled by the
+// owning variable's domain width. After each step `constrain_params` restores the
+// shape invariants (apex ordering, positive widths).
+use std::{borrow::Borrow, collections::HashMap, hash::Hash};
+
+use crate::{
+    error::{self, FuzzyError, MissingSpace},
+    intern::TermId,
+    membership::MembershipFn,
+    rulespace::{RuleSpace, Scratch},
+    sampler::UniformSampler,
+    Float,
+};
+
+/// Finite-difference step, as a fraction of the variable's domain width.
+pub(crate) const FD_STEP: Float = 1e-3;
+
+/// Training knobs for `RuleSpace::tune`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TuneOptions {
+    /// Number of gradient steps.
+    pub epochs: usize,
+    /// Initial step size in domain-normalized coordinates; halved whenever a step
+    /// would increase the loss.
+    pub learning_rate: Float,
+    /// Minimum distance kept between ordered parameters (and the minimum Gaussian
+    /// width), as a fraction of the variable's domain width.
+    pub min_gap: Float,
+}
+
+impl Default for TuneOptions {
+    fn default() -> Self {
+        Self {
+            epochs: 100,
+            learning_rate: 0.1,
+            min_gap: 1e-2,
+        }
+    }
+}
+
+/// One training row: crisp inputs and the expected outputs, both keyed by variable name.
+pub type LabeledRow<K> = (HashMap<K, Float>, HashMap<K, Float>);
+
+/// One tunable term and the width used to scale its parameters.
+pub(crate) struct Slot {
+    var: usize,
+    term: TermId,
+    pub(crate) width: Float,
+}
+
+impl RuleSpace {
+    /// Tunes the parameters of every term that exposes them to minimize the
+    /// domain-scaled mean squared error on `data`, given as `(inputs, targets)` rows.
+    ///
+    /// Targets may name any subset of the outputs; unknown target names fail with
+    /// `NotFound`. Rows where an output is undefined (no rule fired) count as the
+    /// largest possible error. Steps that would increase the loss are rejected, so
+    /// the returned per-epoch losses never increase.
+    pub fn tune<KI>(
+        &mut self,
+        data: &[LabeledRow<KI>],
+        sampler: &UniformSampler,
+        opts: TuneOptions,
+    ) -> error::Result<Vec<Float>>
+    where
+        KI: Eq + Hash + Borrow<str>,
+    {
+        if data.is_empty() {
+            return Err(FuzzyError::EmptyInput);
+        }
+        let slots = self.tune_slots();
+
+        let mut scratch = self.scratch(sampler)?;
+        let mut loss = self.tune_loss(data, &mut scratch)?;
+        let mut lr = opts.learning_rate;
+        let mut history = Vec::with_capacity(opts.epochs);
+
+        for _ in 0..opts.epochs {
+            let current: Vec<Vec<Float>> = slots.iter().map(|s| self.slot_params(s)).collect();
+
+            let grads = self.tune_gradient(&slots, &current, data, loss, &mut scratch)?;
+
+            for ((slot, p), g) in slots.iter().zip(&current).zip(&grads) {
+                let w2 = slot.width * slot.width;
+                let mut next: Vec<Float> =
+                    p.iter().zip(g).map(|(pi, gi)| pi - lr * w2 * gi).collect();
+                self.slot_term(slot)
+                    .constrain_params(&mut next, opts.min_gap * slot.width);
+                if self.set_slot(slot, &next).is_err() {
+                    self.set_slot(slot, p)?;
+                }
+            }
+
+            let next_loss = self.tune_loss(data, &mut scratch)?;
+            if next_loss <= loss {
+                loss = next_loss;
+            } else {
+                for (slot, p) in slots.iter().zip(&current) {
+                    self.set_slot(slot, p)?;
+                }
+                lr *= 0.5;
+            }
+            history.push(loss);
+        }
+        Ok(history)
+    }
+
+    /// Every term that exposes parameters, in variable and term id order.
+    pub(crate) fn tune_slots(&self) -> Vec<Slot> {
+        self.vars
+            .iter()
+            .enumerate()
+            .flat_map(|(v, var)| {
+                let (min, max) = var.domain();
+                var.terms()
+                    .filter(|(_, t)| !t.params().is_empty())
+                    .map(move |(name, _)| (v, name, max - min))
+                    .collect::<Vec<_>>()
+            })
+            .map(|(var, name, width)| Slot {
+                var,
+                term: self.vars[var]
+                    .term_id(name)
+                    .expect("name comes from the variable"),
+                width,
+            })
+            .collect()
+    }
+
+    /// Finite-difference gradient of the loss with respect to every slot's
+    /// parameters at `current`, where the loss is `loss`. Restores `current`.
+    pub(crate) fn tune_gradient<KI>(
+        &mut self,
+        slots: &[Slot],
+        current: &[Vec<Float>],
+        data: &[LabeledRow<KI>],
+        loss: Float,
+        scratch: &mut Scratch,
+    ) -> error::Result<Vec<Vec<Float>>>
+    where
+        KI: Eq + Hash + Borrow<str>,
+    {
+        let mut grads = Vec::with_capacity(slots.len());
+        for (slot, p) in slots.iter().zip(current) {
+            let h = FD_STEP * slot.width;
+            let mut g = vec![0.0; p.len()];
+            for (k, gk) in g.iter_mut().enumerate() {
+                let mut probe = p.clone();
+                probe[k] = p[k] + h;
+                let up = self.probe_loss(slot, &probe, data, scratch)?;
+                probe[k] = p[k] - h;
+                let down = self.probe_loss(slot, &probe, data, scratch)?;
+                *gk = match (up, down) {
+                    (Some(u), Some(d)) => (u - d) / (2.0 * h),
+                    (Some(u), None) => (u - loss) / h,
+                    (None, Some(d)) => (loss - d) / h,
+                    (None, None) => 0.0,
+                };
+            }
+            self.set_slot(slot, p)?;
+            grads.push(g);
+        }
+        Ok(grads)
+    }
+
+    /// Finite-difference gradient of the loss with respect to every rule
+    /// weight, probing within [0, 1]. Restores the weights.
+    pub(crate) fn weight_gradient<KI>(
+        &mut self,
+        data: &[LabeledRow<KI>],
+        scratch: &mut Scratch,
+    ) -> error::Result<Vec<Float>>
+    where
+        KI: Eq + Hash + Borrow<str>,
+    {
+        let mut grads = Vec::with_capacity(self.rules.len());
+        for r in 0..self.rules.len() {
+            let w = self.rules[r].weight;
+            let (lo, hi) = ((w - FD_STEP).max(0.0), (w + FD_STEP).min(1.0));
+            self.rules[r].weight = hi;
+            let up = self.tune_loss(data, scratch);
+            self.rules[r].weight = lo;
+            let down = self.tune_loss(data, scratch);
+            self.rules[r].weight = w;
+            grads.push((up? - down?) / (hi - lo));
+        }
+        Ok(grads)
+    }
+
+    /// Domain-scaled mean squared error of the current parameters on `data`.
+    pub(crate) fn tune_loss<KI>(
+        &self,
+        data: &[LabeledRow<KI>],
+        scratch: &mut Scratch,
+    ) -> error::Result<Float>
+    where
+        KI: Eq + Hash + Borrow<str>,
+    {
+        let (mut total, mut count) = (0.0, 0usize);
+        for (input, targets) in data {
+            let out = self.evaluate_in(input, scratch)?;
+            for (name, &t) in targets {
+                let name = name.borrow();
+                let missing = || FuzzyError::NotFound {
+                    space: MissingSpace::Var,
+                    key: name.to_string(),
+                };
+                let y = *out.get(name).ok_or_else(missing)?;
+                let (min, max) = self.var(name).ok_or_else(missing)?.domain();
+                let e = if y.is_finite() {
+                    (y - t) / (max - min)
+                } else {
+                    1.0
+                };
+                total += e * e;
+                count += 1;
+            }
+        }
+        if count == 0 {
+            return Err(FuzzyError::EmptyInput);
+        }
+        Ok(total / count as Float)
+    }
+
+    /// Loss with `slot` set to `params`, or `None` if the shape rejects them.
+    /// Leaves `params` applied; the caller restores the original values.
+    pub(crate) fn probe_loss<KI>(
+        &mut self,
+        slot: &Slot,
+        params: &[Float],
+        data: &[LabeledRow<KI>],
+        scratch: &mut Scratch,
+    ) -> error::Result<Option<Float>>
+    where
+        KI: Eq + Hash + Borrow<str>,
+    {
+        if self.set_slot(slot, params).is_err() {
+            return Ok(None);
+        }
+        self.tune_loss(data, scratch).map(Some)
+    }
+
+    pub(crate) fn slot_term(&self, slot: &Slot) -> &crate::term::Term {
+        self.vars[slot.var]
+            .term(slot.term)
+            .expect("slot ids come from the variable")
+    }
+
+    pub(crate) fn slot_params(&self, slot: &Slot) -> Vec<Float> {
+        self.slot_term(slot).params()
+    }
+
+    pub(crate) fn set_slot(&mut self, slot: &Slot, params: &[Float]) -> error::Result<()> {
+        self.vars[slot.var]
+            .term_mut(slot.term)
+            .expect("slot ids come from the variable")
+            .set_params(params)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::{LabeledRow, TuneOptions};
+    use crate::antecedent::Antecedent;
+    use crate::error::FuzzyError;
+    use crate::mamdani::{Consequent, Rule};
+    use crate::prelude::*;
+    use crate::rulespace::RuleSpace;
+    use crate::variable::Variable;
+
+    /// temp -> fan with `hot` peaking at `apex` and a Gaussian `high` fan term.
+    fn space(apex: Float, fan_mean: Float) -> RuleSpace {
+        let mut temp = Variable::new(0.0, 10.0).unwrap();
+        temp.insert_term(
+            "cold",
+            Term::new("cold", Triangular::new(-10.0, 0.0, 10.0).unwrap()),
+        )
+        .unwrap();
+        temp.insert_term(
+            "hot",
+            Term::new("hot", Triangular::new(0.0, apex, 20.0).unwrap()),
+        )
+        .unwrap();
+        let mut fan = Variable::new(0.0, 100.0).unwrap();
+        fan.insert_term(
+            "low",
+            Term::new("low", Triangular::new(-50.0, 0.0, 50.0).unwrap()),
+        )
+        .unwrap();
+        fan.insert_term(
+            "high",
+            Term::new("high", Gaussian::with_mean_sd(fan_mean, 15.0).unwrap()),
+        )
+        .unwrap();
+        let rule = |t: &'static str, f: &'static str| Rule {
+            antecedent: Antecedent::Atom {
+                var: "temp".into(),
+                term: t.into(),
+            },
+            consequent: vec![Consequent::Term {
+                var: "fan".into(),
+                term: f.into(),
+                hedge: None,
+            }],
+            weight: 1.0,
+        };
+        let vars = HashMap::from([("temp".to_string(), temp), ("fan".to_string(), fan)]);
+        RuleSpace::new(vars, vec![rule("cold", "low"), rule("hot", "high")]).unwrap()
+    }
+
+    #[test]
+    fn tuning_recovers_a_reference_system() {
+        let sampler = UniformSampler::default();
+        let reference = space(10.0, 80.0);
+        let data: Vec<_> = (0..=20)
+            .map(|i| {
+                let x = i as Float / 2.0;
+                let input = HashMap::from([("temp", x)]);
+                let y = reference.evaluate(&input, &sampler).unwrap()["fan"];
+                (input, HashMap::from([("fan", y)]))
+            })
+            .collect();
+
+        let mut rs = space(4.0, 55.0);
+        let history = rs
+            .tune(
+                &data,
+                &sampler,
+                TuneOptions {
+                    epochs: 60,
+                    ..TuneOptions::default()
+                },
+            )
+            .unwrap();
+
+        assert!(history.windows(2).all(|w| w[1] <= w[0]));
+        assert!(history[history.len() - 1] < 0.1 * history[0], "{history:?}");
+        // Tuned shapes stay valid.
+        let hot = rs.var("temp").unwrap().get("hot").unwrap().params();
+        assert!(hot[0] < hot[1] && hot[1] < hot[2]);
+    }
+
+    #[test]
+    fn unknown_targets_and_empty_data_are_rejected() {
+        let sampler = UniformSampler::default();
+        let mut rs = space(10.0, 80.0);
+        let data = vec![(
+            HashMap::from([("temp", 5.0)]),
+            HashMap::from([("nope", 1.0)]),
+        )];
+        assert!(matches!(
+            rs.tune(&data, &sampler, TuneOptions::default()),
+            Err(FuzzyError::NotFound { .. })
+        ));
+        let empty: Vec<LabeledRow<&str>> = Vec::new();
+        assert_eq!(
+            rs.tune(&empty, &sampler, TuneOptions::default()),
+            Err(FuzzyError::EmptyInput)
+        );
+    }
+}
@@ -0,0 +1,195 @@
+// Units-of-measure aware inputs (`uom` feature).
+//
+// Rule spaces work on bare numbers, so a temperature given in Fahrenheit to a
+// variable defined in Celsius silently produces nonsense. `Units` records, per
+// input variable, which `uom` quantity it expects and how to read it as the
+// number the variable's terms were defined in. Typed inputs are converted on the
+// way in, and a quantity of the wrong kind is rejected before evaluation.
+use std::{any::Any, collections::HashMap};
+
+use crate::{
+    error::{self, FuzzyError, MissingSpace},
+    Float,
+};
+
+type Convert = Box<dyn Fn(&dyn Any) -> Option<Float> + Send + Sync>;
+
+/// The quantity each input variable expects.
+#[derive(Default)]
+pub struct Units {
+    vars: HashMap<String, (Convert, &'static str)>,
+}
+
+impl Units {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Declares that `var` takes quantities of type `Q`, read as a number by
+    /// `to_value`, e.g. for a variable defined in degrees Celsius:
+    ///
+    /// ```rust
+    /// use rust_fuzzylogic::units::Units;
+    /// use uom::si::f64::ThermodynamicTemperature;
+    /// use uom::si::thermodynamic_temperature::{degree_celsius, degree_fahrenheit};
+    ///
+    /// let units = Units::new().declare("temp", |t: &ThermodynamicTemperature| {
+    ///     t.get::<degree_celsius>()
+    /// });
+    /// let inputs = units
+    ///     .inputs()
+    ///     .set("temp", ThermodynamicTemperature::new::<degree_fahrenheit>(212.0))
+    ///     .unwrap()
+    ///     .into_map();
+    /// assert!((inputs["temp"] - 100.0).abs() < 1e-9);
+    /// ```
+    pub fn declare<Q: 'static>(
+        mut self,
+        var: &str,
+        to_value: impl Fn(&Q) -> Float + Send + Sync + 'static,
+    ) -> Self {
+        let convert: Convert = Box::new(move |q| q.downcast_ref::<Q>().map(&to_value));
+        self.vars
+            .insert(var.to_string(), (convert, std::any::type_name::<Q>()));
+        self
+    }
+
+    /// Declares that `var` takes SI quantities of dimension `D`, read in SI
+    /// base units (kelvin for temperatures, metres for lengths, ...).
+    pub fn declare_si<D>(self, var: &str) -> Self
+    where
+        D: uom::si::Dimension + ?Sized + 'static,
+    {
+        self.declare(
+            var,
+            |q: &uom::si::Quantity<D, uom::si::SI<Float>, Float>| q.value,
+        )
+    }
+
+    /// Reads quantity `q` as the number variable `var` expects.
+    ///
+    /// Fails with `NotFound` if `var` was not declared and with `TypeMismatch`
+    /// if `q` is not the declared quantity.
+    pub fn convert<Q: 'static>(&self, var: &str, q: &Q) -> error::Result<Float> {
+        let (convert, _) = self.vars.get(var).ok_or_else(|| FuzzyError::NotFound {
+            space: MissingSpace::Input,
+            key: var.to_string(),
+        })?;
+        convert(q).ok_or(FuzzyError::TypeMismatch)
+    }
+
+    /// Type name of the quantity `var` expects, if declared.
+    pub fn quantity(&self, var: &str) -> Option<&'static str> {
+        self.vars.get(var).map(|&(_, name)| name)
+    }
+
+    /// Starts an input map for `RuleSpace::evaluate` and friends.
+    pub fn inputs(&self) -> TypedInputs<'_> {
+        TypedInputs {
+            units: self,
+            values: HashMap::new(),
+        }
+    }
+}
+
+/// Inputs collected through `Units`, converted as they are set.
+pub struct TypedInputs<'a> {
+    units: &'a Units,
+    values: HashMap<String, Float>,
+}
+
+impl TypedInputs<'_> {
+    /// Sets `var` from a typed quantity; fails like `Units::convert`.
+    pub fn set<Q: 'static>(mut self, var: &str, q: Q) -> error::Result<Self> {
+        let x = self.units.convert(var, &q)?;
+        self.values.insert(var.to_string(), x);
+        Ok(self)
+    }
+
+    /// Sets a variable without a declared unit (a count, a code, a ratio...).
+    ///
+    /// Fails with `TypeMismatch` if `var` was declared with a quantity.
+    pub fn set_raw(mut self, var: &str, x: Float) -> error::Result<Self> {
+        if self.units.vars.contains_key(var) {
+            return Err(FuzzyError::TypeMismatch);
+        }
+        self.values.insert(var.to_string(), x);
+        Ok(self)
+    }
+
+    pub fn into_map(self) -> HashMap<String, Float> {
+        self.values
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use uom::si::length::meter;
+    use uom::si::thermodynamic_temperature::{degree_celsius, kelvin};
+
+    use super::Units;
+    use crate::error::FuzzyError;
+    use crate::prelude::*;
+
+    #[cfg(feature = "f32")]
+    use uom::si::f32::{Length, ThermodynamicTemperature};
+    #[cfg(not(feature = "f32"))]
+    use uom::si::f64::{Length, ThermodynamicTemperature};
+
+    #[test]
+    fn typed_inputs_convert_and_reject_mismatches() {
+        let mut temp = Variable::new(0.0, 40.0).unwrap();
+        temp.insert_term(
+            "hot",
+            Term::new("hot", Triangular::new(20.0, 40.0, 60.0).unwrap()),
+        )
+        .unwrap();
+        let mut fan = Variable::new(0.0, 10.0).unwrap();
+        fan.insert_term(
+            "high",
+            Term::new("high", Triangular::new(5.0, 10.0, 15.0).unwrap()),
+        )
+        .unwrap();
+        let rule = Rule::builder()
+            .when(atom("temp", "hot"))
+            .then("fan", "high")
+            .build()
+            .unwrap();
+        let vars = HashMap::from([("temp".to_string(), temp), ("fan".to_string(), fan)]);
+        let rs = RuleSpace::new(vars, vec![rule]).unwrap();
+
+        let units = Units::new()
+            .declare("temp", |t: &ThermodynamicTemperature| {
+                t.get::<degree_celsius>()
+            })
+            .declare_si::<uom::si::length::Dimension>("gap");
+        let sampler = UniformSampler::default();
+        let typed = units
+            .inputs()
+            .set("temp", ThermodynamicTemperature::new::<kelvin>(303.15))
+            .unwrap()
+            .into_map();
+        assert!((typed["temp"] - 30.0).abs() < 1e-3);
+        let direct = rs
+            .evaluate(&HashMap::from([("temp", typed["temp"])]), &sampler)
+            .unwrap();
+        assert_eq!(rs.evaluate(&typed, &sampler).unwrap(), direct);
+
+        let gap = units.convert("gap", &Length::new::<meter>(2.5)).unwrap();
+        assert_eq!(gap, 2.5);
+        assert!(matches!(
+            units.inputs().set("temp", Length::new::<meter>(1.0)),
+            Err(FuzzyError::TypeMismatch)
+        ));
+        assert!(matches!(
+            units.inputs().set("rh", Length::new::<meter>(1.0)),
+            Err(FuzzyError::NotFound { .. })
+        ));
+        assert!(matches!(
+            units.inputs().set_raw("temp", 30.0),
+            Err(FuzzyError::TypeMismatch)
+        ));
+    }
+}
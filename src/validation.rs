@@ -0,0 +1,246 @@
+// K-fold cross-validation.
+//
+// `kfold` splits a dataset into folds and hands each train/test split to a
+// caller-supplied closure that fits a model and returns its score;
+// `cross_validate_tune` is the ready-made version for `RuleSpace::tune`, which
+// rebuilds the untuned system for every fold because a `RuleSpace` cannot be cloned.
+use std::{borrow::Borrow, hash::Hash};
+
+use crate::{
+    error::{self, FuzzyError},
+    metrics::Metrics,
+    rng::Rng,
+    rulespace::RuleSpace,
+    sampler::UniformSampler,
+    tune::{LabeledRow, TuneOptions},
+    Float,
+};
+
+/// How a dataset is split into folds.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct KFoldOptions {
+    /// Number of folds, between 2 and the number of rows.
+    pub folds: usize,
+    /// Shuffle rows before splitting; otherwise folds are contiguous runs.
+    pub shuffle: bool,
+    pub seed: u64,
+}
+
+impl Default for KFoldOptions {
+    fn default() -> Self {
+        Self {
+            folds: 5,
+            shuffle: true,
+            seed: 0x5EED,
+        }
+    }
+}
+
+/// Which statistic `cross_validate_tune` reports.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Metric {
+    Rmse,
+    Mae,
+    R2,
+}
+
+impl Metric {
+    pub fn of(self, m: &Metrics) -> Float {
+        match self {
+            Metric::Rmse => m.rmse,
+            Metric::Mae => m.mae,
+            Metric::R2 => m.r2,
+        }
+    }
+}
+
+/// Per-fold scores with their mean and sample variance.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CvReport {
+    pub scores: Vec<Float>,
+    pub mean: Float,
+    pub variance: Float,
+}
+
+/// Row indices of the test set of each fold. Fold sizes differ by at most one
+/// and every row is tested exactly once.
+pub fn folds(rows: usize, opts: KFoldOptions) -> error::Result<Vec<Vec<usize>>> {
+    if opts.folds < 2 || opts.folds > rows {
+        return Err(FuzzyError::OutOfBounds);
+    }
+    let mut order: Vec<usize> = (0..rows).collect();
+    if opts.shuffle {
+        Rng::new(opts.seed).shuffle(&mut order);
+    }
+    let (size, extra) = (rows / opts.folds, rows % opts.folds);
+    let mut start = 0;
+    Ok((0..opts.folds)
+        .map(|k| {
+            let end = start + size + usize::from(k < extra);
+            let fold = order[start..end].to_vec();
+            start = end;
+            fold
+        })
+        .collect())
+}
+
+/// Runs `fit_score(train, test)` on every fold of `data` and summarizes the scores.
+pub fn kfold<T, F>(data: &[T], opts: KFoldOptions, mut fit_score: F) -> error::Result<CvReport>
+where
+    T: Clone,
+    F: FnMut(&[T], &[T]) -> error::Result<Float>,
+{
+    let folds = folds(data.len(), opts)?;
+    let mut in_test = vec![false; data.len()];
+    let mut scores = Vec::with_capacity(folds.len());
+    for fold in &folds {
+        in_test.iter_mut().for_each(|t| *t = false);
+        fold.iter().for_each(|&i| in_test[i] = true);
+        let test: Vec<T> = fold.iter().map(|&i| data[i].clone()).collect();
+        let train: Vec<T> = (0..data.len())
+            .filter(|&i| !in_test[i])
+            .map(|i| data[i].clone())
+            .collect();
+        scores.push(fit_score(&train, &test)?);
+    }
+    let n = scores.len() as Float;
+    let mean = scores.iter().sum::<Float>() / n;
+    let variance = scores
+        .iter()
+        .map(|s| (s - mean) * (s - mean))
+        .sum::<Float>()
+        / (n - 1.0);
+    Ok(CvReport {
+        scores,
+        mean,
+        variance,
+    })
+}
+
+/// Cross-validates `RuleSpace::tune`: for every fold, `build` creates a fresh
+/// system, which is tuned on the training rows and scored on the test rows.
+///
+/// A fold's score is `metric` averaged over the outputs its test rows label.
+pub fn cross_validate_tune<KI, B>(
+    mut build: B,
+    data: &[LabeledRow<KI>],
+    sampler: &UniformSampler,
+    tune: TuneOptions,
+    metric: Metric,
+    opts: KFoldOptions,
+) -> error::Result<CvReport>
+where
+    KI: Eq + Hash + Borrow<str> + Clone,
+    B: FnMut() -> error::Result<RuleSpace>,
+{
+    kfold(data, opts, |train, test| {
+        let mut rs = build()?;
+        rs.tune(train, sampler, tune)?;
+        let report = rs.score(test, sampler)?;
+        let n = report.outputs.len() as Float;
+        Ok(report
+            .outputs
+            .iter()
+            .map(|(_, m)| metric.of(m))
+            .sum::<Float>()
+            / n)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+    use crate::antecedent::Antecedent;
+    use crate::mamdani::{Consequent, Rule};
+    use crate::prelude::*;
+    use crate::variable::Variable;
+
+    #[test]
+    fn folds_partition_the_rows() {
+        let f = folds(10, KFoldOptions::default()).unwrap();
+        assert_eq!(f.iter().map(Vec::len).collect::<Vec<_>>(), vec![2; 5]);
+        let mut all: Vec<usize> = f.concat();
+        all.sort_unstable();
+        assert_eq!(all, (0..10).collect::<Vec<_>>());
+
+        let plain = KFoldOptions {
+            folds: 3,
+            shuffle: false,
+            ..KFoldOptions::default()
+        };
+        assert_eq!(
+            folds(7, plain).unwrap(),
+            vec![vec![0, 1, 2], vec![3, 4], vec![5, 6]]
+        );
+        assert_eq!(folds(1, plain), Err(FuzzyError::OutOfBounds));
+
+        let report = kfold(&[1.0, 2.0, 3.0], plain, |train, test| {
+            Ok(test[0] + train.len() as Float)
+        })
+        .unwrap();
+        assert_eq!(report.scores, vec![3.0, 4.0, 5.0]);
+        assert_eq!((report.mean, report.variance), (4.0, 1.0));
+    }
+
+    fn space(apex: Float) -> error::Result<RuleSpace> {
+        let mut temp = Variable::new(0.0, 10.0)?;
+        temp.insert_term(
+            "cold",
+            Term::new("cold", Triangular::new(-10.0, 0.0, 10.0)?),
+        )?;
+        temp.insert_term("hot", Term::new("hot", Triangular::new(0.0, apex, 20.0)?))?;
+        let mut fan = Variable::new(0.0, 100.0)?;
+        fan.insert_term("low", Term::new("low", Triangular::new(-50.0, 0.0, 50.0)?))?;
+        fan.insert_term(
+            "high",
+            Term::new("high", Triangular::new(50.0, 100.0, 150.0)?),
+        )?;
+        let rule = |t: &'static str, f: &'static str| Rule {
+            antecedent: Antecedent::Atom {
+                var: "temp".into(),
+                term: t.into(),
+            },
+            consequent: vec![Consequent::Term {
+                var: "fan".into(),
+                term: f.into(),
+                hedge: None,
+            }],
+            weight: 1.0,
+        };
+        let vars = HashMap::from([("temp".to_string(), temp), ("fan".to_string(), fan)]);
+        RuleSpace::new(vars, vec![rule("cold", "low"), rule("hot", "high")])
+    }
+
+    #[test]
+    fn tuning_generalizes_across_folds() {
+        let sampler = UniformSampler::default();
+        let reference = space(10.0).unwrap();
+        let data: Vec<_> = (1..=20)
+            .map(|i| {
+                let input = HashMap::from([("temp", i as Float / 2.0)]);
+                let y = reference.evaluate(&input, &sampler).unwrap()["fan"];
+                (input, HashMap::from([("fan", y)]))
+            })
+            .collect();
+        let cv = |epochs| {
+            let tune = TuneOptions {
+                epochs,
+                ..TuneOptions::default()
+            };
+            cross_validate_tune(
+                || space(4.0),
+                &data,
+                &sampler,
+                tune,
+                Metric::Rmse,
+                KFoldOptions::default(),
+            )
+            .unwrap()
+        };
+        let (untuned, tuned) = (cv(0), cv(60));
+        assert_eq!(tuned.scores.len(), 5);
+        assert!(tuned.mean < 0.75 * untuned.mean, "{tuned:?} vs {untuned:?}");
+    }
+}
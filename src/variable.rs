@@ -1,70 +1,254 @@
 // Variable: crisp scalar with domain and named fuzzy terms.
 // This file defines the `Variable` type plus red tests for its API.
-use crate::{error::FuzzyError, membership::MembershipFn, term::Term, Float};
-
-use std::collections::HashMap;
+use crate::{
+    error::FuzzyError,
+    hedges::Hedge,
+    intern::{Interner, TermId},
+    membership::{MembershipFn, MembershipKind, MfExpr, Rectangular},
+    term::Term,
+    Float,
+};
 
 /// A crisp variable with an inclusive numeric domain and a set of named terms.
+#[derive(Debug)]
 pub struct Variable {
+    /// Name errors report; set by `with_name` or when a rule space takes the
+    /// variable, empty until then.
+    name: String,
+
     /// Inclusive lower bound of the variable's domain.
     min: Float,
 
     /// Inclusive upper bound of the variable's domain.
     max: Float,
 
-    /// Mapping from term name to its labeled membership function wrapper.
-    pub terms: HashMap<String, Term>,
+    /// Term names, interned so that `TermId`s index into `terms`.
+    names: Interner,
+
+    /// Labeled membership function wrappers in insertion order.
+    terms: Vec<Term>,
+
+    /// Whether input degrees are divided by their sum over all terms.
+    normalized: bool,
 }
 impl Variable {
     /// Constructs a new variable, validating that `min < max`.
+    ///
+    /// Fails with `InvalidParameter` naming `max` (or `min`, when it is NaN)
+    /// for bounds that are not strictly ordered.
     pub fn new(min: Float, max: Float) -> crate::error::Result<Self> {
         // Domain validation: bounds must be strictly ordered.
-        if min >= max {
-            Err(FuzzyError::OutOfBounds)
+        if min.is_nan() || max.is_nan() || min >= max {
+            let (field, value) = if min.is_nan() {
+                ("min", min)
+            } else {
+                ("max", max)
+            };
+            Err(FuzzyError::InvalidParameter {
+                mf: "Variable",
+                field,
+                value,
+            })
         } else {
             // Initialize with an empty term map.
             Ok(Self {
-                min: min,
-                max: max,
-                terms: HashMap::new(),
+                name: String::new(),
+                min,
+                max,
+                names: Interner::new(),
+                terms: Vec::new(),
+                normalized: false,
             })
         }
     }
 
+    /// Categorical variable whose terms are `labels`, matched by equality.
+    ///
+    /// Label `i` is a crisp interval around code `i`, so feeding the input
+    /// `code(label)` fires exactly that label's term. Fails with `EmptyInput`
+    /// for no labels or an empty label and `DuplicateTerm` for a repeated one.
+    pub fn categorical<S: AsRef<str>>(labels: &[S]) -> crate::error::Result<Self> {
+        if labels.is_empty() {
+            return Err(FuzzyError::EmptyInput);
+        }
+        let mut var = Self::new(-0.5, labels.len() as Float - 0.5)?;
+        for (i, label) in labels.iter().enumerate() {
+            let label = label.as_ref();
+            let c = i as Float;
+            var.insert_term(label, Term::new(label, Rectangular::new(c - 0.5, c + 0.5)?))?;
+        }
+        Ok(var)
+    }
+
+    /// Boolean variable with terms `"false"` and `"true"` (codes 0 and 1).
+    pub fn boolean() -> Self {
+        Self::categorical(&["false", "true"]).expect("two distinct labels")
+    }
+
+    /// Input code of the categorical label `label`: the midpoint of its crisp
+    /// interval term. `None` for unknown labels and non-crisp terms.
+    pub fn code(&self, label: &str) -> Option<Float> {
+        match self.get(label)?.kind() {
+            MembershipKind::Rectangular(r) => Some(r.center()),
+            _ => None,
+        }
+    }
+
+    /// Names the variable, so the errors it raises say where they come from.
+    /// `RuleSpace::new` names every variable after its key.
+    pub fn with_name(mut self, name: impl Into<String>) -> Self {
+        self.name = name.into();
+        self
+    }
+
+    /// The variable's name, empty if it was never given one.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
     /// Inserts a named term; rejects empty names and duplicates.
     ///
     /// - Empty name -> `FuzzyError::EmptyInput`
-    /// - Duplicate name -> `FuzzyError::TypeMismatch`
+    /// - Duplicate name -> `FuzzyError::DuplicateTerm`
     pub fn insert_term(&mut self, name: &str, t: Term) -> crate::error::Result<()> {
         // Reject empty label.
-        if name == "" {
+        if name.is_empty() {
             Err(FuzzyError::EmptyInput)
         }
         // Reject duplicates to avoid silent overwrites.
         else if self.get(name).is_some() {
-            Err(FuzzyError::TypeMismatch)
+            Err(FuzzyError::DuplicateTerm {
+                var: self.name.clone(),
+                term: name.to_string(),
+            })
         } else {
-            // Store the term by name.
-            self.terms.insert(name.to_string(), t);
+            // Store the term under the next free id.
+            self.names.intern(name);
+            self.terms.push(t);
             Ok(())
         }
     }
 
+    /// Registers `not_<term>`, the complement `1 - mu` of the term `term`.
+    ///
+    /// The new term copies `term`'s current shape; tuning `term` afterwards
+    /// does not change it. Fails with `UnknownTerm` for an unknown `term`,
+    /// `TypeMismatch` for a custom-shaped one (it cannot be copied) and
+    /// `DuplicateTerm` for a name already taken.
+    pub fn derive_not(&mut self, term: &str) -> crate::error::Result<()> {
+        self.derive(format!("not_{term}"), term, MfExpr::Not)
+    }
+
+    /// Registers `very_<term>`, the concentration `mu²` of the term `term`;
+    /// see `derive_hedged`.
+    pub fn derive_very(&mut self, term: &str) -> crate::error::Result<()> {
+        self.derive_hedged(term, Hedge::Very)
+    }
+
+    /// Registers `<hedge>_<term>` (e.g. `somewhat_hot`), the term `term`
+    /// reshaped by `hedge`. Copies and fails like `derive_not`, and with
+    /// `InvalidParameter` for an invalid `hedge` (see `Hedge::validate`).
+    pub fn derive_hedged(&mut self, term: &str, hedge: Hedge) -> crate::error::Result<()> {
+        let hedge = hedge.validate()?;
+        self.derive(format!("{hedge}_{term}"), term, |e| {
+            MfExpr::Hedged(hedge, e)
+        })
+    }
+
+    fn derive(
+        &mut self,
+        name: String,
+        term: &str,
+        wrap: impl FnOnce(Box<MfExpr>) -> MfExpr,
+    ) -> crate::error::Result<()> {
+        let shape = self
+            .get(term)
+            .ok_or_else(|| self.unknown(term))?
+            .kind()
+            .copied()
+            .ok_or(FuzzyError::TypeMismatch)?;
+        let kind = wrap(Box::new(MfExpr::Shape(shape))).into_kind();
+        self.insert_term(&name, Term::with_kind(name.as_str(), kind))
+    }
+
     /// Returns a reference to the term for `name`, if present.
     pub fn get(&self, name: &str) -> Option<&Term> {
-        self.terms.get(name)
+        self.term_id(name).map(|id| &self.terms[id.index()])
+    }
+
+    /// Resolves a term name to its id within this variable.
+    pub fn term_id(&self, name: &str) -> Option<TermId> {
+        self.names.get(name).map(TermId)
+    }
+
+    /// Returns the term behind `id`, if it belongs to this variable.
+    pub fn term(&self, id: TermId) -> Option<&Term> {
+        self.terms.get(id.index())
+    }
+
+    /// Mutable access to the term behind `id`, e.g. to adjust its parameters.
+    pub fn term_mut(&mut self, id: TermId) -> Option<&mut Term> {
+        self.terms.get_mut(id.index())
+    }
+
+    /// Name the term behind `id` was inserted under.
+    pub fn term_name(&self, id: TermId) -> &str {
+        self.names.name(id.0)
+    }
+
+    /// Iterates `(name, term)` pairs in insertion order.
+    pub fn terms(&self) -> impl Iterator<Item = (&str, &Term)> {
+        self.names.iter().zip(self.terms.iter())
     }
 
     /// Evaluates the membership degree for term `name` at input `x`.
     ///
-    /// - Unknown term -> `FuzzyError::TypeMismatch`
-    /// - `x` out of `[min, max]` -> `FuzzyError::OutOfBounds`
+    /// - Unknown term -> `FuzzyError::UnknownTerm`
+    /// - `x` out of `[min, max]`, or NaN -> `FuzzyError::OutOfDomain`
     pub fn eval(&self, name: &str, x: Float) -> crate::error::Result<Float> {
         // Resolve term by name.
-        let v = &self.terms.get(name).ok_or(FuzzyError::TypeMismatch)?;
-        // Domain check is inclusive: allow x == min or x == max.
-        if self.max < x || self.min > x {
-            Err(FuzzyError::OutOfBounds)
+        let id = self.term_id(name).ok_or_else(|| self.unknown(name))?;
+        self.eval_id(id, x)
+    }
+
+    /// `UnknownTerm` for `term` on this variable.
+    fn unknown(&self, term: &str) -> FuzzyError {
+        FuzzyError::UnknownTerm {
+            var: self.name.clone(),
+            term: term.to_string(),
+        }
+    }
+
+    /// The term behind `id`, or `UnknownTerm` naming the id.
+    fn term_or_err(&self, id: TermId) -> crate::error::Result<&Term> {
+        self.term(id)
+            .ok_or_else(|| self.unknown(&format!("#{}", id.index())))
+    }
+
+    /// `OutOfDomain` unless `x` lies in `[min, max]`; NaN lies in no domain.
+    fn check_domain(&self, x: Float) -> crate::error::Result<()> {
+        if (self.min..=self.max).contains(&x) {
+            Ok(())
+        } else {
+            Err(FuzzyError::OutOfDomain {
+                var: self.name.clone(),
+                value: x,
+            })
+        }
+    }
+
+    /// Evaluates the membership degree of the term behind `id` at input `x`.
+    ///
+    /// Same errors as `eval`, without hashing the term name.
+    pub fn eval_id(&self, id: TermId, x: Float) -> crate::error::Result<Float> {
+        let v = self.term_or_err(id)?;
+        // Domain check is inclusive: allow x == min or x == max. NaN is in no
+        // domain, so it fails here instead of reaching the shape's comparisons.
+        self.check_domain(x)?;
+        // Divide by the degrees of all terms so they form a partition.
+        if self.normalized {
+            let sum: Float = self.terms.iter().map(|t| t.eval(x)).sum();
+            Ok(if sum > 0.0 { v.eval(x) / sum } else { 0.0 })
         }
         // Delegate to the term's membership function.
         else {
@@ -72,6 +256,60 @@ impl Variable {
         }
     }
 
+    /// Evaluates term `name` at every input of `xs` into the matching slot of `out`.
+    ///
+    /// The term is looked up and the domain checked once for the whole batch,
+    /// then the membership function's `eval_grid` runs over it. Same errors as
+    /// `eval`, plus `BadArity` when `out` and `xs` differ in length; on error
+    /// `out` is left untouched.
+    pub fn eval_many(
+        &self,
+        name: &str,
+        xs: &[Float],
+        out: &mut [Float],
+    ) -> crate::error::Result<()> {
+        let id = self.term_id(name).ok_or_else(|| self.unknown(name))?;
+        self.eval_many_id(id, xs, out)
+    }
+
+    /// Like `eval_many`, without hashing the term name.
+    pub fn eval_many_id(
+        &self,
+        id: TermId,
+        xs: &[Float],
+        out: &mut [Float],
+    ) -> crate::error::Result<()> {
+        let v = self.term_or_err(id)?;
+        if xs.len() != out.len() {
+            return Err(FuzzyError::BadArity);
+        }
+        xs.iter().try_for_each(|&x| self.check_domain(x))?;
+        if self.normalized {
+            for (&x, mu) in xs.iter().zip(out) {
+                let sum: Float = self.terms.iter().map(|t| t.eval(x)).sum();
+                *mu = if sum > 0.0 { v.eval(x) / sum } else { 0.0 };
+            }
+        } else {
+            v.eval_grid(xs, out);
+        }
+        Ok(())
+    }
+
+    /// Makes the term degrees at any input sum to 1 (a fuzzy partition), as
+    /// TSK and ANFIS-style schemes expect: `eval` divides each degree by the
+    /// sum over all terms at `x`, and an input no term covers stays at 0.
+    ///
+    /// Applies wherever the variable is read as an input; the output sets of
+    /// an output variable are unaffected.
+    pub fn set_normalized(&mut self, normalized: bool) {
+        self.normalized = normalized;
+    }
+
+    /// Whether `eval` sum-normalizes the term degrees, see `set_normalized`.
+    pub fn is_normalized(&self) -> bool {
+        self.normalized
+    }
+
     /// Returns the range of domain for the membership function.(term)
     ///
     /// - retunrs `(min, max)`
@@ -79,7 +317,6 @@ impl Variable {
         (self.min, self.max)
     }
     //Optional helpers:
-    //pub fn names(&self) -> impl Iterator<Item=&str>
     //pub fn fuzzify(&self, x: Float) -> crate::error::Result<Vec<(String, Float)>> to get all memberships at x.
 }
 
@@ -96,11 +333,19 @@ mod tests {
     fn test_new_rejects_invalid_domain() {
         assert!(matches!(
             crate::variable::Variable::new(1.0, 1.0),
-            Err(FuzzyError::OutOfBounds)
+            Err(FuzzyError::InvalidParameter {
+                mf: "Variable",
+                field: "max",
+                ..
+            })
         ));
         assert!(matches!(
             crate::variable::Variable::new(2.0, 1.0),
-            Err(FuzzyError::OutOfBounds)
+            Err(FuzzyError::InvalidParameter { field: "max", .. })
+        ));
+        assert!(matches!(
+            crate::variable::Variable::new(crate::Float::NAN, 1.0),
+            Err(FuzzyError::InvalidParameter { field: "min", .. })
         ));
     }
 
@@ -143,7 +388,9 @@ mod tests {
     /// Reject duplicate term insertions for the same name.
     #[test]
     fn test_duplicate_term_rejected() {
-        let mut v = crate::variable::Variable::new(0.0, 1.0).unwrap();
+        let mut v = crate::variable::Variable::new(0.0, 1.0)
+            .unwrap()
+            .with_name("level");
         let t1 = Term::new("x", Triangular::new(0.0, 0.5, 1.0).unwrap());
         let t2 = Term::new("x", Triangular::new(0.0, 0.25, 0.5).unwrap());
 
@@ -151,30 +398,227 @@ mod tests {
         // Second insertion with the same name should error (reject duplicates).
         assert!(matches!(
             v.insert_term("x", t2),
-            Err(FuzzyError::TypeMismatch)
+            Err(FuzzyError::DuplicateTerm { ref var, ref term }) if var == "level" && term == "x"
         ));
     }
 
     /// Unknown term lookup during eval should return an error.
     #[test]
     fn test_eval_unknown_term_errors() {
-        let v = crate::variable::Variable::new(0.0, 1.0).unwrap();
-        // Unknown term name: return a consistent error variant.
-        assert!(matches!(
+        let v = crate::variable::Variable::new(0.0, 1.0)
+            .unwrap()
+            .with_name("level");
+        // Unknown term name: the error names the variable and the term.
+        assert_eq!(
             v.eval("missing", 0.3),
-            Err(FuzzyError::TypeMismatch)
-        ));
+            Err(FuzzyError::UnknownTerm {
+                var: "level".into(),
+                term: "missing".into()
+            })
+        );
+        assert_eq!(
+            v.eval("missing", 0.3).unwrap_err().to_string(),
+            "Unknown term. level has no term missing"
+        );
     }
 
-    /// Evaluating outside the variable domain should return OutOfBounds.
+    /// Evaluating outside the variable domain should return OutOfDomain.
     #[test]
     fn test_eval_out_of_domain_errors() {
-        let mut v = crate::variable::Variable::new(0.0, 1.0).unwrap();
+        let mut v = crate::variable::Variable::new(0.0, 1.0)
+            .unwrap()
+            .with_name("level");
         v.insert_term("x", Term::new("x", Triangular::new(0.0, 0.5, 1.0).unwrap()))
             .unwrap();
 
-        // Out-of-domain x should return OutOfBounds.
-        assert!(matches!(v.eval("x", -0.1), Err(FuzzyError::OutOfBounds)));
-        assert!(matches!(v.eval("x", 1.1), Err(FuzzyError::OutOfBounds)));
+        // Out-of-domain x should return OutOfDomain naming the input.
+        assert_eq!(
+            v.eval("x", -0.5),
+            Err(FuzzyError::OutOfDomain {
+                var: "level".into(),
+                value: -0.5
+            })
+        );
+        assert_eq!(
+            v.eval("x", 1.5).unwrap_err().to_string(),
+            "Out of domain. level = 1.5"
+        );
+        // NaN is in no domain; it must not slip past the comparisons.
+        assert!(matches!(
+            v.eval("x", crate::Float::NAN),
+            Err(FuzzyError::OutOfDomain { value, .. }) if value.is_nan()
+        ));
+        let mut out = [0.0; 2];
+        assert!(matches!(
+            v.eval_many("x", &[0.5, crate::Float::NAN], &mut out),
+            Err(FuzzyError::OutOfDomain { .. })
+        ));
+    }
+
+    /// Categorical and boolean variables match labels by their input codes.
+    #[test]
+    fn test_categorical_and_boolean() {
+        use crate::prelude::*;
+        use std::collections::HashMap;
+
+        let mode = Variable::categorical(&["off", "heat", "cool"]).unwrap();
+        let heat = mode.code("heat").unwrap();
+        assert_eq!(heat, 1.0);
+        assert_eq!(mode.eval("heat", heat).unwrap(), 1.0);
+        assert_eq!(mode.eval("off", heat).unwrap(), 0.0);
+        assert_eq!(mode.eval("cool", mode.code("cool").unwrap()).unwrap(), 1.0);
+        assert!(mode.code("dry").is_none());
+        assert!(matches!(
+            Variable::categorical(&["a", "a"]),
+            Err(FuzzyError::DuplicateTerm { .. })
+        ));
+        assert!(matches!(
+            Variable::categorical::<&str>(&[]),
+            Err(FuzzyError::EmptyInput)
+        ));
+
+        let mut fan = Variable::new(0.0, 10.0).unwrap();
+        fan.insert_term(
+            "high",
+            Term::new("high", Triangular::new(5.0, 10.0, 15.0).unwrap()),
+        )
+        .unwrap();
+        let vars = HashMap::from([
+            ("boost".to_string(), Variable::boolean()),
+            ("fan".to_string(), fan),
+        ]);
+        let rule = Rule::builder()
+            .when(atom("boost", "true"))
+            .then("fan", "high")
+            .build()
+            .unwrap();
+        let rs = RuleSpace::new(vars, vec![rule]).unwrap();
+        let sampler = UniformSampler::default();
+        let on = Float::from(u8::from(true));
+        assert!(
+            rs.evaluate(&HashMap::from([("boost", on)]), &sampler)
+                .unwrap()["fan"]
+                > 5.0
+        );
+        assert!(rs
+            .evaluate(&HashMap::from([("boost", 0.0)]), &sampler)
+            .unwrap()["fan"]
+            .is_nan());
+    }
+
+    /// Normalized degrees of overlapping terms sum to 1 at every covered input.
+    #[test]
+    fn normalized_degrees_form_a_partition() {
+        let mut v = crate::variable::Variable::new(0.0, 10.0).unwrap();
+        for (name, c) in [("low", 0.0), ("mid", 5.0), ("high", 10.0)] {
+            v.insert_term(
+                name,
+                Term::new(name, Triangular::new(c - 6.0, c, c + 6.0).unwrap()),
+            )
+            .unwrap();
+        }
+        // Wide triangles overlap: at 4 the raw degrees are 1/3, 5/6 and 0.
+        let raw: Float = ["low", "mid", "high"]
+            .iter()
+            .map(|t| v.eval(t, 4.0).unwrap())
+            .sum();
+        assert!((raw - 7.0 / 6.0).abs() < 1e-5);
+
+        v.set_normalized(true);
+        assert!(v.is_normalized());
+        for x in [0.0, 2.5, 4.0, 7.0, 10.0] {
+            let sum: Float = ["low", "mid", "high"]
+                .iter()
+                .map(|t| v.eval(t, x).unwrap())
+                .sum();
+            assert!((sum - 1.0).abs() < 1e-5, "{x}: {sum}");
+        }
+        assert!((v.eval("mid", 4.0).unwrap() - 5.0 / 7.0).abs() < 1e-5);
+        assert!(matches!(
+            v.eval("mid", 11.0),
+            Err(FuzzyError::OutOfDomain { .. })
+        ));
+
+        let mut gap = crate::variable::Variable::new(0.0, 10.0).unwrap();
+        gap.insert_term(
+            "low",
+            Term::new("low", Triangular::new(0.0, 1.0, 2.0).unwrap()),
+        )
+        .unwrap();
+        gap.set_normalized(true);
+        assert_eq!(gap.eval("low", 5.0).unwrap(), 0.0);
+    }
+
+    /// Batch evaluation matches `eval` point by point and checks the whole batch first.
+    #[test]
+    fn eval_many_matches_pointwise_eval() {
+        let mut v = crate::variable::Variable::new(0.0, 10.0).unwrap();
+        for (name, c) in [("low", 0.0), ("high", 10.0)] {
+            v.insert_term(
+                name,
+                Term::new(name, Triangular::new(c - 8.0, c, c + 8.0).unwrap()),
+            )
+            .unwrap();
+        }
+        let xs: Vec<Float> = (0..=20).map(|i| i as Float * 0.5).collect();
+        let mut out = vec![0.0; xs.len()];
+        for normalized in [false, true] {
+            v.set_normalized(normalized);
+            v.eval_many("low", &xs, &mut out).unwrap();
+            for (&x, &mu) in xs.iter().zip(&out) {
+                assert!((mu - v.eval("low", x).unwrap()).abs() < 1e-6, "{x}");
+            }
+        }
+
+        let mut before = out.clone();
+        assert!(matches!(
+            v.eval_many("low", &[1.0, 11.0], &mut before[..2]),
+            Err(FuzzyError::OutOfDomain { .. })
+        ));
+        assert_eq!(before, out);
+        assert!(matches!(
+            v.eval_many("low", &xs, &mut out[..3]),
+            Err(FuzzyError::BadArity)
+        ));
+        assert!(matches!(
+            v.eval_many("warm", &xs, &mut out),
+            Err(FuzzyError::UnknownTerm { .. })
+        ));
+    }
+
+    /// Derived terms follow the source term's shape pointwise.
+    #[test]
+    fn derived_terms_complement_and_concentrate() {
+        let mut v = Variable::new(0.0, 10.0).unwrap();
+        v.insert_term(
+            "hot",
+            Term::new("hot", Triangular::new(5.0, 10.0, 15.0).unwrap()),
+        )
+        .unwrap();
+        v.derive_not("hot").unwrap();
+        v.derive_very("hot").unwrap();
+        v.derive_hedged("hot", Hedge::Somewhat).unwrap();
+        let names: Vec<&str> = v.terms().map(|(n, _)| n).collect();
+        assert_eq!(names, ["hot", "not_hot", "very_hot", "somewhat_hot"]);
+        for x in [0.0, 6.0, 7.5, 9.0, 10.0] {
+            let mu = v.eval("hot", x).unwrap();
+            assert!((v.eval("not_hot", x).unwrap() - (1.0 - mu)).abs() < 1e-6);
+            assert!((v.eval("very_hot", x).unwrap() - mu * mu).abs() < 1e-6);
+            assert!((v.eval("somewhat_hot", x).unwrap() - mu.sqrt()).abs() < 1e-6);
+        }
+
+        // Derived terms are themselves custom shapes and cannot be derived from.
+        assert!(matches!(
+            v.derive_not("very_hot"),
+            Err(FuzzyError::TypeMismatch)
+        ));
+        assert!(matches!(
+            v.derive_not("cold"),
+            Err(FuzzyError::UnknownTerm { .. })
+        ));
+        assert!(matches!(
+            v.derive_very("hot"),
+            Err(FuzzyError::DuplicateTerm { .. })
+        ));
     }
 }
@@ -0,0 +1,209 @@
+// Batch evaluation that cooperates with async executors (feature `async`).
+//
+// Evaluating a large batch is pure CPU work; run inline in an async task it holds
+// the executor thread until the last row is done. `evaluate_chunks` splits a batch
+// into pieces a caller can interleave with other work, and
+// `evaluate_batch_async` does that itself, yielding to the executor between
+// chunks. Neither depends on a particular runtime. For batches that should not
+// share an executor thread at all, move an `Arc<System>` into the runtime's
+// `spawn_blocking` and call `evaluate_batch` there instead.
+use std::{
+    borrow::Borrow,
+    collections::HashMap,
+    future::Future,
+    hash::Hash,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use crate::{
+    error::{self, FuzzyError},
+    rulespace::{RuleSpace, Scratch},
+    sampler::UniformSampler,
+    system::System,
+    Float,
+};
+
+/// Iterator over the outputs of a batch, one `Vec` per chunk of input rows; see
+/// `RuleSpace::evaluate_chunks`.
+pub struct Chunks<'a, KI> {
+    space: &'a RuleSpace,
+    rows: std::slice::Chunks<'a, HashMap<KI, Float>>,
+    scratch: Result<Scratch, Option<FuzzyError>>,
+}
+
+impl<KI> Iterator for Chunks<'_, KI>
+where
+    KI: Eq + Hash + Borrow<str>,
+{
+    type Item = error::Result<Vec<HashMap<String, Float>>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let scratch = match &mut self.scratch {
+            Ok(scratch) => scratch,
+            // A sampler error is reported once, then the iterator ends.
+            Err(e) => return e.take().map(Err),
+        };
+        let rows = self.rows.next()?;
+        Some(
+            rows.iter()
+                .map(|row| self.space.evaluate_in(row, scratch).cloned())
+                .collect(),
+        )
+    }
+}
+
+impl RuleSpace {
+    /// Evaluates `inputs` lazily, `chunk` rows per item (at least one).
+    ///
+    /// The buffers are allocated once and reused across chunks. A failing row
+    /// fails its chunk; later chunks are still evaluated if polled.
+    pub fn evaluate_chunks<'a, KI>(
+        &'a self,
+        inputs: &'a [HashMap<KI, Float>],
+        sampler: &UniformSampler,
+        chunk: usize,
+    ) -> Chunks<'a, KI>
+    where
+        KI: Eq + Hash + Borrow<str>,
+    {
+        Chunks {
+            space: self,
+            rows: inputs.chunks(chunk.max(1)),
+            scratch: self.scratch(sampler).map_err(Some),
+        }
+    }
+
+    /// Like `evaluate_batch`, but yields to the executor after every `chunk`
+    /// rows so a long batch does not starve other tasks.
+    ///
+    /// Rows are evaluated sequentially on the polling thread. The first failing
+    /// row aborts the batch.
+    pub async fn evaluate_batch_async<KI>(
+        &self,
+        inputs: &[HashMap<KI, Float>],
+        sampler: &UniformSampler,
+        chunk: usize,
+    ) -> error::Result<Vec<HashMap<String, Float>>>
+    where
+        KI: Eq + Hash + Borrow<str>,
+    {
+        let mut outputs = Vec::with_capacity(inputs.len());
+        let mut chunks = self.evaluate_chunks(inputs, sampler, chunk).peekable();
+        while let Some(rows) = chunks.next() {
+            outputs.extend(rows?);
+            if chunks.peek().is_some() {
+                YieldNow(false).await;
+            }
+        }
+        Ok(outputs)
+    }
+}
+
+impl System {
+    /// Evaluates every input row, yielding after every `chunk` rows; see
+    /// `RuleSpace::evaluate_batch_async`.
+    pub async fn evaluate_batch_async<KI>(
+        &self,
+        inputs: &[HashMap<KI, Float>],
+        chunk: usize,
+    ) -> error::Result<Vec<HashMap<String, Float>>>
+    where
+        KI: Eq + Hash + Borrow<str>,
+    {
+        self.space()
+            .evaluate_batch_async(inputs, self.sampler(), chunk)
+            .await
+    }
+}
+
+/// Returns `Pending` once, waking itself, so the executor can run other tasks.
+struct YieldNow(bool);
+
+impl Future for YieldNow {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        if self.0 {
+            return Poll::Ready(());
+        }
+        self.0 = true;
+        cx.waker().wake_by_ref();
+        Poll::Pending
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        collections::HashMap,
+        future::Future,
+        pin::pin,
+        sync::Arc,
+        task::{Context, Poll, Wake, Waker},
+    };
+
+    use crate::error::FuzzyError;
+    use crate::prelude::*;
+    use crate::test_support::space;
+
+    struct Noop;
+
+    impl Wake for Noop {
+        fn wake(self: Arc<Self>) {}
+    }
+
+    /// Polls `f` to completion, counting how often it yielded.
+    fn block_on<F: Future>(f: F) -> (F::Output, usize) {
+        let waker = Waker::from(Arc::new(Noop));
+        let mut cx = Context::from_waker(&waker);
+        let mut f = pin!(f);
+        let mut yields = 0;
+        loop {
+            match f.as_mut().poll(&mut cx) {
+                Poll::Ready(out) => return (out, yields),
+                Poll::Pending => yields += 1,
+            }
+        }
+    }
+
+    #[test]
+    fn async_batch_yields_between_chunks() {
+        let rs = space();
+        let sampler = UniformSampler::default();
+        let rows: Vec<HashMap<&str, Float>> = (0..25)
+            .map(|i| HashMap::from([("temp", i as Float * 0.4)]))
+            .collect();
+        let expected = rs.evaluate_batch(&rows, &sampler).unwrap();
+
+        let (out, yields) = block_on(rs.evaluate_batch_async(&rows, &sampler, 10));
+        assert_eq!(out.unwrap(), expected);
+        assert_eq!(yields, 2);
+        let (out, yields) = block_on(rs.evaluate_batch_async(&rows, &sampler, 0));
+        assert_eq!(out.unwrap().len(), 25);
+        assert_eq!(yields, 24);
+
+        let sizes: Vec<usize> = rs
+            .evaluate_chunks(&rows, &sampler, 10)
+            .map(|c| c.unwrap().len())
+            .collect();
+        assert_eq!(sizes, [10, 10, 5]);
+
+        let mut bad = rows.clone();
+        bad[12].clear();
+        let (out, _) = block_on(rs.evaluate_batch_async(&bad, &sampler, 10));
+        assert!(matches!(
+            out.map_err(|e| e.root().clone()),
+            Err(FuzzyError::NotFound { .. })
+        ));
+        let chunks: Vec<bool> = rs
+            .evaluate_chunks(&bad, &sampler, 10)
+            .map(|c| c.is_ok())
+            .collect();
+        assert_eq!(chunks, [true, false, true]);
+
+        let system = System::new(rs, UniformSampler::default()).unwrap();
+        let (out, _) = block_on(system.evaluate_batch_async(&rows, 7));
+        assert_eq!(out.unwrap(), expected);
+    }
+}
@@ -2,7 +2,7 @@
 mod tests {
 
     use rust_fuzzylogic::{
-        aggregate::aggregation,
+        aggregate::{aggregation, aggregation_for},
         antecedent::Antecedent,
         defuzz::defuzzification,
         mamdani::{Consequent, Rule},
@@ -79,33 +79,39 @@ mod tests {
             }))),
         );
 
-        let csqt_1 = Consequent {
-            var: "fanpspeed".to_string(),
-            term: "High".to_string(),
+        let csqt_1 = Consequent::Term {
+            var: "fanpspeed".into(),
+            term: "High".into(),
+            hedge: None,
         };
 
-        let csqt_2 = Consequent {
-            var: "pumpspeed".to_string(),
-            term: "High".to_string(),
+        let csqt_2 = Consequent::Term {
+            var: "pumpspeed".into(),
+            term: "High".into(),
+            hedge: None,
         };
 
-        let csqt_3 = Consequent {
-            var: "fanpspeed".to_string(),
-            term: "Low".to_string(),
+        let csqt_3 = Consequent::Term {
+            var: "fanpspeed".into(),
+            term: "Low".into(),
+            hedge: None,
         };
 
-        let csqt_4 = Consequent {
-            var: "pumpspeed".to_string(),
-            term: "Low".to_string(),
+        let csqt_4 = Consequent::Term {
+            var: "pumpspeed".into(),
+            term: "Low".into(),
+            hedge: None,
         };
 
         let rule = Rule {
             antecedent: ast,
             consequent: vec![csqt_1, csqt_2],
+            weight: 1.0,
         };
         let rule_2 = Rule {
             antecedent: ast_2,
             consequent: vec![csqt_3, csqt_4],
+            weight: 1.0,
         };
 
         let mut inputs: HashMap<&str, Float> = HashMap::new();
@@ -122,5 +128,212 @@ mod tests {
         let centroid = defuzzification(&aggregate, &vars);
 
         println!("{:?}", centroid);
+
+        let only = aggregation_for(&rules, &inputs, &vars, &sampler, &["fanpspeed"]).unwrap();
+        assert_eq!(only.len(), 1);
+        assert_eq!(only["fanpspeed"], aggregate["fanpspeed"]);
+
+        let subset = aggregation(
+            rules
+                .iter()
+                .filter(|r| r.consequent[0].term() == Some("Low")),
+            &inputs,
+            &vars,
+            &sampler,
+        )
+        .unwrap();
+        let second = aggregation(&rules[1..], &inputs, &vars, &sampler).unwrap();
+        assert_eq!(subset, second);
+    }
+
+    #[test]
+    fn builder_matches_struct_literal() {
+        let rule = Rule::builder()
+            .when(atom("temp", "hot").and(not(atom("temp", "cold"))))
+            .then("fanspeed", "High")
+            .then("pumpspeed", "High")
+            .build()
+            .unwrap();
+        let expected = Antecedent::And(
+            Box::new(Antecedent::Atom {
+                var: "temp".into(),
+                term: "hot".into(),
+            }),
+            Box::new(Antecedent::Not(Box::new(Antecedent::Atom {
+                var: "temp".into(),
+                term: "cold".into(),
+            }))),
+        );
+        assert_eq!(rule.antecedent, expected);
+        let then: Vec<(&str, &str)> = rule
+            .consequent
+            .iter()
+            .map(|c| (c.var(), c.term().unwrap()))
+            .collect();
+        assert_eq!(then, [("fanspeed", "High"), ("pumpspeed", "High")]);
+
+        let chained = Rule::builder()
+            .when(atom("temp", "hot"))
+            .when(not(atom("temp", "cold")))
+            .then("fanspeed", "High")
+            .build()
+            .unwrap();
+        assert_eq!(chained.antecedent, expected);
+
+        let hedged = Rule::builder()
+            .when(atom("temp", "hot"))
+            .then_hedged("fanspeed", Hedge::Very, "High")
+            .build()
+            .unwrap();
+        assert_eq!(
+            hedged.consequent,
+            [Consequent::new("fanspeed", "High").with_hedge(Hedge::Very)]
+        );
+
+        assert!(matches!(
+            Rule::builder().then("fanspeed", "High").build(),
+            Err(FuzzyError::EmptyInput)
+        ));
+        assert!(matches!(
+            Rule::builder().when(atom("temp", "hot")).build(),
+            Err(FuzzyError::EmptyInput)
+        ));
+    }
+
+    const FAN: &str = "
+        var temp 0 10
+          cold: triangle(-10, 0, 10)
+          hot: triangle(0, 10, 20)
+        var fan 0 100
+          low: triangle(-100, 0, 100)
+          high: triangle(0, 100, 200)
+        IF temp IS cold THEN fan IS low
+    ";
+
+    fn fan(text: &str, temp: Float) -> Float {
+        let rs: RuleSpace = text.parse().unwrap();
+        let input = HashMap::from([("temp", temp)]);
+        rs.evaluate(&input, &UniformSampler::default()).unwrap()["fan"]
+    }
+
+    /// The variables of `FAN`.
+    fn fan_vars() -> HashMap<String, Variable> {
+        let mut temp = Variable::new(0.0, 10.0).unwrap();
+        for (name, mf) in [("cold", (-10.0, 0.0, 10.0)), ("hot", (0.0, 10.0, 20.0))] {
+            let mf = Triangular::new(mf.0, mf.1, mf.2).unwrap();
+            temp.insert_term(name, Term::new(name, mf)).unwrap();
+        }
+        let mut fan = Variable::new(0.0, 100.0).unwrap();
+        for (name, mf) in [("low", (-100.0, 0.0, 100.0)), ("high", (0.0, 100.0, 200.0))] {
+            let mf = Triangular::new(mf.0, mf.1, mf.2).unwrap();
+            fan.insert_term(name, Term::new(name, mf)).unwrap();
+        }
+        HashMap::from([("temp".to_string(), temp), ("fan".to_string(), fan)])
+    }
+
+    #[test]
+    fn weights_scale_firing_strength() {
+        let weighted = |w: &str| format!("{FAN}\nIF temp IS hot THEN fan IS high {w}");
+        assert_eq!(fan(&weighted("WITH 0"), 3.0), fan(FAN, 3.0));
+        assert_eq!(fan(&weighted("WITH 1"), 3.0), fan(&weighted(""), 3.0));
+        let half = fan(&weighted("WITH 0.5"), 3.0);
+        assert!(fan(FAN, 3.0) < half && half < fan(&weighted(""), 3.0));
+
+        let rs: RuleSpace = weighted("WITH 0.5").parse().unwrap();
+        let vars = fan_vars();
+        let input = HashMap::from([("temp", 3.0)]);
+        let alpha = rs.rules()[1].activation(&input, &vars).unwrap();
+        assert!((alpha - 0.15).abs() < 1e-6);
+
+        // The columnar and strict engines apply the weight too.
+        let sampler = UniformSampler::default();
+        let want = rs.evaluate(&input, &sampler).unwrap()["fan"];
+        assert_eq!(rs.evaluate_strict(&input, &sampler).unwrap()["fan"], want);
+        let temps = [3.0];
+        let mut col = [0.0];
+        rs.evaluate_columns(
+            &HashMap::from([("temp", &temps[..])]),
+            &sampler,
+            &mut [&mut col[..]],
+        )
+        .unwrap();
+        assert_eq!(col[0], want);
+    }
+
+    #[test]
+    fn weights_outside_the_unit_interval_are_rejected() {
+        let rule = |w| {
+            Rule::builder()
+                .when(atom("temp", "hot"))
+                .then("fan", "high")
+                .weight(w)
+                .build()
+        };
+        assert_eq!(rule(0.8).unwrap().weight, 0.8);
+        for w in [-0.1, 1.5, Float::NAN] {
+            assert!(matches!(
+                rule(w),
+                Err(FuzzyError::InvalidParameter {
+                    field: "weight",
+                    ..
+                })
+            ));
+        }
+
+        let mut bad = rule(1.0).unwrap();
+        bad.weight = 2.0;
+        let err = RuleSpace::new(fan_vars(), vec![bad]).err().unwrap();
+        assert!(matches!(
+            err.root(),
+            FuzzyError::InvalidParameter {
+                field: "weight",
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn constant_degrees_outside_the_unit_interval_are_rejected() {
+        let rule = |c| {
+            Rule::builder()
+                .when(Antecedent::Const(c).and(atom("temp", "hot")))
+                .then("fan", "high")
+                .build()
+                .unwrap()
+        };
+        assert!(RuleSpace::new(fan_vars(), vec![rule(0.4)]).is_ok());
+        for c in [-0.1, 2.0, Float::NAN, Float::INFINITY] {
+            let err = RuleSpace::new(fan_vars(), vec![rule(c)]).err().unwrap();
+            assert!(
+                matches!(
+                    err.root(),
+                    FuzzyError::InvalidParameter {
+                        mf: "Antecedent",
+                        field: "const",
+                        ..
+                    }
+                ),
+                "{c}"
+            );
+        }
+    }
+
+    #[test]
+    fn implicating_an_unknown_consequent_term_names_it() {
+        let rule = Rule::builder()
+            .when(atom("temp", "hot"))
+            .then("fan", "turbo")
+            .build()
+            .unwrap();
+        let err = rule
+            .implicate(1.0, &fan_vars(), &UniformSampler::default())
+            .unwrap_err();
+        assert_eq!(
+            err,
+            FuzzyError::UnknownTerm {
+                var: "fan".into(),
+                term: "turbo".into()
+            }
+        );
     }
 }
@@ -0,0 +1,165 @@
+#[cfg(test)]
+mod tests {
+    use rust_fuzzylogic::{builder::SystemBuilder, fam::fam_rules, prelude::*};
+    use std::collections::HashMap;
+    use std::sync::Arc;
+    use std::thread;
+
+    fn levels(builder: SystemBuilder, var: &str, max: Float) -> SystemBuilder {
+        let half = max / 2.0;
+        builder
+            .variable(var, 0.0, max)
+            .term(var, "low", Triangular::new(-half, 0.0, half).unwrap())
+            .term(var, "mid", Triangular::new(0.0, half, max).unwrap())
+            .term(var, "high", Triangular::new(half, max, max + half).unwrap())
+    }
+
+    #[test]
+    fn builder_assembles_an_evaluable_system() {
+        let terms = ["low", "mid", "high"];
+        let matrix: [&[&str]; 3] = [
+            &["low", "low", "mid"],
+            &["low", "mid", "high"],
+            &["mid", "high", "high"],
+        ];
+        let rules = fam_rules(("temp", &terms), ("load", &terms), "fan", &matrix).unwrap();
+        let builder = levels(System::builder(), "temp", 40.0);
+        let builder = levels(builder, "load", 1.0);
+        let mut system = levels(builder, "fan", 100.0)
+            .rules(rules)
+            .sampler(UniformSampler::new(201).unwrap())
+            .build()
+            .unwrap();
+        assert_eq!(system.space().rules().len(), 9);
+        assert_eq!(system.sampler().n, 201);
+
+        let cool = HashMap::from([("temp", 5.0), ("load", 0.1)]);
+        let hot = HashMap::from([("temp", 38.0), ("load", 0.9)]);
+        let low = system.evaluate(&cool).unwrap()["fan"];
+        let high = system.evaluate(&hot).unwrap()["fan"];
+        assert!(low < 40.0 && high > 60.0, "{low} {high}");
+
+        // The facade agrees with evaluating the rule space directly.
+        let batch = system.evaluate_batch(&[cool.clone(), hot]).unwrap();
+        assert!((batch[0]["fan"] - low).abs() < 1e-9);
+        let space = system.into_inner();
+        let direct = space
+            .evaluate(&cool, &UniformSampler::new(201).unwrap())
+            .unwrap();
+        assert!((direct["fan"] - low).abs() < 1e-9);
+    }
+
+    #[test]
+    fn builder_reports_the_first_error() {
+        let rule = || {
+            Rule::builder()
+                .when(atom("temp", "low"))
+                .then("fan", "high")
+                .build()
+                .unwrap()
+        };
+        let base = || levels(levels(System::builder(), "temp", 40.0), "fan", 100.0);
+        assert!(base().rule(rule()).build().is_ok());
+
+        assert!(matches!(
+            base().variable("temp", 0.0, 1.0).rule(rule()).build(),
+            Err(FuzzyError::TypeMismatch)
+        ));
+        assert!(matches!(
+            base()
+                .term("pump", "low", Triangular::new(0.0, 1.0, 2.0).unwrap())
+                .variable("bad", 1.0, 0.0)
+                .build(),
+            Err(FuzzyError::NotFound { .. })
+        ));
+        assert!(matches!(
+            base().variable("bad", 1.0, 0.0).build(),
+            Err(FuzzyError::InvalidParameter { mf: "Variable", .. })
+        ));
+        assert!(matches!(base().build(), Err(FuzzyError::EmptyInput)));
+        let typo = Rule::builder()
+            .when(atom("temp", "lwo"))
+            .then("fan", "high")
+            .build()
+            .unwrap();
+        assert!(matches!(
+            base().rule(typo).build(),
+            Err(FuzzyError::InRule { rule: 0, .. })
+        ));
+    }
+
+    #[test]
+    fn normalized_inputs_flow_through_the_builder() {
+        let rule = |t: &'static str, f: &'static str| {
+            Rule::builder()
+                .when(atom("temp", t))
+                .then("fan", f)
+                .build()
+                .unwrap()
+        };
+        let system = levels(levels(System::builder(), "temp", 40.0), "fan", 100.0)
+            .normalized("temp")
+            .rule(rule("low", "low"))
+            .rule(rule("high", "high"))
+            .build()
+            .unwrap();
+        assert!(system.space().var("temp").unwrap().is_normalized());
+        assert!(matches!(
+            System::builder().normalized("temp").build(),
+            Err(FuzzyError::NotFound { .. })
+        ));
+    }
+
+    #[test]
+    fn shared_system_evaluates_concurrently() {
+        let rule = |t: &'static str, f: &'static str| {
+            Rule::builder()
+                .when(atom("temp", t))
+                .then("fan", f)
+                .build()
+                .unwrap()
+        };
+        let system = levels(levels(System::builder(), "temp", 40.0), "fan", 100.0)
+            .rules([rule("low", "low"), rule("mid", "mid"), rule("high", "high")])
+            .build()
+            .unwrap();
+        let mut space = system.into_inner();
+        space.enable_counters(0.0);
+        let mut system = System::new(space, UniformSampler::default()).unwrap();
+
+        let temps: Vec<Float> = (0..=40).map(|t| t as Float).collect();
+        let expected: Vec<Float> = temps
+            .iter()
+            .map(|&t| system.evaluate(&HashMap::from([("temp", t)])).unwrap()["fan"])
+            .collect();
+
+        let system = Arc::new(system);
+        let threads: Vec<_> = (0..8)
+            .map(|k| {
+                let system = Arc::clone(&system);
+                let temps = temps.clone();
+                thread::spawn(move || {
+                    // Each thread walks the inputs from a different offset.
+                    (0..temps.len())
+                        .map(|i| {
+                            let i = (i + k * 5) % temps.len();
+                            let out = system
+                                .evaluate_shared(&HashMap::from([("temp", temps[i])]))
+                                .unwrap();
+                            (i, out["fan"])
+                        })
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect();
+        for handle in threads {
+            for (i, y) in handle.join().unwrap() {
+                assert_eq!(y, expected[i], "temp {}", temps[i]);
+            }
+        }
+
+        // The atomic counters saw every evaluation from every thread.
+        let metrics = system.space().metrics().unwrap();
+        assert_eq!(metrics.evaluations, (temps.len() * 9) as u64);
+    }
+}
@@ -0,0 +1,143 @@
+// Verifies that `RuleSpace::evaluate_in` and `evaluate_slice_in` stop allocating
+// once their scratch is warm.
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+
+struct CountingAlloc;
+
+thread_local! {
+    // Per-thread so allocations by the test harness on other threads are not counted.
+    static ALLOCS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCS.with(|c| c.set(c.get() + 1));
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCS.with(|c| c.set(c.get() + 1));
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAlloc = CountingAlloc;
+
+#[cfg(test)]
+mod tests {
+    use super::ALLOCS;
+    use rust_fuzzylogic::{
+        antecedent::Antecedent,
+        mamdani::{Consequent, Rule},
+        prelude::*,
+        rulespace::RuleSpace,
+        variable::Variable,
+    };
+    use std::cell::Cell;
+    use std::collections::HashMap;
+
+    fn space() -> RuleSpace {
+        let mut temp = Variable::new(-10.0, 10.0).unwrap();
+        temp.insert_term(
+            "cold",
+            Term::new("cold", Triangular::new(-10.0, -5.0, 0.0).unwrap()),
+        )
+        .unwrap();
+        temp.insert_term(
+            "hot",
+            Term::new("hot", Triangular::new(0.0, 5.0, 10.0).unwrap()),
+        )
+        .unwrap();
+        let mut fan = Variable::new(0.0, 10.0).unwrap();
+        fan.insert_term(
+            "Low",
+            Term::new("Low", Triangular::new(0.0, 2.5, 5.0).unwrap()),
+        )
+        .unwrap();
+        fan.insert_term(
+            "High",
+            Term::new("High", Triangular::new(5.0, 7.5, 10.0).unwrap()),
+        )
+        .unwrap();
+
+        let rule = |t: &'static str, f: &'static str| Rule {
+            antecedent: Antecedent::Or(
+                Box::new(Antecedent::Atom {
+                    var: "temp".into(),
+                    term: t.into(),
+                }),
+                Box::new(Antecedent::Not(Box::new(Antecedent::Atom {
+                    var: "temp".into(),
+                    term: if t == "hot" { "cold" } else { "hot" }.into(),
+                }))),
+            ),
+            consequent: vec![Consequent::Term {
+                var: "fan".into(),
+                term: f.into(),
+                hedge: None,
+            }],
+            weight: 1.0,
+        };
+        let mut vars = HashMap::new();
+        vars.insert("temp".to_string(), temp);
+        vars.insert("fan".to_string(), fan);
+        RuleSpace::new(vars, vec![rule("cold", "Low"), rule("hot", "High")]).unwrap()
+    }
+
+    #[test]
+    fn evaluate_in_is_allocation_free_after_warm_up() {
+        let space = space();
+        let sampler = UniformSampler::default();
+        let mut scratch = space.scratch(&sampler).unwrap();
+        let mut input: HashMap<&str, Float> = HashMap::from([("temp", 0.0)]);
+        space.evaluate_in(&input, &mut scratch).unwrap();
+
+        ALLOCS.with(|c| c.set(0));
+        let mut last = 0.0;
+        for i in 0..1000 {
+            *input.get_mut("temp").unwrap() = -9.0 + (i % 19) as Float;
+            last = space.evaluate_in(&input, &mut scratch).unwrap()["fan"];
+        }
+        assert_eq!(ALLOCS.with(Cell::get), 0);
+
+        // Results must match the allocating path.
+        let expected = space.evaluate(&input, &sampler).unwrap();
+        assert!((last - expected["fan"]).abs() < 1e-9);
+    }
+
+    #[test]
+    fn evaluate_slice_in_is_allocation_free_after_warm_up() {
+        let space = space();
+        let sampler = UniformSampler::default();
+        let mut scratch = space.scratch(&sampler).unwrap();
+        let temp = space.var_id("temp").unwrap().index();
+        let mut input = vec![0.0; space.vars().count()];
+        let mut out = [0.0];
+        space
+            .evaluate_slice_in(&input, &mut scratch, &mut out)
+            .unwrap();
+
+        ALLOCS.with(|c| c.set(0));
+        for i in 0..1000 {
+            input[temp] = -9.0 + (i % 19) as Float;
+            space
+                .evaluate_slice_in(&input, &mut scratch, &mut out)
+                .unwrap();
+        }
+        assert_eq!(ALLOCS.with(Cell::get), 0);
+
+        let expected = space
+            .evaluate(&HashMap::from([("temp", input[temp])]), &sampler)
+            .unwrap();
+        assert!((out[0] - expected["fan"]).abs() < 1e-9);
+        assert!(space
+            .evaluate_slice_in(&input[..1], &mut scratch, &mut out)
+            .is_err());
+    }
+}